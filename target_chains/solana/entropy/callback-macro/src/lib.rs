@@ -0,0 +1,171 @@
+//! `#[entropy_callback]`: turns a typed randomness handler into a callback
+//! entrypoint that entropy can invoke on reveal.
+//!
+//! ```ignore
+//! #[entropy_callback(entropy_program_id = ENTROPY_PROGRAM_ID)]
+//! fn on_random(
+//!     program_id: &Pubkey,
+//!     accounts: &[AccountInfo],
+//!     prefix: &[u8],
+//!     sequence_number: u64,
+//!     provider: Pubkey,
+//!     random_number: [u8; 32],
+//! ) -> ProgramResult {
+//!     // ...
+//! }
+//! ```
+//!
+//! The generated `on_random(program_id, accounts, data)` checks that the first
+//! account is entropy's signer PDA for the configured program, splits the
+//! `(sequence_number, provider, random_number)` tail off `data`, and calls the
+//! handler with the remaining accounts and the caller-defined prefix.
+//!
+//! For requests made with `include_contributions`, add the flag to the
+//! attribute, `#[entropy_callback(entropy_program_id = ..., include_contributions)]`,
+//! and a trailing `contributions: Contributions` parameter to the handler.
+
+use {
+    proc_macro::TokenStream,
+    proc_macro2::Span,
+    quote::{format_ident, quote},
+    syn::{
+        parse::{Parse, ParseStream},
+        parse_macro_input, Error, Expr, ExprLit, Ident, ItemFn, Lit, Token,
+    },
+};
+
+/// Number of parameters the handler must declare, without and with
+/// `include_contributions`.
+const HANDLER_ARITY: usize = 6;
+const HANDLER_ARITY_WITH_CONTRIBUTIONS: usize = 7;
+
+struct CallbackArgs {
+    entropy_program_id: Expr,
+    include_contributions: bool,
+}
+
+impl Parse for CallbackArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+        if key != "entropy_program_id" {
+            return Err(Error::new(
+                key.span(),
+                "expected `entropy_program_id = <Pubkey expression or base58 string>`",
+            ));
+        }
+        input.parse::<Token![=]>()?;
+        let value: Expr = input.parse()?;
+        let include_contributions = if input.is_empty() {
+            false
+        } else {
+            input.parse::<Token![,]>()?;
+            let flag: Ident = input.parse()?;
+            if flag != "include_contributions" {
+                return Err(Error::new(flag.span(), "expected `include_contributions`"));
+            }
+            true
+        };
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after the callback arguments"));
+        }
+
+        // Accept a base58 literal as shorthand for `pubkey!("...")`.
+        let entropy_program_id = match value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(literal),
+                ..
+            }) => syn::parse_quote!(::entropy_cpi::__private::solana_program::pubkey!(#literal)),
+            other => other,
+        };
+        Ok(Self {
+            entropy_program_id,
+            include_contributions,
+        })
+    }
+}
+
+#[proc_macro_attribute]
+pub fn entropy_callback(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as CallbackArgs);
+    let handler = parse_macro_input!(item as ItemFn);
+    expand(args, handler)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(args: CallbackArgs, mut handler: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let (arity, signature) = if args.include_contributions {
+        (
+            HANDLER_ARITY_WITH_CONTRIBUTIONS,
+            "entropy callback handlers with `include_contributions` take (program_id: &Pubkey, \
+             accounts: &[AccountInfo], prefix: &[u8], sequence_number: u64, provider: Pubkey, \
+             random_number: [u8; 32], contributions: Contributions)",
+        )
+    } else {
+        (
+            HANDLER_ARITY,
+            "entropy callback handlers take (program_id: &Pubkey, accounts: &[AccountInfo], \
+             prefix: &[u8], sequence_number: u64, provider: Pubkey, random_number: [u8; 32])",
+        )
+    };
+    if handler.sig.inputs.len() != arity {
+        return Err(Error::new_spanned(&handler.sig.inputs, signature));
+    }
+    if handler.sig.asyncness.is_some() {
+        return Err(Error::new_spanned(
+            handler.sig.asyncness,
+            "entropy callback handlers cannot be async",
+        ));
+    }
+
+    let attrs = std::mem::take(&mut handler.attrs);
+    let vis = std::mem::replace(&mut handler.vis, syn::Visibility::Inherited);
+    let name = handler.sig.ident.clone();
+    let inner = format_ident!("__entropy_callback_{}", name, span = Span::call_site());
+    handler.sig.ident = inner.clone();
+    let entropy_program_id = args.entropy_program_id;
+    let call = if args.include_contributions {
+        quote! {
+            let (prefix, callback, contributions) =
+                ::entropy_cpi::parse_callback_with_contributions(data)?;
+            #inner(
+                program_id,
+                accounts,
+                prefix,
+                callback.sequence_number,
+                callback.provider,
+                callback.random_number,
+                contributions,
+            )
+        }
+    } else {
+        quote! {
+            let (prefix, callback) = ::entropy_cpi::parse_callback(data)?;
+            #inner(
+                program_id,
+                accounts,
+                prefix,
+                callback.sequence_number,
+                callback.provider,
+                callback.random_number,
+            )
+        }
+    };
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis fn #name(
+            program_id: &::entropy_cpi::__private::solana_program::pubkey::Pubkey,
+            accounts: &[::entropy_cpi::__private::solana_program::account_info::AccountInfo],
+            data: &[u8],
+        ) -> ::entropy_cpi::__private::solana_program::entrypoint::ProgramResult {
+            #handler
+
+            let (entropy_signer, accounts) = accounts.split_first().ok_or(
+                ::entropy_cpi::__private::solana_program::program_error::ProgramError::NotEnoughAccountKeys,
+            )?;
+            ::entropy_cpi::verify_entropy_signer(entropy_signer, &(#entropy_program_id))?;
+            #call
+        }
+    })
+}
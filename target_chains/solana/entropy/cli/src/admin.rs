@@ -0,0 +1,238 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::shutdown::KeeperStats;
+
+/// Bounds how long a slow client can hold the single admin thread.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A queued reveal, as reported by `GET /pending-requests`.
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct PendingSummary {
+    pub(crate) provider: String,
+    pub(crate) request_account: String,
+    pub(crate) sequence_number: u64,
+    pub(crate) requester_program: String,
+    pub(crate) provider_fee: u64,
+    pub(crate) deferred: bool,
+}
+
+/// Provider state published once per polling round.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct KeeperSnapshot {
+    pub(crate) provider_account: String,
+    /// First sequence number of the current hash chain, and the one at
+    /// which it runs out.
+    pub(crate) chain_start_sequence: u64,
+    pub(crate) chain_end_sequence: u64,
+    /// Earlier chains still kept to reveal requests made against them.
+    pub(crate) retired_chains: usize,
+    pub(crate) pending: Vec<PendingSummary>,
+}
+
+/// One provider served by the keeper, as seen by the admin API.
+pub(crate) struct ProviderAdmin {
+    stats: Arc<KeeperStats>,
+    rotate_requested: AtomicBool,
+    snapshot: Mutex<KeeperSnapshot>,
+}
+
+impl ProviderAdmin {
+    /// Returns whether a chain rotation was requested, clearing the request.
+    pub(crate) fn take_rotate_request(&self) -> bool {
+        self.rotate_requested.swap(false, Ordering::SeqCst)
+    }
+
+    pub(crate) fn publish(&self, snapshot: KeeperSnapshot) {
+        if let Ok(mut current) = self.snapshot.lock() {
+            *current = snapshot;
+        }
+    }
+
+    fn snapshot(&self) -> KeeperSnapshot {
+        self.snapshot
+            .lock()
+            .map(|snapshot| snapshot.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// State shared between the keeper loop and the admin API. Commands only set
+/// flags; the keeper loop acts on them between reveals. Pausing applies to
+/// every provider, rotation to each one separately.
+pub(crate) struct AdminState {
+    paused: AtomicBool,
+    /// In the keeper's provider order.
+    providers: Vec<ProviderAdmin>,
+}
+
+impl AdminState {
+    pub(crate) fn new(stats: impl IntoIterator<Item = Arc<KeeperStats>>) -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            providers: stats
+                .into_iter()
+                .map(|stats| ProviderAdmin {
+                    stats,
+                    rotate_requested: AtomicBool::new(false),
+                    snapshot: Mutex::default(),
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn provider(&self, index: usize) -> &ProviderAdmin {
+        &self.providers[index]
+    }
+}
+
+/// Serves the admin API on `addr` from a background thread:
+///
+/// - `GET /status`
+/// - `GET /pending-requests`
+/// - `POST /pause`, `POST /resume`
+/// - `POST /rotate-chain`, or `POST /rotate-chain?provider=<AUTHORITY>` to
+///   rotate a single provider
+pub(crate) fn serve(addr: SocketAddr, state: Arc<AdminState>) -> Result<()> {
+    let listener =
+        TcpListener::bind(addr).with_context(|| format!("Failed to bind admin API to {addr}"))?;
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream
+                .map_err(anyhow::Error::from)
+                .and_then(|stream| handle_connection(stream, &state));
+            if let Err(err) = result {
+                warn!(error = %err, "Admin API request failed");
+            }
+        }
+    });
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, state: &AdminState) -> Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Headers and any body are ignored; no endpoint takes input.
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let (status, body) = route(method, path, state);
+
+    let body = body.to_string();
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn route(method: &str, target: &str, state: &AdminState) -> (&'static str, Value) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    match (method, path) {
+        ("GET", "/status") => ("200 OK", status(state)),
+        ("GET", "/pending-requests") => {
+            let pending: Vec<_> = state
+                .providers
+                .iter()
+                .flat_map(|provider| provider.snapshot().pending)
+                .collect();
+            ("200 OK", serde_json::to_value(pending).unwrap_or_default())
+        }
+        ("POST", "/pause") => {
+            state.paused.store(true, Ordering::SeqCst);
+            ("200 OK", json!({ "paused": true }))
+        }
+        ("POST", "/resume") => {
+            state.paused.store(false, Ordering::SeqCst);
+            ("200 OK", json!({ "paused": false }))
+        }
+        ("POST", "/rotate-chain") => {
+            let only = query
+                .split('&')
+                .find_map(|param| param.strip_prefix("provider="));
+            let mut rotating = Vec::new();
+            for provider in &state.providers {
+                let authority = provider.stats.provider.to_string();
+                if only.is_none_or(|only| only == authority) {
+                    provider.rotate_requested.store(true, Ordering::SeqCst);
+                    rotating.push(authority);
+                }
+            }
+            if rotating.is_empty() {
+                return ("404 Not Found", json!({ "error": "unknown provider" }));
+            }
+            ("202 Accepted", json!({ "rotation_pending": rotating }))
+        }
+        (_, "/status" | "/pending-requests" | "/pause" | "/resume" | "/rotate-chain") => (
+            "405 Method Not Allowed",
+            json!({ "error": "method not allowed" }),
+        ),
+        _ => ("404 Not Found", json!({ "error": "not found" })),
+    }
+}
+
+fn status(state: &AdminState) -> Value {
+    let load = |counter: &std::sync::atomic::AtomicU64| counter.load(Ordering::Relaxed);
+    let providers: Vec<_> = state
+        .providers
+        .iter()
+        .map(|provider| {
+            let snapshot = provider.snapshot();
+            let stats = &provider.stats;
+            json!({
+                "authority": stats.provider.to_string(),
+                "provider_account": snapshot.provider_account,
+                "rotation_pending": provider.rotate_requested.load(Ordering::SeqCst),
+                "chain": {
+                    "start_sequence": snapshot.chain_start_sequence,
+                    "end_sequence": snapshot.chain_end_sequence,
+                    "retired_chains": snapshot.retired_chains,
+                },
+                "pending_requests": snapshot.pending.len(),
+                "stats": {
+                    "observed": load(&stats.observed),
+                    "revealed": load(&stats.revealed),
+                    "failed": load(&stats.failed),
+                    "skipped_unprofitable": load(&stats.skipped_unprofitable),
+                    "deferred_rate_limited": load(&stats.deferred_rate_limited),
+                    "swept_lamports": load(&stats.swept_lamports),
+                },
+            })
+        })
+        .collect();
+    json!({
+        "paused": state.is_paused(),
+        "providers": providers,
+    })
+}
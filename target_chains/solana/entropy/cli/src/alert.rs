@@ -0,0 +1,183 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use tracing::warn;
+
+use crate::{print_success, print_warn};
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Reveal failure rates over fewer attempts than this are too noisy to page on.
+const MIN_REVEALS_FOR_FAILURE_RATE: usize = 5;
+/// Outcomes kept per provider. The window is only trimmed when alerts are
+/// checked, so without alerting it would otherwise grow with every reveal.
+const MAX_RECORDED_OUTCOMES: usize = 10_000;
+
+/// Payload shape of alert webhooks.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WebhookFormat {
+    /// `{"alert", "status", "provider", "message"}`.
+    Json,
+    /// A Slack incoming webhook `{"text"}` message.
+    Slack,
+}
+
+/// Condition that pages the provider.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AlertKind {
+    /// The current hash chain is close to its end sequence number.
+    ChainNearlyExhausted,
+    /// Too many recent reveals failed.
+    RevealFailures,
+    /// The provider vault holds less than its rent-exempt minimum.
+    VaultBelowRent,
+    /// The RPC node failed several polls in a row.
+    RpcOutage,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct AlertThresholds {
+    /// Alert when at most this many sequence numbers remain in the chain.
+    pub(crate) chain_remaining: u64,
+    /// Alert when more than this fraction of the reveals in `failure_window`
+    /// failed.
+    pub(crate) failure_rate: f64,
+    pub(crate) failure_window: Duration,
+    /// Alert after this many consecutive failed RPC polls.
+    pub(crate) rpc_failures: u32,
+    /// Minimum time between repeated notifications of a lasting alert.
+    pub(crate) cooldown: Duration,
+}
+
+/// Sends webhook notifications when alert conditions start, periodically
+/// while they last, and when they clear. Shared by all providers of the
+/// keeper. Delivery failures are logged, never returned.
+pub(crate) struct Alerter {
+    http: reqwest::blocking::Client,
+    webhook_url: String,
+    format: WebhookFormat,
+    pub(crate) thresholds: AlertThresholds,
+    /// Firing alerts and when they were last sent.
+    firing: Mutex<HashMap<(AlertKind, Option<Pubkey>), Instant>>,
+}
+
+impl Alerter {
+    pub(crate) fn new(
+        webhook_url: String,
+        format: WebhookFormat,
+        thresholds: AlertThresholds,
+    ) -> Result<Self> {
+        let http = reqwest::blocking::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .context("Failed to build HTTP client")?;
+        Ok(Self {
+            http,
+            webhook_url,
+            format,
+            thresholds,
+            firing: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Reports `kind` as firing for `provider` (or the whole keeper).
+    pub(crate) fn raise(&self, kind: AlertKind, provider: Option<Pubkey>, message: &str) {
+        let now = Instant::now();
+        {
+            let Ok(mut firing) = self.firing.lock() else {
+                return;
+            };
+            match firing.get(&(kind, provider)) {
+                Some(sent_at) if now.duration_since(*sent_at) < self.thresholds.cooldown => {
+                    return;
+                }
+                _ => {
+                    firing.insert((kind, provider), now);
+                }
+            }
+        }
+        print_warn(format!("Alert: {message}"));
+        self.send(kind, provider, "firing", message);
+    }
+
+    /// Reports `kind` as resolved, if it was firing.
+    pub(crate) fn resolve(&self, kind: AlertKind, provider: Option<Pubkey>, message: &str) {
+        let was_firing = self
+            .firing
+            .lock()
+            .is_ok_and(|mut firing| firing.remove(&(kind, provider)).is_some());
+        if was_firing {
+            print_success(format!("Resolved: {message}"));
+            self.send(kind, provider, "resolved", message);
+        }
+    }
+
+    fn send(&self, kind: AlertKind, provider: Option<Pubkey>, status: &str, message: &str) {
+        let body = match self.format {
+            WebhookFormat::Json => json!({
+                "alert": kind,
+                "status": status,
+                "provider": provider.map(|provider| provider.to_string()),
+                "message": message,
+            }),
+            WebhookFormat::Slack => {
+                let marker = if status == "firing" {
+                    ":rotating_light:"
+                } else {
+                    ":white_check_mark:"
+                };
+                json!({ "text": format!("{marker} [entropy keeper] {message}") })
+            }
+        };
+        let sent = self
+            .http
+            .post(&self.webhook_url)
+            .json(&body)
+            .send()
+            .and_then(|response| response.error_for_status());
+        if let Err(err) = sent {
+            warn!(alert = ?kind, error = %err, "Failed to deliver alert webhook");
+        }
+    }
+}
+
+/// Outcomes of one provider's recent reveals.
+#[derive(Default)]
+pub(crate) struct FailureWindow {
+    /// Oldest first; `true` for a failed reveal.
+    outcomes: VecDeque<(Instant, bool)>,
+}
+
+impl FailureWindow {
+    pub(crate) fn record(&mut self, now: Instant, failed: bool) {
+        if self.outcomes.len() == MAX_RECORDED_OUTCOMES {
+            self.outcomes.pop_front();
+        }
+        self.outcomes.push_back((now, failed));
+    }
+
+    /// Fraction of the reveals within `window` that failed, or `None` if
+    /// there were too few to tell.
+    pub(crate) fn failure_rate(&mut self, now: Instant, window: Duration) -> Option<f64> {
+        while self
+            .outcomes
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > window)
+        {
+            self.outcomes.pop_front();
+        }
+        if self.outcomes.len() < MIN_REVEALS_FOR_FAILURE_RATE {
+            return None;
+        }
+        let failed = self.outcomes.iter().filter(|(_, failed)| *failed).count();
+        Some(failed as f64 / self.outcomes.len() as f64)
+    }
+}
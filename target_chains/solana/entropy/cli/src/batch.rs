@@ -0,0 +1,177 @@
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash, signature::Keypair};
+use tracing::warn;
+
+use crate::{
+    build_request_transaction, print_info, print_kv, print_success, print_warn, RequestPlan,
+};
+
+/// Blockhashes stay valid for ~150 slots; refreshing well before that lets a
+/// whole batch share a handful of `get_latest_blockhash` calls.
+const BLOCKHASH_REFRESH_INTERVAL: Duration = Duration::from_secs(20);
+/// Upper bound on concurrent in-flight submissions.
+const MAX_WORKERS: u64 = 32;
+
+enum Outcome {
+    Landed(Duration),
+    Failed,
+}
+
+struct BlockhashCache {
+    hash: Hash,
+    fetched_at: Instant,
+}
+
+impl BlockhashCache {
+    fn get(&mut self, rpc_client: &RpcClient) -> Hash {
+        if self.fetched_at.elapsed() >= BLOCKHASH_REFRESH_INTERVAL {
+            match rpc_client.get_latest_blockhash() {
+                Ok(hash) => {
+                    self.hash = hash;
+                    self.fetched_at = Instant::now();
+                }
+                Err(err) => warn!(error = %err, "Failed to refresh blockhash; reusing cached one"),
+            }
+        }
+        self.hash
+    }
+}
+
+/// Sends `count` requests at roughly `rate` per second and reports how many
+/// landed along with confirmation latency percentiles.
+pub(crate) fn run_batch(
+    rpc_client: Arc<RpcClient>,
+    payer: Arc<Keypair>,
+    plan: Arc<RequestPlan>,
+    count: u64,
+    rate: f64,
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    print_info(format!(
+        "Starting load test: {count} requests at {rate} req/s"
+    ));
+
+    let worker_count = count.min(MAX_WORKERS);
+    let (job_sender, job_receiver) = mpsc::sync_channel::<(
+        Instant,
+        solana_sdk::transaction::Transaction,
+    )>(worker_count as usize);
+    let job_receiver = Arc::new(Mutex::new(job_receiver));
+    let (outcome_sender, outcome_receiver) = mpsc::channel::<Outcome>();
+
+    let workers: Vec<_> = (0..worker_count)
+        .map(|_| {
+            let rpc_client = rpc_client.clone();
+            let job_receiver = job_receiver.clone();
+            let outcome_sender = outcome_sender.clone();
+            thread::spawn(move || loop {
+                let job = match job_receiver.lock() {
+                    Ok(receiver) => receiver.recv(),
+                    Err(_) => return,
+                };
+                let Ok((submitted_at, transaction)) = job else {
+                    return;
+                };
+                let outcome = match rpc_client
+                    .send_and_confirm_transaction_with_spinner_and_commitment(
+                        &transaction,
+                        commitment,
+                    ) {
+                    Ok(_) => Outcome::Landed(submitted_at.elapsed()),
+                    Err(err) => {
+                        warn!(error = %err, "Request failed");
+                        Outcome::Failed
+                    }
+                };
+                if outcome_sender.send(outcome).is_err() {
+                    return;
+                }
+            })
+        })
+        .collect();
+    drop(outcome_sender);
+
+    let mut blockhash = BlockhashCache {
+        hash: rpc_client.get_latest_blockhash()?,
+        fetched_at: Instant::now(),
+    };
+    let interval = Duration::from_secs_f64(1.0 / rate);
+    let started = Instant::now();
+    let mut build_failures = 0u64;
+
+    for index in 0..count {
+        let target = started + interval.mul_f64(index as f64);
+        if let Some(wait) = target.checked_duration_since(Instant::now()) {
+            thread::sleep(wait);
+        }
+
+        let hash = blockhash.get(&rpc_client);
+        match build_request_transaction(&plan, &payer, hash) {
+            Ok(built) => {
+                if job_sender
+                    .send((Instant::now(), built.transaction))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            Err(err) => {
+                warn!(error = %err, "Failed to build request transaction");
+                build_failures += 1;
+            }
+        }
+    }
+    let submission_time = started.elapsed();
+    drop(job_sender);
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    let mut latencies = Vec::new();
+    let mut failed = build_failures;
+    for outcome in outcome_receiver {
+        match outcome {
+            Outcome::Landed(latency) => latencies.push(latency),
+            Outcome::Failed => failed += 1,
+        }
+    }
+    latencies.sort();
+
+    print_success("Load test finished");
+    print_kv("landed:", latencies.len());
+    print_kv("failed:", failed);
+    print_kv(
+        "achieved rate:",
+        format!(
+            "{:.2} req/s",
+            count as f64 / submission_time.as_secs_f64().max(f64::EPSILON)
+        ),
+    );
+    if latencies.is_empty() {
+        print_warn("No requests landed; latency percentiles unavailable");
+    } else {
+        for (label, quantile) in [("p50:", 0.5), ("p90:", 0.9), ("p99:", 0.99)] {
+            print_kv(label, format!("{:?}", percentile(&latencies, quantile)));
+        }
+        if let Some(max) = latencies.last() {
+            print_kv("max:", format!("{max:?}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Nearest-rank percentile over an ascending, non-empty slice.
+pub(crate) fn percentile(sorted: &[Duration], quantile: f64) -> Duration {
+    let rank = (quantile * sorted.len() as f64).ceil() as usize;
+    let index = rank.clamp(1, sorted.len()) - 1;
+    sorted.get(index).copied().unwrap_or_default()
+}
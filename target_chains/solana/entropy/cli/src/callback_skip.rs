@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use bytemuck::try_from_bytes;
+use entropy::{
+    accounts::{CallbackFailureStats, CallbackStatus, Provider, Request},
+    instruction::RevealArgs,
+    pda::callback_failure_stats_pda,
+};
+use entropy_sdk::{
+    instruction::{report_callback_failure, reveal_skipping_callback, set_callback_skip_policy},
+    parse_callback_failure, CallbackFailure,
+};
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, instruction::Instruction, message::Message,
+    pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction,
+};
+
+use crate::{print_success, send_and_confirm};
+
+/// `--callback-skip-max-failures` and its window, written to each provider
+/// at startup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct CallbackSkipPolicy {
+    pub(crate) max_failures: u16,
+    pub(crate) window_slots: u32,
+}
+
+/// Writes `policy` to the provider unless it is already set, and mirrors it
+/// into `provider`.
+pub(crate) fn apply_policy(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    entropy_program_id: Pubkey,
+    provider: &mut Provider,
+    policy: CallbackSkipPolicy,
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    if provider.callback_skip_max_failures == policy.max_failures
+        && provider.callback_skip_window_slots == policy.window_slots
+    {
+        return Ok(());
+    }
+    let ix = set_callback_skip_policy(
+        entropy_program_id,
+        payer.pubkey(),
+        policy.max_failures,
+        policy.window_slots,
+    );
+    send_and_confirm(rpc_client, payer, &[ix], commitment)
+        .context("Failed to set the callback skip policy")?;
+    provider.callback_skip_max_failures = policy.max_failures;
+    provider.callback_skip_window_slots = policy.window_slots;
+    print_success(format!(
+        "Callback skip policy set: {} failure(s) per {} slots",
+        policy.max_failures, policy.window_slots
+    ));
+    Ok(())
+}
+
+/// What simulating a reveal said about its callback.
+pub(crate) enum CallbackCheck {
+    /// The callback did not fail; send the reveal as built.
+    Passes,
+    /// The callback failed and the policy now skips its program: send this
+    /// reveal without the callback instead.
+    Skip(Instruction),
+    /// The callback failed, below the policy's limit. A reveal would fail
+    /// the same way.
+    Fails(CallbackFailure),
+}
+
+/// Simulates `reveal_ix`. A failing callback is reported on chain, unless
+/// the request was already, after which the provider's policy decides whether the request is revealed
+/// without it. Failures of anything but the callback count as passing, for
+/// the reveal itself to surface.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn check_callback(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    entropy_program_id: Pubkey,
+    provider: &Provider,
+    request_account: Pubkey,
+    request: &Request,
+    args: RevealArgs,
+    reveal_ix: &Instruction,
+    commitment: CommitmentConfig,
+) -> Result<CallbackCheck> {
+    let message = Message::new(std::slice::from_ref(reveal_ix), Some(&payer.pubkey()));
+    let simulation = rpc_client
+        .simulate_transaction_with_config(
+            &Transaction::new_unsigned(message),
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(commitment),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .context("Failed to simulate reveal")?
+        .value;
+    if simulation.err.is_none() {
+        return Ok(CallbackCheck::Passes);
+    }
+    let logs = simulation.logs.unwrap_or_default();
+    let Some(failure) = parse_callback_failure(&entropy_program_id, &logs) else {
+        return Ok(CallbackCheck::Passes);
+    };
+
+    // Each request is reported once; one already reported only needs the
+    // policy checked again.
+    if request.status() == Ok(CallbackStatus::NotStarted) {
+        let report_ix = report_callback_failure(entropy_program_id, request_account, request);
+        send_and_confirm(rpc_client, payer, &[report_ix], commitment)
+            .context("Failed to report callback failure")?;
+    }
+
+    let (stats_account, _) = callback_failure_stats_pda(
+        &entropy_program_id,
+        &payer.pubkey(),
+        &Pubkey::new_from_array(request.requester_program_id),
+    );
+    let stats_data = rpc_client
+        .get_account_data(&stats_account)
+        .with_context(|| format!("Failed to fetch callback failure stats {stats_account}"))?;
+    let stats = try_from_bytes::<CallbackFailureStats>(&stats_data)
+        .map_err(|err| anyhow::anyhow!("Failed to parse callback failure stats: {err}"))?;
+    let slot = rpc_client.get_slot_with_commitment(commitment)?;
+
+    if provider.skips_callbacks(stats, slot) {
+        Ok(CallbackCheck::Skip(reveal_skipping_callback(
+            entropy_program_id,
+            request_account,
+            request,
+            args,
+        )))
+    } else {
+        Ok(CallbackCheck::Fails(failure))
+    }
+}
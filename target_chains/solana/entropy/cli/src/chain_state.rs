@@ -0,0 +1,193 @@
+use std::{
+    fmt, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use entropy::accounts::Provider;
+use entropy_sdk::HashChain;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+/// What the keeper does when its persisted chain disagrees with the
+/// on-chain provider at startup.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DivergencePolicy {
+    /// Exit without revealing.
+    Refuse,
+    /// Adopt the on-chain head if it is still on the persisted chain,
+    /// otherwise register a fresh chain.
+    Resync,
+}
+
+/// A provider's persisted hash chains, written as one JSON file per
+/// provider.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct ChainStateFile {
+    provider: String,
+    /// Base58 [`HashChain::to_bytes`], oldest first; the last one is current.
+    chains: Vec<String>,
+    /// Highest sequence number this keeper revealed with the current chain.
+    revealed_sequence: Option<u64>,
+}
+
+/// Hash chains loaded from a [`ChainStore`].
+pub(crate) struct ChainState {
+    /// Oldest first; never empty.
+    pub(crate) chains: Vec<HashChain>,
+    pub(crate) revealed_sequence: Option<u64>,
+}
+
+impl ChainState {
+    pub(crate) fn current(&self) -> &HashChain {
+        &self.chains[self.chains.len() - 1]
+    }
+}
+
+/// Where one provider's chains are persisted, so a restarted keeper can
+/// keep revealing from them instead of registering a fresh chain.
+pub(crate) struct ChainStore {
+    path: PathBuf,
+    provider: Pubkey,
+}
+
+impl ChainStore {
+    pub(crate) fn new(dir: &Path, provider: Pubkey) -> Self {
+        Self {
+            path: dir.join(format!("{provider}.json")),
+            provider,
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The persisted state, or `None` if nothing was persisted yet.
+    pub(crate) fn load(&self) -> Result<Option<ChainState>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("Failed to read chain state {}", self.path.display()))
+            }
+        };
+        let file: ChainStateFile = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse chain state {}", self.path.display()))?;
+        if file.provider != self.provider.to_string() {
+            anyhow::bail!(
+                "Chain state {} belongs to provider {}, not {}",
+                self.path.display(),
+                file.provider,
+                self.provider
+            );
+        }
+        let chains = file
+            .chains
+            .iter()
+            .map(|chain| {
+                let bytes = bs58::decode(chain).into_vec()?;
+                Ok(HashChain::from_bytes(&bytes)?)
+            })
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("Invalid hash chain in {}", self.path.display()))?;
+        if chains.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(ChainState {
+            chains,
+            revealed_sequence: file.revealed_sequence,
+        }))
+    }
+
+    /// Replaces the persisted state. The file is written next to the old one
+    /// and renamed over it, so a crash never leaves a torn state.
+    pub(crate) fn save<'a>(
+        &self,
+        chains: impl IntoIterator<Item = &'a HashChain>,
+        revealed_sequence: Option<u64>,
+    ) -> Result<()> {
+        let file = ChainStateFile {
+            provider: self.provider.to_string(),
+            chains: chains
+                .into_iter()
+                .map(|chain| bs58::encode(chain.to_bytes()).into_string())
+                .collect(),
+            revealed_sequence,
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let staging = self.path.with_extension("json.tmp");
+        fs::write(&staging, serde_json::to_vec_pretty(&file)?)
+            .with_context(|| format!("Failed to write {}", staging.display()))?;
+        fs::rename(&staging, &self.path)
+            .with_context(|| format!("Failed to write chain state {}", self.path.display()))
+    }
+}
+
+/// How the on-chain provider disagrees with a persisted chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Divergence {
+    /// The provider account does not exist.
+    NotRegistered,
+    /// The provider registered a chain other than the persisted one.
+    Rotated,
+    /// The on-chain commitment is not on the persisted chain; reveals from
+    /// it would fail with `IncorrectRevelation`.
+    CommitmentMismatch { sequence: u64 },
+    /// The on-chain commitment is further along the persisted chain than
+    /// this keeper revealed, so another instance is revealing with it.
+    RevealedElsewhere { local: u64, on_chain: u64 },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotRegistered => write!(f, "the provider is not registered on chain"),
+            Self::Rotated => write!(
+                f,
+                "the provider registered a hash chain this keeper does not hold"
+            ),
+            Self::CommitmentMismatch { sequence } => write!(
+                f,
+                "the on-chain commitment at sequence {sequence} is not on the persisted chain"
+            ),
+            Self::RevealedElsewhere { local, on_chain } => write!(
+                f,
+                "the on-chain commitment is at sequence {on_chain} but this keeper last revealed \
+                 {local}; another keeper may be revealing for this provider"
+            ),
+        }
+    }
+}
+
+/// Compares the persisted current chain and revealed head with the on-chain
+/// provider.
+pub(crate) fn check(state: &ChainState, provider: Option<&Provider>) -> Result<(), Divergence> {
+    let Some(provider) = provider else {
+        return Err(Divergence::NotRegistered);
+    };
+    let chain = state.current();
+    if provider.original_commitment != chain.commitment()
+        || provider.original_commitment_sequence_number != chain.start_sequence()
+    {
+        return Err(Divergence::Rotated);
+    }
+    let sequence = provider.current_commitment_sequence_number;
+    if chain.contribution_for_sequence(sequence) != Some(provider.current_commitment) {
+        return Err(Divergence::CommitmentMismatch { sequence });
+    }
+    let local = state.revealed_sequence.unwrap_or(chain.start_sequence());
+    if sequence > local {
+        return Err(Divergence::RevealedElsewhere {
+            local,
+            on_chain: sequence,
+        });
+    }
+    Ok(())
+}
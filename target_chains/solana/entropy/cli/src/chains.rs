@@ -0,0 +1,49 @@
+use entropy_sdk::HashChain;
+
+/// The provider's current hash chain plus the chains it rotated away from.
+/// Requests keep the commitment they were made against, so a retired chain
+/// is still needed to reveal them.
+pub(crate) struct ProviderChains {
+    /// Oldest first; the last one is current.
+    chains: Vec<HashChain>,
+}
+
+impl ProviderChains {
+    pub(crate) fn new(current: HashChain) -> Self {
+        Self {
+            chains: vec![current],
+        }
+    }
+
+    /// Restores chains persisted oldest first; `None` if there are none.
+    pub(crate) fn from_chains(chains: Vec<HashChain>) -> Option<Self> {
+        (!chains.is_empty()).then_some(Self { chains })
+    }
+
+    /// Oldest first; the last one is current.
+    pub(crate) fn chains(&self) -> &[HashChain] {
+        &self.chains
+    }
+
+    pub(crate) fn current(&self) -> &HashChain {
+        // `new` and `rotate` keep at least one chain.
+        &self.chains[self.chains.len() - 1]
+    }
+
+    pub(crate) fn retired(&self) -> usize {
+        self.chains.len() - 1
+    }
+
+    /// Makes `chain` current, retiring the previous one.
+    pub(crate) fn rotate(&mut self, chain: HashChain) {
+        self.chains.push(chain);
+    }
+
+    /// Looks `sequence` up in the newest chain that covers it.
+    pub(crate) fn contribution_for_sequence(&self, sequence: u64) -> Option<[u8; 32]> {
+        self.chains
+            .iter()
+            .rev()
+            .find_map(|chain| chain.contribution_for_sequence(sequence))
+    }
+}
@@ -0,0 +1,124 @@
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+use crate::{load_keypair, localnet};
+
+/// A standard Solana cluster, whose RPC URL and entropy deployment fill in
+/// whatever is not passed explicitly.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Cluster {
+    #[value(alias = "mainnet-beta")]
+    Mainnet,
+    Devnet,
+    Testnet,
+    Localnet,
+}
+
+impl Cluster {
+    pub(crate) fn rpc_url(self) -> &'static str {
+        match self {
+            Self::Mainnet => "https://api.mainnet-beta.solana.com",
+            Self::Devnet => "https://api.devnet.solana.com",
+            Self::Testnet => "https://api.testnet.solana.com",
+            Self::Localnet => "http://localhost:8899",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Mainnet => "mainnet",
+            Self::Devnet => "devnet",
+            Self::Testnet => "testnet",
+            Self::Localnet => "localnet",
+        }
+    }
+}
+
+impl std::fmt::Display for Cluster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A deployment published with the CLI.
+struct KnownDeployment {
+    cluster: Cluster,
+    entropy: &'static str,
+    simple_requester: &'static str,
+}
+
+/// Deployments built into the CLI. None is published yet; until then they
+/// come from a `--program-registry` file, and localnet from the
+/// `cargo build-sbf` keypairs.
+const KNOWN_DEPLOYMENTS: &[KnownDeployment] = &[];
+
+/// One cluster's entry in a `--program-registry` file, e.g.
+/// `{"devnet": {"entropy": "<PROGRAM_ID>", "simple_requester": "<PROGRAM_ID>"}}`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct RegistryEntry {
+    entropy: Option<String>,
+    simple_requester: Option<String>,
+}
+
+/// Program ids of one cluster's deployment; either may be unknown.
+#[derive(Debug, Default)]
+pub(crate) struct ProgramIds {
+    pub(crate) entropy: Option<Pubkey>,
+    pub(crate) simple_requester: Option<Pubkey>,
+}
+
+/// The program ids of `cluster`. Each comes from the registry file at
+/// `registry` if it lists one, then from the built-in deployments, and for
+/// localnet from the program keypairs in the default artifacts directory.
+pub(crate) fn program_ids(cluster: Cluster, registry: Option<&Path>) -> Result<ProgramIds> {
+    let mut ids = ProgramIds::default();
+    if let Some(path) = registry {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read program registry {}", path.display()))?;
+        let mut entries: HashMap<String, RegistryEntry> = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse program registry {}", path.display()))?;
+        let entry = entries.remove(cluster.name()).unwrap_or_default();
+        let parse = |id: Option<String>, label: &str| {
+            id.map(|id| {
+                Pubkey::from_str(&id).with_context(|| {
+                    format!("Invalid {label} program id for {cluster} in the registry: {id}")
+                })
+            })
+            .transpose()
+        };
+        ids.entropy = parse(entry.entropy, "entropy")?;
+        ids.simple_requester = parse(entry.simple_requester, "simple requester")?;
+    }
+
+    if let Some(known) = KNOWN_DEPLOYMENTS
+        .iter()
+        .find(|deployment| deployment.cluster == cluster)
+    {
+        ids.entropy = ids.entropy.or_else(|| Pubkey::from_str(known.entropy).ok());
+        ids.simple_requester = ids
+            .simple_requester
+            .or_else(|| Pubkey::from_str(known.simple_requester).ok());
+    }
+
+    if cluster == Cluster::Localnet {
+        let artifact_id = |name: &str| {
+            let path =
+                Path::new(localnet::DEFAULT_ARTIFACTS_DIR).join(format!("{name}-keypair.json"));
+            path.exists()
+                .then(|| load_keypair(&path).map(|keypair| keypair.pubkey()))
+                .transpose()
+        };
+        if ids.entropy.is_none() {
+            ids.entropy = artifact_id("entropy")?;
+        }
+        if ids.simple_requester.is_none() {
+            ids.simple_requester = artifact_id("simple_requester")?;
+        }
+    }
+    Ok(ids)
+}
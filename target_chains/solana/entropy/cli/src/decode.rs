@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bs58::decode as bs58_decode;
+use entropy_sdk::{parse_callback_failure, CallbackFailure, DecodedInstruction, IdlDecoder};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, UiCompiledInstruction, UiInstruction,
+};
+
+use crate::{print_info, print_kv, print_success, print_warn, raw_message_with_keys, verify};
+
+/// Prints the entropy instructions of the transaction `signature`, top-level
+/// and CPI alike: their decoded arguments, their accounts by role, and the
+/// return data entropy set.
+pub(crate) fn decode_transaction(
+    rpc_client: &RpcClient,
+    entropy_program_id: Pubkey,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    let tx = verify::fetch_transaction(rpc_client, signature, commitment)?;
+    let (raw_message, account_keys) = raw_message_with_keys(&tx)?
+        .context("Transaction is not in the raw JSON encoding it was requested in")?;
+    let meta = tx.transaction.meta.as_ref();
+
+    print_info(format!("Transaction {signature}"));
+    print_kv("slot:", tx.slot);
+    match meta.and_then(|meta| meta.err.as_ref()) {
+        Some(err) => {
+            print_warn(format!("Failed: {err}"));
+            if let Some(OptionSerializer::Some(logs)) = meta.map(|meta| &meta.log_messages) {
+                if let Some(failure) = parse_callback_failure(&entropy_program_id, logs) {
+                    print_callback_failure(&failure);
+                }
+            }
+        }
+        None => print_success("Succeeded"),
+    }
+
+    let inner: &[_] = match meta.map(|meta| &meta.inner_instructions) {
+        Some(OptionSerializer::Some(inner)) => inner,
+        _ => &[],
+    };
+    let mut instructions = Vec::new();
+    for (index, instruction) in raw_message.instructions.iter().enumerate() {
+        instructions.push((index.to_string(), instruction));
+        let cpis = inner
+            .iter()
+            .filter(|inner| usize::from(inner.index) == index)
+            .flat_map(|inner| &inner.instructions)
+            .filter_map(|instruction| match instruction {
+                UiInstruction::Compiled(compiled) => Some(compiled),
+                _ => None,
+            });
+        for (cpi_index, instruction) in cpis.enumerate() {
+            instructions.push((format!("{index}.{}", cpi_index + 1), instruction));
+        }
+    }
+
+    let decoder = IdlDecoder::new();
+    let mut last_decoded = None;
+    for (position, instruction) in instructions {
+        let program = account_keys.get(usize::from(instruction.program_id_index));
+        if program != Some(&entropy_program_id) {
+            continue;
+        }
+        print_info(format!("Instruction #{position}"));
+        match decode_instruction(&decoder, instruction, &account_keys) {
+            Ok(decoded) => {
+                print_decoded(&decoded)?;
+                last_decoded = Some(decoded);
+            }
+            Err(err) => print_warn(format!("Failed to decode: {err:#}")),
+        }
+    }
+    let Some(last_decoded) = last_decoded else {
+        print_warn(format!(
+            "No entropy instructions for program {entropy_program_id}"
+        ));
+        return Ok(());
+    };
+
+    let return_data = match meta.map(|meta| &meta.return_data) {
+        Some(OptionSerializer::Some(return_data))
+            if return_data.program_id == entropy_program_id.to_string() =>
+        {
+            STANDARD
+                .decode(&return_data.data.0)
+                .context("Failed to decode the return data")?
+        }
+        _ => return Ok(()),
+    };
+    print_info("Return data");
+    match decoder.decode_return_data(&last_decoded, &return_data) {
+        Ok(Some(value)) => print_json(&value)?,
+        Ok(None) => print_kv("raw:", STANDARD.encode(&return_data)),
+        Err(err) => {
+            print_warn(format!("Failed to decode: {err}"));
+            print_kv("raw:", STANDARD.encode(&return_data));
+        }
+    }
+    Ok(())
+}
+
+fn decode_instruction(
+    decoder: &IdlDecoder,
+    instruction: &UiCompiledInstruction,
+    account_keys: &[Pubkey],
+) -> Result<DecodedInstruction> {
+    let data = bs58_decode(&instruction.data)
+        .into_vec()
+        .context("Instruction data is not base58")?;
+    let accounts = instruction
+        .accounts
+        .iter()
+        .map(|index| {
+            account_keys
+                .get(usize::from(*index))
+                .copied()
+                .with_context(|| format!("Account index {index} out of range"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(decoder.decode(&data, &accounts)?)
+}
+
+fn print_decoded(decoded: &DecodedInstruction) -> Result<()> {
+    print_kv("instruction:", &decoded.name);
+    for (role, pubkey) in &decoded.accounts {
+        print_kv(&format!("{role}:"), pubkey);
+    }
+    if let Some(args) = &decoded.args {
+        print_kv("args:", "");
+        print_json(args)?;
+    }
+    Ok(())
+}
+
+/// Prints the request whose callback failed, with what the callback failed
+/// with, from [`parse_callback_failure`].
+pub(crate) fn print_callback_failure(failure: &CallbackFailure) {
+    let request = &failure.request;
+    print_warn("Callback failed");
+    print_kv(
+        "request account:",
+        Pubkey::new_from_array(request.request_account),
+    );
+    print_kv("provider:", Pubkey::new_from_array(request.provider));
+    print_kv("sequence number:", request.sequence_number);
+    print_kv(
+        "callback program:",
+        Pubkey::new_from_array(request.requester_program_id),
+    );
+    print_kv("request slot:", request.request_slot);
+    print_kv("compute unit limit:", request.compute_unit_limit);
+    if let Some(units) = failure.compute_units_consumed {
+        print_kv("compute units used:", units);
+    }
+    match failure.error_code {
+        Some(code) => print_kv("error:", format!("{} ({code})", failure.error)),
+        None => print_kv("error:", &failure.error),
+    }
+}
+
+fn print_json(value: &serde_json::Value) -> Result<()> {
+    for line in serde_json::to_string_pretty(value)?.lines() {
+        println!("    {line}");
+    }
+    Ok(())
+}
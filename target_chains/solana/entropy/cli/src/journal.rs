@@ -0,0 +1,299 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use tracing::warn;
+
+use crate::{batch::percentile, print_info, print_kv, print_warn};
+
+/// One keeper event, written as a single JSON line.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct JournalEntry {
+    /// Milliseconds since the Unix epoch.
+    pub(crate) at_ms: u64,
+    #[serde(flatten)]
+    pub(crate) event: JournalEvent,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub(crate) enum JournalEvent {
+    /// A callback request for this provider was queued for reveal.
+    Observed {
+        request_account: String,
+        sequence_number: u64,
+        requester_program: String,
+        provider_fee: u64,
+        /// Base58; needed to replay the reveal.
+        user_randomness: String,
+    },
+    RevealAttempt {
+        request_account: String,
+        sequence_number: u64,
+    },
+    Revealed {
+        request_account: String,
+        sequence_number: u64,
+        signature: String,
+    },
+    RevealFailed {
+        request_account: String,
+        sequence_number: u64,
+        error: String,
+    },
+    /// The keeper gave up on the request, e.g. because it was unprofitable
+    /// or already closed.
+    Skipped {
+        request_account: String,
+        sequence_number: u64,
+        reason: String,
+    },
+}
+
+impl JournalEvent {
+    pub(crate) fn skipped(request_account: &Pubkey, sequence_number: u64, reason: &str) -> Self {
+        Self::Skipped {
+            request_account: request_account.to_string(),
+            sequence_number,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn request_account(&self) -> &str {
+        match self {
+            Self::Observed {
+                request_account, ..
+            }
+            | Self::RevealAttempt {
+                request_account, ..
+            }
+            | Self::Revealed {
+                request_account, ..
+            }
+            | Self::RevealFailed {
+                request_account, ..
+            }
+            | Self::Skipped {
+                request_account, ..
+            } => request_account,
+        }
+    }
+
+    /// Revealed or given up on; nothing left to replay.
+    fn is_final(&self) -> bool {
+        matches!(self, Self::Revealed { .. } | Self::Skipped { .. })
+    }
+}
+
+/// Append-only request journal. Write failures are logged rather than
+/// returned, so a full disk degrades auditing but never stops reveals.
+/// Shared by all providers of the keeper, which record from their own
+/// threads.
+pub(crate) struct Journal {
+    file: Option<Mutex<File>>,
+}
+
+impl Journal {
+    /// Opens `path` for appending, or a journal that records nothing.
+    pub(crate) fn open(path: Option<&Path>) -> Result<Self> {
+        let file = path
+            .map(|path| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("Failed to open journal {}", path.display()))
+                    .map(Mutex::new)
+            })
+            .transpose()?;
+        Ok(Self { file })
+    }
+
+    pub(crate) fn record(&self, event: JournalEvent) {
+        let Some(file) = &self.file else {
+            return;
+        };
+        let entry = JournalEntry {
+            at_ms: now_ms(),
+            event,
+        };
+        let written = serde_json::to_string(&entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| {
+                let mut file = file
+                    .lock()
+                    .map_err(|_| anyhow::anyhow!("journal lock poisoned"))?;
+                Ok(writeln!(file, "{line}")?)
+            });
+        if let Err(err) = written {
+            warn!(error = %err, "Failed to write journal entry");
+        }
+    }
+}
+
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Reads every entry of the journal at `path`. A torn last line, left by a
+/// crash mid-write, is skipped with a warning.
+pub(crate) fn read_journal(path: &Path) -> Result<Vec<JournalEntry>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open journal {}", path.display()))?;
+    let mut entries = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.context("Failed to read journal")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => {
+                warn!(line = index + 1, error = %err, "Skipping malformed journal entry");
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// A journaled request whose reveal never confirmed.
+pub(crate) struct Unfinished {
+    pub(crate) request_account: Pubkey,
+    pub(crate) user_randomness: [u8; 32],
+}
+
+/// Requests observed in `entries` with no final outcome, in observation order.
+pub(crate) fn unfinished(entries: &[JournalEntry]) -> Vec<Unfinished> {
+    let finished: HashSet<&str> = entries
+        .iter()
+        .filter(|entry| entry.event.is_final())
+        .map(|entry| entry.event.request_account())
+        .collect();
+    let mut seen = HashSet::new();
+    entries
+        .iter()
+        .filter_map(|entry| match &entry.event {
+            JournalEvent::Observed {
+                request_account,
+                user_randomness,
+                ..
+            } if !finished.contains(request_account.as_str())
+                && seen.insert(request_account.as_str()) =>
+            {
+                let request_account = Pubkey::from_str(request_account).ok()?;
+                let user_randomness = bs58::decode(user_randomness)
+                    .into_vec()
+                    .ok()?
+                    .try_into()
+                    .ok()?;
+                Some(Unfinished {
+                    request_account,
+                    user_randomness,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Fulfillment summary of a journal.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct JournalStats {
+    pub(crate) entries: usize,
+    pub(crate) observed: usize,
+    pub(crate) attempts: usize,
+    pub(crate) revealed: usize,
+    pub(crate) failed_attempts: usize,
+    pub(crate) skipped: HashMap<String, usize>,
+    pub(crate) pending: usize,
+    /// First observation to confirmed reveal, ascending.
+    pub(crate) latencies: Vec<Duration>,
+}
+
+impl JournalStats {
+    pub(crate) fn from_entries(entries: &[JournalEntry]) -> Self {
+        let mut stats = Self {
+            entries: entries.len(),
+            ..Self::default()
+        };
+        let mut first_observed: HashMap<&str, u64> = HashMap::new();
+        for entry in entries {
+            match &entry.event {
+                // Entries are appended in order, and replays re-observe a
+                // request, so only the first observation counts.
+                JournalEvent::Observed {
+                    request_account, ..
+                } => {
+                    if let Entry::Vacant(vacant) = first_observed.entry(request_account) {
+                        vacant.insert(entry.at_ms);
+                        stats.observed += 1;
+                    }
+                }
+                JournalEvent::RevealAttempt { .. } => stats.attempts += 1,
+                JournalEvent::Revealed {
+                    request_account, ..
+                } => {
+                    stats.revealed += 1;
+                    if let Some(observed_at) = first_observed.get(request_account.as_str()) {
+                        stats.latencies.push(Duration::from_millis(
+                            entry.at_ms.saturating_sub(*observed_at),
+                        ));
+                    }
+                }
+                JournalEvent::RevealFailed { .. } => stats.failed_attempts += 1,
+                JournalEvent::Skipped { reason, .. } => {
+                    *stats.skipped.entry(reason.clone()).or_default() += 1;
+                }
+            }
+        }
+        stats.pending = unfinished(entries).len();
+        stats.latencies.sort();
+        stats
+    }
+
+    pub(crate) fn print(&self) {
+        print_info("Journal summary");
+        print_kv("entries:", self.entries);
+        print_kv("requests observed:", self.observed);
+        print_kv("reveal attempts:", self.attempts);
+        print_kv("reveals confirmed:", self.revealed);
+        print_kv("attempts failed:", self.failed_attempts);
+        let mut skipped: Vec<_> = self.skipped.iter().collect();
+        skipped.sort();
+        for (reason, count) in skipped {
+            print_kv(&format!("skipped ({reason}):"), count);
+        }
+        print_kv("pending:", self.pending);
+        if self.latencies.is_empty() {
+            print_warn("No confirmed reveals; latency percentiles unavailable");
+        } else {
+            for (label, quantile) in [("p50:", 0.5), ("p90:", 0.9), ("p99:", 0.99)] {
+                print_kv(
+                    label,
+                    format!("{:?}", percentile(&self.latencies, quantile)),
+                );
+            }
+            if let Some(max) = self.latencies.last() {
+                print_kv("max:", format!("{max:?}"));
+            }
+        }
+    }
+}
+
+/// `entropy journal stats`.
+pub(crate) fn print_stats(path: PathBuf) -> Result<()> {
+    let entries = read_journal(&path)?;
+    JournalStats::from_entries(&entries).print();
+    Ok(())
+}
@@ -0,0 +1,862 @@
+use std::{
+    path::Path,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use bytemuck::try_from_bytes;
+use entropy::{
+    accounts::{CallbackAccountList, CallbackStatus, Provider, Request},
+    instruction::RevealArgs,
+    pda::{provider_pda, provider_vault_pda},
+};
+use entropy_sdk::instruction::{reveal_with_callback, reveal_with_callback_account_list};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, hash::Hash, instruction::Instruction, pubkey::Pubkey,
+    signature::Keypair, signer::Signer,
+};
+use tracing::{info, warn};
+
+use crate::{
+    admin::{AdminState, KeeperSnapshot, PendingSummary},
+    alert::{AlertKind, Alerter, FailureWindow},
+    callback_skip::{self, CallbackCheck, CallbackSkipPolicy},
+    chain_state::{self, ChainState, ChainStore, Divergence, DivergencePolicy},
+    chains::ProviderChains,
+    journal::{Journal, JournalEvent},
+    lease::{LeaseHolder, LeaseStatus},
+    print_error, print_info, print_kv, print_success, print_warn, profit, register_chain,
+    request_store::RequestStore,
+    schedule::{PendingReveal, RequesterLimit, RevealOrder, RevealScheduler},
+    shutdown::{KeeperStats, Shutdown},
+    submit::Submitter,
+    sweep::FeeSweeper,
+    RequestKind, RequestObservation,
+};
+
+/// Everything the providers served by one keeper process share.
+pub(crate) struct KeeperContext<'a> {
+    pub(crate) rpc_client: &'a RpcClient,
+    pub(crate) entropy_program_id: Pubkey,
+    pub(crate) commitment: CommitmentConfig,
+    pub(crate) submitter: &'a Submitter,
+    pub(crate) shutdown: &'a Shutdown,
+    pub(crate) journal: &'a Journal,
+    pub(crate) requests: &'a RequestStore,
+    pub(crate) admin: &'a AdminState,
+    pub(crate) sweeper: Option<&'a FeeSweeper>,
+    pub(crate) alerter: Option<&'a Alerter>,
+    pub(crate) chain_state_dir: Option<&'a Path>,
+    pub(crate) on_divergence: DivergencePolicy,
+    /// Hold each provider's on-chain keeper lease for this many slots.
+    pub(crate) keeper_lease_slots: Option<u64>,
+    /// Written to each provider at startup; unset leaves the on-chain policy.
+    pub(crate) callback_skip_policy: Option<CallbackSkipPolicy>,
+    pub(crate) uri: &'a str,
+    pub(crate) https_only: bool,
+    pub(crate) only_profitable: bool,
+    pub(crate) reveal_order: RevealOrder,
+    pub(crate) max_reveals_per_requester: Option<u32>,
+    pub(crate) requester_window: Duration,
+}
+
+/// One provider's keypair, hash chains and reveal queue.
+pub(crate) struct ProviderKeeper {
+    /// Position in the admin API's provider list.
+    index: usize,
+    payer: Keypair,
+    provider_account: Pubkey,
+    provider: Provider,
+    chains: ProviderChains,
+    scheduler: RevealScheduler,
+    stats: Arc<KeeperStats>,
+    failures: FailureWindow,
+    last_alert_check: Option<Instant>,
+    /// Persists the chains so a restart can resume them.
+    store: Option<ChainStore>,
+    /// Highest sequence number revealed with the current chain.
+    revealed_sequence: Option<u64>,
+    lease: Option<LeaseHolder>,
+    lease_lost: bool,
+}
+
+/// Alert conditions that need account reads are checked at most this often.
+const ALERT_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+impl ProviderKeeper {
+    /// Resumes the chains persisted for `payer` if they still match the
+    /// on-chain provider, or registers it with a fresh hash chain. With a
+    /// keeper lease, first stands by until the lease is free; returns `None`
+    /// if shutdown is requested meanwhile.
+    pub(crate) fn register(
+        ctx: &KeeperContext,
+        index: usize,
+        payer: Keypair,
+        stats: Arc<KeeperStats>,
+    ) -> Result<Option<Self>> {
+        let (provider_account, _) = provider_pda(&ctx.entropy_program_id, &payer.pubkey());
+        let mut lease = ctx
+            .keeper_lease_slots
+            .map(|slots| LeaseHolder::new(ctx.entropy_program_id, &payer.pubkey(), slots));
+        // The lease needs a registered provider; before that there is no
+        // chain another keeper could be revealing from.
+        if let Some(lease) = &mut lease {
+            if ctx.rpc_client.get_account(&provider_account).is_ok()
+                && !lease.acquire(ctx.rpc_client, &payer, ctx.commitment, ctx.shutdown)?
+            {
+                return Ok(None);
+            }
+        }
+        let on_chain = ctx
+            .rpc_client
+            .get_account_data(&provider_account)
+            .ok()
+            .and_then(|data| try_from_bytes::<Provider>(&data).ok().copied());
+        let store = ctx
+            .chain_state_dir
+            .map(|dir| ChainStore::new(dir, payer.pubkey()));
+        let persisted = store.as_ref().map(ChainStore::load).transpose()?.flatten();
+
+        let mut retired = Vec::new();
+        let mut resumed = None;
+        if let Some(state) = persisted {
+            match chain_state::check(&state, on_chain.as_ref()) {
+                Ok(()) => resumed = Some(state),
+                Err(divergence) if ctx.on_divergence == DivergencePolicy::Refuse => {
+                    anyhow::bail!(
+                        "Persisted chain of {} diverged from the on-chain provider: {divergence}. \
+                         Stop any other keeper for this provider, then restart with \
+                         --on-chain-divergence resync",
+                        payer.pubkey()
+                    );
+                }
+                Err(Divergence::RevealedElsewhere { on_chain, .. }) => {
+                    print_warn(format!(
+                        "Resyncing {} to the on-chain head at sequence {on_chain}",
+                        payer.pubkey()
+                    ));
+                    resumed = Some(ChainState {
+                        revealed_sequence: Some(on_chain),
+                        ..state
+                    });
+                }
+                Err(divergence) => {
+                    print_warn(format!(
+                        "Persisted chain of {} diverged ({divergence}); registering a fresh one",
+                        payer.pubkey()
+                    ));
+                    retired = state.chains;
+                }
+            }
+        }
+
+        let (chains, mut provider, revealed_sequence) = match (resumed, on_chain) {
+            (Some(state), Some(provider)) => {
+                print_success(format!(
+                    "Resumed hash chain of {} at sequence {}",
+                    payer.pubkey(),
+                    provider.current_commitment_sequence_number
+                ));
+                let chains = ProviderChains::from_chains(state.chains)
+                    .context("Persisted chain state holds no chains")?;
+                (chains, provider, state.revealed_sequence)
+            }
+            _ => {
+                // Registering rotates to a fresh chain; requests made against
+                // the current one can then only be revealed by whoever still
+                // holds its seed.
+                if let Some(provider) =
+                    on_chain.filter(|provider| provider.outstanding_requests > 0)
+                {
+                    warn!(
+                        provider = %payer.pubkey(),
+                        outstanding_requests = provider.outstanding_requests,
+                        "Rotating provider commitment with outstanding requests"
+                    );
+                    print_warn(format!(
+                        "{} outstanding request(s) were made against the previous commitment \
+                         of {} and will not be revealed by this keeper",
+                        provider.outstanding_requests,
+                        payer.pubkey()
+                    ));
+                }
+
+                print_info(format!("Registering provider {}...", payer.pubkey()));
+                let (hash_chain, provider) = register_chain(
+                    ctx.rpc_client,
+                    &payer,
+                    ctx.entropy_program_id,
+                    ctx.uri,
+                    ctx.https_only,
+                    ctx.commitment,
+                )?;
+                print_success("Provider registered");
+                // Chains that diverged are kept for requests made against them.
+                let chains = match ProviderChains::from_chains(retired) {
+                    Some(mut chains) => {
+                        chains.rotate(hash_chain);
+                        chains
+                    }
+                    None => ProviderChains::new(hash_chain),
+                };
+                (chains, provider, None)
+            }
+        };
+        if let Some(policy) = ctx.callback_skip_policy {
+            callback_skip::apply_policy(
+                ctx.rpc_client,
+                &payer,
+                ctx.entropy_program_id,
+                &mut provider,
+                policy,
+                ctx.commitment,
+            )?;
+        }
+        print_kv("authority:", payer.pubkey());
+        print_kv("provider account:", provider_account);
+        print_kv("outstanding requests:", provider.outstanding_requests);
+        if let Some(store) = &store {
+            store.save(chains.chains(), revealed_sequence)?;
+            print_kv("chain state:", store.path().display());
+        }
+        if let Some(lease) = &mut lease {
+            if !lease.is_held()
+                && !lease.acquire(ctx.rpc_client, &payer, ctx.commitment, ctx.shutdown)?
+            {
+                return Ok(None);
+            }
+            print_kv("keeper lease:", lease.lease_account());
+            print_kv("keeper id:", lease.keeper_id());
+        }
+
+        let scheduler = RevealScheduler::new(
+            ctx.reveal_order,
+            ctx.max_reveals_per_requester
+                .map(|max_reveals| RequesterLimit {
+                    max_reveals: max_reveals as usize,
+                    window: ctx.requester_window,
+                }),
+        );
+        Ok(Some(Self {
+            index,
+            payer,
+            provider_account,
+            provider,
+            chains,
+            scheduler,
+            stats,
+            failures: FailureWindow::default(),
+            last_alert_check: None,
+            store,
+            revealed_sequence,
+            lease,
+            lease_lost: false,
+        }))
+    }
+
+    pub(crate) fn authority(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    pub(crate) fn provider_account(&self) -> Pubkey {
+        self.provider_account
+    }
+
+    pub(crate) fn stats(&self) -> &KeeperStats {
+        &self.stats
+    }
+
+    /// Whether another keeper took over this provider's keeper lease.
+    pub(crate) fn lease_lost(&self) -> bool {
+        self.lease_lost
+    }
+
+    /// Handles a request made against this provider: callback requests are
+    /// queued, plain requests only logged.
+    pub(crate) fn observe(&mut self, ctx: &KeeperContext, observation: &RequestObservation) {
+        KeeperStats::record(&self.stats.observed);
+
+        let Ok(request_data) = ctx
+            .rpc_client
+            .get_account_data(&observation.request_account)
+        else {
+            return;
+        };
+        let request = match try_from_bytes::<Request>(&request_data) {
+            Ok(request) => *request,
+            Err(err) => {
+                warn!(
+                    request = %observation.request_account,
+                    error = %err,
+                    "Failed to parse request account"
+                );
+                return;
+            }
+        };
+
+        let pending = match observation.kind {
+            RequestKind::Request { .. } => request.status() == Ok(CallbackStatus::NotNecessary),
+            RequestKind::RequestWithCallback { .. } => request.awaits_callback(),
+        };
+        if !pending {
+            return;
+        }
+        if Pubkey::new_from_array(request.provider) != self.payer.pubkey() {
+            return;
+        }
+        match observation.kind {
+            RequestKind::RequestWithCallback { user_randomness } => {
+                self.queue(ctx, observation.request_account, request, user_randomness);
+            }
+            RequestKind::Request { user_commitment } => {
+                let Some(provider_contribution) = self
+                    .chains
+                    .contribution_for_sequence(request.sequence_number)
+                else {
+                    print_warn("Out of provider randomness. Re-register provider.");
+                    return;
+                };
+                // Plain requests are revealed by the requester, who alone
+                // knows the user contribution behind the commitment.
+                print_info(format!(
+                    "Request {} (sequence {}) awaits requester reveal",
+                    observation.request_account, request.sequence_number
+                ));
+                print_kv("user commitment:", Hash::new_from_array(user_commitment));
+                print_kv(
+                    "provider contribution:",
+                    Hash::new_from_array(provider_contribution),
+                );
+            }
+        }
+    }
+
+    /// Queues a pending callback request for reveal, unless it is queued
+    /// already.
+    pub(crate) fn queue(
+        &mut self,
+        ctx: &KeeperContext,
+        request_account: Pubkey,
+        request: Request,
+        user_randomness: [u8; 32],
+    ) {
+        if ctx.requests.track(
+            &self.payer.pubkey(),
+            &request_account,
+            request.sequence_number,
+            &user_randomness,
+        ) {
+            self.resume(ctx, request_account, request, user_randomness);
+        }
+    }
+
+    /// Queues a request the request store already tracks, e.g. one left
+    /// queued by a previous run.
+    pub(crate) fn resume(
+        &mut self,
+        ctx: &KeeperContext,
+        request_account: Pubkey,
+        request: Request,
+        user_randomness: [u8; 32],
+    ) {
+        let provider_fee = self
+            .provider
+            .calculate_provider_fee(request.compute_unit_limit)
+            .unwrap_or(self.provider.fee_lamports);
+        ctx.journal.record(JournalEvent::Observed {
+            request_account: request_account.to_string(),
+            sequence_number: request.sequence_number,
+            requester_program: Pubkey::new_from_array(request.requester_program_id).to_string(),
+            provider_fee,
+            user_randomness: bs58::encode(user_randomness).into_string(),
+        });
+        self.scheduler.push(PendingReveal {
+            request_account,
+            request,
+            user_randomness,
+            provider_fee,
+            deferred: false,
+        });
+    }
+
+    /// One polling round: acts on admin commands, reveals what is due,
+    /// sweeps fees and publishes a snapshot for the admin API.
+    pub(crate) fn round(&mut self, ctx: &KeeperContext) {
+        let admin = ctx.admin.provider(self.index);
+        if admin.take_rotate_request() {
+            self.rotate(ctx);
+        }
+
+        let lease_status = self
+            .lease
+            .as_mut()
+            .map(|lease| lease.maintain(ctx.rpc_client, &self.payer, ctx.commitment));
+        match lease_status {
+            Some(LeaseStatus::Lost) => {
+                print_error(format!(
+                    "Another keeper took over the keeper lease of {}",
+                    self.payer.pubkey()
+                ));
+                self.lease_lost = true;
+                return;
+            }
+            Some(LeaseStatus::Unconfirmed) => {
+                print_warn(format!(
+                    "Keeper lease of {} may have lapsed; holding reveals",
+                    self.payer.pubkey()
+                ));
+            }
+            _ => {}
+        }
+
+        // While paused, or while the lease is unconfirmed, requests are
+        // still observed and queued.
+        let (due, newly_deferred) =
+            if ctx.admin.is_paused() || lease_status == Some(LeaseStatus::Unconfirmed) {
+                (Vec::new(), 0)
+            } else {
+                self.scheduler.take_due(Instant::now())
+            };
+        self.stats
+            .deferred_rate_limited
+            .fetch_add(newly_deferred, Ordering::Relaxed);
+        let total_due = due.len();
+        for (index, reveal) in due.into_iter().enumerate() {
+            if !ctx.shutdown.is_running() {
+                self.stats
+                    .skipped_on_shutdown
+                    .fetch_add((total_due - index) as u64, Ordering::Relaxed);
+                break;
+            }
+            self.reveal(ctx, reveal);
+        }
+
+        if let Some(sweeper) = ctx.sweeper {
+            self.sweep(ctx, sweeper);
+        }
+        if let Some(alerter) = ctx.alerter {
+            self.check_alerts(ctx, alerter);
+        }
+
+        admin.publish(KeeperSnapshot {
+            provider_account: self.provider_account.to_string(),
+            chain_start_sequence: self.chains.current().start_sequence(),
+            chain_end_sequence: self.provider.end_sequence_number,
+            retired_chains: self.chains.retired(),
+            pending: self
+                .scheduler
+                .pending()
+                .iter()
+                .map(|reveal| PendingSummary {
+                    provider: self.payer.pubkey().to_string(),
+                    request_account: reveal.request_account.to_string(),
+                    sequence_number: reveal.request.sequence_number,
+                    requester_program: Pubkey::new_from_array(reveal.request.requester_program_id)
+                        .to_string(),
+                    provider_fee: reveal.provider_fee,
+                    deferred: reveal.deferred,
+                })
+                .collect(),
+        });
+    }
+
+    /// Counts the reveals still queued as skipped and releases the keeper
+    /// lease; called once on shutdown.
+    pub(crate) fn finish(&mut self, ctx: &KeeperContext) {
+        self.stats
+            .skipped_on_shutdown
+            .fetch_add(self.scheduler.len() as u64, Ordering::Relaxed);
+        if let Some(lease) = self.lease.as_mut().filter(|_| !self.lease_lost) {
+            lease.release(ctx.rpc_client, &self.payer, ctx.commitment);
+        }
+    }
+
+    fn rotate(&mut self, ctx: &KeeperContext) {
+        print_info(format!("Rotating hash chain of {}...", self.payer.pubkey()));
+        match register_chain(
+            ctx.rpc_client,
+            &self.payer,
+            ctx.entropy_program_id,
+            ctx.uri,
+            ctx.https_only,
+            ctx.commitment,
+        ) {
+            Ok((hash_chain, provider)) => {
+                self.chains.rotate(hash_chain);
+                self.provider = provider;
+                self.revealed_sequence = None;
+                self.persist_chains();
+                print_success(format!(
+                    "Hash chain rotated at sequence {}",
+                    self.chains.current().start_sequence()
+                ));
+            }
+            Err(err) => print_error(format!("Failed to rotate hash chain: {err}")),
+        }
+    }
+
+    fn reveal(&mut self, ctx: &KeeperContext, mut reveal: PendingReveal) {
+        // A deferred request may have been revealed or closed meanwhile.
+        if reveal.deferred {
+            let refreshed = ctx
+                .rpc_client
+                .get_account_data(&reveal.request_account)
+                .ok()
+                .and_then(|data| try_from_bytes::<Request>(&data).ok().copied());
+            match refreshed {
+                Some(request) if request.awaits_callback() => {
+                    reveal.request = request;
+                }
+                _ => {
+                    ctx.requests.finish(&reveal.request_account);
+                    ctx.journal.record(JournalEvent::skipped(
+                        &reveal.request_account,
+                        reveal.request.sequence_number,
+                        "closed",
+                    ));
+                    return;
+                }
+            }
+        }
+        let request = &reveal.request;
+
+        let Some(provider_contribution) = self
+            .chains
+            .contribution_for_sequence(request.sequence_number)
+        else {
+            print_warn("Out of provider randomness. Re-register provider.");
+            ctx.requests.finish(&reveal.request_account);
+            ctx.journal.record(JournalEvent::skipped(
+                &reveal.request_account,
+                request.sequence_number,
+                "no provider contribution",
+            ));
+            return;
+        };
+        let reveal_args = RevealArgs {
+            user_contribution: reveal.user_randomness,
+            provider_contribution,
+        };
+
+        let mut reveal_ix = match reveal_instruction(
+            ctx.rpc_client,
+            ctx.entropy_program_id,
+            reveal.request_account,
+            request,
+            reveal_args,
+        ) {
+            Ok(reveal_ix) => reveal_ix,
+            Err(err) => {
+                ctx.journal.record(JournalEvent::RevealFailed {
+                    request_account: reveal.request_account.to_string(),
+                    sequence_number: request.sequence_number,
+                    error: format!("{err:#}"),
+                });
+                print_error(format!("Failed to reveal: {err:#}"));
+                return;
+            }
+        };
+
+        // A callback failure is only visible in the simulated reveal's logs,
+        // so with a skip policy every reveal is simulated before it is sent.
+        let mut skipping_callback = false;
+        if self.provider.callback_skip_max_failures != 0 {
+            match callback_skip::check_callback(
+                ctx.rpc_client,
+                &self.payer,
+                ctx.entropy_program_id,
+                &self.provider,
+                reveal.request_account,
+                request,
+                reveal_args,
+                &reveal_ix,
+                ctx.commitment,
+            ) {
+                Ok(CallbackCheck::Passes) => {}
+                Ok(CallbackCheck::Skip(skip_ix)) => {
+                    print_warn(format!(
+                        "Callbacks of {} keep failing; revealing request {} without its callback",
+                        Pubkey::new_from_array(request.requester_program_id),
+                        reveal.request_account
+                    ));
+                    reveal_ix = skip_ix;
+                    skipping_callback = true;
+                }
+                Ok(CallbackCheck::Fails(failure)) => {
+                    KeeperStats::record(&self.stats.failed);
+                    self.failures.record(Instant::now(), true);
+                    ctx.journal.record(JournalEvent::RevealFailed {
+                        request_account: reveal.request_account.to_string(),
+                        sequence_number: request.sequence_number,
+                        error: format!("callback failed: {}", failure.error),
+                    });
+                    print_error(format!(
+                        "Callback of request {} failed: {}",
+                        reveal.request_account, failure.error
+                    ));
+                    return;
+                }
+                // The reveal itself reports any real failure.
+                Err(err) => {
+                    warn!(
+                        request = %reveal.request_account,
+                        error = %err,
+                        "Failed to check reveal callback"
+                    );
+                }
+            }
+        }
+
+        // Skipping refunds the provider fee, so there is no margin to check.
+        if !skipping_callback {
+            match profit::estimate_reveal(
+                ctx.rpc_client,
+                &self.payer.pubkey(),
+                std::slice::from_ref(&reveal_ix),
+                reveal.provider_fee,
+                ctx.submitter.tip_lamports(),
+                ctx.commitment,
+            ) {
+                Ok(estimate) => {
+                    info!(
+                        request = %reveal.request_account,
+                        compute_units = estimate.compute_units,
+                        cost = estimate.cost(),
+                        provider_fee = estimate.provider_fee,
+                        margin = estimate.margin(),
+                        "Reveal estimate"
+                    );
+                    if !estimate.is_profitable() {
+                        print_warn(format!(
+                            "Reveal for request {} costs ~{} lamports but earned {} lamports",
+                            reveal.request_account,
+                            estimate.cost(),
+                            estimate.provider_fee
+                        ));
+                        if ctx.only_profitable {
+                            KeeperStats::record(&self.stats.skipped_unprofitable);
+                            ctx.requests.finish(&reveal.request_account);
+                            ctx.journal.record(JournalEvent::skipped(
+                                &reveal.request_account,
+                                request.sequence_number,
+                                "unprofitable",
+                            ));
+                            return;
+                        }
+                    }
+                }
+                // An estimate is advisory; the reveal itself reports any real failure.
+                Err(err) => {
+                    warn!(
+                        request = %reveal.request_account,
+                        error = %err,
+                        "Failed to estimate reveal cost"
+                    );
+                }
+            }
+        }
+
+        print_info(format!(
+            "Revealing for request {} (sequence {})",
+            reveal.request_account, request.sequence_number
+        ));
+
+        ctx.journal.record(JournalEvent::RevealAttempt {
+            request_account: reveal.request_account.to_string(),
+            sequence_number: request.sequence_number,
+        });
+        match ctx
+            .submitter
+            .submit(ctx.rpc_client, &self.payer, &[reveal_ix], ctx.commitment)
+        {
+            Ok(signature) => {
+                KeeperStats::record(&self.stats.revealed);
+                self.failures.record(Instant::now(), false);
+                // Only reveals from the current chain move its on-chain head.
+                if self
+                    .chains
+                    .current()
+                    .contribution_for_sequence(request.sequence_number)
+                    .is_some()
+                    && self
+                        .revealed_sequence
+                        .is_none_or(|revealed| request.sequence_number > revealed)
+                {
+                    self.revealed_sequence = Some(request.sequence_number);
+                    self.persist_chains();
+                }
+                ctx.requests.finish(&reveal.request_account);
+                ctx.journal.record(JournalEvent::Revealed {
+                    request_account: reveal.request_account.to_string(),
+                    sequence_number: request.sequence_number,
+                    signature: signature.to_string(),
+                });
+                println!("Successful reveal!: {signature}");
+            }
+            Err(err) => {
+                KeeperStats::record(&self.stats.failed);
+                self.failures.record(Instant::now(), true);
+                ctx.journal.record(JournalEvent::RevealFailed {
+                    request_account: reveal.request_account.to_string(),
+                    sequence_number: request.sequence_number,
+                    error: format!("{err:#}"),
+                });
+                print_error(format!("Failed to reveal: {err}"));
+            }
+        }
+    }
+
+    /// A failed write is only logged; the next reveal or rotation retries it.
+    fn persist_chains(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Err(err) = store.save(self.chains.chains(), self.revealed_sequence) {
+            warn!(error = %err, "Failed to persist chain state");
+            print_warn(format!("Failed to persist chain state: {err:#}"));
+        }
+    }
+
+    fn check_alerts(&mut self, ctx: &KeeperContext, alerter: &Alerter) {
+        let authority = self.payer.pubkey();
+        let thresholds = alerter.thresholds;
+        let now = Instant::now();
+        match self.failures.failure_rate(now, thresholds.failure_window) {
+            Some(rate) if rate > thresholds.failure_rate => alerter.raise(
+                AlertKind::RevealFailures,
+                Some(authority),
+                &format!(
+                    "{:.0}% of reveals by {authority} failed in the last {:?}",
+                    rate * 100.0,
+                    thresholds.failure_window
+                ),
+            ),
+            _ => alerter.resolve(
+                AlertKind::RevealFailures,
+                Some(authority),
+                &format!("Reveals by {authority} are succeeding again"),
+            ),
+        }
+
+        if self
+            .last_alert_check
+            .is_some_and(|checked| now.duration_since(checked) < ALERT_CHECK_INTERVAL)
+        {
+            return;
+        }
+        self.last_alert_check = Some(now);
+
+        // An unreachable RPC node is alerted on by the polling loop.
+        let (provider_vault, _) = provider_vault_pda(&ctx.entropy_program_id, &authority);
+        let accounts = ctx
+            .rpc_client
+            .get_multiple_accounts(&[self.provider_account, provider_vault]);
+        let rent_floor = ctx.rpc_client.get_minimum_balance_for_rent_exemption(0);
+        let (Ok(accounts), Ok(rent_floor)) = (accounts, rent_floor) else {
+            warn!(provider = %authority, "Failed to fetch accounts for alert checks");
+            return;
+        };
+
+        if let Some(provider) = accounts[0]
+            .as_ref()
+            .and_then(|account| try_from_bytes::<Provider>(&account.data).ok())
+        {
+            let remaining = provider
+                .end_sequence_number
+                .saturating_sub(provider.sequence_number);
+            if remaining <= thresholds.chain_remaining {
+                alerter.raise(
+                    AlertKind::ChainNearlyExhausted,
+                    Some(authority),
+                    &format!(
+                        "Hash chain of {authority} has {remaining} sequence number(s) left; \
+                         rotate it before requests start failing"
+                    ),
+                );
+            } else {
+                alerter.resolve(
+                    AlertKind::ChainNearlyExhausted,
+                    Some(authority),
+                    &format!("Hash chain of {authority} has {remaining} sequence numbers left"),
+                );
+            }
+        }
+
+        let vault_balance = accounts[1].as_ref().map_or(0, |account| account.lamports);
+        if vault_balance < rent_floor {
+            alerter.raise(
+                AlertKind::VaultBelowRent,
+                Some(authority),
+                &format!(
+                    "Provider vault {provider_vault} of {authority} holds {vault_balance} \
+                     lamports, below its rent-exempt minimum of {rent_floor}"
+                ),
+            );
+        } else {
+            alerter.resolve(
+                AlertKind::VaultBelowRent,
+                Some(authority),
+                &format!("Provider vault {provider_vault} of {authority} is rent exempt again"),
+            );
+        }
+    }
+
+    fn sweep(&self, ctx: &KeeperContext, sweeper: &FeeSweeper) {
+        match sweeper.sweep(
+            ctx.rpc_client,
+            &self.payer,
+            ctx.entropy_program_id,
+            ctx.commitment,
+        ) {
+            Ok(Some(sweep)) => {
+                self.stats
+                    .swept_lamports
+                    .fetch_add(sweep.lamports, Ordering::Relaxed);
+                print_success(format!(
+                    "Swept {} lamports to {}: {}",
+                    sweep.lamports, sweeper.destination, sweep.signature
+                ));
+            }
+            Ok(None) => {}
+            // The fees stay in the vault; the next round tries again.
+            Err(err) => {
+                warn!(error = %err, "Failed to sweep provider fees");
+                print_warn(format!("Failed to sweep provider fees: {err}"));
+            }
+        }
+    }
+}
+
+/// Builds the reveal of `request`, reading its callback account list if it
+/// has one.
+fn reveal_instruction(
+    rpc_client: &RpcClient,
+    entropy_program_id: Pubkey,
+    request_account: Pubkey,
+    request: &Request,
+    args: RevealArgs,
+) -> Result<Instruction> {
+    if !request.has_callback_account_list() {
+        return Ok(reveal_with_callback(
+            entropy_program_id,
+            request_account,
+            request,
+            args,
+        ));
+    }
+    let list_account = Pubkey::new_from_array(request.callback_account_list);
+    let list_data = rpc_client
+        .get_account_data(&list_account)
+        .with_context(|| format!("Failed to fetch callback account list {list_account}"))?;
+    let list = try_from_bytes::<CallbackAccountList>(&list_data)
+        .map_err(|err| anyhow::anyhow!("Failed to parse callback account list: {err}"))?;
+    Ok(reveal_with_callback_account_list(
+        entropy_program_id,
+        request_account,
+        request,
+        list,
+        args,
+    ))
+}
@@ -0,0 +1,76 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Seek, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use fs2::FileExt;
+
+use crate::{print_info, print_success, shutdown::Shutdown};
+
+/// How often a standby keeper retries the lock.
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Exclusive lock making one keeper the leader among instances sharing the
+/// lock file, e.g. redundant keepers on one host or on a shared volume. The
+/// operating system releases it when the leader exits, however it exits, so
+/// a standby takes over without a stale lock to clean up.
+pub(crate) struct LeaderLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl LeaderLock {
+    /// Blocks until this process holds the lock at `path`. Returns `None` if
+    /// shutdown is requested while standing by.
+    pub(crate) fn acquire(path: &Path, shutdown: &Shutdown) -> Result<Option<Self>> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)
+            .with_context(|| format!("Failed to open leader lock {}", path.display()))?;
+
+        let mut waiting = false;
+        while file.try_lock_exclusive().is_err() {
+            if !waiting {
+                let holder = fs::read_to_string(path).unwrap_or_default();
+                print_info(format!(
+                    "Standing by: leader lock {} is held{}",
+                    path.display(),
+                    match holder.trim() {
+                        "" => String::new(),
+                        holder => format!(" by {holder}"),
+                    }
+                ));
+                waiting = true;
+            }
+            shutdown.sleep(LOCK_RETRY_INTERVAL);
+            if !shutdown.is_running() {
+                return Ok(None);
+            }
+        }
+
+        // Recorded for standbys to report; the lock itself is what counts.
+        file.set_len(0)?;
+        file.rewind()?;
+        writeln!(file, "pid {}", std::process::id())?;
+        print_success(format!("Acquired leader lock {}", path.display()));
+        Ok(Some(Self {
+            file,
+            path: path.to_path_buf(),
+        }))
+    }
+}
+
+impl Drop for LeaderLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        if FileExt::unlock(&self.file).is_ok() {
+            print_info(format!("Released leader lock {}", self.path.display()));
+        }
+    }
+}
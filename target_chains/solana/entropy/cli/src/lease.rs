@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use bytemuck::try_from_bytes;
+use entropy::{accounts::KeeperLease, pda::keeper_lease_pda};
+use entropy_sdk::instruction::refresh_keeper_lease;
+use rand::{rngs::OsRng, RngCore};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair, signer::Signer,
+};
+use tracing::warn;
+
+use crate::{print_info, print_success, print_warn, send_and_confirm, shutdown::Shutdown};
+
+/// How often a standby keeper checks whether the lease went stale.
+const LEASE_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Whether this keeper may reveal for the provider.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LeaseStatus {
+    /// The lease is ours until at least the slot it was last confirmed for.
+    Held,
+    /// The lease could not be refreshed or read and may have lapsed.
+    Unconfirmed,
+    /// Another keeper took the lease over.
+    Lost,
+}
+
+/// This keeper's claim on a provider's on-chain [`KeeperLease`]. Unlike the
+/// leader lock it works across hosts, and anyone can read the lease account
+/// to tell whether the provider's keeper is alive.
+pub(crate) struct LeaseHolder {
+    program_id: Pubkey,
+    lease_account: Pubkey,
+    /// Random per process, so a restarted keeper waits out its own lease
+    /// like any standby instead of racing a keeper that took over.
+    keeper: [u8; 32],
+    lease_slots: u64,
+    /// Last slot the lease is known to be ours through.
+    held_through: Option<u64>,
+}
+
+impl LeaseHolder {
+    pub(crate) fn new(program_id: Pubkey, provider_authority: &Pubkey, lease_slots: u64) -> Self {
+        let (lease_account, _) = keeper_lease_pda(&program_id, provider_authority);
+        let mut keeper = [0u8; 32];
+        OsRng.fill_bytes(&mut keeper);
+        Self {
+            program_id,
+            lease_account,
+            keeper,
+            lease_slots,
+            held_through: None,
+        }
+    }
+
+    pub(crate) fn lease_account(&self) -> Pubkey {
+        self.lease_account
+    }
+
+    pub(crate) fn is_held(&self) -> bool {
+        self.held_through.is_some()
+    }
+
+    pub(crate) fn keeper_id(&self) -> String {
+        bs58::encode(self.keeper).into_string()
+    }
+
+    /// Blocks until this keeper holds the lease. Returns `false` if shutdown
+    /// is requested while standing by.
+    pub(crate) fn acquire(
+        &mut self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        commitment: CommitmentConfig,
+        shutdown: &Shutdown,
+    ) -> Result<bool> {
+        let mut waiting = false;
+        loop {
+            let slot = rpc_client.get_slot()?;
+            match fetch(rpc_client, &self.lease_account)? {
+                Some(lease) if lease.keeper != self.keeper && !lease.is_stale(slot) => {
+                    if !waiting {
+                        print_info(format!(
+                            "Standing by: keeper lease {} is held by {} through slot {}",
+                            self.lease_account,
+                            bs58::encode(lease.keeper).into_string(),
+                            lease.refreshed_slot.saturating_add(lease.lease_slots)
+                        ));
+                        waiting = true;
+                    }
+                }
+                _ => match self.refresh(rpc_client, payer, commitment, slot) {
+                    Ok(()) => {
+                        print_success(format!("Acquired keeper lease {}", self.lease_account));
+                        return Ok(true);
+                    }
+                    // Most likely another standby claimed it first.
+                    Err(err) => warn!(error = %err, "Failed to claim keeper lease"),
+                },
+            }
+            shutdown.sleep(LEASE_RETRY_INTERVAL);
+            if !shutdown.is_running() {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Refreshes the lease once half of it has elapsed. Called every round.
+    pub(crate) fn maintain(
+        &mut self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        commitment: CommitmentConfig,
+    ) -> LeaseStatus {
+        let Ok(slot) = rpc_client.get_slot() else {
+            return LeaseStatus::Unconfirmed;
+        };
+        let Some(held_through) = self.held_through else {
+            return LeaseStatus::Unconfirmed;
+        };
+        if slot.saturating_add(self.lease_slots / 2) < held_through {
+            return LeaseStatus::Held;
+        }
+
+        match self.refresh(rpc_client, payer, commitment, slot) {
+            Ok(()) => LeaseStatus::Held,
+            Err(err) => {
+                warn!(error = %err, "Failed to refresh keeper lease");
+                match fetch(rpc_client, &self.lease_account) {
+                    Ok(Some(lease)) if lease.keeper != self.keeper => LeaseStatus::Lost,
+                    _ if slot <= held_through => LeaseStatus::Held,
+                    _ => LeaseStatus::Unconfirmed,
+                }
+            }
+        }
+    }
+
+    /// Lets a standby take over from the next slot instead of waiting for
+    /// the lease to run out. Failures are only logged.
+    pub(crate) fn release(
+        &mut self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        commitment: CommitmentConfig,
+    ) {
+        if self.held_through.take().is_none() {
+            return;
+        }
+        let ix = refresh_keeper_lease(self.program_id, payer.pubkey(), self.keeper, 0);
+        match send_and_confirm(rpc_client, payer, &[ix], commitment) {
+            Ok(_) => print_info(format!("Released keeper lease {}", self.lease_account)),
+            Err(err) => {
+                warn!(error = %err, "Failed to release keeper lease");
+                print_warn(format!("Failed to release keeper lease: {err}"));
+            }
+        }
+    }
+
+    fn refresh(
+        &mut self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        commitment: CommitmentConfig,
+        slot: u64,
+    ) -> Result<()> {
+        let ix = refresh_keeper_lease(
+            self.program_id,
+            payer.pubkey(),
+            self.keeper,
+            self.lease_slots,
+        );
+        send_and_confirm(rpc_client, payer, &[ix], commitment)?;
+        // The refresh landed at `slot` or later, so this errs on the short side.
+        self.held_through = Some(slot.saturating_add(self.lease_slots));
+        Ok(())
+    }
+}
+
+/// The lease at `lease_account`, or `None` before its first refresh.
+fn fetch(rpc_client: &RpcClient, lease_account: &Pubkey) -> Result<Option<KeeperLease>> {
+    let account = rpc_client
+        .get_account_with_commitment(lease_account, rpc_client.commitment())?
+        .value;
+    Ok(account.and_then(|account| try_from_bytes::<KeeperLease>(&account.data).ok().copied()))
+}
@@ -0,0 +1,299 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use entropy::pda::{
+    config_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda, requester_signer_pda,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
+    signature::Keypair, signer::Signer,
+};
+
+use crate::{
+    load_keypair, print_info, print_kv, print_success, print_warn, register_chain, send_and_confirm,
+};
+
+/// Where `cargo build-sbf` leaves the workspace programs.
+pub(crate) const DEFAULT_ARTIFACTS_DIR: &str = "target/deploy";
+/// `solana-test-validator`'s own default ledger directory.
+pub(crate) const DEFAULT_LEDGER_DIR: &str = "test-ledger";
+const VALIDATOR_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+const VALIDATOR_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// The payer is topped up to cover deployments, rent and reveals.
+const MIN_PAYER_BALANCE: u64 = 10 * LAMPORTS_PER_SOL;
+const AIRDROP_LAMPORTS: u64 = 100 * LAMPORTS_PER_SOL;
+
+pub(crate) struct LocalnetConfig {
+    pub(crate) rpc_url: String,
+    pub(crate) keypair_path: PathBuf,
+    pub(crate) artifacts_dir: PathBuf,
+    pub(crate) ledger_dir: PathBuf,
+    pub(crate) pyth_fee_lamports: u64,
+    pub(crate) commitment: CommitmentConfig,
+}
+
+/// A built program: its shared object and the keypair fixing its address.
+struct ProgramArtifact {
+    name: &'static str,
+    so_path: PathBuf,
+    keypair_path: PathBuf,
+    program_id: Pubkey,
+}
+
+impl ProgramArtifact {
+    fn load(artifacts_dir: &Path, name: &'static str) -> Result<Self> {
+        let so_path = artifacts_dir.join(format!("{name}.so"));
+        let keypair_path = artifacts_dir.join(format!("{name}-keypair.json"));
+        if !so_path.exists() {
+            anyhow::bail!(
+                "{} not found; build the programs with `cargo build-sbf` first",
+                so_path.display()
+            );
+        }
+        let program_id = load_keypair(&keypair_path)?.pubkey();
+        Ok(Self {
+            name,
+            so_path,
+            keypair_path,
+            program_id,
+        })
+    }
+}
+
+/// Brings up a local validator with entropy and the simple requester
+/// deployed, an initialized config and a registered dev provider. An already
+/// running validator at `rpc_url` is reused; programs missing from it are
+/// deployed with the `solana` CLI.
+pub(crate) fn setup(config: LocalnetConfig) -> Result<()> {
+    let payer = load_keypair(&config.keypair_path)?;
+    let entropy = ProgramArtifact::load(&config.artifacts_dir, "entropy")?;
+    let requester = ProgramArtifact::load(&config.artifacts_dir, "simple_requester")?;
+    let rpc_client = RpcClient::new_with_commitment(config.rpc_url.clone(), config.commitment);
+
+    if rpc_client.get_health().is_ok() {
+        print_info(format!("Attaching to validator at {}", config.rpc_url));
+        for program in [&entropy, &requester] {
+            deploy_if_missing(&rpc_client, &config, program)?;
+        }
+    } else {
+        let mut validator = start_validator(&config, &payer.pubkey(), &[&entropy, &requester])?;
+        wait_for_validator(&rpc_client, &mut validator, &config.ledger_dir)?;
+        print_success(format!("Validator running (pid {})", validator.id()));
+    }
+
+    fund(&rpc_client, &payer, config.commitment)?;
+
+    let entropy_program_id = entropy.program_id;
+    let (config_account, _) = config_pda(&entropy_program_id);
+    print_info("Initializing entropy config...");
+    let ix = entropy_sdk::instruction::initialize_if_needed(
+        entropy_program_id,
+        payer.pubkey(),
+        payer.pubkey(),
+        payer.pubkey(),
+        config.pyth_fee_lamports,
+    );
+    send_and_confirm(&rpc_client, &payer, &[ix], config.commitment)
+        .context("Failed to initialize entropy config (an existing one must match)")?;
+    print_success("Entropy config ready");
+
+    print_info("Registering dev provider...");
+    register_chain(
+        &rpc_client,
+        &payer,
+        entropy_program_id,
+        "",
+        false,
+        config.commitment,
+    )?;
+    print_success("Dev provider registered");
+
+    print_info("Addresses");
+    print_kv("entropy program:", entropy_program_id);
+    print_kv("simple requester program:", requester.program_id);
+    print_kv("admin / provider authority:", payer.pubkey());
+    print_kv("config:", config_account);
+    print_kv("pyth fee vault:", pyth_fee_vault_pda(&entropy_program_id).0);
+    print_kv(
+        "provider account:",
+        provider_pda(&entropy_program_id, &payer.pubkey()).0,
+    );
+    print_kv(
+        "provider vault:",
+        provider_vault_pda(&entropy_program_id, &payer.pubkey()).0,
+    );
+    print_kv(
+        "requester signer:",
+        requester_signer_pda(&requester.program_id, &entropy_program_id).0,
+    );
+
+    print_info("Environment");
+    println!("export SOLANA_RPC_URL={}", config.rpc_url);
+    println!("export SOLANA_KEYPAIR={}", config.keypair_path.display());
+    println!("export ENTROPY_PROGRAM_ID={entropy_program_id}");
+    println!(
+        "export SIMPLE_REQUESTER_PROGRAM_ID={}",
+        requester.program_id
+    );
+    // The setup chain's seed is not kept; the keeper rotates to its own.
+    print_info("Run `entropy provide` to start a keeper for the dev provider");
+    Ok(())
+}
+
+fn start_validator(
+    config: &LocalnetConfig,
+    upgrade_authority: &Pubkey,
+    programs: &[&ProgramArtifact],
+) -> Result<Child> {
+    if !is_local_url(&config.rpc_url) {
+        anyhow::bail!(
+            "No validator is responding at {} and only a local one can be started",
+            config.rpc_url
+        );
+    }
+    let rpc_port = rpc_port(&config.rpc_url).unwrap_or(8899);
+    let log_path = config.ledger_dir.with_extension("log");
+    if let Some(parent) = log_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let log = File::create(&log_path)
+        .with_context(|| format!("Failed to create {}", log_path.display()))?;
+
+    print_info("Starting solana-test-validator...");
+    print_kv("ledger:", config.ledger_dir.display());
+    print_kv("log:", log_path.display());
+    let mut command = Command::new("solana-test-validator");
+    command
+        .arg("--reset")
+        .arg("--ledger")
+        .arg(&config.ledger_dir)
+        .arg("--rpc-port")
+        .arg(rpc_port.to_string());
+    // Preloaded as upgradeable so the payer is the upgrade authority that
+    // `Initialize` requires.
+    for program in programs {
+        command
+            .arg("--upgradeable-program")
+            .arg(program.program_id.to_string())
+            .arg(&program.so_path)
+            .arg(upgrade_authority.to_string());
+    }
+    command
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .spawn()
+        .context("Failed to start solana-test-validator; is the Solana CLI installed?")
+}
+
+fn wait_for_validator(
+    rpc_client: &RpcClient,
+    validator: &mut Child,
+    ledger_dir: &Path,
+) -> Result<()> {
+    let deadline = Instant::now() + VALIDATOR_STARTUP_TIMEOUT;
+    while rpc_client.get_health().is_err() {
+        if let Some(status) = validator.try_wait()? {
+            anyhow::bail!(
+                "solana-test-validator exited with {status}; see {}",
+                ledger_dir.with_extension("log").display()
+            );
+        }
+        if Instant::now() >= deadline {
+            anyhow::bail!(
+                "solana-test-validator did not become healthy within {VALIDATOR_STARTUP_TIMEOUT:?}"
+            );
+        }
+        thread::sleep(VALIDATOR_POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+fn deploy_if_missing(
+    rpc_client: &RpcClient,
+    config: &LocalnetConfig,
+    program: &ProgramArtifact,
+) -> Result<()> {
+    if rpc_client
+        .get_account(&program.program_id)
+        .is_ok_and(|account| account.executable)
+    {
+        print_info(format!(
+            "{} already deployed at {}",
+            program.name, program.program_id
+        ));
+        return Ok(());
+    }
+
+    print_info(format!(
+        "Deploying {} to {}...",
+        program.name, program.program_id
+    ));
+    let status = Command::new("solana")
+        .arg("program")
+        .arg("deploy")
+        .arg("--url")
+        .arg(&config.rpc_url)
+        .arg("--keypair")
+        .arg(&config.keypair_path)
+        .arg("--program-id")
+        .arg(&program.keypair_path)
+        .arg(&program.so_path)
+        .status()
+        .context("Failed to run `solana program deploy`; is the Solana CLI installed?")?;
+    if !status.success() {
+        anyhow::bail!("Deploying {} failed with {status}", program.name);
+    }
+    print_success(format!("{} deployed", program.name));
+    Ok(())
+}
+
+fn fund(rpc_client: &RpcClient, payer: &Keypair, commitment: CommitmentConfig) -> Result<()> {
+    let balance = rpc_client
+        .get_balance(&payer.pubkey())
+        .context("Failed to fetch payer balance")?;
+    if balance >= MIN_PAYER_BALANCE {
+        return Ok(());
+    }
+    print_info(format!(
+        "Airdropping {AIRDROP_LAMPORTS} lamports to {}",
+        payer.pubkey()
+    ));
+    let signature = rpc_client
+        .request_airdrop(&payer.pubkey(), AIRDROP_LAMPORTS)
+        .context("Airdrop failed")?;
+    if let Err(err) = rpc_client.poll_for_signature_with_commitment(&signature, commitment) {
+        print_warn(format!("Airdrop {signature} not confirmed: {err}"));
+    }
+    Ok(())
+}
+
+fn is_local_url(url: &str) -> bool {
+    let host = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', ':'])
+        .next()
+        .unwrap_or_default();
+    matches!(host, "localhost" | "127.0.0.1" | "0.0.0.0")
+}
+
+fn rpc_port(url: &str) -> Option<u16> {
+    let authority = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split('/')
+        .next()?;
+    authority.rsplit_once(':')?.1.parse().ok()
+}
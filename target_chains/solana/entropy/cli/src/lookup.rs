@@ -0,0 +1,355 @@
+use std::{collections::HashMap, fmt, sync::Mutex, thread::sleep, time::Duration};
+
+use anyhow::{Context, Result};
+use entropy_sdk::provider_lookup_addresses;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    message::VersionedMessage,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+use tracing::info;
+
+#[allow(deprecated)]
+use solana_sdk::address_lookup_table::{
+    instruction::{create_lookup_table, extend_lookup_table},
+    state::{AddressLookupTable, LOOKUP_TABLE_MAX_ADDRESSES},
+    AddressLookupTableAccount,
+};
+
+use crate::{print_info, print_kv, print_success, print_warn, send_and_confirm};
+
+/// Accounts a transaction may lock until the cluster raises the limit.
+pub(crate) const MAX_TX_ACCOUNT_LOCKS: usize = 64;
+
+/// Addresses added per extend transaction, which keeps it within a packet.
+const EXTEND_BATCH: usize = 20;
+
+const SLOT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Reveals an account must appear in before it earns a table entry.
+const FREQUENT_USES: u32 = 3;
+
+/// Accounts whose uses are counted; past it the once-used ones are dropped.
+const MAX_TRACKED_ACCOUNTS: usize = 4096;
+
+/// What a transaction takes of the limits every transaction must fit.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Footprint {
+    pub(crate) size: usize,
+    pub(crate) accounts: usize,
+}
+
+impl Footprint {
+    pub(crate) fn of(message: &VersionedMessage) -> Self {
+        let signatures = usize::from(message.header().num_required_signatures);
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default(); signatures],
+            message: message.clone(),
+        };
+        let loaded = match message {
+            VersionedMessage::Legacy(_) => 0,
+            VersionedMessage::V0(message) => message
+                .address_table_lookups
+                .iter()
+                .map(|lookup| lookup.writable_indexes.len() + lookup.readonly_indexes.len())
+                .sum(),
+        };
+        Self {
+            size: bincode::serialized_size(&transaction).map_or(usize::MAX, |size| size as usize),
+            accounts: message.static_account_keys().len() + loaded,
+        }
+    }
+
+    pub(crate) fn fits(&self) -> bool {
+        self.size <= PACKET_DATA_SIZE && self.accounts <= MAX_TX_ACCOUNT_LOCKS
+    }
+
+    /// Lookup tables shrink the size, but every account still counts.
+    pub(crate) fn lookup_can_help(&self) -> bool {
+        self.accounts <= MAX_TX_ACCOUNT_LOCKS
+    }
+}
+
+impl fmt::Display for Footprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {PACKET_DATA_SIZE} bytes, {} of {MAX_TX_ACCOUNT_LOCKS} accounts",
+            self.size, self.accounts
+        )
+    }
+}
+
+/// Address lookup tables for transactions too large to send as legacy
+/// ones, one per payer, which is also the table's authority. A payer's
+/// table is the configured one if the payer owns it, else one created on
+/// first need. Whenever it is extended it also takes the entropy accounts
+/// of every reveal for the payer and the accounts reveals use often.
+pub(crate) struct LookupTables {
+    entropy_program_id: Pubkey,
+    configured: Option<Pubkey>,
+    by_payer: Mutex<HashMap<Pubkey, Pubkey>>,
+    uses: Mutex<HashMap<Pubkey, u32>>,
+}
+
+impl LookupTables {
+    pub(crate) fn new(entropy_program_id: Pubkey, configured: Option<Pubkey>) -> Self {
+        Self {
+            entropy_program_id,
+            configured,
+            by_payer: Mutex::new(HashMap::new()),
+            uses: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts the loadable accounts of a transaction about to be sent.
+    pub(crate) fn record_uses(&self, instructions: &[Instruction]) {
+        let Ok(mut uses) = self.uses.lock() else {
+            return;
+        };
+        if uses.len() > MAX_TRACKED_ACCOUNTS {
+            uses.retain(|_, count| *count > 1);
+        }
+        for key in loadable_accounts(instructions) {
+            *uses.entry(key).or_default() += 1;
+        }
+    }
+
+    /// A table of `payer`'s holding every loadable account of
+    /// `instructions`.
+    pub(crate) fn covering(
+        &self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        instructions: &[Instruction],
+        commitment: CommitmentConfig,
+    ) -> Result<AddressLookupTableAccount> {
+        let wanted = loadable_accounts(instructions);
+
+        let mut by_payer = self
+            .by_payer
+            .lock()
+            .map_err(|_| anyhow::anyhow!("lookup table lock poisoned"))?;
+        let mut address = match by_payer.get(&payer.pubkey()) {
+            Some(address) => *address,
+            None => {
+                let address = self.initial_table(rpc_client, payer, commitment)?;
+                by_payer.insert(payer.pubkey(), address);
+                address
+            }
+        };
+
+        let mut table = fetch_table(rpc_client, &address)?;
+        if wanted.iter().all(|key| table.addresses.contains(key)) {
+            return Ok(table);
+        }
+        let mut required = wanted;
+        for key in provider_lookup_addresses(self.entropy_program_id, payer.pubkey()) {
+            if !required.contains(&key) {
+                required.push(key);
+            }
+        }
+        if missing(&table, &required).len() > LOOKUP_TABLE_MAX_ADDRESSES - table.addresses.len() {
+            print_warn(format!("Lookup table {address} is full; creating another"));
+            address = create_table(rpc_client, payer, commitment)?;
+            by_payer.insert(payer.pubkey(), address);
+            table = AddressLookupTableAccount {
+                key: address,
+                addresses: Vec::new(),
+            };
+        }
+        let mut additions = missing(&table, &required);
+        let room = LOOKUP_TABLE_MAX_ADDRESSES - table.addresses.len() - additions.len();
+        let frequent: Vec<Pubkey> = self
+            .frequent_accounts()
+            .into_iter()
+            .filter(|key| !table.addresses.contains(key) && !additions.contains(key))
+            .take(room)
+            .collect();
+        additions.extend(frequent);
+
+        extend_table(rpc_client, payer, address, &additions, commitment)?;
+        fetch_table(rpc_client, &address)
+    }
+
+    /// The configured table if `payer` can extend it, else a new one.
+    fn initial_table(
+        &self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        commitment: CommitmentConfig,
+    ) -> Result<Pubkey> {
+        if let Some(configured) = self.configured {
+            let data = rpc_client
+                .get_account_data(&configured)
+                .with_context(|| format!("Failed to fetch lookup table {configured}"))?;
+            let table = AddressLookupTable::deserialize(&data)
+                .map_err(|err| anyhow::anyhow!("Invalid lookup table {configured}: {err}"))?;
+            if table.meta.authority == Some(payer.pubkey()) {
+                return Ok(configured);
+            }
+            print_warn(format!(
+                "Lookup table {configured} is not extendable by {}; creating one it owns",
+                payer.pubkey()
+            ));
+        }
+        create_table(rpc_client, payer, commitment)
+    }
+
+    /// Most used first.
+    fn frequent_accounts(&self) -> Vec<Pubkey> {
+        let Ok(uses) = self.uses.lock() else {
+            return Vec::new();
+        };
+        let mut frequent: Vec<(Pubkey, u32)> = uses
+            .iter()
+            .filter(|(_, count)| **count >= FREQUENT_USES)
+            .map(|(key, count)| (*key, *count))
+            .collect();
+        frequent.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        frequent.into_iter().map(|(key, _)| key).collect()
+    }
+}
+
+/// Creates a lookup table for `payer`'s reveals, or extends `existing`,
+/// with `addresses` it lacks. A dry run only lists them.
+pub(crate) fn maintain_table(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    existing: Option<Pubkey>,
+    addresses: &[Pubkey],
+    dry_run: bool,
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    let table = existing
+        .map(|address| fetch_table(rpc_client, &address))
+        .transpose()?;
+    let held = table.as_ref().map_or(0, |table| table.addresses.len());
+    let additions: Vec<Pubkey> = match &table {
+        Some(table) => missing(table, addresses),
+        None => addresses.to_vec(),
+    };
+    if held + additions.len() > LOOKUP_TABLE_MAX_ADDRESSES {
+        anyhow::bail!(
+            "The table holds {held} addresses; {} more exceed its {LOOKUP_TABLE_MAX_ADDRESSES}",
+            additions.len()
+        );
+    }
+    print_kv("addresses to add:", additions.len());
+    if dry_run {
+        for address in &additions {
+            print_kv("add:", address);
+        }
+        return Ok(());
+    }
+
+    let address = match existing {
+        Some(address) => address,
+        None => create_table(rpc_client, payer, commitment)?,
+    };
+    extend_table(rpc_client, payer, address, &additions, commitment)?;
+    let table = fetch_table(rpc_client, &address)?;
+    print_success(format!(
+        "Lookup table {address} holds {} addresses",
+        table.addresses.len()
+    ));
+    Ok(())
+}
+
+/// Accounts a lookup can load: those that neither sign nor are invoked at
+/// the top level, in order of first use.
+fn loadable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut loadable: Vec<Pubkey> = Vec::new();
+    for meta in instructions
+        .iter()
+        .flat_map(|instruction| &instruction.accounts)
+    {
+        let invoked = instructions
+            .iter()
+            .any(|instruction| instruction.program_id == meta.pubkey);
+        if !meta.is_signer && !invoked && !loadable.contains(&meta.pubkey) {
+            loadable.push(meta.pubkey);
+        }
+    }
+    loadable
+}
+
+fn missing(table: &AddressLookupTableAccount, keys: &[Pubkey]) -> Vec<Pubkey> {
+    keys.iter()
+        .filter(|key| !table.addresses.contains(key))
+        .copied()
+        .collect()
+}
+
+pub(crate) fn create_table(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    commitment: CommitmentConfig,
+) -> Result<Pubkey> {
+    // The table address derives from a slot the program must find in
+    // SlotHashes, so take one every validator has.
+    let recent_slot = rpc_client.get_slot_with_commitment(CommitmentConfig::finalized())?;
+    let (instruction, address) = create_lookup_table(payer.pubkey(), payer.pubkey(), recent_slot);
+    send_and_confirm(rpc_client, payer, &[instruction], commitment)
+        .context("Failed to create a lookup table")?;
+    print_info("Created an address lookup table for large reveals");
+    print_kv("lookup table:", address);
+    print_kv("authority:", payer.pubkey());
+    print_info("Pass it with --lookup-table to reuse it after a restart");
+    Ok(address)
+}
+
+/// Adds `addresses` to the table and waits until they can be looked up.
+pub(crate) fn extend_table(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    address: Pubkey,
+    addresses: &[Pubkey],
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    if addresses.is_empty() {
+        return Ok(());
+    }
+    for batch in addresses.chunks(EXTEND_BATCH) {
+        send_and_confirm(
+            rpc_client,
+            payer,
+            &[extend_lookup_table(
+                address,
+                payer.pubkey(),
+                Some(payer.pubkey()),
+                batch.to_vec(),
+            )],
+            commitment,
+        )
+        .with_context(|| format!("Failed to extend lookup table {address}"))?;
+    }
+    info!(table = %address, added = addresses.len(), "Extended lookup table");
+    // Addresses added in a slot can only be looked up from the next one.
+    let extended_at = rpc_client.get_slot_with_commitment(commitment)?;
+    while rpc_client.get_slot_with_commitment(commitment)? <= extended_at {
+        sleep(SLOT_POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+pub(crate) fn fetch_table(
+    rpc_client: &RpcClient,
+    address: &Pubkey,
+) -> Result<AddressLookupTableAccount> {
+    let data = rpc_client
+        .get_account_data(address)
+        .with_context(|| format!("Failed to fetch lookup table {address}"))?;
+    let table = AddressLookupTable::deserialize(&data)
+        .map_err(|err| anyhow::anyhow!("Invalid lookup table {address}: {err}"))?;
+    Ok(AddressLookupTableAccount {
+        key: *address,
+        addresses: table.addresses.to_vec(),
+    })
+}
@@ -1,60 +1,103 @@
-use std::{
-    collections::HashSet,
-    path::PathBuf,
-    str::FromStr,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
-    thread::sleep,
-    time::Duration,
-};
+use std::{collections::HashSet, path::PathBuf, str::FromStr, sync::Arc, time::Duration};
 
 use anyhow::{Context, Result};
 use bs58::decode as bs58_decode;
-use bytemuck::{bytes_of, try_from_bytes};
+use bytemuck::try_from_bytes;
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use entropy::{
-    accounts::{CallbackMeta, Provider, Request},
-    constants::{CALLBACK_NOT_STARTED, COMMITMENT_METADATA_LEN, URI_LEN},
-    instruction::{EntropyInstruction, InitializeArgs, RegisterProviderArgs, RevealArgs},
-    pda::{config_pda, entropy_signer_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda},
+    accounts::{Config, Provider, ProviderDirectoryPage, Request},
+    constants::{CALLBACK_IX_DATA_LEN, MAX_CALLBACK_ACCOUNTS},
+    instruction::EntropyInstruction,
+    pda::{config_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda, requester_signer_pda},
+};
+use entropy_sdk::{
+    default_websocket_url,
+    instruction::{
+        forward_request_with_callback, register_provider, register_provider_args,
+        request_with_callback, RequestAccounts, RequestParams,
+    },
+    listed_providers, parse_logs, provider_directory_addresses, provider_lookup_addresses,
+    provider_uri, request_fee, validate_provider_uri, EntropyEvent, HashChain, ProviderMetadata,
+    RequestFee,
 };
+use owo_colors::OwoColorize;
 use rand::{rngs::OsRng, RngCore};
+use simple_requester::{CALLBACK_ACTION, CALLBACK_STATE_LEN};
 use solana_client::{
     rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
     rpc_config::{RpcSendTransactionConfig, RpcTransactionConfig},
+    rpc_request::MAX_MULTIPLE_ACCOUNTS,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
 };
 use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::hash::Hash;
 use solana_sdk::{
-    hash::hash,
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     signature::{read_keypair_file, Keypair, Signature},
     signer::Signer,
-    sysvar::slot_hashes,
     transaction::Transaction,
 };
 use solana_transaction_status::{
     option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
-    EncodedTransaction, UiCompiledInstruction, UiInstruction, UiMessage, UiTransaction,
-    UiTransactionEncoding,
+    EncodedTransaction, UiCompiledInstruction, UiInstruction, UiMessage, UiRawMessage,
+    UiTransaction, UiTransactionEncoding,
 };
-use owo_colors::OwoColorize;
-use tracing::{error, info, warn};
+use tracing::{error, warn};
 use tracing_subscriber::EnvFilter;
-use bytemuck::{cast_slice, Pod, Zeroable};
-use entropy::{
-    constants::REQUESTER_SIGNER_SEED,
-};
-use simple_requester::{CALLBACK_ACTION, CALLBACK_STATE_LEN, REQUEST_WITH_CALLBACK_ACTION};
-use solana_sdk::{
-    hash::Hash,
-};
 
 #[allow(deprecated)]
-use solana_sdk::{system_instruction, system_program};
+use solana_sdk::system_instruction;
+
+mod admin;
+mod alert;
+mod batch;
+mod callback_skip;
+mod chain_state;
+mod chains;
+mod cluster;
+mod decode;
+mod journal;
+mod keeper;
+mod leader;
+mod lease;
+mod localnet;
+mod lookup;
+mod onboard;
+mod profit;
+mod prompt;
+mod request_store;
+mod schedule;
+mod secrets;
+mod shutdown;
+mod simulate;
+mod stale;
+mod submit;
+mod sweep;
+mod verify;
+mod watch;
+
+use admin::AdminState;
+use alert::{AlertKind, AlertThresholds, Alerter, WebhookFormat};
+use callback_skip::CallbackSkipPolicy;
+use chain_state::DivergencePolicy;
+use cluster::{Cluster, ProgramIds};
+use journal::Journal;
+use keeper::{KeeperContext, ProviderKeeper};
+use leader::LeaderLock;
+use lookup::LookupTables;
+use request_store::RequestStore;
+use schedule::RevealOrder;
+use secrets::{SecretRecord, UserContribution};
+use shutdown::{KeeperStats, Shutdown};
+use submit::{SubmitVia, Submitter};
+use sweep::FeeSweeper;
+
 const DEFAULT_CALLBACK_COMPUTE_UNITS: u32 = 200_000;
+const PROVIDER_METADATA_TIMEOUT: Duration = Duration::from_secs(10);
+/// Metadata documents are a few hundred bytes; anything far larger is
+/// not one.
+const MAX_PROVIDER_METADATA_LEN: usize = 64 * 1024;
 
 fn init_tracing() {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
@@ -85,48 +128,55 @@ fn print_kv(label: &str, value: impl std::fmt::Display) {
     println!("  {} {}", label.dimmed(), value);
 }
 
-#[repr(C)]
-#[derive(Clone, Copy, Pod, Zeroable)]
-struct RequestWithCallbackHeader {
-    user_randomness: [u8; 32],
-    compute_unit_limit: u32,
-    callback_accounts_len: u32,
+fn parse_pubkey(value: &str, label: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).with_context(|| format!("Invalid {label}: {value}"))
 }
 
-fn build_request_with_callback_data(
-    user_randomness: [u8; 32],
-    compute_unit_limit: u32,
-    callback_accounts: &[CallbackMeta],
-    callback_ix_data: &[u8],
-) -> Vec<u8> {
-    let header = RequestWithCallbackHeader {
-        user_randomness,
-        compute_unit_limit,
-        callback_accounts_len: callback_accounts.len() as u32,
-    };
-
-    let mut data = Vec::with_capacity(
-        8 + core::mem::size_of::<RequestWithCallbackHeader>()
-            + callback_accounts.len() * CallbackMeta::LEN
-            + 4
-            + callback_ix_data.len(),
-    );
-    data.extend_from_slice(&EntropyInstruction::RequestWithCallback.discriminator());
-    data.extend_from_slice(bytes_of(&header));
-    data.extend_from_slice(cast_slice(callback_accounts));
-    data.extend_from_slice(&(callback_ix_data.len() as u32).to_le_bytes());
-    data.extend_from_slice(callback_ix_data);
-    data
+fn parse_flag(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        other => Err(format!("expected true/false or 1/0, got `{other}`")),
+    }
 }
 
-fn parse_pubkey(value: &str, label: &str) -> Result<Pubkey> {
-    Pubkey::from_str(value).with_context(|| format!("Invalid {label}: {value}"))
+fn parse_callback_account(value: &str) -> Result<CallbackAccountArg, String> {
+    let parts: Vec<&str> = value.split(':').collect();
+    let [pubkey, is_writable, is_signer] = parts.as_slice() else {
+        return Err(format!("expected PUBKEY:WRITABLE:SIGNER, got `{value}`"));
+    };
+    let pubkey = Pubkey::from_str(pubkey).map_err(|err| format!("invalid pubkey: {err}"))?;
+    Ok(CallbackAccountArg {
+        pubkey,
+        is_writable: parse_flag(is_writable)?,
+        is_signer: parse_flag(is_signer)?,
+    })
 }
 
+fn parse_hex(value: &str) -> Result<Vec<u8>, String> {
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    if !value.len().is_multiple_of(2) {
+        return Err("hex string must have an even number of digits".to_string());
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|index| {
+            value
+                .get(index..index + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| format!("invalid hex digit near offset {index}"))
+        })
+        .collect()
+}
 
 #[derive(Parser, Debug)]
 #[command(name = "entropy", about = "Entropy CLI tool", version)]
 struct Cli {
+    /// Simulate state-changing transactions and print their logs, compute
+    /// units and account changes instead of sending them.
+    #[arg(long, global = true)]
+    dry_run: bool,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -137,16 +187,52 @@ enum Command {
     Provide(ProvideArgs),
     /// Send a request to a provider.
     Request(RequestArgs),
+    /// Inspect a keeper journal.
+    Journal(JournalArgs),
+    /// Show a provider's on-chain state and the metadata document at its URI.
+    ProviderInfo(ProviderInfoArgs),
+    /// List the providers in the on-chain provider directory.
+    ListProviders(ListProvidersArgs),
+    /// Check a revealed random number against its request.
+    Verify(VerifyArgs),
+    /// Decode the entropy instructions of a transaction.
+    DecodeTx(DecodeTxArgs),
+    /// Print the changes to a request or provider account as they happen.
+    Watch(WatchArgs),
+    /// Close expired requests, refunding their rent to the payers.
+    CloseStaleRequests(CloseStaleRequestsArgs),
+    /// Create or extend the address lookup table the keeper sends large
+    /// reveals through.
+    LookupTable(LookupTableArgs),
+    /// Start (or attach to) a local validator, deploy and initialize entropy
+    /// and register a dev provider.
+    LocalnetSetup(LocalnetSetupArgs),
+    /// Generate a provider keypair, register it once funded and write the
+    /// keeper's configuration.
+    OnboardProvider(OnboardProviderArgs),
 }
 
 #[derive(Args, Clone, Debug)]
 struct SharedArgs {
-    /// Solana RPC URL.
-    #[arg(long, env = "SOLANA_RPC_URL", default_value = "http://localhost:8899")]
-    rpc_url: String,
+    /// Solana RPC URL. Defaults to the `--cluster` one, else localhost.
+    #[arg(long, env = "SOLANA_RPC_URL")]
+    rpc_url: Option<String>,
+
+    /// Standard cluster providing the default RPC URL and program ids.
+    #[arg(long, env = "ENTROPY_CLUSTER", value_enum)]
+    cluster: Option<Cluster>,
+
+    /// JSON file of program ids per cluster, overriding the built-in ones,
+    /// e.g. `{"devnet": {"entropy": "...", "simple_requester": "..."}}`.
+    #[arg(long, env = "ENTROPY_PROGRAM_REGISTRY", value_name = "PATH")]
+    program_registry: Option<String>,
 
     /// Keypair file path.
-    #[arg(long, env = "SOLANA_KEYPAIR", default_value = "~/.config/solana/id.json")]
+    #[arg(
+        long,
+        env = "SOLANA_KEYPAIR",
+        default_value = "~/.config/solana/id.json"
+    )]
     keypair: String,
 
     /// Commitment level.
@@ -154,6 +240,53 @@ struct SharedArgs {
     commitment: CommitmentArg,
 }
 
+impl SharedArgs {
+    fn rpc_url(&self) -> &str {
+        self.rpc_url
+            .as_deref()
+            .or(self.cluster.map(Cluster::rpc_url))
+            .unwrap_or(Cluster::Localnet.rpc_url())
+    }
+
+    fn cluster_program_ids(&self) -> Result<ProgramIds> {
+        let Some(cluster) = self.cluster else {
+            return Ok(ProgramIds::default());
+        };
+        let registry = self
+            .program_registry
+            .as_deref()
+            .map(expand_path)
+            .transpose()?;
+        cluster::program_ids(cluster, registry.as_deref())
+    }
+
+    /// `explicit`, or else the `--cluster` deployment's entropy program.
+    fn entropy_program_id(&self, explicit: Option<&str>) -> Result<Pubkey> {
+        if let Some(id) = explicit {
+            return parse_pubkey(id, "entropy program id");
+        }
+        self.cluster_program_ids()?
+            .entropy
+            .with_context(|| match self.cluster {
+                Some(cluster) => format!(
+                    "No entropy program id known for {cluster}; pass --entropy-program-id \
+                 (or ENTROPY_PROGRAM_ID) or list it in --program-registry"
+                ),
+                None => {
+                    "Missing --entropy-program-id (or ENTROPY_PROGRAM_ID or --cluster)".to_string()
+                }
+            })
+    }
+
+    /// `explicit`, or else the `--cluster` deployment's simple requester.
+    fn simple_requester_program_id(&self, explicit: Option<&str>) -> Result<Option<Pubkey>> {
+        match explicit {
+            Some(id) => parse_pubkey(id, "requester program id").map(Some),
+            None => Ok(self.cluster_program_ids()?.simple_requester),
+        }
+    }
+}
+
 #[derive(Args, Debug)]
 struct ProvideArgs {
     #[command(flatten)]
@@ -162,6 +295,369 @@ struct ProvideArgs {
     /// Entropy program id.
     #[arg(long, value_name = "PROGRAM_ID")]
     entropy_program_id: Option<String>,
+
+    /// How reveal transactions are submitted.
+    #[arg(long, value_enum, default_value_t = SubmitVia::Rpc)]
+    submit_via: SubmitVia,
+
+    /// Tip paid to the Jito block engine per reveal bundle.
+    #[arg(long, default_value_t = 10_000)]
+    tip_lamports: u64,
+
+    /// Jito block engine URL used with `--submit-via jito`.
+    #[arg(long, default_value = submit::DEFAULT_JITO_URL)]
+    jito_url: String,
+
+    /// Maximum time to wait for in-flight reveals after Ctrl-C.
+    #[arg(long, default_value_t = 30)]
+    drain_timeout_secs: u64,
+
+    /// Durable nonce account (authority: the keypair) used to sign reveals
+    /// once and retry them without re-signing when blockhashes expire.
+    #[arg(long, value_name = "PUBKEY")]
+    nonce_account: Option<String>,
+
+    /// Address lookup table (authority: the keypair) loading the accounts of
+    /// reveals too large for a legacy transaction. One is created when
+    /// first needed if unset.
+    #[arg(long, value_name = "PUBKEY", conflicts_with = "no_lookup_tables")]
+    lookup_table: Option<String>,
+
+    /// Fail reveals too large for a legacy transaction instead of sending
+    /// them through a lookup table.
+    #[arg(long)]
+    no_lookup_tables: bool,
+
+    /// Skip reveals whose estimated fees and tip exceed the provider fee earned.
+    #[arg(long)]
+    only_profitable: bool,
+
+    /// Order in which pending callback requests are revealed.
+    #[arg(long, value_enum, default_value_t = RevealOrder::Fifo)]
+    reveal_order: RevealOrder,
+
+    /// Maximum reveals per requester program within `--requester-window-secs`.
+    /// Requests over the limit wait for a later round. Unlimited by default.
+    #[arg(long, value_name = "COUNT", value_parser = clap::value_parser!(u32).range(1..))]
+    max_reveals_per_requester: Option<u32>,
+
+    /// Window of `--max-reveals-per-requester`.
+    #[arg(long, default_value_t = 60)]
+    requester_window_secs: u64,
+
+    /// Reveal a requester program's requests without their callback once
+    /// this many of its callbacks failed within `--callback-skip-window-slots`,
+    /// more than succeeded there, refunding the provider fee. Failures are
+    /// reported on chain as the keeper sees them, once per request. Written
+    /// to the provider at startup; 0 turns skipping off, any other value must
+    /// be at least 3, and unset keeps the on-chain policy.
+    #[arg(long, value_name = "COUNT")]
+    callback_skip_max_failures: Option<u16>,
+
+    /// Window of `--callback-skip-max-failures`.
+    #[arg(long, value_name = "SLOTS", default_value_t = 9_000)]
+    callback_skip_window_slots: u32,
+
+    /// Withdraw the provider vault's fees to `--sweep-destination` whenever
+    /// they exceed this many lamports.
+    #[arg(long, value_name = "LAMPORTS", requires = "sweep_destination")]
+    sweep_threshold_lamports: Option<u64>,
+
+    /// Cold wallet that receives swept provider fees.
+    #[arg(long, value_name = "PUBKEY", requires = "sweep_threshold_lamports")]
+    sweep_destination: Option<String>,
+
+    /// Append observed requests, reveal attempts and their outcomes to this
+    /// JSON lines file. Requests it lists as unfinished are queued again on
+    /// startup.
+    #[arg(long, env = "ENTROPY_JOURNAL", value_name = "PATH")]
+    journal: Option<String>,
+
+    /// SQLite file recording processed transactions and queued reveals.
+    /// A restarted keeper resumes its queue from it and polls from where it
+    /// stopped. Kept in memory when unset.
+    #[arg(long, env = "ENTROPY_REQUEST_DB", value_name = "PATH")]
+    request_db: Option<String>,
+
+    /// Metadata document URL stored in the provider account (see
+    /// `entropy_sdk::provider_metadata`).
+    #[arg(long, env = "ENTROPY_PROVIDER_URI", value_name = "URI")]
+    uri: Option<String>,
+
+    /// Reject a `--uri` that does not use https, and have the program
+    /// enforce it on registration too.
+    #[arg(long, env = "ENTROPY_HTTPS_ONLY")]
+    https_only: bool,
+
+    /// Serve the admin API (status, pending-requests, pause, resume,
+    /// rotate-chain) on this address, e.g. 127.0.0.1:9000.
+    #[arg(long, value_name = "ADDR")]
+    admin_addr: Option<std::net::SocketAddr>,
+
+    /// Additional provider keypair to serve alongside `--keypair`, sharing
+    /// this keeper's RPC connection and settings. Repeat for each provider.
+    #[arg(long = "provider-keypair", value_name = "PATH")]
+    provider_keypairs: Vec<String>,
+
+    /// Persist each provider's hash chains and revealed head in this
+    /// directory. On restart they are checked against the on-chain provider
+    /// and resumed instead of registering a fresh chain.
+    #[arg(long, env = "ENTROPY_CHAIN_STATE_DIR", value_name = "DIR")]
+    chain_state_dir: Option<String>,
+
+    /// What to do when a persisted chain disagrees with the on-chain
+    /// provider at startup.
+    #[arg(long, value_enum, default_value_t = DivergencePolicy::Refuse)]
+    on_chain_divergence: DivergencePolicy,
+
+    /// Lock file shared by redundant keepers. Only the instance holding it
+    /// sends transactions; the others stand by and take over when it exits.
+    #[arg(long, value_name = "PATH")]
+    leader_lock: Option<String>,
+
+    /// Hold each provider's on-chain keeper lease, refreshing it before this
+    /// many slots pass. Keepers sharing a provider stand by until the lease
+    /// goes stale, and monitoring can read it to spot a dead keeper.
+    #[arg(long, value_name = "SLOTS", value_parser = clap::value_parser!(u64).range(2..))]
+    keeper_lease_slots: Option<u64>,
+
+    /// Webhook notified when a chain is nearly exhausted, reveals keep
+    /// failing, a provider vault drops below rent or the RPC node is down.
+    #[arg(long, value_name = "URL")]
+    alert_webhook: Option<String>,
+
+    /// Payload format of `--alert-webhook`.
+    #[arg(long, value_enum, default_value_t = WebhookFormat::Json)]
+    alert_format: WebhookFormat,
+
+    /// Alert when at most this many sequence numbers remain in a provider's
+    /// hash chain.
+    #[arg(long, default_value_t = 32)]
+    alert_chain_remaining: u64,
+
+    /// Alert when more than this fraction of a provider's reveals failed
+    /// within `--alert-window-secs`.
+    #[arg(long, default_value_t = 0.5)]
+    alert_failure_rate: f64,
+
+    /// Window of `--alert-failure-rate`.
+    #[arg(long, default_value_t = 300)]
+    alert_window_secs: u64,
+
+    /// Alert after this many consecutive failed RPC polls.
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+    alert_rpc_failures: u32,
+
+    /// Minimum time between repeated notifications of an ongoing alert.
+    #[arg(long, default_value_t = 1800)]
+    alert_cooldown_secs: u64,
+}
+
+#[derive(Args, Debug)]
+struct JournalArgs {
+    #[command(subcommand)]
+    command: JournalCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum JournalCommand {
+    /// Summarize fulfillment and reveal latency.
+    Stats {
+        /// Journal written by `provide --journal`.
+        path: String,
+    },
+}
+
+#[derive(Args, Debug)]
+struct ProviderInfoArgs {
+    #[command(flatten)]
+    shared: SharedArgs,
+
+    /// Provider authority.
+    #[arg(value_name = "PUBKEY")]
+    provider: String,
+
+    /// Entropy program id.
+    #[arg(long, env = "ENTROPY_PROGRAM_ID", value_name = "PROGRAM_ID")]
+    entropy_program_id: Option<String>,
+
+    /// Refuse to fetch a metadata document over plain http.
+    #[arg(long)]
+    https_only: bool,
+}
+
+#[derive(Args, Debug)]
+struct ListProvidersArgs {
+    #[command(flatten)]
+    shared: SharedArgs,
+
+    /// Entropy program id.
+    #[arg(long, env = "ENTROPY_PROGRAM_ID", value_name = "PROGRAM_ID")]
+    entropy_program_id: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct VerifyArgs {
+    #[command(flatten)]
+    shared: SharedArgs,
+
+    /// Request transaction signature or request account.
+    #[arg(long, value_name = "SIG|PUBKEY")]
+    request: String,
+
+    /// Entropy program id.
+    #[arg(long, env = "ENTROPY_PROGRAM_ID", value_name = "PROGRAM_ID")]
+    entropy_program_id: Option<String>,
+
+    /// SlotHashes entry of the request slot, for `use_blockhash` requests
+    /// older than the sysvar's ~512 slots.
+    #[arg(long, value_name = "HASH")]
+    blockhash: Option<Hash>,
+
+    /// Secrets file written by `request`; a stored secret for the request is
+    /// checked against the revealed user contribution.
+    #[arg(long, value_name = "PATH", default_value = secrets::DEFAULT_SECRETS_FILE)]
+    secrets_file: String,
+}
+
+#[derive(Args, Debug)]
+struct DecodeTxArgs {
+    #[command(flatten)]
+    shared: SharedArgs,
+
+    /// Transaction signature.
+    #[arg(value_name = "SIGNATURE")]
+    signature: Signature,
+
+    /// Entropy program id.
+    #[arg(long, env = "ENTROPY_PROGRAM_ID", value_name = "PROGRAM_ID")]
+    entropy_program_id: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct WatchArgs {
+    #[command(flatten)]
+    shared: SharedArgs,
+
+    /// Request account to watch until it is closed.
+    #[arg(
+        long,
+        value_name = "PUBKEY",
+        conflicts_with = "provider",
+        required_unless_present = "provider"
+    )]
+    request: Option<String>,
+
+    /// Provider authority whose provider account to watch.
+    #[arg(long, value_name = "PUBKEY")]
+    provider: Option<String>,
+
+    /// Entropy program id.
+    #[arg(long, env = "ENTROPY_PROGRAM_ID", value_name = "PROGRAM_ID")]
+    entropy_program_id: Option<String>,
+
+    /// Websocket URL. Defaults to the RPC URL's.
+    #[arg(long, value_name = "URL")]
+    ws_url: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct CloseStaleRequestsArgs {
+    #[command(flatten)]
+    shared: SharedArgs,
+
+    /// Entropy program id.
+    #[arg(long, env = "ENTROPY_PROGRAM_ID", value_name = "PROGRAM_ID")]
+    entropy_program_id: Option<String>,
+
+    /// Close expired requests of every payer, not just the keypair's.
+    /// Refunds still go to each request's payer.
+    #[arg(long)]
+    any_payer: bool,
+
+    /// Requests closed per transaction; more would not fit in one when every
+    /// request has a different payer.
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u16).range(1..=12))]
+    batch_size: u16,
+}
+
+#[derive(Args, Debug)]
+struct LookupTableArgs {
+    #[command(flatten)]
+    shared: SharedArgs,
+
+    /// Entropy program id.
+    #[arg(long, env = "ENTROPY_PROGRAM_ID", value_name = "PROGRAM_ID")]
+    entropy_program_id: Option<String>,
+
+    /// Table to extend (authority: the keypair). A new one is created if
+    /// unset.
+    #[arg(long, value_name = "PUBKEY")]
+    address: Option<String>,
+
+    /// Requester program whose callbacks the provider reveals; its program
+    /// id and requester signer are added. Repeatable.
+    #[arg(long = "requester-program", value_name = "PROGRAM_ID")]
+    requester_programs: Vec<String>,
+
+    /// Another account common to many callbacks. Repeatable.
+    #[arg(long = "account", value_name = "PUBKEY")]
+    accounts: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+struct LocalnetSetupArgs {
+    #[command(flatten)]
+    shared: SharedArgs,
+
+    /// Directory holding the `cargo build-sbf` output (`entropy.so`,
+    /// `simple_requester.so` and their keypairs).
+    #[arg(long, value_name = "PATH", default_value = localnet::DEFAULT_ARTIFACTS_DIR)]
+    artifacts_dir: String,
+
+    /// Ledger directory of a validator started by this command. It is reset
+    /// on start.
+    #[arg(long, value_name = "PATH", default_value = localnet::DEFAULT_LEDGER_DIR)]
+    ledger_dir: String,
+
+    /// Pyth fee set in the config.
+    #[arg(long, default_value_t = 0)]
+    pyth_fee_lamports: u64,
+}
+
+#[derive(Args, Debug)]
+struct OnboardProviderArgs {
+    #[command(flatten)]
+    shared: SharedArgs,
+
+    /// Entropy program id.
+    #[arg(long, env = "ENTROPY_PROGRAM_ID", value_name = "PROGRAM_ID")]
+    entropy_program_id: Option<String>,
+
+    /// Directory receiving the provider keypair, its hash chain state and
+    /// `keeper.env`. Rerunning with the same directory resumes onboarding.
+    #[arg(long, value_name = "DIR", default_value = "entropy-provider")]
+    output_dir: String,
+
+    /// Metadata document URL stored in the provider account. Prompted for
+    /// when unset.
+    #[arg(long, value_name = "URI")]
+    uri: Option<String>,
+
+    /// Reject a `--uri` that does not use https, and have the program
+    /// enforce it on registration too.
+    #[arg(long)]
+    https_only: bool,
+
+    /// Lamports to hold for reveal fees on top of the registration rent
+    /// before registering.
+    #[arg(long, value_name = "LAMPORTS", default_value_t = 50_000_000)]
+    reveal_budget_lamports: u64,
+
+    /// Never prompt: use the flags as given and fail instead of waiting for
+    /// funds.
+    #[arg(long)]
+    yes: bool,
 }
 
 #[derive(Args, Debug)]
@@ -180,19 +676,108 @@ struct RequestArgs {
     /// Simple requester program id.
     #[arg(long, env = "SIMPLE_REQUESTER_PROGRAM_ID", value_name = "PROGRAM_ID")]
     requester_program_id: Option<String>,
+
+    /// Consumer program that issues the request and receives the callback.
+    /// Must accept the simple requester's request-with-callback forwarding
+    /// instruction. Defaults to the requester program id.
+    #[arg(long, value_name = "PROGRAM_ID")]
+    callback_program: Option<String>,
+
+    /// Callback account as `PUBKEY:WRITABLE:SIGNER` (e.g. `<PUBKEY>:true:false`).
+    /// Repeat for multiple accounts; order is preserved. When omitted, a
+    /// simple requester callback state account is created.
+    #[arg(long = "callback-account", value_name = "PUBKEY:WRITABLE:SIGNER", value_parser = parse_callback_account)]
+    callback_accounts: Vec<CallbackAccountArg>,
+
+    /// Callback instruction data prefix, hex encoded. Defaults to the simple
+    /// requester callback action followed by the entropy program id.
+    #[arg(long, value_name = "HEX", value_parser = parse_hex)]
+    callback_data_hex: Option<Vec<u8>>,
+
+    /// Slots after which the request can no longer be revealed. 0 disables
+    /// expiry.
+    #[arg(long, default_value_t = 0)]
+    ttl_slots: u32,
+
+    /// Slots that must pass after the request before it can be revealed.
+    #[arg(long, default_value_t = 0)]
+    min_reveal_delay_slots: u16,
+
+    /// Append the user and provider contributions to the callback data.
+    #[arg(long)]
+    include_contributions: bool,
+
+    /// Random number derivation: 0 (original) or 1 (bound to the entropy
+    /// program id).
+    #[arg(long, default_value_t = 0)]
+    random_number_version: u8,
+
+    /// Address that receives the request account's rent when it is closed,
+    /// instead of the payer.
+    #[arg(long, value_name = "PUBKEY")]
+    refund_address: Option<String>,
+
+    /// Number of requests to send. Values above 1 run a load test.
+    #[arg(long, default_value_t = 1)]
+    count: u64,
+
+    /// Target submission rate in requests per second when `--count` > 1.
+    #[arg(long, default_value_t = 1.0)]
+    rate: f64,
+
+    /// Secret the user randomness is derived from, together with the request
+    /// account. A random one is generated when omitted.
+    #[arg(long, value_name = "SECRET")]
+    user_secret: Option<String>,
+
+    /// File the secret, user randomness and commitment of each request are
+    /// appended to, for `verify` to check the reveal against.
+    #[arg(long, value_name = "PATH", default_value = secrets::DEFAULT_SECRETS_FILE)]
+    secrets_file: String,
+
+    /// Ask before sending when the requests cost more than this many
+    /// lamports in total, rent included.
+    #[arg(long, value_name = "LAMPORTS", default_value_t = 10_000_000)]
+    confirm_above_lamports: u64,
+
+    /// Send without asking, whatever the cost.
+    #[arg(long)]
+    yes: bool,
+}
+
+#[derive(Clone, Debug)]
+struct CallbackAccountArg {
+    pubkey: Pubkey,
+    is_writable: bool,
+    is_signer: bool,
 }
 
-struct ProviderChain {
-    chain: Vec<[u8; 32]>,
-    current_index: usize,
-    current_sequence: u64,
+impl CallbackAccountArg {
+    fn to_meta(&self) -> AccountMeta {
+        AccountMeta {
+            pubkey: self.pubkey,
+            is_signer: self.is_signer,
+            is_writable: self.is_writable,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 struct RequestObservation {
     request_account: Pubkey,
     provider_account: Pubkey,
-    user_randomness: [u8; 32],
+    kind: RequestKind,
+}
+
+/// Request variant observed in a transaction, with the user input it carried.
+#[derive(Clone, Debug)]
+enum RequestKind {
+    /// `Request`: only the user commitment is public, so the requester has to
+    /// submit the reveal with their own contribution.
+    Request { user_commitment: [u8; 32] },
+    /// `RequestWithCallback` or `RequestV2`: the keeper reveals and triggers
+    /// the callback.
+    RequestWithCallback { user_randomness: [u8; 32] },
 }
 
 #[derive(ValueEnum, Clone, Debug)]
@@ -219,102 +804,81 @@ fn expand_path(path: &str) -> Result<PathBuf> {
 }
 
 fn load_keypair(path: &PathBuf) -> Result<Keypair> {
-    read_keypair_file(path).map_err(|err| {
-        anyhow::anyhow!("Failed to read keypair file {}: {err}", path.display())
-    })
+    read_keypair_file(path)
+        .map_err(|err| anyhow::anyhow!("Failed to read keypair file {}: {err}", path.display()))
 }
 
-fn build_initialize_ix(
-    program_id: Pubkey,
-    payer: Pubkey,
-    admin: Pubkey,
-    default_provider: Pubkey,
-    pyth_fee_lamports: u64,
-) -> Instruction {
-    let (config, _) = config_pda(&program_id);
-    let (pyth_fee_vault, _) = pyth_fee_vault_pda(&program_id);
-    let args = InitializeArgs {
-        admin: admin.to_bytes(),
-        pyth_fee_lamports,
-        default_provider: default_provider.to_bytes(),
+/// Page of the provider directory the next newly listed provider goes in.
+/// A config that does not exist yet (a dry run against a fresh deployment)
+/// has an empty directory.
+fn provider_directory_page(rpc_client: &RpcClient, entropy_program_id: Pubkey) -> Result<u32> {
+    let (config_address, _) = config_pda(&entropy_program_id);
+    let Ok(config_data) = rpc_client.get_account_data(&config_address) else {
+        return Ok(0);
     };
-    let mut data = Vec::with_capacity(8 + core::mem::size_of::<InitializeArgs>());
-    data.extend_from_slice(&EntropyInstruction::Initialize.discriminator());
-    data.extend_from_slice(bytes_of(&args));
-
-    Instruction {
-        program_id,
-        data,
-        accounts: vec![
-            AccountMeta::new(payer, true),
-            AccountMeta::new(config, false),
-            AccountMeta::new(pyth_fee_vault, false),
-            AccountMeta::new_readonly(system_program::id(), false),
-        ],
-    }
+    let config = try_from_bytes::<Config>(&config_data)
+        .map_err(|err| anyhow::anyhow!("Failed to parse entropy config: {err}"))?;
+    Ok(config.provider_directory_next_page())
 }
 
-fn build_register_provider_ix(
-    program_id: Pubkey,
+/// Generates a fresh random hash chain and the `RegisterProvider`
+/// instruction committing to it.
+fn register_instruction(
+    rpc_client: &RpcClient,
     provider_authority: Pubkey,
-    args: RegisterProviderArgs,
-) -> Instruction {
-    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
-    let (provider_vault, _) = provider_vault_pda(&program_id, &provider_authority);
-
-    let mut data = Vec::with_capacity(8 + core::mem::size_of::<RegisterProviderArgs>());
-    data.extend_from_slice(&EntropyInstruction::RegisterProvider.discriminator());
-    data.extend_from_slice(bytes_of(&args));
-
-    Instruction {
-        program_id,
-        data,
-        accounts: vec![
-            AccountMeta::new(provider_authority, true),
-            AccountMeta::new(provider_account, false),
-            AccountMeta::new(provider_vault, false),
-            AccountMeta::new_readonly(system_program::id(), false),
-        ],
-    }
+    entropy_program_id: Pubkey,
+    uri: &str,
+    https_only: bool,
+) -> Result<(HashChain, Instruction)> {
+    let chain_length = 256u64;
+    let mut seed = [0u8; 32];
+    OsRng.fill_bytes(&mut seed);
+    let hash_chain = HashChain::from_seed(seed, chain_length);
+    let register_args = register_provider_args(
+        0,
+        hash_chain.commitment(),
+        chain_length,
+        &[],
+        uri.as_bytes(),
+        https_only,
+    )?;
+    let register_ix = register_provider(
+        entropy_program_id,
+        provider_authority,
+        register_args,
+        provider_directory_page(rpc_client, entropy_program_id)?,
+    );
+    Ok((hash_chain, register_ix))
 }
 
-fn build_reveal_with_callback_ix(
-    program_id: Pubkey,
-    request_account: Pubkey,
-    provider_account: Pubkey,
-    entropy_signer: Pubkey,
-    callback_program: Pubkey,
-    payer: Pubkey,
-    callback_accounts: &[CallbackMeta],
-    args: RevealArgs,
-) -> Instruction {
-    let mut data = Vec::with_capacity(8 + core::mem::size_of::<RevealArgs>());
-    data.extend_from_slice(&EntropyInstruction::RevealWithCallback.discriminator());
-    data.extend_from_slice(bytes_of(&args));
-
-    let mut accounts = Vec::with_capacity(7 + callback_accounts.len());
-    accounts.push(AccountMeta::new(request_account, false));
-    accounts.push(AccountMeta::new(provider_account, false));
-    accounts.push(AccountMeta::new_readonly(slot_hashes::id(), false));
-    accounts.push(AccountMeta::new_readonly(entropy_signer, false));
-    accounts.push(AccountMeta::new_readonly(callback_program, false));
-    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
-    accounts.push(AccountMeta::new(payer, false));
-
-    for meta in callback_accounts {
-        let key = Pubkey::new_from_array(meta.pubkey);
-        if meta.is_writable == 1 {
-            accounts.push(AccountMeta::new(key, meta.is_signer == 1));
-        } else {
-            accounts.push(AccountMeta::new_readonly(key, meta.is_signer == 1));
-        }
-    }
+/// Registers the provider with a fresh random hash chain, rotating away from
+/// any current one, and returns the chain and the updated provider account.
+fn register_chain(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    entropy_program_id: Pubkey,
+    uri: &str,
+    https_only: bool,
+    commitment: CommitmentConfig,
+) -> Result<(HashChain, Provider)> {
+    let (mut hash_chain, register_ix) = register_instruction(
+        rpc_client,
+        payer.pubkey(),
+        entropy_program_id,
+        uri,
+        https_only,
+    )?;
+    send_and_confirm(rpc_client, payer, &[register_ix], commitment)?;
 
-    Instruction {
-        program_id,
-        data,
-        accounts,
-    }
+    let (provider_account, _) = provider_pda(&entropy_program_id, &payer.pubkey());
+    let provider_data = rpc_client
+        .get_account_data(&provider_account)
+        .context("Failed to fetch provider account")?;
+    let provider = *try_from_bytes::<Provider>(&provider_data)
+        .map_err(|err| anyhow::anyhow!("Failed to parse provider account: {err}"))?;
+
+    hash_chain.set_start_sequence(provider.current_commitment_sequence_number);
+    Ok((hash_chain, provider))
 }
 
 fn send_and_confirm(
@@ -340,88 +904,157 @@ fn send_and_confirm(
         .context("Transaction failed")
 }
 
-fn build_register_args(commitment: [u8; 32], chain_length: u64) -> RegisterProviderArgs {
-    let commitment_metadata = [0u8; COMMITMENT_METADATA_LEN];
-    let uri = [0u8; URI_LEN];
-
-    RegisterProviderArgs {
-        fee_lamports: 0,
-        commitment,
-        commitment_metadata_len: 0,
-        _padding0: [0u8; 6],
-        commitment_metadata,
-        chain_length,
-        uri_len: 0,
-        uri,
-        _padding1: [0u8; 6],
-    }
-}
-
-fn build_chain(chain_length: usize) -> ([u8; 32], Vec<[u8; 32]>) {
-    let mut seed = [0u8; 32];
-    OsRng.fill_bytes(&mut seed);
-    let mut chain = Vec::with_capacity(chain_length + 1);
-    chain.push(seed);
-    for index in 0..chain_length {
-        let next = hash(&chain[index]).to_bytes();
-        chain.push(next);
-    }
-    let commitment = *chain.last().expect("chain is non-empty");
-    (commitment, chain)
-}
-
-fn parse_user_randomness(data: &[u8]) -> Option<[u8; 32]> {
-    let discriminator = EntropyInstruction::RequestWithCallback.discriminator();
-    if data.len() < 8 + 32 || data[..8] != discriminator {
-        return None;
+fn parse_request_kind(data: &[u8]) -> Option<RequestKind> {
+    let (instruction, payload) = EntropyInstruction::parse(data).ok()?;
+    let user_input: [u8; 32] = payload.get(..32)?.try_into().ok()?;
+    match instruction {
+        EntropyInstruction::Request => Some(RequestKind::Request {
+            user_commitment: user_input,
+        }),
+        EntropyInstruction::RequestWithCallback => Some(RequestKind::RequestWithCallback {
+            user_randomness: user_input,
+        }),
+        _ => None,
     }
-    let mut user_randomness = [0u8; 32];
-    user_randomness.copy_from_slice(&data[8..40]);
-    Some(user_randomness)
 }
 
-fn parse_request_observations(
+/// The transaction's raw message with its account keys, including those
+/// loaded from lookup tables. `None` for encodings other than raw JSON.
+fn raw_message_with_keys(
     tx: &EncodedConfirmedTransactionWithStatusMeta,
-    entropy_program_id: &Pubkey,
-) -> Result<Vec<RequestObservation>> {
+) -> Result<Option<(&UiRawMessage, Vec<Pubkey>)>> {
     let message = match &tx.transaction.transaction {
         EncodedTransaction::Json(UiTransaction { message, .. }) => message,
-        _ => return Ok(Vec::new()),
+        _ => return Ok(None),
     };
 
     let raw_message = match message {
         UiMessage::Raw(raw) => raw,
-        _ => return Ok(Vec::new()),
+        _ => return Ok(None),
     };
 
+    // v0 messages index past the static keys into addresses loaded from lookup
+    // tables: all writable loaded addresses first, then all readonly ones.
+    let loaded_addresses =
+        tx.transaction
+            .meta
+            .as_ref()
+            .and_then(|meta| match &meta.loaded_addresses {
+                OptionSerializer::Some(loaded) => Some(loaded),
+                _ => None,
+            });
+    let loaded_keys = loaded_addresses
+        .into_iter()
+        .flat_map(|loaded| loaded.writable.iter().chain(loaded.readonly.iter()));
+    if loaded_addresses.is_none() && raw_message.address_table_lookups.is_some() {
+        warn!("Transaction uses lookup tables but meta has no loaded addresses");
+    }
+
     let account_keys: Vec<Pubkey> = raw_message
         .account_keys
         .iter()
+        .chain(loaded_keys)
         .map(|key| Pubkey::from_str(key))
         .collect::<std::result::Result<Vec<_>, _>>()
         .context("Failed to parse account keys")?;
+    Ok(Some((raw_message, account_keys)))
+}
+
+/// Largest page `getSignaturesForAddress` returns.
+const SIGNATURE_PAGE_LIMIT: usize = 1000;
+
+/// The program's transactions newer than `until`, newest first, paging back
+/// as far as needed so nothing confirmed while the keeper was down is
+/// missed. Without `until` (a first run) only the latest 100 are read.
+fn fetch_new_signatures(
+    rpc_client: &RpcClient,
+    entropy_program_id: &Pubkey,
+    until: Option<Signature>,
+) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+    let limit = if until.is_some() {
+        SIGNATURE_PAGE_LIMIT
+    } else {
+        100
+    };
+    let mut signatures: Vec<RpcConfirmedTransactionStatusWithSignature> = Vec::new();
+    loop {
+        let before = signatures
+            .last()
+            .and_then(|status| Signature::from_str(&status.signature).ok());
+        let page = rpc_client.get_signatures_for_address_with_config(
+            entropy_program_id,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some(limit),
+                ..GetConfirmedSignaturesForAddress2Config::default()
+            },
+        )?;
+        let done = until.is_none() || page.len() < limit;
+        signatures.extend(page);
+        if done {
+            return Ok(signatures);
+        }
+    }
+}
+
+/// The request at `request_account` if it still awaits its callback reveal.
+fn pending_callback_request(rpc_client: &RpcClient, request_account: &Pubkey) -> Option<Request> {
+    rpc_client
+        .get_account_data(request_account)
+        .ok()
+        .and_then(|data| try_from_bytes::<Request>(&data).ok().copied())
+        .filter(Request::awaits_callback)
+}
+
+fn parse_request_observations(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    entropy_program_id: &Pubkey,
+) -> Result<Vec<RequestObservation>> {
+    let Some((raw_message, account_keys)) = raw_message_with_keys(tx)? else {
+        return Ok(Vec::new());
+    };
 
     let mut observations = Vec::new();
 
     for instruction in &raw_message.instructions {
-        collect_compiled_observation(
+        observations.extend(compiled_observation(
             instruction,
             &account_keys,
             entropy_program_id,
-            &mut observations,
-        );
+        ));
     }
 
     if let Some(meta) = &tx.transaction.meta {
+        // `RequestV2` carries no user randomness; entropy logs what it
+        // generated instead.
+        if let OptionSerializer::Some(logs) = &meta.log_messages {
+            for event in parse_logs(entropy_program_id, logs) {
+                if let EntropyEvent::UserRandomnessGenerated(generated) = event {
+                    observations.push(RequestObservation {
+                        request_account: Pubkey::new_from_array(generated.request_account),
+                        provider_account: provider_pda(
+                            entropy_program_id,
+                            &Pubkey::new_from_array(generated.provider),
+                        )
+                        .0,
+                        kind: RequestKind::RequestWithCallback {
+                            user_randomness: generated.user_randomness,
+                        },
+                    });
+                }
+            }
+        }
         if let OptionSerializer::Some(inner) = &meta.inner_instructions {
             for inner_ix in inner {
                 for instruction in &inner_ix.instructions {
-                    collect_request_observation(
-                        instruction,
-                        &account_keys,
-                        entropy_program_id,
-                        &mut observations,
-                    );
+                    if let UiInstruction::Compiled(compiled) = instruction {
+                        observations.extend(compiled_observation(
+                            compiled,
+                            &account_keys,
+                            entropy_program_id,
+                        ));
+                    }
                 }
             }
         }
@@ -430,185 +1063,357 @@ fn parse_request_observations(
     Ok(observations)
 }
 
-fn collect_request_observation(
-    instruction: &UiInstruction,
+fn compiled_observation(
+    instruction: &UiCompiledInstruction,
     account_keys: &[Pubkey],
     entropy_program_id: &Pubkey,
-    observations: &mut Vec<RequestObservation>,
-) {
-    let compiled = match instruction {
-        UiInstruction::Compiled(compiled) => compiled,
-        _ => return,
-    };
-
-    let program_key = match account_keys.get(compiled.program_id_index as usize) {
-        Some(key) => key,
-        None => return,
-    };
+) -> Option<RequestObservation> {
+    let program_key = account_keys.get(usize::from(instruction.program_id_index))?;
     if program_key != entropy_program_id {
-        return;
+        return None;
     }
 
-    let data = match bs58_decode(&compiled.data).into_vec() {
-        Ok(data) => data,
-        Err(_) => return,
-    };
-    let user_randomness = match parse_user_randomness(&data) {
-        Some(randomness) => randomness,
-        None => return,
-    };
-
-    let request_index = match compiled.accounts.get(3) {
-        Some(index) => *index as usize,
-        None => return,
-    };
-    let provider_index = match compiled.accounts.get(4) {
-        Some(index) => *index as usize,
-        None => return,
-    };
+    let data = bs58_decode(&instruction.data).into_vec().ok()?;
+    let kind = parse_request_kind(&data)?;
 
-    let request_account = match account_keys.get(request_index) {
-        Some(key) => *key,
-        None => return,
-    };
-    let provider_account = match account_keys.get(provider_index) {
-        Some(key) => *key,
-        None => return,
-    };
+    let request_index = usize::from(*instruction.accounts.get(3)?);
+    let provider_index = usize::from(*instruction.accounts.get(4)?);
 
-    observations.push(RequestObservation {
-        request_account,
-        provider_account,
-        user_randomness,
-    });
+    Some(RequestObservation {
+        request_account: *account_keys.get(request_index)?,
+        provider_account: *account_keys.get(provider_index)?,
+        kind,
+    })
 }
 
-fn collect_compiled_observation(
-    instruction: &UiCompiledInstruction,
-    account_keys: &[Pubkey],
-    entropy_program_id: &Pubkey,
-    observations: &mut Vec<RequestObservation>,
-) {
-    let program_key = match account_keys.get(instruction.program_id_index as usize) {
-        Some(key) => key,
-        None => return,
-    };
-    if program_key != entropy_program_id {
-        return;
-    }
-
-    let data = match bs58_decode(&instruction.data).into_vec() {
-        Ok(data) => data,
-        Err(_) => return,
-    };
-    let user_randomness = match parse_user_randomness(&data) {
-        Some(randomness) => randomness,
-        None => return,
-    };
-
-    let request_index = match instruction.accounts.get(3) {
-        Some(index) => *index as usize,
-        None => return,
-    };
-    let provider_index = match instruction.accounts.get(4) {
-        Some(index) => *index as usize,
-        None => return,
-    };
-
-    let request_account = match account_keys.get(request_index) {
-        Some(key) => *key,
-        None => return,
-    };
-    let provider_account = match account_keys.get(provider_index) {
-        Some(key) => *key,
-        None => return,
-    };
-
-    observations.push(RequestObservation {
-        request_account,
-        provider_account,
-        user_randomness,
-    });
-}
-
-fn handle_provide(args: ProvideArgs) -> Result<()> {
+fn handle_provide(args: ProvideArgs, dry_run: bool) -> Result<()> {
     let keypair_path = expand_path(&args.shared.keypair)
         .with_context(|| format!("Invalid keypair path: {}", args.shared.keypair))?;
     let commitment = args.shared.commitment.to_config();
-    let rpc_client = RpcClient::new_with_commitment(args.shared.rpc_url.clone(), commitment);
+    let rpc_client = RpcClient::new_with_commitment(args.shared.rpc_url().to_string(), commitment);
     let payer = load_keypair(&keypair_path)?;
+    let mut payers = vec![payer];
+    for path in &args.provider_keypairs {
+        let path = expand_path(path).with_context(|| format!("Invalid keypair path: {path}"))?;
+        let keypair = load_keypair(&path)?;
+        if payers
+            .iter()
+            .any(|payer| payer.pubkey() == keypair.pubkey())
+        {
+            anyhow::bail!("Provider {} is listed more than once", keypair.pubkey());
+        }
+        payers.push(keypair);
+    }
+    if payers.len() > 1 && args.nonce_account.is_some() {
+        anyhow::bail!("--nonce-account can only be used with a single provider");
+    }
 
     let entropy_program_id = args
-        .entropy_program_id
-        .as_deref()
-        .ok_or_else(|| anyhow::anyhow!("--entropy-program-id is required"))?;
-    let entropy_program_id = Pubkey::from_str(entropy_program_id)
-        .with_context(|| format!("Invalid entropy program id: {}", entropy_program_id))?;
+        .shared
+        .entropy_program_id(args.entropy_program_id.as_deref())?;
 
     print_info("Starting provider mode");
-    print_kv("rpc url:", &args.shared.rpc_url);
+    print_kv("rpc url:", args.shared.rpc_url());
     print_kv("keypair:", keypair_path.display());
+    if payers.len() > 1 {
+        print_kv("providers:", payers.len());
+    }
     print_kv("commitment:", format!("{:?}", commitment.commitment));
     print_kv("program id:", entropy_program_id);
+    print_kv("submit via:", format!("{:?}", args.submit_via));
+    if args.submit_via == SubmitVia::Jito {
+        print_kv("jito url:", &args.jito_url);
+        print_kv("tip lamports:", args.tip_lamports);
+    }
 
-    let running = Arc::new(AtomicBool::new(true));
-    let shutdown = running.clone();
-    ctrlc::set_handler(move || {
-        shutdown.store(false, Ordering::SeqCst);
-    })
-    .context("Failed to set Ctrl-C handler")?;
+    let nonce_account = args
+        .nonce_account
+        .as_deref()
+        .map(|value| parse_pubkey(value, "nonce account"))
+        .transpose()?;
+    if let Some(nonce_account) = nonce_account {
+        print_kv("nonce account:", nonce_account);
+    }
+    let lookup_tables = if args.no_lookup_tables {
+        None
+    } else {
+        let lookup_table = args
+            .lookup_table
+            .as_deref()
+            .map(|value| parse_pubkey(value, "lookup table"))
+            .transpose()?;
+        if let Some(lookup_table) = lookup_table {
+            print_kv("lookup table:", lookup_table);
+        }
+        Some(LookupTables::new(entropy_program_id, lookup_table))
+    };
+    let uri = args.uri.clone().unwrap_or_default();
+    if !uri.is_empty() {
+        validate_provider_uri(&uri, args.https_only)?;
+        print_kv("uri:", &uri);
+    }
+    print_kv("only profitable:", args.only_profitable);
+    print_kv("reveal order:", format!("{:?}", args.reveal_order));
+    if let Some(max_reveals) = args.max_reveals_per_requester {
+        print_kv(
+            "requester limit:",
+            format!("{max_reveals} reveals per {}s", args.requester_window_secs),
+        );
+    }
+    let callback_skip_policy =
+        args.callback_skip_max_failures
+            .map(|max_failures| CallbackSkipPolicy {
+                max_failures,
+                window_slots: args.callback_skip_window_slots,
+            });
+    if let Some(policy) = callback_skip_policy {
+        print_kv(
+            "callback skip:",
+            format!(
+                "{} failure(s) per {} slots",
+                policy.max_failures, policy.window_slots
+            ),
+        );
+    }
+    let sweeper = match (
+        args.sweep_threshold_lamports,
+        args.sweep_destination.as_deref(),
+    ) {
+        (Some(threshold), Some(destination)) => Some(FeeSweeper {
+            threshold,
+            destination: parse_pubkey(destination, "sweep destination")?,
+        }),
+        _ => None,
+    };
+    if let Some(sweeper) = &sweeper {
+        print_kv(
+            "fee sweep:",
+            format!(
+                "above {} lamports to {}",
+                sweeper.threshold, sweeper.destination
+            ),
+        );
+    }
 
+    if !(0.0..=1.0).contains(&args.alert_failure_rate) {
+        return Err(anyhow::anyhow!(
+            "--alert-failure-rate must be between 0 and 1"
+        ));
+    }
+    let alerter = args
+        .alert_webhook
+        .clone()
+        .map(|webhook| {
+            Alerter::new(
+                webhook,
+                args.alert_format,
+                AlertThresholds {
+                    chain_remaining: args.alert_chain_remaining,
+                    failure_rate: args.alert_failure_rate,
+                    failure_window: Duration::from_secs(args.alert_window_secs),
+                    rpc_failures: args.alert_rpc_failures,
+                    cooldown: Duration::from_secs(args.alert_cooldown_secs),
+                },
+            )
+        })
+        .transpose()?;
+    if alerter.is_some() {
+        print_kv("alerts:", format!("{:?} webhook", args.alert_format));
+    }
+
+    let submitter = Submitter::new(
+        args.submit_via,
+        args.tip_lamports,
+        args.jito_url.clone(),
+        nonce_account,
+        lookup_tables,
+    )?;
+
+    let stats: Vec<_> = payers
+        .iter()
+        .map(|payer| Arc::new(KeeperStats::new(payer.pubkey())))
+        .collect();
+    let shutdown = Shutdown::install(Duration::from_secs(args.drain_timeout_secs), stats.clone())?;
+
+    // Standbys wait here, before sending anything: registering would rotate
+    // the leader's hash chain.
+    let _leader_lock = match args.leader_lock.as_deref().filter(|_| !dry_run) {
+        Some(path) => match LeaderLock::acquire(&expand_path(path)?, &shutdown)? {
+            Some(lock) => Some(lock),
+            None => {
+                print_info("Shutdown requested while standing by");
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let payer = &payers[0];
     let (config_address, _) = config_pda(&entropy_program_id);
     if rpc_client.get_account(&config_address).is_err() {
-        print_info("Initializing entropy config...");
-        let ix = build_initialize_ix(
+        // Only the program's upgrade authority may initialize, so the payer
+        // must be the keypair that deployed entropy.
+        let ix = entropy_sdk::instruction::initialize(
             entropy_program_id,
             payer.pubkey(),
             payer.pubkey(),
             payer.pubkey(),
             0,
         );
-      send_and_confirm(&rpc_client, &payer, &[ix], commitment)?;
-      print_success("Entropy config initialized");
+        if dry_run {
+            print_info("Simulating entropy config initialization...");
+            simulate::simulate_instructions(
+                &rpc_client,
+                payer,
+                &[ix],
+                &entropy_program_id,
+                commitment,
+            )?;
+        } else {
+            print_info("Initializing entropy config...");
+            send_and_confirm(&rpc_client, payer, &[ix], commitment)?;
+            print_success("Entropy config initialized");
+        }
     } else {
         print_info("Entropy config already initialized");
     }
+    // Registration is the first thing the keeper sends; a dry run stops
+    // there.
+    if dry_run {
+        for payer in &payers {
+            print_info(format!("Simulating registration of {}...", payer.pubkey()));
+            let (_, register_ix) = register_instruction(
+                &rpc_client,
+                payer.pubkey(),
+                entropy_program_id,
+                &uri,
+                args.https_only,
+            )?;
+            simulate::simulate_instructions(
+                &rpc_client,
+                payer,
+                &[register_ix],
+                &entropy_program_id,
+                commitment,
+            )?;
+        }
+        return Ok(());
+    }
 
-    let chain_length = 256u64;
-    let (commitment_value, chain) = build_chain(chain_length as usize);
-    let register_args = build_register_args(commitment_value, chain_length);
-    let register_ix = build_register_provider_ix(entropy_program_id, payer.pubkey(), register_args);
-    print_info("Registering provider...");
-    send_and_confirm(&rpc_client, &payer, &[register_ix], commitment)?;
-    print_success("Provider registered");
-
-    let (provider_account, _) = provider_pda(&entropy_program_id, &payer.pubkey());
-    let provider_data = rpc_client
-        .get_account_data(&provider_account)
-        .context("Failed to fetch provider account")?;
-    let provider = try_from_bytes::<Provider>(&provider_data)
-        .map_err(|err| anyhow::anyhow!("Failed to parse provider account: {err}"))?;
-
-    let mut provider_chain = ProviderChain {
-        chain,
-        current_index: chain_length as usize,
-        current_sequence: provider.current_commitment_sequence_number,
+    let journal_path = args.journal.as_deref().map(expand_path).transpose()?;
+    let replay = match journal_path.as_deref().filter(|path| path.exists()) {
+        Some(path) => journal::unfinished(&journal::read_journal(path)?),
+        None => Vec::new(),
+    };
+    let journal = Journal::open(journal_path.as_deref())?;
+    let request_db_path = args.request_db.as_deref().map(expand_path).transpose()?;
+    let requests = RequestStore::open(request_db_path.as_deref())?;
+    let chain_state_dir = args
+        .chain_state_dir
+        .as_deref()
+        .map(expand_path)
+        .transpose()?;
+    let admin = Arc::new(AdminState::new(stats.iter().cloned()));
+    let ctx = KeeperContext {
+        rpc_client: &rpc_client,
+        entropy_program_id,
+        commitment,
+        submitter: &submitter,
+        shutdown: &shutdown,
+        journal: &journal,
+        requests: &requests,
+        admin: &admin,
+        sweeper: sweeper.as_ref(),
+        alerter: alerter.as_ref(),
+        chain_state_dir: chain_state_dir.as_deref(),
+        on_divergence: args.on_chain_divergence,
+        keeper_lease_slots: args.keeper_lease_slots,
+        callback_skip_policy,
+        uri: &uri,
+        https_only: args.https_only,
+        only_profitable: args.only_profitable,
+        reveal_order: args.reveal_order,
+        max_reveals_per_requester: args.max_reveals_per_requester,
+        requester_window: Duration::from_secs(args.requester_window_secs),
     };
 
-    print_info("Provider ready");
-    print_kv("authority:", payer.pubkey());
-    print_kv("provider account:", provider_account);
-    print_info("Listening for request_with_callback...");
+    let mut keepers = Vec::with_capacity(payers.len());
+    for (index, (payer, stats)) in payers.into_iter().zip(stats).enumerate() {
+        match ProviderKeeper::register(&ctx, index, payer, stats)? {
+            Some(keeper) => keepers.push(keeper),
+            None => {
+                keepers.iter_mut().for_each(|keeper| keeper.finish(&ctx));
+                print_info("Shutdown requested while standing by");
+                return Ok(());
+            }
+        }
+    }
+    print_info("Listening for requests...");
+
+    for keeper in &mut keepers {
+        let stored = requests.pending(&keeper.authority());
+        if !stored.is_empty() {
+            print_info(format!(
+                "Resuming {} queued request(s) of {}",
+                stored.len(),
+                keeper.authority()
+            ));
+        }
+        for reveal in stored {
+            match pending_callback_request(&rpc_client, &reveal.request_account) {
+                Some(request) => keeper.resume(
+                    &ctx,
+                    reveal.request_account,
+                    request,
+                    reveal.user_randomness,
+                ),
+                None => requests.finish(&reveal.request_account),
+            }
+        }
+    }
+    if !replay.is_empty() {
+        print_info(format!(
+            "Replaying {} unfinished request(s) from the journal",
+            replay.len()
+        ));
+    }
+    for entry in replay {
+        let request = pending_callback_request(&rpc_client, &entry.request_account);
+        let keeper = request.and_then(|request| {
+            let provider = Pubkey::new_from_array(request.provider);
+            keepers
+                .iter_mut()
+                .find(|keeper| keeper.authority() == provider)
+                .map(|keeper| (keeper, request))
+        });
+        match keeper {
+            Some((keeper, request)) => {
+                keeper.queue(&ctx, entry.request_account, request, entry.user_randomness)
+            }
+            None => print_warn(format!(
+                "Journaled request {} is no longer pending",
+                entry.request_account
+            )),
+        }
+    }
+    if let Some(path) = &journal_path {
+        print_kv("journal:", path.display());
+    }
+    if let Some(path) = &request_db_path {
+        print_kv("request db:", path.display());
+    }
+
+    if let Some(addr) = args.admin_addr {
+        admin::serve(addr, admin.clone())?;
+        print_kv("admin api:", format!("http://{addr}"));
+    }
 
-    let mut processed_signatures = HashSet::new();
-    let mut last_seen: Option<String> = None;
-    while running.load(Ordering::SeqCst) {
-        let signatures = rpc_client.get_signatures_for_address_with_config(
+    let mut consecutive_rpc_failures = 0u32;
+    while shutdown.is_running() {
+        let signatures = fetch_new_signatures(
+            &rpc_client,
             &entropy_program_id,
-            GetConfirmedSignaturesForAddress2Config {
-                limit: Some(100),
-                ..GetConfirmedSignaturesForAddress2Config::default()
-            },
+            requests.newest_signature(),
         );
 
         let signatures = match signatures {
@@ -616,33 +1421,54 @@ fn handle_provide(args: ProvideArgs) -> Result<()> {
             Err(err) => {
                 warn!(error = %err, "Failed to fetch signatures");
                 print_warn("Failed to fetch signatures; retrying");
-                sleep(Duration::from_secs(2));
+                consecutive_rpc_failures += 1;
+                if let Some(alerter) = alerter
+                    .as_ref()
+                    .filter(|alerter| consecutive_rpc_failures >= alerter.thresholds.rpc_failures)
+                {
+                    alerter.raise(
+                        AlertKind::RpcOutage,
+                        None,
+                        &format!(
+                            "RPC node {} failed {consecutive_rpc_failures} consecutive polls: {err}",
+                            args.shared.rpc_url()
+                        ),
+                    );
+                }
+                shutdown.sleep(Duration::from_secs(2));
                 continue;
             }
         };
-
-        if signatures.is_empty() {
-            sleep(Duration::from_secs(2));
-            continue;
-        }
-
-        let mut new_signatures = Vec::new();
-        for sig in &signatures {
-            if last_seen.as_deref() == Some(&sig.signature) {
-                break;
-            }
-            if processed_signatures.insert(sig.signature.clone()) {
-                new_signatures.push(sig.signature.clone());
-            }
-        }
-
-        if let Some(first) = signatures.first() {
-            last_seen = Some(first.signature.clone());
+        if let Some(alerter) = alerter
+            .as_ref()
+            .filter(|alerter| consecutive_rpc_failures >= alerter.thresholds.rpc_failures)
+        {
+            alerter.resolve(
+                AlertKind::RpcOutage,
+                None,
+                &format!("RPC node {} is responding again", args.shared.rpc_url()),
+            );
         }
+        consecutive_rpc_failures = 0;
 
+        let mut new_signatures: Vec<_> = signatures
+            .into_iter()
+            .filter(|status| !requests.is_processed(&status.signature))
+            .collect();
         new_signatures.reverse();
 
-        for signature_str in new_signatures {
+        let total_signatures = new_signatures.len();
+        for (index, status) in new_signatures.into_iter().enumerate() {
+            // Stop taking new work once shutdown is requested. The unread
+            // transactions may belong to any provider, so they are not counted.
+            if !shutdown.is_running() {
+                print_warn(format!(
+                    "{} transaction(s) left unread on shutdown",
+                    total_signatures - index
+                ));
+                break;
+            }
+            let signature_str = status.signature;
             let signature = match Signature::from_str(&signature_str) {
                 Ok(sig) => sig,
                 Err(_) => continue,
@@ -665,128 +1491,185 @@ fn handle_provide(args: ProvideArgs) -> Result<()> {
 
             let observations = parse_request_observations(&tx, &entropy_program_id)?;
             for observation in observations {
-                if observation.provider_account != provider_account {
-                    continue;
+                if let Some(keeper) = keepers
+                    .iter_mut()
+                    .find(|keeper| keeper.provider_account() == observation.provider_account)
+                {
+                    keeper.observe(&ctx, &observation);
                 }
+            }
+            requests.mark_processed(&signature_str, status.slot);
+        }
+        requests.prune();
 
-                let request_data = match rpc_client.get_account_data(&observation.request_account)
-                {
-                    Ok(data) => data,
-                    Err(_) => {
-                        continue;
-                    }
-                };
-                let request = match try_from_bytes::<Request>(&request_data) {
-                    Ok(request) => request,
-                    Err(err) => {
-                        warn!(
-                            request = %observation.request_account,
-                            error = %err,
-                            "Failed to parse request account"
-                        );
-                        continue;
-                    }
-                };
+        // Providers reveal concurrently; each only touches its own state.
+        std::thread::scope(|scope| {
+            for keeper in &mut keepers {
+                let ctx = &ctx;
+                scope.spawn(move || keeper.round(ctx));
+            }
+        });
+        if keepers.iter().any(ProviderKeeper::lease_lost) {
+            keepers.iter_mut().for_each(|keeper| keeper.finish(&ctx));
+            anyhow::bail!("Lost the keeper lease to another keeper; exiting");
+        }
 
-                if request.callback_status != CALLBACK_NOT_STARTED {
-                    continue;
-                }
-                if Pubkey::new_from_array(request.provider) != payer.pubkey() {
-                    continue;
-                }
-                if request.sequence_number <= provider_chain.current_sequence {
-                    continue;
-                }
+        shutdown.sleep(Duration::from_secs(2));
+    }
 
-                let num_hashes = request
-                    .sequence_number
-                    .checked_sub(provider_chain.current_sequence)
-                    .unwrap_or(0);
-                let num_hashes_usize = match usize::try_from(num_hashes) {
-                    Ok(value) => value,
-                    Err(_) => {
-                        warn!(sequence = request.sequence_number, "Sequence number too large");
-                        continue;
-                    }
-                };
-                if num_hashes_usize > provider_chain.current_index {
-                    print_warn("Out of provider randomness. Re-register provider.");
-                    continue;
-                }
+    keepers.iter_mut().for_each(|keeper| keeper.finish(&ctx));
+    print_info("Shutdown complete");
+    for keeper in &keepers {
+        keeper.stats().print_summary();
+    }
+    Ok(())
+}
 
-                let provider_contribution =
-                    provider_chain.chain[provider_chain.current_index - num_hashes_usize];
-                let reveal_args = RevealArgs {
-                    user_contribution: observation.user_randomness,
-                    provider_contribution,
-                };
-
-                let entropy_signer = entropy_signer_pda(&entropy_program_id).0;
-                let callback_program = Pubkey::new_from_array(request.requester_program_id);
-
-                let callback_accounts_len = request.callback_accounts_len as usize;
-                let callback_accounts = &request.callback_accounts[..callback_accounts_len];
-
-                let reveal_ix = build_reveal_with_callback_ix(
-                    entropy_program_id,
-                    observation.request_account,
-                    provider_account,
-                    entropy_signer,
-                    callback_program,
-                    Pubkey::new_from_array(request.payer),
-                    callback_accounts,
-                    reveal_args,
-                );
+/// Everything needed to build request transactions, resolved once per run.
+struct RequestPlan {
+    entropy_program_id: Pubkey,
+    requester_program_id: Pubkey,
+    provider_authority: Pubkey,
+    provider_vault: Pubkey,
+    config_account: Pubkey,
+    pyth_fee_vault: Pubkey,
+    requester_signer: Pubkey,
+    compute_unit_limit: u32,
+    ttl_slots: u32,
+    min_reveal_delay_slots: u16,
+    include_contributions: bool,
+    random_number_version: u8,
+    refund_address: Option<Pubkey>,
+    callback_state_rent: u64,
+    callback_accounts: Vec<CallbackAccountArg>,
+    callback_ix_data: Vec<u8>,
+    user_secret: Option<String>,
+}
 
-                print_info(format!(
-                    "Revealing for request {} (sequence {})",
-                    observation.request_account, request.sequence_number
-                ));
+/// A signed request transaction plus the accounts it creates.
+struct BuiltRequest {
+    transaction: Transaction,
+    request_account: Pubkey,
+    callback_state: Option<Pubkey>,
+    user_contribution: UserContribution,
+}
 
-                match send_and_confirm(&rpc_client, &payer, &[reveal_ix], commitment) {
-                    Ok(signature) => {
-                        provider_chain.current_index -= num_hashes_usize;
-                        provider_chain.current_sequence = request.sequence_number;
-                        println!("Successful reveal!: {signature}");
-                    }
-                    Err(err) => {
-                        print_error(format!("Failed to reveal: {err}"));
-                    }
-                }
-            }
-        }
+fn build_request_transaction(
+    plan: &RequestPlan,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+) -> Result<BuiltRequest> {
+    let request_account = Keypair::new();
+    let mut instructions = Vec::with_capacity(2);
+
+    // Without explicit callback accounts, fall back to the simple requester
+    // flow and create a fresh callback state account for the callback to write.
+    let callback_state = if plan.callback_accounts.is_empty() {
+        let callback_state = Keypair::new();
+        instructions.push(system_instruction::create_account(
+            &payer.pubkey(),
+            &callback_state.pubkey(),
+            plan.callback_state_rent,
+            CALLBACK_STATE_LEN as u64,
+            &plan.requester_program_id,
+        ));
+        Some(callback_state)
+    } else {
+        None
+    };
 
-        sleep(Duration::from_secs(2));
+    let callback_accounts: Vec<AccountMeta> = match &callback_state {
+        Some(callback_state) => vec![AccountMeta::new(callback_state.pubkey(), false)],
+        None => plan
+            .callback_accounts
+            .iter()
+            .map(CallbackAccountArg::to_meta)
+            .collect(),
+    };
+
+    let user_contribution =
+        UserContribution::derive(plan.user_secret.as_deref(), &request_account.pubkey());
+
+    let entropy_ix = request_with_callback(
+        plan.entropy_program_id,
+        &RequestAccounts {
+            requester_program: plan.requester_program_id,
+            payer: payer.pubkey(),
+            request_account: request_account.pubkey(),
+            provider_authority: plan.provider_authority,
+        },
+        user_contribution.user_randomness,
+        &RequestParams {
+            compute_unit_limit: plan.compute_unit_limit,
+            ttl_slots: plan.ttl_slots,
+            min_reveal_delay_slots: plan.min_reveal_delay_slots,
+            include_contributions: plan.include_contributions,
+            random_number_version: plan.random_number_version,
+            refund_address: plan.refund_address,
+            callback_accounts: &callback_accounts,
+            callback_ix_data: &plan.callback_ix_data,
+        },
+    )?;
+    instructions.push(forward_request_with_callback(
+        plan.requester_program_id,
+        &entropy_ix,
+    ));
+
+    let mut signers: Vec<&Keypair> = vec![payer, &request_account];
+    if let Some(callback_state) = &callback_state {
+        signers.push(callback_state);
     }
 
-    print_info("Shutdown requested; exiting");
-    Ok(())
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    transaction.try_sign(&signers, recent_blockhash)?;
+
+    Ok(BuiltRequest {
+        transaction,
+        request_account: request_account.pubkey(),
+        callback_state: callback_state.map(|keypair| keypair.pubkey()),
+        user_contribution,
+    })
 }
 
-fn handle_request(args: RequestArgs) -> Result<()> {
+fn handle_request(args: RequestArgs, dry_run: bool) -> Result<()> {
     let keypair_path = expand_path(&args.shared.keypair)
         .with_context(|| format!("Invalid keypair path: {}", args.shared.keypair))?;
+    let secrets_file = expand_path(&args.secrets_file)?;
     let commitment = args.shared.commitment.to_config();
 
+    if args.count == 0 {
+        return Err(anyhow::anyhow!("--count must be at least 1"));
+    }
+    if !(args.rate.is_finite() && args.rate > 0.0) {
+        return Err(anyhow::anyhow!("--rate must be a positive number"));
+    }
+
     let entropy_program_id = args
-        .entropy_program_id
-        .as_deref()
-        .context("Missing --entropy-program-id (or ENTROPY_PROGRAM_ID)")?;
+        .shared
+        .entropy_program_id(args.entropy_program_id.as_deref())?;
     let requester_program_id = args
-        .requester_program_id
-        .as_deref()
-        .context("Missing --requester-program-id (or SIMPLE_REQUESTER_PROGRAM_ID)")?;
-
-    let entropy_program_id = parse_pubkey(entropy_program_id, "entropy program id")?;
-    let requester_program_id = parse_pubkey(requester_program_id, "requester program id")?;
+        .shared
+        .simple_requester_program_id(
+            args.callback_program
+                .as_deref()
+                .or(args.requester_program_id.as_deref()),
+        )?
+        .context(
+            "Missing --requester-program-id (or SIMPLE_REQUESTER_PROGRAM_ID), --callback-program \
+             or a --cluster with a known simple requester",
+        )?;
     let provider_id = parse_pubkey(&args.provider_id, "provider id")?;
+    let refund_address = args
+        .refund_address
+        .as_deref()
+        .map(|value| parse_pubkey(value, "refund address"))
+        .transpose()?;
 
     let payer = load_keypair(&keypair_path)?;
-    let rpc_client =
-        RpcClient::new_with_commitment(args.shared.rpc_url.clone(), commitment.clone());
+    let rpc_client = RpcClient::new_with_commitment(args.shared.rpc_url().to_string(), commitment);
 
     print_info("Starting request mode");
-    print_kv("rpc url:", &args.shared.rpc_url);
+    print_kv("rpc url:", args.shared.rpc_url());
     print_kv("keypair:", keypair_path.display());
     print_kv("commitment:", format!("{:?}", commitment.commitment));
     print_kv("entropy program:", entropy_program_id);
@@ -803,32 +1686,39 @@ fn handle_request(args: RequestArgs) -> Result<()> {
             provider_account.owner
         ));
     }
-    let provider_data: Provider = *try_from_bytes(&provider_account.data)
-        .unwrap();
+    let provider_data: Provider = *try_from_bytes(&provider_account.data).unwrap();
     let provider_authority = Pubkey::new_from_array(provider_data.provider_authority);
 
-    let (provider_vault, _) = provider_vault_pda(&entropy_program_id, &provider_authority);
-    let (config_account, _) = config_pda(&entropy_program_id);
-    let (pyth_fee_vault, _) = pyth_fee_vault_pda(&entropy_program_id);
-    let (requester_signer, _) = Pubkey::find_program_address(
-        &[REQUESTER_SIGNER_SEED, entropy_program_id.as_ref()],
-        &requester_program_id,
-    );
+    if args.callback_accounts.len() > MAX_CALLBACK_ACCOUNTS {
+        return Err(anyhow::anyhow!(
+            "Too many callback accounts: {} (max {MAX_CALLBACK_ACCOUNTS})",
+            args.callback_accounts.len()
+        ));
+    }
 
-    let request_account = Keypair::new();
-    let callback_state = Keypair::new();
-
-    print_info("Creating callback state account");
-    let callback_state_rent = rpc_client
-        .get_minimum_balance_for_rent_exemption(CALLBACK_STATE_LEN)
-        .context("Failed to fetch rent exemption for callback state")?;
-    let create_callback_state_ix = system_instruction::create_account(
-        &payer.pubkey(),
-        &callback_state.pubkey(),
-        callback_state_rent,
-        CALLBACK_STATE_LEN as u64,
-        &requester_program_id,
-    );
+    let callback_ix_data = match args.callback_data_hex {
+        Some(data) => data,
+        None => {
+            let mut data = Vec::with_capacity(1 + 32);
+            data.push(CALLBACK_ACTION);
+            data.extend_from_slice(entropy_program_id.as_ref());
+            data
+        }
+    };
+    if callback_ix_data.len() > CALLBACK_IX_DATA_LEN {
+        return Err(anyhow::anyhow!(
+            "Callback data too long: {} bytes (max {CALLBACK_IX_DATA_LEN})",
+            callback_ix_data.len()
+        ));
+    }
+
+    let callback_state_rent = if args.callback_accounts.is_empty() {
+        rpc_client
+            .get_minimum_balance_for_rent_exemption(CALLBACK_STATE_LEN)
+            .context("Failed to fetch rent exemption for callback state")?
+    } else {
+        0
+    };
 
     let compute_unit_limit = if provider_data.default_compute_unit_limit > 0 {
         provider_data.default_compute_unit_limit
@@ -836,57 +1726,90 @@ fn handle_request(args: RequestArgs) -> Result<()> {
         DEFAULT_CALLBACK_COMPUTE_UNITS
     };
 
-    let user_randomness = Hash::new_unique().to_bytes();
-    let callback_accounts = [CallbackMeta {
-        pubkey: callback_state.pubkey().to_bytes(),
-        is_signer: 0,
-        is_writable: 1,
-    }];
+    let (config_address, _) = config_pda(&entropy_program_id);
+    let config_data = rpc_client
+        .get_account_data(&config_address)
+        .with_context(|| format!("Failed to fetch entropy config {config_address}"))?;
+    let config = try_from_bytes::<Config>(&config_data)
+        .map_err(|err| anyhow::anyhow!("Failed to parse entropy config: {err}"))?;
+    let fee = RequestFee {
+        request_rent: rpc_client
+            .get_minimum_balance_for_rent_exemption(Request::LEN)
+            .context("Failed to fetch rent exemption for the request account")?,
+        ..request_fee(config, &provider_data, compute_unit_limit)?
+    };
+    let total_lamports = print_request_cost(&fee, callback_state_rent, args.count);
+
+    let plan = RequestPlan {
+        entropy_program_id,
+        requester_program_id,
+        provider_authority,
+        provider_vault: provider_vault_pda(&entropy_program_id, &provider_authority).0,
+        config_account: config_pda(&entropy_program_id).0,
+        pyth_fee_vault: pyth_fee_vault_pda(&entropy_program_id).0,
+        requester_signer: requester_signer_pda(&requester_program_id, &entropy_program_id).0,
+        compute_unit_limit,
+        ttl_slots: args.ttl_slots,
+        min_reveal_delay_slots: args.min_reveal_delay_slots,
+        include_contributions: args.include_contributions,
+        random_number_version: args.random_number_version,
+        refund_address,
+        callback_state_rent,
+        callback_accounts: args.callback_accounts,
+        callback_ix_data,
+        user_secret: args.user_secret,
+    };
 
-    let mut callback_ix_data = Vec::with_capacity(1 + 32);
-    callback_ix_data.push(CALLBACK_ACTION);
-    callback_ix_data.extend_from_slice(entropy_program_id.as_ref());
+    if dry_run {
+        if args.count > 1 {
+            print_info(format!("Simulating one of the {} requests", args.count));
+        }
+        let blockhash = rpc_client.get_latest_blockhash()?;
+        let built = build_request_transaction(&plan, &payer, blockhash)?;
+        print_kv("request account:", built.request_account);
+        print_user_contribution(&built.user_contribution);
+        return simulate::simulate(
+            &rpc_client,
+            &built.transaction,
+            &entropy_program_id,
+            commitment,
+        );
+    }
 
-    let entropy_request_data = build_request_with_callback_data(
-        user_randomness,
-        compute_unit_limit,
-        &callback_accounts,
-        &callback_ix_data,
-    );
+    if total_lamports > args.confirm_above_lamports && !args.yes {
+        if !prompt::can_prompt(args.yes) {
+            anyhow::bail!(
+                "The requests cost {total_lamports} lamports, above --confirm-above-lamports \
+                 ({}); pass --yes to send them",
+                args.confirm_above_lamports
+            );
+        }
+        if !prompt::confirm(&format!("Send for {total_lamports} lamports?"))? {
+            anyhow::bail!("Request cancelled");
+        }
+    }
 
-    let mut requester_data = Vec::with_capacity(1 + entropy_request_data.len());
-    requester_data.push(REQUEST_WITH_CALLBACK_ACTION);
-    requester_data.extend_from_slice(&entropy_request_data);
-
-    let request_with_callback_ix = Instruction {
-        program_id: requester_program_id,
-        data: requester_data,
-        accounts: vec![
-            AccountMeta::new_readonly(requester_signer, false),
-            AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new_readonly(requester_program_id, false),
-            AccountMeta::new(request_account.pubkey(), true),
-            AccountMeta::new(provider_id, false),
-            AccountMeta::new(provider_vault, false),
-            AccountMeta::new_readonly(config_account, false),
-            AccountMeta::new(pyth_fee_vault, false),
-            AccountMeta::new_readonly(system_program::id(), false),
-            AccountMeta::new_readonly(requester_program_id, false),
-            AccountMeta::new_readonly(entropy_program_id, false),
-        ],
-    };
+    if args.count > 1 {
+        return batch::run_batch(
+            Arc::new(rpc_client),
+            Arc::new(payer),
+            Arc::new(plan),
+            args.count,
+            args.rate,
+            commitment,
+        );
+    }
 
-    let mut transaction = Transaction::new_with_payer(
-        &[create_callback_state_ix, request_with_callback_ix],
-        Some(&payer.pubkey()),
-    );
+    if plan.callback_accounts.is_empty() {
+        print_info("Creating callback state account");
+    }
     let blockhash = rpc_client.get_latest_blockhash()?;
-    transaction.sign(&[&payer, &callback_state, &request_account], blockhash);
+    let built = build_request_transaction(&plan, &payer, blockhash)?;
 
     print_info("Submitting request_with_callback transaction");
     let signature = rpc_client
         .send_and_confirm_transaction_with_spinner_and_config(
-            &transaction,
+            &built.transaction,
             commitment,
             RpcSendTransactionConfig {
                 skip_preflight: false,
@@ -898,23 +1821,415 @@ fn handle_request(args: RequestArgs) -> Result<()> {
 
     print_success("Request submitted");
     print_kv("request signature:", signature);
-    print_kv("request account:", request_account.pubkey());
-    print_kv("callback state:", callback_state.pubkey());
-    print_kv("requester signer:", requester_signer);
-    print_kv("provider vault:", provider_vault);
-    print_kv("config:", config_account);
-    print_kv("pyth fee vault:", pyth_fee_vault);
+    print_kv("request account:", built.request_account);
+    print_user_contribution(&built.user_contribution);
+    let record = SecretRecord::new(&built.request_account, &signature, &built.user_contribution);
+    match secrets::store(&secrets_file, &record) {
+        Ok(()) => print_kv("secret stored in:", secrets_file.display()),
+        Err(err) => print_warn(format!("Failed to store user secret: {err:#}")),
+    }
+    match built.callback_state {
+        Some(callback_state) => print_kv("callback state:", callback_state),
+        None => {
+            for meta in &plan.callback_accounts {
+                print_kv(
+                    "callback account:",
+                    format!(
+                        "{} (writable: {}, signer: {})",
+                        meta.pubkey, meta.is_writable, meta.is_signer
+                    ),
+                );
+            }
+        }
+    }
+    print_kv("requester signer:", plan.requester_signer);
+    print_kv("provider vault:", plan.provider_vault);
+    print_kv("config:", plan.config_account);
+    print_kv("pyth fee vault:", plan.pyth_fee_vault);
 
     Ok(())
 }
 
+/// Prints what the requests cost the payer and returns the total in
+/// lamports. The request account's rent is refunded on reveal.
+fn print_request_cost(fee: &RequestFee, callback_state_rent: u64, count: u64) -> u64 {
+    let per_request = fee.total().saturating_add(callback_state_rent);
+    let total = per_request.saturating_mul(count);
+    print_info("Request cost (lamports)");
+    print_kv("provider fee:", fee.provider_fee);
+    print_kv("pyth fee:", fee.pyth_fee);
+    print_kv(
+        "request rent:",
+        format!("{} (refunded on reveal)", fee.request_rent),
+    );
+    print_kv("callback state rent:", callback_state_rent);
+    if count > 1 {
+        print_kv("per request:", per_request);
+        print_kv("requests:", count);
+    }
+    print_kv("total:", total);
+    total
+}
+
+fn print_user_contribution(contribution: &UserContribution) {
+    print_kv("user secret:", &contribution.secret);
+    print_kv(
+        "user randomness:",
+        Hash::new_from_array(contribution.user_randomness),
+    );
+    print_kv(
+        "user commitment:",
+        Hash::new_from_array(contribution.user_commitment),
+    );
+}
+
+fn handle_list_providers(args: ListProvidersArgs) -> Result<()> {
+    let commitment = args.shared.commitment.to_config();
+    let entropy_program_id = args
+        .shared
+        .entropy_program_id(args.entropy_program_id.as_deref())?;
+    let rpc_client = RpcClient::new_with_commitment(args.shared.rpc_url().to_string(), commitment);
+
+    let (config_address, _) = config_pda(&entropy_program_id);
+    let config_data = rpc_client
+        .get_account_data(&config_address)
+        .with_context(|| format!("Failed to fetch entropy config {config_address}"))?;
+    let config = try_from_bytes::<Config>(&config_data)
+        .map_err(|err| anyhow::anyhow!("Failed to parse entropy config: {err}"))?;
+
+    let page_addresses = provider_directory_addresses(&entropy_program_id, config);
+    let mut pages = Vec::with_capacity(page_addresses.len());
+    for (address, account) in page_addresses
+        .iter()
+        .zip(get_multiple_accounts(&rpc_client, &page_addresses)?)
+    {
+        let account =
+            account.with_context(|| format!("Missing provider directory page {address}"))?;
+        let page = try_from_bytes::<ProviderDirectoryPage>(&account.data).map_err(|err| {
+            anyhow::anyhow!("Failed to parse provider directory page {address}: {err}")
+        })?;
+        pages.push(*page);
+    }
+
+    let authorities = listed_providers(&pages);
+    let provider_addresses: Vec<Pubkey> = authorities
+        .iter()
+        .map(|authority| provider_pda(&entropy_program_id, authority).0)
+        .collect();
+    let accounts = get_multiple_accounts(&rpc_client, &provider_addresses)?;
+    print_info(format!("{} listed providers", authorities.len()));
+    for (authority, account) in authorities.iter().zip(accounts) {
+        // The provider was closed after the pages were fetched.
+        let Some(account) = account else {
+            continue;
+        };
+        let provider = try_from_bytes::<Provider>(&account.data)
+            .map_err(|err| anyhow::anyhow!("Failed to parse provider {authority}: {err}"))?;
+        print_kv("authority:", authority);
+        print_kv("fee lamports:", provider.fee_lamports);
+        print_kv("uri:", provider_uri(provider).unwrap_or("-"));
+    }
+    Ok(())
+}
+
+/// `getMultipleAccounts` in batches the RPC accepts.
+fn get_multiple_accounts(
+    rpc_client: &RpcClient,
+    addresses: &[Pubkey],
+) -> Result<Vec<Option<solana_sdk::account::Account>>> {
+    let mut accounts = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        accounts.extend(
+            rpc_client
+                .get_multiple_accounts(chunk)
+                .context("Failed to fetch accounts")?,
+        );
+    }
+    Ok(accounts)
+}
+
+fn handle_provider_info(args: ProviderInfoArgs) -> Result<()> {
+    let commitment = args.shared.commitment.to_config();
+    let entropy_program_id = args
+        .shared
+        .entropy_program_id(args.entropy_program_id.as_deref())?;
+    let authority = parse_pubkey(&args.provider, "provider")?;
+    let rpc_client = RpcClient::new_with_commitment(args.shared.rpc_url().to_string(), commitment);
+
+    let (provider_account, _) = provider_pda(&entropy_program_id, &authority);
+    let provider_data = rpc_client
+        .get_account_data(&provider_account)
+        .with_context(|| format!("Failed to fetch provider account {provider_account}"))?;
+    let provider = try_from_bytes::<Provider>(&provider_data)
+        .map_err(|err| anyhow::anyhow!("Failed to parse provider account: {err}"))?;
+
+    print_info("Provider");
+    print_kv("authority:", authority);
+    print_kv("provider account:", provider_account);
+    print_kv("fee lamports:", provider.fee_lamports);
+    print_kv(
+        "default compute units:",
+        provider.default_compute_unit_limit,
+    );
+    print_kv("sequence number:", provider.sequence_number);
+    print_kv("end sequence number:", provider.end_sequence_number);
+    print_kv("outstanding requests:", provider.outstanding_requests);
+    print_kv("reserved fee lamports:", provider.reserved_fee_lamports);
+    print_kv("reveals:", provider.reveal_count);
+    if let Some(average) = provider.average_reveal_latency_slots() {
+        print_kv("average latency:", format!("{average} slots"));
+        print_kv(
+            "max latency:",
+            format!("{} slots", provider.reveal_latency_max_slots),
+        );
+    }
+
+    let Some(uri) = provider_uri(provider) else {
+        print_warn("Provider has no metadata uri");
+        return Ok(());
+    };
+    print_kv("uri:", uri);
+    validate_provider_uri(uri, args.https_only)?;
+
+    let response = reqwest::blocking::Client::builder()
+        .timeout(PROVIDER_METADATA_TIMEOUT)
+        .build()
+        .context("Failed to build HTTP client")?
+        .get(uri)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to fetch provider metadata from {uri}"))?;
+    let body = response
+        .bytes()
+        .context("Failed to read provider metadata")?;
+    if body.len() > MAX_PROVIDER_METADATA_LEN {
+        anyhow::bail!(
+            "Provider metadata is {} bytes (max {MAX_PROVIDER_METADATA_LEN})",
+            body.len()
+        );
+    }
+    let metadata = ProviderMetadata::from_json(&body)?;
+
+    print_info("Metadata");
+    print_kv("name:", &metadata.name);
+    if let Some(description) = &metadata.description {
+        print_kv("description:", description);
+    }
+    if let Some(email) = &metadata.contact.email {
+        print_kv("contact email:", email);
+    }
+    if let Some(url) = &metadata.contact.url {
+        print_kv("contact url:", url);
+    }
+    for fee in &metadata.fees {
+        let limit = fee.max_compute_unit_limit.map_or_else(
+            || "any compute units".to_string(),
+            |limit| format!("up to {limit} CU"),
+        );
+        let description = fee
+            .description
+            .as_deref()
+            .map(|description| format!(" ({description})"))
+            .unwrap_or_default();
+        print_kv(
+            "fee:",
+            format!("{} lamports for {limit}{description}", fee.fee_lamports),
+        );
+    }
+    // The document is self-reported; the account is what requests pay.
+    if metadata
+        .fees
+        .first()
+        .is_some_and(|fee| fee.fee_lamports != provider.fee_lamports)
+    {
+        print_warn(format!(
+            "Declared base fee differs from the on-chain fee of {} lamports",
+            provider.fee_lamports
+        ));
+    }
+
+    Ok(())
+}
+
+fn handle_verify(args: VerifyArgs) -> Result<()> {
+    let commitment = args.shared.commitment.to_config();
+    let entropy_program_id = args
+        .shared
+        .entropy_program_id(args.entropy_program_id.as_deref())?;
+    let secrets_file = expand_path(&args.secrets_file)?;
+    let rpc_client = RpcClient::new_with_commitment(args.shared.rpc_url().to_string(), commitment);
+    verify::verify(
+        &rpc_client,
+        entropy_program_id,
+        &args.request,
+        args.blockhash,
+        &secrets_file,
+        commitment,
+    )
+}
+
+fn handle_decode_tx(args: DecodeTxArgs) -> Result<()> {
+    let commitment = args.shared.commitment.to_config();
+    let entropy_program_id = args
+        .shared
+        .entropy_program_id(args.entropy_program_id.as_deref())?;
+    let rpc_client = RpcClient::new_with_commitment(args.shared.rpc_url().to_string(), commitment);
+    decode::decode_transaction(&rpc_client, entropy_program_id, &args.signature, commitment)
+}
+
+fn handle_watch(args: WatchArgs) -> Result<()> {
+    let commitment = args.shared.commitment.to_config();
+    let entropy_program_id = args
+        .shared
+        .entropy_program_id(args.entropy_program_id.as_deref())?;
+    let target = match (&args.request, &args.provider) {
+        (Some(request), _) => watch::WatchTarget::Request(parse_pubkey(request, "request")?),
+        (None, Some(provider)) => {
+            watch::WatchTarget::Provider(parse_pubkey(provider, "provider authority")?)
+        }
+        (None, None) => anyhow::bail!("Pass --request or --provider"),
+    };
+    let rpc_client = RpcClient::new_with_commitment(args.shared.rpc_url().to_string(), commitment);
+    watch::watch(
+        &rpc_client,
+        watch::WatchConfig {
+            websocket_url: args
+                .ws_url
+                .unwrap_or_else(|| default_websocket_url(args.shared.rpc_url())),
+            entropy_program_id,
+            target,
+            commitment,
+        },
+    )
+}
+
+fn handle_close_stale_requests(args: CloseStaleRequestsArgs, dry_run: bool) -> Result<()> {
+    let keypair_path = expand_path(&args.shared.keypair)
+        .with_context(|| format!("Invalid keypair path: {}", args.shared.keypair))?;
+    let payer = load_keypair(&keypair_path)?;
+    let commitment = args.shared.commitment.to_config();
+    let entropy_program_id = args
+        .shared
+        .entropy_program_id(args.entropy_program_id.as_deref())?;
+    let rpc_client = RpcClient::new_with_commitment(args.shared.rpc_url().to_string(), commitment);
+    let only_payer = (!args.any_payer).then(|| payer.pubkey());
+
+    print_info("Closing expired requests");
+    print_kv("rpc url:", args.shared.rpc_url());
+    print_kv("program id:", entropy_program_id);
+    match only_payer {
+        Some(only_payer) => print_kv("payer:", only_payer),
+        None => print_kv("payer:", "any"),
+    }
+    stale::close_stale_requests(
+        &rpc_client,
+        &payer,
+        entropy_program_id,
+        only_payer,
+        usize::from(args.batch_size),
+        dry_run,
+        commitment,
+    )
+}
+
+fn handle_lookup_table(args: LookupTableArgs, dry_run: bool) -> Result<()> {
+    let keypair_path = expand_path(&args.shared.keypair)
+        .with_context(|| format!("Invalid keypair path: {}", args.shared.keypair))?;
+    let payer = load_keypair(&keypair_path)?;
+    let commitment = args.shared.commitment.to_config();
+    let entropy_program_id = args
+        .shared
+        .entropy_program_id(args.entropy_program_id.as_deref())?;
+    let rpc_client = RpcClient::new_with_commitment(args.shared.rpc_url().to_string(), commitment);
+    let existing = args
+        .address
+        .as_deref()
+        .map(|value| parse_pubkey(value, "lookup table"))
+        .transpose()?;
+
+    let mut addresses = provider_lookup_addresses(entropy_program_id, payer.pubkey());
+    for program in &args.requester_programs {
+        let program = parse_pubkey(program, "requester program")?;
+        addresses.push(program);
+        addresses.push(requester_signer_pda(&program, &entropy_program_id).0);
+    }
+    for account in &args.accounts {
+        addresses.push(parse_pubkey(account, "account")?);
+    }
+    let mut seen = HashSet::new();
+    addresses.retain(|address| seen.insert(*address));
+
+    print_info("Maintaining the reveal lookup table");
+    print_kv("rpc url:", args.shared.rpc_url());
+    print_kv("program id:", entropy_program_id);
+    print_kv("authority:", payer.pubkey());
+    if let Some(existing) = existing {
+        print_kv("lookup table:", existing);
+    }
+    lookup::maintain_table(
+        &rpc_client,
+        &payer,
+        existing,
+        &addresses,
+        dry_run,
+        commitment,
+    )
+}
+
+fn handle_localnet_setup(args: LocalnetSetupArgs, dry_run: bool) -> Result<()> {
+    if dry_run {
+        anyhow::bail!("localnet-setup does not support --dry-run");
+    }
+    let keypair_path = expand_path(&args.shared.keypair)
+        .with_context(|| format!("Invalid keypair path: {}", args.shared.keypair))?;
+    localnet::setup(localnet::LocalnetConfig {
+        rpc_url: args.shared.rpc_url().to_string(),
+        keypair_path,
+        artifacts_dir: expand_path(&args.artifacts_dir)?,
+        ledger_dir: expand_path(&args.ledger_dir)?,
+        pyth_fee_lamports: args.pyth_fee_lamports,
+        commitment: args.shared.commitment.to_config(),
+    })
+}
+
+fn handle_onboard_provider(args: OnboardProviderArgs, dry_run: bool) -> Result<()> {
+    if dry_run {
+        anyhow::bail!("onboard-provider does not support --dry-run");
+    }
+    let entropy_program_id = args
+        .shared
+        .entropy_program_id(args.entropy_program_id.as_deref())?;
+    onboard::onboard(onboard::OnboardConfig {
+        rpc_url: args.shared.rpc_url().to_string(),
+        entropy_program_id,
+        output_dir: expand_path(&args.output_dir)?,
+        uri: args.uri,
+        https_only: args.https_only,
+        reveal_budget_lamports: args.reveal_budget_lamports,
+        interactive: prompt::can_prompt(args.yes),
+        commitment: args.shared.commitment.to_config(),
+    })
+}
+
 fn main() -> Result<()> {
     init_tracing();
     let cli = Cli::parse();
 
     let result = match cli.command {
-        Command::Provide(args) => handle_provide(args),
-        Command::Request(args) => handle_request(args),
+        Command::Provide(args) => handle_provide(args, cli.dry_run),
+        Command::Request(args) => handle_request(args, cli.dry_run),
+        Command::ProviderInfo(args) => handle_provider_info(args),
+        Command::ListProviders(args) => handle_list_providers(args),
+        Command::Verify(args) => handle_verify(args),
+        Command::DecodeTx(args) => handle_decode_tx(args),
+        Command::Watch(args) => handle_watch(args),
+        Command::CloseStaleRequests(args) => handle_close_stale_requests(args, cli.dry_run),
+        Command::LookupTable(args) => handle_lookup_table(args, cli.dry_run),
+        Command::LocalnetSetup(args) => handle_localnet_setup(args, cli.dry_run),
+        Command::OnboardProvider(args) => handle_onboard_provider(args, cli.dry_run),
+        Command::Journal(JournalArgs {
+            command: JournalCommand::Stats { path },
+        }) => expand_path(&path).and_then(journal::print_stats),
     };
 
     if let Err(err) = result {
@@ -0,0 +1,218 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use bytemuck::try_from_bytes;
+use entropy::{
+    accounts::{Provider, ProviderDirectoryPage},
+    pda::provider_pda,
+};
+use entropy_sdk::{provider_uri, validate_provider_uri};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::{keypair::write_keypair_file, Signer},
+};
+
+use crate::{
+    chain_state::{self, ChainStore},
+    load_keypair, print_info, print_kv, print_success, print_warn,
+    prompt::{confirm, prompt},
+    register_chain,
+};
+
+const KEYPAIR_FILE: &str = "provider-keypair.json";
+const CHAIN_STATE_DIR: &str = "chain-state";
+const KEEPER_ENV_FILE: &str = "keeper.env";
+const FUNDING_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+pub(crate) struct OnboardConfig {
+    pub(crate) rpc_url: String,
+    pub(crate) entropy_program_id: Pubkey,
+    pub(crate) output_dir: PathBuf,
+    pub(crate) uri: Option<String>,
+    pub(crate) https_only: bool,
+    pub(crate) reveal_budget_lamports: u64,
+    /// Prompt for what flags leave open and wait for funding.
+    pub(crate) interactive: bool,
+    pub(crate) commitment: CommitmentConfig,
+}
+
+/// Sets up a new provider in `output_dir`: its keypair, its registration
+/// with a fresh hash chain persisted for the keeper, and a keeper config.
+/// Rerunning it resumes an onboarding that stopped part way.
+pub(crate) fn onboard(config: OnboardConfig) -> Result<()> {
+    fs::create_dir_all(&config.output_dir)
+        .with_context(|| format!("Failed to create {}", config.output_dir.display()))?;
+    let output_dir = config
+        .output_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", config.output_dir.display()))?;
+    let rpc_client = RpcClient::new_with_commitment(config.rpc_url.clone(), config.commitment);
+
+    let keypair_path = output_dir.join(KEYPAIR_FILE);
+    let keypair = if keypair_path.exists() {
+        print_info("Reusing the provider keypair");
+        load_keypair(&keypair_path)?
+    } else {
+        let keypair = Keypair::new();
+        write_keypair_file(&keypair, &keypair_path)
+            .map_err(|err| anyhow::anyhow!("Failed to write {}: {err}", keypair_path.display()))?;
+        print_success("Generated a provider keypair");
+        keypair
+    };
+    let authority = keypair.pubkey();
+    print_kv("provider authority:", authority);
+    print_kv("keypair:", keypair_path.display());
+
+    let chain_state_dir = output_dir.join(CHAIN_STATE_DIR);
+    let store = ChainStore::new(&chain_state_dir, authority);
+    let (provider_account, _) = provider_pda(&config.entropy_program_id, &authority);
+    let on_chain = rpc_client
+        .get_account_data(&provider_account)
+        .ok()
+        .and_then(|data| try_from_bytes::<Provider>(&data).ok().copied());
+    let persisted = store.load()?;
+
+    let uri = match (&persisted, on_chain) {
+        (Some(state), Some(provider)) if chain_state::check(state, Some(&provider)).is_ok() => {
+            print_success("Provider already registered with the persisted hash chain");
+            provider_uri(&provider).unwrap_or_default().to_string()
+        }
+        (_, Some(_)) => anyhow::bail!(
+            "Provider {authority} is registered with a hash chain not persisted in {}; \
+             run `entropy provide` with its keypair to rotate to a new one",
+            chain_state_dir.display()
+        ),
+        (_, None) => {
+            let uri = match &config.uri {
+                Some(uri) => uri.clone(),
+                None if config.interactive => prompt("Provider metadata URI (empty for none):")?,
+                None => String::new(),
+            };
+            if !uri.is_empty() {
+                validate_provider_uri(&uri, config.https_only)?;
+            }
+            wait_for_funds(&rpc_client, &authority, &config)?;
+            if config.interactive
+                && !confirm(&format!(
+                    "Register provider {authority} with entropy {} at {}?",
+                    config.entropy_program_id, config.rpc_url
+                ))?
+            {
+                anyhow::bail!("Onboarding cancelled; rerun to continue");
+            }
+
+            print_info("Registering provider...");
+            let (hash_chain, _) = register_chain(
+                &rpc_client,
+                &keypair,
+                config.entropy_program_id,
+                &uri,
+                config.https_only,
+                config.commitment,
+            )?;
+            store.save([&hash_chain], None)?;
+            print_success("Provider registered");
+            uri
+        }
+    };
+    print_kv("provider account:", provider_account);
+    print_kv("chain state:", store.path().display());
+
+    let env_path = output_dir.join(KEEPER_ENV_FILE);
+    write_keeper_env(
+        &env_path,
+        &config,
+        &keypair_path,
+        &chain_state_dir,
+        &output_dir,
+        &uri,
+    )?;
+    print_kv("keeper config:", env_path.display());
+
+    print_info("Next steps");
+    println!(
+        "1. Back up {} and {}; losing either stops reveals for this provider.",
+        keypair_path.display(),
+        chain_state_dir.display()
+    );
+    println!("2. Keep {authority} funded; it pays the fees of every reveal.");
+    println!(
+        "3. Start the keeper: set -a; . {}; set +a; entropy provide",
+        env_path.display()
+    );
+    Ok(())
+}
+
+/// Registration rent plus the reveal budget. A new directory page is only
+/// paid for by the provider that fills the last one, but is counted anyway.
+fn required_balance(rpc_client: &RpcClient, reveal_budget_lamports: u64) -> Result<u64> {
+    let mut required = reveal_budget_lamports;
+    for len in [Provider::LEN, 0, ProviderDirectoryPage::LEN] {
+        required += rpc_client.get_minimum_balance_for_rent_exemption(len)?;
+    }
+    Ok(required)
+}
+
+fn wait_for_funds(
+    rpc_client: &RpcClient,
+    authority: &Pubkey,
+    config: &OnboardConfig,
+) -> Result<()> {
+    let required = required_balance(rpc_client, config.reveal_budget_lamports)?;
+    let mut announced = false;
+    loop {
+        let balance = rpc_client.get_balance(authority)?;
+        if balance >= required {
+            print_kv("balance:", format!("{balance} lamports"));
+            return Ok(());
+        }
+        let message = format!(
+            "{authority} holds {balance} lamports but needs {required} for registration rent \
+             and reveal fees"
+        );
+        if !config.interactive {
+            anyhow::bail!("{message}; fund it and rerun");
+        }
+        if !announced {
+            print_warn(format!("{message}; waiting for funds (Ctrl-C to stop)"));
+            announced = true;
+        }
+        std::thread::sleep(FUNDING_POLL_INTERVAL);
+    }
+}
+
+/// Environment for `entropy provide`, usable as a systemd `EnvironmentFile`
+/// or sourced by a shell.
+fn write_keeper_env(
+    path: &Path,
+    config: &OnboardConfig,
+    keypair_path: &Path,
+    chain_state_dir: &Path,
+    output_dir: &Path,
+    uri: &str,
+) -> Result<()> {
+    let mut env = format!(
+        "SOLANA_RPC_URL={}\nSOLANA_KEYPAIR={}\nENTROPY_PROGRAM_ID={}\n\
+         ENTROPY_CHAIN_STATE_DIR={}\nENTROPY_REQUEST_DB={}\nENTROPY_JOURNAL={}\n",
+        config.rpc_url,
+        keypair_path.display(),
+        config.entropy_program_id,
+        chain_state_dir.display(),
+        output_dir.join("requests.db").display(),
+        output_dir.join("keeper.jsonl").display(),
+    );
+    if !uri.is_empty() {
+        env.push_str(&format!("ENTROPY_PROVIDER_URI={uri}\n"));
+    }
+    if config.https_only {
+        env.push_str("ENTROPY_HTTPS_ONLY=true\n");
+    }
+    fs::write(path, env).with_context(|| format!("Failed to write {}", path.display()))
+}
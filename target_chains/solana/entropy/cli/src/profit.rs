@@ -0,0 +1,99 @@
+use anyhow::{Context, Result};
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, instruction::Instruction, message::Message,
+    pubkey::Pubkey, transaction::Transaction,
+};
+
+/// Compute unit prices are quoted in micro-lamports per CU.
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Estimated cost of landing a reveal versus the fee the provider earned for it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RevealEstimate {
+    pub(crate) compute_units: u64,
+    pub(crate) network_fee: u64,
+    pub(crate) priority_fee: u64,
+    pub(crate) tip: u64,
+    pub(crate) provider_fee: u64,
+}
+
+impl RevealEstimate {
+    pub(crate) fn cost(&self) -> u64 {
+        self.network_fee
+            .saturating_add(self.priority_fee)
+            .saturating_add(self.tip)
+    }
+
+    /// Provider fee minus landing cost; negative for loss-making reveals.
+    pub(crate) fn margin(&self) -> i128 {
+        i128::from(self.provider_fee) - i128::from(self.cost())
+    }
+
+    pub(crate) fn is_profitable(&self) -> bool {
+        self.margin() >= 0
+    }
+}
+
+/// Simulates the reveal to measure its compute units, then prices it at the
+/// current signature fee plus the median recent priority fee paid on the
+/// accounts it writes. `tip` covers any out-of-band payment such as a Jito tip.
+pub(crate) fn estimate_reveal(
+    rpc_client: &RpcClient,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    provider_fee: u64,
+    tip: u64,
+    commitment: CommitmentConfig,
+) -> Result<RevealEstimate> {
+    let recent_blockhash = rpc_client.get_latest_blockhash()?;
+    let message = Message::new_with_blockhash(instructions, Some(payer), &recent_blockhash);
+
+    let simulation = rpc_client
+        .simulate_transaction_with_config(
+            &Transaction::new_unsigned(message.clone()),
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(commitment),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .context("Failed to simulate reveal")?
+        .value;
+    if let Some(err) = simulation.err {
+        return Err(anyhow::anyhow!("Reveal simulation failed: {err}"));
+    }
+    let compute_units = simulation
+        .units_consumed
+        .context("Simulation did not report compute units")?;
+
+    let network_fee = rpc_client
+        .get_fee_for_message(&message)
+        .context("Failed to fetch network fee")?;
+
+    let writable_accounts: Vec<Pubkey> = instructions
+        .iter()
+        .flat_map(|instruction| &instruction.accounts)
+        .filter(|meta| meta.is_writable)
+        .map(|meta| meta.pubkey)
+        .collect();
+    let mut prices: Vec<u64> = rpc_client
+        .get_recent_prioritization_fees(&writable_accounts)
+        .context("Failed to fetch recent prioritization fees")?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+    prices.sort_unstable();
+    let unit_price = prices.get(prices.len() / 2).copied().unwrap_or_default();
+    let priority_fee =
+        (u128::from(compute_units) * u128::from(unit_price)).div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+
+    Ok(RevealEstimate {
+        compute_units,
+        network_fee,
+        priority_fee: u64::try_from(priority_fee).unwrap_or(u64::MAX),
+        tip,
+        provider_fee,
+    })
+}
@@ -0,0 +1,22 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::Result;
+
+/// Whether prompts can be answered: stdin is a terminal and `--yes` was not
+/// passed.
+pub(crate) fn can_prompt(yes: bool) -> bool {
+    !yes && io::stdin().is_terminal()
+}
+
+pub(crate) fn prompt(question: &str) -> Result<String> {
+    print!("{question} ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+pub(crate) fn confirm(question: &str) -> Result<bool> {
+    let answer = prompt(&format!("{question} [y/N]"))?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,225 @@
+use std::{
+    path::Path,
+    str::FromStr,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tracing::warn;
+
+use crate::journal::now_ms;
+
+/// Processed transactions more than this many slots older than the newest
+/// one are forgotten. Polling only looks past the newest, so this only
+/// needs to cover transactions confirmed out of slot order.
+const SIGNATURE_RETENTION_SLOTS: u64 = 10_000;
+
+/// At most this many processed transactions are kept, newest first, however
+/// busy the program is.
+const MAX_PROCESSED_SIGNATURES: u64 = 50_000;
+
+/// Queued reveals left unfinished this long are forgotten; their requests
+/// have expired by then.
+const PENDING_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS processed_signature (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS processed_signature_slot ON processed_signature (slot);
+CREATE TABLE IF NOT EXISTS pending_reveal (
+    request_account TEXT PRIMARY KEY,
+    provider TEXT NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    user_randomness BLOB NOT NULL,
+    queued_at_ms INTEGER NOT NULL
+);
+";
+
+/// A queued reveal read back from the store.
+pub(crate) struct StoredReveal {
+    pub(crate) request_account: Pubkey,
+    pub(crate) user_randomness: [u8; 32],
+}
+
+/// SQLite record of the transactions the keeper has processed and the
+/// reveals it has queued but not finished. With a file, a restarted keeper
+/// neither processes a transaction twice nor loses queued work; without
+/// one it lives in memory. Either way pruning keeps it bounded.
+///
+/// Failures are logged rather than returned, like the [`Journal`]'s: a
+/// broken store costs duplicate RPC reads, never reveals.
+///
+/// [`Journal`]: crate::journal::Journal
+pub(crate) struct RequestStore {
+    connection: Mutex<Connection>,
+}
+
+impl RequestStore {
+    /// Opens (creating if needed) the store at `path`, or an in-memory one.
+    pub(crate) fn open(path: Option<&Path>) -> Result<Self> {
+        let connection = match path {
+            Some(path) => Connection::open(path)
+                .with_context(|| format!("Failed to open request store {}", path.display()))?,
+            None => Connection::open_in_memory()?,
+        };
+        connection
+            .execute_batch(SCHEMA)
+            .context("Failed to create the request store schema")?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn connection(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.connection
+            .lock()
+            .map_err(|_| anyhow::anyhow!("request store lock poisoned"))
+    }
+
+    fn log_failure<T: Default>(action: &str, result: Result<T>) -> T {
+        result.unwrap_or_else(|err| {
+            warn!(error = %err, "Failed to {action} in the request store");
+            T::default()
+        })
+    }
+
+    /// The newest processed transaction, which polling continues from.
+    pub(crate) fn newest_signature(&self) -> Option<Signature> {
+        let newest = self.connection().and_then(|connection| {
+            let signature: Option<String> = connection
+                .query_row(
+                    "SELECT signature FROM processed_signature \
+                     ORDER BY slot DESC, rowid DESC LIMIT 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(signature.and_then(|signature| Signature::from_str(&signature).ok()))
+        });
+        Self::log_failure("read the newest signature", newest)
+    }
+
+    pub(crate) fn is_processed(&self, signature: &str) -> bool {
+        let processed = self.connection().and_then(|connection| {
+            Ok(connection
+                .query_row(
+                    "SELECT 1 FROM processed_signature WHERE signature = ?1",
+                    [signature],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some())
+        });
+        Self::log_failure("look up a signature", processed)
+    }
+
+    /// Recorded once the transaction's requests are queued, so a crash
+    /// before then processes it again.
+    pub(crate) fn mark_processed(&self, signature: &str, slot: u64) {
+        let marked = self.connection().and_then(|connection| {
+            connection.execute(
+                "INSERT OR IGNORE INTO processed_signature (signature, slot) VALUES (?1, ?2)",
+                params![signature, slot as i64],
+            )?;
+            Ok(())
+        });
+        Self::log_failure("record a signature", marked)
+    }
+
+    /// Records a queued reveal. Returns false if the request is already
+    /// queued, in which case it must not be queued again.
+    pub(crate) fn track(
+        &self,
+        provider: &Pubkey,
+        request_account: &Pubkey,
+        sequence_number: u64,
+        user_randomness: &[u8; 32],
+    ) -> bool {
+        let inserted = self.connection().and_then(|connection| {
+            Ok(connection.execute(
+                "INSERT OR IGNORE INTO pending_reveal \
+                 (request_account, provider, sequence_number, user_randomness, queued_at_ms) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    request_account.to_string(),
+                    provider.to_string(),
+                    sequence_number as i64,
+                    user_randomness.as_slice(),
+                    now_ms() as i64
+                ],
+            )? > 0)
+        });
+        // Without the store, queueing twice beats not queueing at all.
+        inserted.unwrap_or_else(|err| {
+            warn!(error = %err, "Failed to record a queued reveal in the request store");
+            true
+        })
+    }
+
+    /// Forgets a reveal that was made or given up on.
+    pub(crate) fn finish(&self, request_account: &Pubkey) {
+        let finished = self.connection().and_then(|connection| {
+            connection.execute(
+                "DELETE FROM pending_reveal WHERE request_account = ?1",
+                [request_account.to_string()],
+            )?;
+            Ok(())
+        });
+        Self::log_failure("finish a queued reveal", finished)
+    }
+
+    /// Reveals still queued for `provider`, oldest first.
+    pub(crate) fn pending(&self, provider: &Pubkey) -> Vec<StoredReveal> {
+        let pending = self.connection().and_then(|connection| {
+            let mut statement = connection.prepare(
+                "SELECT request_account, user_randomness FROM pending_reveal \
+                 WHERE provider = ?1 ORDER BY queued_at_ms, sequence_number",
+            )?;
+            let rows = statement
+                .query_map([provider.to_string()], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows
+                .into_iter()
+                .filter_map(|(request_account, user_randomness)| {
+                    Some(StoredReveal {
+                        request_account: Pubkey::from_str(&request_account).ok()?,
+                        user_randomness: user_randomness.try_into().ok()?,
+                    })
+                })
+                .collect())
+        });
+        Self::log_failure("read queued reveals", pending)
+    }
+
+    /// Forgets signatures too old to be polled again, the oldest beyond
+    /// [`MAX_PROCESSED_SIGNATURES`], and queued reveals too old to still be
+    /// pending.
+    pub(crate) fn prune(&self) {
+        let pruned = self.connection().and_then(|connection| {
+            connection.execute(
+                "DELETE FROM processed_signature WHERE slot < \
+                 (SELECT MAX(slot) FROM processed_signature) - ?1",
+                [SIGNATURE_RETENTION_SLOTS as i64],
+            )?;
+            connection.execute(
+                "DELETE FROM processed_signature WHERE rowid NOT IN \
+                 (SELECT rowid FROM processed_signature \
+                 ORDER BY slot DESC, rowid DESC LIMIT ?1)",
+                [MAX_PROCESSED_SIGNATURES as i64],
+            )?;
+            connection.execute(
+                "DELETE FROM pending_reveal WHERE queued_at_ms < ?1",
+                [now_ms().saturating_sub(PENDING_RETENTION.as_millis() as u64) as i64],
+            )?;
+            Ok(())
+        });
+        Self::log_failure("prune", pruned)
+    }
+}
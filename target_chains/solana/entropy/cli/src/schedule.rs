@@ -0,0 +1,146 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use entropy::accounts::Request;
+use solana_sdk::pubkey::Pubkey;
+
+/// Order in which queued callback requests are revealed.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RevealOrder {
+    /// Lowest sequence number first.
+    Fifo,
+    /// Highest provider fee first. The fee grows with the compute unit limit
+    /// the requester paid for, so this serves the best-paying requests first.
+    Tip,
+    /// Oldest request slot first.
+    Age,
+}
+
+/// A callback request waiting for its reveal.
+pub(crate) struct PendingReveal {
+    pub(crate) request_account: Pubkey,
+    pub(crate) request: Request,
+    pub(crate) user_randomness: [u8; 32],
+    pub(crate) provider_fee: u64,
+    /// Held back by the requester limit at least once; the request account
+    /// may have changed since it was observed.
+    pub(crate) deferred: bool,
+}
+
+impl PendingReveal {
+    fn requester(&self) -> Pubkey {
+        Pubkey::new_from_array(self.request.requester_program_id)
+    }
+}
+
+/// At most `max_reveals` reveals per requester program in any `window`.
+pub(crate) struct RequesterLimit {
+    pub(crate) max_reveals: usize,
+    pub(crate) window: Duration,
+}
+
+/// Queue of pending reveals. Reveals over a requester's limit stay queued
+/// for a later round instead of being dropped, so a requester that floods
+/// the provider only delays its own requests.
+pub(crate) struct RevealScheduler {
+    order: RevealOrder,
+    limit: Option<RequesterLimit>,
+    pending: Vec<PendingReveal>,
+    /// Admission times per requester within the current window.
+    admitted: HashMap<Pubkey, VecDeque<Instant>>,
+}
+
+impl RevealScheduler {
+    pub(crate) fn new(order: RevealOrder, limit: Option<RequesterLimit>) -> Self {
+        Self {
+            order,
+            limit,
+            pending: Vec::new(),
+            admitted: HashMap::new(),
+        }
+    }
+
+    /// Queues `reveal` unless its request account is already queued.
+    pub(crate) fn push(&mut self, reveal: PendingReveal) {
+        if self
+            .pending
+            .iter()
+            .all(|queued| queued.request_account != reveal.request_account)
+        {
+            self.pending.push(reveal);
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Queued reveals, in no particular order.
+    pub(crate) fn pending(&self) -> &[PendingReveal] {
+        &self.pending
+    }
+
+    /// Removes and returns the reveals to submit now, in order. Also returns
+    /// how many reveals the requester limit held back for the first time.
+    pub(crate) fn take_due(&mut self, now: Instant) -> (Vec<PendingReveal>, u64) {
+        let mut pending = std::mem::take(&mut self.pending);
+        match self.order {
+            RevealOrder::Fifo => pending.sort_by_key(|reveal| reveal.request.sequence_number),
+            RevealOrder::Tip => pending.sort_by_key(|reveal| {
+                (
+                    std::cmp::Reverse(reveal.provider_fee),
+                    reveal.request.sequence_number,
+                )
+            }),
+            RevealOrder::Age => pending.sort_by_key(|reveal| {
+                (reveal.request.request_slot, reveal.request.sequence_number)
+            }),
+        }
+
+        // Requesters with nothing admitted in the window are forgotten, so
+        // the map does not grow with every requester ever seen.
+        if let Some(limit) = &self.limit {
+            self.admitted.retain(|_, admitted| {
+                admitted
+                    .back()
+                    .is_some_and(|at| now.duration_since(*at) < limit.window)
+            });
+        }
+
+        let mut due = Vec::new();
+        let mut newly_deferred = 0;
+        for mut reveal in pending {
+            if self.admit(reveal.requester(), now) {
+                due.push(reveal);
+            } else {
+                if !reveal.deferred {
+                    newly_deferred += 1;
+                }
+                reveal.deferred = true;
+                self.pending.push(reveal);
+            }
+        }
+        (due, newly_deferred)
+    }
+
+    fn admit(&mut self, requester: Pubkey, now: Instant) -> bool {
+        let Some(limit) = &self.limit else {
+            return true;
+        };
+        let admitted = self.admitted.entry(requester).or_default();
+        while admitted
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= limit.window)
+        {
+            admitted.pop_front();
+        }
+        if admitted.len() >= limit.max_reveals {
+            return false;
+        }
+        admitted.push_back(now);
+        true
+    }
+}
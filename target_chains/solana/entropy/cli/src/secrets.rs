@@ -0,0 +1,140 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    hash::{hash, hashv},
+    pubkey::Pubkey,
+    signature::Signature,
+};
+use tracing::warn;
+
+use crate::journal::now_ms;
+
+/// Default file the request command stores user secrets in.
+pub(crate) const DEFAULT_SECRETS_FILE: &str = "entropy-secrets.jsonl";
+
+/// The user's side of a request's commit-reveal: the secret it was derived
+/// from, the randomness sent with the request and the commitment the program
+/// stores for it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct UserContribution {
+    pub(crate) secret: String,
+    pub(crate) user_randomness: [u8; 32],
+    pub(crate) user_commitment: [u8; 32],
+}
+
+impl UserContribution {
+    /// Derives the contribution for `request_account` from `secret`, or from
+    /// a fresh random secret. Mixing in the request account keeps the
+    /// randomness distinct across requests that reuse a secret.
+    pub(crate) fn derive(secret: Option<&str>, request_account: &Pubkey) -> Self {
+        let secret = secret.map_or_else(random_secret, str::to_string);
+        let user_randomness = hashv(&[secret.as_bytes(), request_account.as_ref()]).to_bytes();
+        Self {
+            user_commitment: hash(&user_randomness).to_bytes(),
+            secret,
+            user_randomness,
+        }
+    }
+}
+
+fn random_secret() -> String {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    bs58::encode(secret).into_string()
+}
+
+/// One stored request, written as a single JSON line.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SecretRecord {
+    /// Milliseconds since the Unix epoch.
+    pub(crate) at_ms: u64,
+    pub(crate) request_account: String,
+    pub(crate) signature: String,
+    pub(crate) secret: String,
+    /// Base58.
+    pub(crate) user_randomness: String,
+    /// Base58.
+    pub(crate) user_commitment: String,
+}
+
+impl SecretRecord {
+    pub(crate) fn new(
+        request_account: &Pubkey,
+        signature: &Signature,
+        contribution: &UserContribution,
+    ) -> Self {
+        Self {
+            at_ms: now_ms(),
+            request_account: request_account.to_string(),
+            signature: signature.to_string(),
+            secret: contribution.secret.clone(),
+            user_randomness: bs58::encode(contribution.user_randomness).into_string(),
+            user_commitment: bs58::encode(contribution.user_commitment).into_string(),
+        }
+    }
+
+    /// Recomputes the contribution from the stored secret, so a tampered or
+    /// mistyped record is not mistaken for the one that was sent.
+    pub(crate) fn contribution(&self) -> Result<UserContribution> {
+        let request_account = self
+            .request_account
+            .parse()
+            .with_context(|| format!("Invalid request account {}", self.request_account))?;
+        let contribution = UserContribution::derive(Some(&self.secret), &request_account);
+        if bs58::encode(contribution.user_randomness).into_string() != self.user_randomness {
+            anyhow::bail!(
+                "Stored user randomness for {} does not match its secret",
+                self.request_account
+            );
+        }
+        Ok(contribution)
+    }
+}
+
+/// Appends `record` to the secrets file at `path`.
+pub(crate) fn store(path: &Path, record: &SecretRecord) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open secrets file {}", path.display()))?;
+    let line = serde_json::to_string(record)?;
+    writeln!(file, "{line}")
+        .with_context(|| format!("Failed to write secrets file {}", path.display()))
+}
+
+/// The stored record for `request_account`, if the secrets file at `path`
+/// has one. A missing file has none.
+pub(crate) fn find(path: &Path, request_account: &Pubkey) -> Result<Option<SecretRecord>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("Failed to open secrets file {}", path.display()))
+        }
+    };
+    let request_account = request_account.to_string();
+    let mut found = None;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.context("Failed to read secrets file")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<SecretRecord>(&line) {
+            Ok(record) if record.request_account == request_account => found = Some(record),
+            Ok(_) => {}
+            Err(err) => {
+                warn!(line = index + 1, error = %err, "Skipping malformed secrets entry");
+            }
+        }
+    }
+    Ok(found)
+}
@@ -0,0 +1,118 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{print_info, print_kv, print_warn};
+
+/// Granularity at which idle sleeps notice a shutdown request.
+const SLEEP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Counters of one provider served by the keeper, reported when the
+/// process exits.
+#[derive(Default)]
+pub(crate) struct KeeperStats {
+    /// Authority of the provider these counters belong to.
+    pub(crate) provider: Pubkey,
+    pub(crate) observed: AtomicU64,
+    pub(crate) revealed: AtomicU64,
+    pub(crate) failed: AtomicU64,
+    pub(crate) skipped_unprofitable: AtomicU64,
+    pub(crate) skipped_on_shutdown: AtomicU64,
+    pub(crate) deferred_rate_limited: AtomicU64,
+    pub(crate) swept_lamports: AtomicU64,
+}
+
+impl KeeperStats {
+    pub(crate) fn new(provider: Pubkey) -> Self {
+        Self {
+            provider,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn record(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn print_summary(&self) {
+        print_info(format!("Keeper summary for {}", self.provider));
+        print_kv("requests observed:", self.observed.load(Ordering::Relaxed));
+        print_kv("reveals confirmed:", self.revealed.load(Ordering::Relaxed));
+        print_kv("reveals failed:", self.failed.load(Ordering::Relaxed));
+        print_kv(
+            "skipped as unprofitable:",
+            self.skipped_unprofitable.load(Ordering::Relaxed),
+        );
+        print_kv(
+            "skipped on shutdown:",
+            self.skipped_on_shutdown.load(Ordering::Relaxed),
+        );
+        print_kv(
+            "deferred by requester limit:",
+            self.deferred_rate_limited.load(Ordering::Relaxed),
+        );
+        print_kv(
+            "lamports swept:",
+            self.swept_lamports.load(Ordering::Relaxed),
+        );
+    }
+}
+
+/// Ctrl-C driven shutdown. The first signal stops new work and lets the
+/// in-flight reveal finish within `drain_timeout`; a second signal (or the
+/// timeout) exits immediately with a summary.
+pub(crate) struct Shutdown {
+    running: Arc<AtomicBool>,
+}
+
+impl Shutdown {
+    pub(crate) fn install(drain_timeout: Duration, stats: Vec<Arc<KeeperStats>>) -> Result<Self> {
+        let running = Arc::new(AtomicBool::new(true));
+        let handler_running = running.clone();
+        ctrlc::set_handler(move || {
+            if !handler_running.swap(false, Ordering::SeqCst) {
+                print_warn("Forced shutdown");
+                stats.iter().for_each(|stats| stats.print_summary());
+                std::process::exit(130);
+            }
+
+            print_warn(format!(
+                "Shutdown requested; draining in-flight reveals for up to {drain_timeout:?} \
+                 (press Ctrl-C again to force)"
+            ));
+            let stats = stats.clone();
+            thread::spawn(move || {
+                thread::sleep(drain_timeout);
+                print_warn("Drain timed out; exiting with reveals still in flight");
+                stats.iter().for_each(|stats| stats.print_summary());
+                std::process::exit(1);
+            });
+        })
+        .context("Failed to set Ctrl-C handler")?;
+
+        Ok(Self { running })
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Sleeps for `duration`, returning early if shutdown is requested.
+    pub(crate) fn sleep(&self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        while self.is_running() {
+            let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                return;
+            };
+            thread::sleep(remaining.min(SLEEP_POLL_INTERVAL));
+        }
+    }
+}
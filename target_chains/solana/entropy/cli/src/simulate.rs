@@ -0,0 +1,214 @@
+use anyhow::{Context, Result};
+use entropy_sdk::{parse_callback_failure, parse_logs, EntropyEvent};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+};
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, hash::Hash, instruction::Instruction,
+    pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction,
+};
+
+use crate::{decode::print_callback_failure, print_error, print_info, print_kv, print_success};
+
+/// Builds and signs a transaction of `instructions` paid by `payer`, then
+/// simulates it; see [`simulate`].
+pub(crate) fn simulate_instructions(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    entropy_program_id: &Pubkey,
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    let recent_blockhash = rpc_client.get_latest_blockhash()?;
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    transaction.sign(&[payer], recent_blockhash);
+    simulate(rpc_client, &transaction, entropy_program_id, commitment)
+}
+
+/// `--dry-run` in place of sending `transaction`: simulates it and prints
+/// its logs, the entropy events it would emit, the compute units used and
+/// how each writable account would change. A failed simulation is reported
+/// as an error.
+pub(crate) fn simulate(
+    rpc_client: &RpcClient,
+    transaction: &Transaction,
+    entropy_program_id: &Pubkey,
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    let writable = writable_accounts(transaction);
+    let before = rpc_client
+        .get_multiple_accounts(&writable)
+        .context("Failed to fetch accounts before simulation")?;
+    let result = rpc_client
+        .simulate_transaction_with_config(
+            transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: true,
+                commitment: Some(commitment),
+                accounts: Some(RpcSimulateTransactionAccountsConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    addresses: writable.iter().map(Pubkey::to_string).collect(),
+                }),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .context("Failed to simulate transaction")?
+        .value;
+
+    print_info("Simulation (dry run, nothing sent)");
+    if let Some(units) = result.units_consumed {
+        print_kv("compute units:", units);
+    }
+    let logs = result.logs.unwrap_or_default();
+    for event in parse_logs(entropy_program_id, &logs) {
+        print_kv("event:", describe_event(&event));
+    }
+    for line in &logs {
+        print_kv("log:", line);
+    }
+
+    let after = result.accounts.unwrap_or_default();
+    for (index, address) in writable.iter().enumerate() {
+        let before = before.get(index).cloned().flatten();
+        let after = after
+            .get(index)
+            .cloned()
+            .flatten()
+            .and_then(|account| account.decode::<Account>());
+        if let Some(diff) = describe_diff(before.as_ref(), after.as_ref()) {
+            print_kv("account:", format!("{address} {diff}"));
+        }
+    }
+
+    match result.err {
+        None => {
+            print_success("Simulation succeeded");
+            Ok(())
+        }
+        Some(err) => {
+            if let Some(failure) = parse_callback_failure(entropy_program_id, &logs) {
+                print_callback_failure(&failure);
+            }
+            print_error(format!("Simulation failed: {err}"));
+            Err(anyhow::anyhow!("Simulated transaction failed: {err}"))
+        }
+    }
+}
+
+/// Accounts the transaction may write, from the message header's layout:
+/// writable signers first, then writable non-signers.
+fn writable_accounts(transaction: &Transaction) -> Vec<Pubkey> {
+    let message = &transaction.message;
+    let header = &message.header;
+    let signed = usize::from(header.num_required_signatures);
+    let writable_signed = signed.saturating_sub(usize::from(header.num_readonly_signed_accounts));
+    let writable_unsigned_end = message
+        .account_keys
+        .len()
+        .saturating_sub(usize::from(header.num_readonly_unsigned_accounts));
+    message
+        .account_keys
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| {
+            *index < writable_signed || (*index >= signed && *index < writable_unsigned_end)
+        })
+        .map(|(_, key)| *key)
+        .collect()
+}
+
+/// `None` if the account is unchanged.
+fn describe_diff(before: Option<&Account>, after: Option<&Account>) -> Option<String> {
+    match (before, after) {
+        (None, None) => None,
+        (None, Some(after)) => Some(format!(
+            "created: {} lamports, {} bytes, owner {}",
+            after.lamports,
+            after.data.len(),
+            after.owner
+        )),
+        (Some(before), None) => Some(format!("closed: {} lamports released", before.lamports)),
+        (Some(before), Some(after)) if before == after => None,
+        (Some(before), Some(after)) => {
+            let mut changes = Vec::new();
+            if before.lamports != after.lamports {
+                changes.push(format!(
+                    "lamports {} -> {} ({:+})",
+                    before.lamports,
+                    after.lamports,
+                    i128::from(after.lamports) - i128::from(before.lamports)
+                ));
+            }
+            if before.data.len() != after.data.len() {
+                changes.push(format!(
+                    "data {} -> {} bytes",
+                    before.data.len(),
+                    after.data.len()
+                ));
+            } else if before.data != after.data {
+                let changed = before
+                    .data
+                    .iter()
+                    .zip(&after.data)
+                    .filter(|(before, after)| before != after)
+                    .count();
+                changes.push(format!("{changed} data bytes changed"));
+            }
+            if before.owner != after.owner {
+                changes.push(format!("owner {} -> {}", before.owner, after.owner));
+            }
+            Some(changes.join(", "))
+        }
+    }
+}
+
+fn describe_event(event: &EntropyEvent) -> String {
+    match event {
+        EntropyEvent::RequestCreated(created) => format!(
+            "RequestCreated {} (provider {}, sequence {})",
+            Pubkey::new_from_array(created.request_account),
+            Pubkey::new_from_array(created.provider),
+            created.sequence_number
+        ),
+        EntropyEvent::RandomnessRevealed(revealed) => format!(
+            "RandomnessRevealed {} (sequence {}, random number {})",
+            Pubkey::new_from_array(revealed.request_account),
+            revealed.sequence_number,
+            Hash::new_from_array(revealed.random_number)
+        ),
+        EntropyEvent::PythFeeSet(set) => format!(
+            "PythFeeSet {} -> {} lamports",
+            set.old_fee_lamports, set.new_fee_lamports
+        ),
+        EntropyEvent::DefaultProviderSet(set) => format!(
+            "DefaultProviderSet {} -> {}",
+            Pubkey::new_from_array(set.old_default_provider),
+            Pubkey::new_from_array(set.new_default_provider)
+        ),
+        EntropyEvent::UserRandomnessGenerated(generated) => format!(
+            "UserRandomnessGenerated {} (sequence {})",
+            Pubkey::new_from_array(generated.request_account),
+            generated.sequence_number
+        ),
+        EntropyEvent::AggregateRequestCreated(created) => format!(
+            "AggregateRequestCreated {} ({} of {} providers)",
+            Pubkey::new_from_array(created.request_account),
+            created.threshold,
+            created.num_providers
+        ),
+        EntropyEvent::AggregateRandomnessRevealed(revealed) => format!(
+            "AggregateRandomnessRevealed {} (providers {:#06b}, random number {})",
+            Pubkey::new_from_array(revealed.request_account),
+            revealed.revealed_mask,
+            Hash::new_from_array(revealed.random_number)
+        ),
+        EntropyEvent::CallbackInvoked(invoked) => format!(
+            "CallbackInvoked {} (program {}, sequence {})",
+            Pubkey::new_from_array(invoked.request_account),
+            Pubkey::new_from_array(invoked.requester_program_id),
+            invoked.sequence_number
+        ),
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::{Context, Result};
+use bytemuck::try_pod_read_unaligned;
+use entropy::accounts::{Account, Request};
+use entropy_sdk::instruction::close_expired_request;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair};
+
+use crate::{
+    print_error, print_info, print_kv, print_success, send_and_confirm,
+    simulate::simulate_instructions,
+};
+
+/// An expired request and what closing it refunds at most: its rent and
+/// provider fee.
+struct StaleRequest {
+    address: Pubkey,
+    request: Request,
+    lamports: u64,
+}
+
+/// Closes expired requests `batch_size` at a time, refunding their rent and
+/// provider fees to the payers that funded them. With `only_payer`, only that payer's
+/// requests are closed; otherwise every expired request is (closing is
+/// permissionless). `payer` only pays the transaction fees. A dry run lists
+/// the requests and simulates each batch instead.
+pub(crate) fn close_stale_requests(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    entropy_program_id: Pubkey,
+    only_payer: Option<Pubkey>,
+    batch_size: usize,
+    dry_run: bool,
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    let stale = find_stale_requests(rpc_client, &entropy_program_id, only_payer, commitment)?;
+    let reclaimable: u64 = stale.iter().map(|stale| stale.lamports).sum();
+    print_info(format!("Found {} expired request(s)", stale.len()));
+    print_kv("reclaimable lamports:", reclaimable);
+    if dry_run {
+        for stale in &stale {
+            print_kv(
+                "expired:",
+                format!(
+                    "{} (sequence {}, payer {})",
+                    stale.address,
+                    stale.request.sequence_number,
+                    Pubkey::new_from_array(stale.request.payer)
+                ),
+            );
+        }
+    }
+
+    let mut closed = 0;
+    let mut refunded = 0;
+    for batch in stale.chunks(batch_size) {
+        let instructions: Vec<_> = batch
+            .iter()
+            .map(|stale| close_expired_request(entropy_program_id, stale.address, &stale.request))
+            .collect();
+        if dry_run {
+            simulate_instructions(
+                rpc_client,
+                payer,
+                &instructions,
+                &entropy_program_id,
+                commitment,
+            )?;
+            continue;
+        }
+        // A request revealed since the scan fails the whole batch; the
+        // rest are picked up by the next run.
+        match send_and_confirm(rpc_client, payer, &instructions, commitment) {
+            Ok(signature) => {
+                closed += batch.len();
+                refunded += batch.iter().map(|stale| stale.lamports).sum::<u64>();
+                print_success(format!("Closed {} request(s): {signature}", batch.len()));
+            }
+            Err(err) => print_error(format!("Failed to close batch: {err:#}")),
+        }
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+    print_info("Summary");
+    print_kv("requests closed:", closed);
+    print_kv("lamports refunded:", refunded);
+    if closed < stale.len() {
+        anyhow::bail!(
+            "{} expired request(s) were not closed",
+            stale.len() - closed
+        );
+    }
+    Ok(())
+}
+
+fn find_stale_requests(
+    rpc_client: &RpcClient,
+    entropy_program_id: &Pubkey,
+    only_payer: Option<Pubkey>,
+    commitment: CommitmentConfig,
+) -> Result<Vec<StaleRequest>> {
+    let mut filters = vec![
+        RpcFilterType::DataSize(Request::LEN as u64),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, Request::discriminator().to_vec())),
+    ];
+    if let Some(payer) = only_payer {
+        filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            core::mem::offset_of!(Request, payer),
+            payer.to_bytes().to_vec(),
+        )));
+    }
+    let accounts = rpc_client
+        .get_program_accounts_with_config(
+            entropy_program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(filters),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(commitment),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )
+        .context("Failed to fetch request accounts")?;
+    let slot = rpc_client
+        .get_slot_with_commitment(commitment)
+        .context("Failed to fetch current slot")?;
+
+    let mut stale: Vec<_> = accounts
+        .into_iter()
+        .filter_map(|(address, account)| {
+            let request = try_pod_read_unaligned::<Request>(&account.data).ok()?;
+            request.is_expired(slot).then_some(StaleRequest {
+                address,
+                request,
+                lamports: account.lamports.saturating_add(request.provider_fee),
+            })
+        })
+        .collect();
+    stale.sort_by_key(|stale| stale.request.request_slot);
+    Ok(stale)
+}
@@ -0,0 +1,364 @@
+use std::{
+    str::FromStr,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use rand::{rngs::OsRng, seq::SliceRandom};
+use serde_json::json;
+use solana_client::{nonce_utils, rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+use tracing::{info, warn};
+
+#[allow(deprecated)]
+use solana_sdk::system_instruction;
+
+use crate::{
+    lookup::{Footprint, LookupTables},
+    print_warn,
+};
+
+/// Default Jito block engine (mainnet).
+pub(crate) const DEFAULT_JITO_URL: &str = "https://mainnet.block-engine.jito.wtf";
+
+/// Jito tip payment accounts. A bundle must transfer its tip to one of these.
+const JITO_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+const JITO_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a nonce-signed transaction is re-sent while waiting for it to land.
+const NONCE_RESEND_INTERVAL: Duration = Duration::from_secs(2);
+/// Give up on a nonce-signed transaction after this long without landing.
+const NONCE_SUBMIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SubmitVia {
+    /// Send through the configured RPC node.
+    Rpc,
+    /// Send as a single-transaction Jito bundle with a tip.
+    Jito,
+}
+
+/// Submits keeper transactions either through RPC or as a Jito bundle.
+/// Transactions too large for a legacy one are sent as v0 transactions
+/// that load their accounts from a lookup table, when allowed to.
+pub(crate) struct Submitter {
+    via: SubmitVia,
+    tip_lamports: u64,
+    jito_url: String,
+    nonce_account: Option<Pubkey>,
+    lookup_tables: Option<LookupTables>,
+    http: reqwest::blocking::Client,
+}
+
+impl Submitter {
+    pub(crate) fn new(
+        via: SubmitVia,
+        tip_lamports: u64,
+        jito_url: String,
+        nonce_account: Option<Pubkey>,
+        lookup_tables: Option<LookupTables>,
+    ) -> Result<Self> {
+        let http = reqwest::blocking::Client::builder()
+            .timeout(JITO_REQUEST_TIMEOUT)
+            .build()
+            .context("Failed to build HTTP client")?;
+        Ok(Self {
+            via,
+            tip_lamports,
+            jito_url,
+            nonce_account,
+            lookup_tables,
+            http,
+        })
+    }
+
+    /// Lamports paid on top of transaction fees for each submission.
+    pub(crate) fn tip_lamports(&self) -> u64 {
+        match self.via {
+            SubmitVia::Rpc => 0,
+            SubmitVia::Jito => self.tip_lamports,
+        }
+    }
+
+    pub(crate) fn submit(
+        &self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        instructions: &[Instruction],
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        match self.via {
+            SubmitVia::Rpc => self.submit_rpc(rpc_client, payer, instructions, commitment),
+            SubmitVia::Jito => {
+                match self.submit_bundle(rpc_client, payer, instructions, commitment) {
+                    Ok(signature) => Ok(signature),
+                    Err(err) => {
+                        warn!(error = %err, "Jito submission failed; falling back to RPC");
+                        print_warn("Jito bundle submission failed; falling back to RPC");
+                        self.submit_rpc(rpc_client, payer, instructions, commitment)
+                    }
+                }
+            }
+        }
+    }
+
+    fn submit_rpc(
+        &self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        instructions: &[Instruction],
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        if let Some(nonce_account) = self.nonce_account {
+            return self.submit_with_nonce(
+                rpc_client,
+                payer,
+                nonce_account,
+                instructions,
+                commitment,
+            );
+        }
+        let recent_blockhash = rpc_client.get_latest_blockhash()?;
+        let transaction = self.compile(
+            rpc_client,
+            payer,
+            instructions,
+            recent_blockhash,
+            commitment,
+        )?;
+        rpc_client
+            .send_and_confirm_transaction_with_spinner_and_config(
+                &transaction,
+                commitment,
+                RpcSendTransactionConfig {
+                    skip_preflight: false,
+                    preflight_commitment: Some(commitment.commitment),
+                    ..RpcSendTransactionConfig::default()
+                },
+            )
+            .context("Transaction failed")
+    }
+
+    /// Signs `instructions` as a legacy transaction if it fits the packet
+    /// size and account limits, else as a v0 one loading accounts from the
+    /// payer's lookup table. Fails with both footprints when neither fits.
+    fn compile(
+        &self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        instructions: &[Instruction],
+        blockhash: Hash,
+        commitment: CommitmentConfig,
+    ) -> Result<VersionedTransaction> {
+        let legacy = VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(&payer.pubkey()),
+            &blockhash,
+        ));
+        let footprint = Footprint::of(&legacy);
+        if let Some(lookup_tables) = &self.lookup_tables {
+            lookup_tables.record_uses(instructions);
+        }
+        let message = if footprint.fits() {
+            legacy
+        } else {
+            if !footprint.lookup_can_help() {
+                anyhow::bail!(
+                    "Transaction locks too many accounts ({footprint}); a lookup table cannot \
+                     help"
+                );
+            }
+            let Some(lookup_tables) = &self.lookup_tables else {
+                anyhow::bail!(
+                    "Transaction is too large ({footprint}) and lookup tables are disabled"
+                );
+            };
+            let table = lookup_tables
+                .covering(rpc_client, payer, instructions, commitment)
+                .with_context(|| {
+                    format!("Transaction is too large ({footprint}) and no lookup table is ready")
+                })?;
+            let table_address = table.key;
+            let message = VersionedMessage::V0(
+                v0::Message::try_compile(&payer.pubkey(), instructions, &[table], blockhash)
+                    .context("Failed to compile a v0 message")?,
+            );
+            let compressed = Footprint::of(&message);
+            if !compressed.fits() {
+                anyhow::bail!(
+                    "Transaction is too large even with lookup table {table_address} \
+                     ({compressed}; legacy: {footprint})"
+                );
+            }
+            info!(
+                table = %table_address,
+                legacy_size = footprint.size,
+                size = compressed.size,
+                "Sending through a lookup table"
+            );
+            message
+        };
+        Ok(VersionedTransaction::try_new(message, &[payer])?)
+    }
+
+    fn submit_bundle(
+        &self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        instructions: &[Instruction],
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        let tip_account = JITO_TIP_ACCOUNTS
+            .choose(&mut OsRng)
+            .map(|account| Pubkey::from_str(account))
+            .context("No Jito tip account configured")??;
+
+        let mut bundle_instructions = instructions.to_vec();
+        bundle_instructions.push(system_instruction::transfer(
+            &payer.pubkey(),
+            &tip_account,
+            self.tip_lamports,
+        ));
+
+        let recent_blockhash = rpc_client.get_latest_blockhash()?;
+        let transaction = self.compile(
+            rpc_client,
+            payer,
+            &bundle_instructions,
+            recent_blockhash,
+            commitment,
+        )?;
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+
+        let serialized =
+            bincode::serialize(&transaction).context("Failed to serialize transaction")?;
+        let encoded = bs58::encode(serialized).into_string();
+        let response: serde_json::Value = self
+            .http
+            .post(format!(
+                "{}/api/v1/bundles",
+                self.jito_url.trim_end_matches('/')
+            ))
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "sendBundle",
+                "params": [[encoded]],
+            }))
+            .send()
+            .context("Failed to reach Jito block engine")?
+            .error_for_status()
+            .context("Jito block engine rejected the request")?
+            .json()
+            .context("Invalid Jito block engine response")?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow::anyhow!("Jito sendBundle error: {error}"));
+        }
+
+        // The bundle is only useful while its blockhash is valid; a landed
+        // bundle shows up as a regular confirmed signature.
+        rpc_client
+            .poll_for_signature_with_commitment(&signature, commitment)
+            .context("Jito bundle did not land")?;
+        Ok(signature)
+    }
+
+    /// Signs the transaction once against the durable nonce and keeps re-sending
+    /// the same bytes until it lands. The transaction can no longer land once the
+    /// nonce has advanced without it, so that ends the retries.
+    fn submit_with_nonce(
+        &self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        nonce_account: Pubkey,
+        instructions: &[Instruction],
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        let nonce = fetch_nonce(rpc_client, &nonce_account, commitment)?;
+
+        let mut nonce_instructions = Vec::with_capacity(instructions.len() + 1);
+        nonce_instructions.push(system_instruction::advance_nonce_account(
+            &nonce_account,
+            &payer.pubkey(),
+        ));
+        nonce_instructions.extend_from_slice(instructions);
+
+        let transaction =
+            self.compile(rpc_client, payer, &nonce_instructions, nonce, commitment)?;
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+
+        let started = Instant::now();
+        while started.elapsed() < NONCE_SUBMIT_TIMEOUT {
+            if let Err(err) = rpc_client.send_transaction_with_config(
+                &transaction,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..RpcSendTransactionConfig::default()
+                },
+            ) {
+                warn!(signature = %signature, error = %err, "Failed to send nonce transaction");
+            }
+
+            sleep(NONCE_RESEND_INTERVAL);
+
+            match rpc_client.get_signature_status_with_commitment(&signature, commitment) {
+                Ok(Some(Ok(()))) => return Ok(signature),
+                Ok(Some(Err(err))) => {
+                    return Err(anyhow::anyhow!("Transaction {signature} failed: {err}"));
+                }
+                Ok(None) => {}
+                Err(err) => warn!(signature = %signature, error = %err, "Failed to fetch status"),
+            }
+
+            if fetch_nonce(rpc_client, &nonce_account, commitment)? != nonce {
+                // The nonce may have been advanced by this very transaction just
+                // after the status check; look once more before giving up.
+                if let Ok(Some(Ok(()))) =
+                    rpc_client.get_signature_status_with_commitment(&signature, commitment)
+                {
+                    return Ok(signature);
+                }
+                return Err(anyhow::anyhow!(
+                    "Nonce {nonce_account} advanced before transaction {signature} landed"
+                ));
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Transaction {signature} did not land within {NONCE_SUBMIT_TIMEOUT:?}"
+        ))
+    }
+}
+
+fn fetch_nonce(
+    rpc_client: &RpcClient,
+    nonce_account: &Pubkey,
+    commitment: CommitmentConfig,
+) -> Result<Hash> {
+    let account = nonce_utils::get_account_with_commitment(rpc_client, nonce_account, commitment)
+        .with_context(|| format!("Failed to fetch nonce account {nonce_account}"))?;
+    let data = nonce_utils::data_from_account(&account)
+        .with_context(|| format!("Invalid nonce account {nonce_account}"))?;
+    Ok(data.blockhash())
+}
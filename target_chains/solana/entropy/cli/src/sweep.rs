@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use bytemuck::try_from_bytes;
+use entropy::{
+    accounts::Provider,
+    pda::{provider_pda, provider_vault_pda},
+    vault::withdrawable_fees,
+};
+use entropy_sdk::instruction::withdraw_provider_fees;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    sysvar::rent,
+};
+
+use crate::send_and_confirm;
+
+/// Moves provider fees to a cold wallet once they exceed `threshold`, so the
+/// keeper's hot key never controls more than that.
+pub(crate) struct FeeSweeper {
+    pub(crate) threshold: u64,
+    pub(crate) destination: Pubkey,
+}
+
+/// A confirmed sweep.
+pub(crate) struct Sweep {
+    pub(crate) signature: Signature,
+    pub(crate) lamports: u64,
+}
+
+impl FeeSweeper {
+    /// Withdraws the vault's [`withdrawable_fees`], less the fees reserved
+    /// for outstanding requests, if they exceed the threshold.
+    pub(crate) fn sweep(
+        &self,
+        rpc_client: &RpcClient,
+        payer: &Keypair,
+        entropy_program_id: Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<Option<Sweep>> {
+        let (provider_vault, _) = provider_vault_pda(&entropy_program_id, &payer.pubkey());
+        let balance = rpc_client
+            .get_balance(&provider_vault)
+            .context("Failed to fetch provider vault balance")?;
+        let rent_data = rpc_client
+            .get_account_data(&rent::id())
+            .context("Failed to fetch the rent sysvar")?;
+        let rent: Rent =
+            bincode::deserialize(&rent_data).context("Failed to decode the rent sysvar")?;
+        let (provider_account, _) = provider_pda(&entropy_program_id, &payer.pubkey());
+        let provider_data = rpc_client
+            .get_account_data(&provider_account)
+            .with_context(|| format!("Failed to fetch provider account {provider_account}"))?;
+        let provider = try_from_bytes::<Provider>(&provider_data)
+            .map_err(|err| anyhow::anyhow!("Failed to parse provider account: {err}"))?;
+        let lamports =
+            withdrawable_fees(balance, &rent).saturating_sub(provider.reserved_fee_lamports);
+        if lamports <= self.threshold {
+            return Ok(None);
+        }
+
+        let ix = withdraw_provider_fees(
+            entropy_program_id,
+            payer.pubkey(),
+            self.destination,
+            lamports,
+        );
+        let signature = send_and_confirm(rpc_client, payer, &[ix], commitment)?;
+        Ok(Some(Sweep {
+            signature,
+            lamports,
+        }))
+    }
+}
@@ -0,0 +1,363 @@
+use std::{path::Path, str::FromStr};
+
+use anyhow::{Context, Result};
+use bs58::decode as bs58_decode;
+use bytemuck::try_from_bytes;
+use entropy::{
+    accounts::Provider,
+    events::{RandomnessRevealed, RequestCreated},
+    instruction::{parse_args, EntropyInstruction, RevealArgs},
+    pda::provider_pda,
+};
+use entropy_sdk::{parse_logs, verify_reveal, EntropyEvent};
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::{hash, hashv, Hash},
+    pubkey::Pubkey,
+    signature::Signature,
+    slot_hashes::SlotHashes,
+    sysvar::slot_hashes,
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta, UiInstruction,
+    UiTransactionEncoding,
+};
+
+use crate::{
+    parse_pubkey, parse_request_observations, print_info, print_kv, print_success, print_warn,
+    raw_message_with_keys, secrets, RequestKind,
+};
+
+/// Re-derives a revealed request's random number from its request and reveal
+/// transactions. The request account is closed on reveal, so its commitment
+/// is rebuilt from the user commitment in the request transaction and the
+/// provider's current on-chain commitment, which the revealed provider
+/// contribution must hash to (or from).
+///
+/// `target` is the request transaction's signature or the request account.
+/// If `secrets_file` holds the secret the request was made with, the revealed
+/// user contribution must also derive from it. Returns an error if the reveal
+/// is inconsistent with the request; checks that cannot be completed are
+/// reported as warnings.
+pub(crate) fn verify(
+    rpc_client: &RpcClient,
+    entropy_program_id: Pubkey,
+    target: &str,
+    blockhash: Option<Hash>,
+    secrets_file: &Path,
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    let request_account = match Signature::from_str(target) {
+        Ok(signature) => {
+            let tx = fetch_transaction(rpc_client, &signature, commitment)?;
+            let created: Vec<_> = events(&tx, &entropy_program_id)
+                .into_iter()
+                .filter_map(|event| match event {
+                    EntropyEvent::RequestCreated(created) => Some(created),
+                    _ => None,
+                })
+                .collect();
+            match created.as_slice() {
+                [created] => Pubkey::new_from_array(created.request_account),
+                [] => anyhow::bail!("Transaction {signature} created no entropy request"),
+                _ => anyhow::bail!(
+                    "Transaction {signature} created {} requests; pass the request account \
+                     instead",
+                    created.len()
+                ),
+            }
+        }
+        Err(_) => parse_pubkey(target, "request")?,
+    };
+
+    let (request, reveal) = find_request_and_reveal(
+        rpc_client,
+        &entropy_program_id,
+        &request_account,
+        commitment,
+    )?;
+    let (request_signature, created, request_tx) =
+        request.with_context(|| format!("No request transaction found for {request_account}"))?;
+    let (reveal_signature, revealed, reveal_tx) =
+        reveal.with_context(|| format!("Request {request_account} has not been revealed"))?;
+
+    print_info("Request");
+    print_kv("request account:", request_account);
+    print_kv("request signature:", request_signature);
+    print_kv("reveal signature:", reveal_signature);
+    print_kv("provider:", Pubkey::new_from_array(created.provider));
+    print_kv("sequence number:", created.sequence_number);
+    print_kv("request slot:", created.request_slot);
+    print_kv(
+        "random number:",
+        Hash::new_from_array(revealed.random_number),
+    );
+
+    let user_commitment = parse_request_observations(&request_tx, &entropy_program_id)?
+        .into_iter()
+        .find(|observation| observation.request_account == request_account)
+        .map(|observation| match observation.kind {
+            RequestKind::Request { user_commitment } => user_commitment,
+            RequestKind::RequestWithCallback { user_randomness } => {
+                hash(&user_randomness).to_bytes()
+            }
+        })
+        .context("Request transaction carries no user commitment")?;
+    let reveal_args = find_reveal_args(&reveal_tx, &entropy_program_id, &request_account)?
+        .context("Reveal transaction carries no reveal instruction")?;
+
+    let (num_hashes, provider_commitment) =
+        provider_commitment_at(rpc_client, &entropy_program_id, &created)?;
+    let request_commitment = hashv(&[&user_commitment, &provider_commitment]).to_bytes();
+
+    let blockhash = if created.use_blockhash == 1 {
+        let blockhash = blockhash
+            .map(|blockhash| blockhash.to_bytes())
+            .or_else(|| slot_hash(rpc_client, created.request_slot));
+        if blockhash.is_none() {
+            print_warn(format!(
+                "Slot {} has left the SlotHashes sysvar; pass its hash with --blockhash to \
+                 check the random number",
+                created.request_slot
+            ));
+        }
+        blockhash
+    } else {
+        Some([0u8; 32])
+    };
+
+    print_info("Checks");
+    if hash(&reveal_args.user_contribution).to_bytes() != user_commitment {
+        anyhow::bail!("Revealed user contribution does not match the request's user commitment");
+    }
+    print_success("user contribution matches the request");
+
+    match secrets::find(secrets_file, &request_account)? {
+        Some(record) => {
+            let stored = record.contribution()?;
+            if stored.user_randomness != reveal_args.user_contribution {
+                anyhow::bail!(
+                    "Revealed user contribution was not derived from the secret stored in {}",
+                    secrets_file.display()
+                );
+            }
+            print_success(format!(
+                "user contribution derives from the stored secret {}",
+                stored.secret
+            ));
+        }
+        None => print_warn(format!(
+            "No stored secret for {request_account} in {}; the user contribution was not \
+             checked against it",
+            secrets_file.display()
+        )),
+    }
+
+    let Some(random_number) = verify_reveal(
+        &entropy_program_id,
+        created.random_number_version,
+        reveal_args.user_contribution,
+        reveal_args.provider_contribution,
+        num_hashes,
+        request_commitment,
+        blockhash,
+    ) else {
+        // A provider that registered a new chain since the request no longer
+        // commits to the one it was revealed from.
+        print_warn(
+            "Provider contribution does not hash to the provider's current commitment; the \
+             provider may have rotated its hash chain since the request",
+        );
+        return Ok(());
+    };
+    print_success("provider contribution matches the provider's hash chain");
+
+    if blockhash.is_none() {
+        return Ok(());
+    }
+    if random_number != revealed.random_number {
+        anyhow::bail!(
+            "Revealed random number {} differs from the recomputed {}",
+            Hash::new_from_array(revealed.random_number),
+            Hash::new_from_array(random_number)
+        );
+    }
+    print_success("random number matches the contributions");
+    Ok(())
+}
+
+/// The provider commitment the request should be checked against, and the
+/// number of hashes from the revealed contribution to it. If later reveals
+/// moved the provider's commitment past this request, it is hashed back down
+/// to the request's sequence number.
+fn provider_commitment_at(
+    rpc_client: &RpcClient,
+    entropy_program_id: &Pubkey,
+    created: &RequestCreated,
+) -> Result<(u32, [u8; 32])> {
+    let (provider_account, _) = provider_pda(
+        entropy_program_id,
+        &Pubkey::new_from_array(created.provider),
+    );
+    let provider_data = rpc_client
+        .get_account_data(&provider_account)
+        .with_context(|| format!("Failed to fetch provider account {provider_account}"))?;
+    let provider = try_from_bytes::<Provider>(&provider_data)
+        .map_err(|err| anyhow::anyhow!("Failed to parse provider account: {err}"))?;
+
+    let current_sequence = provider.current_commitment_sequence_number;
+    if created.sequence_number >= current_sequence {
+        let num_hashes = u32::try_from(created.sequence_number - current_sequence)
+            .context("Request is too far ahead of the provider's commitment")?;
+        return Ok((num_hashes, provider.current_commitment));
+    }
+    let mut commitment = provider.current_commitment;
+    for _ in created.sequence_number..current_sequence {
+        commitment = hash(&commitment).to_bytes();
+    }
+    Ok((0, commitment))
+}
+
+type Found<T> = Option<(Signature, T, EncodedConfirmedTransactionWithStatusMeta)>;
+
+/// Walks the request account's transactions, oldest first, for the ones that
+/// created and revealed it.
+fn find_request_and_reveal(
+    rpc_client: &RpcClient,
+    entropy_program_id: &Pubkey,
+    request_account: &Pubkey,
+    commitment: CommitmentConfig,
+) -> Result<(Found<RequestCreated>, Found<RandomnessRevealed>)> {
+    let signatures = rpc_client
+        .get_signatures_for_address(request_account)
+        .with_context(|| format!("Failed to fetch transactions of {request_account}"))?;
+    let mut request = None;
+    let mut reveal = None;
+    for status in signatures
+        .iter()
+        .rev()
+        .filter(|status| status.err.is_none())
+    {
+        let signature = Signature::from_str(&status.signature)
+            .with_context(|| format!("Invalid signature {}", status.signature))?;
+        let tx = fetch_transaction(rpc_client, &signature, commitment)?;
+        let events = events(&tx, entropy_program_id);
+        let created = events.iter().find_map(|event| match event {
+            EntropyEvent::RequestCreated(created)
+                if request.is_none() && created.request_account == request_account.to_bytes() =>
+            {
+                Some(*created)
+            }
+            _ => None,
+        });
+        let revealed = events.iter().find_map(|event| match event {
+            EntropyEvent::RandomnessRevealed(revealed)
+                if reveal.is_none() && revealed.request_account == request_account.to_bytes() =>
+            {
+                Some(*revealed)
+            }
+            _ => None,
+        });
+        match (created, revealed) {
+            (Some(created), Some(revealed)) => {
+                let reveal_tx = fetch_transaction(rpc_client, &signature, commitment)?;
+                reveal = Some((signature, revealed, reveal_tx));
+                request = Some((signature, created, tx));
+            }
+            (Some(created), None) => request = Some((signature, created, tx)),
+            (None, Some(revealed)) => reveal = Some((signature, revealed, tx)),
+            (None, None) => {}
+        }
+        if request.is_some() && reveal.is_some() {
+            break;
+        }
+    }
+    Ok((request, reveal))
+}
+
+pub(crate) fn fetch_transaction(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+    rpc_client
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(commitment),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .with_context(|| format!("Failed to fetch transaction {signature}"))
+}
+
+fn events(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    entropy_program_id: &Pubkey,
+) -> Vec<EntropyEvent> {
+    match tx.transaction.meta.as_ref().map(|meta| &meta.log_messages) {
+        Some(OptionSerializer::Some(logs)) => parse_logs(entropy_program_id, logs),
+        _ => Vec::new(),
+    }
+}
+
+/// Arguments of the `RevealWithCallback` for `request_account`, whether
+/// called directly or through CPI.
+fn find_reveal_args(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    entropy_program_id: &Pubkey,
+    request_account: &Pubkey,
+) -> Result<Option<RevealArgs>> {
+    let Some((raw_message, account_keys)) = raw_message_with_keys(tx)? else {
+        return Ok(None);
+    };
+    let inner = match tx
+        .transaction
+        .meta
+        .as_ref()
+        .map(|meta| &meta.inner_instructions)
+    {
+        Some(OptionSerializer::Some(inner)) => inner.as_slice(),
+        _ => &[],
+    };
+    let inner = inner
+        .iter()
+        .flat_map(|inner| &inner.instructions)
+        .filter_map(|instruction| match instruction {
+            UiInstruction::Compiled(compiled) => Some(compiled),
+            _ => None,
+        });
+
+    for instruction in raw_message.instructions.iter().chain(inner) {
+        let program = account_keys.get(usize::from(instruction.program_id_index));
+        let request = instruction
+            .accounts
+            .first()
+            .and_then(|index| account_keys.get(usize::from(*index)));
+        if program != Some(entropy_program_id) || request != Some(request_account) {
+            continue;
+        }
+        let Ok(data) = bs58_decode(&instruction.data).into_vec() else {
+            continue;
+        };
+        let Ok((EntropyInstruction::RevealWithCallback, payload)) =
+            EntropyInstruction::parse(&data)
+        else {
+            continue;
+        };
+        if let Ok(args) = parse_args::<RevealArgs>(payload) {
+            return Ok(Some(*args));
+        }
+    }
+    Ok(None)
+}
+
+/// The hash the program mixes in for `use_blockhash` requests, if `slot` is
+/// still recent enough to be in the SlotHashes sysvar.
+fn slot_hash(rpc_client: &RpcClient, slot: u64) -> Option<[u8; 32]> {
+    let data = rpc_client.get_account_data(&slot_hashes::id()).ok()?;
+    let slot_hashes: SlotHashes = bincode::deserialize(&data).ok()?;
+    slot_hashes.get(&slot).map(|hash| hash.to_bytes())
+}
@@ -0,0 +1,193 @@
+use anyhow::{Context, Result};
+use bytemuck::try_pod_read_unaligned;
+use entropy::{accounts::Provider, pda::provider_pda};
+use entropy_sdk::IdlDecoder;
+use serde_json::Value;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    pubsub_client::PubsubClient, rpc_client::RpcClient, rpc_config::RpcAccountInfoConfig,
+};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use crate::{print_info, print_kv, print_success, print_warn};
+
+pub(crate) enum WatchTarget {
+    Request(Pubkey),
+    /// A provider authority; its provider account is watched.
+    Provider(Pubkey),
+}
+
+pub(crate) struct WatchConfig {
+    pub(crate) websocket_url: String,
+    pub(crate) entropy_program_id: Pubkey,
+    pub(crate) target: WatchTarget,
+    pub(crate) commitment: CommitmentConfig,
+}
+
+/// An account's state as last seen.
+struct Snapshot {
+    lamports: u64,
+    data: Vec<u8>,
+    fields: Value,
+}
+
+/// Subscribes to a request or provider account and prints what changes in
+/// it as the changes land. Watching a request ends when it is closed.
+pub(crate) fn watch(rpc_client: &RpcClient, config: WatchConfig) -> Result<()> {
+    let (address, is_request) = match config.target {
+        WatchTarget::Request(address) => (address, true),
+        WatchTarget::Provider(authority) => (
+            provider_pda(&config.entropy_program_id, &authority).0,
+            false,
+        ),
+    };
+    let kind = if is_request { "request" } else { "provider" };
+    let decoder = IdlDecoder::new();
+
+    let (_subscription, updates) = PubsubClient::account_subscribe(
+        &config.websocket_url,
+        &address,
+        Some(RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(config.commitment),
+            ..RpcAccountInfoConfig::default()
+        }),
+    )
+    .with_context(|| {
+        format!(
+            "Failed to subscribe to {address} at {}",
+            config.websocket_url
+        )
+    })?;
+
+    // Read after subscribing so no change falls between the two.
+    let account = rpc_client
+        .get_account_with_commitment(&address, config.commitment)
+        .with_context(|| format!("Failed to fetch {kind} account {address}"))?
+        .value;
+    print_info(format!(
+        "Watching {kind} account {address} (Ctrl-C to stop)"
+    ));
+    let mut previous = snapshot(&decoder, &config.entropy_program_id, account)?;
+    match &previous {
+        Some(state) => print_state(state),
+        None if is_request => {
+            print_warn("The request account does not exist: it was never created or is closed");
+            return Ok(());
+        }
+        None => print_warn("The provider is not registered; waiting for it"),
+    }
+
+    for update in updates.iter() {
+        let current = snapshot(
+            &decoder,
+            &config.entropy_program_id,
+            update.value.decode::<Account>(),
+        )?;
+        print_info(format!("Slot {}", update.context.slot));
+        match (&previous, &current) {
+            (Some(_), None) => {
+                print_warn(format!("The {kind} account was closed"));
+                if is_request {
+                    return Ok(());
+                }
+            }
+            (None, Some(state)) => {
+                print_success(format!("The {kind} account was created"));
+                print_state(state);
+            }
+            (Some(old), Some(new)) => print_changes(old, new, is_request),
+            (None, None) => {}
+        }
+        previous = current;
+    }
+    anyhow::bail!("Subscription to {address} closed")
+}
+
+/// `None` for a closed or missing account; an error for one entropy does
+/// not own or that does not decode.
+fn snapshot(
+    decoder: &IdlDecoder,
+    entropy_program_id: &Pubkey,
+    account: Option<Account>,
+) -> Result<Option<Snapshot>> {
+    let Some(account) = account.filter(|account| account.lamports > 0) else {
+        return Ok(None);
+    };
+    if account.owner != *entropy_program_id {
+        anyhow::bail!("Account is owned by {}, not entropy", account.owner);
+    }
+    let (_, fields) = decoder.decode_account(&account.data)?;
+    Ok(Some(Snapshot {
+        lamports: account.lamports,
+        data: account.data,
+        fields,
+    }))
+}
+
+fn print_state(state: &Snapshot) {
+    print_kv("lamports:", state.lamports);
+    if let Value::Object(fields) = &state.fields {
+        for (name, value) in fields {
+            print_kv(&format!("{name}:"), compact(value));
+        }
+    }
+}
+
+fn print_changes(old: &Snapshot, new: &Snapshot, is_request: bool) {
+    if !is_request {
+        describe_provider_change(old, new);
+    }
+    if old.lamports != new.lamports {
+        print_kv("lamports:", format!("{} -> {}", old.lamports, new.lamports));
+    }
+    if let (Value::Object(old_fields), Value::Object(new_fields)) = (&old.fields, &new.fields) {
+        for (name, value) in new_fields {
+            let old_value = old_fields.get(name).unwrap_or(&Value::Null);
+            if old_value != value {
+                print_kv(
+                    &format!("{name}:"),
+                    format!("{} -> {}", compact(old_value), compact(value)),
+                );
+            }
+        }
+    }
+}
+
+/// Names the provider changes a keeper operator cares about.
+fn describe_provider_change(old: &Snapshot, new: &Snapshot) {
+    let (Ok(old), Ok(new)) = (
+        try_pod_read_unaligned::<Provider>(&old.data),
+        try_pod_read_unaligned::<Provider>(&new.data),
+    ) else {
+        return;
+    };
+    if new.sequence_number > old.sequence_number {
+        print_success(format!(
+            "Sequence advanced {} -> {} ({} new requests)",
+            old.sequence_number,
+            new.sequence_number,
+            new.sequence_number - old.sequence_number
+        ));
+    }
+    if new.original_commitment != old.original_commitment
+        || new.original_commitment_sequence_number != old.original_commitment_sequence_number
+    {
+        print_success(format!(
+            "Commitment rotated: new hash chain from sequence {}",
+            new.original_commitment_sequence_number
+        ));
+    } else if new.current_commitment_sequence_number > old.current_commitment_sequence_number {
+        print_success(format!(
+            "Revealed through sequence {}",
+            new.current_commitment_sequence_number
+        ));
+    }
+}
+
+fn compact(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,159 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::{
+        accounts::{Account, ProgramRegistry},
+        pda::entropy_signer_pda,
+        randomness::combine_random_values,
+    },
+    solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey},
+};
+
+/// Bytes entropy appends to the callback data registered with the request:
+/// sequence number (u64 LE), provider authority and random number.
+pub const CALLBACK_SUFFIX_LEN: usize = 8 + 32 + 32;
+
+/// The values entropy appends to a callback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Callback {
+    pub sequence_number: u64,
+    pub provider: Pubkey,
+    pub random_number: [u8; 32],
+}
+
+/// Splits callback instruction data into the caller's prefix and the trailing
+/// `(sequence, provider, random_number)` tuple.
+pub fn parse_callback(data: &[u8]) -> Result<(&[u8], Callback), ProgramError> {
+    let prefix_len = data
+        .len()
+        .checked_sub(CALLBACK_SUFFIX_LEN)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let (prefix, suffix) = data.split_at(prefix_len);
+    let (sequence_bytes, rest) = suffix.split_at(8);
+    let (provider_bytes, random_bytes) = rest.split_at(32);
+
+    let callback = Callback {
+        sequence_number: u64::from_le_bytes(
+            sequence_bytes
+                .try_into()
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+        ),
+        provider: Pubkey::new_from_array(
+            provider_bytes
+                .try_into()
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+        ),
+        random_number: random_bytes
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+    };
+    Ok((prefix, callback))
+}
+
+/// Bytes entropy appends after the [`CALLBACK_SUFFIX_LEN`] tail when the
+/// request set `include_contributions`: the user and provider contributions.
+pub const CALLBACK_CONTRIBUTIONS_LEN: usize = 32 + 32;
+
+/// The contributions a callback's random number was derived from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Contributions {
+    pub user_contribution: [u8; 32],
+    pub provider_contribution: [u8; 32],
+}
+
+impl Contributions {
+    /// The random number `entropy_program_id` derives from these
+    /// contributions for a callback request made with
+    /// `random_number_version`; callback requests never mix in a blockhash.
+    /// Compare it with [`Callback::random_number`] to check the
+    /// contributions.
+    pub fn random_number(
+        &self,
+        random_number_version: u8,
+        entropy_program_id: &Pubkey,
+    ) -> [u8; 32] {
+        combine_random_values(
+            random_number_version,
+            entropy_program_id,
+            &self.user_contribution,
+            &self.provider_contribution,
+            &[0u8; 32],
+        )
+    }
+}
+
+/// Like [`parse_callback`], for requests made with `include_contributions`:
+/// also splits off the contributions entropy appends after the random number.
+pub fn parse_callback_with_contributions(
+    data: &[u8],
+) -> Result<(&[u8], Callback, Contributions), ProgramError> {
+    let callback_len = data
+        .len()
+        .checked_sub(CALLBACK_CONTRIBUTIONS_LEN)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let (callback_data, contribution_bytes) = data.split_at(callback_len);
+    let (user_bytes, provider_bytes) = contribution_bytes.split_at(32);
+    let (prefix, callback) = parse_callback(callback_data)?;
+
+    let contributions = Contributions {
+        user_contribution: user_bytes
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+        provider_contribution: provider_bytes
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+    };
+    Ok((prefix, callback, contributions))
+}
+
+/// Checks that `entropy_signer` (the first callback account) is entropy's
+/// signer PDA, proving the callback came from `entropy_program_id`.
+pub fn verify_entropy_signer(
+    entropy_signer: &AccountInfo,
+    entropy_program_id: &Pubkey,
+) -> Result<(), ProgramError> {
+    let (expected_entropy_signer, _bump) = entropy_signer_pda(entropy_program_id);
+    if entropy_signer.key != &expected_entropy_signer {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !entropy_signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+/// Like [`verify_entropy_signer`], but accepts any entropy deployment listed
+/// in the program registry of `registry_program_id`, the one entropy program
+/// the consumer trusts. `registry` is that program's entropy signer PDA
+/// (the same account as `entropy_signer` for callbacks it delivers itself).
+/// Returns the program id the callback came from.
+pub fn verify_registered_entropy_signer(
+    entropy_signer: &AccountInfo,
+    registry: &AccountInfo,
+    registry_program_id: &Pubkey,
+) -> Result<Pubkey, ProgramError> {
+    if !entropy_signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (expected_registry, _bump) = entropy_signer_pda(registry_program_id);
+    if registry.key != &expected_registry {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if registry.owner != registry_program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let data = registry.try_borrow_data()?;
+    let registry =
+        try_from_bytes::<ProgramRegistry>(&data).map_err(|_| ProgramError::InvalidAccountData)?;
+    if registry.discriminator != ProgramRegistry::discriminator() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    registry
+        .registered_programs()
+        .iter()
+        .find(|program| program.entropy_signer == entropy_signer.key.to_bytes())
+        .map(|program| Pubkey::new_from_array(program.program_id))
+        .ok_or(ProgramError::InvalidSeeds)
+}
@@ -0,0 +1,22 @@
+//! Helpers for consumer programs: issue `Request` / `RequestWithCallback` via
+//! CPI signed by the requester-signer PDA, and decode the callback entropy
+//! delivers on reveal. [`rand`] maps the delivered random number onto ranges,
+//! shuffles and draws without modulo bias.
+#![no_std]
+
+extern crate alloc;
+
+pub mod callback;
+pub mod rand;
+pub mod request;
+pub mod seeds;
+
+pub use callback::*;
+pub use entropy_callback_macro::entropy_callback;
+pub use request::*;
+
+/// Paths used by code generated from [`entropy_callback`].
+#[doc(hidden)]
+pub mod __private {
+    pub use solana_program;
+}
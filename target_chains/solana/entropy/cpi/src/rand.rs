@@ -0,0 +1,207 @@
+//! Unbiased ways to turn the 32-byte random number entropy delivers into the
+//! values a program actually needs.
+//!
+//! Reducing the random number with `%` favours small results whenever the
+//! range does not divide 2^64; [`RandomSource`] rejects and redraws instead.
+//! Two sources implement it:
+//!
+//! - [`RandomStream`] starts with the random number itself and continues with
+//!   `sha256(random_number || counter)`, so the common single-draw case costs
+//!   no hashing.
+//! - [`EntropyRng`] is a ChaCha20 keystream keyed by the random number, the
+//!   request's sequence number and a consumer-chosen seed, for callbacks that
+//!   need many values or want independent streams per purpose.
+//!
+//! Both are plain integer code and produce identical output on-chain and
+//! off-chain, so a client can recompute what a callback drew.
+
+use solana_program::hash::hashv;
+
+/// A deterministic source of `u64`s and the unbiased draws built on it.
+pub trait RandomSource {
+    fn next_u64(&mut self) -> u64;
+
+    /// Uniform value in `low..=high`. Panics if `low > high`.
+    fn u64_in_range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low <= high, "empty range");
+        let span = (high - low).wrapping_add(1);
+        if span == 0 {
+            return self.next_u64();
+        }
+        // Values below `threshold` would make `% span` favour small results.
+        let threshold = span.wrapping_neg() % span;
+        loop {
+            let value = self.next_u64();
+            if value >= threshold {
+                return low + value % span;
+            }
+        }
+    }
+
+    /// Fisher–Yates shuffle, e.g. of a fixed-size array.
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.u64_in_range(0, i as u64) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// `K` distinct values from `0..n`, in no particular order. Panics if
+    /// `K > n`.
+    fn draw_without_replacement<const K: usize>(&mut self, n: u64) -> [u64; K] {
+        assert!(K as u64 <= n, "cannot draw more values than the population");
+        // Floyd's algorithm: uniform over subsets without allocating.
+        let mut drawn = [0u64; K];
+        for (i, top) in (n - K as u64..n).enumerate() {
+            let candidate = self.u64_in_range(0, top);
+            drawn[i] = if drawn[..i].contains(&candidate) {
+                top
+            } else {
+                candidate
+            };
+        }
+        drawn
+    }
+}
+
+/// A deterministic stream of `u64`s derived from one entropy random number.
+#[derive(Clone, Debug)]
+pub struct RandomStream {
+    seed: [u8; 32],
+    block: [u8; 32],
+    counter: u64,
+    offset: usize,
+}
+
+impl RandomStream {
+    pub fn new(seed: &[u8; 32]) -> Self {
+        Self {
+            seed: *seed,
+            block: *seed,
+            counter: 0,
+            offset: 0,
+        }
+    }
+}
+
+impl RandomSource for RandomStream {
+    fn next_u64(&mut self) -> u64 {
+        if self.offset == self.block.len() {
+            self.counter += 1;
+            self.block = hashv(&[&self.seed, &self.counter.to_le_bytes()]).to_bytes();
+            self.offset = 0;
+        }
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.block[self.offset..self.offset + 8]);
+        self.offset += 8;
+        u64::from_le_bytes(bytes)
+    }
+}
+
+/// ChaCha20 keystream for one request. The key is
+/// `sha256(random_number || sequence_number (u64 LE) || consumer_seed)`; the
+/// 64-bit block counter starts at zero and the nonce is zero, as in the
+/// original ChaCha construction.
+#[derive(Clone, Debug)]
+pub struct EntropyRng {
+    key: [u32; 8],
+    counter: u64,
+    block: [u8; 64],
+    offset: usize,
+}
+
+impl EntropyRng {
+    /// `consumer_seed` separates independent streams drawn from the same
+    /// request, e.g. one per game or purpose.
+    pub fn new(random_number: &[u8; 32], sequence_number: u64, consumer_seed: &[u8]) -> Self {
+        let key = hashv(&[random_number, &sequence_number.to_le_bytes(), consumer_seed]).to_bytes();
+        Self::from_key(&key)
+    }
+
+    /// Keystream for a raw ChaCha20 key.
+    pub fn from_key(key: &[u8; 32]) -> Self {
+        let mut words = [0u32; 8];
+        for (word, bytes) in words.iter_mut().zip(key.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        Self {
+            key: words,
+            counter: 0,
+            block: [0u8; 64],
+            offset: 64,
+        }
+    }
+
+    pub fn fill_bytes(&mut self, out: &mut [u8]) {
+        for byte in out {
+            if self.offset == self.block.len() {
+                self.block = chacha20_block(&self.key, self.counter);
+                self.counter = self.counter.wrapping_add(1);
+                self.offset = 0;
+            }
+            *byte = self.block[self.offset];
+            self.offset += 1;
+        }
+    }
+}
+
+impl RandomSource for EntropyRng {
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+fn chacha20_block(key: &[u32; 8], counter: u64) -> [u8; 64] {
+    let mut input = [0u32; 16];
+    // "expand 32-byte k"
+    input[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+    input[4..12].copy_from_slice(key);
+    input[12] = counter as u32;
+    input[13] = (counter >> 32) as u32;
+
+    let mut state = input;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut out = [0u8; 64];
+    for (i, bytes) in out.chunks_exact_mut(4).enumerate() {
+        bytes.copy_from_slice(&state[i].wrapping_add(input[i]).to_le_bytes());
+    }
+    out
+}
+
+/// Uniform value in `low..=high` drawn from `seed`. Panics if `low > high`.
+pub fn u64_in_range(seed: &[u8; 32], low: u64, high: u64) -> u64 {
+    RandomStream::new(seed).u64_in_range(low, high)
+}
+
+/// Shuffles `items` in place using `seed`.
+pub fn shuffle<T>(seed: &[u8; 32], items: &mut [T]) {
+    RandomStream::new(seed).shuffle(items)
+}
+
+/// `K` distinct values from `0..n` drawn from `seed`. Panics if `K > n`.
+pub fn draw_without_replacement<const K: usize>(seed: &[u8; 32], n: u64) -> [u64; K] {
+    RandomStream::new(seed).draw_without_replacement(n)
+}
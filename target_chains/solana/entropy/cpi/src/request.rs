@@ -0,0 +1,509 @@
+use {
+    alloc::{vec, vec::Vec},
+    bytemuck::Zeroable,
+    entropy::{
+        accounts::CallbackMeta,
+        instruction::{
+            parse_args, parse_request_aggregate_args, parse_request_v2_args,
+            parse_request_with_callback_args, EntropyInstruction, RequestAggregateHeader,
+            RequestArgs, RequestV2Header, RequestWithCallbackHeader,
+        },
+        seeds::requester_signer_seeds,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        instruction::{AccountMeta, Instruction},
+        program::{get_return_data, invoke_signed},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+/// Index of the config account in [`RequestAccounts::metas`].
+const CONFIG_ACCOUNT_INDEX: usize = 6;
+
+/// Accounts entropy expects for a request, in order. `requester_program` must
+/// be the calling program; it also receives the callback.
+pub struct RequestAccounts<'a, 'info> {
+    pub requester_signer: &'a AccountInfo<'info>,
+    /// Bump of `requester_signer`, typically derived once off-chain or stored
+    /// in the caller's state; checked with `create_program_address` rather
+    /// than searched for on every request.
+    pub requester_signer_bump: u8,
+    pub payer: &'a AccountInfo<'info>,
+    pub requester_program: &'a AccountInfo<'info>,
+    pub request_account: &'a AccountInfo<'info>,
+    pub provider: &'a AccountInfo<'info>,
+    pub provider_vault: &'a AccountInfo<'info>,
+    pub config: &'a AccountInfo<'info>,
+    pub pyth_fee_vault: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub entropy_program: &'a AccountInfo<'info>,
+}
+
+impl<'info> RequestAccounts<'_, 'info> {
+    fn verified_requester_signer_bump(&self, program_id: &Pubkey) -> Result<u8, ProgramError> {
+        verify_requester_signer(
+            program_id,
+            self.requester_signer,
+            self.requester_signer_bump,
+            self.requester_program,
+            self.entropy_program,
+        )
+    }
+
+    fn metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(*self.requester_signer.key, true),
+            AccountMeta::new(*self.payer.key, true),
+            AccountMeta::new_readonly(*self.requester_program.key, false),
+            AccountMeta::new(*self.request_account.key, true),
+            AccountMeta::new(*self.provider.key, false),
+            AccountMeta::new(*self.provider_vault.key, false),
+            AccountMeta::new_readonly(*self.config.key, false),
+            AccountMeta::new(*self.pyth_fee_vault.key, false),
+            AccountMeta::new_readonly(*self.system_program.key, false),
+        ]
+    }
+
+    fn infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.requester_signer.clone(),
+            self.payer.clone(),
+            self.requester_program.clone(),
+            self.request_account.clone(),
+            self.provider.clone(),
+            self.provider_vault.clone(),
+            self.config.clone(),
+            self.pyth_fee_vault.clone(),
+            self.system_program.clone(),
+        ]
+    }
+}
+
+/// Accounts entropy expects for an aggregate request, in order.
+/// `providers` holds a `[provider, provider_vault]` pair per provider, in the
+/// order the request lists them.
+pub struct AggregateRequestAccounts<'a, 'info> {
+    pub requester_signer: &'a AccountInfo<'info>,
+    /// See [`RequestAccounts::requester_signer_bump`].
+    pub requester_signer_bump: u8,
+    pub payer: &'a AccountInfo<'info>,
+    pub requester_program: &'a AccountInfo<'info>,
+    pub request_account: &'a AccountInfo<'info>,
+    pub config: &'a AccountInfo<'info>,
+    pub pyth_fee_vault: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub providers: &'a [AccountInfo<'info>],
+    pub entropy_program: &'a AccountInfo<'info>,
+}
+
+impl<'info> AggregateRequestAccounts<'_, 'info> {
+    fn verified_requester_signer_bump(&self, program_id: &Pubkey) -> Result<u8, ProgramError> {
+        verify_requester_signer(
+            program_id,
+            self.requester_signer,
+            self.requester_signer_bump,
+            self.requester_program,
+            self.entropy_program,
+        )
+    }
+
+    fn metas(&self) -> Vec<AccountMeta> {
+        let mut metas = vec![
+            AccountMeta::new_readonly(*self.requester_signer.key, true),
+            AccountMeta::new(*self.payer.key, true),
+            AccountMeta::new_readonly(*self.requester_program.key, false),
+            AccountMeta::new(*self.request_account.key, true),
+            AccountMeta::new_readonly(*self.config.key, false),
+            AccountMeta::new(*self.pyth_fee_vault.key, false),
+            AccountMeta::new_readonly(*self.system_program.key, false),
+        ];
+        metas.extend(
+            self.providers
+                .iter()
+                .map(|account| AccountMeta::new(*account.key, false)),
+        );
+        metas
+    }
+
+    fn infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = vec![
+            self.requester_signer.clone(),
+            self.payer.clone(),
+            self.requester_program.clone(),
+            self.request_account.clone(),
+            self.config.clone(),
+            self.pyth_fee_vault.clone(),
+            self.system_program.clone(),
+        ];
+        infos.extend(self.providers.iter().cloned());
+        infos
+    }
+}
+
+/// Options of a request issued with [`request_with_callback`] or
+/// [`request_v2`]. The default request never expires, may be revealed in
+/// the slot it lands, uses the provider's default compute unit limit,
+/// refunds the payer and calls back with no accounts or data; set the
+/// fields that differ and take the rest from `..Default::default()`.
+#[derive(Clone, Copy, Default)]
+pub struct RequestParams<'a> {
+    /// Compute units for the callback; the provider's default if lower.
+    pub compute_unit_limit: u32,
+    /// Slots after which the request can no longer be revealed; zero for no
+    /// expiry.
+    pub ttl_slots: u32,
+    /// Slots that must pass after the request before it can be revealed.
+    pub min_reveal_delay_slots: u16,
+    /// Appends the user and provider contributions to the callback data;
+    /// see [`parse_callback_with_contributions`](crate::callback::parse_callback_with_contributions).
+    pub include_contributions: bool,
+    /// See `RequestArgs::random_number_version`.
+    pub random_number_version: u8,
+    /// Receives the request account's rent when it is closed; the payer if
+    /// `None`.
+    pub refund_address: Option<Pubkey>,
+    /// Accounts the callback receives, after the entropy signer.
+    pub callback_accounts: &'a [CallbackMeta],
+    /// Prefix of the callback instruction data.
+    pub callback_ix_data: &'a [u8],
+}
+
+/// Options of a request issued with [`request_aggregate`]; see
+/// [`RequestParams`], whose defaults these share.
+#[derive(Clone, Copy, Default)]
+pub struct AggregateRequestParams<'a> {
+    pub compute_unit_limit: u32,
+    pub ttl_slots: u32,
+    pub min_reveal_delay_slots: u16,
+    /// Mixes the blockhash of the request slot into the random number, which
+    /// keeps the request unrevealable for at least one slot.
+    pub use_blockhash: bool,
+    pub random_number_version: u8,
+    pub refund_address: Option<Pubkey>,
+    pub callback_accounts: &'a [CallbackMeta],
+    pub callback_ix_data: &'a [u8],
+}
+
+pub fn callback_meta(pubkey: &Pubkey, is_signer: bool, is_writable: bool) -> CallbackMeta {
+    CallbackMeta {
+        pubkey: pubkey.to_bytes(),
+        is_signer: u16::from(is_signer),
+        is_writable: u16::from(is_writable),
+    }
+}
+
+/// Issues entropy `Request` signed by the caller's requester-signer PDA and
+/// returns the assigned sequence number.
+pub fn request(
+    program_id: &Pubkey,
+    accounts: &RequestAccounts,
+    args: &RequestArgs,
+) -> Result<u64, ProgramError> {
+    let bump = accounts.verified_requester_signer_bump(program_id)?;
+    let args = RequestArgs {
+        requester_signer_bump: bump,
+        ..*args
+    };
+
+    invoke_request(
+        accounts.entropy_program,
+        bump,
+        EntropyInstruction::build(&args),
+        accounts.metas(),
+        accounts.infos(),
+    )
+}
+
+/// Issues entropy `RequestWithCallback`; the callback is delivered to
+/// `program_id` with `params.callback_accounts` and `params.callback_ix_data`
+/// as the prefix.
+pub fn request_with_callback(
+    program_id: &Pubkey,
+    accounts: &RequestAccounts,
+    user_randomness: [u8; 32],
+    params: &RequestParams,
+) -> Result<u64, ProgramError> {
+    let bump = accounts.verified_requester_signer_bump(program_id)?;
+    let header = RequestWithCallbackHeader {
+        user_randomness,
+        compute_unit_limit: params.compute_unit_limit,
+        ttl_slots: params.ttl_slots,
+        min_reveal_delay_slots: params.min_reveal_delay_slots,
+        include_contributions: u8::from(params.include_contributions),
+        random_number_version: params.random_number_version,
+        refund_address: refund_address_bytes(params.refund_address),
+        ..RequestWithCallbackHeader::zeroed()
+    };
+    let data = EntropyInstruction::build_callback_request(
+        &header,
+        bump,
+        params.callback_accounts,
+        params.callback_ix_data,
+    );
+    invoke_callback_request(accounts, None, bump, data, false)
+}
+
+/// Like [`request_with_callback`], for callers that already hold packed
+/// `RequestWithCallback` data (e.g. forwarded from a client). The data is
+/// passed on as is: entropy checks its `requester_signer_bump` against
+/// `accounts.requester_signer`, and searches for the bump if it is 0. The
+/// call itself is signed with `accounts.requester_signer_bump`.
+pub fn request_with_callback_raw(
+    program_id: &Pubkey,
+    accounts: &RequestAccounts,
+    data: Vec<u8>,
+) -> Result<u64, ProgramError> {
+    callback_request_raw(program_id, accounts, None, data, false)
+}
+
+/// Like [`request_with_callback_raw`], for a request whose callback account
+/// list was written beforehand: the callback also receives the listed
+/// accounts, after those in `data`.
+pub fn request_with_callback_account_list_raw<'info>(
+    program_id: &Pubkey,
+    accounts: &RequestAccounts<'_, 'info>,
+    callback_account_list: &AccountInfo<'info>,
+    data: Vec<u8>,
+) -> Result<u64, ProgramError> {
+    callback_request_raw(
+        program_id,
+        accounts,
+        Some(callback_account_list),
+        data,
+        false,
+    )
+}
+
+/// Issues entropy `RequestV2`: like [`request_with_callback`], but entropy
+/// generates the user randomness from its config seed. `accounts.config` must
+/// be writable.
+pub fn request_v2(
+    program_id: &Pubkey,
+    accounts: &RequestAccounts,
+    params: &RequestParams,
+) -> Result<u64, ProgramError> {
+    let bump = accounts.verified_requester_signer_bump(program_id)?;
+    let header = RequestV2Header {
+        compute_unit_limit: params.compute_unit_limit,
+        ttl_slots: params.ttl_slots,
+        min_reveal_delay_slots: params.min_reveal_delay_slots,
+        include_contributions: u8::from(params.include_contributions),
+        random_number_version: params.random_number_version,
+        refund_address: refund_address_bytes(params.refund_address),
+        ..RequestV2Header::zeroed()
+    };
+    let data = EntropyInstruction::build_callback_request(
+        &header,
+        bump,
+        params.callback_accounts,
+        params.callback_ix_data,
+    );
+    invoke_callback_request(accounts, None, bump, data, true)
+}
+
+/// Like [`request_v2`], for callers that already hold packed `RequestV2`
+/// data; see [`request_with_callback_raw`].
+pub fn request_v2_raw(
+    program_id: &Pubkey,
+    accounts: &RequestAccounts,
+    data: Vec<u8>,
+) -> Result<u64, ProgramError> {
+    callback_request_raw(program_id, accounts, None, data, true)
+}
+
+/// Like [`request_v2_raw`], for a request with a callback account list; see
+/// [`request_with_callback_account_list_raw`].
+pub fn request_v2_with_callback_account_list_raw<'info>(
+    program_id: &Pubkey,
+    accounts: &RequestAccounts<'_, 'info>,
+    callback_account_list: &AccountInfo<'info>,
+    data: Vec<u8>,
+) -> Result<u64, ProgramError> {
+    callback_request_raw(
+        program_id,
+        accounts,
+        Some(callback_account_list),
+        data,
+        true,
+    )
+}
+
+fn callback_request_raw<'info>(
+    program_id: &Pubkey,
+    accounts: &RequestAccounts<'_, 'info>,
+    callback_account_list: Option<&AccountInfo<'info>>,
+    data: Vec<u8>,
+    config_writable: bool,
+) -> Result<u64, ProgramError> {
+    let bump = accounts.verified_requester_signer_bump(program_id)?;
+    invoke_callback_request(accounts, callback_account_list, bump, data, config_writable)
+}
+
+/// Issues `RequestWithCallback` or `RequestV2`. Entropy expects the callback
+/// program after the request accounts, then the callback account list if
+/// there is one.
+fn invoke_callback_request<'info>(
+    accounts: &RequestAccounts<'_, 'info>,
+    callback_account_list: Option<&AccountInfo<'info>>,
+    requester_signer_bump: u8,
+    data: Vec<u8>,
+    config_writable: bool,
+) -> Result<u64, ProgramError> {
+    let mut metas = accounts.metas();
+    metas[CONFIG_ACCOUNT_INDEX].is_writable = config_writable;
+    metas.push(AccountMeta::new_readonly(
+        *accounts.requester_program.key,
+        false,
+    ));
+    let mut infos = accounts.infos();
+    infos.push(accounts.requester_program.clone());
+    if let Some(callback_account_list) = callback_account_list {
+        metas.push(AccountMeta::new_readonly(*callback_account_list.key, false));
+        infos.push(callback_account_list.clone());
+    }
+
+    invoke_request(
+        accounts.entropy_program,
+        requester_signer_bump,
+        data,
+        metas,
+        infos,
+    )
+}
+
+/// Issues entropy `RequestAggregate`: one request served by every provider in
+/// `accounts.providers`, finalized once `threshold` of them have revealed.
+/// Returns the first provider's sequence number, which the callback carries.
+pub fn request_aggregate(
+    program_id: &Pubkey,
+    accounts: &AggregateRequestAccounts,
+    user_randomness: [u8; 32],
+    threshold: u8,
+    params: &AggregateRequestParams,
+) -> Result<u64, ProgramError> {
+    let num_providers =
+        u8::try_from(accounts.providers.len() / 2).map_err(|_| ProgramError::InvalidArgument)?;
+    let bump = accounts.verified_requester_signer_bump(program_id)?;
+    let header = RequestAggregateHeader {
+        user_randomness,
+        compute_unit_limit: params.compute_unit_limit,
+        ttl_slots: params.ttl_slots,
+        num_providers,
+        threshold,
+        min_reveal_delay_slots: params.min_reveal_delay_slots,
+        use_blockhash: u8::from(params.use_blockhash),
+        random_number_version: params.random_number_version,
+        refund_address: refund_address_bytes(params.refund_address),
+        ..RequestAggregateHeader::zeroed()
+    };
+    let data = EntropyInstruction::build_callback_request(
+        &header,
+        bump,
+        params.callback_accounts,
+        params.callback_ix_data,
+    );
+    invoke_request(
+        accounts.entropy_program,
+        bump,
+        data,
+        accounts.metas(),
+        accounts.infos(),
+    )
+}
+
+/// Like [`request_aggregate`], for callers that already hold packed
+/// `RequestAggregate` data; see [`request_with_callback_raw`].
+pub fn request_aggregate_raw(
+    program_id: &Pubkey,
+    accounts: &AggregateRequestAccounts,
+    data: Vec<u8>,
+) -> Result<u64, ProgramError> {
+    let bump = accounts.verified_requester_signer_bump(program_id)?;
+    invoke_request(
+        accounts.entropy_program,
+        bump,
+        data,
+        accounts.metas(),
+        accounts.infos(),
+    )
+}
+
+/// The `requester_signer_bump` carried by packed `Request`,
+/// `RequestWithCallback`, `RequestV2` or `RequestAggregate` data, for
+/// programs that forward such data to the `_raw` helpers and sign with the
+/// bump the client derived.
+pub fn parse_requester_signer_bump(data: &[u8]) -> Result<u8, ProgramError> {
+    let (instruction, payload) = EntropyInstruction::parse(data)?;
+    match instruction {
+        EntropyInstruction::Request => {
+            Ok(parse_args::<RequestArgs>(payload)?.requester_signer_bump)
+        }
+        EntropyInstruction::RequestWithCallback => {
+            Ok(parse_request_with_callback_args(payload)?.requester_signer_bump)
+        }
+        EntropyInstruction::RequestV2 => Ok(parse_request_v2_args(payload)?.requester_signer_bump),
+        EntropyInstruction::RequestAggregate => {
+            Ok(parse_request_aggregate_args(payload)?.requester_signer_bump)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn refund_address_bytes(refund_address: Option<Pubkey>) -> [u8; 32] {
+    refund_address.map_or([0u8; 32], |address| address.to_bytes())
+}
+
+/// Checks that `requester_signer` is the requester signer PDA, with
+/// `requester_signer_bump`, of the calling program `program_id` for
+/// `entropy_program`, and returns the bump.
+fn verify_requester_signer(
+    program_id: &Pubkey,
+    requester_signer: &AccountInfo,
+    requester_signer_bump: u8,
+    requester_program: &AccountInfo,
+    entropy_program: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    if requester_program.key != program_id {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let expected_signer = Pubkey::create_program_address(
+        &requester_signer_seeds(entropy_program.key, &requester_signer_bump),
+        program_id,
+    )
+    .map_err(|_| ProgramError::InvalidSeeds)?;
+    if requester_signer.key != &expected_signer {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(requester_signer_bump)
+}
+
+/// Invokes entropy signed by the requester signer PDA with
+/// `requester_signer_bump`, and returns the sequence number it assigned.
+fn invoke_request(
+    entropy_program: &AccountInfo,
+    requester_signer_bump: u8,
+    data: Vec<u8>,
+    metas: Vec<AccountMeta>,
+    infos: Vec<AccountInfo>,
+) -> Result<u64, ProgramError> {
+    let entropy_program_id = entropy_program.key;
+    let instruction = Instruction {
+        program_id: *entropy_program_id,
+        data,
+        accounts: metas,
+    };
+    let signer_seeds = requester_signer_seeds(entropy_program_id, &requester_signer_bump);
+    invoke_signed(&instruction, &infos, &[&signer_seeds])?;
+
+    // Entropy returns the sequence number as u64 LE return data.
+    match get_return_data() {
+        Some((program, data)) if &program == entropy_program_id => data
+            .get(..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or(ProgramError::InvalidAccountData),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
@@ -0,0 +1,10 @@
+//! Entropy's signer seeds, and helpers that lay them out for
+//! `invoke_signed`. [`request_with_callback`](crate::request_with_callback)
+//! and friends already sign with the requester signer; these are for
+//! programs that build their own entropy instructions.
+
+pub use entropy::{
+    constants::{ENTROPY_SIGNER_SEED, REQUESTER_SIGNER_SEED},
+    pda::{entropy_signer_pda, requester_signer_pda},
+    seeds::{entropy_signer_seeds, requester_signer_seeds},
+};
@@ -0,0 +1,158 @@
+use {
+    entropy::pda::entropy_signer_pda,
+    entropy_cpi::{entropy_callback, Contributions},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::cell::Cell,
+};
+
+const ENTROPY_PROGRAM_ID: Pubkey = Pubkey::new_from_array([7u8; 32]);
+
+/// (remaining accounts, first prefix byte, sequence number, provider, random number)
+type Received = (usize, u8, u64, Pubkey, [u8; 32]);
+
+thread_local! {
+    static RECEIVED: Cell<Option<Received>> = const { Cell::new(None) };
+    static RECEIVED_CONTRIBUTIONS: Cell<Option<(u64, Contributions)>> = const { Cell::new(None) };
+}
+
+#[entropy_callback(entropy_program_id = ENTROPY_PROGRAM_ID)]
+fn on_random(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    prefix: &[u8],
+    sequence_number: u64,
+    provider: Pubkey,
+    random_number: [u8; 32],
+) -> ProgramResult {
+    RECEIVED.with(|received| {
+        received.set(Some((
+            accounts.len(),
+            prefix[0],
+            sequence_number,
+            provider,
+            random_number,
+        )))
+    });
+    Ok(())
+}
+
+#[entropy_callback(entropy_program_id = ENTROPY_PROGRAM_ID, include_contributions)]
+fn on_random_with_contributions(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _prefix: &[u8],
+    sequence_number: u64,
+    _provider: Pubkey,
+    _random_number: [u8; 32],
+    contributions: Contributions,
+) -> ProgramResult {
+    RECEIVED_CONTRIBUTIONS.with(|received| received.set(Some((sequence_number, contributions))));
+    Ok(())
+}
+
+fn callback_data(prefix: &[u8], sequence_number: u64, provider: &Pubkey) -> Vec<u8> {
+    let mut data = prefix.to_vec();
+    data.extend_from_slice(&sequence_number.to_le_bytes());
+    data.extend_from_slice(provider.as_ref());
+    data.extend_from_slice(&[9u8; 32]);
+    data
+}
+
+fn invoke(signer: Pubkey, is_signer: bool, data: &[u8]) -> ProgramResult {
+    invoke_handler(on_random, signer, is_signer, data)
+}
+
+fn invoke_handler(
+    handler: fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult,
+    signer: Pubkey,
+    is_signer: bool,
+    data: &[u8],
+) -> ProgramResult {
+    let program_id = Pubkey::new_unique();
+    let state = Pubkey::new_unique();
+    let (mut signer_lamports, mut state_lamports) = (0u64, 0u64);
+    let (mut signer_data, mut state_data) = (Vec::new(), vec![0u8; 8]);
+    let accounts = [
+        AccountInfo::new(
+            &signer,
+            is_signer,
+            false,
+            &mut signer_lamports,
+            &mut signer_data,
+            &ENTROPY_PROGRAM_ID,
+            false,
+            0,
+        ),
+        AccountInfo::new(
+            &state,
+            false,
+            true,
+            &mut state_lamports,
+            &mut state_data,
+            &program_id,
+            false,
+            0,
+        ),
+    ];
+    handler(&program_id, &accounts, data)
+}
+
+#[test]
+fn test_entropy_callback_passes_typed_arguments() {
+    let (entropy_signer, _) = entropy_signer_pda(&ENTROPY_PROGRAM_ID);
+    let provider = Pubkey::new_unique();
+
+    invoke(entropy_signer, true, &callback_data(&[0xCB], 42, &provider)).unwrap();
+
+    let received = RECEIVED.with(Cell::take).unwrap();
+    assert_eq!(received, (1, 0xCB, 42, provider, [9u8; 32]));
+}
+
+#[test]
+fn test_entropy_callback_rejects_wrong_signer() {
+    let provider = Pubkey::new_unique();
+    let data = callback_data(&[], 1, &provider);
+
+    let err = invoke(Pubkey::new_unique(), true, &data).unwrap_err();
+    assert_eq!(err, ProgramError::InvalidSeeds);
+
+    let (entropy_signer, _) = entropy_signer_pda(&ENTROPY_PROGRAM_ID);
+    let err = invoke(entropy_signer, false, &data).unwrap_err();
+    assert_eq!(err, ProgramError::MissingRequiredSignature);
+}
+
+#[test]
+fn test_entropy_callback_rejects_short_data() {
+    let (entropy_signer, _) = entropy_signer_pda(&ENTROPY_PROGRAM_ID);
+    let err = invoke(entropy_signer, true, &[0u8; 10]).unwrap_err();
+    assert_eq!(err, ProgramError::InvalidInstructionData);
+}
+
+#[test]
+fn test_entropy_callback_passes_contributions() {
+    let (entropy_signer, _) = entropy_signer_pda(&ENTROPY_PROGRAM_ID);
+    let mut data = callback_data(&[0xCB], 42, &Pubkey::new_unique());
+    data.extend_from_slice(&[1u8; 32]);
+    data.extend_from_slice(&[2u8; 32]);
+
+    invoke_handler(on_random_with_contributions, entropy_signer, true, &data).unwrap();
+
+    let (sequence_number, contributions) = RECEIVED_CONTRIBUTIONS.with(Cell::take).unwrap();
+    assert_eq!(sequence_number, 42);
+    assert_eq!(
+        contributions,
+        Contributions {
+            user_contribution: [1u8; 32],
+            provider_contribution: [2u8; 32],
+        }
+    );
+
+    // Without the contributions the tail is too short to hold them.
+    let short = callback_data(&[], 42, &Pubkey::new_unique());
+    let err =
+        invoke_handler(on_random_with_contributions, entropy_signer, true, &short).unwrap_err();
+    assert_eq!(err, ProgramError::InvalidInstructionData);
+}
@@ -0,0 +1,153 @@
+use {
+    bytemuck::{bytes_of, Zeroable},
+    entropy::{
+        accounts::{ProgramRegistry, RegisteredProgram},
+        discriminator::program_registry_discriminator,
+        pda::entropy_signer_pda,
+    },
+    entropy_cpi::verify_registered_entropy_signer,
+    solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey},
+};
+
+const REGISTRY_PROGRAM_ID: Pubkey = Pubkey::new_from_array([7u8; 32]);
+const REDEPLOYED_PROGRAM_ID: Pubkey = Pubkey::new_from_array([8u8; 32]);
+
+fn registry_data(program_ids: &[Pubkey]) -> Vec<u8> {
+    let mut registry = ProgramRegistry::zeroed();
+    registry.discriminator = program_registry_discriminator();
+    registry.num_programs = program_ids.len() as u8;
+    for (entry, program_id) in registry.programs.iter_mut().zip(program_ids) {
+        *entry = RegisteredProgram {
+            program_id: program_id.to_bytes(),
+            entropy_signer: entropy_signer_pda(program_id).0.to_bytes(),
+        };
+    }
+    bytes_of(&registry).to_vec()
+}
+
+/// Verifies `signer` against a registry account at `registry_key` owned by
+/// `registry_owner`.
+fn verify(
+    signer: Pubkey,
+    is_signer: bool,
+    registry_key: Pubkey,
+    registry_owner: Pubkey,
+    mut registry_data: Vec<u8>,
+) -> Result<Pubkey, ProgramError> {
+    let (mut signer_lamports, mut registry_lamports) = (0u64, 0u64);
+    let mut signer_data = Vec::new();
+    let signer_owner = Pubkey::default();
+    let entropy_signer = AccountInfo::new(
+        &signer,
+        is_signer,
+        false,
+        &mut signer_lamports,
+        &mut signer_data,
+        &signer_owner,
+        false,
+        0,
+    );
+    let registry = AccountInfo::new(
+        &registry_key,
+        false,
+        false,
+        &mut registry_lamports,
+        &mut registry_data,
+        &registry_owner,
+        false,
+        0,
+    );
+    verify_registered_entropy_signer(&entropy_signer, &registry, &REGISTRY_PROGRAM_ID)
+}
+
+#[test]
+fn test_accepts_registered_programs() {
+    let (registry_key, _) = entropy_signer_pda(&REGISTRY_PROGRAM_ID);
+    let data = registry_data(&[REGISTRY_PROGRAM_ID, REDEPLOYED_PROGRAM_ID]);
+
+    for program_id in [REGISTRY_PROGRAM_ID, REDEPLOYED_PROGRAM_ID] {
+        let (signer, _) = entropy_signer_pda(&program_id);
+        assert_eq!(
+            verify(
+                signer,
+                true,
+                registry_key,
+                REGISTRY_PROGRAM_ID,
+                data.clone()
+            ),
+            Ok(program_id)
+        );
+    }
+}
+
+#[test]
+fn test_rejects_unregistered_or_unsigned_signer() {
+    let (registry_key, _) = entropy_signer_pda(&REGISTRY_PROGRAM_ID);
+    let data = registry_data(&[REGISTRY_PROGRAM_ID]);
+
+    let (signer, _) = entropy_signer_pda(&REDEPLOYED_PROGRAM_ID);
+    assert_eq!(
+        verify(
+            signer,
+            true,
+            registry_key,
+            REGISTRY_PROGRAM_ID,
+            data.clone()
+        ),
+        Err(ProgramError::InvalidSeeds)
+    );
+
+    let (signer, _) = entropy_signer_pda(&REGISTRY_PROGRAM_ID);
+    assert_eq!(
+        verify(signer, false, registry_key, REGISTRY_PROGRAM_ID, data),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+}
+
+#[test]
+fn test_rejects_untrusted_registry() {
+    let (signer, _) = entropy_signer_pda(&REDEPLOYED_PROGRAM_ID);
+    let data = registry_data(&[REDEPLOYED_PROGRAM_ID]);
+
+    // A registry kept by another program lists whatever it likes.
+    let (other_registry, _) = entropy_signer_pda(&REDEPLOYED_PROGRAM_ID);
+    assert_eq!(
+        verify(
+            signer,
+            true,
+            other_registry,
+            REDEPLOYED_PROGRAM_ID,
+            data.clone()
+        ),
+        Err(ProgramError::InvalidSeeds)
+    );
+
+    let (registry_key, _) = entropy_signer_pda(&REGISTRY_PROGRAM_ID);
+    assert_eq!(
+        verify(
+            signer,
+            true,
+            registry_key,
+            REDEPLOYED_PROGRAM_ID,
+            data.clone()
+        ),
+        Err(ProgramError::IllegalOwner)
+    );
+
+    let mut wrong_discriminator = data;
+    wrong_discriminator[0] = 0xff;
+    assert_eq!(
+        verify(
+            signer,
+            true,
+            registry_key,
+            REGISTRY_PROGRAM_ID,
+            wrong_discriminator
+        ),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        verify(signer, true, registry_key, REGISTRY_PROGRAM_ID, Vec::new()),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
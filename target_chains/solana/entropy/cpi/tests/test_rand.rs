@@ -0,0 +1,133 @@
+use entropy_cpi::rand::{
+    draw_without_replacement, shuffle, u64_in_range, EntropyRng, RandomSource, RandomStream,
+};
+
+fn seed(byte: u8) -> [u8; 32] {
+    let mut seed = [0u8; 32];
+    seed[0] = byte;
+    seed[31] = byte.wrapping_mul(31);
+    seed
+}
+
+#[test]
+fn test_u64_in_range_stays_in_bounds() {
+    for byte in 0..=u8::MAX {
+        let value = u64_in_range(&seed(byte), 10, 20);
+        assert!((10..=20).contains(&value));
+    }
+    assert_eq!(u64_in_range(&seed(1), 7, 7), 7);
+    assert_eq!(u64_in_range(&[0xff; 32], 0, u64::MAX), u64::MAX);
+}
+
+#[test]
+fn test_u64_in_range_rejects_biased_values() {
+    // The first word is below the rejection threshold for a span of 2^63 + 1,
+    // so the draw must come from the next word instead of `word % span`.
+    let mut seed = [0u8; 32];
+    seed[8..16].copy_from_slice(&(u64::MAX - 1).to_le_bytes());
+    let high = 1u64 << 63;
+    assert_eq!(u64_in_range(&seed, 0, high), (u64::MAX - 1) % (high + 1));
+}
+
+#[test]
+fn test_stream_extends_past_seed() {
+    let mut stream = RandomStream::new(&seed(3));
+    let words: Vec<u64> = (0..12).map(|_| stream.next_u64()).collect();
+    assert_eq!(
+        words[0],
+        u64::from_le_bytes(seed(3)[..8].try_into().unwrap())
+    );
+
+    let mut again = RandomStream::new(&seed(3));
+    assert!(words.iter().all(|word| *word == again.next_u64()));
+    assert_ne!(words[4..8], words[8..12]);
+}
+
+#[test]
+fn test_shuffle_is_a_permutation() {
+    let mut items = [0u8, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+    shuffle(&seed(5), &mut items);
+    let mut sorted = items;
+    sorted.sort();
+    assert_eq!(sorted, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+    let mut other = [0u8, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+    shuffle(&seed(6), &mut other);
+    assert_ne!(items, other);
+}
+
+#[test]
+fn test_draw_without_replacement_is_distinct() {
+    for byte in 0..64 {
+        let drawn: [u64; 5] = draw_without_replacement(&seed(byte), 6);
+        for (i, value) in drawn.iter().enumerate() {
+            assert!(*value < 6);
+            assert!(!drawn[..i].contains(value));
+        }
+    }
+    let all: [u64; 3] = draw_without_replacement(&seed(9), 3);
+    let mut sorted = all;
+    sorted.sort();
+    assert_eq!(sorted, [0, 1, 2]);
+}
+
+#[test]
+#[should_panic]
+fn test_draw_more_than_population_panics() {
+    let _: [u64; 4] = draw_without_replacement(&seed(1), 3);
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[test]
+fn test_entropy_rng_matches_chacha20_vectors() {
+    // RFC 8439 appendix A.1, test vectors 1 and 2: all-zero key and nonce,
+    // block counters 0 and 1.
+    let mut keystream = [0u8; 128];
+    EntropyRng::from_key(&[0u8; 32]).fill_bytes(&mut keystream);
+    assert_eq!(
+        hex(&keystream[..64]),
+        "76b8e0ada0f13d90405d6ae55386bd28bdd219b8a08ded1aa836efcc8b770dc7\
+         da41597c5157488d7724e03fb8d84a376a43b8f41518a11cc387b669b2ee6586"
+    );
+    assert_eq!(
+        hex(&keystream[64..]),
+        "9f07e7be5551387a98ba977c732d080dcb0f29a048e3656912c6533e32ee7aed\
+         29b721769ce64e43d57133b074d839d531ed1f28510afb45ace10a1f4b794d6f"
+    );
+}
+
+#[test]
+fn test_entropy_rng_is_deterministic() {
+    // Pinned output: on-chain and off-chain callers must see the same values.
+    let mut rng = EntropyRng::new(&seed(1), 42, b"game");
+    let words: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+    assert_eq!(
+        words,
+        [
+            3182545137393892213,
+            3234910570453185473,
+            16232127693046365427
+        ]
+    );
+
+    // Split reads see the same keystream as one contiguous read.
+    let mut whole = [0u8; 100];
+    EntropyRng::new(&seed(1), 42, b"game").fill_bytes(&mut whole);
+    let mut split = [0u8; 100];
+    let mut rng = EntropyRng::new(&seed(1), 42, b"game");
+    rng.fill_bytes(&mut split[..7]);
+    rng.fill_bytes(&mut split[7..70]);
+    rng.fill_bytes(&mut split[70..]);
+    assert_eq!(whole, split);
+}
+
+#[test]
+fn test_entropy_rng_separates_inputs() {
+    let first = EntropyRng::new(&seed(1), 42, b"game").next_u64();
+    assert_ne!(first, EntropyRng::new(&seed(2), 42, b"game").next_u64());
+    assert_ne!(first, EntropyRng::new(&seed(1), 43, b"game").next_u64());
+    assert_ne!(first, EntropyRng::new(&seed(1), 42, b"lottery").next_u64());
+}
@@ -0,0 +1,27 @@
+use {
+    entropy_cpi::seeds::{
+        entropy_signer_pda, entropy_signer_seeds, requester_signer_pda, requester_signer_seeds,
+    },
+    solana_program::pubkey::Pubkey,
+};
+
+const ENTROPY_PROGRAM_ID: Pubkey = Pubkey::new_from_array([7u8; 32]);
+const REQUESTER_PROGRAM_ID: Pubkey = Pubkey::new_from_array([9u8; 32]);
+
+#[test]
+fn test_signer_seeds_recreate_the_pdas() {
+    let (requester_signer, bump) = requester_signer_pda(&REQUESTER_PROGRAM_ID, &ENTROPY_PROGRAM_ID);
+    assert_eq!(
+        Pubkey::create_program_address(
+            &requester_signer_seeds(&ENTROPY_PROGRAM_ID, &bump),
+            &REQUESTER_PROGRAM_ID,
+        ),
+        Ok(requester_signer)
+    );
+
+    let (entropy_signer, bump) = entropy_signer_pda(&ENTROPY_PROGRAM_ID);
+    assert_eq!(
+        Pubkey::create_program_address(&entropy_signer_seeds(&bump), &ENTROPY_PROGRAM_ID),
+        Ok(entropy_signer)
+    );
+}
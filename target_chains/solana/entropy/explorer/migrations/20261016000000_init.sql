@@ -0,0 +1,47 @@
+-- Requests are keyed like the program keys them: a provider's sequence
+-- numbers are never reused.
+CREATE TABLE request (
+    provider TEXT NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    request_account TEXT NOT NULL,
+    requester_program TEXT NOT NULL,
+    -- NULL when only the reveal was indexed.
+    payer TEXT,
+    request_slot INTEGER NOT NULL,
+    request_signature TEXT,
+    compute_unit_limit INTEGER,
+    use_blockhash INTEGER,
+    random_number_version INTEGER,
+    -- 'pending', 'revealed' or 'closed' (closed without a reveal, i.e.
+    -- expired and reclaimed).
+    state TEXT NOT NULL,
+    reveal_slot INTEGER,
+    reveal_signature TEXT,
+    random_number TEXT,
+    callback_invoked INTEGER,
+    PRIMARY KEY (provider, sequence_number)
+);
+CREATE INDEX request__provider__request_slot ON request (provider, request_slot);
+CREATE INDEX request__requester_program__request_slot ON request (requester_program, request_slot);
+CREATE INDEX request__state__request_slot ON request (state, request_slot);
+
+-- Latest snapshot of each listed provider account.
+CREATE TABLE provider (
+    authority TEXT PRIMARY KEY NOT NULL,
+    fee_lamports INTEGER NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    end_sequence_number INTEGER NOT NULL,
+    outstanding_requests INTEGER NOT NULL,
+    reveal_count INTEGER NOT NULL,
+    reveal_latency_sum_slots INTEGER NOT NULL,
+    reveal_latency_max_slots INTEGER NOT NULL,
+    uri TEXT NOT NULL,
+    updated_slot INTEGER NOT NULL
+);
+
+-- The newest transaction indexed, where the next poll resumes.
+CREATE TABLE cursor (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 0),
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL
+);
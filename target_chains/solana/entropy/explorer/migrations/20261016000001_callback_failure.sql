@@ -0,0 +1,20 @@
+-- Callbacks that failed inside a reveal, from the failed transactions'
+-- logs. A failed reveal changes nothing, so a request retried after its
+-- callback failed has one row per failed transaction.
+CREATE TABLE callback_failure (
+    signature TEXT PRIMARY KEY NOT NULL,
+    provider TEXT NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    request_account TEXT NOT NULL,
+    requester_program TEXT NOT NULL,
+    request_slot INTEGER NOT NULL,
+    reveal_slot INTEGER NOT NULL,
+    compute_unit_limit INTEGER NOT NULL,
+    -- NULL when the runtime did not log it.
+    compute_units_consumed INTEGER,
+    error TEXT NOT NULL,
+    -- NULL unless the callback failed with a custom program error.
+    error_code INTEGER
+);
+CREATE INDEX callback_failure__requester_program__reveal_slot
+    ON callback_failure (requester_program, reveal_slot);
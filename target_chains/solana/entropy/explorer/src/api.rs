@@ -0,0 +1,282 @@
+//! JSON API over the indexed data. Pubkeys are base58 path segments; lists
+//! are newest first and paged with `limit` / `offset`.
+
+use {
+    crate::{
+        db::{CallbackFailureRecord, Database, ProviderRecord, RequestFilter, RequestRecord},
+        stats::LatencyStats,
+    },
+    axum::{
+        extract::{Path, Query, State},
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        routing::get,
+        Json, Router,
+    },
+    serde::{Deserialize, Serialize},
+    solana_sdk::pubkey::Pubkey,
+    std::str::FromStr,
+    tracing::warn,
+};
+
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1000;
+/// Reveals summarized by the latency endpoint unless `window` says otherwise.
+const DEFAULT_LATENCY_WINDOW: u32 = 1000;
+const MAX_LATENCY_WINDOW: u32 = 100_000;
+
+#[derive(Clone)]
+pub struct ApiState {
+    pub db: Database,
+    /// A provider whose oldest pending request is this many slots older
+    /// than its snapshot is reported as stalled.
+    pub stall_slots: u64,
+}
+
+pub fn routes(state: ApiState) -> Router {
+    Router::new()
+        .route("/live", get(|| async { "OK" }))
+        .route("/v1/providers", get(providers))
+        .route("/v1/providers/:authority", get(provider))
+        .route("/v1/providers/:authority/latency", get(provider_latency))
+        .route("/v1/providers/:authority/requests", get(provider_requests))
+        .route(
+            "/v1/providers/:authority/requests/:sequence_number",
+            get(provider_request),
+        )
+        .route("/v1/requesters/:program/requests", get(requester_requests))
+        .route(
+            "/v1/requesters/:program/callback-failures",
+            get(requester_callback_failures),
+        )
+        .with_state(state)
+}
+
+pub enum ApiError {
+    InvalidPubkey(String),
+    InvalidLimit,
+    NotFound,
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        ApiError::Internal(err)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::InvalidPubkey(value) => {
+                (StatusCode::BAD_REQUEST, format!("Invalid pubkey {value}")).into_response()
+            }
+            ApiError::InvalidLimit => (
+                StatusCode::BAD_REQUEST,
+                format!("limit must be between 1 and {MAX_LIMIT}"),
+            )
+                .into_response(),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "Not found").into_response(),
+            ApiError::Internal(err) => {
+                warn!(error = %err, "Explorer query failed");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal error").into_response()
+            }
+        }
+    }
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, ApiError> {
+    Pubkey::from_str(value).map_err(|_| ApiError::InvalidPubkey(value.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageParams {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    /// `pending`, `revealed` or `closed`.
+    pub state: Option<String>,
+}
+
+impl PageParams {
+    fn limit(&self) -> Result<u32, ApiError> {
+        match self.limit.unwrap_or(DEFAULT_LIMIT) {
+            limit @ 1..=MAX_LIMIT => Ok(limit),
+            _ => Err(ApiError::InvalidLimit),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    /// Requests have waited longer than `stall_slots` for a reveal.
+    Stalled,
+    /// The provider's hash chain is used up; it must rotate to serve again.
+    OutOfRandomness,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProviderHealth {
+    #[serde(flatten)]
+    pub provider: ProviderRecord,
+    /// Requests the current hash chain can still serve.
+    pub remaining_randomness: u64,
+    /// Indexed requests without a reveal.
+    pub pending_requests: u64,
+    pub oldest_pending_slot: Option<u64>,
+    pub last_reveal_slot: Option<u64>,
+    pub status: HealthStatus,
+}
+
+impl ProviderHealth {
+    pub fn new(
+        provider: ProviderRecord,
+        pending_requests: u64,
+        oldest_pending_slot: Option<u64>,
+        last_reveal_slot: Option<u64>,
+        stall_slots: u64,
+    ) -> Self {
+        let remaining_randomness = provider
+            .end_sequence_number
+            .saturating_sub(provider.sequence_number);
+        let stalled = oldest_pending_slot
+            .is_some_and(|slot| provider.updated_slot.saturating_sub(slot) > stall_slots);
+        let status = if remaining_randomness == 0 {
+            HealthStatus::OutOfRandomness
+        } else if stalled {
+            HealthStatus::Stalled
+        } else {
+            HealthStatus::Ok
+        };
+        Self {
+            provider,
+            remaining_randomness,
+            pending_requests,
+            oldest_pending_slot,
+            last_reveal_slot,
+            status,
+        }
+    }
+}
+
+async fn health(state: &ApiState, provider: ProviderRecord) -> Result<ProviderHealth, ApiError> {
+    let authority = parse_pubkey(&provider.authority)?;
+    let (pending_requests, oldest_pending_slot) = state.db.pending_summary(&authority).await?;
+    let last_reveal_slot = state.db.last_reveal_slot(&authority).await?;
+    Ok(ProviderHealth::new(
+        provider,
+        pending_requests,
+        oldest_pending_slot,
+        last_reveal_slot,
+        state.stall_slots,
+    ))
+}
+
+async fn providers(State(state): State<ApiState>) -> Result<Json<Vec<ProviderHealth>>, ApiError> {
+    let mut providers = Vec::new();
+    for provider in state.db.providers().await? {
+        providers.push(health(&state, provider).await?);
+    }
+    Ok(Json(providers))
+}
+
+async fn provider(
+    State(state): State<ApiState>,
+    Path(authority): Path<String>,
+) -> Result<Json<ProviderHealth>, ApiError> {
+    let authority = parse_pubkey(&authority)?;
+    let provider = state
+        .db
+        .provider(&authority)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(health(&state, provider).await?))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LatencyParams {
+    /// Number of most recent reveals to summarize.
+    pub window: Option<u32>,
+}
+
+async fn provider_latency(
+    State(state): State<ApiState>,
+    Path(authority): Path<String>,
+    Query(params): Query<LatencyParams>,
+) -> Result<Json<LatencyStats>, ApiError> {
+    let authority = parse_pubkey(&authority)?;
+    let window = params
+        .window
+        .unwrap_or(DEFAULT_LATENCY_WINDOW)
+        .clamp(1, MAX_LATENCY_WINDOW);
+    let mut latencies = state.db.reveal_latencies(&authority, window).await?;
+    Ok(Json(LatencyStats::from_latencies(&mut latencies)))
+}
+
+async fn requests(
+    state: &ApiState,
+    filter: RequestFilter,
+    params: &PageParams,
+) -> Result<Json<Vec<RequestRecord>>, ApiError> {
+    let requests = state
+        .db
+        .requests(&filter, params.limit()?, params.offset.unwrap_or(0))
+        .await?;
+    Ok(Json(requests))
+}
+
+async fn provider_requests(
+    State(state): State<ApiState>,
+    Path(authority): Path<String>,
+    Query(params): Query<PageParams>,
+) -> Result<Json<Vec<RequestRecord>>, ApiError> {
+    let filter = RequestFilter {
+        provider: Some(parse_pubkey(&authority)?),
+        state: params.state.clone(),
+        ..RequestFilter::default()
+    };
+    requests(&state, filter, &params).await
+}
+
+async fn provider_request(
+    State(state): State<ApiState>,
+    Path((authority, sequence_number)): Path<(String, u64)>,
+) -> Result<Json<RequestRecord>, ApiError> {
+    let authority = parse_pubkey(&authority)?;
+    let request = state
+        .db
+        .request(&authority, sequence_number)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(request))
+}
+
+async fn requester_requests(
+    State(state): State<ApiState>,
+    Path(program): Path<String>,
+    Query(params): Query<PageParams>,
+) -> Result<Json<Vec<RequestRecord>>, ApiError> {
+    let filter = RequestFilter {
+        requester_program: Some(parse_pubkey(&program)?),
+        state: params.state.clone(),
+        ..RequestFilter::default()
+    };
+    requests(&state, filter, &params).await
+}
+
+async fn requester_callback_failures(
+    State(state): State<ApiState>,
+    Path(program): Path<String>,
+    Query(params): Query<PageParams>,
+) -> Result<Json<Vec<CallbackFailureRecord>>, ApiError> {
+    let failures = state
+        .db
+        .callback_failures(
+            &parse_pubkey(&program)?,
+            params.limit()?,
+            params.offset.unwrap_or(0),
+        )
+        .await?;
+    Ok(Json(failures))
+}
@@ -0,0 +1,546 @@
+//! SQLite store of indexed requests, callback failures and provider
+//! snapshots. SQLite integers
+//! are signed, so u64 values are stored as their i64 bit pattern and read
+//! back with `as u64`.
+
+use {
+    anyhow::Result,
+    entropy::{
+        accounts::Provider,
+        events::{RandomnessRevealed, RequestCreated},
+    },
+    entropy_sdk::{provider_uri, CallbackFailure},
+    serde::Serialize,
+    solana_sdk::{pubkey::Pubkey, signature::Signature},
+    sqlx::{
+        sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+        FromRow, SqlitePool,
+    },
+    std::str::FromStr,
+};
+
+pub const STATE_PENDING: &str = "pending";
+pub const STATE_REVEALED: &str = "revealed";
+/// Closed without a reveal: the request expired and its rent was reclaimed.
+pub const STATE_CLOSED: &str = "closed";
+
+#[derive(Clone)]
+pub struct Database {
+    pool: SqlitePool,
+}
+
+/// An indexed request. Fields only the `RequestCreated` event carries are
+/// `None` when only the reveal was indexed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RequestRecord {
+    pub provider: String,
+    pub sequence_number: u64,
+    pub request_account: String,
+    pub requester_program: String,
+    pub payer: Option<String>,
+    pub request_slot: u64,
+    pub request_signature: Option<String>,
+    pub compute_unit_limit: Option<u32>,
+    pub use_blockhash: Option<bool>,
+    pub random_number_version: Option<u8>,
+    pub state: String,
+    pub reveal_slot: Option<u64>,
+    pub reveal_signature: Option<String>,
+    /// Hex-encoded.
+    pub random_number: Option<String>,
+    pub callback_invoked: Option<bool>,
+}
+
+#[derive(FromRow)]
+struct RequestRow {
+    provider: String,
+    sequence_number: i64,
+    request_account: String,
+    requester_program: String,
+    payer: Option<String>,
+    request_slot: i64,
+    request_signature: Option<String>,
+    compute_unit_limit: Option<i64>,
+    use_blockhash: Option<i64>,
+    random_number_version: Option<i64>,
+    state: String,
+    reveal_slot: Option<i64>,
+    reveal_signature: Option<String>,
+    random_number: Option<String>,
+    callback_invoked: Option<i64>,
+}
+
+impl From<RequestRow> for RequestRecord {
+    fn from(row: RequestRow) -> Self {
+        Self {
+            provider: row.provider,
+            sequence_number: row.sequence_number as u64,
+            request_account: row.request_account,
+            requester_program: row.requester_program,
+            payer: row.payer,
+            request_slot: row.request_slot as u64,
+            request_signature: row.request_signature,
+            compute_unit_limit: row.compute_unit_limit.map(|limit| limit as u32),
+            use_blockhash: row.use_blockhash.map(|flag| flag != 0),
+            random_number_version: row.random_number_version.map(|version| version as u8),
+            state: row.state,
+            reveal_slot: row.reveal_slot.map(|slot| slot as u64),
+            reveal_signature: row.reveal_signature,
+            random_number: row.random_number,
+            callback_invoked: row.callback_invoked.map(|flag| flag != 0),
+        }
+    }
+}
+
+/// The latest indexed snapshot of a provider account.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProviderRecord {
+    pub authority: String,
+    pub fee_lamports: u64,
+    pub sequence_number: u64,
+    pub end_sequence_number: u64,
+    pub outstanding_requests: u32,
+    pub reveal_count: u64,
+    pub reveal_latency_sum_slots: u64,
+    pub reveal_latency_max_slots: u64,
+    pub uri: String,
+    /// Slot the snapshot was taken at.
+    pub updated_slot: u64,
+}
+
+#[derive(FromRow)]
+struct ProviderRow {
+    authority: String,
+    fee_lamports: i64,
+    sequence_number: i64,
+    end_sequence_number: i64,
+    outstanding_requests: i64,
+    reveal_count: i64,
+    reveal_latency_sum_slots: i64,
+    reveal_latency_max_slots: i64,
+    uri: String,
+    updated_slot: i64,
+}
+
+impl From<ProviderRow> for ProviderRecord {
+    fn from(row: ProviderRow) -> Self {
+        Self {
+            authority: row.authority,
+            fee_lamports: row.fee_lamports as u64,
+            sequence_number: row.sequence_number as u64,
+            end_sequence_number: row.end_sequence_number as u64,
+            outstanding_requests: row.outstanding_requests as u32,
+            reveal_count: row.reveal_count as u64,
+            reveal_latency_sum_slots: row.reveal_latency_sum_slots as u64,
+            reveal_latency_max_slots: row.reveal_latency_max_slots as u64,
+            uri: row.uri,
+            updated_slot: row.updated_slot as u64,
+        }
+    }
+}
+
+/// A callback that failed inside a reveal; see [`CallbackFailure`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CallbackFailureRecord {
+    /// The failed reveal transaction.
+    pub signature: String,
+    pub provider: String,
+    pub sequence_number: u64,
+    pub request_account: String,
+    pub requester_program: String,
+    pub request_slot: u64,
+    pub reveal_slot: u64,
+    pub compute_unit_limit: u32,
+    pub compute_units_consumed: Option<u64>,
+    pub error: String,
+    pub error_code: Option<u32>,
+}
+
+#[derive(FromRow)]
+struct CallbackFailureRow {
+    signature: String,
+    provider: String,
+    sequence_number: i64,
+    request_account: String,
+    requester_program: String,
+    request_slot: i64,
+    reveal_slot: i64,
+    compute_unit_limit: i64,
+    compute_units_consumed: Option<i64>,
+    error: String,
+    error_code: Option<i64>,
+}
+
+impl From<CallbackFailureRow> for CallbackFailureRecord {
+    fn from(row: CallbackFailureRow) -> Self {
+        Self {
+            signature: row.signature,
+            provider: row.provider,
+            sequence_number: row.sequence_number as u64,
+            request_account: row.request_account,
+            requester_program: row.requester_program,
+            request_slot: row.request_slot as u64,
+            reveal_slot: row.reveal_slot as u64,
+            compute_unit_limit: row.compute_unit_limit as u32,
+            compute_units_consumed: row.compute_units_consumed.map(|units| units as u64),
+            error: row.error,
+            error_code: row.error_code.map(|code| code as u32),
+        }
+    }
+}
+
+/// Filters for [`Database::requests`]; `None` matches everything.
+#[derive(Clone, Debug, Default)]
+pub struct RequestFilter {
+    pub provider: Option<Pubkey>,
+    pub requester_program: Option<Pubkey>,
+    pub state: Option<String>,
+}
+
+/// A pending request, as needed to check whether it was closed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingRequest {
+    pub provider: Pubkey,
+    pub sequence_number: u64,
+    pub request_account: Pubkey,
+}
+
+const REQUEST_COLUMNS: &str = "provider, sequence_number, request_account, requester_program, \
+     payer, request_slot, request_signature, compute_unit_limit, use_blockhash, \
+     random_number_version, state, reveal_slot, reveal_signature, random_number, \
+     callback_invoked";
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+impl Database {
+    /// Opens (creating if needed) the database at `url`, e.g.
+    /// `sqlite:explorer.db`, and applies pending migrations.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await?;
+        Self::migrate(pool).await
+    }
+
+    /// A fresh in-memory database. SQLite gives every connection its own
+    /// in-memory database, so the pool keeps a single one.
+    pub async fn in_memory() -> Result<Self> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await?;
+        Self::migrate(pool).await
+    }
+
+    async fn migrate(pool: SqlitePool) -> Result<Self> {
+        sqlx::migrate!("./migrations").run(&pool).await?;
+        Ok(Self { pool })
+    }
+
+    /// Records a new request. Indexing the same event again, or after its
+    /// reveal, keeps the reveal.
+    pub async fn record_request_created(
+        &self,
+        event: &RequestCreated,
+        signature: &Signature,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO request (provider, sequence_number, request_account, requester_program, \
+             payer, request_slot, request_signature, compute_unit_limit, use_blockhash, \
+             random_number_version, state) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT (provider, sequence_number) DO UPDATE SET \
+             payer = excluded.payer, request_signature = excluded.request_signature, \
+             compute_unit_limit = excluded.compute_unit_limit, \
+             use_blockhash = excluded.use_blockhash, \
+             random_number_version = excluded.random_number_version",
+        )
+        .bind(Pubkey::new_from_array(event.provider).to_string())
+        .bind(event.sequence_number as i64)
+        .bind(Pubkey::new_from_array(event.request_account).to_string())
+        .bind(Pubkey::new_from_array(event.requester_program_id).to_string())
+        .bind(Pubkey::new_from_array(event.payer).to_string())
+        .bind(event.request_slot as i64)
+        .bind(signature.to_string())
+        .bind(i64::from(event.compute_unit_limit))
+        .bind(i64::from(event.use_blockhash))
+        .bind(i64::from(event.random_number_version))
+        .bind(STATE_PENDING)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Records a reveal, creating the request from the reveal alone if its
+    /// `RequestCreated` was not indexed.
+    pub async fn record_randomness_revealed(
+        &self,
+        event: &RandomnessRevealed,
+        signature: &Signature,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO request (provider, sequence_number, request_account, requester_program, \
+             request_slot, state, reveal_slot, reveal_signature, random_number, callback_invoked) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT (provider, sequence_number) DO UPDATE SET \
+             state = excluded.state, reveal_slot = excluded.reveal_slot, \
+             reveal_signature = excluded.reveal_signature, \
+             random_number = excluded.random_number, \
+             callback_invoked = excluded.callback_invoked",
+        )
+        .bind(Pubkey::new_from_array(event.provider).to_string())
+        .bind(event.sequence_number as i64)
+        .bind(Pubkey::new_from_array(event.request_account).to_string())
+        .bind(Pubkey::new_from_array(event.requester_program_id).to_string())
+        .bind(event.request_slot as i64)
+        .bind(STATE_REVEALED)
+        .bind(event.reveal_slot as i64)
+        .bind(signature.to_string())
+        .bind(hex(&event.random_number))
+        .bind(i64::from(event.callback_invoked))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Records the callback failure of the failed reveal `signature`.
+    /// Indexing the same transaction again changes nothing.
+    pub async fn record_callback_failure(
+        &self,
+        failure: &CallbackFailure,
+        signature: &Signature,
+    ) -> Result<()> {
+        let request = &failure.request;
+        sqlx::query(
+            "INSERT INTO callback_failure (signature, provider, sequence_number, \
+             request_account, requester_program, request_slot, reveal_slot, compute_unit_limit, \
+             compute_units_consumed, error, error_code) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT (signature) DO NOTHING",
+        )
+        .bind(signature.to_string())
+        .bind(Pubkey::new_from_array(request.provider).to_string())
+        .bind(request.sequence_number as i64)
+        .bind(Pubkey::new_from_array(request.request_account).to_string())
+        .bind(Pubkey::new_from_array(request.requester_program_id).to_string())
+        .bind(request.request_slot as i64)
+        .bind(request.reveal_slot as i64)
+        .bind(i64::from(request.compute_unit_limit))
+        .bind(failure.compute_units_consumed.map(|units| units as i64))
+        .bind(&failure.error)
+        .bind(failure.error_code.map(i64::from))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Callback failures of the requester program's requests, newest first.
+    pub async fn callback_failures(
+        &self,
+        requester_program: &Pubkey,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<CallbackFailureRecord>> {
+        let rows: Vec<CallbackFailureRow> = sqlx::query_as(
+            "SELECT * FROM callback_failure WHERE requester_program = ? \
+             ORDER BY reveal_slot DESC, signature LIMIT ? OFFSET ?",
+        )
+        .bind(requester_program.to_string())
+        .bind(i64::from(limit))
+        .bind(i64::from(offset))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(CallbackFailureRecord::from).collect())
+    }
+
+    /// Pending requests made at or before `slot`, oldest first.
+    pub async fn pending_requests_before(&self, slot: u64) -> Result<Vec<PendingRequest>> {
+        let rows: Vec<(String, i64, String)> = sqlx::query_as(
+            "SELECT provider, sequence_number, request_account FROM request \
+             WHERE state = ? AND request_slot <= ? ORDER BY request_slot",
+        )
+        .bind(STATE_PENDING)
+        .bind(slot as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|(provider, sequence_number, request_account)| {
+                Ok(PendingRequest {
+                    provider: Pubkey::from_str(&provider)?,
+                    sequence_number: sequence_number as u64,
+                    request_account: Pubkey::from_str(&request_account)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Marks a request closed unless a reveal was indexed for it meanwhile.
+    pub async fn mark_closed(&self, provider: &Pubkey, sequence_number: u64) -> Result<()> {
+        sqlx::query(
+            "UPDATE request SET state = ? \
+             WHERE provider = ? AND sequence_number = ? AND state = ?",
+        )
+        .bind(STATE_CLOSED)
+        .bind(provider.to_string())
+        .bind(sequence_number as i64)
+        .bind(STATE_PENDING)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn request(
+        &self,
+        provider: &Pubkey,
+        sequence_number: u64,
+    ) -> Result<Option<RequestRecord>> {
+        let row: Option<RequestRow> = sqlx::query_as(&format!(
+            "SELECT {REQUEST_COLUMNS} FROM request WHERE provider = ? AND sequence_number = ?"
+        ))
+        .bind(provider.to_string())
+        .bind(sequence_number as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(RequestRecord::from))
+    }
+
+    /// Requests matching `filter`, newest first.
+    pub async fn requests(
+        &self,
+        filter: &RequestFilter,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<RequestRecord>> {
+        let rows: Vec<RequestRow> = sqlx::query_as(&format!(
+            "SELECT {REQUEST_COLUMNS} FROM request \
+             WHERE (?1 IS NULL OR provider = ?1) \
+             AND (?2 IS NULL OR requester_program = ?2) \
+             AND (?3 IS NULL OR state = ?3) \
+             ORDER BY request_slot DESC, sequence_number DESC LIMIT ?4 OFFSET ?5"
+        ))
+        .bind(filter.provider.map(|provider| provider.to_string()))
+        .bind(
+            filter
+                .requester_program
+                .map(|requester_program| requester_program.to_string()),
+        )
+        .bind(filter.state.as_deref())
+        .bind(i64::from(limit))
+        .bind(i64::from(offset))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(RequestRecord::from).collect())
+    }
+
+    /// Latencies in slots of the provider's `limit` most recent reveals.
+    pub async fn reveal_latencies(&self, provider: &Pubkey, limit: u32) -> Result<Vec<u64>> {
+        let rows: Vec<(i64,)> = sqlx::query_as(
+            "SELECT reveal_slot - request_slot FROM request \
+             WHERE provider = ? AND state = ? ORDER BY reveal_slot DESC LIMIT ?",
+        )
+        .bind(provider.to_string())
+        .bind(STATE_REVEALED)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(latency,)| latency.max(0) as u64)
+            .collect())
+    }
+
+    /// Number of the provider's pending requests and the slot of the oldest.
+    pub async fn pending_summary(&self, provider: &Pubkey) -> Result<(u64, Option<u64>)> {
+        let (count, oldest): (i64, Option<i64>) = sqlx::query_as(
+            "SELECT COUNT(*), MIN(request_slot) FROM request WHERE provider = ? AND state = ?",
+        )
+        .bind(provider.to_string())
+        .bind(STATE_PENDING)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok((count as u64, oldest.map(|slot| slot as u64)))
+    }
+
+    /// Slot of the provider's most recent indexed reveal.
+    pub async fn last_reveal_slot(&self, provider: &Pubkey) -> Result<Option<u64>> {
+        let (slot,): (Option<i64>,) =
+            sqlx::query_as("SELECT MAX(reveal_slot) FROM request WHERE provider = ?")
+                .bind(provider.to_string())
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(slot.map(|slot| slot as u64))
+    }
+
+    pub async fn upsert_provider(
+        &self,
+        authority: &Pubkey,
+        provider: &Provider,
+        slot: u64,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO provider (authority, fee_lamports, sequence_number, end_sequence_number, \
+             outstanding_requests, reveal_count, reveal_latency_sum_slots, \
+             reveal_latency_max_slots, uri, updated_slot) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT (authority) DO UPDATE SET \
+             fee_lamports = excluded.fee_lamports, sequence_number = excluded.sequence_number, \
+             end_sequence_number = excluded.end_sequence_number, \
+             outstanding_requests = excluded.outstanding_requests, \
+             reveal_count = excluded.reveal_count, \
+             reveal_latency_sum_slots = excluded.reveal_latency_sum_slots, \
+             reveal_latency_max_slots = excluded.reveal_latency_max_slots, \
+             uri = excluded.uri, updated_slot = excluded.updated_slot",
+        )
+        .bind(authority.to_string())
+        .bind(provider.fee_lamports as i64)
+        .bind(provider.sequence_number as i64)
+        .bind(provider.end_sequence_number as i64)
+        .bind(i64::from(provider.outstanding_requests))
+        .bind(provider.reveal_count as i64)
+        .bind(provider.reveal_latency_sum_slots as i64)
+        .bind(provider.reveal_latency_max_slots as i64)
+        .bind(provider_uri(provider).unwrap_or_default())
+        .bind(slot as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn providers(&self) -> Result<Vec<ProviderRecord>> {
+        let rows: Vec<ProviderRow> = sqlx::query_as("SELECT * FROM provider ORDER BY authority")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(ProviderRecord::from).collect())
+    }
+
+    pub async fn provider(&self, authority: &Pubkey) -> Result<Option<ProviderRecord>> {
+        let row: Option<ProviderRow> = sqlx::query_as("SELECT * FROM provider WHERE authority = ?")
+            .bind(authority.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(ProviderRecord::from))
+    }
+
+    /// The newest indexed transaction and its slot.
+    pub async fn cursor(&self) -> Result<Option<(Signature, u64)>> {
+        let row: Option<(String, i64)> =
+            sqlx::query_as("SELECT signature, slot FROM cursor WHERE id = 0")
+                .fetch_optional(&self.pool)
+                .await?;
+        row.map(|(signature, slot)| Ok((Signature::from_str(&signature)?, slot as u64)))
+            .transpose()
+    }
+
+    pub async fn set_cursor(&self, signature: &Signature, slot: u64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO cursor (id, signature, slot) VALUES (0, ?, ?) \
+             ON CONFLICT (id) DO UPDATE SET signature = excluded.signature, slot = excluded.slot",
+        )
+        .bind(signature.to_string())
+        .bind(slot as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+//! Polls the entropy program's transaction history into the database and
+//! snapshots the providers in the provider directory.
+
+use {
+    crate::db::{Database, PendingRequest},
+    anyhow::{Context, Result},
+    entropy_sdk::{parse_callback_failure, parse_logs, EntropyClient, EntropyEvent},
+    solana_client::{
+        rpc_client::GetConfirmedSignaturesForAddress2Config, rpc_config::RpcTransactionConfig,
+        rpc_request::MAX_MULTIPLE_ACCOUNTS,
+        rpc_response::RpcConfirmedTransactionStatusWithSignature,
+    },
+    solana_sdk::{commitment_config::CommitmentConfig, signature::Signature},
+    solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding},
+    std::{str::FromStr, time::Duration},
+    tracing::{info, warn},
+};
+
+/// Largest page `getSignaturesForAddress` returns.
+const SIGNATURE_PAGE_LIMIT: usize = 1000;
+
+pub struct Indexer {
+    client: EntropyClient,
+    db: Database,
+    /// Pending requests at least this many slots old are checked for having
+    /// been closed without a reveal.
+    closed_check_slots: u64,
+}
+
+impl Indexer {
+    pub fn new(client: EntropyClient, db: Database, closed_check_slots: u64) -> Self {
+        Self {
+            client,
+            db,
+            closed_check_slots,
+        }
+    }
+
+    /// Indexes forever, every `interval`. A failed round is logged and
+    /// retried on the next one; the cursor only moves past indexed
+    /// transactions.
+    pub async fn run(self, interval: Duration) {
+        loop {
+            if let Err(err) = self.round().await {
+                warn!(error = %err, "Indexing round failed");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    pub async fn round(&self) -> Result<()> {
+        let slot = self
+            .client
+            .rpc()
+            .get_slot_with_commitment(CommitmentConfig::confirmed())
+            .await?;
+
+        // Requests have no close event. Which old pending requests are gone
+        // is read before indexing, so a reveal that closed one is indexed by
+        // the time it would be marked closed.
+        let closed = self.closed_pending_requests(slot).await?;
+        self.index_new_transactions().await?;
+        for request in closed {
+            self.db
+                .mark_closed(&request.provider, request.sequence_number)
+                .await?;
+        }
+
+        for (authority, provider) in self.client.list_providers().await? {
+            self.db.upsert_provider(&authority, &provider, slot).await?;
+        }
+        Ok(())
+    }
+
+    async fn closed_pending_requests(&self, slot: u64) -> Result<Vec<PendingRequest>> {
+        let pending = self
+            .db
+            .pending_requests_before(slot.saturating_sub(self.closed_check_slots))
+            .await?;
+        let mut closed = Vec::new();
+        for chunk in pending.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let addresses: Vec<_> = chunk
+                .iter()
+                .map(|request| request.request_account)
+                .collect();
+            let accounts = self
+                .client
+                .rpc()
+                .get_multiple_accounts_with_commitment(&addresses, CommitmentConfig::confirmed())
+                .await?
+                .value;
+            closed.extend(
+                chunk
+                    .iter()
+                    .zip(accounts)
+                    .filter(|(_, account)| account.is_none())
+                    .map(|(request, _)| request.clone()),
+            );
+        }
+        Ok(closed)
+    }
+
+    /// Indexes the program's transactions since the cursor, oldest first.
+    async fn index_new_transactions(&self) -> Result<()> {
+        let until = self.db.cursor().await?.map(|(signature, _)| signature);
+        let mut signatures = self.signatures_since(until).await?;
+        signatures.reverse();
+        if !signatures.is_empty() {
+            info!(count = signatures.len(), "Indexing transactions");
+        }
+
+        for status in signatures {
+            let signature = Signature::from_str(&status.signature)
+                .with_context(|| format!("Invalid signature {}", status.signature))?;
+            // Failed transactions change nothing; of them only failed
+            // callbacks are recorded.
+            if status.err.is_none() {
+                self.index_transaction(&signature).await?;
+            } else {
+                self.index_failed_transaction(&signature).await?;
+            }
+            self.db.set_cursor(&signature, status.slot).await?;
+        }
+        Ok(())
+    }
+
+    /// Signatures of the program's confirmed transactions newer than
+    /// `until` (all of them without one), newest first.
+    async fn signatures_since(
+        &self,
+        until: Option<Signature>,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let mut signatures: Vec<RpcConfirmedTransactionStatusWithSignature> = Vec::new();
+        loop {
+            let before = signatures
+                .last()
+                .map(|status| Signature::from_str(&status.signature))
+                .transpose()?;
+            let page = self
+                .client
+                .rpc()
+                .get_signatures_for_address_with_config(
+                    &self.client.program_id(),
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some(SIGNATURE_PAGE_LIMIT),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                    },
+                )
+                .await?;
+            let done = page.len() < SIGNATURE_PAGE_LIMIT;
+            signatures.extend(page);
+            if done {
+                return Ok(signatures);
+            }
+        }
+    }
+
+    /// The transaction's log messages; `None` if the RPC node has none.
+    async fn transaction_logs(&self, signature: &Signature) -> Result<Option<Vec<String>>> {
+        let transaction = self
+            .client
+            .rpc()
+            .get_transaction_with_config(
+                signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Json),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+            .with_context(|| format!("Failed to fetch transaction {signature}"))?;
+        Ok(transaction
+            .transaction
+            .meta
+            .and_then(|meta| match meta.log_messages {
+                OptionSerializer::Some(logs) => Some(logs),
+                _ => None,
+            }))
+    }
+
+    async fn index_transaction(&self, signature: &Signature) -> Result<()> {
+        let Some(logs) = self.transaction_logs(signature).await? else {
+            return Ok(());
+        };
+
+        for event in parse_logs(&self.client.program_id(), &logs) {
+            match event {
+                EntropyEvent::RequestCreated(event) => {
+                    self.db.record_request_created(&event, signature).await?
+                }
+                EntropyEvent::RandomnessRevealed(event) => {
+                    self.db
+                        .record_randomness_revealed(&event, signature)
+                        .await?
+                }
+                // Aggregate requests and config changes are not indexed.
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the callback failure of a failed reveal, if that is why it
+    /// failed.
+    async fn index_failed_transaction(&self, signature: &Signature) -> Result<()> {
+        let Some(logs) = self.transaction_logs(signature).await? else {
+            return Ok(());
+        };
+        if let Some(failure) = parse_callback_failure(&self.client.program_id(), &logs) {
+            self.db.record_callback_failure(&failure, signature).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+//! Entropy explorer: indexes requests and reveals into SQLite and serves
+//! them, with per-provider latency and health, over a JSON API.
+
+pub mod api;
+pub mod db;
+pub mod indexer;
+pub mod stats;
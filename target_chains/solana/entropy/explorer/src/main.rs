@@ -0,0 +1,92 @@
+use {
+    anyhow::{Context, Result},
+    clap::Parser,
+    entropy_explorer::{
+        api::{self, ApiState},
+        db::Database,
+        indexer::Indexer,
+    },
+    entropy_sdk::EntropyClient,
+    solana_client::nonblocking::rpc_client::RpcClient,
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    std::{net::SocketAddr, str::FromStr, time::Duration},
+    tracing::info,
+    tracing_subscriber::EnvFilter,
+};
+
+#[derive(Parser, Debug)]
+#[command(about = "Indexes Entropy requests and serves them over a JSON API")]
+struct Args {
+    /// Solana RPC URL.
+    #[arg(long, env = "SOLANA_RPC_URL", default_value = "http://localhost:8899")]
+    rpc_url: String,
+
+    /// Entropy program id.
+    #[arg(long, env = "ENTROPY_PROGRAM_ID", value_name = "PROGRAM_ID")]
+    entropy_program_id: String,
+
+    /// SQLite database, created on first run.
+    #[arg(
+        long,
+        env = "DATABASE_URL",
+        default_value = "sqlite:entropy-explorer.db"
+    )]
+    database_url: String,
+
+    /// Address the API listens on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// Seconds between indexing rounds.
+    #[arg(long, default_value_t = 5)]
+    poll_interval_secs: u64,
+
+    /// Pending requests this many slots old are checked for having been
+    /// closed without a reveal.
+    #[arg(long, value_name = "SLOTS", default_value_t = 150)]
+    closed_check_slots: u64,
+
+    /// Providers with a request pending this many slots are reported as
+    /// stalled.
+    #[arg(long, value_name = "SLOTS", default_value_t = 150)]
+    stall_slots: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .compact()
+        .init();
+
+    let args = Args::parse();
+    let program_id = Pubkey::from_str(&args.entropy_program_id)
+        .with_context(|| format!("Invalid entropy program id {}", args.entropy_program_id))?;
+    let db = Database::connect(&args.database_url).await?;
+
+    let client = EntropyClient::new(
+        RpcClient::new_with_commitment(args.rpc_url, CommitmentConfig::confirmed()),
+        program_id,
+    );
+    let indexer = Indexer::new(client, db.clone(), args.closed_check_slots);
+    tokio::spawn(indexer.run(Duration::from_secs(args.poll_interval_secs)));
+
+    let listener = tokio::net::TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("Failed to listen on {}", args.listen))?;
+    info!(address = %args.listen, "Serving the explorer API");
+    axum::serve(
+        listener,
+        api::routes(ApiState {
+            db,
+            stall_slots: args.stall_slots,
+        }),
+    )
+    .with_graceful_shutdown(async {
+        let _ = tokio::signal::ctrl_c().await;
+    })
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,42 @@
+//! Reveal latency summaries, in slots from the request to its reveal.
+
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct LatencyStats {
+    /// Reveals summarized.
+    pub count: u64,
+    /// Rounded down; zero without reveals.
+    pub average_slots: u64,
+    pub p50_slots: u64,
+    pub p90_slots: u64,
+    pub p99_slots: u64,
+    pub max_slots: u64,
+}
+
+impl LatencyStats {
+    /// Summarizes `latencies`, which are sorted in place. Percentiles are
+    /// nearest-rank.
+    pub fn from_latencies(latencies: &mut [u64]) -> Self {
+        if latencies.is_empty() {
+            return Self::default();
+        }
+        latencies.sort_unstable();
+        let count = latencies.len() as u64;
+        let sum: u128 = latencies.iter().map(|&latency| u128::from(latency)).sum();
+        Self {
+            count,
+            average_slots: (sum / u128::from(count)) as u64,
+            p50_slots: percentile(latencies, 50),
+            p90_slots: percentile(latencies, 90),
+            p99_slots: percentile(latencies, 99),
+            max_slots: latencies[latencies.len() - 1],
+        }
+    }
+}
+
+/// Nearest-rank percentile of non-empty, sorted `latencies`.
+fn percentile(latencies: &[u64], percent: usize) -> u64 {
+    let rank = (latencies.len() * percent).div_ceil(100).max(1);
+    latencies[rank - 1]
+}
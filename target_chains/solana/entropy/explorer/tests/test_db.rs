@@ -0,0 +1,355 @@
+use {
+    bytemuck::Zeroable,
+    entropy::{
+        accounts::Provider,
+        events::{CallbackInvoked, RandomnessRevealed, RequestCreated},
+    },
+    entropy_explorer::{
+        api::{HealthStatus, ProviderHealth},
+        db::{Database, RequestFilter, STATE_CLOSED, STATE_PENDING, STATE_REVEALED},
+        stats::LatencyStats,
+    },
+    entropy_sdk::CallbackFailure,
+    solana_sdk::{pubkey::Pubkey, signature::Signature},
+};
+
+fn request_created(provider: Pubkey, requester: Pubkey, sequence_number: u64) -> RequestCreated {
+    RequestCreated {
+        provider: provider.to_bytes(),
+        requester_program_id: requester.to_bytes(),
+        request_account: Pubkey::new_unique().to_bytes(),
+        payer: Pubkey::new_unique().to_bytes(),
+        sequence_number,
+        request_slot: 100 + sequence_number,
+        compute_unit_limit: 200_000,
+        use_blockhash: 1,
+        callback_status: 0,
+        random_number_version: 1,
+        _padding0: [0; 1],
+    }
+}
+
+fn randomness_revealed(created: &RequestCreated, reveal_slot: u64) -> RandomnessRevealed {
+    RandomnessRevealed {
+        provider: created.provider,
+        requester_program_id: created.requester_program_id,
+        request_account: created.request_account,
+        sequence_number: created.sequence_number,
+        random_number: [0xab; 32],
+        callback_invoked: 1,
+        _padding0: [0; 7],
+        request_slot: created.request_slot,
+        reveal_slot,
+    }
+}
+
+#[tokio::test]
+async fn test_reveal_updates_request() {
+    let db = Database::in_memory().await.unwrap();
+    let provider = Pubkey::new_unique();
+    let created = request_created(provider, Pubkey::new_unique(), 1);
+    db.record_request_created(&created, &Signature::new_unique())
+        .await
+        .unwrap();
+
+    let request = db.request(&provider, 1).await.unwrap().unwrap();
+    assert_eq!(request.state, STATE_PENDING);
+    assert_eq!(request.request_slot, 101);
+    assert_eq!(request.compute_unit_limit, Some(200_000));
+    assert_eq!(request.reveal_slot, None);
+
+    let reveal_signature = Signature::new_unique();
+    db.record_randomness_revealed(&randomness_revealed(&created, 104), &reveal_signature)
+        .await
+        .unwrap();
+    // Indexing the request again keeps the reveal.
+    db.record_request_created(&created, &Signature::new_unique())
+        .await
+        .unwrap();
+
+    let request = db.request(&provider, 1).await.unwrap().unwrap();
+    assert_eq!(request.state, STATE_REVEALED);
+    assert_eq!(request.reveal_slot, Some(104));
+    assert_eq!(request.reveal_signature, Some(reveal_signature.to_string()));
+    assert_eq!(request.random_number, Some("ab".repeat(32)));
+    assert_eq!(request.callback_invoked, Some(true));
+    assert_eq!(request.compute_unit_limit, Some(200_000));
+}
+
+#[tokio::test]
+async fn test_reveal_without_request_is_recorded() {
+    let db = Database::in_memory().await.unwrap();
+    let provider = Pubkey::new_unique();
+    let created = request_created(provider, Pubkey::new_unique(), 3);
+    db.record_randomness_revealed(
+        &randomness_revealed(&created, 110),
+        &Signature::new_unique(),
+    )
+    .await
+    .unwrap();
+
+    let request = db.request(&provider, 3).await.unwrap().unwrap();
+    assert_eq!(request.state, STATE_REVEALED);
+    assert_eq!(request.payer, None);
+    assert_eq!(request.request_slot, 103);
+}
+
+#[tokio::test]
+async fn test_requests_filters() {
+    let db = Database::in_memory().await.unwrap();
+    let provider = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let other_requester = Pubkey::new_unique();
+    for sequence_number in 1..=3 {
+        db.record_request_created(
+            &request_created(provider, requester, sequence_number),
+            &Signature::new_unique(),
+        )
+        .await
+        .unwrap();
+    }
+    let other = request_created(provider, other_requester, 4);
+    db.record_request_created(&other, &Signature::new_unique())
+        .await
+        .unwrap();
+    db.record_randomness_revealed(&randomness_revealed(&other, 120), &Signature::new_unique())
+        .await
+        .unwrap();
+
+    let by_provider = RequestFilter {
+        provider: Some(provider),
+        ..RequestFilter::default()
+    };
+    let sequence_numbers: Vec<_> = db
+        .requests(&by_provider, 10, 0)
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.sequence_number)
+        .collect();
+    assert_eq!(sequence_numbers, vec![4, 3, 2, 1]);
+
+    let page = db.requests(&by_provider, 2, 1).await.unwrap();
+    assert_eq!(page.len(), 2);
+    assert_eq!(page[0].sequence_number, 3);
+
+    let by_requester = RequestFilter {
+        requester_program: Some(requester),
+        ..RequestFilter::default()
+    };
+    assert_eq!(db.requests(&by_requester, 10, 0).await.unwrap().len(), 3);
+
+    let revealed = RequestFilter {
+        provider: Some(provider),
+        state: Some(STATE_REVEALED.to_string()),
+        ..RequestFilter::default()
+    };
+    let requests = db.requests(&revealed, 10, 0).await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].requester_program, other_requester.to_string());
+
+    let other_provider = RequestFilter {
+        provider: Some(Pubkey::new_unique()),
+        ..RequestFilter::default()
+    };
+    assert!(db
+        .requests(&other_provider, 10, 0)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_mark_closed_keeps_reveals() {
+    let db = Database::in_memory().await.unwrap();
+    let provider = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let pending = request_created(provider, requester, 1);
+    let revealed = request_created(provider, requester, 2);
+    for created in [&pending, &revealed] {
+        db.record_request_created(created, &Signature::new_unique())
+            .await
+            .unwrap();
+    }
+    db.record_randomness_revealed(
+        &randomness_revealed(&revealed, 105),
+        &Signature::new_unique(),
+    )
+    .await
+    .unwrap();
+
+    let old = db.pending_requests_before(101).await.unwrap();
+    assert_eq!(old.len(), 1);
+    assert_eq!(old[0].sequence_number, 1);
+    assert_eq!(old[0].request_account.to_bytes(), pending.request_account);
+    assert!(db.pending_requests_before(100).await.unwrap().is_empty());
+
+    db.mark_closed(&provider, 1).await.unwrap();
+    db.mark_closed(&provider, 2).await.unwrap();
+    let request = db.request(&provider, 1).await.unwrap().unwrap();
+    assert_eq!(request.state, STATE_CLOSED);
+    let request = db.request(&provider, 2).await.unwrap().unwrap();
+    assert_eq!(request.state, STATE_REVEALED);
+    assert_eq!(db.pending_summary(&provider).await.unwrap(), (0, None));
+}
+
+#[tokio::test]
+async fn test_latency_and_pending_summary() {
+    let db = Database::in_memory().await.unwrap();
+    let provider = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    for (sequence_number, latency) in [(1, 2), (2, 5), (3, 3)] {
+        let created = request_created(provider, requester, sequence_number);
+        db.record_request_created(&created, &Signature::new_unique())
+            .await
+            .unwrap();
+        db.record_randomness_revealed(
+            &randomness_revealed(&created, created.request_slot + latency),
+            &Signature::new_unique(),
+        )
+        .await
+        .unwrap();
+    }
+    for sequence_number in [4, 5] {
+        db.record_request_created(
+            &request_created(provider, requester, sequence_number),
+            &Signature::new_unique(),
+        )
+        .await
+        .unwrap();
+    }
+
+    let mut latencies = db.reveal_latencies(&provider, 10).await.unwrap();
+    assert_eq!(
+        LatencyStats::from_latencies(&mut latencies),
+        LatencyStats {
+            count: 3,
+            average_slots: 3,
+            p50_slots: 3,
+            p90_slots: 5,
+            p99_slots: 5,
+            max_slots: 5,
+        }
+    );
+    // The window keeps the most recent reveals: the second request's, at
+    // slot 107.
+    assert_eq!(db.reveal_latencies(&provider, 1).await.unwrap(), vec![5]);
+
+    assert_eq!(db.pending_summary(&provider).await.unwrap(), (2, Some(104)));
+    assert_eq!(db.last_reveal_slot(&provider).await.unwrap(), Some(107));
+}
+
+#[test]
+fn test_latency_stats_percentiles() {
+    assert_eq!(
+        LatencyStats::from_latencies(&mut []),
+        LatencyStats::default()
+    );
+
+    let mut latencies: Vec<u64> = (1..=100).rev().collect();
+    let stats = LatencyStats::from_latencies(&mut latencies);
+    assert_eq!(stats.count, 100);
+    assert_eq!(stats.average_slots, 50);
+    assert_eq!(stats.p50_slots, 50);
+    assert_eq!(stats.p90_slots, 90);
+    assert_eq!(stats.p99_slots, 99);
+    assert_eq!(stats.max_slots, 100);
+}
+
+#[tokio::test]
+async fn test_provider_health() {
+    let db = Database::in_memory().await.unwrap();
+    assert!(db.providers().await.unwrap().is_empty());
+    let authority = Pubkey::new_unique();
+    let provider = Provider {
+        sequence_number: 10,
+        end_sequence_number: 20,
+        ..Provider::zeroed()
+    };
+    db.upsert_provider(&authority, &provider, 1_000)
+        .await
+        .unwrap();
+    let record = db.provider(&authority).await.unwrap().unwrap();
+    assert_eq!(record.authority, authority.to_string());
+    assert_eq!(record.updated_slot, 1_000);
+    assert_eq!(db.providers().await.unwrap(), vec![record.clone()]);
+
+    let health = ProviderHealth::new(record.clone(), 1, Some(900), None, 150);
+    assert_eq!(health.remaining_randomness, 10);
+    assert_eq!(health.status, HealthStatus::Ok);
+    let health = ProviderHealth::new(record.clone(), 1, Some(849), None, 150);
+    assert_eq!(health.status, HealthStatus::Stalled);
+
+    let exhausted = Provider {
+        sequence_number: 20,
+        ..provider
+    };
+    db.upsert_provider(&authority, &exhausted, 1_001)
+        .await
+        .unwrap();
+    let record = db.provider(&authority).await.unwrap().unwrap();
+    let health = ProviderHealth::new(record, 0, None, None, 150);
+    assert_eq!(health.status, HealthStatus::OutOfRandomness);
+}
+
+fn callback_failure(created: &RequestCreated, reveal_slot: u64) -> CallbackFailure {
+    CallbackFailure {
+        request: CallbackInvoked {
+            provider: created.provider,
+            requester_program_id: created.requester_program_id,
+            request_account: created.request_account,
+            sequence_number: created.sequence_number,
+            request_slot: created.request_slot,
+            reveal_slot,
+            compute_unit_limit: created.compute_unit_limit,
+            _padding0: [0; 4],
+        },
+        error: "custom program error: 0x1770".to_string(),
+        error_code: Some(6000),
+        compute_units_consumed: Some(4200),
+    }
+}
+
+#[tokio::test]
+async fn test_callback_failures() {
+    let db = Database::in_memory().await.unwrap();
+    let provider = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let created = request_created(provider, requester, 1);
+    let first = Signature::new_unique();
+    let retry = Signature::new_unique();
+    db.record_callback_failure(&callback_failure(&created, 104), &first)
+        .await
+        .unwrap();
+    let mut retried = callback_failure(&created, 106);
+    retried.error = "SBF program panicked".to_string();
+    retried.error_code = None;
+    retried.compute_units_consumed = None;
+    db.record_callback_failure(&retried, &retry).await.unwrap();
+    // Indexing a transaction again adds nothing.
+    db.record_callback_failure(&callback_failure(&created, 104), &first)
+        .await
+        .unwrap();
+    let other = request_created(provider, Pubkey::new_unique(), 2);
+    db.record_callback_failure(&callback_failure(&other, 105), &Signature::new_unique())
+        .await
+        .unwrap();
+
+    let failures = db.callback_failures(&requester, 10, 0).await.unwrap();
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].signature, retry.to_string());
+    assert_eq!(failures[0].error, "SBF program panicked");
+    assert_eq!(failures[0].error_code, None);
+    assert_eq!(failures[0].compute_units_consumed, None);
+    assert_eq!(failures[1].signature, first.to_string());
+    assert_eq!(failures[1].provider, provider.to_string());
+    assert_eq!(failures[1].sequence_number, 1);
+    assert_eq!(failures[1].request_slot, 101);
+    assert_eq!(failures[1].reveal_slot, 104);
+    assert_eq!(failures[1].compute_unit_limit, 200_000);
+    assert_eq!(failures[1].compute_units_consumed, Some(4200));
+    assert_eq!(failures[1].error_code, Some(6000));
+
+    let page = db.callback_failures(&requester, 1, 1).await.unwrap();
+    assert_eq!(page, failures[1..]);
+}
@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[repr(u32)]
+pub enum CoinFlipError {
+    #[error("invalid account")]
+    InvalidAccount = 0,
+    #[error("invalid PDA")]
+    InvalidPda = 1,
+    #[error("guess must be heads or tails")]
+    InvalidGuess = 2,
+    #[error("wager must be non-zero")]
+    InvalidWager = 3,
+    #[error("a flip needs a callback compute unit limit")]
+    CallbackRequired = 4,
+    #[error("house cannot cover the wager")]
+    HouseCannotCover = 5,
+    #[error("flip not expired")]
+    FlipNotExpired = 6,
+    #[error("provider is not the house's provider")]
+    WrongProvider = 7,
+}
+
+impl From<CoinFlipError> for solana_program::program_error::ProgramError {
+    fn from(value: CoinFlipError) -> Self {
+        solana_program::program_error::ProgramError::Custom(value as u32)
+    }
+}
@@ -0,0 +1,135 @@
+#[allow(deprecated)]
+use {
+    crate::state::{flip_pda, house_pda},
+    bytemuck::{Pod, Zeroable},
+    entropy::pda::{
+        config_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda, requester_signer_pda,
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+pub const INITIALIZE_HOUSE_ACTION: u8 = 0;
+pub const FLIP_ACTION: u8 = 1;
+pub const REFUND_ACTION: u8 = 2;
+pub const CALLBACK_ACTION: u8 = 0xCB;
+
+/// Data of `INITIALIZE_HOUSE_ACTION`, after the action byte.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct InitializeHouseArgs {
+    pub provider_authority: [u8; 32],
+}
+
+/// Data of `FLIP_ACTION`, after the action byte.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct FlipArgs {
+    pub user_randomness: [u8; 32],
+    pub wager_lamports: u64,
+    /// Compute units reserved for the settling callback; must be non-zero.
+    pub compute_unit_limit: u32,
+    /// [`HEADS`](crate::state::HEADS) or [`TAILS`](crate::state::TAILS).
+    pub guess: u8,
+    /// Bump of the requester signer; filled in by [`flip`].
+    pub requester_signer_bump: u8,
+    pub _padding: [u8; 2],
+}
+
+/// Creates the house for `entropy_program_id`, paid by `payer`, taking flips
+/// only through `provider_authority`.
+pub fn initialize_house(
+    program_id: Pubkey,
+    entropy_program_id: Pubkey,
+    payer: Pubkey,
+    provider_authority: Pubkey,
+) -> Instruction {
+    let (house, _) = house_pda(&program_id, &entropy_program_id);
+    let mut data = vec![INITIALIZE_HOUSE_ACTION];
+    data.extend_from_slice(bytemuck::bytes_of(&InitializeHouseArgs {
+        provider_authority: provider_authority.to_bytes(),
+    }));
+    Instruction {
+        program_id,
+        data,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(house, false),
+            AccountMeta::new_readonly(entropy_program_id, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    }
+}
+
+/// Escrows `args.wager_lamports` from `player` and requests entropy from
+/// `provider_authority`, which must be the house's provider.
+/// `request_account` is a fresh keypair that must sign.
+pub fn flip(
+    program_id: Pubkey,
+    entropy_program_id: Pubkey,
+    player: Pubkey,
+    request_account: Pubkey,
+    provider_authority: Pubkey,
+    args: &FlipArgs,
+) -> Instruction {
+    let (flip, _) = flip_pda(&program_id, &request_account);
+    let (house, _) = house_pda(&program_id, &entropy_program_id);
+    let (requester_signer, requester_signer_bump) =
+        requester_signer_pda(&program_id, &entropy_program_id);
+    let args = FlipArgs {
+        requester_signer_bump,
+        ..*args
+    };
+
+    let mut data = vec![FLIP_ACTION];
+    data.extend_from_slice(bytemuck::bytes_of(&args));
+
+    Instruction {
+        program_id,
+        data,
+        accounts: vec![
+            AccountMeta::new(player, true),
+            AccountMeta::new(flip, false),
+            AccountMeta::new(house, false),
+            AccountMeta::new_readonly(requester_signer, false),
+            AccountMeta::new(request_account, true),
+            AccountMeta::new(
+                provider_pda(&entropy_program_id, &provider_authority).0,
+                false,
+            ),
+            AccountMeta::new(
+                provider_vault_pda(&entropy_program_id, &provider_authority).0,
+                false,
+            ),
+            AccountMeta::new_readonly(config_pda(&entropy_program_id).0, false),
+            AccountMeta::new(pyth_fee_vault_pda(&entropy_program_id).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new_readonly(entropy_program_id, false),
+        ],
+    }
+}
+
+/// Returns the wager and rent of an expired, unsettled flip to its player.
+/// Anyone may submit it.
+pub fn refund(
+    program_id: Pubkey,
+    entropy_program_id: Pubkey,
+    player: Pubkey,
+    request_account: Pubkey,
+) -> Instruction {
+    let (flip, _) = flip_pda(&program_id, &request_account);
+    let (house, _) = house_pda(&program_id, &entropy_program_id);
+    Instruction {
+        program_id,
+        data: vec![REFUND_ACTION],
+        accounts: vec![
+            AccountMeta::new(flip, false),
+            AccountMeta::new(house, false),
+            AccountMeta::new(player, false),
+        ],
+    }
+}
@@ -0,0 +1,16 @@
+//! Example entropy consumer: a coin flip against a house bankroll.
+//!
+//! A player escrows a wager in a flip account and requests entropy with a
+//! callback. The callback resolves heads or tails from the random number and
+//! pays out from the escrow and the house; if no reveal lands within
+//! [`FLIP_TTL_SLOTS`](state::FLIP_TTL_SLOTS), the player takes the wager back.
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+pub use processor::process_instruction;
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
@@ -0,0 +1,352 @@
+#[allow(deprecated)]
+use {
+    crate::{
+        error::CoinFlipError,
+        instruction::{
+            FlipArgs, InitializeHouseArgs, CALLBACK_ACTION, FLIP_ACTION, INITIALIZE_HOUSE_ACTION,
+            REFUND_ACTION,
+        },
+        state::{
+            flip_pda, house_pda, outcome, Flip, House, FLIP_SEED, FLIP_TTL_SLOTS, HEADS,
+            HOUSE_SEED, TAILS,
+        },
+    },
+    bytemuck::{try_from_bytes, try_from_bytes_mut},
+    entropy::pda::provider_pda,
+    entropy_cpi::{
+        callback_meta, parse_callback, verify_entropy_signer, RequestAccounts, RequestParams,
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program::invoke_signed,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+        sysvar::{clock::Clock, Sysvar},
+    },
+};
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (action, data) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    match *action {
+        INITIALIZE_HOUSE_ACTION => process_initialize_house(program_id, accounts, data),
+        FLIP_ACTION => process_flip(program_id, accounts, data),
+        REFUND_ACTION => process_refund(program_id, accounts, data),
+        CALLBACK_ACTION => process_callback(program_id, accounts, data),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn process_initialize_house(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = try_from_bytes::<InitializeHouseArgs>(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let mut account_info_iter = accounts.iter();
+    let payer = next_account_info(&mut account_info_iter)?;
+    let house_account = next_account_info(&mut account_info_iter)?;
+    let entropy_program = next_account_info(&mut account_info_iter)?;
+    let system_program = next_account_info(&mut account_info_iter)?;
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (expected_house, bump) = house_pda(program_id, entropy_program.key);
+    if house_account.key != &expected_house {
+        return Err(CoinFlipError::InvalidPda.into());
+    }
+
+    let create_ix = system_instruction::create_account(
+        payer.key,
+        house_account.key,
+        Rent::get()?.minimum_balance(House::LEN),
+        House::LEN as u64,
+        program_id,
+    );
+    invoke_signed(
+        &create_ix,
+        &[payer.clone(), house_account.clone(), system_program.clone()],
+        &[&[HOUSE_SEED, entropy_program.key.as_ref(), &[bump]]],
+    )?;
+
+    let mut house_data = house_account.try_borrow_mut_data()?;
+    let house = load_mut::<House>(&mut house_data)?;
+    house.provider_authority = args.provider_authority;
+    house.bump = bump;
+    Ok(())
+}
+
+/// Escrows the wager, locks the same amount of the house bankroll and
+/// requests entropy with a callback that settles the flip.
+fn process_flip(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let args =
+        try_from_bytes::<FlipArgs>(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    if args.guess != HEADS && args.guess != TAILS {
+        return Err(CoinFlipError::InvalidGuess.into());
+    }
+    if args.wager_lamports == 0 {
+        return Err(CoinFlipError::InvalidWager.into());
+    }
+    // Entropy skips the callback when the limit is zero, which would leave
+    // the flip unsettled until it expires.
+    if args.compute_unit_limit == 0 {
+        return Err(CoinFlipError::CallbackRequired.into());
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let player = next_account_info(&mut account_info_iter)?;
+    let flip_account = next_account_info(&mut account_info_iter)?;
+    let house_account = next_account_info(&mut account_info_iter)?;
+    let requester_signer = next_account_info(&mut account_info_iter)?;
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let provider = next_account_info(&mut account_info_iter)?;
+    let provider_vault = next_account_info(&mut account_info_iter)?;
+    let config = next_account_info(&mut account_info_iter)?;
+    let pyth_fee_vault = next_account_info(&mut account_info_iter)?;
+    let system_program = next_account_info(&mut account_info_iter)?;
+    let requester_program = next_account_info(&mut account_info_iter)?;
+    let entropy_program = next_account_info(&mut account_info_iter)?;
+
+    if !player.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (expected_flip, flip_bump) = flip_pda(program_id, request_account.key);
+    if flip_account.key != &expected_flip {
+        return Err(CoinFlipError::InvalidPda.into());
+    }
+    let (expected_house, _house_bump) = house_pda(program_id, entropy_program.key);
+    if house_account.key != &expected_house {
+        return Err(CoinFlipError::InvalidPda.into());
+    }
+    if house_account.owner != program_id {
+        return Err(CoinFlipError::InvalidAccount.into());
+    }
+
+    let rent = Rent::get()?;
+    {
+        let mut house_data = house_account.try_borrow_mut_data()?;
+        let house = load_mut::<House>(&mut house_data)?;
+        let (expected_provider, _provider_bump) = provider_pda(
+            entropy_program.key,
+            &Pubkey::new_from_array(house.provider_authority),
+        );
+        if provider.key != &expected_provider {
+            return Err(CoinFlipError::WrongProvider.into());
+        }
+        let available = house_account
+            .lamports()
+            .saturating_sub(rent.minimum_balance(House::LEN))
+            .saturating_sub(house.locked_lamports);
+        if available < args.wager_lamports {
+            return Err(CoinFlipError::HouseCannotCover.into());
+        }
+        house.locked_lamports = house
+            .locked_lamports
+            .checked_add(args.wager_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+
+    // The flip account holds its rent plus the escrowed wager.
+    let create_ix = system_instruction::create_account(
+        player.key,
+        flip_account.key,
+        rent.minimum_balance(Flip::LEN)
+            .checked_add(args.wager_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+        Flip::LEN as u64,
+        program_id,
+    );
+    invoke_signed(
+        &create_ix,
+        &[player.clone(), flip_account.clone(), system_program.clone()],
+        &[&[FLIP_SEED, request_account.key.as_ref(), &[flip_bump]]],
+    )?;
+
+    let sequence_number = entropy_cpi::request_with_callback(
+        program_id,
+        &RequestAccounts {
+            requester_signer,
+            requester_signer_bump: args.requester_signer_bump,
+            payer: player,
+            requester_program,
+            request_account,
+            provider,
+            provider_vault,
+            config,
+            pyth_fee_vault,
+            system_program,
+            entropy_program,
+        },
+        args.user_randomness,
+        &RequestParams {
+            compute_unit_limit: args.compute_unit_limit,
+            ttl_slots: FLIP_TTL_SLOTS,
+            callback_accounts: &[
+                callback_meta(flip_account.key, false, true),
+                callback_meta(house_account.key, false, true),
+                callback_meta(player.key, false, true),
+            ],
+            callback_ix_data: &[CALLBACK_ACTION],
+            ..Default::default()
+        },
+    )?;
+
+    let mut flip_data = flip_account.try_borrow_mut_data()?;
+    let flip = load_mut::<Flip>(&mut flip_data)?;
+    flip.player = player.key.to_bytes();
+    flip.entropy_program = entropy_program.key.to_bytes();
+    flip.request_account = request_account.key.to_bytes();
+    flip.sequence_number = sequence_number;
+    flip.wager_lamports = args.wager_lamports;
+    flip.request_slot = Clock::get()?.slot;
+    flip.guess = args.guess;
+    flip.bump = flip_bump;
+    Ok(())
+}
+
+/// Settles a flip: a winner receives the escrow plus the same amount from the
+/// house, otherwise the wager goes to the house. The flip's rent always
+/// returns to the player.
+fn process_callback(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (prefix, callback) = parse_callback(data)?;
+    if !prefix.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let entropy_signer = next_account_info(&mut account_info_iter)?;
+    let flip_account = next_account_info(&mut account_info_iter)?;
+    let house_account = next_account_info(&mut account_info_iter)?;
+    let player = next_account_info(&mut account_info_iter)?;
+
+    let flip = load_flip(program_id, flip_account, house_account, player)?;
+    verify_entropy_signer(
+        entropy_signer,
+        &Pubkey::new_from_array(flip.entropy_program),
+    )?;
+    if callback.sequence_number != flip.sequence_number {
+        return Err(CoinFlipError::InvalidAccount.into());
+    }
+
+    let outcome = outcome(&callback.random_number);
+    let won = outcome == flip.guess;
+    msg!(
+        "Flip {}: {} ({})",
+        flip.sequence_number,
+        if outcome == HEADS { "heads" } else { "tails" },
+        if won { "won" } else { "lost" }
+    );
+
+    let house_lamports = if won {
+        house_account.lamports().checked_sub(flip.wager_lamports)
+    } else {
+        house_account.lamports().checked_add(flip.wager_lamports)
+    }
+    .ok_or(ProgramError::ArithmeticOverflow)?;
+    let player_credit = if won {
+        flip_account.lamports().checked_add(flip.wager_lamports)
+    } else {
+        flip_account.lamports().checked_sub(flip.wager_lamports)
+    }
+    .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    **house_account.try_borrow_mut_lamports()? = house_lamports;
+    close_flip(&flip, flip_account, house_account, player, player_credit)
+}
+
+/// Returns the escrow of a flip whose reveal never landed, e.g. because the
+/// callback kept failing or the provider stopped revealing.
+fn process_refund(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if !data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let flip_account = next_account_info(&mut account_info_iter)?;
+    let house_account = next_account_info(&mut account_info_iter)?;
+    let player = next_account_info(&mut account_info_iter)?;
+
+    let flip = load_flip(program_id, flip_account, house_account, player)?;
+    if !flip.is_expired(Clock::get()?.slot) {
+        return Err(CoinFlipError::FlipNotExpired.into());
+    }
+
+    close_flip(
+        &flip,
+        flip_account,
+        house_account,
+        player,
+        flip_account.lamports(),
+    )
+}
+
+/// Loads an unsettled flip and checks the house and player accounts passed
+/// with it.
+fn load_flip(
+    program_id: &Pubkey,
+    flip_account: &AccountInfo,
+    house_account: &AccountInfo,
+    player: &AccountInfo,
+) -> Result<Flip, ProgramError> {
+    if flip_account.owner != program_id
+        || !flip_account.is_writable
+        || house_account.owner != program_id
+        || !house_account.is_writable
+        || !player.is_writable
+    {
+        return Err(CoinFlipError::InvalidAccount.into());
+    }
+
+    let flip = *try_from_bytes::<Flip>(&flip_account.try_borrow_data()?)
+        .map_err(|_| CoinFlipError::InvalidAccount)?;
+    if player.key.to_bytes() != flip.player {
+        return Err(CoinFlipError::InvalidAccount.into());
+    }
+    let (expected_house, _house_bump) =
+        house_pda(program_id, &Pubkey::new_from_array(flip.entropy_program));
+    if house_account.key != &expected_house {
+        return Err(CoinFlipError::InvalidPda.into());
+    }
+    Ok(flip)
+}
+
+/// Unlocks the flip's wager in the house, credits `player_credit` to the
+/// player and closes the flip account.
+fn close_flip(
+    flip: &Flip,
+    flip_account: &AccountInfo,
+    house_account: &AccountInfo,
+    player: &AccountInfo,
+    player_credit: u64,
+) -> ProgramResult {
+    {
+        let mut house_data = house_account.try_borrow_mut_data()?;
+        let house = load_mut::<House>(&mut house_data)?;
+        house.locked_lamports = house.locked_lamports.saturating_sub(flip.wager_lamports);
+    }
+
+    let player_lamports = player
+        .lamports()
+        .checked_add(player_credit)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    flip_account.try_borrow_mut_data()?.fill(0);
+    **flip_account.try_borrow_mut_lamports()? = 0;
+    **player.try_borrow_mut_lamports()? = player_lamports;
+    Ok(())
+}
+
+fn load_mut<T: bytemuck::Pod>(data: &mut [u8]) -> Result<&mut T, ProgramError> {
+    try_from_bytes_mut::<T>(data).map_err(|_| CoinFlipError::InvalidAccount.into())
+}
@@ -0,0 +1,77 @@
+use {
+    bytemuck::{Pod, Zeroable},
+    entropy_cpi::rand,
+    solana_program::pubkey::Pubkey,
+};
+
+pub const HOUSE_SEED: &[u8] = b"house";
+pub const FLIP_SEED: &[u8] = b"flip";
+
+/// Slots a flip waits for its reveal before the player can reclaim the
+/// wager. Flips request entropy with the same TTL, so entropy rejects the
+/// reveal of any flip that can be refunded.
+pub const FLIP_TTL_SLOTS: u32 = 300;
+
+pub const HEADS: u8 = 0;
+pub const TAILS: u8 = 1;
+
+/// The bankroll flips are played against, one per entropy deployment. Its
+/// lamports above rent and `locked_lamports` back new wagers; anyone may
+/// fund it with a plain transfer.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct House {
+    /// The only provider flips may request from. A provider sees the random
+    /// number before revealing it, so one picked by the player could reveal
+    /// only the flips the player wins.
+    pub provider_authority: [u8; 32],
+    /// Sum of the wagers of unsettled flips, owed to their players if they win.
+    pub locked_lamports: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl House {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+}
+
+/// An unsettled flip. Its lamports are the rent plus the escrowed wager; the
+/// account is closed when the flip is settled or refunded.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Flip {
+    pub player: [u8; 32],
+    pub entropy_program: [u8; 32],
+    pub request_account: [u8; 32],
+    pub sequence_number: u64,
+    pub wager_lamports: u64,
+    pub request_slot: u64,
+    pub guess: u8,
+    pub bump: u8,
+    pub _padding: [u8; 6],
+}
+
+impl Flip {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    /// Matches entropy's expiry rule for the underlying request.
+    pub fn is_expired(&self, slot: u64) -> bool {
+        slot > self.request_slot.saturating_add(u64::from(FLIP_TTL_SLOTS))
+    }
+}
+
+pub fn house_pda(program_id: &Pubkey, entropy_program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[HOUSE_SEED, entropy_program_id.as_ref()], program_id)
+}
+
+/// Flips are keyed by the fresh entropy request account, so the client knows
+/// the address before the sequence number is assigned.
+pub fn flip_pda(program_id: &Pubkey, request_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FLIP_SEED, request_account.as_ref()], program_id)
+}
+
+/// [`HEADS`] or [`TAILS`]: the lowest bit of the random number, so anyone can
+/// check a result against the `RandomnessRevealed` event.
+pub fn outcome(random_number: &[u8; 32]) -> u8 {
+    rand::u64_in_range(random_number, 0, 1) as u8
+}
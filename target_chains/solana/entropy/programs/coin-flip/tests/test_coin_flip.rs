@@ -0,0 +1,316 @@
+use {
+    bytemuck::try_from_bytes,
+    coin_flip::{
+        error::CoinFlipError,
+        instruction::{flip, initialize_house, refund, FlipArgs},
+        state::{flip_pda, house_pda, outcome, Flip, House, FLIP_TTL_SLOTS, HEADS},
+    },
+    entropy::{
+        error::EntropyError,
+        pda::{provider_pda, provider_vault_pda},
+    },
+    entropy_sdk::hash_chain::HashChain,
+    entropy_test_utils::{
+        build_register_args, build_register_provider_ix, submit_tx, submit_tx_expect_err,
+        EntropyFixture, EntropyTestEnv, MockKeeper,
+    },
+    solana_program::{pubkey::Pubkey, system_instruction},
+    solana_program_test::processor,
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+const WAGER: u64 = 1_000_000_000;
+
+struct Game {
+    env: EntropyTestEnv,
+    keeper: MockKeeper,
+    program_id: Pubkey,
+    player: Keypair,
+}
+
+/// Starts entropy with a free provider, creates the house with
+/// `house_funding` lamports of bankroll and funds a player.
+async fn start(house_funding: u64) -> Game {
+    let program_id = Pubkey::new_unique();
+    let mut env = EntropyFixture::new()
+        .with_provider(0, 16)
+        .with_setup(move |program_test| {
+            program_test.add_program(
+                "coin_flip",
+                program_id,
+                processor!(coin_flip::process_instruction),
+            )
+        })
+        .start()
+        .await;
+    let keeper = MockKeeper::new(&env);
+    let payer = env.payer();
+    let player = Keypair::new();
+    let (house, _) = house_pda(&program_id, &env.program_id);
+
+    let mut instructions = vec![
+        initialize_house(
+            program_id,
+            env.program_id,
+            payer.pubkey(),
+            env.provider().authority,
+        ),
+        system_instruction::transfer(&payer.pubkey(), &player.pubkey(), 10 * WAGER),
+    ];
+    if house_funding > 0 {
+        instructions.push(system_instruction::transfer(
+            &payer.pubkey(),
+            &house,
+            house_funding,
+        ));
+    }
+    submit_tx(&mut env.context.banks_client, &payer, &instructions, &[]).await;
+
+    Game {
+        env,
+        keeper,
+        program_id,
+        player,
+    }
+}
+
+impl Game {
+    fn flip_ix(&self, request_account: &Keypair, guess: u8) -> Instruction {
+        self.flip_ix_through(request_account, guess, self.env.provider().authority)
+    }
+
+    fn flip_ix_through(
+        &self,
+        request_account: &Keypair,
+        guess: u8,
+        provider_authority: Pubkey,
+    ) -> Instruction {
+        flip(
+            self.program_id,
+            self.env.program_id,
+            self.player.pubkey(),
+            request_account.pubkey(),
+            provider_authority,
+            &FlipArgs {
+                user_randomness: [5u8; 32],
+                wager_lamports: WAGER,
+                compute_unit_limit: 100_000,
+                guess,
+                requester_signer_bump: 0,
+                _padding: [0; 2],
+            },
+        )
+    }
+
+    async fn lamports(&mut self, address: Pubkey) -> Option<u64> {
+        self.env
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .map(|account| account.lamports)
+    }
+
+    async fn house(&mut self) -> (u64, House) {
+        let (address, _) = house_pda(&self.program_id, &self.env.program_id);
+        let account = self
+            .env
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        (
+            account.lamports,
+            *try_from_bytes::<House>(&account.data).unwrap(),
+        )
+    }
+}
+
+fn custom(code: u32) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code))
+}
+
+#[tokio::test]
+async fn test_flip_settles_wager() {
+    let mut game = start(10 * WAGER).await;
+    let payer = game.env.payer();
+    let player = game.player.pubkey();
+    let player_before = game.lamports(player).await.unwrap();
+    let (house_before, _) = game.house().await;
+
+    let request_account = Keypair::new();
+    let instruction = game.flip_ix(&request_account, HEADS);
+    let (flip_address, _) = flip_pda(&game.program_id, &request_account.pubkey());
+    let player_keypair = game.player.insecure_clone();
+    let reveals = game
+        .keeper
+        .submit(
+            &mut game.env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&player_keypair, &request_account],
+        )
+        .await
+        .unwrap();
+    assert_eq!(reveals.len(), 1);
+    let random_number = reveals[0].random_number().unwrap();
+
+    // Rent of the flip and request accounts comes back; only the wager moves.
+    let won = outcome(&random_number) == HEADS;
+    let (house_after, house) = game.house().await;
+    let player_after = game.lamports(player).await.unwrap();
+    if won {
+        assert_eq!(player_after, player_before + WAGER);
+        assert_eq!(house_after, house_before - WAGER);
+    } else {
+        assert_eq!(player_after, player_before - WAGER);
+        assert_eq!(house_after, house_before + WAGER);
+    }
+    assert_eq!(house.locked_lamports, 0);
+    assert!(game.lamports(flip_address).await.is_none());
+}
+
+#[tokio::test]
+async fn test_refund_after_timeout() {
+    let mut game = start(10 * WAGER).await;
+    let payer = game.env.payer();
+    let player = game.player.pubkey();
+    let player_keypair = game.player.insecure_clone();
+    let player_before = game.lamports(player).await.unwrap();
+
+    let request_account = Keypair::new();
+    let instruction = game.flip_ix(&request_account, HEADS);
+    submit_tx(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&player_keypair, &request_account],
+    )
+    .await;
+    let (flip_address, _) = flip_pda(&game.program_id, &request_account.pubkey());
+    let flip_account = game
+        .env
+        .context
+        .banks_client
+        .get_account(flip_address)
+        .await
+        .unwrap()
+        .unwrap();
+    let flip_state = *try_from_bytes::<Flip>(&flip_account.data).unwrap();
+    assert_eq!(flip_state.wager_lamports, WAGER);
+    assert_eq!(game.house().await.1.locked_lamports, WAGER);
+
+    let refund_ix = refund(
+        game.program_id,
+        game.env.program_id,
+        player,
+        request_account.pubkey(),
+    );
+    let err = submit_tx_expect_err(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[refund_ix.clone()],
+        &[],
+    )
+    .await;
+    assert_eq!(err, custom(CoinFlipError::FlipNotExpired as u32));
+
+    // No reveal lands in time: entropy refuses it and the player is refunded.
+    game.env
+        .context
+        .warp_to_slot(flip_state.request_slot + u64::from(FLIP_TTL_SLOTS) + 1)
+        .unwrap();
+    let reveals = game
+        .keeper
+        .reveal_pending(&mut game.env.context.banks_client, &payer)
+        .await;
+    assert_eq!(
+        reveals[0].result.as_ref().err(),
+        Some(&custom(EntropyError::RequestExpired as u32))
+    );
+
+    submit_tx(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[refund_ix],
+        &[],
+    )
+    .await;
+    assert!(game.lamports(flip_address).await.is_none());
+    assert_eq!(game.house().await.1.locked_lamports, 0);
+    // Everything but the still-open entropy request account's rent is back.
+    let request_rent = game.lamports(request_account.pubkey()).await.unwrap();
+    assert_eq!(
+        game.lamports(player).await.unwrap(),
+        player_before - request_rent
+    );
+}
+
+#[tokio::test]
+async fn test_flip_rejects_uncovered_wager() {
+    let mut game = start(WAGER / 2).await;
+    let payer = game.env.payer();
+    let player_keypair = game.player.insecure_clone();
+
+    let request_account = Keypair::new();
+    let instruction = game.flip_ix(&request_account, HEADS);
+    let err = submit_tx_expect_err(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&player_keypair, &request_account],
+    )
+    .await;
+    assert_eq!(err, custom(CoinFlipError::HouseCannotCover as u32));
+}
+
+#[tokio::test]
+async fn test_flip_rejects_other_providers() {
+    let mut game = start(10 * WAGER).await;
+    let payer = game.env.payer();
+    let player_keypair = game.player.insecure_clone();
+    let (_, house) = game.house().await;
+    assert_eq!(
+        house.provider_authority,
+        game.env.provider().authority.to_bytes()
+    );
+
+    // A provider of the player's own, which could reveal only winning flips.
+    let rogue = Keypair::new();
+    let chain = HashChain::from_seed([9u8; 32], 16);
+    let fund_ix = system_instruction::transfer(&payer.pubkey(), &rogue.pubkey(), 1_000_000_000);
+    let register_ix = build_register_provider_ix(
+        game.env.program_id,
+        rogue.pubkey(),
+        provider_pda(&game.env.program_id, &rogue.pubkey()).0,
+        provider_vault_pda(&game.env.program_id, &rogue.pubkey()).0,
+        build_register_args(0, chain.commitment(), 16),
+        true,
+    );
+    submit_tx(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[fund_ix, register_ix],
+        &[&rogue],
+    )
+    .await;
+
+    let request_account = Keypair::new();
+    let instruction = game.flip_ix_through(&request_account, HEADS, rogue.pubkey());
+    let err = submit_tx_expect_err(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&player_keypair, &request_account],
+    )
+    .await;
+    assert_eq!(err, custom(CoinFlipError::WrongProvider as u32));
+    assert_eq!(game.house().await.1.locked_lamports, 0);
+}
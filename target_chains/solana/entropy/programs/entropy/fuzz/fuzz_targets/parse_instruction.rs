@@ -0,0 +1,32 @@
+#![no_main]
+
+use {
+    entropy::{
+        accounts::CallbackMeta,
+        instruction::{
+            parse_request_with_callback_args, EntropyInstruction, RequestWithCallbackHeader,
+        },
+    },
+    libfuzzer_sys::fuzz_target,
+};
+
+fuzz_target!(|data: &[u8]| {
+    // The runtime hands programs 8-byte aligned instruction data; copy into a
+    // fresh allocation so the zero-copy casts see the same alignment.
+    let data = data.to_vec();
+    let Ok((instruction, payload)) = EntropyInstruction::parse(&data) else {
+        return;
+    };
+    if !matches!(instruction, EntropyInstruction::RequestWithCallback) {
+        return;
+    }
+    if let Ok(args) = parse_request_with_callback_args(payload) {
+        assert_eq!(
+            core::mem::size_of::<RequestWithCallbackHeader>()
+                + args.callback_accounts.len() * CallbackMeta::LEN
+                + 4
+                + args.callback_ix_data.len(),
+            payload.len()
+        );
+    }
+});
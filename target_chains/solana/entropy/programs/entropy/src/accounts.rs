@@ -1,7 +1,15 @@
 use crate::constants::{
-    CALLBACK_IX_DATA_LEN, COMMITMENT_METADATA_LEN, MAX_CALLBACK_ACCOUNTS, URI_LEN,
+    CALLBACK_IX_DATA_LEN, COMMITMENT_METADATA_LEN, MAX_AGGREGATE_PROVIDERS, MAX_ALLOWED_PROVIDERS,
+    MAX_CALLBACK_ACCOUNTS, MAX_CALLBACK_ACCOUNT_LIST_LEN, MAX_NUM_HASHES, MAX_REGISTERED_PROGRAMS,
+    PROVIDER_DIRECTORY_PAGE_LEN, URI_LEN,
 };
-use crate::discriminator::{config_discriminator, provider_discriminator, request_discriminator};
+use crate::discriminator::{
+    aggregate_request_discriminator, callback_account_list_discriminator,
+    callback_failure_stats_discriminator, config_discriminator, governance_config_discriminator,
+    keeper_lease_discriminator, program_registry_discriminator, provider_allowlist_discriminator,
+    provider_directory_page_discriminator, provider_discriminator, request_discriminator,
+};
+use crate::error::EntropyError;
 use bytemuck::{Pod, Zeroable};
 use solana_program::program_error::ProgramError;
 
@@ -22,11 +30,35 @@ pub struct Config {
     pub proposed_admin: PubkeyBytes,
     pub seed: [u8; 32],
     pub bump: u8,
-    pub _padding0: [u8; 7],
+    /// 1 to restrict `RegisterProvider` to the authorities in the
+    /// [`ProviderAllowlist`]; 0 lets anyone register.
+    pub permissioned_providers: u8,
+    /// Canonical bump of the entropy signer, copied into each request so
+    /// reveals need not search for it. Zero in configs initialized before it
+    /// was recorded.
+    pub entropy_signer_bump: u8,
+    /// [`PROGRAM_VERSION`](crate::constants::PROGRAM_VERSION) of the program
+    /// that initialized the config; zero in configs initialized before it
+    /// was recorded. `GetVersion` reports the version currently deployed.
+    pub program_version: u8,
+    /// Provider authorities listed in the provider directory; the next one
+    /// goes to page `provider_directory_len / PROVIDER_DIRECTORY_PAGE_LEN`.
+    pub provider_directory_len: u32,
 }
 
 impl Config {
     pub const LEN: usize = core::mem::size_of::<Self>();
+
+    /// Number of provider directory pages in use.
+    pub fn provider_directory_pages(&self) -> u32 {
+        self.provider_directory_len
+            .div_ceil(PROVIDER_DIRECTORY_PAGE_LEN as u32)
+    }
+
+    /// Page the next registered provider is listed in.
+    pub fn provider_directory_next_page(&self) -> u32 {
+        self.provider_directory_len / PROVIDER_DIRECTORY_PAGE_LEN as u32
+    }
 }
 
 impl Account for Config {
@@ -49,7 +81,11 @@ pub struct Provider {
     pub commitment_metadata: [u8; COMMITMENT_METADATA_LEN],
     pub uri_len: u16,
     pub uri: [u8; URI_LEN],
-    pub _padding0: [u8; 4],
+    /// Requests not yet revealed or closed. Incremented on request and
+    /// decremented on reveal or `CloseExpiredRequest`; `CloseProvider`
+    /// requires it to be zero, and keepers can read it instead of scanning
+    /// request accounts.
+    pub outstanding_requests: u32,
     pub end_sequence_number: u64,
     pub sequence_number: u64,
     pub current_commitment: [u8; 32],
@@ -58,12 +94,88 @@ pub struct Provider {
     pub max_num_hashes: u32,
     pub default_compute_unit_limit: u32,
     pub bump: u8,
-    pub _padding1: [u8; 7],
+    /// 1 once the authority is listed in the provider directory. Providers
+    /// registered before the directory existed are listed when they next
+    /// register.
+    pub in_provider_directory: u8,
+    /// Callback failures within `callback_skip_window_slots` after which
+    /// the provider may reveal a requester program's requests without their
+    /// callback, provided they outnumber the callbacks that succeeded; zero
+    /// disables skipping. The failures are the provider's own reports, so
+    /// requesters trust the provider not to report callbacks that succeeded.
+    /// Set by `SetCallbackSkipPolicy`; see [`Provider::skips_callbacks`].
+    pub callback_skip_max_failures: u16,
+    pub callback_skip_window_slots: u32,
+    /// Reveals counted in the latency stats below. Latency is the number of
+    /// slots from the request to its reveal; requests that expire are not
+    /// counted.
+    pub reveal_count: u64,
+    pub reveal_latency_sum_slots: u64,
+    pub reveal_latency_max_slots: u64,
+    /// Provider fees of outstanding requests, which the vault holds back
+    /// from withdrawal so that a refund can always be paid.
+    pub reserved_fee_lamports: u64,
 }
 
 impl Provider {
     pub const LEN: usize = core::mem::size_of::<Self>();
 
+    /// The registered URI, empty if unset. Registration only accepts UTF-8;
+    /// a provider registered before that was checked reads as empty if its
+    /// URI is not.
+    pub fn uri_str(&self) -> &str {
+        let len = usize::from(self.uri_len).min(URI_LEN);
+        core::str::from_utf8(&self.uri[..len]).unwrap_or_default()
+    }
+
+    /// Mean request-to-reveal latency in slots, or `None` before the first
+    /// reveal.
+    pub fn average_reveal_latency_slots(&self) -> Option<u64> {
+        self.reveal_latency_sum_slots.checked_div(self.reveal_count)
+    }
+
+    /// Most hashes a request to this provider may leave for its reveal:
+    /// `max_num_hashes` if set, capped at [`MAX_NUM_HASHES`].
+    pub fn num_hashes_cap(&self) -> u32 {
+        match self.max_num_hashes {
+            0 => MAX_NUM_HASHES,
+            max_num_hashes => max_num_hashes.min(MAX_NUM_HASHES),
+        }
+    }
+
+    /// Whether the skip policy lets the provider reveal the requests of the
+    /// program `stats` counts without invoking their callback at `slot`: the
+    /// open window must hold at least `callback_skip_max_failures` failures,
+    /// and more failures than successful callbacks.
+    pub fn skips_callbacks(&self, stats: &CallbackFailureStats, slot: u64) -> bool {
+        let failures = stats.failures_in_window(slot, self.callback_skip_window_slots);
+        self.callback_skip_max_failures != 0
+            && failures >= u32::from(self.callback_skip_max_failures)
+            && failures > stats.callbacks_in_window(slot, self.callback_skip_window_slots)
+    }
+
+    pub fn record_reveal_latency(&mut self, latency_slots: u64) {
+        self.reveal_count = self.reveal_count.saturating_add(1);
+        self.reveal_latency_sum_slots = self.reveal_latency_sum_slots.saturating_add(latency_slots);
+        self.reveal_latency_max_slots = self.reveal_latency_max_slots.max(latency_slots);
+    }
+
+    /// Holds `fee` back from withdrawal until its request is revealed or
+    /// refunded.
+    pub fn reserve_fee(&mut self, fee: u64) -> Result<(), ProgramError> {
+        self.reserved_fee_lamports = self
+            .reserved_fee_lamports
+            .checked_add(fee)
+            .ok_or(ProgramError::InvalidArgument)?;
+        Ok(())
+    }
+
+    /// Releases a fee held by [`Provider::reserve_fee`]. Saturating: requests
+    /// made before the reserve existed were never added to it.
+    pub fn release_fee(&mut self, fee: u64) {
+        self.reserved_fee_lamports = self.reserved_fee_lamports.saturating_sub(fee);
+    }
+
     pub fn calculate_provider_fee(&self, compute_unit_limit: u32) -> Result<u64, ProgramError> {
         if self.default_compute_unit_limit > 0
             && compute_unit_limit > self.default_compute_unit_limit
@@ -101,6 +213,46 @@ impl CallbackMeta {
     pub const LEN: usize = core::mem::size_of::<Self>();
 }
 
+/// Callback status of a request (mirrors EntropyStatusConstants), stored as
+/// its `u8` value in [`Request::callback_status`].
+///
+/// A failed callback aborts the whole reveal, so the program never sees one:
+/// [`CallbackStatus::Failed`] only records that the provider reported it.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallbackStatus {
+    /// A request without a callback; revealed by the requester.
+    NotNecessary = 0,
+    /// A callback request waiting for its reveal.
+    NotStarted = 1,
+    /// Reserved for a callback that is executing; never stored.
+    InProgress = 2,
+    /// A callback request whose failure the provider reported with
+    /// `ReportCallbackFailure`. Still revealable with its callback, and
+    /// without it once the provider's skip policy allows.
+    Failed = 3,
+}
+
+impl TryFrom<u8> for CallbackStatus {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(CallbackStatus::NotNecessary),
+            1 => Ok(CallbackStatus::NotStarted),
+            2 => Ok(CallbackStatus::InProgress),
+            3 => Ok(CallbackStatus::Failed),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+impl From<CallbackStatus> for u8 {
+    fn from(status: CallbackStatus) -> u8 {
+        status as u8
+    }
+}
+
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
 pub struct Request {
@@ -109,26 +261,86 @@ pub struct Request {
     pub sequence_number: u64,
     pub num_hashes: u32,
     pub commitment: [u8; 32],
-    pub _padding0: [u8; 4],
+    /// Zero if the request never expires; see [`Request::is_expired`].
+    pub ttl_slots: u32,
     pub request_slot: u64,
     pub requester_program_id: PubkeyBytes,
-    pub requester_signer: PubkeyBytes,
+    /// The request's [`CallbackAccountList`], whose accounts the callback
+    /// receives after `callback_accounts`; zero if it has none.
+    pub callback_account_list: PubkeyBytes,
     pub payer: PubkeyBytes,
     pub use_blockhash: u8,
+    /// A [`CallbackStatus`]; read it with [`Request::status`].
     pub callback_status: u8,
-    pub _padding1: [u8; 2],
+    /// See [`Request::is_revealable_yet`].
+    pub min_reveal_delay_slots: u16,
     pub compute_unit_limit: u32,
     pub callback_accounts_len: u8,
-    pub _padding2: [u8; 1],
+    /// 1 if the callback data carries the user and provider contributions
+    /// after the random number.
+    pub include_contributions: u8,
     pub callback_accounts: [CallbackMeta; MAX_CALLBACK_ACCOUNTS],
     pub callback_ix_data_len: u16,
     pub callback_ix_data: [u8; CALLBACK_IX_DATA_LEN],
     pub bump: u8,
-    pub _padding3: [u8; 3],
+    /// Derivation of the random number; see
+    /// [`combine_random_values`](crate::randomness::combine_random_values).
+    pub random_number_version: u8,
+    /// Canonical bumps of the provider account and the entropy signer,
+    /// recorded at request time so the reveal re-derives both addresses
+    /// without searching. Zero if unknown when the request was made; see
+    /// [`provider_pda_with_bump`](crate::pda::provider_pda_with_bump).
+    pub provider_bump: u8,
+    pub entropy_signer_bump: u8,
+    /// Provider fee the payer paid into the provider vault, refunded by
+    /// `CloseExpiredRequest` if the request is never revealed.
+    pub provider_fee: u64,
+    /// Where closing the request sends its lamports: the payer unless the
+    /// request named another address.
+    pub refund_address: PubkeyBytes,
 }
 
 impl Request {
     pub const LEN: usize = core::mem::size_of::<Self>();
+
+    /// Whether the request can no longer be revealed at `slot`. A request
+    /// with a TTL stays revealable up to and including
+    /// `request_slot + ttl_slots`.
+    pub fn is_expired(&self, slot: u64) -> bool {
+        self.ttl_slots != 0 && slot > self.request_slot.saturating_add(u64::from(self.ttl_slots))
+    }
+
+    /// Whether the minimum reveal delay has passed at `slot`. A request is
+    /// revealable from `request_slot + min_reveal_delay_slots` onwards. One
+    /// that mixes in its slot's blockhash waits at least one slot regardless,
+    /// so the reveal never shares a slot with the hash it depends on.
+    pub fn is_revealable_yet(&self, slot: u64) -> bool {
+        let min_reveal_delay_slots = self
+            .min_reveal_delay_slots
+            .max(u16::from(self.use_blockhash == 1));
+        slot >= self
+            .request_slot
+            .saturating_add(u64::from(min_reveal_delay_slots))
+    }
+
+    /// The request's [`CallbackStatus`]; `InvalidAccountData` if the stored
+    /// byte is none of them.
+    pub fn status(&self) -> Result<CallbackStatus, ProgramError> {
+        CallbackStatus::try_from(self.callback_status)
+    }
+
+    /// Whether the request still waits for its callback, reported as failing
+    /// or not.
+    pub fn awaits_callback(&self) -> bool {
+        matches!(
+            self.status(),
+            Ok(CallbackStatus::NotStarted | CallbackStatus::Failed)
+        )
+    }
+
+    pub fn has_callback_account_list(&self) -> bool {
+        self.callback_account_list != [0u8; 32]
+    }
 }
 
 impl Account for Request {
@@ -138,3 +350,485 @@ impl Account for Request {
         request_discriminator()
     }
 }
+
+/// Borrowed view of a [`Request`] whose callback accessors return only the
+/// populated prefixes of the fixed arrays, never the zeroed tail.
+#[derive(Clone, Copy)]
+pub struct RequestRef<'a> {
+    request: &'a Request,
+}
+
+impl<'a> RequestRef<'a> {
+    /// Reads a request in place from account data, checking its size and
+    /// discriminator. Fails on data not aligned for [`Request`].
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, ProgramError> {
+        if data.len() != Request::LEN {
+            return Err(EntropyError::WrongAccountSize.into());
+        }
+        if data[..8] != request_discriminator() {
+            return Err(EntropyError::WrongAccountType.into());
+        }
+        let request =
+            bytemuck::try_from_bytes(data).map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok(Self { request })
+    }
+
+    pub fn request(&self) -> &'a Request {
+        self.request
+    }
+
+    /// The `callback_accounts_len` accounts the callback is invoked with.
+    pub fn callback_accounts(&self) -> &'a [CallbackMeta] {
+        let len = usize::from(self.request.callback_accounts_len).min(MAX_CALLBACK_ACCOUNTS);
+        &self.request.callback_accounts[..len]
+    }
+
+    /// The `callback_ix_data_len` bytes the callback data starts with.
+    pub fn callback_ix_data(&self) -> &'a [u8] {
+        let len = usize::from(self.request.callback_ix_data_len).min(CALLBACK_IX_DATA_LEN);
+        &self.request.callback_ix_data[..len]
+    }
+}
+
+impl<'a> From<&'a Request> for RequestRef<'a> {
+    fn from(request: &'a Request) -> Self {
+        Self { request }
+    }
+}
+
+impl core::ops::Deref for RequestRef<'_> {
+    type Target = Request;
+
+    fn deref(&self) -> &Request {
+        self.request
+    }
+}
+
+/// Callback accounts past the [`MAX_CALLBACK_ACCOUNTS`] a request stores
+/// inline, a PDA at
+/// [`callback_account_list_pda`](crate::pda::callback_account_list_pda).
+/// `WriteCallbackAccountList` fills it before the request is made, the
+/// request records its address, and the reveal passes its accounts to the
+/// callback after the inline ones, then closes it.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct CallbackAccountList {
+    pub discriminator: [u8; 8],
+    pub request_account: PubkeyBytes,
+    pub num_accounts: u8,
+    pub bump: u8,
+    pub _padding0: [u8; 6],
+    pub callback_accounts: [CallbackMeta; MAX_CALLBACK_ACCOUNT_LIST_LEN],
+}
+
+impl CallbackAccountList {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    /// The `num_accounts` listed accounts, in callback order.
+    pub fn callback_accounts(&self) -> &[CallbackMeta] {
+        let len = usize::from(self.num_accounts).min(MAX_CALLBACK_ACCOUNT_LIST_LEN);
+        &self.callback_accounts[..len]
+    }
+}
+
+impl Account for CallbackAccountList {
+    const LEN: usize = Self::LEN;
+
+    fn discriminator() -> [u8; 8] {
+        callback_account_list_discriminator()
+    }
+}
+
+/// Callback failures of one requester program's requests to one provider, a
+/// PDA at [`callback_failure_stats_pda`](crate::pda::callback_failure_stats_pda)
+/// created by the first `ReportCallbackFailure`. A failed callback aborts its
+/// reveal, so the chain cannot count failures itself: the provider reports
+/// them, once per request, and `RevealWithCallback` counts the callbacks
+/// that succeed against them. The provider's
+/// [skip policy](Provider::skips_callbacks) reads both.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct CallbackFailureStats {
+    pub discriminator: [u8; 8],
+    pub provider_authority: PubkeyBytes,
+    pub requester_program_id: PubkeyBytes,
+    /// Failures reported since the account was created.
+    pub total_failures: u64,
+    /// Successful callbacks since the account was created.
+    pub total_callbacks: u64,
+    /// Slot of the first failure counted in `window_failures`.
+    pub window_start_slot: u64,
+    pub window_failures: u32,
+    /// Successful callbacks since `window_start_slot`.
+    pub window_callbacks: u32,
+    pub bump: u8,
+    pub _padding0: [u8; 7],
+}
+
+impl CallbackFailureStats {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    /// Failures counted in a window of `window_slots` that is still open at
+    /// `slot`, or zero once it has closed. A window lasts up to but not
+    /// including `window_start_slot + window_slots`.
+    pub fn failures_in_window(&self, slot: u64, window_slots: u32) -> u32 {
+        if self.window_is_open(slot, window_slots) {
+            self.window_failures
+        } else {
+            0
+        }
+    }
+
+    /// Successful callbacks counted in the same window as
+    /// [`CallbackFailureStats::failures_in_window`].
+    pub fn callbacks_in_window(&self, slot: u64, window_slots: u32) -> u32 {
+        if self.window_is_open(slot, window_slots) {
+            self.window_callbacks
+        } else {
+            0
+        }
+    }
+
+    /// Counts a failure at `slot`, opening a new window if the last one has
+    /// closed.
+    pub fn record_failure(&mut self, slot: u64, window_slots: u32) {
+        if self.failures_in_window(slot, window_slots) == 0 {
+            self.window_start_slot = slot;
+            self.window_failures = 0;
+            self.window_callbacks = 0;
+        }
+        self.window_failures = self.window_failures.saturating_add(1);
+        self.total_failures = self.total_failures.saturating_add(1);
+    }
+
+    /// Counts a successful callback at `slot`. Only failures open a window,
+    /// so outside one the callback only adds to the total.
+    pub fn record_callback(&mut self, slot: u64, window_slots: u32) {
+        if self.failures_in_window(slot, window_slots) != 0 {
+            self.window_callbacks = self.window_callbacks.saturating_add(1);
+        }
+        self.total_callbacks = self.total_callbacks.saturating_add(1);
+    }
+
+    fn window_is_open(&self, slot: u64, window_slots: u32) -> bool {
+        slot < self
+            .window_start_slot
+            .saturating_add(u64::from(window_slots))
+    }
+}
+
+impl Account for CallbackFailureStats {
+    const LEN: usize = Self::LEN;
+
+    fn discriminator() -> [u8; 8] {
+        callback_failure_stats_discriminator()
+    }
+}
+
+/// One provider's part of an [`AggregateRequest`].
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct AggregateContribution {
+    pub provider: PubkeyBytes,
+    pub sequence_number: u64,
+    /// The provider's current commitment when the request was made; the
+    /// contribution hashes to it `num_hashes` times.
+    pub provider_commitment: [u8; 32],
+    /// Zero until `revealed` is set.
+    pub contribution: [u8; 32],
+    pub provider_fee: u64,
+    pub num_hashes: u32,
+    pub revealed: u8,
+    pub _padding0: [u8; 3],
+}
+
+/// A callback request answered by several providers, created by
+/// `RequestAggregate`. Each provider reveals its own contribution; once
+/// `threshold` have, the callback receives the hash of the user contribution
+/// and the revealed provider contributions, and the account is closed.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct AggregateRequest {
+    pub discriminator: [u8; 8],
+    pub user_commitment: [u8; 32],
+    pub requester_program_id: PubkeyBytes,
+    pub payer: PubkeyBytes,
+    pub request_slot: u64,
+    /// Zero if the request never expires; see [`AggregateRequest::is_expired`].
+    pub ttl_slots: u32,
+    pub compute_unit_limit: u32,
+    pub num_providers: u8,
+    pub threshold: u8,
+    pub revealed_count: u8,
+    pub callback_accounts_len: u8,
+    pub callback_ix_data_len: u16,
+    /// See [`Request::is_revealable_yet`].
+    pub min_reveal_delay_slots: u16,
+    /// Receives the rent, and the fees of providers that did not reveal,
+    /// when the request is closed; the payer unless the request named
+    /// another address.
+    pub refund_address: PubkeyBytes,
+    /// See [`Request::use_blockhash`].
+    pub use_blockhash: u8,
+    /// See [`Request::random_number_version`].
+    pub random_number_version: u8,
+    pub _padding0: [u8; 6],
+    pub contributions: [AggregateContribution; MAX_AGGREGATE_PROVIDERS],
+    pub callback_accounts: [CallbackMeta; MAX_CALLBACK_ACCOUNTS],
+    pub callback_ix_data: [u8; CALLBACK_IX_DATA_LEN],
+}
+
+impl AggregateRequest {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    /// Same rule as [`Request::is_expired`].
+    pub fn is_expired(&self, slot: u64) -> bool {
+        self.ttl_slots != 0 && slot > self.request_slot.saturating_add(u64::from(self.ttl_slots))
+    }
+
+    /// Same rule as [`Request::is_revealable_yet`].
+    pub fn is_revealable_yet(&self, slot: u64) -> bool {
+        let min_reveal_delay_slots = self
+            .min_reveal_delay_slots
+            .max(u16::from(self.use_blockhash == 1));
+        slot >= self
+            .request_slot
+            .saturating_add(u64::from(min_reveal_delay_slots))
+    }
+
+    /// The contributions of the `num_providers` providers, in request order.
+    pub fn active_contributions(&self) -> &[AggregateContribution] {
+        let len = usize::from(self.num_providers).min(MAX_AGGREGATE_PROVIDERS);
+        &self.contributions[..len]
+    }
+}
+
+impl Account for AggregateRequest {
+    const LEN: usize = Self::LEN;
+
+    fn discriminator() -> [u8; 8] {
+        aggregate_request_discriminator()
+    }
+}
+
+/// Wormhole governance state, created by `InitializeGovernance`.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct GovernanceConfig {
+    pub discriminator: [u8; 8],
+    /// Wormhole core bridge that owns the posted VAAs.
+    pub wormhole_program: PubkeyBytes,
+    pub emitter_address: [u8; 32],
+    pub emitter_chain: u16,
+    pub _padding0: [u8; 6],
+    /// Sequence of the last executed VAA; later VAAs must be newer.
+    pub last_executed_sequence: u64,
+    pub bump: u8,
+    pub _padding1: [u8; 7],
+}
+
+impl GovernanceConfig {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+}
+
+impl Account for GovernanceConfig {
+    const LEN: usize = Self::LEN;
+
+    fn discriminator() -> [u8; 8] {
+        governance_config_discriminator()
+    }
+}
+
+/// Which keeper instance reveals for a provider, created by the first
+/// `RefreshKeeperLease`. The active keeper refreshes it periodically; once it
+/// goes stale a standby may claim it, and monitoring can tell a dead keeper
+/// from the chain alone.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct KeeperLease {
+    pub discriminator: [u8; 8],
+    pub provider_authority: PubkeyBytes,
+    /// Keeper-chosen id of the holder.
+    pub keeper: [u8; 32],
+    pub refreshed_slot: u64,
+    /// Zero once the holder released the lease.
+    pub lease_slots: u64,
+    pub bump: u8,
+    pub _padding0: [u8; 7],
+}
+
+impl KeeperLease {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    /// Whether another keeper may claim the lease at `slot`. The holder's
+    /// claim lasts up to and including `refreshed_slot + lease_slots`.
+    pub fn is_stale(&self, slot: u64) -> bool {
+        slot > self.refreshed_slot.saturating_add(self.lease_slots)
+    }
+}
+
+impl Account for KeeperLease {
+    const LEN: usize = Self::LEN;
+
+    fn discriminator() -> [u8; 8] {
+        keeper_lease_discriminator()
+    }
+}
+
+/// One entropy deployment listed in a [`ProgramRegistry`].
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct RegisteredProgram {
+    pub program_id: PubkeyBytes,
+    /// `entropy_signer_pda(program_id)`, stored so consumers can match the
+    /// signer of a callback without deriving it.
+    pub entropy_signer: PubkeyBytes,
+}
+
+/// The official entropy program ids on this cluster, written by
+/// `SetProgramRegistry`. It lives in the entropy signer PDA, so a consumer
+/// that trusts one entropy program id can accept callbacks from every
+/// deployment that program lists.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ProgramRegistry {
+    pub discriminator: [u8; 8],
+    pub num_programs: u8,
+    pub bump: u8,
+    pub _padding0: [u8; 6],
+    pub programs: [RegisteredProgram; MAX_REGISTERED_PROGRAMS],
+}
+
+impl ProgramRegistry {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    /// The `num_programs` listed programs.
+    pub fn registered_programs(&self) -> &[RegisteredProgram] {
+        let len = usize::from(self.num_programs).min(MAX_REGISTERED_PROGRAMS);
+        &self.programs[..len]
+    }
+}
+
+impl Account for ProgramRegistry {
+    const LEN: usize = Self::LEN;
+
+    fn discriminator() -> [u8; 8] {
+        program_registry_discriminator()
+    }
+}
+
+/// The provider authorities that may call `RegisterProvider` while
+/// [`Config::permissioned_providers`] is set, written by
+/// `SetProviderAllowlist`.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ProviderAllowlist {
+    pub discriminator: [u8; 8],
+    pub num_providers: u8,
+    pub bump: u8,
+    pub _padding0: [u8; 6],
+    pub providers: [PubkeyBytes; MAX_ALLOWED_PROVIDERS],
+}
+
+impl ProviderAllowlist {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    /// The `num_providers` allowed provider authorities.
+    pub fn allowed_providers(&self) -> &[PubkeyBytes] {
+        let len = usize::from(self.num_providers).min(MAX_ALLOWED_PROVIDERS);
+        &self.providers[..len]
+    }
+
+    pub fn allows(&self, provider_authority: &PubkeyBytes) -> bool {
+        self.allowed_providers().contains(provider_authority)
+    }
+}
+
+impl Account for ProviderAllowlist {
+    const LEN: usize = Self::LEN;
+
+    fn discriminator() -> [u8; 8] {
+        provider_allowlist_discriminator()
+    }
+}
+
+/// One page of the provider directory, a PDA at
+/// [`provider_directory_pda`](crate::pda::provider_directory_pda) listing
+/// provider authorities so clients can enumerate providers. Pages fill one
+/// after the other; `RegisterProvider` creates each when its first provider
+/// is listed. `CloseProvider` moves the last listed authority into the
+/// closed one's place, so each authority is listed at most once.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ProviderDirectoryPage {
+    pub discriminator: [u8; 8],
+    pub page: u32,
+    pub num_providers: u8,
+    pub bump: u8,
+    pub _padding0: [u8; 2],
+    pub providers: [PubkeyBytes; PROVIDER_DIRECTORY_PAGE_LEN],
+}
+
+impl ProviderDirectoryPage {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    /// The `num_providers` provider authorities listed in this page.
+    pub fn listed_providers(&self) -> &[PubkeyBytes] {
+        let len = usize::from(self.num_providers).min(PROVIDER_DIRECTORY_PAGE_LEN);
+        &self.providers[..len]
+    }
+}
+
+impl Account for ProviderDirectoryPage {
+    const LEN: usize = Self::LEN;
+
+    fn discriminator() -> [u8; 8] {
+        provider_directory_page_discriminator()
+    }
+}
+
+// Account layouts are part of the program's ABI; changing a size breaks
+// existing accounts and off-chain decoders.
+const _: () = assert!(Config::LEN == 152);
+const _: () = assert!(Provider::LEN == 552);
+const _: () = assert!(CallbackMeta::LEN == 36);
+const _: () = assert!(Request::LEN == 1080);
+const _: () = assert!(GovernanceConfig::LEN == 96);
+const _: () = assert!(KeeperLease::LEN == 96);
+const _: () = assert!(core::mem::size_of::<AggregateContribution>() == 120);
+const _: () = assert!(AggregateRequest::LEN == 1480);
+const _: () = assert!(ProgramRegistry::LEN == 528);
+const _: () = assert!(ProviderAllowlist::LEN == 528);
+const _: () = assert!(ProviderDirectoryPage::LEN == 2064);
+const _: () = assert!(CallbackAccountList::LEN == 1200);
+const _: () = assert!(CallbackFailureStats::LEN == 112);
+
+// Accounts are read in place from account data, which the runtime aligns to
+// 8 bytes: a stricter alignment would make those reads fail, and a change
+// in alignment moves padding.
+const _: () = assert!(core::mem::align_of::<Config>() == 8);
+const _: () = assert!(core::mem::align_of::<Provider>() == 8);
+const _: () = assert!(core::mem::align_of::<CallbackMeta>() == 2);
+const _: () = assert!(core::mem::align_of::<Request>() == 8);
+const _: () = assert!(core::mem::align_of::<GovernanceConfig>() == 8);
+const _: () = assert!(core::mem::align_of::<KeeperLease>() == 8);
+const _: () = assert!(core::mem::align_of::<AggregateContribution>() == 8);
+const _: () = assert!(core::mem::align_of::<AggregateRequest>() == 8);
+const _: () = assert!(core::mem::align_of::<ProgramRegistry>() == 1);
+const _: () = assert!(core::mem::align_of::<ProviderAllowlist>() == 1);
+const _: () = assert!(core::mem::align_of::<ProviderDirectoryPage>() == 4);
+const _: () = assert!(core::mem::align_of::<CallbackAccountList>() == 2);
+const _: () = assert!(core::mem::align_of::<CallbackFailureStats>() == 8);
+
+// `load_account` checks the discriminator in the first 8 bytes.
+const _: () = assert!(core::mem::offset_of!(Config, discriminator) == 0);
+const _: () = assert!(core::mem::offset_of!(Provider, discriminator) == 0);
+const _: () = assert!(core::mem::offset_of!(Request, discriminator) == 0);
+const _: () = assert!(core::mem::offset_of!(GovernanceConfig, discriminator) == 0);
+const _: () = assert!(core::mem::offset_of!(KeeperLease, discriminator) == 0);
+const _: () = assert!(core::mem::offset_of!(AggregateRequest, discriminator) == 0);
+const _: () = assert!(core::mem::offset_of!(ProgramRegistry, discriminator) == 0);
+const _: () = assert!(core::mem::offset_of!(ProviderAllowlist, discriminator) == 0);
+const _: () = assert!(core::mem::offset_of!(ProviderDirectoryPage, discriminator) == 0);
+const _: () = assert!(core::mem::offset_of!(CallbackAccountList, discriminator) == 0);
+const _: () = assert!(core::mem::offset_of!(CallbackFailureStats, discriminator) == 0);
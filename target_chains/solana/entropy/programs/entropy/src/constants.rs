@@ -1,11 +1,50 @@
+/// Version of the instruction set this program implements, returned by
+/// `GetVersion` and recorded in the config at initialization. Bumped when
+/// an instruction is added, changes its accounts or data, or a stubbed one
+/// starts working; deployments that predate it reject `GetVersion` and count
+/// as version 0.
+pub const PROGRAM_VERSION: u8 = 7;
+
 /// Fixed-size buffer length for provider commitment metadata.
 pub const COMMITMENT_METADATA_LEN: usize = 64;
 /// Fixed-size buffer length for provider URIs.
 pub const URI_LEN: usize = 256;
 /// Maximum number of callback accounts stored in a request.
 pub const MAX_CALLBACK_ACCOUNTS: usize = 16;
+/// Maximum number of additional callback accounts a request's
+/// [`CallbackAccountList`](crate::accounts::CallbackAccountList) holds.
+pub const MAX_CALLBACK_ACCOUNT_LIST_LEN: usize = 32;
 /// Fixed-size buffer length for callback instruction data.
 pub const CALLBACK_IX_DATA_LEN: usize = 256;
+/// Maximum number of providers contributing to an aggregate request.
+pub const MAX_AGGREGATE_PROVIDERS: usize = 4;
+/// Maximum number of entropy program ids listed in a program registry.
+pub const MAX_REGISTERED_PROGRAMS: usize = 8;
+/// Maximum number of provider authorities in the provider allowlist.
+pub const MAX_ALLOWED_PROVIDERS: usize = 16;
+/// Provider authorities listed per provider directory page.
+pub const PROVIDER_DIRECTORY_PAGE_LEN: usize = 64;
+
+/// Compute units a transaction may use at most.
+pub const MAX_TRANSACTION_COMPUTE_UNITS: u32 = 1_400_000;
+/// Compute units one step of a reveal's hash chain costs, rounded up: the
+/// sha256 syscall on 32 bytes is 85 base plus 16 for the bytes, and the loop
+/// around it adds a few more. The reveal cases of `tests/test_compute_units.rs`
+/// measure it as the slope between their hash counts.
+pub const COMPUTE_UNITS_PER_HASH: u32 = 120;
+/// Compute units left for the rest of a reveal, callback included, when it
+/// verifies the longest hash chain allowed.
+pub const REVEAL_RESERVED_COMPUTE_UNITS: u32 = 200_000;
+/// Longest hash chain a reveal may have to verify, so that every request
+/// stays revealable within one transaction. Applies on top of the provider's
+/// own `max_num_hashes`, and only when a request is made: a provider that
+/// drifts past it recovers by re-registering with a fresh chain.
+pub const MAX_NUM_HASHES: u32 =
+    (MAX_TRANSACTION_COMPUTE_UNITS - REVEAL_RESERVED_COMPUTE_UNITS) / COMPUTE_UNITS_PER_HASH;
+/// Lowest non-zero `max_failures` a callback skip policy may set. Failure
+/// reports come from the provider, so a single one must never be enough to
+/// skip a requester program's callbacks.
+pub const MIN_CALLBACK_SKIP_MAX_FAILURES: u16 = 3;
 
 /// Seed for the config PDA.
 pub const CONFIG_SEED: &[u8] = b"config";
@@ -21,8 +60,35 @@ pub const PYTH_FEE_VAULT_SEED: &[u8] = b"pyth_fee_vault";
 pub const ENTROPY_SIGNER_SEED: &[u8] = b"entropy_signer";
 /// Seed for the requester signer PDA (owned by requester program).
 pub const REQUESTER_SIGNER_SEED: &[u8] = b"requester_signer";
+/// Seed for the governance PDA.
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+/// Seed for the keeper lease PDA.
+pub const KEEPER_LEASE_SEED: &[u8] = b"keeper_lease";
+/// Seed for the provider allowlist PDA.
+pub const PROVIDER_ALLOWLIST_SEED: &[u8] = b"provider_allowlist";
+/// Seed for the provider directory page PDAs, followed by the page index.
+pub const PROVIDER_DIRECTORY_SEED: &[u8] = b"provider_directory";
+/// Seed for the callback account list PDAs, followed by the request account.
+pub const CALLBACK_ACCOUNT_LIST_SEED: &[u8] = b"callback_account_list";
+/// Seed for the callback failure stats PDAs, followed by the provider
+/// authority and the requester program.
+pub const CALLBACK_FAILURES_SEED: &[u8] = b"callback_failures";
+/// Domain tag of the `RequestV2` config seed evolution.
+pub const USER_RANDOMNESS_DOMAIN: &[u8] = b"entropy:request_v2:user_randomness";
+/// Domain tag of version 1 of the random number derivation.
+pub const RANDOM_NUMBER_DOMAIN: &[u8] = b"entropy:random_number:v1";
+
+/// Random number derivations a request can ask for; see
+/// [`combine_random_values`](crate::randomness::combine_random_values).
+pub const RANDOM_NUMBER_V0: u8 = 0;
+pub const RANDOM_NUMBER_V1: u8 = 1;
 
-/// Callback status constants (mirror EntropyStatusConstants).
-pub const CALLBACK_NOT_NECESSARY: u8 = 0;
-pub const CALLBACK_NOT_STARTED: u8 = 1;
-pub const CALLBACK_IN_PROGRESS: u8 = 2;
+/// Wormhole chain id of Solana. Governance messages must target it or 0
+/// (every chain).
+pub const WORMHOLE_CHAIN_ID_SOLANA: u16 = 1;
+/// Magic prefix of Pyth governance messages.
+pub const GOVERNANCE_MAGIC: [u8; 4] = *b"PTGM";
+/// Governance module of the entropy actions, next to Pyth's executor (0),
+/// target (1), EVM executor (2) and Lazer (3) modules. A module of its own
+/// keeps messages for the Solana price receiver from executing here.
+pub const GOVERNANCE_MODULE_ENTROPY: u8 = 4;
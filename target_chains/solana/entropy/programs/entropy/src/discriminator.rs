@@ -15,3 +15,35 @@ pub fn provider_discriminator() -> [u8; 8] {
 pub fn request_discriminator() -> [u8; 8] {
     account_discriminator(2)
 }
+
+pub fn governance_config_discriminator() -> [u8; 8] {
+    account_discriminator(3)
+}
+
+pub fn keeper_lease_discriminator() -> [u8; 8] {
+    account_discriminator(4)
+}
+
+pub fn aggregate_request_discriminator() -> [u8; 8] {
+    account_discriminator(5)
+}
+
+pub fn program_registry_discriminator() -> [u8; 8] {
+    account_discriminator(6)
+}
+
+pub fn provider_allowlist_discriminator() -> [u8; 8] {
+    account_discriminator(7)
+}
+
+pub fn provider_directory_page_discriminator() -> [u8; 8] {
+    account_discriminator(8)
+}
+
+pub fn callback_account_list_discriminator() -> [u8; 8] {
+    account_discriminator(9)
+}
+
+pub fn callback_failure_stats_discriminator() -> [u8; 8] {
+    account_discriminator(10)
+}
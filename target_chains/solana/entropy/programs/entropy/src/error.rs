@@ -5,8 +5,11 @@ use thiserror::Error;
 pub enum EntropyError {
     #[error("invalid instruction")]
     InvalidInstruction = 0,
+    /// Superseded by the specific account errors from 19 on; kept so the
+    /// codes that follow stay stable.
     #[error("invalid account")]
     InvalidAccount = 1,
+    /// Superseded by the specific `Wrong*` errors; see `InvalidAccount`.
     #[error("invalid PDA")]
     InvalidPda = 2,
     #[error("not implemented")]
@@ -23,6 +26,112 @@ pub enum EntropyError {
     InvalidRevealCall = 8,
     #[error("insufficient gas")]
     InsufficientGas = 9,
+    #[error("unauthorized")]
+    Unauthorized = 10,
+    #[error("invalid VAA")]
+    InvalidVaa = 11,
+    #[error("invalid governance data source")]
+    InvalidGovernanceDataSource = 12,
+    #[error("old governance message")]
+    OldGovernanceMessage = 13,
+    #[error("invalid governance message")]
+    InvalidGovernanceMessage = 14,
+    #[error("invalid governance target")]
+    InvalidGovernanceTarget = 15,
+    #[error("request expired")]
+    RequestExpired = 16,
+    #[error("request not expired")]
+    RequestNotExpired = 17,
+    #[error("provider has outstanding requests")]
+    ProviderHasOutstandingRequests = 18,
+    #[error("account is not writable")]
+    AccountNotWritable = 19,
+    #[error("wrong system program")]
+    WrongSystemProgram = 20,
+    #[error("wrong config account")]
+    WrongConfig = 21,
+    #[error("wrong Pyth fee vault")]
+    WrongPythFeeVault = 22,
+    #[error("wrong provider account")]
+    WrongProvider = 23,
+    #[error("wrong provider vault")]
+    WrongProviderVault = 24,
+    #[error("wrong requester signer")]
+    WrongRequesterSigner = 25,
+    #[error("wrong entropy signer")]
+    WrongEntropySigner = 26,
+    #[error("wrong governance account")]
+    WrongGovernance = 27,
+    #[error("wrong slot hashes sysvar")]
+    WrongSlotHashes = 28,
+    #[error("wrong program data account")]
+    WrongProgramData = 29,
+    #[error("malformed program data account")]
+    InvalidProgramData = 30,
+    #[error("account is not owned by entropy")]
+    WrongAccountOwner = 31,
+    #[error("account has the wrong size")]
+    WrongAccountSize = 32,
+    #[error("account has the wrong discriminator")]
+    WrongAccountType = 33,
+    #[error("account is already initialized")]
+    AccountAlreadyInitialized = 34,
+    #[error("vault is not a data-less system account")]
+    InvalidVault = 35,
+    #[error("request account is not empty")]
+    RequestAccountNotEmpty = 36,
+    #[error("provider account belongs to another authority")]
+    ProviderAuthorityMismatch = 37,
+    #[error("payer does not match the request")]
+    WrongPayer = 38,
+    #[error("callback program does not match the request")]
+    WrongCallbackProgram = 39,
+    #[error("callback accounts do not match the request")]
+    CallbackAccountMismatch = 40,
+    #[error("callback accounts include the entropy program")]
+    EntropyInCallbackAccounts = 41,
+    #[error("too many callback accounts")]
+    TooManyCallbackAccounts = 42,
+    #[error("callback instruction data too long")]
+    CallbackDataTooLong = 43,
+    #[error("fee transfer failed")]
+    FeeTransferFailed = 44,
+    #[error("recipient does not match the governance target")]
+    WrongRecipient = 45,
+    #[error("keeper lease is held by another keeper")]
+    KeeperLeaseHeld = 46,
+    #[error("keeper lease account does not match the provider")]
+    WrongKeeperLease = 47,
+    #[error("aggregate threshold must be between 1 and the number of providers")]
+    InvalidAggregateThreshold = 48,
+    #[error("provider appears twice in an aggregate request")]
+    DuplicateAggregateProvider = 49,
+    #[error("request cannot be revealed before its minimum reveal delay")]
+    RevealTooEarly = 50,
+    #[error("program registry lists a zero or duplicate program id")]
+    InvalidProgramRegistry = 51,
+    #[error("provider uri is not UTF-8, contains a NUL byte or is not https")]
+    InvalidProviderUri = 52,
+    #[error("provider authority is not in the provider allowlist")]
+    ProviderNotAllowed = 53,
+    #[error("provider allowlist lists a zero or duplicate provider")]
+    InvalidProviderAllowlist = 54,
+    #[error("wrong provider allowlist account")]
+    WrongProviderAllowlist = 55,
+    #[error("config is already initialized with different parameters")]
+    ConfigMismatch = 56,
+    #[error("provider directory page is not the one the next provider is listed in")]
+    WrongProviderDirectory = 57,
+    #[error("callback account list does not belong to the request")]
+    WrongCallbackAccountList = 58,
+    #[error("callback skip policy has no window or too low a failure limit")]
+    InvalidCallbackSkipPolicy = 59,
+    #[error("callback failure stats account does not match the provider and requester program")]
+    WrongCallbackFailureStats = 60,
+    #[error("requester program has not failed enough callbacks to skip them")]
+    CallbackSkipNotAllowed = 61,
+    #[error("provider vault holds less than the fee to refund")]
+    ProviderVaultShort = 62,
 }
 
 impl From<EntropyError> for solana_program::program_error::ProgramError {
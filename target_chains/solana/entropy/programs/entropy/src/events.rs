@@ -0,0 +1,216 @@
+use bytemuck::{bytes_of, Pod, Zeroable};
+use solana_program::log::sol_log_data;
+
+use crate::accounts::PubkeyBytes;
+
+/// Events are logged with `sol_log_data` as two fields: an 8-byte
+/// discriminator followed by the Pod-encoded event body.
+pub trait Event: Pod {
+    fn discriminator() -> [u8; 8];
+}
+
+fn event_discriminator(ordinal: u64) -> [u8; 8] {
+    ordinal.to_le_bytes()
+}
+
+pub fn request_created_discriminator() -> [u8; 8] {
+    event_discriminator(0)
+}
+
+pub fn randomness_revealed_discriminator() -> [u8; 8] {
+    event_discriminator(1)
+}
+
+pub fn pyth_fee_set_discriminator() -> [u8; 8] {
+    event_discriminator(2)
+}
+
+pub fn default_provider_set_discriminator() -> [u8; 8] {
+    event_discriminator(3)
+}
+
+pub fn user_randomness_generated_discriminator() -> [u8; 8] {
+    event_discriminator(4)
+}
+
+pub fn aggregate_request_created_discriminator() -> [u8; 8] {
+    event_discriminator(5)
+}
+
+pub fn aggregate_randomness_revealed_discriminator() -> [u8; 8] {
+    event_discriminator(6)
+}
+
+pub fn callback_invoked_discriminator() -> [u8; 8] {
+    event_discriminator(7)
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct RequestCreated {
+    pub provider: PubkeyBytes,
+    pub requester_program_id: PubkeyBytes,
+    pub request_account: PubkeyBytes,
+    pub payer: PubkeyBytes,
+    pub sequence_number: u64,
+    pub request_slot: u64,
+    pub compute_unit_limit: u32,
+    pub use_blockhash: u8,
+    /// A [`CallbackStatus`](crate::accounts::CallbackStatus).
+    pub callback_status: u8,
+    pub random_number_version: u8,
+    pub _padding0: [u8; 1],
+}
+
+impl Event for RequestCreated {
+    fn discriminator() -> [u8; 8] {
+        request_created_discriminator()
+    }
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct RandomnessRevealed {
+    pub provider: PubkeyBytes,
+    pub requester_program_id: PubkeyBytes,
+    pub request_account: PubkeyBytes,
+    pub sequence_number: u64,
+    pub random_number: [u8; 32],
+    pub callback_invoked: u8,
+    pub _padding0: [u8; 7],
+    pub request_slot: u64,
+    pub reveal_slot: u64,
+}
+
+impl Event for RandomnessRevealed {
+    fn discriminator() -> [u8; 8] {
+        randomness_revealed_discriminator()
+    }
+}
+
+/// Mirrors `PythFeeSet`: emitted whenever the admin or governance changes
+/// the Pyth fee.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct PythFeeSet {
+    pub old_fee_lamports: u64,
+    pub new_fee_lamports: u64,
+}
+
+impl Event for PythFeeSet {
+    fn discriminator() -> [u8; 8] {
+        pyth_fee_set_discriminator()
+    }
+}
+
+/// Mirrors `DefaultProviderSet`.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct DefaultProviderSet {
+    pub old_default_provider: PubkeyBytes,
+    pub new_default_provider: PubkeyBytes,
+}
+
+impl Event for DefaultProviderSet {
+    fn discriminator() -> [u8; 8] {
+        default_provider_set_discriminator()
+    }
+}
+
+/// Emitted by `RequestV2` next to `RequestCreated`: the user randomness
+/// entropy generated for the request, which the keeper needs to reveal it.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct UserRandomnessGenerated {
+    pub provider: PubkeyBytes,
+    pub request_account: PubkeyBytes,
+    pub sequence_number: u64,
+    pub user_randomness: [u8; 32],
+}
+
+impl Event for UserRandomnessGenerated {
+    fn discriminator() -> [u8; 8] {
+        user_randomness_generated_discriminator()
+    }
+}
+
+/// Emitted by `RequestAggregate`. The providers and their sequence numbers
+/// are in the request account.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct AggregateRequestCreated {
+    pub requester_program_id: PubkeyBytes,
+    pub request_account: PubkeyBytes,
+    pub payer: PubkeyBytes,
+    pub request_slot: u64,
+    pub num_providers: u8,
+    pub threshold: u8,
+    pub _padding0: [u8; 6],
+}
+
+impl Event for AggregateRequestCreated {
+    fn discriminator() -> [u8; 8] {
+        aggregate_request_created_discriminator()
+    }
+}
+
+/// Emitted by the `RevealAggregate` that reaches the threshold.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct AggregateRandomnessRevealed {
+    pub requester_program_id: PubkeyBytes,
+    pub request_account: PubkeyBytes,
+    pub random_number: [u8; 32],
+    pub request_slot: u64,
+    pub reveal_slot: u64,
+    /// Bit `i` is set if provider `i`'s contribution went into the random
+    /// number.
+    pub revealed_mask: u8,
+    pub callback_invoked: u8,
+    pub _padding0: [u8; 6],
+}
+
+impl Event for AggregateRandomnessRevealed {
+    fn discriminator() -> [u8; 8] {
+        aggregate_randomness_revealed_discriminator()
+    }
+}
+
+/// Emitted by a reveal right before it invokes the callback: the request as
+/// the callback sees it. A failing callback aborts the reveal, so there is no
+/// failed event; this one stays in the failed transaction's logs next to the
+/// runtime's lines for the callback, which name its error and compute units.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct CallbackInvoked {
+    /// For aggregate requests, the first provider, which the callback is
+    /// given.
+    pub provider: PubkeyBytes,
+    pub requester_program_id: PubkeyBytes,
+    pub request_account: PubkeyBytes,
+    pub sequence_number: u64,
+    pub request_slot: u64,
+    pub reveal_slot: u64,
+    pub compute_unit_limit: u32,
+    pub _padding0: [u8; 4],
+}
+
+impl Event for CallbackInvoked {
+    fn discriminator() -> [u8; 8] {
+        callback_invoked_discriminator()
+    }
+}
+
+pub fn emit_event<T: Event>(event: &T) {
+    sol_log_data(&[&T::discriminator(), bytes_of(event)]);
+}
+
+// Event layouts are decoded off-chain; keep their sizes stable.
+const _: () = assert!(core::mem::size_of::<RequestCreated>() == 152);
+const _: () = assert!(core::mem::size_of::<RandomnessRevealed>() == 160);
+const _: () = assert!(core::mem::size_of::<PythFeeSet>() == 16);
+const _: () = assert!(core::mem::size_of::<DefaultProviderSet>() == 64);
+const _: () = assert!(core::mem::size_of::<UserRandomnessGenerated>() == 104);
+const _: () = assert!(core::mem::size_of::<AggregateRequestCreated>() == 112);
+const _: () = assert!(core::mem::size_of::<AggregateRandomnessRevealed>() == 120);
+const _: () = assert!(core::mem::size_of::<CallbackInvoked>() == 128);
@@ -0,0 +1,237 @@
+//! Wormhole governance: the posted VAA accounts of the core bridge and the
+//! Pyth governance messages they carry.
+//!
+//! A message is the Pyth governance header (`"PTGM"`, module, action, target
+//! chain as u16 BE) followed by the action's fields, all big-endian like the
+//! rest of Pyth's governance payloads. Fees are given as `value * 10^expo`
+//! lamports, mirroring the target module's `SetFee` and `WithdrawFee`.
+
+use solana_program::program_error::ProgramError;
+
+use crate::{
+    accounts::PubkeyBytes,
+    constants::{GOVERNANCE_MAGIC, GOVERNANCE_MODULE_ENTROPY},
+    error::EntropyError,
+};
+
+pub const ACTION_SET_FEE: u8 = 0;
+pub const ACTION_SET_ADMIN: u8 = 1;
+pub const ACTION_SET_DEFAULT_PROVIDER: u8 = 2;
+pub const ACTION_WITHDRAW_FEE: u8 = 3;
+pub const ACTION_SET_PERMISSIONED_PROVIDERS: u8 = 4;
+
+/// Magic prefix of the core bridge's `PostedVAAV1` accounts.
+pub const POSTED_VAA_MAGIC: [u8; 3] = *b"vaa";
+
+// `PostedVAAV1` is Borsh: magic, version (u8), consistency level (u8), VAA
+// time (u32), signature set (32), submission time (u32), nonce (u32),
+// sequence (u64), emitter chain (u16), emitter address (32), payload (u32
+// length + bytes).
+const POSTED_VAA_SEQUENCE_OFFSET: usize = 49;
+const POSTED_VAA_EMITTER_CHAIN_OFFSET: usize = 57;
+const POSTED_VAA_EMITTER_ADDRESS_OFFSET: usize = 59;
+const POSTED_VAA_PAYLOAD_OFFSET: usize = 91;
+
+/// The fields entropy reads from a posted VAA account.
+pub struct PostedVaa<'a> {
+    pub sequence: u64,
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub payload: &'a [u8],
+}
+
+fn read_array<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..offset.checked_add(N)?)?.try_into().ok()
+}
+
+/// Parses a `PostedVAAV1` account. The caller checks the account is owned by
+/// the Wormhole core bridge, which only writes verified VAAs.
+pub fn parse_posted_vaa(data: &[u8]) -> Result<PostedVaa<'_>, ProgramError> {
+    let invalid = || ProgramError::from(EntropyError::InvalidVaa);
+    if read_array::<3>(data, 0) != Some(POSTED_VAA_MAGIC) {
+        return Err(invalid());
+    }
+    let sequence = read_array(data, POSTED_VAA_SEQUENCE_OFFSET)
+        .map(u64::from_le_bytes)
+        .ok_or_else(invalid)?;
+    let emitter_chain = read_array(data, POSTED_VAA_EMITTER_CHAIN_OFFSET)
+        .map(u16::from_le_bytes)
+        .ok_or_else(invalid)?;
+    let emitter_address =
+        read_array(data, POSTED_VAA_EMITTER_ADDRESS_OFFSET).ok_or_else(invalid)?;
+    let payload_len = read_array(data, POSTED_VAA_PAYLOAD_OFFSET)
+        .map(u32::from_le_bytes)
+        .ok_or_else(invalid)? as usize;
+    let payload_start = POSTED_VAA_PAYLOAD_OFFSET + 4;
+    let payload = data
+        .get(payload_start..payload_start.saturating_add(payload_len))
+        .ok_or_else(invalid)?;
+
+    Ok(PostedVaa {
+        sequence,
+        emitter_chain,
+        emitter_address,
+        payload,
+    })
+}
+
+/// Serializes a `PostedVAAV1` account with zeroed metadata, for tests and
+/// local validators that stand in for the core bridge.
+pub fn serialize_posted_vaa(
+    sequence: u64,
+    emitter_chain: u16,
+    emitter_address: [u8; 32],
+    payload: &[u8],
+) -> Vec<u8> {
+    let mut data = vec![0u8; POSTED_VAA_PAYLOAD_OFFSET];
+    data[..3].copy_from_slice(&POSTED_VAA_MAGIC);
+    data[POSTED_VAA_SEQUENCE_OFFSET..POSTED_VAA_EMITTER_CHAIN_OFFSET]
+        .copy_from_slice(&sequence.to_le_bytes());
+    data[POSTED_VAA_EMITTER_CHAIN_OFFSET..POSTED_VAA_EMITTER_ADDRESS_OFFSET]
+        .copy_from_slice(&emitter_chain.to_le_bytes());
+    data[POSTED_VAA_EMITTER_ADDRESS_OFFSET..POSTED_VAA_PAYLOAD_OFFSET]
+        .copy_from_slice(&emitter_address);
+    data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    data.extend_from_slice(payload);
+    data
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GovernanceAction {
+    /// Sets the Pyth fee to `value * 10^expo` lamports.
+    SetFee {
+        value: u64,
+        expo: u64,
+    },
+    SetAdmin {
+        new_admin: PubkeyBytes,
+    },
+    SetDefaultProvider {
+        provider: PubkeyBytes,
+    },
+    /// Moves `value * 10^expo` lamports from the Pyth fee vault to `target`.
+    WithdrawFee {
+        target: PubkeyBytes,
+        value: u64,
+        expo: u64,
+    },
+    /// Restricts `RegisterProvider` to the provider allowlist, or lifts the
+    /// restriction.
+    SetPermissionedProviders {
+        permissioned: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GovernanceMessage {
+    pub target_chain_id: u16,
+    pub action: GovernanceAction,
+}
+
+/// `value * 10^expo`, or `None` on overflow.
+pub fn scaled_amount(value: u64, expo: u64) -> Option<u64> {
+    value.checked_mul(10u64.checked_pow(u32::try_from(expo).ok()?)?)
+}
+
+impl GovernanceMessage {
+    pub fn parse(payload: &[u8]) -> Result<Self, ProgramError> {
+        let invalid = || ProgramError::from(EntropyError::InvalidGovernanceMessage);
+        if read_array::<4>(payload, 0) != Some(GOVERNANCE_MAGIC)
+            || payload.get(4) != Some(&GOVERNANCE_MODULE_ENTROPY)
+        {
+            return Err(invalid());
+        }
+        let action = *payload.get(5).ok_or_else(invalid)?;
+        let target_chain_id = read_array(payload, 6)
+            .map(u16::from_be_bytes)
+            .ok_or_else(invalid)?;
+        let body = &payload[8..];
+        let u64_at = |offset| {
+            read_array(body, offset)
+                .map(u64::from_be_bytes)
+                .ok_or_else(invalid)
+        };
+
+        let (action, body_len) = match action {
+            ACTION_SET_FEE => (
+                GovernanceAction::SetFee {
+                    value: u64_at(0)?,
+                    expo: u64_at(8)?,
+                },
+                16,
+            ),
+            ACTION_SET_ADMIN => (
+                GovernanceAction::SetAdmin {
+                    new_admin: read_array(body, 0).ok_or_else(invalid)?,
+                },
+                32,
+            ),
+            ACTION_SET_DEFAULT_PROVIDER => (
+                GovernanceAction::SetDefaultProvider {
+                    provider: read_array(body, 0).ok_or_else(invalid)?,
+                },
+                32,
+            ),
+            ACTION_WITHDRAW_FEE => (
+                GovernanceAction::WithdrawFee {
+                    target: read_array(body, 0).ok_or_else(invalid)?,
+                    value: u64_at(32)?,
+                    expo: u64_at(40)?,
+                },
+                48,
+            ),
+            ACTION_SET_PERMISSIONED_PROVIDERS => (
+                GovernanceAction::SetPermissionedProviders {
+                    permissioned: match body.first() {
+                        Some(0) => false,
+                        Some(1) => true,
+                        _ => return Err(invalid()),
+                    },
+                },
+                1,
+            ),
+            _ => return Err(invalid()),
+        };
+        if body.len() != body_len {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            target_chain_id,
+            action,
+        })
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let (action, body) = match self.action {
+            GovernanceAction::SetFee { value, expo } => (
+                ACTION_SET_FEE,
+                [value.to_be_bytes(), expo.to_be_bytes()].concat(),
+            ),
+            GovernanceAction::SetAdmin { new_admin } => (ACTION_SET_ADMIN, new_admin.to_vec()),
+            GovernanceAction::SetDefaultProvider { provider } => {
+                (ACTION_SET_DEFAULT_PROVIDER, provider.to_vec())
+            }
+            GovernanceAction::WithdrawFee {
+                target,
+                value,
+                expo,
+            } => (
+                ACTION_WITHDRAW_FEE,
+                [&target[..], &value.to_be_bytes(), &expo.to_be_bytes()].concat(),
+            ),
+            GovernanceAction::SetPermissionedProviders { permissioned } => (
+                ACTION_SET_PERMISSIONED_PROVIDERS,
+                vec![u8::from(permissioned)],
+            ),
+        };
+
+        let mut payload = Vec::with_capacity(8 + body.len());
+        payload.extend_from_slice(&GOVERNANCE_MAGIC);
+        payload.push(GOVERNANCE_MODULE_ENTROPY);
+        payload.push(action);
+        payload.extend_from_slice(&self.target_chain_id.to_be_bytes());
+        payload.extend_from_slice(&body);
+        payload
+    }
+}
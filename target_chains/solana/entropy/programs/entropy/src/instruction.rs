@@ -1,11 +1,16 @@
-use bytemuck::{Pod, Zeroable};
+use bytemuck::{bytes_of, cast_slice, try_cast_slice, try_from_bytes, Pod, Zeroable};
+use solana_program::program_error::ProgramError;
 
 use crate::{
-    accounts::PubkeyBytes,
-    constants::{COMMITMENT_METADATA_LEN, URI_LEN},
+    accounts::{CallbackMeta, PubkeyBytes},
+    constants::{
+        COMMITMENT_METADATA_LEN, MAX_ALLOWED_PROVIDERS, MAX_CALLBACK_ACCOUNTS,
+        MAX_REGISTERED_PROGRAMS, URI_LEN,
+    },
 };
 
 #[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum EntropyInstruction {
     Initialize = 0,
     RegisterProvider = 1,
@@ -17,6 +22,55 @@ pub enum EntropyInstruction {
     UpdateProviderConfig = 7,
     WithdrawProviderFees = 8,
     Governance = 9,
+    InitializeGovernance = 10,
+    ClaimAdmin = 11,
+    SetPythFee = 12,
+    SetDefaultProvider = 13,
+    CloseExpiredRequest = 14,
+    CloseProvider = 15,
+    /// `RequestWithCallback` with the user randomness generated by entropy.
+    RequestV2 = 16,
+    /// Claims or renews the provider's [`KeeperLease`](crate::accounts::KeeperLease).
+    RefreshKeeperLease = 17,
+    /// Callback request answered by several providers; see
+    /// [`AggregateRequest`](crate::accounts::AggregateRequest).
+    RequestAggregate = 18,
+    /// Any `threshold` of the providers can finalize the request, so a
+    /// provider that has seen the others' reveals may withhold its own to
+    /// drop its contribution; the result is only unpredictable while fewer
+    /// than `threshold` providers collude.
+    RevealAggregate = 19,
+    CloseExpiredAggregateRequest = 20,
+    /// Replaces the [`ProgramRegistry`](crate::accounts::ProgramRegistry)
+    /// kept in the entropy signer PDA.
+    SetProgramRegistry = 21,
+    /// Replaces the [`ProviderAllowlist`](crate::accounts::ProviderAllowlist)
+    /// and sets whether `RegisterProvider` is restricted to it.
+    SetProviderAllowlist = 22,
+    /// Sets the return data to [`PROGRAM_VERSION`](crate::constants::PROGRAM_VERSION).
+    GetVersion = 23,
+    /// `Initialize` that succeeds without changes when the config already
+    /// holds the same arguments, for tooling that bootstraps deployments.
+    InitializeIfNeeded = 24,
+    /// Sets the return data to the [`RequestPreview`] of a request to the
+    /// provider, without changing anything; meant to be simulated.
+    PreviewRequest = 25,
+    /// Appends accounts to the
+    /// [`CallbackAccountList`](crate::accounts::CallbackAccountList) of a
+    /// request not made yet, creating it on first use.
+    WriteCallbackAccountList = 26,
+    /// Closes the callback account list of a request not made yet.
+    CloseCallbackAccountList = 27,
+    /// Sets when the provider may skip the callbacks of requester programs
+    /// that keep failing them.
+    SetCallbackSkipPolicy = 28,
+    /// Counts a failed callback of a pending request in the provider's
+    /// [`CallbackFailureStats`](crate::accounts::CallbackFailureStats) for
+    /// its requester program, creating them on first use.
+    ReportCallbackFailure = 29,
+    /// `RevealWithCallback` without the callback, for requester programs
+    /// the provider's skip policy allows it for; refunds the provider fee.
+    RevealSkippingCallback = 30,
 }
 
 pub const INSTRUCTION_DISCRIMINATOR_LEN: usize = 8;
@@ -26,11 +80,51 @@ impl EntropyInstruction {
         (self as u64).to_le_bytes()
     }
 
-    pub fn parse(
-        input: &[u8],
-    ) -> Result<(EntropyInstruction, &[u8]), solana_program::program_error::ProgramError> {
+    /// Instruction data for the instruction `args` belong to.
+    /// [`EntropyInstruction::parse`] followed by [`parse_args`] reads it
+    /// back.
+    pub fn build<A: InstructionArgs>(args: &A) -> Vec<u8> {
+        let payload = bytes_of(args);
+        let mut data = Vec::with_capacity(INSTRUCTION_DISCRIMINATOR_LEN + payload.len());
+        data.extend_from_slice(&A::INSTRUCTION.discriminator());
+        data.extend_from_slice(payload);
+        data
+    }
+
+    /// Instruction data for the callback request `header` belongs to: the
+    /// header with `requester_signer_bump` and the number of callback
+    /// accounts filled in, the callback accounts, a u32 LE callback data
+    /// length and the callback data. Read back by
+    /// [`parse_request_with_callback_args`], [`parse_request_v2_args`] or
+    /// [`parse_request_aggregate_args`]; the lengths are checked there, not
+    /// here.
+    pub fn build_callback_request<H: CallbackRequestHeader>(
+        header: &H,
+        requester_signer_bump: u8,
+        callback_accounts: &[CallbackMeta],
+        callback_ix_data: &[u8],
+    ) -> Vec<u8> {
+        let mut header = *header;
+        header.set_layout(requester_signer_bump, callback_accounts.len() as u32);
+        let header = bytes_of(&header);
+        let mut data = Vec::with_capacity(
+            INSTRUCTION_DISCRIMINATOR_LEN
+                + header.len()
+                + callback_accounts.len() * CallbackMeta::LEN
+                + 4
+                + callback_ix_data.len(),
+        );
+        data.extend_from_slice(&H::INSTRUCTION.discriminator());
+        data.extend_from_slice(header);
+        data.extend_from_slice(cast_slice(callback_accounts));
+        data.extend_from_slice(&(callback_ix_data.len() as u32).to_le_bytes());
+        data.extend_from_slice(callback_ix_data);
+        data
+    }
+
+    pub fn parse(input: &[u8]) -> Result<(EntropyInstruction, &[u8]), ProgramError> {
         if input.len() < INSTRUCTION_DISCRIMINATOR_LEN {
-            return Err(solana_program::program_error::ProgramError::InvalidInstructionData);
+            return Err(ProgramError::InvalidInstructionData);
         }
         let mut discriminator_bytes = [0u8; INSTRUCTION_DISCRIMINATOR_LEN];
         discriminator_bytes.copy_from_slice(&input[..INSTRUCTION_DISCRIMINATOR_LEN]);
@@ -47,12 +141,134 @@ impl EntropyInstruction {
             7 => EntropyInstruction::UpdateProviderConfig,
             8 => EntropyInstruction::WithdrawProviderFees,
             9 => EntropyInstruction::Governance,
-            _ => return Err(solana_program::program_error::ProgramError::InvalidInstructionData),
+            10 => EntropyInstruction::InitializeGovernance,
+            11 => EntropyInstruction::ClaimAdmin,
+            12 => EntropyInstruction::SetPythFee,
+            13 => EntropyInstruction::SetDefaultProvider,
+            14 => EntropyInstruction::CloseExpiredRequest,
+            15 => EntropyInstruction::CloseProvider,
+            16 => EntropyInstruction::RequestV2,
+            17 => EntropyInstruction::RefreshKeeperLease,
+            18 => EntropyInstruction::RequestAggregate,
+            19 => EntropyInstruction::RevealAggregate,
+            20 => EntropyInstruction::CloseExpiredAggregateRequest,
+            21 => EntropyInstruction::SetProgramRegistry,
+            22 => EntropyInstruction::SetProviderAllowlist,
+            23 => EntropyInstruction::GetVersion,
+            24 => EntropyInstruction::InitializeIfNeeded,
+            25 => EntropyInstruction::PreviewRequest,
+            26 => EntropyInstruction::WriteCallbackAccountList,
+            27 => EntropyInstruction::CloseCallbackAccountList,
+            28 => EntropyInstruction::SetCallbackSkipPolicy,
+            29 => EntropyInstruction::ReportCallbackFailure,
+            30 => EntropyInstruction::RevealSkippingCallback,
+            _ => return Err(ProgramError::InvalidInstructionData),
         };
         Ok((instruction, payload))
     }
 }
 
+/// Fixed-size args of one instruction, which they determine: data built
+/// with [`EntropyInstruction::build`] always carries the instruction that
+/// parses them.
+pub trait InstructionArgs: Pod {
+    const INSTRUCTION: EntropyInstruction;
+}
+
+/// Fixed-size header of a callback request, which determines the
+/// instruction; see [`EntropyInstruction::build_callback_request`].
+pub trait CallbackRequestHeader: Pod {
+    const INSTRUCTION: EntropyInstruction;
+
+    /// Sets the fields the builder fills in: the requester signer's bump
+    /// and the number of callback accounts that follow the header.
+    fn set_layout(&mut self, requester_signer_bump: u8, callback_accounts_len: u32);
+}
+
+macro_rules! instruction_args {
+    ($($args:ty => $instruction:ident),* $(,)?) => {
+        $(
+            impl InstructionArgs for $args {
+                const INSTRUCTION: EntropyInstruction = EntropyInstruction::$instruction;
+            }
+        )*
+    };
+}
+
+macro_rules! callback_request_headers {
+    ($($header:ty => $instruction:ident),* $(,)?) => {
+        $(
+            impl CallbackRequestHeader for $header {
+                const INSTRUCTION: EntropyInstruction = EntropyInstruction::$instruction;
+
+                fn set_layout(&mut self, requester_signer_bump: u8, callback_accounts_len: u32) {
+                    self.requester_signer_bump = requester_signer_bump;
+                    self.callback_accounts_len = callback_accounts_len;
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! no_args {
+    ($($args:ident => $instruction:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("Args of `", stringify!($instruction), "`, which takes none.")]
+            #[derive(Clone, Copy, Pod, Zeroable)]
+            #[repr(C)]
+            pub struct $args;
+        )*
+        instruction_args!($($args => $instruction),*);
+    };
+}
+
+instruction_args! {
+    InitializeArgs => Initialize,
+    RegisterProviderArgs => RegisterProvider,
+    RequestArgs => Request,
+    RevealArgs => RevealWithCallback,
+    WithdrawProviderFeesArgs => WithdrawProviderFees,
+    InitializeGovernanceArgs => InitializeGovernance,
+    SetPythFeeArgs => SetPythFee,
+    SetDefaultProviderArgs => SetDefaultProvider,
+    RefreshKeeperLeaseArgs => RefreshKeeperLease,
+    RevealAggregateArgs => RevealAggregate,
+    SetProgramRegistryArgs => SetProgramRegistry,
+    SetProviderAllowlistArgs => SetProviderAllowlist,
+    InitializeIfNeededArgs => InitializeIfNeeded,
+    PreviewRequestArgs => PreviewRequest,
+    WriteCallbackAccountListArgs => WriteCallbackAccountList,
+    SetCallbackSkipPolicyArgs => SetCallbackSkipPolicy,
+    RevealSkippingCallbackArgs => RevealSkippingCallback,
+}
+
+no_args! {
+    GovernanceArgs => Governance,
+    ClaimAdminArgs => ClaimAdmin,
+    CloseExpiredRequestArgs => CloseExpiredRequest,
+    CloseProviderArgs => CloseProvider,
+    CloseExpiredAggregateRequestArgs => CloseExpiredAggregateRequest,
+    GetVersionArgs => GetVersion,
+    CloseCallbackAccountListArgs => CloseCallbackAccountList,
+    ReportCallbackFailureArgs => ReportCallbackFailure,
+}
+
+callback_request_headers! {
+    RequestWithCallbackHeader => RequestWithCallback,
+    RequestV2Header => RequestV2,
+    RequestAggregateHeader => RequestAggregate,
+}
+
+/// Reads the fixed-size args of an instruction from its payload (the data
+/// after the discriminator), which must be exactly their size.
+pub fn parse_args<T: Pod>(payload: &[u8]) -> Result<&T, ProgramError> {
+    if payload.len() != core::mem::size_of::<T>() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    try_from_bytes::<T>(payload).map_err(|_| ProgramError::InvalidInstructionData)
+}
+
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
 pub struct InitializeArgs {
@@ -61,6 +277,11 @@ pub struct InitializeArgs {
     pub default_provider: PubkeyBytes,
 }
 
+/// Args of `InitializeIfNeeded`: the same as `Initialize`'s.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct InitializeIfNeededArgs(pub InitializeArgs);
+
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
 pub struct RegisterProviderArgs {
@@ -72,7 +293,9 @@ pub struct RegisterProviderArgs {
     pub chain_length: u64,
     pub uri_len: u16,
     pub uri: [u8; URI_LEN],
-    pub _padding1: [u8; 6],
+    /// 1 to also reject a `uri` that does not start with `https://`.
+    pub require_https: u8,
+    pub _padding1: [u8; 5],
 }
 
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -80,8 +303,251 @@ pub struct RegisterProviderArgs {
 pub struct RequestArgs {
     pub user_commitment: [u8; 32],
     pub use_blockhash: u8,
+    /// [`RANDOM_NUMBER_V0`](crate::constants::RANDOM_NUMBER_V0) or
+    /// [`RANDOM_NUMBER_V1`](crate::constants::RANDOM_NUMBER_V1).
+    pub random_number_version: u8,
+    /// Slots that must pass after the request slot before it may be
+    /// revealed; zero allows a reveal in the request slot itself.
+    pub min_reveal_delay_slots: u16,
+    pub compute_unit_limit: u32,
+    /// Slots after the request slot during which it may be revealed; zero
+    /// means the request never expires.
+    pub ttl_slots: u32,
+    /// Receives the request account's rent, and the provider fee if the
+    /// request expires, when it is closed. Zero refunds the payer.
+    pub refund_address: PubkeyBytes,
+    /// Bump of `requester_signer`, so entropy can check the signer without
+    /// searching for its bump. Zero makes entropy search.
+    pub requester_signer_bump: u8,
     pub _padding0: [u8; 3],
+}
+
+/// Fixed-size prefix of `RequestWithCallback` data. It is followed by
+/// `callback_accounts_len` [`CallbackMeta`](crate::accounts::CallbackMeta)
+/// entries, a u32 LE callback data length and the callback data itself.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct RequestWithCallbackHeader {
+    pub user_randomness: [u8; 32],
     pub compute_unit_limit: u32,
+    /// See [`RequestArgs::ttl_slots`].
+    pub ttl_slots: u32,
+    /// See [`RequestArgs::min_reveal_delay_slots`].
+    pub min_reveal_delay_slots: u16,
+    /// 1 to append the user and provider contributions to the callback
+    /// data after the random number.
+    pub include_contributions: u8,
+    /// See [`RequestArgs::random_number_version`].
+    pub random_number_version: u8,
+    /// See [`RequestArgs::refund_address`].
+    pub refund_address: PubkeyBytes,
+    /// See [`RequestArgs::requester_signer_bump`].
+    pub requester_signer_bump: u8,
+    pub _padding0: [u8; 3],
+    pub callback_accounts_len: u32,
+}
+
+/// Borrowed view of `RequestWithCallback` instruction data.
+pub struct RequestWithCallbackArgs<'a> {
+    pub user_randomness: [u8; 32],
+    pub compute_unit_limit: u32,
+    pub ttl_slots: u32,
+    pub min_reveal_delay_slots: u16,
+    pub include_contributions: u8,
+    pub random_number_version: u8,
+    pub refund_address: PubkeyBytes,
+    pub requester_signer_bump: u8,
+    pub callback_accounts: &'a [CallbackMeta],
+    pub callback_ix_data: &'a [u8],
+}
+
+/// Fixed-size prefix of `RequestV2` data: `RequestWithCallbackHeader`
+/// without the user randomness, which entropy derives from the config seed.
+/// The same callback accounts and data follow.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct RequestV2Header {
+    pub compute_unit_limit: u32,
+    /// See [`RequestArgs::ttl_slots`].
+    pub ttl_slots: u32,
+    /// See [`RequestArgs::min_reveal_delay_slots`].
+    pub min_reveal_delay_slots: u16,
+    /// See [`RequestWithCallbackHeader::include_contributions`].
+    pub include_contributions: u8,
+    /// See [`RequestArgs::random_number_version`].
+    pub random_number_version: u8,
+    /// See [`RequestArgs::refund_address`].
+    pub refund_address: PubkeyBytes,
+    /// See [`RequestArgs::requester_signer_bump`].
+    pub requester_signer_bump: u8,
+    pub _padding0: [u8; 3],
+    pub callback_accounts_len: u32,
+}
+
+/// Fixed-size prefix of `RequestAggregate` data, followed by the same
+/// callback accounts and data as `RequestWithCallback`. The accounts list
+/// one provider and provider vault pair per provider.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct RequestAggregateHeader {
+    pub user_randomness: [u8; 32],
+    pub compute_unit_limit: u32,
+    /// See [`RequestArgs::ttl_slots`].
+    pub ttl_slots: u32,
+    pub num_providers: u8,
+    /// Reveals needed before the callback; `num_providers` requires all.
+    pub threshold: u8,
+    /// See [`RequestArgs::min_reveal_delay_slots`].
+    pub min_reveal_delay_slots: u16,
+    /// See [`RequestArgs::use_blockhash`].
+    pub use_blockhash: u8,
+    /// See [`RequestArgs::random_number_version`].
+    pub random_number_version: u8,
+    /// See [`RequestArgs::requester_signer_bump`].
+    pub requester_signer_bump: u8,
+    pub _padding0: [u8; 1],
+    /// See [`RequestArgs::refund_address`]; also receives the fees of
+    /// providers that do not reveal.
+    pub refund_address: PubkeyBytes,
+    pub callback_accounts_len: u32,
+}
+
+/// Borrowed view of `RequestAggregate` instruction data.
+pub struct RequestAggregateArgs<'a> {
+    pub user_randomness: [u8; 32],
+    pub compute_unit_limit: u32,
+    pub ttl_slots: u32,
+    pub num_providers: u8,
+    pub threshold: u8,
+    pub min_reveal_delay_slots: u16,
+    pub use_blockhash: u8,
+    pub random_number_version: u8,
+    pub requester_signer_bump: u8,
+    pub refund_address: PubkeyBytes,
+    pub callback_accounts: &'a [CallbackMeta],
+    pub callback_ix_data: &'a [u8],
+}
+
+/// Parses `RequestWithCallback` data (after the discriminator). The data must
+/// be consumed exactly: trailing bytes are rejected.
+pub fn parse_request_with_callback_args(
+    data: &[u8],
+) -> Result<RequestWithCallbackArgs<'_>, ProgramError> {
+    if data.len() < core::mem::size_of::<RequestWithCallbackHeader>() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let (header_bytes, rest) = data.split_at(core::mem::size_of::<RequestWithCallbackHeader>());
+    let header = try_from_bytes::<RequestWithCallbackHeader>(header_bytes)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let (callback_accounts, callback_ix_data) =
+        parse_callback_tail(rest, header.callback_accounts_len)?;
+
+    Ok(RequestWithCallbackArgs {
+        user_randomness: header.user_randomness,
+        compute_unit_limit: header.compute_unit_limit,
+        ttl_slots: header.ttl_slots,
+        min_reveal_delay_slots: header.min_reveal_delay_slots,
+        include_contributions: header.include_contributions,
+        random_number_version: header.random_number_version,
+        refund_address: header.refund_address,
+        requester_signer_bump: header.requester_signer_bump,
+        callback_accounts,
+        callback_ix_data,
+    })
+}
+
+/// Parses `RequestV2` data (after the discriminator) with the same rules as
+/// [`parse_request_with_callback_args`]. `user_randomness` is left zeroed
+/// for entropy to fill in.
+pub fn parse_request_v2_args(data: &[u8]) -> Result<RequestWithCallbackArgs<'_>, ProgramError> {
+    if data.len() < core::mem::size_of::<RequestV2Header>() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let (header_bytes, rest) = data.split_at(core::mem::size_of::<RequestV2Header>());
+    let header = try_from_bytes::<RequestV2Header>(header_bytes)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let (callback_accounts, callback_ix_data) =
+        parse_callback_tail(rest, header.callback_accounts_len)?;
+
+    Ok(RequestWithCallbackArgs {
+        user_randomness: [0u8; 32],
+        compute_unit_limit: header.compute_unit_limit,
+        ttl_slots: header.ttl_slots,
+        min_reveal_delay_slots: header.min_reveal_delay_slots,
+        include_contributions: header.include_contributions,
+        random_number_version: header.random_number_version,
+        refund_address: header.refund_address,
+        requester_signer_bump: header.requester_signer_bump,
+        callback_accounts,
+        callback_ix_data,
+    })
+}
+
+/// Parses `RequestAggregate` data (after the discriminator) with the same
+/// rules as [`parse_request_with_callback_args`].
+pub fn parse_request_aggregate_args(data: &[u8]) -> Result<RequestAggregateArgs<'_>, ProgramError> {
+    if data.len() < core::mem::size_of::<RequestAggregateHeader>() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let (header_bytes, rest) = data.split_at(core::mem::size_of::<RequestAggregateHeader>());
+    let header = try_from_bytes::<RequestAggregateHeader>(header_bytes)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let (callback_accounts, callback_ix_data) =
+        parse_callback_tail(rest, header.callback_accounts_len)?;
+
+    Ok(RequestAggregateArgs {
+        user_randomness: header.user_randomness,
+        compute_unit_limit: header.compute_unit_limit,
+        ttl_slots: header.ttl_slots,
+        num_providers: header.num_providers,
+        threshold: header.threshold,
+        min_reveal_delay_slots: header.min_reveal_delay_slots,
+        use_blockhash: header.use_blockhash,
+        random_number_version: header.random_number_version,
+        requester_signer_bump: header.requester_signer_bump,
+        refund_address: header.refund_address,
+        callback_accounts,
+        callback_ix_data,
+    })
+}
+
+/// Splits the callback accounts and length-prefixed callback data that
+/// follow a request header.
+fn parse_callback_tail(
+    rest: &[u8],
+    callback_accounts_len: u32,
+) -> Result<(&[CallbackMeta], &[u8]), ProgramError> {
+    let callback_accounts_len = callback_accounts_len as usize;
+
+    let callback_accounts_bytes_len = callback_accounts_len
+        .checked_mul(CallbackMeta::LEN)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if rest.len() < callback_accounts_bytes_len.saturating_add(4) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let (callback_accounts_bytes, rest) = rest.split_at(callback_accounts_bytes_len);
+    let callback_accounts = try_cast_slice::<u8, CallbackMeta>(callback_accounts_bytes)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    for meta in callback_accounts {
+        if meta.is_signer > 1 || meta.is_writable > 1 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    }
+
+    let (callback_ix_len_bytes, rest) = rest.split_at(4);
+    let mut callback_ix_len = [0u8; 4];
+    callback_ix_len.copy_from_slice(callback_ix_len_bytes);
+    let callback_ix_data_len = u32::from_le_bytes(callback_ix_len) as usize;
+
+    if rest.len() != callback_ix_data_len {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok((callback_accounts, rest))
 }
 
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -90,3 +556,137 @@ pub struct RevealArgs {
     pub user_contribution: [u8; 32],
     pub provider_contribution: [u8; 32],
 }
+
+/// Args of `RevealSkippingCallback`: the same as `RevealWithCallback`'s.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct RevealSkippingCallbackArgs(pub RevealArgs);
+
+/// Wormhole data source whose VAAs `Governance` executes.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct InitializeGovernanceArgs {
+    pub wormhole_program: PubkeyBytes,
+    pub emitter_address: [u8; 32],
+    pub emitter_chain: u16,
+    pub _padding0: [u8; 6],
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct SetPythFeeArgs {
+    pub pyth_fee_lamports: u64,
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct SetDefaultProviderArgs {
+    pub default_provider: PubkeyBytes,
+}
+
+/// The full list of official entropy program ids; the first `num_programs`
+/// entries are used.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct SetProgramRegistryArgs {
+    pub program_ids: [PubkeyBytes; MAX_REGISTERED_PROGRAMS],
+    pub num_programs: u8,
+    pub _padding0: [u8; 7],
+}
+
+/// The full provider allowlist; the first `num_providers` entries are used.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct SetProviderAllowlistArgs {
+    pub providers: [PubkeyBytes; MAX_ALLOWED_PROVIDERS],
+    pub num_providers: u8,
+    /// Stored as [`Config::permissioned_providers`](crate::accounts::Config::permissioned_providers).
+    pub permissioned_providers: u8,
+    pub _padding0: [u8; 6],
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct WithdrawProviderFeesArgs {
+    pub amount: u64,
+}
+
+/// `RevealArgs` for one provider of an aggregate request.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct RevealAggregateArgs {
+    pub user_contribution: [u8; 32],
+    pub provider_contribution: [u8; 32],
+    /// Index of the revealing provider in the request.
+    pub provider_index: u8,
+    pub _padding0: [u8; 7],
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct RefreshKeeperLeaseArgs {
+    /// Identifies the keeper instance; opaque to the program.
+    pub keeper: [u8; 32],
+    /// Slots the lease stays valid after this refresh; zero releases it.
+    pub lease_slots: u64,
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct PreviewRequestArgs {
+    /// Hash of the user randomness, as passed to `Request`.
+    pub user_commitment: [u8; 32],
+}
+
+/// Return data of `PreviewRequest`: what a request made now with the same
+/// user commitment would store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Pod, Zeroable)]
+#[repr(C)]
+pub struct RequestPreview {
+    pub sequence_number: u64,
+    pub num_hashes: u32,
+    pub _padding0: [u8; 4],
+    /// See [`request_commitment`](crate::randomness::request_commitment).
+    pub commitment: [u8; 32],
+}
+
+/// Accounts to append to a callback account list; the first `num_accounts`
+/// entries are used.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct WriteCallbackAccountListArgs {
+    pub callback_accounts: [CallbackMeta; MAX_CALLBACK_ACCOUNTS],
+    pub num_accounts: u8,
+    pub _padding0: [u8; 1],
+}
+
+/// A zero `max_failures` disables skipping; otherwise `window_slots` must be
+/// non-zero.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct SetCallbackSkipPolicyArgs {
+    pub window_slots: u32,
+    pub max_failures: u16,
+    pub _padding0: [u8; 2],
+}
+
+// Instruction payload sizes are part of the program's ABI.
+const _: () = assert!(core::mem::size_of::<InitializeArgs>() == 72);
+const _: () = assert!(core::mem::size_of::<RegisterProviderArgs>() == 384);
+const _: () = assert!(core::mem::size_of::<RequestArgs>() == 80);
+const _: () = assert!(core::mem::size_of::<RequestWithCallbackHeader>() == 84);
+const _: () = assert!(core::mem::size_of::<RequestV2Header>() == 52);
+const _: () = assert!(core::mem::size_of::<RevealArgs>() == 64);
+const _: () = assert!(core::mem::size_of::<InitializeGovernanceArgs>() == 72);
+const _: () = assert!(core::mem::size_of::<SetPythFeeArgs>() == 8);
+const _: () = assert!(core::mem::size_of::<SetDefaultProviderArgs>() == 32);
+const _: () = assert!(core::mem::size_of::<WithdrawProviderFeesArgs>() == 8);
+const _: () = assert!(core::mem::size_of::<SetProgramRegistryArgs>() == 264);
+const _: () = assert!(core::mem::size_of::<SetProviderAllowlistArgs>() == 520);
+const _: () = assert!(core::mem::size_of::<RefreshKeeperLeaseArgs>() == 40);
+const _: () = assert!(core::mem::size_of::<RequestAggregateHeader>() == 84);
+const _: () = assert!(core::mem::size_of::<RevealAggregateArgs>() == 72);
+const _: () = assert!(core::mem::size_of::<PreviewRequestArgs>() == 32);
+const _: () = assert!(core::mem::size_of::<RequestPreview>() == 48);
+const _: () = assert!(core::mem::size_of::<WriteCallbackAccountListArgs>() == 578);
+const _: () = assert!(core::mem::size_of::<SetCallbackSkipPolicyArgs>() == 8);
@@ -5,17 +5,27 @@ pub mod constants;
 pub mod discriminator;
 pub mod entrypoint;
 pub mod error;
+pub mod events;
+pub mod governance;
 pub mod instruction;
 pub mod pda;
 pub mod pda_loader;
 pub mod processor;
+pub mod program_data;
+pub mod randomness;
+pub mod seeds;
 pub mod vault;
 
 pub use accounts::*;
 pub use constants::*;
 pub use discriminator::*;
 pub use error::*;
+pub use events::*;
+pub use governance::*;
 pub use instruction::*;
 pub use pda::*;
 pub use pda_loader::*;
+pub use program_data::*;
+pub use randomness::*;
+pub use seeds::*;
 pub use vault::*;
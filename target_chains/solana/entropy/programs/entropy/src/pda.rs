@@ -1,8 +1,9 @@
 use solana_program::pubkey::Pubkey;
 
 use crate::constants::{
-    CONFIG_SEED, ENTROPY_SIGNER_SEED, PROVIDER_SEED, PROVIDER_VAULT_SEED, PYTH_FEE_VAULT_SEED,
-    REQUEST_SEED,
+    CALLBACK_ACCOUNT_LIST_SEED, CALLBACK_FAILURES_SEED, CONFIG_SEED, ENTROPY_SIGNER_SEED,
+    GOVERNANCE_SEED, KEEPER_LEASE_SEED, PROVIDER_ALLOWLIST_SEED, PROVIDER_DIRECTORY_SEED,
+    PROVIDER_SEED, PROVIDER_VAULT_SEED, PYTH_FEE_VAULT_SEED, REQUESTER_SIGNER_SEED, REQUEST_SEED,
 };
 
 pub fn config_pda(program_id: &Pubkey) -> (Pubkey, u8) {
@@ -13,6 +14,26 @@ pub fn provider_pda(program_id: &Pubkey, provider_authority: &Pubkey) -> (Pubkey
     Pubkey::find_program_address(&[PROVIDER_SEED, provider_authority.as_ref()], program_id)
 }
 
+/// [`provider_pda`] from a bump recorded when the account was created, which
+/// skips `find_program_address`'s search. A zero bump marks accounts written
+/// before bumps were recorded and falls back to the search; no canonical bump
+/// is zero in practice.
+pub fn provider_pda_with_bump(
+    program_id: &Pubkey,
+    provider_authority: &Pubkey,
+    bump: u8,
+) -> (Pubkey, u8) {
+    if bump != 0 {
+        if let Ok(address) = Pubkey::create_program_address(
+            &[PROVIDER_SEED, provider_authority.as_ref(), &[bump]],
+            program_id,
+        ) {
+            return (address, bump);
+        }
+    }
+    provider_pda(program_id, provider_authority)
+}
+
 pub fn provider_vault_pda(program_id: &Pubkey, provider_authority: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(
         &[PROVIDER_VAULT_SEED, provider_authority.as_ref()],
@@ -36,6 +57,95 @@ pub fn pyth_fee_vault_pda(program_id: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[PYTH_FEE_VAULT_SEED], program_id)
 }
 
+pub fn governance_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GOVERNANCE_SEED], program_id)
+}
+
+pub fn keeper_lease_pda(program_id: &Pubkey, provider_authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[KEEPER_LEASE_SEED, provider_authority.as_ref()],
+        program_id,
+    )
+}
+
 pub fn entropy_signer_pda(program_id: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[ENTROPY_SIGNER_SEED], program_id)
 }
+
+/// [`entropy_signer_pda`] from a recorded bump; same fallback as
+/// [`provider_pda_with_bump`].
+pub fn entropy_signer_pda_with_bump(program_id: &Pubkey, bump: u8) -> (Pubkey, u8) {
+    if bump != 0 {
+        if let Ok(address) =
+            Pubkey::create_program_address(&[ENTROPY_SIGNER_SEED, &[bump]], program_id)
+        {
+            return (address, bump);
+        }
+    }
+    entropy_signer_pda(program_id)
+}
+
+pub fn provider_allowlist_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROVIDER_ALLOWLIST_SEED], program_id)
+}
+
+/// Page `page` of the provider directory; see
+/// [`ProviderDirectoryPage`](crate::accounts::ProviderDirectoryPage).
+pub fn provider_directory_pda(program_id: &Pubkey, page: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROVIDER_DIRECTORY_SEED, &page.to_le_bytes()], program_id)
+}
+
+/// The callback account list of the request at `request_account`; see
+/// [`CallbackAccountList`](crate::accounts::CallbackAccountList).
+pub fn callback_account_list_pda(program_id: &Pubkey, request_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CALLBACK_ACCOUNT_LIST_SEED, request_account.as_ref()],
+        program_id,
+    )
+}
+
+/// The provider's failure counts for callbacks into `requester_program_id`;
+/// see [`CallbackFailureStats`](crate::accounts::CallbackFailureStats).
+pub fn callback_failure_stats_pda(
+    program_id: &Pubkey,
+    provider_authority: &Pubkey,
+    requester_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            CALLBACK_FAILURES_SEED,
+            provider_authority.as_ref(),
+            requester_program_id.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Signer a requester program uses when calling into entropy. Derived under
+/// the requester program, not entropy.
+pub fn requester_signer_pda(requester_program_id: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[REQUESTER_SIGNER_SEED, program_id.as_ref()],
+        requester_program_id,
+    )
+}
+
+/// [`requester_signer_pda`] from a bump the requester passed in; same
+/// fallback as [`provider_pda_with_bump`]. The bump need not be canonical:
+/// any address derived from the requester's seeds can only be signed for by
+/// the requester program.
+pub fn requester_signer_pda_with_bump(
+    requester_program_id: &Pubkey,
+    program_id: &Pubkey,
+    bump: u8,
+) -> (Pubkey, u8) {
+    if bump != 0 {
+        if let Ok(address) = Pubkey::create_program_address(
+            &[REQUESTER_SIGNER_SEED, program_id.as_ref(), &[bump]],
+            requester_program_id,
+        ) {
+            return (address, bump);
+        }
+    }
+    requester_signer_pda(requester_program_id, program_id)
+}
@@ -17,14 +17,17 @@ pub fn load_account<'a, T: Account>(
     account: &'a AccountInfo,
     program_id: &Pubkey,
 ) -> Result<Ref<'a, T>, ProgramError> {
-    if account.owner != program_id || account.data_len() != T::LEN {
-        return Err(EntropyError::InvalidAccount.into());
+    if account.owner != program_id {
+        return Err(EntropyError::WrongAccountOwner.into());
+    }
+    if account.data_len() != T::LEN {
+        return Err(EntropyError::WrongAccountSize.into());
     }
 
     let data = account.data.borrow();
     let discriminator = data.get(0..8).ok_or(ProgramError::InvalidAccountData)?;
     if discriminator != T::discriminator() {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::WrongAccountType.into());
     }
 
     Ok(Ref::map(data, |data| bytemuck::from_bytes::<T>(data)))
@@ -34,15 +37,18 @@ pub fn load_account_mut<'a, 'info, T: Account>(
     account: &'a AccountInfo<'info>,
     program_id: &Pubkey,
 ) -> Result<RefMut<'a, T>, ProgramError> {
-    if account.owner != program_id || account.data_len() != T::LEN {
-        return Err(EntropyError::InvalidAccount.into());
+    if account.owner != program_id {
+        return Err(EntropyError::WrongAccountOwner.into());
+    }
+    if account.data_len() != T::LEN {
+        return Err(EntropyError::WrongAccountSize.into());
     }
 
     {
         let data = account.data.borrow();
         let discriminator = data.get(0..8).ok_or(ProgramError::InvalidAccountData)?;
         if discriminator != T::discriminator() {
-            return Err(EntropyError::InvalidAccount.into());
+            return Err(EntropyError::WrongAccountType.into());
         }
     }
 
@@ -59,7 +65,7 @@ pub fn init_pda_mut<'a, 'info, T: bytemuck::Pod>(
     space: usize,
 ) -> Result<RefMut<'a, T>, ProgramError> {
     if account.owner != &system_program::ID || account.data_len() != 0 {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::AccountAlreadyInitialized.into());
     }
 
     let rent = Rent::get()?;
@@ -118,3 +124,37 @@ pub fn init_pda_mut<'a, 'info, T: bytemuck::Pod>(
     let data = account.data.borrow_mut();
     Ok(RefMut::map(data, |data| from_bytes_mut::<T>(data)))
 }
+
+/// Grows a program-owned account to `space` bytes, zero-filling the new tail
+/// and topping its rent up from `payer`. A no-op if it is already that large.
+pub fn grow_account<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program_account: &AccountInfo<'info>,
+    space: usize,
+) -> Result<(), ProgramError> {
+    if account.data_len() >= space {
+        return Ok(());
+    }
+
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+    if current_lamports < required_lamports {
+        let transfer_ix = system_instruction::transfer(
+            payer.key,
+            account.key,
+            required_lamports - current_lamports,
+        );
+        invoke(
+            &transfer_ix,
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program_account.clone(),
+            ],
+        )?;
+    }
+
+    #[allow(deprecated)]
+    account.realloc(space, true)
+}
@@ -0,0 +1,292 @@
+use std::cell::RefMut;
+
+use bytemuck::Zeroable;
+#[allow(deprecated)]
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{
+    accounts::{Config, ProgramRegistry, ProviderAllowlist, RegisteredProgram},
+    constants::{MAX_ALLOWED_PROVIDERS, MAX_REGISTERED_PROGRAMS, PROVIDER_ALLOWLIST_SEED},
+    discriminator::{program_registry_discriminator, provider_allowlist_discriminator},
+    error::EntropyError,
+    events::{emit_event, DefaultProviderSet, PythFeeSet},
+    instruction::{
+        SetDefaultProviderArgs, SetProgramRegistryArgs, SetProviderAllowlistArgs, SetPythFeeArgs,
+    },
+    pda::{config_pda, entropy_signer_pda, provider_allowlist_pda},
+    pda_loader::{init_pda_mut, load_account, load_account_mut},
+    processor::parse_args,
+    program_data::{authorize_admin, load_upgrade_authority},
+    seeds::entropy_signer_seeds,
+};
+
+/// Loads the config for an admin instruction whose accounts are `[signer]`
+/// admin, `[writable]` config and, optionally, the program data account
+/// when the upgrade authority signs instead of the admin.
+fn load_config_as_admin<'a, 'info>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<RefMut<'a, Config>, ProgramError> {
+    let mut account_info_iter = accounts.iter();
+    let admin = next_account_info(&mut account_info_iter)?;
+    let config_account = next_account_info(&mut account_info_iter)?;
+    let program_data = account_info_iter.next();
+
+    if !config_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    let (expected_config, _config_bump) = config_pda(program_id);
+    if config_account.key != &expected_config {
+        return Err(EntropyError::WrongConfig.into());
+    }
+
+    let config = load_account_mut::<Config>(config_account, program_id)?;
+    authorize_admin(program_id, &config, admin, program_data)?;
+    Ok(config)
+}
+
+pub fn process_set_pyth_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<SetPythFeeArgs>(data)?;
+    let mut config = load_config_as_admin(program_id, accounts)?;
+
+    let old_fee_lamports = config.pyth_fee_lamports;
+    config.pyth_fee_lamports = args.pyth_fee_lamports;
+    emit_event(&PythFeeSet {
+        old_fee_lamports,
+        new_fee_lamports: args.pyth_fee_lamports,
+    });
+
+    Ok(())
+}
+
+pub fn process_set_default_provider(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<SetDefaultProviderArgs>(data)?;
+    if args.default_provider == [0u8; 32] {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut config = load_config_as_admin(program_id, accounts)?;
+
+    let old_default_provider = config.default_provider;
+    config.default_provider = args.default_provider;
+    emit_event(&DefaultProviderSet {
+        old_default_provider,
+        new_default_provider: args.default_provider,
+    });
+
+    Ok(())
+}
+
+/// Makes the program's upgrade authority the admin, so a deployer can take
+/// back a config initialized by someone else.
+pub fn process_claim_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if !data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let upgrade_authority = next_account_info(&mut account_info_iter)?;
+    let config_account = next_account_info(&mut account_info_iter)?;
+    let program_data = next_account_info(&mut account_info_iter)?;
+
+    if !upgrade_authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !config_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    let (expected_config, _config_bump) = config_pda(program_id);
+    if config_account.key != &expected_config {
+        return Err(EntropyError::WrongConfig.into());
+    }
+
+    if load_upgrade_authority(program_id, program_data)? != Some(*upgrade_authority.key) {
+        return Err(EntropyError::Unauthorized.into());
+    }
+
+    let mut config = load_account_mut::<Config>(config_account, program_id)?;
+    config.admin = upgrade_authority.key.to_bytes();
+    config.proposed_admin = [0u8; 32];
+
+    Ok(())
+}
+
+/// Replaces the program registry kept in the entropy signer PDA, creating the
+/// account on first use. Accounts: `[signer]` admin, config, `[signer,
+/// writable]` payer, `[writable]` entropy signer, the system program and,
+/// optionally, the program data account as for the other admin actions.
+pub fn process_set_program_registry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<SetProgramRegistryArgs>(data)?;
+    let num_programs = usize::from(args.num_programs);
+    if num_programs > MAX_REGISTERED_PROGRAMS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let program_ids = &args.program_ids[..num_programs];
+    for (index, program) in program_ids.iter().enumerate() {
+        if *program == [0u8; 32] || program_ids[..index].contains(program) {
+            return Err(EntropyError::InvalidProgramRegistry.into());
+        }
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let admin = next_account_info(&mut account_info_iter)?;
+    let config_account = next_account_info(&mut account_info_iter)?;
+    let payer = next_account_info(&mut account_info_iter)?;
+    let entropy_signer_account = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+    let program_data = account_info_iter.next();
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !payer.is_writable || !entropy_signer_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let (expected_config, _config_bump) = config_pda(program_id);
+    if config_account.key != &expected_config {
+        return Err(EntropyError::WrongConfig.into());
+    }
+    {
+        let config = load_account::<Config>(config_account, program_id)?;
+        authorize_admin(program_id, &config, admin, program_data)?;
+    }
+
+    let (expected_entropy_signer, entropy_signer_bump) = entropy_signer_pda(program_id);
+    if entropy_signer_account.key != &expected_entropy_signer {
+        return Err(EntropyError::WrongEntropySigner.into());
+    }
+
+    let mut registry = if entropy_signer_account.owner == &system_program::ID {
+        let mut registry = init_pda_mut::<ProgramRegistry>(
+            program_id,
+            payer,
+            entropy_signer_account,
+            system_program_account,
+            &entropy_signer_seeds(&entropy_signer_bump),
+            ProgramRegistry::LEN,
+        )?;
+        registry.discriminator = program_registry_discriminator();
+        registry.bump = entropy_signer_bump;
+        registry
+    } else {
+        load_account_mut::<ProgramRegistry>(entropy_signer_account, program_id)?
+    };
+
+    registry.num_programs = args.num_programs;
+    registry.programs = [RegisteredProgram::zeroed(); MAX_REGISTERED_PROGRAMS];
+    for (entry, program) in registry.programs.iter_mut().zip(program_ids) {
+        entry.program_id = *program;
+        entry.entropy_signer = entropy_signer_pda(&Pubkey::new_from_array(*program))
+            .0
+            .to_bytes();
+    }
+
+    Ok(())
+}
+
+/// Replaces the provider allowlist, creating the account on first use, and
+/// sets `config.permissioned_providers`. Accounts: `[signer]` admin,
+/// `[writable]` config, `[signer, writable]` payer, `[writable]` provider
+/// allowlist, the system program and, optionally, the program data account.
+pub fn process_set_provider_allowlist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<SetProviderAllowlistArgs>(data)?;
+    let num_providers = usize::from(args.num_providers);
+    if num_providers > MAX_ALLOWED_PROVIDERS || args.permissioned_providers > 1 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let providers = &args.providers[..num_providers];
+    for (index, provider) in providers.iter().enumerate() {
+        if *provider == [0u8; 32] || providers[..index].contains(provider) {
+            return Err(EntropyError::InvalidProviderAllowlist.into());
+        }
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let admin = next_account_info(&mut account_info_iter)?;
+    let config_account = next_account_info(&mut account_info_iter)?;
+    let payer = next_account_info(&mut account_info_iter)?;
+    let allowlist_account = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+    let program_data = account_info_iter.next();
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !config_account.is_writable || !payer.is_writable || !allowlist_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let (expected_config, _config_bump) = config_pda(program_id);
+    if config_account.key != &expected_config {
+        return Err(EntropyError::WrongConfig.into());
+    }
+    let mut config = load_account_mut::<Config>(config_account, program_id)?;
+    authorize_admin(program_id, &config, admin, program_data)?;
+
+    let (expected_allowlist, allowlist_bump) = provider_allowlist_pda(program_id);
+    if allowlist_account.key != &expected_allowlist {
+        return Err(EntropyError::WrongProviderAllowlist.into());
+    }
+
+    let mut allowlist = if allowlist_account.owner == &system_program::ID {
+        let mut allowlist = init_pda_mut::<ProviderAllowlist>(
+            program_id,
+            payer,
+            allowlist_account,
+            system_program_account,
+            &[PROVIDER_ALLOWLIST_SEED, &[allowlist_bump]],
+            ProviderAllowlist::LEN,
+        )?;
+        allowlist.discriminator = provider_allowlist_discriminator();
+        allowlist.bump = allowlist_bump;
+        allowlist
+    } else {
+        load_account_mut::<ProviderAllowlist>(allowlist_account, program_id)?
+    };
+
+    allowlist.num_providers = args.num_providers;
+    allowlist.providers = [[0u8; 32]; MAX_ALLOWED_PROVIDERS];
+    allowlist.providers[..num_providers].copy_from_slice(providers);
+    config.permissioned_providers = args.permissioned_providers;
+
+    Ok(())
+}
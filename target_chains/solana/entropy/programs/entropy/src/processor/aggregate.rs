@@ -0,0 +1,552 @@
+#[allow(deprecated)]
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hash,
+    program::set_return_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::{clock::Clock, slot_hashes, Sysvar},
+};
+
+use crate::{
+    accounts::{AggregateContribution, AggregateRequest, Config, Provider},
+    constants::{
+        CALLBACK_IX_DATA_LEN, MAX_AGGREGATE_PROVIDERS, MAX_CALLBACK_ACCOUNTS, RANDOM_NUMBER_V1,
+    },
+    discriminator::aggregate_request_discriminator,
+    error::EntropyError,
+    events::{emit_event, AggregateRandomnessRevealed, AggregateRequestCreated, CallbackInvoked},
+    instruction::{parse_request_aggregate_args, RevealAggregateArgs},
+    pda::{
+        config_pda, entropy_signer_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda,
+        requester_signer_pda_with_bump,
+    },
+    pda_loader::{load_account, load_account_mut},
+    processor::{
+        close_expired_request::refund_provider_fee,
+        close_request_account, parse_args,
+        request::{init_request_account_mut, reserve_sequence_number, transfer_fee},
+        reveal_with_callback::{
+            hash_provider_commitment, invoke_callback, request_blockhash,
+            validate_callback_accounts,
+        },
+    },
+    randomness::{aggregate_provider_contribution, combine_random_values},
+};
+
+/// Creates an [`AggregateRequest`]: a callback request for which each of the
+/// listed providers reserves a sequence number and is paid its fee. The Pyth
+/// fee is charged once.
+pub fn process_request_aggregate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_request_aggregate_args(data)?;
+    if args.use_blockhash > 1 || args.random_number_version > RANDOM_NUMBER_V1 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let num_providers = usize::from(args.num_providers);
+    if num_providers == 0
+        || num_providers > MAX_AGGREGATE_PROVIDERS
+        || args.threshold == 0
+        || args.threshold > args.num_providers
+    {
+        return Err(EntropyError::InvalidAggregateThreshold.into());
+    }
+    if args.callback_accounts.len() > MAX_CALLBACK_ACCOUNTS {
+        return Err(EntropyError::TooManyCallbackAccounts.into());
+    }
+    if args.callback_ix_data.len() > CALLBACK_IX_DATA_LEN {
+        return Err(EntropyError::CallbackDataTooLong.into());
+    }
+    let entropy_program_id = program_id.to_bytes();
+    if args
+        .callback_accounts
+        .iter()
+        .any(|meta| meta.pubkey == entropy_program_id)
+    {
+        return Err(EntropyError::EntropyInCallbackAccounts.into());
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let requester_signer = next_account_info(&mut account_info_iter)?;
+    let payer = next_account_info(&mut account_info_iter)?;
+    let requester_program = next_account_info(&mut account_info_iter)?;
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let config_account = next_account_info(&mut account_info_iter)?;
+    let pyth_fee_vault = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+    let provider_accounts = account_info_iter
+        .as_slice()
+        .get(..num_providers * 2)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    if !requester_signer.is_signer || !payer.is_signer || !request_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !payer.is_writable || !request_account.is_writable || !pyth_fee_vault.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let (expected_requester_signer, _bump) = requester_signer_pda_with_bump(
+        requester_program.key,
+        program_id,
+        args.requester_signer_bump,
+    );
+    if requester_signer.key != &expected_requester_signer {
+        return Err(EntropyError::WrongRequesterSigner.into());
+    }
+
+    let (expected_config, _config_bump) = config_pda(program_id);
+    if config_account.key != &expected_config {
+        return Err(EntropyError::WrongConfig.into());
+    }
+
+    let (expected_pyth_fee_vault, _pyth_fee_vault_bump) = pyth_fee_vault_pda(program_id);
+    if pyth_fee_vault.key != &expected_pyth_fee_vault {
+        return Err(EntropyError::WrongPythFeeVault.into());
+    }
+    if pyth_fee_vault.owner != &system_program::ID || pyth_fee_vault.data_len() != 0 {
+        return Err(EntropyError::InvalidVault.into());
+    }
+
+    if request_account.owner != &system_program::ID || request_account.data_len() != 0 {
+        return Err(EntropyError::RequestAccountNotEmpty.into());
+    }
+
+    let config = load_account::<Config>(config_account, program_id)?;
+    let mut request = init_request_account_mut::<AggregateRequest>(
+        program_id,
+        payer,
+        request_account,
+        system_program_account,
+        AggregateRequest::LEN,
+    )?;
+
+    for (index, pair) in provider_accounts.chunks_exact(2).enumerate() {
+        let (provider_account, provider_vault) = (&pair[0], &pair[1]);
+        if !provider_account.is_writable || !provider_vault.is_writable {
+            return Err(EntropyError::AccountNotWritable.into());
+        }
+
+        let mut provider = load_account_mut::<Provider>(provider_account, program_id)?;
+        let provider_authority = Pubkey::new_from_array(provider.provider_authority);
+        let (expected_provider, _provider_bump) = provider_pda(program_id, &provider_authority);
+        if provider_account.key != &expected_provider {
+            return Err(EntropyError::WrongProvider.into());
+        }
+
+        let (expected_provider_vault, _provider_vault_bump) =
+            provider_vault_pda(program_id, &provider_authority);
+        if provider_vault.key != &expected_provider_vault {
+            return Err(EntropyError::WrongProviderVault.into());
+        }
+        if provider_vault.owner != &system_program::ID || provider_vault.data_len() != 0 {
+            return Err(EntropyError::InvalidVault.into());
+        }
+
+        if request.contributions[..index]
+            .iter()
+            .any(|contribution| contribution.provider == provider.provider_authority)
+        {
+            return Err(EntropyError::DuplicateAggregateProvider.into());
+        }
+
+        let (sequence_number, num_hashes) = reserve_sequence_number(&mut provider)?;
+        let provider_fee = provider.calculate_provider_fee(args.compute_unit_limit)?;
+        transfer_fee(payer, provider_vault, system_program_account, provider_fee)?;
+        provider.reserve_fee(provider_fee)?;
+
+        request.contributions[index] = AggregateContribution {
+            provider: provider.provider_authority,
+            sequence_number,
+            provider_commitment: provider.current_commitment,
+            contribution: [0u8; 32],
+            provider_fee,
+            num_hashes,
+            revealed: 0,
+            _padding0: [0u8; 3],
+        };
+    }
+    transfer_fee(
+        payer,
+        pyth_fee_vault,
+        system_program_account,
+        config.pyth_fee_lamports,
+    )?;
+
+    request.discriminator = aggregate_request_discriminator();
+    request.user_commitment = hash(&args.user_randomness).to_bytes();
+    request.requester_program_id = requester_program.key.to_bytes();
+    request.payer = payer.key.to_bytes();
+    request.refund_address = if args.refund_address == [0u8; 32] {
+        request.payer
+    } else {
+        args.refund_address
+    };
+    request.use_blockhash = args.use_blockhash;
+    request.random_number_version = args.random_number_version;
+    request.request_slot = Clock::get()?.slot;
+    request.ttl_slots = args.ttl_slots;
+    request.min_reveal_delay_slots = args.min_reveal_delay_slots;
+    request.compute_unit_limit = args.compute_unit_limit;
+    request.num_providers = args.num_providers;
+    request.threshold = args.threshold;
+    request.callback_accounts_len = args.callback_accounts.len() as u8;
+    request.callback_accounts[..args.callback_accounts.len()]
+        .copy_from_slice(args.callback_accounts);
+    request.callback_ix_data_len = args.callback_ix_data.len() as u16;
+    request.callback_ix_data[..args.callback_ix_data.len()].copy_from_slice(args.callback_ix_data);
+
+    emit_event(&AggregateRequestCreated {
+        requester_program_id: request.requester_program_id,
+        request_account: request_account.key.to_bytes(),
+        payer: request.payer,
+        request_slot: request.request_slot,
+        num_providers: request.num_providers,
+        threshold: request.threshold,
+        _padding0: [0u8; 6],
+    });
+
+    // Callbacks identify the request by its first provider's sequence number.
+    set_return_data(&request.contributions[0].sequence_number.to_le_bytes());
+    Ok(())
+}
+
+/// Records one provider's contribution to an [`AggregateRequest`]. The reveal
+/// that reaches the threshold derives the random number from the user
+/// contribution and the revealed provider contributions, invokes the callback
+/// and closes the request to its refund address. Providers that have not
+/// revealed by then are dropped from it and their fees refunded.
+///
+/// Any `threshold` of the providers can finalize the request, so the result
+/// is only unpredictable if fewer than `threshold` of them collude: a
+/// provider that sees the others' reveals can compute the random number with
+/// and without its own contribution and withhold it, leaving the request to
+/// expire. Requesters that do not accept this should set `threshold` to
+/// `num_providers`, which turns withholding into a refund and nothing more.
+pub fn process_reveal_aggregate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<RevealAggregateArgs>(data)?;
+
+    let mut account_info_iter = accounts.iter();
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let slot_hashes_account = next_account_info(&mut account_info_iter)?;
+    let entropy_signer_account = next_account_info(&mut account_info_iter)?;
+    let callback_program = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+    let refund_account = next_account_info(&mut account_info_iter)?;
+
+    if !request_account.is_writable || !refund_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    if slot_hashes_account.key != &slot_hashes::ID {
+        return Err(EntropyError::WrongSlotHashes.into());
+    }
+
+    let (expected_entropy_signer, entropy_signer_bump) = entropy_signer_pda(program_id);
+    if entropy_signer_account.key != &expected_entropy_signer {
+        return Err(EntropyError::WrongEntropySigner.into());
+    }
+
+    let mut request = load_account_mut::<AggregateRequest>(request_account, program_id)?;
+
+    let reveal_slot = Clock::get()?.slot;
+    if request.is_expired(reveal_slot) {
+        return Err(EntropyError::RequestExpired.into());
+    }
+    if !request.is_revealable_yet(reveal_slot) {
+        return Err(EntropyError::RevealTooEarly.into());
+    }
+
+    if refund_account.key.to_bytes() != request.refund_address {
+        return Err(EntropyError::WrongPayer.into());
+    }
+
+    let num_providers = request.active_contributions().len();
+    let (provider_accounts, remaining_accounts) =
+        split_provider_accounts(account_info_iter.as_slice(), num_providers)?;
+    let mut vault_bumps = [0u8; MAX_AGGREGATE_PROVIDERS];
+    for ((contribution, pair), vault_bump) in request
+        .active_contributions()
+        .iter()
+        .zip(provider_accounts.chunks_exact(2))
+        .zip(vault_bumps.iter_mut())
+    {
+        *vault_bump = check_provider_accounts(program_id, contribution, pair)?;
+    }
+
+    let index = usize::from(args.provider_index);
+    if index >= num_providers || request.contributions[index].revealed != 0 {
+        return Err(EntropyError::InvalidRevealCall.into());
+    }
+
+    if hash(&args.user_contribution).to_bytes() != request.user_commitment {
+        return Err(EntropyError::IncorrectRevelation.into());
+    }
+    let request_slot = request.request_slot;
+    let contribution = &mut request.contributions[index];
+    let provider_commitment =
+        hash_provider_commitment(args.provider_contribution, contribution.num_hashes)?;
+    if provider_commitment != contribution.provider_commitment {
+        return Err(EntropyError::IncorrectRevelation.into());
+    }
+    contribution.contribution = args.provider_contribution;
+    contribution.revealed = 1;
+
+    {
+        let mut provider = load_account_mut::<Provider>(&provider_accounts[index * 2], program_id)?;
+        if provider.current_commitment_sequence_number < contribution.sequence_number {
+            provider.current_commitment_sequence_number = contribution.sequence_number;
+            provider.current_commitment = args.provider_contribution;
+        }
+        provider.outstanding_requests = provider.outstanding_requests.saturating_sub(1);
+        provider.release_fee(contribution.provider_fee);
+        provider.record_reveal_latency(reveal_slot.saturating_sub(request_slot));
+    }
+
+    request.revealed_count = request.revealed_count.saturating_add(1);
+    if request.revealed_count < request.threshold {
+        return Ok(());
+    }
+
+    let blockhash = request_blockhash(slot_hashes_account, request.use_blockhash, request_slot)?;
+    let mut revealed_mask = 0u8;
+    let mut revealed_contributions: Vec<&[u8]> = Vec::with_capacity(MAX_AGGREGATE_PROVIDERS);
+    for (index, contribution) in request.active_contributions().iter().enumerate() {
+        if contribution.revealed != 0 {
+            revealed_mask |= 1 << index;
+            revealed_contributions.push(&contribution.contribution);
+        }
+    }
+    let random_number = combine_random_values(
+        request.random_number_version,
+        program_id,
+        &args.user_contribution,
+        &aggregate_provider_contribution(&revealed_contributions),
+        &blockhash,
+    );
+
+    let requester_program_id = Pubkey::new_from_array(request.requester_program_id);
+    if callback_program.key != &requester_program_id {
+        return Err(EntropyError::WrongCallbackProgram.into());
+    }
+
+    let callback_accounts_len = usize::from(request.callback_accounts_len);
+    if callback_accounts_len > MAX_CALLBACK_ACCOUNTS {
+        return Err(EntropyError::TooManyCallbackAccounts.into());
+    }
+    let callback_accounts = remaining_accounts
+        .get(..callback_accounts_len)
+        .ok_or(EntropyError::CallbackAccountMismatch)?;
+    validate_callback_accounts(
+        &request.callback_accounts[..callback_accounts_len],
+        callback_accounts,
+    )?;
+
+    let callback_invoked = request.compute_unit_limit != 0;
+    let callback_ix_data = request
+        .callback_ix_data
+        .get(..usize::from(request.callback_ix_data_len))
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .to_vec();
+    let contributions = request.contributions;
+    let first = contributions[0];
+    let invoked_event = CallbackInvoked {
+        provider: first.provider,
+        requester_program_id: request.requester_program_id,
+        request_account: request_account.key.to_bytes(),
+        sequence_number: first.sequence_number,
+        request_slot,
+        reveal_slot,
+        compute_unit_limit: request.compute_unit_limit,
+        _padding0: [0u8; 4],
+    };
+    let event = AggregateRandomnessRevealed {
+        requester_program_id: request.requester_program_id,
+        request_account: request_account.key.to_bytes(),
+        random_number,
+        request_slot,
+        reveal_slot,
+        revealed_mask,
+        callback_invoked: u8::from(callback_invoked),
+        _padding0: [0u8; 6],
+    };
+    drop(request);
+
+    // Unrevealed sequence numbers are abandoned with the request; like an
+    // expired request, their fees go back to the refund address.
+    for ((contribution, pair), vault_bump) in contributions[..num_providers]
+        .iter()
+        .zip(provider_accounts.chunks_exact(2))
+        .zip(vault_bumps)
+    {
+        if contribution.revealed == 0 {
+            drop_unrevealed_contribution(
+                program_id,
+                contribution,
+                pair,
+                vault_bump,
+                refund_account,
+                system_program_account,
+            )?;
+        }
+    }
+
+    if callback_invoked {
+        emit_event(&invoked_event);
+        invoke_callback(
+            callback_program,
+            entropy_signer_account,
+            entropy_signer_bump,
+            callback_accounts,
+            &callback_ix_data,
+            first.sequence_number,
+            first.provider,
+            random_number,
+            None,
+        )?;
+    }
+
+    emit_event(&event);
+    close_request_account(request_account, refund_account)
+}
+
+/// Closes an [`AggregateRequest`] whose TTL passed before it reached its
+/// threshold. Providers that had not revealed refund their fee to the refund
+/// address; those that had keep it.
+pub fn process_close_expired_aggregate_request(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if !data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let refund_account = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+
+    if !request_account.is_writable || !refund_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let request = load_account::<AggregateRequest>(request_account, program_id)?;
+
+    if refund_account.key.to_bytes() != request.refund_address {
+        return Err(EntropyError::WrongPayer.into());
+    }
+
+    if !request.is_expired(Clock::get()?.slot) {
+        return Err(EntropyError::RequestNotExpired.into());
+    }
+
+    let contributions = request.active_contributions();
+    let (provider_accounts, _) =
+        split_provider_accounts(account_info_iter.as_slice(), contributions.len())?;
+    for (contribution, pair) in contributions.iter().zip(provider_accounts.chunks_exact(2)) {
+        let vault_bump = check_provider_accounts(program_id, contribution, pair)?;
+        if contribution.revealed == 0 {
+            drop_unrevealed_contribution(
+                program_id,
+                contribution,
+                pair,
+                vault_bump,
+                refund_account,
+                system_program_account,
+            )?;
+        }
+    }
+
+    drop(request);
+    close_request_account(request_account, refund_account)
+}
+
+/// Splits the `[provider, provider vault]` pairs of `num_providers`
+/// providers off the front of `accounts`.
+fn split_provider_accounts<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    num_providers: usize,
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>]), ProgramError> {
+    if accounts.len() < num_providers * 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    Ok(accounts.split_at(num_providers * 2))
+}
+
+/// Checks that `pair` is the writable provider account and provider vault of
+/// `contribution`'s provider. Returns the vault bump.
+fn check_provider_accounts(
+    program_id: &Pubkey,
+    contribution: &AggregateContribution,
+    pair: &[AccountInfo],
+) -> Result<u8, ProgramError> {
+    let (provider_account, provider_vault) = (&pair[0], &pair[1]);
+    if !provider_account.is_writable || !provider_vault.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    let provider_authority = Pubkey::new_from_array(contribution.provider);
+    let (expected_provider, _provider_bump) = provider_pda(program_id, &provider_authority);
+    if provider_account.key != &expected_provider {
+        return Err(EntropyError::WrongProvider.into());
+    }
+
+    let (expected_vault, vault_bump) = provider_vault_pda(program_id, &provider_authority);
+    if provider_vault.key != &expected_vault {
+        return Err(EntropyError::WrongProviderVault.into());
+    }
+    if provider_vault.owner != &system_program::ID || provider_vault.data_len() != 0 {
+        return Err(EntropyError::InvalidVault.into());
+    }
+    Ok(vault_bump)
+}
+
+/// Releases an unrevealed contribution's sequence number from its provider's
+/// outstanding requests and refunds its fee to `refund_account`. `pair` has
+/// been checked by [`check_provider_accounts`].
+fn drop_unrevealed_contribution<'info>(
+    program_id: &Pubkey,
+    contribution: &AggregateContribution,
+    pair: &[AccountInfo<'info>],
+    vault_bump: u8,
+    refund_account: &AccountInfo<'info>,
+    system_program_account: &AccountInfo<'info>,
+) -> ProgramResult {
+    {
+        let mut provider = load_account_mut::<Provider>(&pair[0], program_id)?;
+        provider.outstanding_requests = provider.outstanding_requests.saturating_sub(1);
+        provider.release_fee(contribution.provider_fee);
+    }
+    refund_provider_fee(
+        &pair[1],
+        refund_account,
+        system_program_account,
+        &Pubkey::new_from_array(contribution.provider),
+        vault_bump,
+        contribution.provider_fee,
+    )
+}
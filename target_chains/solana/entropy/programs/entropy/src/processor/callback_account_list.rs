@@ -0,0 +1,168 @@
+use std::cell::Ref;
+
+#[allow(deprecated)]
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{
+    accounts::{CallbackAccountList, Request},
+    constants::{CALLBACK_ACCOUNT_LIST_SEED, MAX_CALLBACK_ACCOUNTS, MAX_CALLBACK_ACCOUNT_LIST_LEN},
+    discriminator::callback_account_list_discriminator,
+    error::EntropyError,
+    instruction::WriteCallbackAccountListArgs,
+    pda::callback_account_list_pda,
+    pda_loader::{init_pda_mut, load_account, load_account_mut},
+    processor::{close_request_account, parse_args},
+};
+
+/// Appends accounts to the callback account list of `request_account`,
+/// creating the list on first use. Only the request account's keypair may
+/// write its list, and only before the request is made, so a request's
+/// callback accounts are fixed once it exists.
+pub fn process_write_callback_account_list(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<WriteCallbackAccountListArgs>(data)?;
+
+    let mut account_info_iter = accounts.iter();
+    let payer = next_account_info(&mut account_info_iter)?;
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let list_account = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+
+    if !payer.is_signer || !request_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !payer.is_writable || !list_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+    check_request_not_made(request_account)?;
+
+    let (expected_list, list_bump) = callback_account_list_pda(program_id, request_account.key);
+    if list_account.key != &expected_list {
+        return Err(EntropyError::WrongCallbackAccountList.into());
+    }
+
+    let num_accounts = usize::from(args.num_accounts);
+    if num_accounts > MAX_CALLBACK_ACCOUNTS {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let new_accounts = &args.callback_accounts[..num_accounts];
+    if new_accounts
+        .iter()
+        .any(|meta| meta.is_signer > 1 || meta.is_writable > 1)
+    {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let entropy_program_id = program_id.to_bytes();
+    if new_accounts
+        .iter()
+        .any(|meta| meta.pubkey == entropy_program_id)
+    {
+        return Err(EntropyError::EntropyInCallbackAccounts.into());
+    }
+
+    let mut list = if list_account.owner == program_id {
+        load_account_mut::<CallbackAccountList>(list_account, program_id)?
+    } else {
+        let mut list = init_pda_mut::<CallbackAccountList>(
+            program_id,
+            payer,
+            list_account,
+            system_program_account,
+            &[
+                CALLBACK_ACCOUNT_LIST_SEED,
+                request_account.key.as_ref(),
+                &[list_bump],
+            ],
+            CallbackAccountList::LEN,
+        )?;
+        list.discriminator = callback_account_list_discriminator();
+        list.request_account = request_account.key.to_bytes();
+        list.bump = list_bump;
+        list
+    };
+
+    let start = usize::from(list.num_accounts);
+    let end = start
+        .checked_add(num_accounts)
+        .filter(|end| *end <= MAX_CALLBACK_ACCOUNT_LIST_LEN)
+        .ok_or(EntropyError::TooManyCallbackAccounts)?;
+    list.callback_accounts[start..end].copy_from_slice(new_accounts);
+    list.num_accounts = end as u8;
+    Ok(())
+}
+
+/// Closes the callback account list of a request that was never made,
+/// returning its rent to `recipient`. Lists of requests that were made are
+/// closed with the request instead.
+pub fn process_close_callback_account_list(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if !data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let list_account = next_account_info(&mut account_info_iter)?;
+    let recipient = next_account_info(&mut account_info_iter)?;
+
+    if !request_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !list_account.is_writable || !recipient.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+    check_request_not_made(request_account)?;
+
+    let list = load_account::<CallbackAccountList>(list_account, program_id)?;
+    if list.request_account != request_account.key.to_bytes() {
+        return Err(EntropyError::WrongCallbackAccountList.into());
+    }
+
+    drop(list);
+    close_request_account(list_account, recipient)
+}
+
+/// Loads the callback account list `request` recorded, for the reveal or
+/// close of the request at `request_account`.
+pub(super) fn load_callback_account_list<'a>(
+    program_id: &Pubkey,
+    request_account: &AccountInfo,
+    request: &Request,
+    list_account: &'a AccountInfo,
+) -> Result<Ref<'a, CallbackAccountList>, ProgramError> {
+    if list_account.key.to_bytes() != request.callback_account_list {
+        return Err(EntropyError::WrongCallbackAccountList.into());
+    }
+    if !list_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+    let list = load_account::<CallbackAccountList>(list_account, program_id)?;
+    if list.request_account != request_account.key.to_bytes() {
+        return Err(EntropyError::WrongCallbackAccountList.into());
+    }
+    Ok(list)
+}
+
+/// A request account still owned by the system program and empty has not
+/// been used for a request, or its request was closed.
+fn check_request_not_made(request_account: &AccountInfo) -> ProgramResult {
+    if request_account.owner != &system_program::ID || request_account.data_len() != 0 {
+        return Err(EntropyError::RequestAccountNotEmpty.into());
+    }
+    Ok(())
+}
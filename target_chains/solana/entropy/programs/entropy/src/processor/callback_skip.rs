@@ -0,0 +1,336 @@
+#[allow(deprecated)]
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::{clock::Clock, slot_hashes, Sysvar},
+};
+
+use crate::{
+    accounts::{CallbackFailureStats, CallbackStatus, Provider, Request},
+    constants::{CALLBACK_FAILURES_SEED, MIN_CALLBACK_SKIP_MAX_FAILURES},
+    discriminator::callback_failure_stats_discriminator,
+    error::EntropyError,
+    events::{emit_event, RandomnessRevealed},
+    instruction::{RevealSkippingCallbackArgs, SetCallbackSkipPolicyArgs},
+    pda::{callback_failure_stats_pda, provider_pda, provider_pda_with_bump, provider_vault_pda},
+    pda_loader::{init_pda_mut, load_account, load_account_mut},
+    processor::{
+        callback_account_list::load_callback_account_list,
+        close_expired_request::refund_provider_fee, close_request_account, parse_args,
+        reveal_with_callback::reveal_request,
+    },
+};
+
+/// Sets the provider's callback skip policy: once a requester program has
+/// `max_failures` callback failures reported within `window_slots`, more than
+/// its callbacks that succeeded there, the provider may reveal its failed
+/// requests with `RevealSkippingCallback`.
+pub fn process_set_callback_skip_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<SetCallbackSkipPolicyArgs>(data)?;
+
+    let mut account_info_iter = accounts.iter();
+    let provider_authority = next_account_info(&mut account_info_iter)?;
+    let provider_account = next_account_info(&mut account_info_iter)?;
+
+    if !provider_authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !provider_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    let (expected_provider, _provider_bump) = provider_pda(program_id, provider_authority.key);
+    if provider_account.key != &expected_provider {
+        return Err(EntropyError::WrongProvider.into());
+    }
+    let mut provider = load_account_mut::<Provider>(provider_account, program_id)?;
+    if provider.provider_authority != provider_authority.key.to_bytes() {
+        return Err(EntropyError::ProviderAuthorityMismatch.into());
+    }
+
+    if args.max_failures != 0
+        && (args.window_slots == 0 || args.max_failures < MIN_CALLBACK_SKIP_MAX_FAILURES)
+    {
+        return Err(EntropyError::InvalidCallbackSkipPolicy.into());
+    }
+
+    provider.callback_skip_max_failures = args.max_failures;
+    provider.callback_skip_window_slots = args.window_slots;
+    Ok(())
+}
+
+/// Counts a failed callback of one of the provider's pending requests
+/// against its requester program and marks the request
+/// [`CallbackStatus::Failed`]. The runtime rolls back everything a failed
+/// reveal did, so the provider reports the failure it saw when simulating or
+/// sending the reveal; the request must still be revealable, and each request
+/// is counted once. Nothing checks that the callback really failed: the skip
+/// policy takes the provider's word for it.
+pub fn process_report_callback_failure(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if !data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let provider_authority = next_account_info(&mut account_info_iter)?;
+    let provider_account = next_account_info(&mut account_info_iter)?;
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let stats_account = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+
+    if !provider_authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !provider_authority.is_writable || !request_account.is_writable || !stats_account.is_writable
+    {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let (expected_provider, _provider_bump) = provider_pda(program_id, provider_authority.key);
+    if provider_account.key != &expected_provider {
+        return Err(EntropyError::WrongProvider.into());
+    }
+    let provider = load_account::<Provider>(provider_account, program_id)?;
+    if provider.provider_authority != provider_authority.key.to_bytes() {
+        return Err(EntropyError::ProviderAuthorityMismatch.into());
+    }
+
+    let mut request = load_account_mut::<Request>(request_account, program_id)?;
+    if request.provider != provider_authority.key.to_bytes() {
+        return Err(EntropyError::WrongProvider.into());
+    }
+    let slot = Clock::get()?.slot;
+    check_callback_revealable(&request, CallbackStatus::NotStarted, slot)?;
+    request.callback_status = CallbackStatus::Failed.into();
+
+    let requester_program_id = Pubkey::new_from_array(request.requester_program_id);
+    let (expected_stats, stats_bump) =
+        callback_failure_stats_pda(program_id, provider_authority.key, &requester_program_id);
+    if stats_account.key != &expected_stats {
+        return Err(EntropyError::WrongCallbackFailureStats.into());
+    }
+
+    let mut stats = if stats_account.owner == &system_program::ID {
+        let mut stats = init_pda_mut::<CallbackFailureStats>(
+            program_id,
+            provider_authority,
+            stats_account,
+            system_program_account,
+            &[
+                CALLBACK_FAILURES_SEED,
+                provider_authority.key.as_ref(),
+                requester_program_id.as_ref(),
+                &[stats_bump],
+            ],
+            CallbackFailureStats::LEN,
+        )?;
+        stats.discriminator = callback_failure_stats_discriminator();
+        stats.provider_authority = provider_authority.key.to_bytes();
+        stats.requester_program_id = requester_program_id.to_bytes();
+        stats.bump = stats_bump;
+        stats
+    } else {
+        load_account_mut::<CallbackFailureStats>(stats_account, program_id)?
+    };
+
+    stats.record_failure(slot, provider.callback_skip_window_slots);
+    Ok(())
+}
+
+/// Reveals a callback request reported with `ReportCallbackFailure` without
+/// invoking its callback, when the provider's skip policy allows it for the
+/// requester program. The request
+/// and its callback account list are closed to its refund address, which
+/// also gets the provider fee back, as for an expired request.
+pub fn process_reveal_skipping_callback(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = &parse_args::<RevealSkippingCallbackArgs>(data)?.0;
+
+    let mut account_info_iter = accounts.iter();
+    let provider_authority = next_account_info(&mut account_info_iter)?;
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let provider_account = next_account_info(&mut account_info_iter)?;
+    let slot_hashes_account = next_account_info(&mut account_info_iter)?;
+    let stats_account = next_account_info(&mut account_info_iter)?;
+    let provider_vault = next_account_info(&mut account_info_iter)?;
+    let refund_account = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+
+    if !provider_authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !request_account.is_writable
+        || !provider_account.is_writable
+        || !provider_vault.is_writable
+        || !refund_account.is_writable
+    {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+    if slot_hashes_account.key != &slot_hashes::ID {
+        return Err(EntropyError::WrongSlotHashes.into());
+    }
+
+    let request = load_account::<Request>(request_account, program_id)?;
+    let list_account = if request.has_callback_account_list() {
+        let list_account = next_account_info(&mut account_info_iter)?;
+        load_callback_account_list(program_id, request_account, &request, list_account)?;
+        Some(list_account)
+    } else {
+        None
+    };
+
+    if request.provider != provider_authority.key.to_bytes() {
+        return Err(EntropyError::WrongProvider.into());
+    }
+    let reveal_slot = Clock::get()?.slot;
+    check_callback_revealable(&request, CallbackStatus::Failed, reveal_slot)?;
+
+    let (expected_provider, _provider_bump) =
+        provider_pda_with_bump(program_id, provider_authority.key, request.provider_bump);
+    if provider_account.key != &expected_provider {
+        return Err(EntropyError::WrongProvider.into());
+    }
+
+    let (expected_vault, vault_bump) = provider_vault_pda(program_id, provider_authority.key);
+    if provider_vault.key != &expected_vault {
+        return Err(EntropyError::WrongProviderVault.into());
+    }
+    if provider_vault.owner != &system_program::ID || provider_vault.data_len() != 0 {
+        return Err(EntropyError::InvalidVault.into());
+    }
+
+    if refund_account.key.to_bytes() != request.refund_address {
+        return Err(EntropyError::WrongPayer.into());
+    }
+
+    let requester_program_id = Pubkey::new_from_array(request.requester_program_id);
+    let (expected_stats, _stats_bump) =
+        callback_failure_stats_pda(program_id, provider_authority.key, &requester_program_id);
+    if stats_account.key != &expected_stats {
+        return Err(EntropyError::WrongCallbackFailureStats.into());
+    }
+    let stats = load_account::<CallbackFailureStats>(stats_account, program_id)?;
+
+    let mut provider = load_account_mut::<Provider>(provider_account, program_id)?;
+    if !provider.skips_callbacks(&stats, reveal_slot) {
+        return Err(EntropyError::CallbackSkipNotAllowed.into());
+    }
+    let random_number = reveal_request(
+        program_id,
+        &request,
+        &mut provider,
+        slot_hashes_account,
+        args,
+        reveal_slot,
+    )?;
+    drop(provider);
+
+    refund_provider_fee(
+        provider_vault,
+        refund_account,
+        system_program_account,
+        provider_authority.key,
+        vault_bump,
+        request.provider_fee,
+    )?;
+
+    emit_event(&RandomnessRevealed {
+        provider: request.provider,
+        requester_program_id: request.requester_program_id,
+        request_account: request_account.key.to_bytes(),
+        sequence_number: request.sequence_number,
+        random_number,
+        callback_invoked: 0,
+        _padding0: [0u8; 7],
+        request_slot: request.request_slot,
+        reveal_slot,
+    });
+
+    drop(request);
+    close_request_account(request_account, refund_account)?;
+    if let Some(list_account) = list_account {
+        close_request_account(list_account, refund_account)?;
+    }
+    Ok(())
+}
+
+/// Counts a successful callback of a request to `provider_authority` into
+/// `requester_program_id` in the stats account that followed the callback
+/// accounts. Every callback reveal carries the account, whatever the
+/// provider's policy, so the provider cannot leave successes uncounted and
+/// skip later on the failures it reported. A stats account that does not
+/// exist yet has nothing to count against.
+pub(super) fn record_callback(
+    program_id: &Pubkey,
+    provider: &Provider,
+    requester_program_id: &Pubkey,
+    stats_account: Option<&AccountInfo>,
+    slot: u64,
+) -> ProgramResult {
+    let Some(stats_account) = stats_account else {
+        return Err(EntropyError::WrongCallbackFailureStats.into());
+    };
+    let provider_authority = Pubkey::new_from_array(provider.provider_authority);
+
+    if stats_account.owner != program_id {
+        let (expected_stats, _stats_bump) =
+            callback_failure_stats_pda(program_id, &provider_authority, requester_program_id);
+        if stats_account.key != &expected_stats {
+            return Err(EntropyError::WrongCallbackFailureStats.into());
+        }
+        return Ok(());
+    }
+
+    if !stats_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+    // Only this program creates stats accounts, at the address derived from
+    // the two keys they store, so matching them identifies the PDA.
+    let mut stats = load_account_mut::<CallbackFailureStats>(stats_account, program_id)?;
+    if stats.provider_authority != provider.provider_authority
+        || stats.requester_program_id != requester_program_id.to_bytes()
+    {
+        return Err(EntropyError::WrongCallbackFailureStats.into());
+    }
+    stats.record_callback(slot, provider.callback_skip_window_slots);
+    Ok(())
+}
+
+/// Checks that `request` has a callback to invoke, is in `status`, and could
+/// be revealed at `slot`.
+fn check_callback_revealable(
+    request: &Request,
+    status: CallbackStatus,
+    slot: u64,
+) -> ProgramResult {
+    if request.status()? != status || request.compute_unit_limit == 0 {
+        return Err(EntropyError::InvalidRevealCall.into());
+    }
+    if request.is_expired(slot) {
+        return Err(EntropyError::RequestExpired.into());
+    }
+    if !request.is_revealable_yet(slot) {
+        return Err(EntropyError::RevealTooEarly.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+#[allow(deprecated)]
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
+};
+
+use crate::{
+    accounts::{Provider, Request},
+    constants::PROVIDER_VAULT_SEED,
+    error::EntropyError,
+    pda::{provider_pda, provider_vault_pda},
+    pda_loader::{load_account, load_account_mut},
+    processor::{callback_account_list::load_callback_account_list, close_request_account},
+    vault::withdrawable_fees,
+};
+
+/// Closes a request whose TTL has passed without a reveal, with its callback
+/// account list if it has one, and refunds their rent and the provider fee
+/// to its refund address. Anyone may submit it: the lamports can only go to
+/// the recorded address.
+pub fn process_close_expired_request(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if !data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let refund_account = next_account_info(&mut account_info_iter)?;
+    let provider_account = next_account_info(&mut account_info_iter)?;
+    let provider_vault = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+
+    if !request_account.is_writable
+        || !refund_account.is_writable
+        || !provider_account.is_writable
+        || !provider_vault.is_writable
+    {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let request = load_account::<Request>(request_account, program_id)?;
+    let list_account = if request.has_callback_account_list() {
+        let list_account = next_account_info(&mut account_info_iter)?;
+        load_callback_account_list(program_id, request_account, &request, list_account)?;
+        Some(list_account)
+    } else {
+        None
+    };
+
+    if refund_account.key.to_bytes() != request.refund_address {
+        return Err(EntropyError::WrongPayer.into());
+    }
+
+    let provider_authority = Pubkey::new_from_array(request.provider);
+    let (expected_provider, _provider_bump) = provider_pda(program_id, &provider_authority);
+    if provider_account.key != &expected_provider {
+        return Err(EntropyError::WrongProvider.into());
+    }
+
+    let (expected_vault, vault_bump) = provider_vault_pda(program_id, &provider_authority);
+    if provider_vault.key != &expected_vault {
+        return Err(EntropyError::WrongProviderVault.into());
+    }
+    if provider_vault.owner != &system_program::ID || provider_vault.data_len() != 0 {
+        return Err(EntropyError::InvalidVault.into());
+    }
+
+    if !request.is_expired(Clock::get()?.slot) {
+        return Err(EntropyError::RequestNotExpired.into());
+    }
+
+    let mut provider = load_account_mut::<Provider>(provider_account, program_id)?;
+    provider.outstanding_requests = provider.outstanding_requests.saturating_sub(1);
+    provider.release_fee(request.provider_fee);
+    drop(provider);
+
+    refund_provider_fee(
+        provider_vault,
+        refund_account,
+        system_program_account,
+        &provider_authority,
+        vault_bump,
+        request.provider_fee,
+    )?;
+
+    drop(request);
+    close_request_account(request_account, refund_account)?;
+    if let Some(list_account) = list_account {
+        close_request_account(list_account, refund_account)?;
+    }
+    Ok(())
+}
+
+/// Refunds a provider fee from the provider's vault to `refund_account`.
+/// The fee was reserved against withdrawal when the request was made, so
+/// the vault holds it above its rent floor; a vault that does not fails with
+/// `ProviderVaultShort` rather than refunding less.
+pub(super) fn refund_provider_fee<'info>(
+    provider_vault: &AccountInfo<'info>,
+    refund_account: &AccountInfo<'info>,
+    system_program_account: &AccountInfo<'info>,
+    provider_authority: &Pubkey,
+    vault_bump: u8,
+    provider_fee: u64,
+) -> ProgramResult {
+    if provider_fee == 0 {
+        return Ok(());
+    }
+    if provider_fee > withdrawable_fees(provider_vault.lamports(), &Rent::get()?) {
+        return Err(EntropyError::ProviderVaultShort.into());
+    }
+
+    let transfer_ix =
+        system_instruction::transfer(provider_vault.key, refund_account.key, provider_fee);
+    invoke_signed(
+        &transfer_ix,
+        &[
+            provider_vault.clone(),
+            refund_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[
+            PROVIDER_VAULT_SEED,
+            provider_authority.as_ref(),
+            &[vault_bump],
+        ]],
+    )
+}
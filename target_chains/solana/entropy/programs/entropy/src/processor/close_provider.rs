@@ -0,0 +1,182 @@
+#[allow(deprecated)]
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+
+use crate::{
+    accounts::{Config, Provider, ProviderDirectoryPage},
+    constants::{PROVIDER_DIRECTORY_PAGE_LEN, PROVIDER_VAULT_SEED},
+    error::EntropyError,
+    pda::{config_pda, provider_directory_pda, provider_pda, provider_vault_pda},
+    pda_loader::{load_account, load_account_mut},
+};
+
+/// Retires a provider once no request references it. The provider account's
+/// rent and the whole vault, floor included, go back to the authority, and
+/// the authority leaves the provider directory so registering again lists it
+/// once.
+pub fn process_close_provider(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if !data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let provider_authority = next_account_info(&mut account_info_iter)?;
+    let provider_account = next_account_info(&mut account_info_iter)?;
+    let provider_vault = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+    let config_account = next_account_info(&mut account_info_iter)?;
+    let directory_account = next_account_info(&mut account_info_iter)?;
+    let last_directory_account = next_account_info(&mut account_info_iter)?;
+
+    if !provider_authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !provider_authority.is_writable
+        || !provider_account.is_writable
+        || !provider_vault.is_writable
+    {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let (expected_provider, _provider_bump) = provider_pda(program_id, provider_authority.key);
+    if provider_account.key != &expected_provider {
+        return Err(EntropyError::WrongProvider.into());
+    }
+
+    let (expected_vault, vault_bump) = provider_vault_pda(program_id, provider_authority.key);
+    if provider_vault.key != &expected_vault {
+        return Err(EntropyError::WrongProviderVault.into());
+    }
+    if provider_vault.owner != &system_program::ID || provider_vault.data_len() != 0 {
+        return Err(EntropyError::InvalidVault.into());
+    }
+
+    let listed = {
+        let provider = load_account::<Provider>(provider_account, program_id)?;
+        if provider.provider_authority != provider_authority.key.to_bytes() {
+            return Err(EntropyError::ProviderAuthorityMismatch.into());
+        }
+        if provider.outstanding_requests != 0 || provider.reserved_fee_lamports != 0 {
+            return Err(EntropyError::ProviderHasOutstandingRequests.into());
+        }
+        provider.in_provider_directory == 1
+    };
+    if listed {
+        unlist_provider(
+            program_id,
+            provider_authority.key,
+            config_account,
+            directory_account,
+            last_directory_account,
+        )?;
+    }
+
+    let vault_lamports = provider_vault.lamports();
+    if vault_lamports > 0 {
+        let transfer_ix = system_instruction::transfer(
+            provider_vault.key,
+            provider_authority.key,
+            vault_lamports,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                provider_vault.clone(),
+                provider_authority.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[
+                PROVIDER_VAULT_SEED,
+                provider_authority.key.as_ref(),
+                &[vault_bump],
+            ]],
+        )?;
+    }
+
+    // Wipe the data so the account cannot be loaded again before the runtime
+    // deletes it at the end of the transaction.
+    provider_account.try_borrow_mut_data()?.fill(0);
+    let authority_lamports = provider_authority
+        .lamports()
+        .checked_add(provider_account.lamports())
+        .ok_or(ProgramError::InvalidArgument)?;
+    **provider_account.try_borrow_mut_lamports()? = 0;
+    **provider_authority.try_borrow_mut_lamports()? = authority_lamports;
+
+    Ok(())
+}
+
+/// Removes `provider_authority` from the provider directory page
+/// `directory_account`, moving the last listed authority, from
+/// `last_directory_account`, into its place. An emptied last page is kept
+/// for the next provider listed.
+fn unlist_provider(
+    program_id: &Pubkey,
+    provider_authority: &Pubkey,
+    config_account: &AccountInfo,
+    directory_account: &AccountInfo,
+    last_directory_account: &AccountInfo,
+) -> ProgramResult {
+    if !config_account.is_writable
+        || !directory_account.is_writable
+        || !last_directory_account.is_writable
+    {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    let (expected_config, _config_bump) = config_pda(program_id);
+    if config_account.key != &expected_config {
+        return Err(EntropyError::WrongConfig.into());
+    }
+    let mut config = load_account_mut::<Config>(config_account, program_id)?;
+    let last_index = config
+        .provider_directory_len
+        .checked_sub(1)
+        .ok_or(EntropyError::WrongProviderDirectory)?;
+    let last_page = last_index / PROVIDER_DIRECTORY_PAGE_LEN as u32;
+    let (expected_last_directory, _last_directory_bump) =
+        provider_directory_pda(program_id, last_page);
+    if last_directory_account.key != &expected_last_directory {
+        return Err(EntropyError::WrongProviderDirectory.into());
+    }
+
+    let last_slot = last_index as usize % PROVIDER_DIRECTORY_PAGE_LEN;
+    let last_provider = {
+        let mut last_directory =
+            load_account_mut::<ProviderDirectoryPage>(last_directory_account, program_id)?;
+        let last_provider = last_directory.providers[last_slot];
+        last_directory.providers[last_slot] = [0u8; 32];
+        last_directory.num_providers = last_slot as u8;
+        last_provider
+    };
+    config.provider_directory_len = last_index;
+    if last_provider == provider_authority.to_bytes() {
+        return Ok(());
+    }
+
+    // Pages are only created at their PDA, so a page that lists the
+    // authority is one of this deployment's.
+    let mut directory = load_account_mut::<ProviderDirectoryPage>(directory_account, program_id)?;
+    let slot = directory
+        .listed_providers()
+        .iter()
+        .position(|listed| listed == &provider_authority.to_bytes())
+        .ok_or(EntropyError::WrongProviderDirectory)?;
+    directory.providers[slot] = last_provider;
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program::set_return_data,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+
+use crate::constants::PROGRAM_VERSION;
+
+/// Returns [`PROGRAM_VERSION`] so clients can tell which instructions the
+/// deployed program supports. Takes no accounts and needs no signature, so
+/// it can be simulated with any fee payer.
+pub fn process_get_version(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if !data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    set_return_data(&[PROGRAM_VERSION]);
+    Ok(())
+}
@@ -0,0 +1,245 @@
+#[allow(deprecated)]
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+    sysvar::{rent::Rent, Sysvar},
+};
+
+use crate::{
+    accounts::{Config, GovernanceConfig},
+    constants::{GOVERNANCE_SEED, PYTH_FEE_VAULT_SEED, WORMHOLE_CHAIN_ID_SOLANA},
+    discriminator::governance_config_discriminator,
+    error::EntropyError,
+    events::{emit_event, DefaultProviderSet, PythFeeSet},
+    governance::{parse_posted_vaa, scaled_amount, GovernanceAction, GovernanceMessage},
+    instruction::InitializeGovernanceArgs,
+    pda::{config_pda, governance_pda, pyth_fee_vault_pda},
+    pda_loader::{init_pda_mut, load_account, load_account_mut},
+    processor::parse_args,
+    program_data::authorize_admin,
+    vault::withdrawable_fees,
+};
+
+pub fn process_initialize_governance(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<InitializeGovernanceArgs>(data)?;
+
+    if args.wormhole_program == [0u8; 32] || args.emitter_address == [0u8; 32] {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let admin = next_account_info(&mut account_info_iter)?;
+    let payer = next_account_info(&mut account_info_iter)?;
+    let config_account = next_account_info(&mut account_info_iter)?;
+    let governance_account = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+    let program_data = account_info_iter.next();
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !payer.is_writable || !governance_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let (expected_config, _config_bump) = config_pda(program_id);
+    if config_account.key != &expected_config {
+        return Err(EntropyError::WrongConfig.into());
+    }
+
+    let config = load_account::<Config>(config_account, program_id)?;
+    authorize_admin(program_id, &config, admin, program_data)?;
+
+    let (expected_governance, governance_bump) = governance_pda(program_id);
+    if governance_account.key != &expected_governance {
+        return Err(EntropyError::WrongGovernance.into());
+    }
+
+    let mut governance = init_pda_mut::<GovernanceConfig>(
+        program_id,
+        payer,
+        governance_account,
+        system_program_account,
+        &[GOVERNANCE_SEED, &[governance_bump]],
+        GovernanceConfig::LEN,
+    )?;
+
+    *governance = GovernanceConfig {
+        discriminator: governance_config_discriminator(),
+        wormhole_program: args.wormhole_program,
+        emitter_address: args.emitter_address,
+        emitter_chain: args.emitter_chain,
+        _padding0: [0u8; 6],
+        last_executed_sequence: 0,
+        bump: governance_bump,
+        _padding1: [0u8; 7],
+    };
+
+    Ok(())
+}
+
+pub fn process_governance(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if !data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let config_account = next_account_info(&mut account_info_iter)?;
+    let governance_account = next_account_info(&mut account_info_iter)?;
+    let posted_vaa_account = next_account_info(&mut account_info_iter)?;
+
+    if !config_account.is_writable || !governance_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    let (expected_config, _config_bump) = config_pda(program_id);
+    if config_account.key != &expected_config {
+        return Err(EntropyError::WrongConfig.into());
+    }
+
+    let (expected_governance, _governance_bump) = governance_pda(program_id);
+    if governance_account.key != &expected_governance {
+        return Err(EntropyError::WrongGovernance.into());
+    }
+
+    let mut governance = load_account_mut::<GovernanceConfig>(governance_account, program_id)?;
+
+    // Only the core bridge can create accounts it owns, and it only posts
+    // VAAs whose guardian signatures it has verified.
+    if posted_vaa_account.owner.to_bytes() != governance.wormhole_program {
+        return Err(EntropyError::InvalidVaa.into());
+    }
+
+    let vaa_data = posted_vaa_account.data.borrow();
+    let vaa = parse_posted_vaa(&vaa_data)?;
+
+    if vaa.emitter_chain != governance.emitter_chain
+        || vaa.emitter_address != governance.emitter_address
+    {
+        return Err(EntropyError::InvalidGovernanceDataSource.into());
+    }
+
+    if vaa.sequence <= governance.last_executed_sequence {
+        return Err(EntropyError::OldGovernanceMessage.into());
+    }
+
+    let message = GovernanceMessage::parse(vaa.payload)?;
+    if message.target_chain_id != 0 && message.target_chain_id != WORMHOLE_CHAIN_ID_SOLANA {
+        return Err(EntropyError::InvalidGovernanceTarget.into());
+    }
+
+    governance.last_executed_sequence = vaa.sequence;
+
+    let mut config = load_account_mut::<Config>(config_account, program_id)?;
+    match message.action {
+        GovernanceAction::SetFee { value, expo } => {
+            let new_fee_lamports =
+                scaled_amount(value, expo).ok_or(EntropyError::InvalidGovernanceMessage)?;
+            emit_event(&PythFeeSet {
+                old_fee_lamports: config.pyth_fee_lamports,
+                new_fee_lamports,
+            });
+            config.pyth_fee_lamports = new_fee_lamports;
+        }
+        GovernanceAction::SetAdmin { new_admin } => {
+            if new_admin == [0u8; 32] {
+                return Err(EntropyError::InvalidGovernanceMessage.into());
+            }
+            config.admin = new_admin;
+            config.proposed_admin = [0u8; 32];
+        }
+        GovernanceAction::SetDefaultProvider { provider } => {
+            if provider == [0u8; 32] {
+                return Err(EntropyError::InvalidGovernanceMessage.into());
+            }
+            emit_event(&DefaultProviderSet {
+                old_default_provider: config.default_provider,
+                new_default_provider: provider,
+            });
+            config.default_provider = provider;
+        }
+        GovernanceAction::SetPermissionedProviders { permissioned } => {
+            config.permissioned_providers = u8::from(permissioned);
+        }
+        GovernanceAction::WithdrawFee {
+            target,
+            value,
+            expo,
+        } => {
+            let amount =
+                scaled_amount(value, expo).ok_or(EntropyError::InvalidGovernanceMessage)?;
+            let pyth_fee_vault = next_account_info(&mut account_info_iter)?;
+            let recipient = next_account_info(&mut account_info_iter)?;
+            let system_program_account = next_account_info(&mut account_info_iter)?;
+            withdraw_pyth_fees(
+                program_id,
+                pyth_fee_vault,
+                recipient,
+                system_program_account,
+                &target,
+                amount,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn withdraw_pyth_fees<'info>(
+    program_id: &Pubkey,
+    pyth_fee_vault: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    system_program_account: &AccountInfo<'info>,
+    target: &[u8; 32],
+    amount: u64,
+) -> ProgramResult {
+    if !pyth_fee_vault.is_writable || !recipient.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let (expected_fee_vault, fee_vault_bump) = pyth_fee_vault_pda(program_id);
+    if pyth_fee_vault.key != &expected_fee_vault {
+        return Err(EntropyError::WrongPythFeeVault.into());
+    }
+
+    if recipient.key.to_bytes() != *target {
+        return Err(EntropyError::WrongRecipient.into());
+    }
+
+    let available = withdrawable_fees(pyth_fee_vault.lamports(), &Rent::get()?);
+    if amount > available {
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    let transfer_ix = system_instruction::transfer(pyth_fee_vault.key, recipient.key, amount);
+    invoke_signed(
+        &transfer_ix,
+        &[
+            pyth_fee_vault.clone(),
+            recipient.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[PYTH_FEE_VAULT_SEED, &[fee_vault_bump]]],
+    )
+}
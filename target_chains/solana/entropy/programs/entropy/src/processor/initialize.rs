@@ -9,13 +9,14 @@ use solana_program::{
 
 use crate::{
     accounts::Config,
-    constants::CONFIG_SEED,
+    constants::{CONFIG_SEED, PROGRAM_VERSION},
     discriminator::config_discriminator,
     error::EntropyError,
-    instruction::InitializeArgs,
-    pda::{config_pda, pyth_fee_vault_pda},
-    pda_loader::init_pda_mut,
+    instruction::{InitializeArgs, InitializeIfNeededArgs},
+    pda::{config_pda, entropy_signer_pda, pyth_fee_vault_pda},
+    pda_loader::{init_pda_mut, load_account},
     processor::parse_args,
+    program_data::load_upgrade_authority,
     vault::init_vault_pda,
 };
 
@@ -25,7 +26,27 @@ pub fn process_initialize(
     data: &[u8],
 ) -> ProgramResult {
     let args = parse_args::<InitializeArgs>(data)?;
+    initialize(program_id, accounts, args, false)
+}
+
+/// `Initialize` that leaves an existing config holding the same arguments
+/// alone, and fails with `ConfigMismatch` if it holds others. Admin and fee
+/// changes made since initialization count as a mismatch.
+pub fn process_initialize_if_needed(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<InitializeIfNeededArgs>(data)?;
+    initialize(program_id, accounts, &args.0, true)
+}
 
+fn initialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: &InitializeArgs,
+    if_needed: bool,
+) -> ProgramResult {
     if args.admin == [0u8; 32] || args.default_provider == [0u8; 32] {
         return Err(ProgramError::InvalidArgument);
     }
@@ -35,31 +56,48 @@ pub fn process_initialize(
     let config_account = next_account_info(&mut account_info_iter)?;
     let pyth_fee_vault = next_account_info(&mut account_info_iter)?;
     let system_program_account = next_account_info(&mut account_info_iter)?;
+    let program_data = next_account_info(&mut account_info_iter)?;
 
     if !payer.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    // Only the deployer may initialize, so nobody can front-run it and
+    // install themselves as admin.
+    if load_upgrade_authority(program_id, program_data)? != Some(*payer.key) {
+        return Err(EntropyError::Unauthorized.into());
+    }
+
     if !payer.is_writable || !config_account.is_writable || !pyth_fee_vault.is_writable {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::AccountNotWritable.into());
     }
 
     if system_program_account.key != &system_program::ID {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::WrongSystemProgram.into());
     }
 
     let (expected_config, config_bump) = config_pda(program_id);
     if config_account.key != &expected_config {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongConfig.into());
     }
 
     let (expected_fee_vault, _fee_vault_bump) = pyth_fee_vault_pda(program_id);
     if pyth_fee_vault.key != &expected_fee_vault {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongPythFeeVault.into());
     }
 
     if config_account.owner != &system_program::ID || config_account.data_len() != 0 {
-        return Err(EntropyError::InvalidAccount.into());
+        if !if_needed {
+            return Err(EntropyError::AccountAlreadyInitialized.into());
+        }
+        let config = load_account::<Config>(config_account, program_id)?;
+        if config.admin != args.admin
+            || config.pyth_fee_lamports != args.pyth_fee_lamports
+            || config.default_provider != args.default_provider
+        {
+            return Err(EntropyError::ConfigMismatch.into());
+        }
+        return Ok(());
     }
 
     let mut config = init_pda_mut::<Config>(
@@ -81,7 +119,10 @@ pub fn process_initialize(
         proposed_admin: [0u8; 32],
         seed: [0u8; 32],
         bump: config_bump,
-        _padding0: [0u8; 7],
+        permissioned_providers: 0,
+        entropy_signer_bump: entropy_signer_pda(program_id).1,
+        program_version: PROGRAM_VERSION,
+        provider_directory_len: 0,
     };
 
     Ok(())
@@ -1,28 +1,70 @@
+mod admin;
+mod aggregate;
+mod callback_account_list;
+mod callback_skip;
+mod close_expired_request;
+mod close_provider;
+mod get_version;
+mod governance;
 mod initialize;
+mod preview_request;
+mod refresh_keeper_lease;
 mod register_provider;
 mod request;
 mod reveal_with_callback;
+mod withdraw_provider_fees;
 
-use bytemuck::{try_from_bytes, Pod};
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
     pubkey::Pubkey,
 };
 
 use self::{
-    initialize::process_initialize,
+    admin::{
+        process_claim_admin, process_set_default_provider, process_set_program_registry,
+        process_set_provider_allowlist, process_set_pyth_fee,
+    },
+    aggregate::{
+        process_close_expired_aggregate_request, process_request_aggregate,
+        process_reveal_aggregate,
+    },
+    callback_account_list::{
+        process_close_callback_account_list, process_write_callback_account_list,
+    },
+    callback_skip::{
+        process_report_callback_failure, process_reveal_skipping_callback,
+        process_set_callback_skip_policy,
+    },
+    close_expired_request::process_close_expired_request,
+    close_provider::process_close_provider,
+    get_version::process_get_version,
+    governance::{process_governance, process_initialize_governance},
+    initialize::{process_initialize, process_initialize_if_needed},
+    preview_request::process_preview_request,
+    refresh_keeper_lease::process_refresh_keeper_lease,
     register_provider::process_register_provider,
-    request::{process_request, process_request_with_callback},
+    request::{process_request, process_request_v2, process_request_with_callback},
     reveal_with_callback::process_reveal_with_callback,
+    withdraw_provider_fees::process_withdraw_provider_fees,
 };
+pub(crate) use crate::instruction::parse_args;
 use crate::{error::EntropyError, instruction::EntropyInstruction};
 
-pub(crate) fn parse_args<T: Pod>(data: &[u8]) -> Result<&T, ProgramError> {
-    if data.len() != core::mem::size_of::<T>() {
-        return Err(ProgramError::InvalidInstructionData);
-    }
+/// Moves all of the request account's lamports to `refund_account`; the
+/// runtime then deletes the emptied account.
+pub(crate) fn close_request_account(
+    request_account: &AccountInfo,
+    refund_account: &AccountInfo,
+) -> ProgramResult {
+    let lamports = request_account.lamports();
+    let refund_lamports = refund_account
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::InvalidArgument)?;
 
-    try_from_bytes::<T>(data).map_err(|_| ProgramError::InvalidInstructionData)
+    **request_account.try_borrow_mut_lamports()? = 0;
+    **refund_account.try_borrow_mut_lamports()? = refund_lamports;
+    Ok(())
 }
 
 pub fn process_instruction(
@@ -46,7 +88,62 @@ pub fn process_instruction(
         }
         EntropyInstruction::AdvanceProviderCommitment => Err(EntropyError::NotImplemented.into()),
         EntropyInstruction::UpdateProviderConfig => Err(EntropyError::NotImplemented.into()),
-        EntropyInstruction::WithdrawProviderFees => Err(EntropyError::NotImplemented.into()),
-        EntropyInstruction::Governance => Err(EntropyError::NotImplemented.into()),
+        EntropyInstruction::WithdrawProviderFees => {
+            process_withdraw_provider_fees(program_id, accounts, payload)
+        }
+        EntropyInstruction::Governance => process_governance(program_id, accounts, payload),
+        EntropyInstruction::InitializeGovernance => {
+            process_initialize_governance(program_id, accounts, payload)
+        }
+        EntropyInstruction::ClaimAdmin => process_claim_admin(program_id, accounts, payload),
+        EntropyInstruction::SetPythFee => process_set_pyth_fee(program_id, accounts, payload),
+        EntropyInstruction::SetDefaultProvider => {
+            process_set_default_provider(program_id, accounts, payload)
+        }
+        EntropyInstruction::CloseExpiredRequest => {
+            process_close_expired_request(program_id, accounts, payload)
+        }
+        EntropyInstruction::CloseProvider => process_close_provider(program_id, accounts, payload),
+        EntropyInstruction::RequestV2 => process_request_v2(program_id, accounts, payload),
+        EntropyInstruction::RefreshKeeperLease => {
+            process_refresh_keeper_lease(program_id, accounts, payload)
+        }
+        EntropyInstruction::RequestAggregate => {
+            process_request_aggregate(program_id, accounts, payload)
+        }
+        EntropyInstruction::RevealAggregate => {
+            process_reveal_aggregate(program_id, accounts, payload)
+        }
+        EntropyInstruction::CloseExpiredAggregateRequest => {
+            process_close_expired_aggregate_request(program_id, accounts, payload)
+        }
+        EntropyInstruction::SetProgramRegistry => {
+            process_set_program_registry(program_id, accounts, payload)
+        }
+        EntropyInstruction::SetProviderAllowlist => {
+            process_set_provider_allowlist(program_id, accounts, payload)
+        }
+        EntropyInstruction::GetVersion => process_get_version(program_id, accounts, payload),
+        EntropyInstruction::InitializeIfNeeded => {
+            process_initialize_if_needed(program_id, accounts, payload)
+        }
+        EntropyInstruction::PreviewRequest => {
+            process_preview_request(program_id, accounts, payload)
+        }
+        EntropyInstruction::WriteCallbackAccountList => {
+            process_write_callback_account_list(program_id, accounts, payload)
+        }
+        EntropyInstruction::CloseCallbackAccountList => {
+            process_close_callback_account_list(program_id, accounts, payload)
+        }
+        EntropyInstruction::SetCallbackSkipPolicy => {
+            process_set_callback_skip_policy(program_id, accounts, payload)
+        }
+        EntropyInstruction::ReportCallbackFailure => {
+            process_report_callback_failure(program_id, accounts, payload)
+        }
+        EntropyInstruction::RevealSkippingCallback => {
+            process_reveal_skipping_callback(program_id, accounts, payload)
+        }
     }
 }
@@ -0,0 +1,42 @@
+use bytemuck::bytes_of;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::set_return_data,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    accounts::Provider,
+    instruction::{PreviewRequestArgs, RequestPreview},
+    pda_loader::load_account,
+    processor::{parse_args, request::reserve_sequence_number},
+    randomness::request_commitment,
+};
+
+/// Returns the [`RequestPreview`] of a request to the provider with the
+/// given user commitment, so clients can check what their request will
+/// commit to before sending it. Reserves the sequence number on a copy of
+/// the provider, so it fails exactly when such a request would for lack of
+/// randomness.
+pub fn process_preview_request(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<PreviewRequestArgs>(data)?;
+
+    let mut account_info_iter = accounts.iter();
+    let provider_account = next_account_info(&mut account_info_iter)?;
+
+    let mut provider = *load_account::<Provider>(provider_account, program_id)?;
+    let (sequence_number, num_hashes) = reserve_sequence_number(&mut provider)?;
+
+    set_return_data(bytes_of(&RequestPreview {
+        sequence_number,
+        num_hashes,
+        _padding0: [0u8; 4],
+        commitment: request_commitment(&args.user_commitment, &provider.current_commitment),
+    }));
+    Ok(())
+}
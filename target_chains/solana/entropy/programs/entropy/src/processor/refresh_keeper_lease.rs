@@ -0,0 +1,97 @@
+#[allow(deprecated)]
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{
+    accounts::{KeeperLease, Provider},
+    constants::KEEPER_LEASE_SEED,
+    discriminator::keeper_lease_discriminator,
+    error::EntropyError,
+    instruction::RefreshKeeperLeaseArgs,
+    pda::{keeper_lease_pda, provider_pda},
+    pda_loader::{init_pda_mut, load_account, load_account_mut},
+    processor::parse_args,
+};
+
+/// Claims or renews the provider's keeper lease for `args.keeper`. A lease
+/// held by another keeper can only be taken over once it is stale; a zero
+/// `lease_slots` releases it from the next slot on.
+pub fn process_refresh_keeper_lease(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<RefreshKeeperLeaseArgs>(data)?;
+
+    let mut account_info_iter = accounts.iter();
+    let provider_authority = next_account_info(&mut account_info_iter)?;
+    let provider_account = next_account_info(&mut account_info_iter)?;
+    let keeper_lease = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+
+    if !provider_authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !provider_authority.is_writable || !keeper_lease.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let (expected_provider, _provider_bump) = provider_pda(program_id, provider_authority.key);
+    if provider_account.key != &expected_provider {
+        return Err(EntropyError::WrongProvider.into());
+    }
+    {
+        let provider = load_account::<Provider>(provider_account, program_id)?;
+        if provider.provider_authority != provider_authority.key.to_bytes() {
+            return Err(EntropyError::ProviderAuthorityMismatch.into());
+        }
+    }
+
+    let (expected_lease, lease_bump) = keeper_lease_pda(program_id, provider_authority.key);
+    if keeper_lease.key != &expected_lease {
+        return Err(EntropyError::WrongKeeperLease.into());
+    }
+
+    let slot = Clock::get()?.slot;
+    let mut lease = if keeper_lease.owner == &system_program::ID {
+        let mut lease = init_pda_mut::<KeeperLease>(
+            program_id,
+            provider_authority,
+            keeper_lease,
+            system_program_account,
+            &[
+                KEEPER_LEASE_SEED,
+                provider_authority.key.as_ref(),
+                &[lease_bump],
+            ],
+            KeeperLease::LEN,
+        )?;
+        lease.discriminator = keeper_lease_discriminator();
+        lease.provider_authority = provider_authority.key.to_bytes();
+        lease.bump = lease_bump;
+        lease
+    } else {
+        let lease = load_account_mut::<KeeperLease>(keeper_lease, program_id)?;
+        if lease.keeper != args.keeper && !lease.is_stale(slot) {
+            return Err(EntropyError::KeeperLeaseHeld.into());
+        }
+        lease
+    };
+
+    lease.keeper = args.keeper;
+    lease.refreshed_slot = slot;
+    lease.lease_slots = args.lease_slots;
+
+    Ok(())
+}
@@ -8,13 +8,19 @@ use solana_program::{
 };
 
 use crate::{
-    accounts::Provider,
-    constants::PROVIDER_SEED,
-    discriminator::provider_discriminator,
+    accounts::{Config, Provider, ProviderAllowlist, ProviderDirectoryPage},
+    constants::{
+        COMMITMENT_METADATA_LEN, PROVIDER_DIRECTORY_PAGE_LEN, PROVIDER_DIRECTORY_SEED,
+        PROVIDER_SEED, URI_LEN,
+    },
+    discriminator::{provider_directory_page_discriminator, provider_discriminator},
     error::EntropyError,
     instruction::RegisterProviderArgs,
-    pda::{provider_pda, provider_vault_pda},
-    pda_loader::{init_pda_mut, load_account_mut},
+    pda::{
+        config_pda, provider_allowlist_pda, provider_directory_pda, provider_pda,
+        provider_vault_pda,
+    },
+    pda_loader::{grow_account, init_pda_mut, load_account, load_account_mut},
     processor::parse_args,
     vault::init_vault_pda,
 };
@@ -30,17 +36,32 @@ pub fn process_register_provider(
         return Err(ProgramError::InvalidArgument);
     }
 
-    if (args.commitment_metadata_len as usize) > crate::constants::COMMITMENT_METADATA_LEN
-        || (args.uri_len as usize) > crate::constants::URI_LEN
+    let commitment_metadata_len = usize::from(args.commitment_metadata_len);
+    let uri_len = usize::from(args.uri_len);
+    if commitment_metadata_len > COMMITMENT_METADATA_LEN
+        || uri_len > URI_LEN
+        || args.require_https > 1
     {
         return Err(ProgramError::InvalidInstructionData);
     }
 
+    // Indexers read the URI as a string, so it must be one.
+    let uri = &args.uri[..uri_len];
+    if uri.contains(&0)
+        || core::str::from_utf8(uri).is_err()
+        || (args.require_https == 1 && !uri.starts_with(b"https://"))
+    {
+        return Err(EntropyError::InvalidProviderUri.into());
+    }
+
     let mut account_info_iter = accounts.iter();
     let provider_authority = next_account_info(&mut account_info_iter)?;
     let provider_account = next_account_info(&mut account_info_iter)?;
     let provider_vault = next_account_info(&mut account_info_iter)?;
     let system_program_account = next_account_info(&mut account_info_iter)?;
+    let config_account = next_account_info(&mut account_info_iter)?;
+    let directory_account = next_account_info(&mut account_info_iter)?;
+    let allowlist_account = account_info_iter.next();
 
     if !provider_authority.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
@@ -50,21 +71,37 @@ pub fn process_register_provider(
         || !provider_account.is_writable
         || !provider_vault.is_writable
     {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::AccountNotWritable.into());
     }
 
     if system_program_account.key != &system_program::ID {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let (expected_config, _config_bump) = config_pda(program_id);
+    if config_account.key != &expected_config {
+        return Err(EntropyError::WrongConfig.into());
+    }
+    if load_account::<Config>(config_account, program_id)?.permissioned_providers == 1 {
+        // Permissionless deployments need not pass the allowlist at all.
+        let allowlist_account = allowlist_account.ok_or(EntropyError::ProviderNotAllowed)?;
+        if allowlist_account.key != &provider_allowlist_pda(program_id).0 {
+            return Err(EntropyError::WrongProviderAllowlist.into());
+        }
+        let allowlist = load_account::<ProviderAllowlist>(allowlist_account, program_id)?;
+        if !allowlist.allows(&provider_authority.key.to_bytes()) {
+            return Err(EntropyError::ProviderNotAllowed.into());
+        }
     }
 
     let (expected_provider, provider_bump) = provider_pda(program_id, provider_authority.key);
     if provider_account.key != &expected_provider {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongProvider.into());
     }
 
     let (expected_vault, _vault_bump) = provider_vault_pda(program_id, provider_authority.key);
     if provider_vault.key != &expected_vault {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongProviderVault.into());
     }
 
     let mut provider = if provider_account.owner == &system_program::ID {
@@ -81,9 +118,19 @@ pub fn process_register_provider(
             Provider::LEN,
         )?
     } else {
+        // Providers registered before the latency stats were added are
+        // smaller; re-registering migrates them.
+        if provider_account.owner == program_id {
+            grow_account(
+                provider_authority,
+                provider_account,
+                system_program_account,
+                Provider::LEN,
+            )?;
+        }
         let provider = load_account_mut::<Provider>(provider_account, program_id)?;
         if provider.provider_authority != provider_authority.key.to_bytes() {
-            return Err(EntropyError::InvalidAccount.into());
+            return Err(EntropyError::ProviderAuthorityMismatch.into());
         }
         provider
     };
@@ -98,10 +145,15 @@ pub fn process_register_provider(
     provider.original_commitment_sequence_number = provider.sequence_number;
     provider.current_commitment = args.commitment;
     provider.current_commitment_sequence_number = provider.sequence_number;
+    // Only the declared prefixes are copied; whatever the caller left past
+    // them, and any longer value from a previous registration, is zeroed.
     provider.commitment_metadata_len = args.commitment_metadata_len;
-    provider.commitment_metadata = args.commitment_metadata;
+    provider.commitment_metadata = [0u8; COMMITMENT_METADATA_LEN];
+    provider.commitment_metadata[..commitment_metadata_len]
+        .copy_from_slice(&args.commitment_metadata[..commitment_metadata_len]);
     provider.uri_len = args.uri_len;
-    provider.uri = args.uri;
+    provider.uri = [0u8; URI_LEN];
+    provider.uri[..uri_len].copy_from_slice(uri);
 
     provider.end_sequence_number = provider
         .sequence_number
@@ -111,5 +163,69 @@ pub fn process_register_provider(
 
     provider.bump = provider_bump;
 
+    if provider.in_provider_directory == 0 {
+        list_provider(
+            program_id,
+            provider_authority,
+            config_account,
+            directory_account,
+            system_program_account,
+        )?;
+        provider.in_provider_directory = 1;
+    }
+
+    Ok(())
+}
+
+/// Appends `provider_authority` to the provider directory, creating the
+/// page it lands in if it is the page's first entry and the page does not
+/// exist yet; `CloseProvider` can leave an emptied page behind.
+fn list_provider<'info>(
+    program_id: &Pubkey,
+    provider_authority: &AccountInfo<'info>,
+    config_account: &AccountInfo<'info>,
+    directory_account: &AccountInfo<'info>,
+    system_program_account: &AccountInfo<'info>,
+) -> ProgramResult {
+    if !config_account.is_writable || !directory_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    let mut config = load_account_mut::<Config>(config_account, program_id)?;
+    let page = config.provider_directory_next_page();
+    let (expected_directory, directory_bump) = provider_directory_pda(program_id, page);
+    if directory_account.key != &expected_directory {
+        return Err(EntropyError::WrongProviderDirectory.into());
+    }
+
+    let index = config.provider_directory_len as usize % PROVIDER_DIRECTORY_PAGE_LEN;
+    let mut directory = if index == 0 && directory_account.owner != program_id {
+        let mut directory = init_pda_mut::<ProviderDirectoryPage>(
+            program_id,
+            provider_authority,
+            directory_account,
+            system_program_account,
+            &[
+                PROVIDER_DIRECTORY_SEED,
+                &page.to_le_bytes(),
+                &[directory_bump],
+            ],
+            ProviderDirectoryPage::LEN,
+        )?;
+        directory.discriminator = provider_directory_page_discriminator();
+        directory.page = page;
+        directory.bump = directory_bump;
+        directory
+    } else {
+        load_account_mut::<ProviderDirectoryPage>(directory_account, program_id)?
+    };
+
+    directory.providers[index] = provider_authority.key.to_bytes();
+    directory.num_providers = index as u8 + 1;
+    config.provider_directory_len = config
+        .provider_directory_len
+        .checked_add(1)
+        .ok_or(ProgramError::InvalidArgument)?;
+
     Ok(())
 }
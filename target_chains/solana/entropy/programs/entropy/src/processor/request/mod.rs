@@ -1,10 +1,10 @@
 use std::cell::RefMut;
 
-use bytemuck::from_bytes_mut;
+use bytemuck::{from_bytes_mut, Pod};
 #[allow(deprecated)]
 use solana_program::{
     account_info::AccountInfo,
-    hash::hashv,
+    entrypoint::ProgramResult,
     program::invoke,
     program_error::ProgramError,
     pubkey::Pubkey,
@@ -13,17 +13,20 @@ use solana_program::{
 };
 
 use crate::{
-    accounts::{Config, Provider, Request},
-    constants::CALLBACK_NOT_NECESSARY,
+    accounts::{CallbackStatus, Config, Provider, Request},
     discriminator::request_discriminator,
     error::EntropyError,
+    events::{emit_event, RequestCreated},
     instruction::RequestArgs,
+    randomness::request_commitment,
 };
 
 #[allow(clippy::module_inception)]
 mod request;
+mod request_v2;
 mod request_with_callback;
 pub use request::process_request;
+pub use request_v2::process_request_v2;
 pub use request_with_callback::process_request_with_callback;
 
 #[allow(clippy::too_many_arguments)]
@@ -39,43 +42,21 @@ fn request_helper<'a, 'info>(
     pyth_fee_vault: &'a AccountInfo<'info>,
     system_program_account: &'a AccountInfo<'info>,
 ) -> Result<u64, ProgramError> {
-    // Assign a sequence number to the request
-    let sequence_number = provider.sequence_number;
-    if sequence_number >= provider.end_sequence_number {
-        return Err(EntropyError::OutOfRandomness.into());
-    }
-    provider.sequence_number = provider
-        .sequence_number
-        .checked_add(1)
-        .ok_or(ProgramError::InvalidArgument)?;
+    let (sequence_number, num_hashes) = reserve_sequence_number(provider)?;
 
-    // Calculate and transfer fees
+    // Calculate and transfer fees. The system program logs why a transfer
+    // failed; the error code only says which step.
     let provider_fee = provider.calculate_provider_fee(args.compute_unit_limit)?;
-    if provider_fee > 0 {
-        let transfer_ix = system_instruction::transfer(payer.key, provider_vault.key, provider_fee);
-        invoke(
-            &transfer_ix,
-            &[
-                payer.clone(),
-                provider_vault.clone(),
-                system_program_account.clone(),
-            ],
-        )?;
-    }
-    if config.pyth_fee_lamports > 0 {
-        let transfer_ix =
-            system_instruction::transfer(payer.key, pyth_fee_vault.key, config.pyth_fee_lamports);
-        invoke(
-            &transfer_ix,
-            &[
-                payer.clone(),
-                pyth_fee_vault.clone(),
-                system_program_account.clone(),
-            ],
-        )?;
-    }
+    transfer_fee(payer, provider_vault, system_program_account, provider_fee)?;
+    provider.reserve_fee(provider_fee)?;
+    transfer_fee(
+        payer,
+        pyth_fee_vault,
+        system_program_account,
+        config.pyth_fee_lamports,
+    )?;
 
-    let mut request = init_request_account_mut(
+    let mut request = init_request_account_mut::<Request>(
         program_id,
         payer,
         request_account,
@@ -85,38 +66,102 @@ fn request_helper<'a, 'info>(
 
     request.provider = provider.provider_authority;
     request.sequence_number = sequence_number;
-
-    let num_hashes = sequence_number
-        .checked_sub(provider.current_commitment_sequence_number)
-        .ok_or(ProgramError::InvalidArgument)?;
-    request.num_hashes = u32::try_from(num_hashes).map_err(|_| ProgramError::InvalidArgument)?;
-    if provider.max_num_hashes != 0 && request.num_hashes > provider.max_num_hashes {
-        return Err(EntropyError::LastRevealedTooOld.into());
-    }
-
-    request.commitment = hashv(&[&args.user_commitment, &provider.current_commitment]).to_bytes();
+    request.num_hashes = num_hashes;
+    request.commitment = request_commitment(&args.user_commitment, &provider.current_commitment);
     request.requester_program_id = requester_program.key.to_bytes();
     request.request_slot = Clock::get()?.slot;
+    request.ttl_slots = args.ttl_slots;
+    request.min_reveal_delay_slots = args.min_reveal_delay_slots;
     request.use_blockhash = args.use_blockhash;
-    request.callback_status = CALLBACK_NOT_NECESSARY;
+    request.random_number_version = args.random_number_version;
+    request.callback_status = CallbackStatus::NotNecessary.into();
     request.compute_unit_limit = if args.compute_unit_limit > provider.default_compute_unit_limit {
         args.compute_unit_limit
     } else {
         provider.default_compute_unit_limit
     };
     request.payer = payer.key.to_bytes();
+    request.refund_address = if args.refund_address == [0u8; 32] {
+        request.payer
+    } else {
+        args.refund_address
+    };
+    request.provider_fee = provider_fee;
+    request.provider_bump = provider.bump;
+    request.entropy_signer_bump = config.entropy_signer_bump;
     request.discriminator = request_discriminator();
 
     Ok(sequence_number)
 }
 
-fn init_request_account_mut<'a, 'info>(
+/// Assigns the provider's next sequence number to a new request and counts
+/// it as outstanding. Returns the sequence number and how many times its
+/// contribution must be hashed to reach the provider's current commitment.
+pub(super) fn reserve_sequence_number(provider: &mut Provider) -> Result<(u64, u32), ProgramError> {
+    let sequence_number = provider.sequence_number;
+    if sequence_number >= provider.end_sequence_number {
+        return Err(EntropyError::OutOfRandomness.into());
+    }
+    provider.sequence_number = provider
+        .sequence_number
+        .checked_add(1)
+        .ok_or(ProgramError::InvalidArgument)?;
+    provider.outstanding_requests = provider
+        .outstanding_requests
+        .checked_add(1)
+        .ok_or(ProgramError::InvalidArgument)?;
+
+    let num_hashes = sequence_number
+        .checked_sub(provider.current_commitment_sequence_number)
+        .ok_or(ProgramError::InvalidArgument)?;
+    let num_hashes = u32::try_from(num_hashes).map_err(|_| ProgramError::InvalidArgument)?;
+    if num_hashes > provider.num_hashes_cap() {
+        return Err(EntropyError::LastRevealedTooOld.into());
+    }
+    Ok((sequence_number, num_hashes))
+}
+
+/// Moves a request fee from the payer into `vault`; a no-op for zero.
+pub(super) fn transfer_fee<'info>(
+    payer: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    system_program_account: &AccountInfo<'info>,
+    lamports: u64,
+) -> ProgramResult {
+    if lamports == 0 {
+        return Ok(());
+    }
+    let transfer_ix = system_instruction::transfer(payer.key, vault.key, lamports);
+    invoke(
+        &transfer_ix,
+        &[payer.clone(), vault.clone(), system_program_account.clone()],
+    )
+    .map_err(|_| EntropyError::FeeTransferFailed.into())
+}
+
+fn emit_request_created(request_account: &Pubkey, request: &Request) {
+    emit_event(&RequestCreated {
+        provider: request.provider,
+        requester_program_id: request.requester_program_id,
+        request_account: request_account.to_bytes(),
+        payer: request.payer,
+        sequence_number: request.sequence_number,
+        request_slot: request.request_slot,
+        compute_unit_limit: request.compute_unit_limit,
+        use_blockhash: request.use_blockhash,
+        callback_status: request.callback_status,
+        random_number_version: request.random_number_version,
+        _padding0: [0u8; 1],
+    });
+}
+
+pub(super) fn init_request_account_mut<'a, 'info, T: Pod>(
     program_id: &Pubkey,
     payer: &AccountInfo<'info>,
     request_account: &'a AccountInfo<'info>,
     system_program_account: &AccountInfo<'info>,
     space: usize,
-) -> Result<RefMut<'a, Request>, ProgramError> {
+) -> Result<RefMut<'a, T>, ProgramError> {
     let rent = Rent::get()?;
     let required_lamports = rent.minimum_balance(space);
     if request_account.lamports() == 0 {
@@ -165,10 +210,13 @@ fn init_request_account_mut<'a, 'info>(
         )?;
     }
 
-    if request_account.owner != program_id || request_account.data_len() != space {
-        return Err(EntropyError::InvalidAccount.into());
+    if request_account.owner != program_id {
+        return Err(EntropyError::WrongAccountOwner.into());
+    }
+    if request_account.data_len() != space {
+        return Err(EntropyError::WrongAccountSize.into());
     }
 
     let data = request_account.data.borrow_mut();
-    Ok(RefMut::map(data, |data| from_bytes_mut::<Request>(data)))
+    Ok(RefMut::map(data, |data| from_bytes_mut::<T>(data)))
 }
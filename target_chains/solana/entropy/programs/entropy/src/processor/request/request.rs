@@ -9,14 +9,17 @@ use solana_program::{
 };
 
 use crate::{
-    accounts::{Config, Provider},
-    constants::REQUESTER_SIGNER_SEED,
+    accounts::{Config, Provider, Request},
+    constants::RANDOM_NUMBER_V1,
     error::EntropyError,
     instruction::RequestArgs,
-    pda::{config_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda},
+    pda::{
+        config_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda,
+        requester_signer_pda_with_bump,
+    },
     pda_loader::{load_account, load_account_mut},
     processor::parse_args,
-    processor::request::request_helper,
+    processor::request::{emit_request_created, request_helper},
 };
 
 pub fn process_request(
@@ -26,7 +29,7 @@ pub fn process_request(
 ) -> ProgramResult {
     let args = parse_args::<RequestArgs>(data)?;
 
-    if args.use_blockhash > 1 {
+    if args.use_blockhash > 1 || args.random_number_version > RANDOM_NUMBER_V1 {
         return Err(ProgramError::InvalidInstructionData);
     }
 
@@ -51,35 +54,37 @@ pub fn process_request(
         || !provider_vault.is_writable
         || !pyth_fee_vault.is_writable
     {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::AccountNotWritable.into());
     }
 
     if system_program_account.key != &system_program::ID {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::WrongSystemProgram.into());
     }
 
-    let requester_signer_seed = [REQUESTER_SIGNER_SEED, program_id.as_ref()];
-    let (expected_requester_signer, _bump) =
-        Pubkey::find_program_address(&requester_signer_seed, requester_program.key);
+    let (expected_requester_signer, _bump) = requester_signer_pda_with_bump(
+        requester_program.key,
+        program_id,
+        args.requester_signer_bump,
+    );
     if requester_signer.key != &expected_requester_signer {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongRequesterSigner.into());
     }
 
     let (expected_config, _config_bump) = config_pda(program_id);
     if config_account.key != &expected_config {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongConfig.into());
     }
 
     let (expected_pyth_fee_vault, _pyth_fee_vault_bump) = pyth_fee_vault_pda(program_id);
     if pyth_fee_vault.key != &expected_pyth_fee_vault {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongPythFeeVault.into());
     }
     if pyth_fee_vault.owner != &system_program::ID || pyth_fee_vault.data_len() != 0 {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::InvalidVault.into());
     }
 
     if request_account.owner != &system_program::ID || request_account.data_len() != 0 {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::RequestAccountNotEmpty.into());
     }
 
     let config = load_account::<Config>(config_account, program_id)?;
@@ -87,16 +92,16 @@ pub fn process_request(
     let provider_authority = Pubkey::new_from_array(provider.provider_authority);
     let (expected_provider, _provider_bump) = provider_pda(program_id, &provider_authority);
     if provider_account.key != &expected_provider {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongProvider.into());
     }
 
     let (expected_provider_vault, _provider_vault_bump) =
         provider_vault_pda(program_id, &provider_authority);
     if provider_vault.key != &expected_provider_vault {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongProviderVault.into());
     }
     if provider_vault.owner != &system_program::ID || provider_vault.data_len() != 0 {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::InvalidVault.into());
     }
 
     let sequence_number = request_helper(
@@ -112,6 +117,9 @@ pub fn process_request(
         system_program_account,
     )?;
 
+    let request = load_account::<Request>(request_account, program_id)?;
+    emit_request_created(request_account.key, &request);
+
     // Return the assigned sequence number for CPI callers.
     set_return_data(&sequence_number.to_le_bytes());
     Ok(())
@@ -0,0 +1,52 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    hash::hashv,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{
+    accounts::Config, constants::USER_RANDOMNESS_DOMAIN, instruction::parse_request_v2_args,
+    processor::request::request_with_callback::create_callback_request,
+};
+
+/// `RequestWithCallback` for requesters that have no randomness of their
+/// own: entropy advances the config seed and uses it as the user
+/// randomness, emitting it in `UserRandomnessGenerated` for the keeper.
+///
+/// Anyone can predict the generated value, so the result is only as
+/// unpredictable as the provider's contribution, as with `requestV2` on EVM
+/// chains. Every such request write-locks the config account.
+pub fn process_request_v2(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_request_v2_args(data)?;
+    create_callback_request(program_id, accounts, &args, true)
+}
+
+/// Advances `config.seed` and returns the new value. The hash is tagged with
+/// [`USER_RANDOMNESS_DOMAIN`], and the request account and sequence number
+/// bind it to one request, so requests sharing a slot and requester (e.g. in
+/// one transaction) differ beyond the seed chaining.
+pub(super) fn next_user_randomness(
+    config: &mut Config,
+    requester_program: &Pubkey,
+    request_account: &Pubkey,
+    sequence_number: u64,
+) -> Result<[u8; 32], ProgramError> {
+    let slot = Clock::get()?.slot;
+    config.seed = hashv(&[
+        USER_RANDOMNESS_DOMAIN,
+        &config.seed,
+        requester_program.as_ref(),
+        request_account.as_ref(),
+        &sequence_number.to_le_bytes(),
+        &slot.to_le_bytes(),
+    ])
+    .to_bytes();
+    Ok(config.seed)
+}
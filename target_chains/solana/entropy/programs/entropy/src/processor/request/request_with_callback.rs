@@ -1,4 +1,3 @@
-use bytemuck::{try_cast_slice, try_from_bytes, Pod, Zeroable};
 #[allow(deprecated)]
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
@@ -11,16 +10,17 @@ use solana_program::{
 };
 
 use crate::{
-    accounts::{CallbackMeta, Config, Provider, Request},
-    constants::{
-        CALLBACK_IX_DATA_LEN, CALLBACK_NOT_STARTED, MAX_CALLBACK_ACCOUNTS, REQUESTER_SIGNER_SEED,
-    },
+    accounts::{CallbackAccountList, CallbackStatus, Config, Provider, Request},
+    constants::{CALLBACK_IX_DATA_LEN, MAX_CALLBACK_ACCOUNTS, RANDOM_NUMBER_V1},
     error::EntropyError,
-    instruction::RequestArgs,
-    pda::{config_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda},
+    events::{emit_event, UserRandomnessGenerated},
+    instruction::{parse_request_with_callback_args, RequestArgs, RequestWithCallbackArgs},
+    pda::{
+        config_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda,
+        requester_signer_pda_with_bump,
+    },
     pda_loader::{load_account, load_account_mut},
-    processor::parse_args,
-    processor::request::request_helper,
+    processor::request::{emit_request_created, request_helper, request_v2::next_user_randomness},
 };
 
 pub fn process_request_with_callback(
@@ -29,13 +29,29 @@ pub fn process_request_with_callback(
     data: &[u8],
 ) -> ProgramResult {
     let args = parse_request_with_callback_args(data)?;
+    create_callback_request(program_id, accounts, &args, false)
+}
+
+/// Shared by `RequestWithCallback` and `RequestV2`. With
+/// `generate_user_randomness`, `args.user_randomness` is ignored and entropy
+/// derives it from the config seed, which requires a writable config.
+pub(super) fn create_callback_request(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: &RequestWithCallbackArgs,
+    generate_user_randomness: bool,
+) -> ProgramResult {
+    if args.include_contributions > 1 || args.random_number_version > RANDOM_NUMBER_V1 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
     let entropy_program_id = program_id.to_bytes();
     if args
         .callback_accounts
         .iter()
         .any(|meta| meta.pubkey == entropy_program_id)
     {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::EntropyInCallbackAccounts.into());
     }
 
     let mut account_info_iter = accounts.iter();
@@ -48,6 +64,9 @@ pub fn process_request_with_callback(
     let config_account = next_account_info(&mut account_info_iter)?;
     let pyth_fee_vault = next_account_info(&mut account_info_iter)?;
     let system_program_account = next_account_info(&mut account_info_iter)?;
+    // The callback program comes next; entropy calls back the requester
+    // program regardless. An optional callback account list follows it.
+    let callback_account_list = account_info_iter.nth(1);
 
     if !requester_signer.is_signer || !payer.is_signer || !request_account.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
@@ -59,60 +78,90 @@ pub fn process_request_with_callback(
         || !provider_vault.is_writable
         || !pyth_fee_vault.is_writable
     {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::AccountNotWritable.into());
     }
 
     if system_program_account.key != &system_program::ID {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::WrongSystemProgram.into());
     }
 
-    let requester_signer_seed = [REQUESTER_SIGNER_SEED, program_id.as_ref()];
-    let (expected_requester_signer, _bump) =
-        Pubkey::find_program_address(&requester_signer_seed, requester_program.key);
+    let (expected_requester_signer, _bump) = requester_signer_pda_with_bump(
+        requester_program.key,
+        program_id,
+        args.requester_signer_bump,
+    );
     if requester_signer.key != &expected_requester_signer {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongRequesterSigner.into());
     }
 
     let (expected_config, _config_bump) = config_pda(program_id);
     if config_account.key != &expected_config {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongConfig.into());
     }
 
     let (expected_pyth_fee_vault, _pyth_fee_vault_bump) = pyth_fee_vault_pda(program_id);
     if pyth_fee_vault.key != &expected_pyth_fee_vault {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongPythFeeVault.into());
     }
     if pyth_fee_vault.owner != &system_program::ID || pyth_fee_vault.data_len() != 0 {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::InvalidVault.into());
     }
 
     if request_account.owner != &system_program::ID || request_account.data_len() != 0 {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::RequestAccountNotEmpty.into());
+    }
+
+    if let Some(list_account) = callback_account_list {
+        let list = load_account::<CallbackAccountList>(list_account, program_id)?;
+        if list.request_account != request_account.key.to_bytes() {
+            return Err(EntropyError::WrongCallbackAccountList.into());
+        }
     }
 
-    let config = load_account::<Config>(config_account, program_id)?;
     let mut provider = load_account_mut::<Provider>(provider_account, program_id)?;
     let provider_authority = Pubkey::new_from_array(provider.provider_authority);
     let (expected_provider, _provider_bump) = provider_pda(program_id, &provider_authority);
     if provider_account.key != &expected_provider {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongProvider.into());
     }
 
     let (expected_provider_vault, _provider_vault_bump) =
         provider_vault_pda(program_id, &provider_authority);
     if provider_vault.key != &expected_provider_vault {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongProviderVault.into());
     }
     if provider_vault.owner != &system_program::ID || provider_vault.data_len() != 0 {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::InvalidVault.into());
     }
 
-    let user_commitment = hash(&args.user_randomness).to_bytes();
+    // `request_helper` assigns the provider's current sequence number.
+    let user_randomness = if generate_user_randomness {
+        if !config_account.is_writable {
+            return Err(EntropyError::AccountNotWritable.into());
+        }
+        let mut config = load_account_mut::<Config>(config_account, program_id)?;
+        next_user_randomness(
+            &mut config,
+            requester_program.key,
+            request_account.key,
+            provider.sequence_number,
+        )?
+    } else {
+        args.user_randomness
+    };
+
+    let config = load_account::<Config>(config_account, program_id)?;
+    let user_commitment = hash(&user_randomness).to_bytes();
     let request_args = RequestArgs {
         user_commitment,
         use_blockhash: 0,
-        _padding0: [0u8; 3],
+        random_number_version: args.random_number_version,
+        min_reveal_delay_slots: args.min_reveal_delay_slots,
         compute_unit_limit: args.compute_unit_limit,
+        ttl_slots: args.ttl_slots,
+        refund_address: args.refund_address,
+        requester_signer_bump: args.requester_signer_bump,
+        _padding0: [0u8; 3],
     };
 
     let sequence_number = request_helper(
@@ -130,83 +179,38 @@ pub fn process_request_with_callback(
 
     {
         let mut request = load_account_mut::<Request>(request_account, program_id)?;
-        request.callback_status = CALLBACK_NOT_STARTED;
+        request.callback_status = CallbackStatus::NotStarted.into();
         request.compute_unit_limit = args.compute_unit_limit;
         request.callback_accounts_len = args.callback_accounts.len() as u8;
         request.callback_ix_data_len = args.callback_ix_data.len() as u16;
+        request.include_contributions = args.include_contributions;
+        if let Some(list_account) = callback_account_list {
+            request.callback_account_list = list_account.key.to_bytes();
+        }
 
         if args.callback_accounts.len() > MAX_CALLBACK_ACCOUNTS {
-            return Err(EntropyError::InvalidAccount.into());
+            return Err(EntropyError::TooManyCallbackAccounts.into());
         }
         for (index, meta) in args.callback_accounts.iter().enumerate() {
             request.callback_accounts[index] = *meta;
         }
         if args.callback_ix_data.len() > CALLBACK_IX_DATA_LEN {
-            return Err(EntropyError::InvalidAccount.into());
+            return Err(EntropyError::CallbackDataTooLong.into());
         }
         request.callback_ix_data[..args.callback_ix_data.len()]
             .copy_from_slice(args.callback_ix_data);
-    }
 
-    set_return_data(&sequence_number.to_le_bytes());
-    Ok(())
-}
-
-struct RequestWithCallbackArgs<'a> {
-    user_randomness: [u8; 32],
-    compute_unit_limit: u32,
-    callback_accounts: &'a [CallbackMeta],
-    callback_ix_data: &'a [u8],
-}
-
-#[derive(Clone, Copy, Pod, Zeroable)]
-#[repr(C)]
-struct RequestWithCallbackHeader {
-    user_randomness: [u8; 32],
-    compute_unit_limit: u32,
-    callback_accounts_len: u32,
-}
-
-fn parse_request_with_callback_args<'a>(
-    data: &'a [u8],
-) -> Result<RequestWithCallbackArgs<'a>, ProgramError> {
-    if data.len() < core::mem::size_of::<RequestWithCallbackHeader>() {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-
-    let (header_bytes, rest) = data.split_at(core::mem::size_of::<RequestWithCallbackHeader>());
-    let header = parse_args::<RequestWithCallbackHeader>(header_bytes)?;
-
-    let callback_accounts_len = header.callback_accounts_len as usize;
-
-    let callback_accounts_bytes_len = callback_accounts_len
-        .checked_mul(CallbackMeta::LEN)
-        .ok_or(ProgramError::InvalidInstructionData)?;
-    if rest.len() < callback_accounts_bytes_len + 4 {
-        return Err(ProgramError::InvalidInstructionData);
+        emit_request_created(request_account.key, &request);
     }
-
-    let (callback_accounts_bytes, rest) = rest.split_at(callback_accounts_bytes_len);
-    let callback_accounts = try_cast_slice::<u8, CallbackMeta>(callback_accounts_bytes)
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
-    for meta in callback_accounts {
-        if meta.is_signer > 1 || meta.is_writable > 1 {
-            return Err(ProgramError::InvalidInstructionData);
-        }
+    if generate_user_randomness {
+        emit_event(&UserRandomnessGenerated {
+            provider: provider.provider_authority,
+            request_account: request_account.key.to_bytes(),
+            sequence_number,
+            user_randomness,
+        });
     }
 
-    let (callback_ix_len_bytes, rest) = rest.split_at(4);
-    let callback_ix_data_len = *try_from_bytes::<u32>(callback_ix_len_bytes)
-        .map_err(|_| ProgramError::InvalidInstructionData)? as usize;
-
-    if rest.len() != callback_ix_data_len {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-
-    Ok(RequestWithCallbackArgs {
-        user_randomness: header.user_randomness,
-        compute_unit_limit: header.compute_unit_limit,
-        callback_accounts,
-        callback_ix_data: rest,
-    })
+    set_return_data(&sequence_number.to_le_bytes());
+    Ok(())
 }
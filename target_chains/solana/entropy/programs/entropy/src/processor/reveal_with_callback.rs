@@ -2,23 +2,29 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
-    hash::{hash, hashv},
+    hash::hash,
     program::invoke_signed,
     program_error::ProgramError,
     pubkey::Pubkey,
     system_program,
-    sysvar::{slot_hashes, slot_hashes::SlotHashes, Sysvar},
+    sysvar::{clock::Clock, slot_hashes, slot_hashes::SlotHashes, Sysvar},
 };
 
 use crate::{
-    accounts::{Provider, Request},
-    constants::{CALLBACK_NOT_STARTED, ENTROPY_SIGNER_SEED, MAX_CALLBACK_ACCOUNTS},
+    accounts::{CallbackMeta, CallbackStatus, Provider, Request, RequestRef},
+    constants::MAX_CALLBACK_ACCOUNTS,
     error::EntropyError,
+    events::{emit_event, CallbackInvoked, RandomnessRevealed},
     instruction::RevealArgs,
     load_account,
-    pda::{entropy_signer_pda, provider_pda},
+    pda::{entropy_signer_pda_with_bump, provider_pda_with_bump},
     pda_loader::load_account_mut,
-    processor::parse_args,
+    processor::{
+        callback_account_list::load_callback_account_list, callback_skip::record_callback,
+        close_request_account, parse_args,
+    },
+    randomness::{combine_random_values, request_commitment},
+    seeds::entropy_signer_seeds,
 };
 
 pub fn process_reveal_with_callback(
@@ -35,137 +41,234 @@ pub fn process_reveal_with_callback(
     let entropy_signer_account = next_account_info(&mut account_info_iter)?;
     let callback_program = next_account_info(&mut account_info_iter)?;
     let system_program_account = next_account_info(&mut account_info_iter)?;
-    let payer_account = next_account_info(&mut account_info_iter)?;
+    let refund_account = next_account_info(&mut account_info_iter)?;
 
     if !request_account.is_writable || !provider_account.is_writable {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::AccountNotWritable.into());
     }
 
     if system_program_account.key != &system_program::ID {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::WrongSystemProgram.into());
     }
 
     if slot_hashes_account.key != &slot_hashes::ID {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::WrongSlotHashes.into());
     }
 
-    let (expected_entropy_signer, _bump) = entropy_signer_pda(program_id);
+    let request = load_account::<Request>(request_account, program_id)?;
+    let list_account = if request.has_callback_account_list() {
+        Some(next_account_info(&mut account_info_iter)?)
+    } else {
+        None
+    };
+
+    // Both bumps were recorded at request time; re-deriving from them skips
+    // the `find_program_address` search on every reveal.
+    let (expected_entropy_signer, entropy_signer_bump) =
+        entropy_signer_pda_with_bump(program_id, request.entropy_signer_bump);
     if entropy_signer_account.key != &expected_entropy_signer {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongEntropySigner.into());
     }
 
-    let request = load_account::<Request>(request_account, program_id)?;
+    match request.status()? {
+        CallbackStatus::NotStarted | CallbackStatus::Failed => {}
+        CallbackStatus::NotNecessary | CallbackStatus::InProgress => {
+            return Err(EntropyError::InvalidRevealCall.into());
+        }
+    }
 
-    if request.callback_status != CALLBACK_NOT_STARTED {
-        return Err(EntropyError::InvalidRevealCall.into());
+    let reveal_slot = Clock::get()?.slot;
+    if request.is_expired(reveal_slot) {
+        return Err(EntropyError::RequestExpired.into());
+    }
+    if !request.is_revealable_yet(reveal_slot) {
+        return Err(EntropyError::RevealTooEarly.into());
     }
 
     let request_provider = Pubkey::new_from_array(request.provider);
 
-    let (expected_provider, _provider_bump) = provider_pda(program_id, &request_provider);
+    let (expected_provider, _provider_bump) =
+        provider_pda_with_bump(program_id, &request_provider, request.provider_bump);
     if provider_account.key != &expected_provider {
-        return Err(EntropyError::InvalidPda.into());
+        return Err(EntropyError::WrongProvider.into());
     }
 
     let mut provider = load_account_mut::<Provider>(provider_account, program_id)?;
-
-    let provider_commitment =
-        hash_provider_commitment(args.provider_contribution, request.num_hashes)?;
-    let user_commitment = hash(&args.user_contribution).to_bytes();
-    let commitment = hashv(&[&user_commitment, &provider_commitment]).to_bytes();
-    if commitment != request.commitment {
-        return Err(EntropyError::IncorrectRevelation.into());
-    }
-
-    let blockhash = if request.use_blockhash == 1 {
-        let slot_hashes = SlotHashes::from_account_info(slot_hashes_account)?;
-        slot_hashes
-            .iter()
-            .find(|(slot, _)| *slot == request.request_slot)
-            .map(|(_, hash)| hash.to_bytes())
-            .ok_or(EntropyError::BlockhashUnavailable)?
-    } else {
-        [0u8; 32]
-    };
-
-    let random_number = hashv(&[
-        &args.user_contribution,
-        &args.provider_contribution,
-        &blockhash,
-    ])
-    .to_bytes();
-
-    if provider.current_commitment_sequence_number < request.sequence_number {
-        provider.current_commitment_sequence_number = request.sequence_number;
-        provider.current_commitment = args.provider_contribution;
-    }
+    let random_number = reveal_request(
+        program_id,
+        &request,
+        &mut provider,
+        slot_hashes_account,
+        args,
+        reveal_slot,
+    )?;
 
     let requester_program_id = Pubkey::new_from_array(request.requester_program_id);
     if callback_program.key != &requester_program_id {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::WrongCallbackProgram.into());
     }
 
     let callback_accounts_len = request.callback_accounts_len as usize;
     if callback_accounts_len > MAX_CALLBACK_ACCOUNTS {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::TooManyCallbackAccounts.into());
     }
 
+    // Listed accounts follow the inline ones in the callback.
+    let list = list_account
+        .map(|list_account| {
+            load_callback_account_list(program_id, request_account, &request, list_account)
+        })
+        .transpose()?;
+    let listed_accounts = list
+        .as_ref()
+        .map_or(&[][..], |list| list.callback_accounts());
+    let total_callback_accounts = callback_accounts_len + listed_accounts.len();
+
     let remaining_accounts = account_info_iter.as_slice();
-    if remaining_accounts.len() < callback_accounts_len {
-        return Err(EntropyError::InvalidAccount.into());
+    if remaining_accounts.len() < total_callback_accounts {
+        return Err(EntropyError::CallbackAccountMismatch.into());
     }
 
-    let (callback_accounts, _) = remaining_accounts.split_at(callback_accounts_len);
-    validate_callback_accounts(&request, callback_accounts)?;
+    let (callback_accounts, trailing_accounts) =
+        remaining_accounts.split_at(total_callback_accounts);
+    let (inline_accounts, listed_account_infos) = callback_accounts.split_at(callback_accounts_len);
+    let request_ref = RequestRef::from(&*request);
+    validate_callback_accounts(request_ref.callback_accounts(), inline_accounts)?;
+    validate_callback_accounts(listed_accounts, listed_account_infos)?;
 
-    let callback_ix_data_len = request.callback_ix_data_len;
-    let callback_ix_data = request.callback_ix_data;
+    let callback_ix_data = request_ref.callback_ix_data();
     let request_sequence_number = request.sequence_number;
     let request_provider_bytes = request.provider;
     let callback_compute_unit_limit = request.compute_unit_limit;
-
-    if callback_compute_unit_limit != 0 && request.callback_status == CALLBACK_NOT_STARTED {
-        let callback_ix = build_callback_ix(
-            callback_program.key,
-            entropy_signer_account.key,
+    let callback_invoked = callback_compute_unit_limit != 0;
+
+    if callback_invoked {
+        emit_event(&CallbackInvoked {
+            provider: request_provider_bytes,
+            requester_program_id: request.requester_program_id,
+            request_account: request_account.key.to_bytes(),
+            sequence_number: request_sequence_number,
+            request_slot: request.request_slot,
+            reveal_slot,
+            compute_unit_limit: callback_compute_unit_limit,
+            _padding0: [0u8; 4],
+        });
+        invoke_callback(
+            callback_program,
+            entropy_signer_account,
+            entropy_signer_bump,
             callback_accounts,
-            callback_ix_data_len,
-            &callback_ix_data,
+            callback_ix_data,
             request_sequence_number,
             request_provider_bytes,
             random_number,
+            (request.include_contributions == 1)
+                .then_some((args.user_contribution, args.provider_contribution)),
+        )?;
+        record_callback(
+            program_id,
+            &provider,
+            &requester_program_id,
+            trailing_accounts.first(),
+            reveal_slot,
         )?;
-
-        // let callback_compute_units_before = sol_remaining_compute_units();
-        let bump_seed = [_bump];
-        let signer_seeds: &[&[u8]] = &[ENTROPY_SIGNER_SEED, &bump_seed];
-        let mut callback_account_infos =
-            Vec::with_capacity(callback_accounts.len().saturating_add(2));
-        callback_account_infos.push(callback_program.clone());
-        callback_account_infos.push(entropy_signer_account.clone());
-        callback_account_infos.extend_from_slice(callback_accounts);
-        invoke_signed(&callback_ix, &callback_account_infos, &[signer_seeds])?;
-        // let callback_compute_units_after: u64 = sol_remaining_compute_units();
-        // let callback_compute_units_spent =
-        //     callback_compute_units_before.saturating_sub(callback_compute_units_after);
-
-        // if callback_compute_units_spent > u64::from(callback_compute_unit_limit) {
-        //     return Err(EntropyError::InsufficientGas.into());
-        // }
     }
 
-    if payer_account.key != &Pubkey::new_from_array(request.payer) || !payer_account.is_writable {
-        return Err(EntropyError::InvalidAccount.into());
+    if refund_account.key != &Pubkey::new_from_array(request.refund_address) {
+        return Err(EntropyError::WrongPayer.into());
+    }
+    if !refund_account.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
     }
 
+    emit_event(&RandomnessRevealed {
+        provider: request_provider_bytes,
+        requester_program_id: request.requester_program_id,
+        request_account: request_account.key.to_bytes(),
+        sequence_number: request_sequence_number,
+        random_number,
+        callback_invoked: u8::from(callback_invoked),
+        _padding0: [0u8; 7],
+        request_slot: request.request_slot,
+        reveal_slot,
+    });
+
+    drop(list);
     drop(request);
-    close_request_account(request_account, payer_account)?;
+    close_request_account(request_account, refund_account)?;
+    if let Some(list_account) = list_account {
+        close_request_account(list_account, refund_account)?;
+    }
 
     Ok(())
 }
 
+/// The blockhash of `request_slot` for a request that mixes one in, zero
+/// otherwise. `BlockhashUnavailable` once the slot has left `SlotHashes`.
+pub(super) fn request_blockhash(
+    slot_hashes_account: &AccountInfo,
+    use_blockhash: u8,
+    request_slot: u64,
+) -> Result<[u8; 32], ProgramError> {
+    if use_blockhash != 1 {
+        return Ok([0u8; 32]);
+    }
+    let slot_hashes = SlotHashes::from_account_info(slot_hashes_account)?;
+    slot_hashes
+        .iter()
+        .find(|(slot, _)| *slot == request_slot)
+        .map(|(_, hash)| hash.to_bytes())
+        .ok_or_else(|| EntropyError::BlockhashUnavailable.into())
+}
 
-fn hash_provider_commitment(
+/// Checks the revealed contributions against `request` and derives its
+/// random number, then records the reveal on the request's `provider`.
+pub(super) fn reveal_request(
+    program_id: &Pubkey,
+    request: &Request,
+    provider: &mut Provider,
+    slot_hashes_account: &AccountInfo,
+    args: &RevealArgs,
+    reveal_slot: u64,
+) -> Result<[u8; 32], ProgramError> {
+    let provider_commitment =
+        hash_provider_commitment(args.provider_contribution, request.num_hashes)?;
+    let user_commitment = hash(&args.user_contribution).to_bytes();
+    if request_commitment(&user_commitment, &provider_commitment) != request.commitment {
+        return Err(EntropyError::IncorrectRevelation.into());
+    }
+
+    let blockhash = request_blockhash(
+        slot_hashes_account,
+        request.use_blockhash,
+        request.request_slot,
+    )?;
+    let random_number = combine_random_values(
+        request.random_number_version,
+        program_id,
+        &args.user_contribution,
+        &args.provider_contribution,
+        &blockhash,
+    );
+
+    if provider.current_commitment_sequence_number < request.sequence_number {
+        provider.current_commitment_sequence_number = request.sequence_number;
+        provider.current_commitment = args.provider_contribution;
+    }
+    // Saturating: providers registered before the counter existed start at
+    // zero with requests already open.
+    provider.outstanding_requests = provider.outstanding_requests.saturating_sub(1);
+    provider.release_fee(request.provider_fee);
+    provider.record_reveal_latency(reveal_slot.saturating_sub(request.request_slot));
+    Ok(random_number)
+}
+
+/// Hashes the contribution `num_hashes` times, at about
+/// [`COMPUTE_UNITS_PER_HASH`](crate::constants::COMPUTE_UNITS_PER_HASH) each.
+/// Every step hashes the previous output, so the syscalls cannot be batched;
+/// [`MAX_NUM_HASHES`](crate::constants::MAX_NUM_HASHES) bounds the total
+/// instead.
+pub(super) fn hash_provider_commitment(
     mut provider_contribution: [u8; 32],
     num_hashes: u32,
 ) -> Result<[u8; 32], ProgramError> {
@@ -175,46 +278,89 @@ fn hash_provider_commitment(
     Ok(provider_contribution)
 }
 
-fn validate_callback_accounts(
-    request: &Request,
+/// Checks the remaining accounts against the callback metas stored with the
+/// request, which the caller has already checked are as many.
+pub(super) fn validate_callback_accounts(
+    expected_accounts: &[CallbackMeta],
     callback_accounts: &[AccountInfo],
 ) -> ProgramResult {
-    for (index, account_info) in callback_accounts.iter().enumerate() {
-        let expected = request.callback_accounts[index];
+    for (expected, account_info) in expected_accounts.iter().zip(callback_accounts) {
         if account_info.key.to_bytes() != expected.pubkey {
-            return Err(EntropyError::InvalidAccount.into());
+            return Err(EntropyError::CallbackAccountMismatch.into());
         }
         if account_info.is_signer != (expected.is_signer == 1) {
-            return Err(EntropyError::InvalidAccount.into());
+            return Err(EntropyError::CallbackAccountMismatch.into());
         }
         if account_info.is_writable != (expected.is_writable == 1) {
-            return Err(EntropyError::InvalidAccount.into());
+            return Err(EntropyError::CallbackAccountMismatch.into());
         }
     }
     Ok(())
 }
 
+/// CPIs into the callback program, signed by the entropy signer, with
+/// `callback_ix_data` followed by the callback payload and, if given, the
+/// `(user, provider)` contributions.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn invoke_callback<'info>(
+    callback_program: &AccountInfo<'info>,
+    entropy_signer_account: &AccountInfo<'info>,
+    entropy_signer_bump: u8,
+    callback_accounts: &[AccountInfo<'info>],
+    callback_ix_data: &[u8],
+    sequence_number: u64,
+    provider: [u8; 32],
+    random_number: [u8; 32],
+    contributions: Option<([u8; 32], [u8; 32])>,
+) -> ProgramResult {
+    let callback_ix = build_callback_ix(
+        callback_program.key,
+        entropy_signer_account.key,
+        callback_accounts,
+        callback_ix_data,
+        sequence_number,
+        provider,
+        random_number,
+        contributions,
+    );
+
+    // let callback_compute_units_before = sol_remaining_compute_units();
+    let signer_seeds = entropy_signer_seeds(&entropy_signer_bump);
+    let mut callback_account_infos = Vec::with_capacity(callback_accounts.len().saturating_add(2));
+    callback_account_infos.push(callback_program.clone());
+    callback_account_infos.push(entropy_signer_account.clone());
+    callback_account_infos.extend_from_slice(callback_accounts);
+    invoke_signed(&callback_ix, &callback_account_infos, &[&signer_seeds])?;
+    // let callback_compute_units_after: u64 = sol_remaining_compute_units();
+    // let callback_compute_units_spent =
+    //     callback_compute_units_before.saturating_sub(callback_compute_units_after);
+
+    // if callback_compute_units_spent > u64::from(callback_compute_unit_limit) {
+    //     return Err(EntropyError::InsufficientGas.into());
+    // }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 fn build_callback_ix(
     program_id: &Pubkey,
     entropy_signer: &Pubkey,
     callback_accounts: &[AccountInfo],
-    callback_ix_data_len: u16,
     callback_ix_data: &[u8],
     sequence_number: u64,
     provider: [u8; 32],
     random_number: [u8; 32],
-) -> Result<solana_program::instruction::Instruction, ProgramError> {
-    let prefix_len = usize::from(callback_ix_data_len);
-    if prefix_len > callback_ix_data.len() {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-
-    let mut data = Vec::with_capacity(prefix_len + 8 + 32 + 32);
-    data.extend_from_slice(&callback_ix_data[..prefix_len]);
+    contributions: Option<([u8; 32], [u8; 32])>,
+) -> solana_program::instruction::Instruction {
+    let mut data = Vec::with_capacity(callback_ix_data.len() + 8 + 32 + 32 + 32 + 32);
+    data.extend_from_slice(callback_ix_data);
     data.extend_from_slice(&sequence_number.to_le_bytes());
     data.extend_from_slice(&provider);
     data.extend_from_slice(&random_number);
+    if let Some((user_contribution, provider_contribution)) = contributions {
+        data.extend_from_slice(&user_contribution);
+        data.extend_from_slice(&provider_contribution);
+    }
 
     let mut metas = Vec::with_capacity(callback_accounts.len().saturating_add(1));
     metas.push(solana_program::instruction::AccountMeta {
@@ -232,24 +378,9 @@ fn build_callback_ix(
             }),
     );
 
-    Ok(solana_program::instruction::Instruction {
+    solana_program::instruction::Instruction {
         program_id: *program_id,
         accounts: metas,
         data,
-    })
-}
-
-fn close_request_account(
-    request_account: &AccountInfo,
-    refund_account: &AccountInfo,
-) -> ProgramResult {
-    let lamports = request_account.lamports();
-    let refund_lamports = refund_account
-        .lamports()
-        .checked_add(lamports)
-        .ok_or(ProgramError::InvalidArgument)?;
-
-    **request_account.try_borrow_mut_lamports()? = 0;
-    **refund_account.try_borrow_mut_lamports()? = refund_lamports;
-    Ok(())
+    }
 }
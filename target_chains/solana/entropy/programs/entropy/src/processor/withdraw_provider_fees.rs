@@ -0,0 +1,97 @@
+#[allow(deprecated)]
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+    sysvar::{rent::Rent, Sysvar},
+};
+
+use crate::{
+    accounts::Provider,
+    constants::PROVIDER_VAULT_SEED,
+    error::EntropyError,
+    instruction::WithdrawProviderFeesArgs,
+    pda::{provider_pda, provider_vault_pda},
+    pda_loader::load_account,
+    processor::parse_args,
+    vault::withdrawable_fees,
+};
+
+/// Moves accrued fees from the provider vault to any destination the
+/// authority picks. The vault keeps its rent floor and the fees reserved for
+/// refunds of outstanding requests.
+pub fn process_withdraw_provider_fees(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<WithdrawProviderFeesArgs>(data)?;
+
+    let mut account_info_iter = accounts.iter();
+    let provider_authority = next_account_info(&mut account_info_iter)?;
+    let provider_account = next_account_info(&mut account_info_iter)?;
+    let provider_vault = next_account_info(&mut account_info_iter)?;
+    let destination = next_account_info(&mut account_info_iter)?;
+    let system_program_account = next_account_info(&mut account_info_iter)?;
+
+    if !provider_authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !provider_vault.is_writable || !destination.is_writable {
+        return Err(EntropyError::AccountNotWritable.into());
+    }
+
+    if system_program_account.key != &system_program::ID {
+        return Err(EntropyError::WrongSystemProgram.into());
+    }
+
+    let (expected_provider, _provider_bump) = provider_pda(program_id, provider_authority.key);
+    if provider_account.key != &expected_provider {
+        return Err(EntropyError::WrongProvider.into());
+    }
+
+    let (expected_vault, vault_bump) = provider_vault_pda(program_id, provider_authority.key);
+    if provider_vault.key != &expected_vault {
+        return Err(EntropyError::WrongProviderVault.into());
+    }
+    if provider_vault.owner != &system_program::ID || provider_vault.data_len() != 0 {
+        return Err(EntropyError::InvalidVault.into());
+    }
+
+    let reserved_fee_lamports = {
+        let provider = load_account::<Provider>(provider_account, program_id)?;
+        if provider.provider_authority != provider_authority.key.to_bytes() {
+            return Err(EntropyError::ProviderAuthorityMismatch.into());
+        }
+        provider.reserved_fee_lamports
+    };
+
+    let available = withdrawable_fees(provider_vault.lamports(), &Rent::get()?)
+        .saturating_sub(reserved_fee_lamports);
+    if args.amount > available {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    if args.amount == 0 {
+        return Ok(());
+    }
+
+    let transfer_ix =
+        system_instruction::transfer(provider_vault.key, destination.key, args.amount);
+    invoke_signed(
+        &transfer_ix,
+        &[
+            provider_vault.clone(),
+            destination.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[
+            PROVIDER_VAULT_SEED,
+            provider_authority.key.as_ref(),
+            &[vault_bump],
+        ]],
+    )
+}
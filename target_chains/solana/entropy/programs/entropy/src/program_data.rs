@@ -0,0 +1,75 @@
+//! The program's upgrade authority, read from its `ProgramData` account under
+//! the upgradeable BPF loader. The upgrade authority is the Solana analogue of
+//! the EVM contracts' owner: it may perform any admin action.
+
+#[allow(deprecated)]
+use solana_program::{
+    account_info::AccountInfo, bpf_loader_upgradeable, program_error::ProgramError, pubkey::Pubkey,
+};
+
+use crate::{accounts::Config, error::EntropyError};
+
+/// Bincode tag of `UpgradeableLoaderState::ProgramData`.
+const PROGRAM_DATA_TAG: u32 = 3;
+/// Tag (u32), last deployment slot (u64), then `Option<Pubkey>`.
+const UPGRADE_AUTHORITY_OPTION_OFFSET: usize = 12;
+pub const PROGRAM_DATA_METADATA_LEN: usize = UPGRADE_AUTHORITY_OPTION_OFFSET + 1 + 32;
+
+#[allow(deprecated)]
+pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/// Returns the upgrade authority recorded in `program_id`'s `ProgramData`
+/// account, or `None` if the program is immutable.
+#[allow(deprecated)]
+pub fn load_upgrade_authority(
+    program_id: &Pubkey,
+    program_data: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    if program_data.key != &program_data_address(program_id)
+        || program_data.owner != &bpf_loader_upgradeable::id()
+    {
+        return Err(EntropyError::WrongProgramData.into());
+    }
+
+    let data = program_data.data.borrow();
+    if data.len() < PROGRAM_DATA_METADATA_LEN || data[..4] != PROGRAM_DATA_TAG.to_le_bytes() {
+        return Err(EntropyError::InvalidProgramData.into());
+    }
+
+    let authority = &data[UPGRADE_AUTHORITY_OPTION_OFFSET + 1..PROGRAM_DATA_METADATA_LEN];
+    match data[UPGRADE_AUTHORITY_OPTION_OFFSET] {
+        0 => Ok(None),
+        1 => Ok(Some(
+            Pubkey::try_from(authority).map_err(|_| EntropyError::InvalidProgramData)?,
+        )),
+        _ => Err(EntropyError::InvalidProgramData.into()),
+    }
+}
+
+/// Mirrors `_authoriseAdminAction`: `signer` must be `config.admin` or, when
+/// `program_data` is supplied, the program's upgrade authority.
+pub fn authorize_admin(
+    program_id: &Pubkey,
+    config: &Config,
+    signer: &AccountInfo,
+    program_data: Option<&AccountInfo>,
+) -> Result<(), ProgramError> {
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if signer.key.to_bytes() == config.admin {
+        return Ok(());
+    }
+
+    match program_data {
+        Some(program_data)
+            if load_upgrade_authority(program_id, program_data)? == Some(*signer.key) =>
+        {
+            Ok(())
+        }
+        _ => Err(EntropyError::Unauthorized.into()),
+    }
+}
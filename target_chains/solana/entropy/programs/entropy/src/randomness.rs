@@ -0,0 +1,47 @@
+//! Derivation of a request's commitment and random number from the
+//! contributions.
+
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+use crate::constants::{RANDOM_NUMBER_DOMAIN, RANDOM_NUMBER_V1};
+
+/// The commitment a request stores: `hash(user_commitment || provider_commitment)`,
+/// where `user_commitment` is the hash of the user's randomness and
+/// `provider_commitment` the provider's current commitment when the request
+/// is made. Mirrors the commitment EVM `request` stores.
+pub fn request_commitment(user_commitment: &[u8; 32], provider_commitment: &[u8; 32]) -> [u8; 32] {
+    hashv(&[user_commitment, provider_commitment]).to_bytes()
+}
+
+/// Mirrors EVM `combineRandomValues`. Version 0 hashes
+/// `user || provider || blockhash`, so equal contributions give equal random
+/// numbers on every deployment; version 1 prefixes [`RANDOM_NUMBER_DOMAIN`]
+/// and the entropy program id. `blockhash` is zero unless the request used
+/// one. Requests only store known versions.
+pub fn combine_random_values(
+    random_number_version: u8,
+    program_id: &Pubkey,
+    user_contribution: &[u8; 32],
+    provider_contribution: &[u8; 32],
+    blockhash: &[u8; 32],
+) -> [u8; 32] {
+    if random_number_version == RANDOM_NUMBER_V1 {
+        hashv(&[
+            RANDOM_NUMBER_DOMAIN,
+            program_id.as_ref(),
+            user_contribution,
+            provider_contribution,
+            blockhash,
+        ])
+        .to_bytes()
+    } else {
+        hashv(&[user_contribution, provider_contribution, blockhash]).to_bytes()
+    }
+}
+
+/// The provider contribution an aggregate request passes to
+/// [`combine_random_values`]: the hash of the revealed providers'
+/// contributions, in request order.
+pub fn aggregate_provider_contribution(revealed_contributions: &[&[u8]]) -> [u8; 32] {
+    hashv(revealed_contributions).to_bytes()
+}
@@ -0,0 +1,28 @@
+//! Signer seeds of the PDAs that sign CPIs, laid out for `invoke_signed`:
+//!
+//! ```ignore
+//! let (_, bump) = requester_signer_pda(program_id, &entropy_program_id);
+//! invoke_signed(&ix, &infos, &[&requester_signer_seeds(&entropy_program_id, &bump)])?;
+//! ```
+//!
+//! The bump is borrowed so the returned seeds can point at it.
+
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::{ENTROPY_SIGNER_SEED, REQUESTER_SIGNER_SEED};
+
+/// Seeds of a requester program's signer for calls into `entropy_program`;
+/// see [`requester_signer_pda`](crate::pda::requester_signer_pda).
+pub fn requester_signer_seeds<'a>(entropy_program: &'a Pubkey, bump: &'a u8) -> [&'a [u8]; 3] {
+    [
+        REQUESTER_SIGNER_SEED,
+        entropy_program.as_ref(),
+        core::slice::from_ref(bump),
+    ]
+}
+
+/// Seeds of entropy's own signer, which signs callbacks; see
+/// [`entropy_signer_pda`](crate::pda::entropy_signer_pda).
+pub fn entropy_signer_seeds(bump: &u8) -> [&[u8]; 2] {
+    [ENTROPY_SIGNER_SEED, core::slice::from_ref(bump)]
+}
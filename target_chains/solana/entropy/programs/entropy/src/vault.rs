@@ -15,7 +15,7 @@ pub fn init_vault_pda<'a>(
     system_program_account: &AccountInfo<'a>,
 ) -> ProgramResult {
     if vault.owner != &system_program::ID || vault.data_len() != 0 {
-        return Err(EntropyError::InvalidAccount.into());
+        return Err(EntropyError::InvalidVault.into());
     }
 
     let rent = Rent::get()?;
@@ -35,3 +35,10 @@ pub fn init_vault_pda<'a>(
 
     Ok(())
 }
+
+/// Fees a vault holding `vault_lamports` can pay out: everything above the
+/// rent-exempt minimum of a data-less account, which the vault keeps so the
+/// runtime never reclaims it. Only closing a provider empties its vault.
+pub fn withdrawable_fees(vault_lamports: u64, rent: &Rent) -> u64 {
+    vault_lamports.saturating_sub(rent.minimum_balance(0))
+}
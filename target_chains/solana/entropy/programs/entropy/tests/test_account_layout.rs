@@ -0,0 +1,313 @@
+//! Golden layouts of the Pod accounts. Accounts are read and written in
+//! place, so a reordered field or a change in padding silently corrupts every
+//! live account; these values may only change together with a migration.
+
+use entropy::{
+    accounts::{
+        Account, AggregateContribution, AggregateRequest, CallbackAccountList,
+        CallbackFailureStats, CallbackMeta, CallbackStatus, Config, GovernanceConfig, KeeperLease,
+        ProgramRegistry, Provider, ProviderAllowlist, ProviderDirectoryPage, RegisteredProgram,
+        Request,
+    },
+    constants::{
+        CALLBACK_IX_DATA_LEN, COMMITMENT_METADATA_LEN, MAX_AGGREGATE_PROVIDERS,
+        MAX_ALLOWED_PROVIDERS, MAX_CALLBACK_ACCOUNTS, MAX_CALLBACK_ACCOUNT_LIST_LEN,
+        MAX_REGISTERED_PROGRAMS, PROVIDER_DIRECTORY_PAGE_LEN, URI_LEN,
+    },
+};
+
+macro_rules! assert_layout {
+    ($ty:ty, size = $size:expr, { $($field:ident: $offset:expr),* $(,)? }) => {{
+        assert_eq!(
+            core::mem::size_of::<$ty>(),
+            $size,
+            "size of {}",
+            stringify!($ty)
+        );
+        $(
+            assert_eq!(
+                core::mem::offset_of!($ty, $field),
+                $offset,
+                "offset of {}::{}",
+                stringify!($ty),
+                stringify!($field)
+            );
+        )*
+    }};
+}
+
+#[test]
+fn test_config_layout() {
+    assert_layout!(Config, size = 152, {
+        discriminator: 0,
+        admin: 8,
+        pyth_fee_lamports: 40,
+        default_provider: 48,
+        proposed_admin: 80,
+        seed: 112,
+        bump: 144,
+        permissioned_providers: 145,
+        entropy_signer_bump: 146,
+        program_version: 147,
+        provider_directory_len: 148,
+    });
+}
+
+#[test]
+fn test_provider_layout() {
+    assert_eq!(COMMITMENT_METADATA_LEN, 64);
+    assert_eq!(URI_LEN, 256);
+    assert_layout!(Provider, size = 552, {
+        discriminator: 0,
+        provider_authority: 8,
+        fee_lamports: 40,
+        original_commitment: 48,
+        original_commitment_sequence_number: 80,
+        commitment_metadata_len: 88,
+        commitment_metadata: 90,
+        uri_len: 154,
+        uri: 156,
+        outstanding_requests: 412,
+        end_sequence_number: 416,
+        sequence_number: 424,
+        current_commitment: 432,
+        current_commitment_sequence_number: 464,
+        fee_manager: 472,
+        max_num_hashes: 504,
+        default_compute_unit_limit: 508,
+        bump: 512,
+        in_provider_directory: 513,
+        callback_skip_max_failures: 514,
+        callback_skip_window_slots: 516,
+        reveal_count: 520,
+        reveal_latency_sum_slots: 528,
+        reveal_latency_max_slots: 536,
+        reserved_fee_lamports: 544,
+    });
+}
+
+#[test]
+fn test_callback_meta_layout() {
+    assert_layout!(CallbackMeta, size = 36, {
+        pubkey: 0,
+        is_signer: 32,
+        is_writable: 34,
+    });
+}
+
+#[test]
+fn test_request_layout() {
+    assert_eq!(MAX_CALLBACK_ACCOUNTS, 16);
+    assert_eq!(CALLBACK_IX_DATA_LEN, 256);
+    assert_layout!(Request, size = 1080, {
+        discriminator: 0,
+        provider: 8,
+        sequence_number: 40,
+        num_hashes: 48,
+        commitment: 52,
+        ttl_slots: 84,
+        request_slot: 88,
+        requester_program_id: 96,
+        callback_account_list: 128,
+        payer: 160,
+        use_blockhash: 192,
+        callback_status: 193,
+        min_reveal_delay_slots: 194,
+        compute_unit_limit: 196,
+        callback_accounts_len: 200,
+        include_contributions: 201,
+        callback_accounts: 202,
+        callback_ix_data_len: 778,
+        callback_ix_data: 780,
+        bump: 1036,
+        random_number_version: 1037,
+        provider_bump: 1038,
+        entropy_signer_bump: 1039,
+        provider_fee: 1040,
+        refund_address: 1048,
+    });
+}
+
+#[test]
+fn test_governance_config_layout() {
+    assert_layout!(GovernanceConfig, size = 96, {
+        discriminator: 0,
+        wormhole_program: 8,
+        emitter_address: 40,
+        emitter_chain: 72,
+        _padding0: 74,
+        last_executed_sequence: 80,
+        bump: 88,
+        _padding1: 89,
+    });
+}
+
+#[test]
+fn test_keeper_lease_layout() {
+    assert_layout!(KeeperLease, size = 96, {
+        discriminator: 0,
+        provider_authority: 8,
+        keeper: 40,
+        refreshed_slot: 72,
+        lease_slots: 80,
+        bump: 88,
+        _padding0: 89,
+    });
+}
+
+#[test]
+fn test_aggregate_request_layout() {
+    assert_eq!(MAX_AGGREGATE_PROVIDERS, 4);
+    assert_layout!(AggregateContribution, size = 120, {
+        provider: 0,
+        sequence_number: 32,
+        provider_commitment: 40,
+        contribution: 72,
+        provider_fee: 104,
+        num_hashes: 112,
+        revealed: 116,
+        _padding0: 117,
+    });
+    assert_layout!(AggregateRequest, size = 1480, {
+        discriminator: 0,
+        user_commitment: 8,
+        requester_program_id: 40,
+        payer: 72,
+        request_slot: 104,
+        ttl_slots: 112,
+        compute_unit_limit: 116,
+        num_providers: 120,
+        threshold: 121,
+        revealed_count: 122,
+        callback_accounts_len: 123,
+        callback_ix_data_len: 124,
+        min_reveal_delay_slots: 126,
+        refund_address: 128,
+        use_blockhash: 160,
+        random_number_version: 161,
+        _padding0: 162,
+        contributions: 168,
+        callback_accounts: 648,
+        callback_ix_data: 1224,
+    });
+}
+
+#[test]
+fn test_program_registry_layout() {
+    assert_eq!(MAX_REGISTERED_PROGRAMS, 8);
+    assert_layout!(RegisteredProgram, size = 64, {
+        program_id: 0,
+        entropy_signer: 32,
+    });
+    assert_layout!(ProgramRegistry, size = 528, {
+        discriminator: 0,
+        num_programs: 8,
+        bump: 9,
+        _padding0: 10,
+        programs: 16,
+    });
+}
+
+#[test]
+fn test_provider_allowlist_layout() {
+    assert_eq!(MAX_ALLOWED_PROVIDERS, 16);
+    assert_layout!(ProviderAllowlist, size = 528, {
+        discriminator: 0,
+        num_providers: 8,
+        bump: 9,
+        _padding0: 10,
+        providers: 16,
+    });
+}
+
+#[test]
+fn test_provider_directory_page_layout() {
+    assert_eq!(PROVIDER_DIRECTORY_PAGE_LEN, 64);
+    assert_layout!(ProviderDirectoryPage, size = 2064, {
+        discriminator: 0,
+        page: 8,
+        num_providers: 12,
+        bump: 13,
+        _padding0: 14,
+        providers: 16,
+    });
+}
+
+#[test]
+fn test_callback_account_list_layout() {
+    assert_eq!(MAX_CALLBACK_ACCOUNT_LIST_LEN, 32);
+    assert_layout!(CallbackAccountList, size = 1200, {
+        discriminator: 0,
+        request_account: 8,
+        num_accounts: 40,
+        bump: 41,
+        _padding0: 42,
+        callback_accounts: 48,
+    });
+}
+
+#[test]
+fn test_callback_failure_stats_layout() {
+    assert_layout!(CallbackFailureStats, size = 112, {
+        discriminator: 0,
+        provider_authority: 8,
+        requester_program_id: 40,
+        total_failures: 72,
+        total_callbacks: 80,
+        window_start_slot: 88,
+        window_failures: 96,
+        window_callbacks: 100,
+        bump: 104,
+        _padding0: 105,
+    });
+}
+
+#[test]
+fn test_account_lens_match_layouts() {
+    assert_eq!(<Config as Account>::LEN, 152);
+    assert_eq!(<Provider as Account>::LEN, 552);
+    assert_eq!(<Request as Account>::LEN, 1080);
+    assert_eq!(<GovernanceConfig as Account>::LEN, 96);
+    assert_eq!(<KeeperLease as Account>::LEN, 96);
+    assert_eq!(<AggregateRequest as Account>::LEN, 1480);
+    assert_eq!(<ProgramRegistry as Account>::LEN, 528);
+    assert_eq!(<ProviderAllowlist as Account>::LEN, 528);
+    assert_eq!(<ProviderDirectoryPage as Account>::LEN, 2064);
+    assert_eq!(<CallbackAccountList as Account>::LEN, 1200);
+    assert_eq!(<CallbackFailureStats as Account>::LEN, 112);
+    assert_eq!(CallbackMeta::LEN, 36);
+}
+
+#[test]
+fn test_account_discriminators() {
+    assert_eq!(Config::discriminator(), [0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(Provider::discriminator(), [1, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(Request::discriminator(), [2, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(GovernanceConfig::discriminator(), [3, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(KeeperLease::discriminator(), [4, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(AggregateRequest::discriminator(), [5, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(ProgramRegistry::discriminator(), [6, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(ProviderAllowlist::discriminator(), [7, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(
+        ProviderDirectoryPage::discriminator(),
+        [8, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(
+        CallbackAccountList::discriminator(),
+        [9, 0, 0, 0, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn test_callback_status_values() {
+    for (status, value) in [
+        (CallbackStatus::NotNecessary, 0u8),
+        (CallbackStatus::NotStarted, 1),
+        (CallbackStatus::InProgress, 2),
+        (CallbackStatus::Failed, 3),
+    ] {
+        assert_eq!(u8::from(status), value);
+        assert_eq!(CallbackStatus::try_from(value), Ok(status));
+    }
+    assert!(CallbackStatus::try_from(4).is_err());
+}
@@ -0,0 +1,226 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::{
+        accounts::Config, error::EntropyError, pda::config_pda, program_data::program_data_address,
+    },
+    entropy_sdk::{
+        events::{parse_logs, EntropyEvent},
+        instruction::{claim_admin, initialize_governance, set_default_provider, set_pyth_fee},
+    },
+    entropy_test_utils::{
+        add_program_data, build_initialize_ix, deployer, new_entropy_program_test, submit_tx,
+        submit_tx_expect_err, submit_tx_with_metadata,
+    },
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey},
+    solana_program_test::{processor, BanksClient, ProgramTest},
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+/// Starts entropy, initialized by the deployer with another key as admin.
+async fn setup() -> (BanksClient, Keypair, Pubkey, Keypair) {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
+
+    let admin = Keypair::new();
+    let deployer = deployer();
+    let initialize_ix = build_initialize_ix(
+        program_id,
+        deployer.pubkey(),
+        admin.pubkey(),
+        Pubkey::new_unique(),
+        0,
+    );
+    submit_tx(&mut banks_client, &payer, &[initialize_ix], &[&deployer]).await;
+
+    (banks_client, payer, program_id, admin)
+}
+
+async fn config(banks_client: &mut BanksClient, program_id: &Pubkey) -> Config {
+    let account = banks_client
+        .get_account(config_pda(program_id).0)
+        .await
+        .unwrap()
+        .unwrap();
+    *try_from_bytes::<Config>(&account.data).unwrap()
+}
+
+async fn admin(banks_client: &mut BanksClient, program_id: &Pubkey) -> [u8; 32] {
+    config(banks_client, program_id).await.admin
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_upgrade_authority_claims_admin() {
+    let (mut banks_client, payer, program_id, _) = setup().await;
+    let deployer = deployer();
+
+    let ix = claim_admin(program_id, deployer.pubkey());
+    submit_tx(&mut banks_client, &payer, &[ix], &[&deployer]).await;
+
+    assert_eq!(
+        admin(&mut banks_client, &program_id).await,
+        deployer.pubkey().to_bytes()
+    );
+}
+
+#[tokio::test]
+async fn test_claim_admin_rejects_other_signers() {
+    let (mut banks_client, payer, program_id, previous_admin) = setup().await;
+
+    let ix = claim_admin(program_id, payer.pubkey());
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::Unauthorized));
+    assert_eq!(
+        admin(&mut banks_client, &program_id).await,
+        previous_admin.pubkey().to_bytes()
+    );
+}
+
+#[tokio::test]
+async fn test_claim_admin_rejects_immutable_program() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "entropy",
+        program_id,
+        processor!(entropy::processor::process_instruction),
+    );
+    add_program_data(&mut program_test, program_id, None);
+    let (mut banks_client, payer, _) = program_test.start().await;
+
+    let ix = claim_admin(program_id, payer.pubkey());
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::Unauthorized));
+}
+
+#[tokio::test]
+async fn test_claim_admin_rejects_fake_program_data() {
+    let (mut banks_client, payer, program_id, _) = setup().await;
+
+    let mut ix = claim_admin(program_id, payer.pubkey());
+    ix.accounts[2] = AccountMeta::new_readonly(config_pda(&program_id).0, false);
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::WrongProgramData));
+}
+
+#[tokio::test]
+async fn test_upgrade_authority_passes_admin_checks() {
+    let (mut banks_client, payer, program_id, _) = setup().await;
+    let deployer = deployer();
+
+    let mut ix = initialize_governance(
+        program_id,
+        deployer.pubkey(),
+        payer.pubkey(),
+        Pubkey::new_unique(),
+        1,
+        [1u8; 32],
+    );
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[ix.clone()], &[&deployer]).await;
+    assert_eq!(err, custom(EntropyError::Unauthorized));
+
+    ix.accounts.push(AccountMeta::new_readonly(
+        program_data_address(&program_id),
+        false,
+    ));
+    submit_tx(&mut banks_client, &payer, &[ix], &[&deployer]).await;
+}
+
+#[tokio::test]
+async fn test_set_pyth_fee_emits_event() {
+    let (mut banks_client, payer, program_id, admin) = setup().await;
+
+    let ix = set_pyth_fee(program_id, admin.pubkey(), 4_321);
+    let (result, logs) = submit_tx_with_metadata(&mut banks_client, &payer, &[ix], &[&admin]).await;
+    result.unwrap();
+
+    assert_eq!(
+        config(&mut banks_client, &program_id)
+            .await
+            .pyth_fee_lamports,
+        4_321
+    );
+    let events = parse_logs(&program_id, &logs);
+    let [EntropyEvent::PythFeeSet(event)] = events.as_slice() else {
+        panic!("expected one PythFeeSet event");
+    };
+    assert_eq!(event.old_fee_lamports, 0);
+    assert_eq!(event.new_fee_lamports, 4_321);
+}
+
+#[tokio::test]
+async fn test_set_default_provider_emits_event() {
+    let (mut banks_client, payer, program_id, admin) = setup().await;
+    let old_default_provider = config(&mut banks_client, &program_id)
+        .await
+        .default_provider;
+    let new_default_provider = Pubkey::new_unique();
+
+    let ix = set_default_provider(program_id, admin.pubkey(), new_default_provider);
+    let (result, logs) = submit_tx_with_metadata(&mut banks_client, &payer, &[ix], &[&admin]).await;
+    result.unwrap();
+
+    assert_eq!(
+        config(&mut banks_client, &program_id)
+            .await
+            .default_provider,
+        new_default_provider.to_bytes()
+    );
+    let events = parse_logs(&program_id, &logs);
+    let [EntropyEvent::DefaultProviderSet(event)] = events.as_slice() else {
+        panic!("expected one DefaultProviderSet event");
+    };
+    assert_eq!(event.old_default_provider, old_default_provider);
+    assert_eq!(event.new_default_provider, new_default_provider.to_bytes());
+}
+
+#[tokio::test]
+async fn test_set_default_provider_rejects_zero() {
+    let (mut banks_client, payer, program_id, admin) = setup().await;
+
+    let ix = set_default_provider(program_id, admin.pubkey(), Pubkey::default());
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[ix], &[&admin]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+}
+
+#[tokio::test]
+async fn test_admin_ops_reject_non_admin() {
+    let (mut banks_client, payer, program_id, _) = setup().await;
+
+    let ix = set_pyth_fee(program_id, payer.pubkey(), 1);
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::Unauthorized));
+
+    let ix = set_default_provider(program_id, payer.pubkey(), Pubkey::new_unique());
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::Unauthorized));
+}
+
+#[tokio::test]
+async fn test_upgrade_authority_sets_pyth_fee() {
+    let (mut banks_client, payer, program_id, _) = setup().await;
+    let deployer = deployer();
+
+    let mut ix = set_pyth_fee(program_id, deployer.pubkey(), 99);
+    ix.accounts.push(AccountMeta::new_readonly(
+        program_data_address(&program_id),
+        false,
+    ));
+    submit_tx(&mut banks_client, &payer, &[ix], &[&deployer]).await;
+
+    assert_eq!(
+        config(&mut banks_client, &program_id)
+            .await
+            .pyth_fee_lamports,
+        99
+    );
+}
@@ -0,0 +1,514 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::{
+        accounts::{AggregateRequest, Provider},
+        constants::RANDOM_NUMBER_V1,
+        error::EntropyError,
+        events::AggregateRandomnessRevealed,
+        instruction::RevealAggregateArgs,
+        pda::{provider_pda, provider_vault_pda},
+        randomness::{aggregate_provider_contribution, combine_random_values},
+    },
+    entropy_sdk::{
+        events::{parse_logs, EntropyEvent},
+        instruction::{
+            close_expired_aggregate_request, forward_request_aggregate, request_aggregate,
+            reveal_aggregate, AggregateRequestAccounts, AggregateRequestParams,
+        },
+    },
+    entropy_test_utils::{
+        build_register_args, build_register_provider_ix, submit_tx, submit_tx_expect_err,
+        submit_tx_with_metadata, EntropyFixture, EntropyTestEnv, HashChain,
+    },
+    simple_requester::{CallbackState, CALLBACK_ACTION},
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey, system_instruction},
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+const CHAIN_LENGTH: u64 = 16;
+const USER_RANDOMNESS: [u8; 32] = [7u8; 32];
+
+/// Offset of `RequestAggregateHeader::threshold` in the forwarded instruction
+/// data: action byte, discriminator, randomness, CU limit, TTL, provider count.
+const FORWARDED_THRESHOLD_OFFSET: usize = 1 + 8 + 32 + 4 + 4 + 1;
+
+struct TestProvider {
+    authority: Pubkey,
+    vault: Pubkey,
+    chain: HashChain,
+}
+
+/// Starts a fixture with `num_providers` registered providers, all charging
+/// `provider_fee`. The first is the fixture's own provider.
+async fn start(num_providers: usize, provider_fee: u64) -> (EntropyTestEnv, Vec<TestProvider>) {
+    let mut env = EntropyFixture::new()
+        .with_provider(provider_fee, CHAIN_LENGTH)
+        .with_requester()
+        .start()
+        .await;
+    let payer = env.payer();
+    let mut providers = vec![TestProvider {
+        authority: env.provider().authority,
+        vault: env.provider().vault,
+        chain: env.provider().chain.clone(),
+    }];
+
+    for index in 1..num_providers {
+        let authority = Keypair::new();
+        let chain = HashChain::from_seed([10 + index as u8; 32], CHAIN_LENGTH);
+        let (address, _) = provider_pda(&env.program_id, &authority.pubkey());
+        let (vault, _) = provider_vault_pda(&env.program_id, &authority.pubkey());
+        let fund_ix =
+            system_instruction::transfer(&payer.pubkey(), &authority.pubkey(), 1_000_000_000);
+        let register_ix = build_register_provider_ix(
+            env.program_id,
+            authority.pubkey(),
+            address,
+            vault,
+            build_register_args(provider_fee, chain.commitment(), CHAIN_LENGTH),
+            true,
+        );
+        submit_tx(
+            &mut env.context.banks_client,
+            &payer,
+            &[fund_ix, register_ix],
+            &[&authority],
+        )
+        .await;
+        providers.push(TestProvider {
+            authority: authority.pubkey(),
+            vault,
+            chain,
+        });
+    }
+    (env, providers)
+}
+
+/// Builds an aggregate request through simple-requester's forwarding action.
+/// Without a `refund_address` the payer gets the refunds.
+fn request_ix(
+    env: &EntropyTestEnv,
+    request_account: Pubkey,
+    provider_authorities: Vec<Pubkey>,
+    threshold: u8,
+    ttl_slots: u32,
+    min_reveal_delay_slots: u16,
+    refund_address: Option<Pubkey>,
+) -> Instruction {
+    let requester_program = env.requester().program_id;
+    let mut callback_ix_data = vec![CALLBACK_ACTION];
+    callback_ix_data.extend_from_slice(env.program_id.as_ref());
+    let entropy_ix = request_aggregate(
+        env.program_id,
+        &AggregateRequestAccounts {
+            requester_program,
+            payer: env.payer().pubkey(),
+            request_account,
+            provider_authorities,
+        },
+        USER_RANDOMNESS,
+        threshold,
+        &AggregateRequestParams {
+            compute_unit_limit: 100_000,
+            ttl_slots,
+            min_reveal_delay_slots,
+            random_number_version: RANDOM_NUMBER_V1,
+            refund_address,
+            callback_accounts: &[AccountMeta::new(env.requester().callback_state, false)],
+            callback_ix_data: &callback_ix_data,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    forward_request_aggregate(requester_program, &entropy_ix)
+}
+
+async fn request(
+    env: &mut EntropyTestEnv,
+    providers: &[TestProvider],
+    threshold: u8,
+    ttl_slots: u32,
+    min_reveal_delay_slots: u16,
+    refund_address: Option<Pubkey>,
+) -> (Pubkey, AggregateRequest) {
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    let instruction = request_ix(
+        env,
+        request_account.pubkey(),
+        providers
+            .iter()
+            .map(|provider| provider.authority)
+            .collect(),
+        threshold,
+        ttl_slots,
+        min_reveal_delay_slots,
+        refund_address,
+    );
+    submit_tx(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+
+    let account = env
+        .context
+        .banks_client
+        .get_account(request_account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let request = *try_from_bytes::<AggregateRequest>(&account.data).unwrap();
+    (request_account.pubkey(), request)
+}
+
+/// Reveals `providers[index]`'s contribution. Returns the finalization event
+/// if this reveal reached the threshold.
+async fn reveal(
+    env: &mut EntropyTestEnv,
+    request_account: Pubkey,
+    request: &AggregateRequest,
+    providers: &[TestProvider],
+    index: u8,
+) -> Result<Option<AggregateRandomnessRevealed>, TransactionError> {
+    let payer = env.payer();
+    let contribution = request.contributions[usize::from(index)];
+    let provider_contribution = providers[usize::from(index)]
+        .chain
+        .contribution_for_sequence(contribution.sequence_number)
+        .unwrap();
+    let reveal_ix = reveal_aggregate(
+        env.program_id,
+        request_account,
+        request,
+        RevealAggregateArgs {
+            user_contribution: USER_RANDOMNESS,
+            provider_contribution,
+            provider_index: index,
+            _padding0: [0u8; 7],
+        },
+    );
+    env.context.get_new_latest_blockhash().await.unwrap();
+    let (result, logs) =
+        submit_tx_with_metadata(&mut env.context.banks_client, &payer, &[reveal_ix], &[]).await;
+    result.map(|()| {
+        parse_logs(&env.program_id, &logs)
+            .into_iter()
+            .find_map(|event| match event {
+                EntropyEvent::AggregateRandomnessRevealed(revealed) => Some(revealed),
+                _ => None,
+            })
+    })
+}
+
+async fn provider_account(env: &mut EntropyTestEnv, authority: &Pubkey) -> Provider {
+    let (address, _) = provider_pda(&env.program_id, authority);
+    let account = env
+        .context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    *try_from_bytes::<Provider>(&account.data).unwrap()
+}
+
+async fn callback_state(env: &mut EntropyTestEnv) -> CallbackState {
+    let account = env
+        .context
+        .banks_client
+        .get_account(env.requester().callback_state)
+        .await
+        .unwrap()
+        .unwrap();
+    *try_from_bytes::<CallbackState>(&account.data).unwrap()
+}
+
+async fn lamports(env: &mut EntropyTestEnv, address: Pubkey) -> Option<u64> {
+    env.context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .map(|account| account.lamports)
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_aggregate_request_finalizes_at_threshold() {
+    let (mut env, providers) = start(2, 0).await;
+
+    let (request_account, request) = request(&mut env, &providers, 2, 0, 0, None).await;
+    assert_eq!(request.num_providers, 2);
+    assert_eq!(request.threshold, 2);
+    for (contribution, provider) in request.active_contributions().iter().zip(&providers) {
+        assert_eq!(contribution.provider, provider.authority.to_bytes());
+        assert_eq!(contribution.revealed, 0);
+        let account = provider_account(&mut env, &provider.authority).await;
+        assert_eq!(account.outstanding_requests, 1);
+    }
+
+    let first = reveal(&mut env, request_account, &request, &providers, 0)
+        .await
+        .unwrap();
+    assert!(first.is_none());
+    assert_eq!(callback_state(&mut env).await.called, 0);
+
+    let revealed = reveal(&mut env, request_account, &request, &providers, 1)
+        .await
+        .unwrap()
+        .expect("second reveal reaches the threshold");
+    assert_eq!(revealed.revealed_mask, 0b11);
+    assert_eq!(revealed.callback_invoked, 1);
+
+    let contributions: Vec<[u8; 32]> = request
+        .active_contributions()
+        .iter()
+        .zip(&providers)
+        .map(|(contribution, provider)| {
+            provider
+                .chain
+                .contribution_for_sequence(contribution.sequence_number)
+                .unwrap()
+        })
+        .collect();
+    let expected = combine_random_values(
+        RANDOM_NUMBER_V1,
+        &env.program_id,
+        &USER_RANDOMNESS,
+        &aggregate_provider_contribution(&[&contributions[0], &contributions[1]]),
+        &[0u8; 32],
+    );
+    assert_eq!(revealed.random_number, expected);
+
+    let state = callback_state(&mut env).await;
+    assert_eq!(state.called, 1);
+    assert_eq!(state.random_number, expected);
+    assert_eq!(
+        state.sequence_number,
+        request.contributions[0].sequence_number
+    );
+    assert_eq!(state.provider, request.contributions[0].provider);
+
+    assert_eq!(lamports(&mut env, request_account).await, None);
+    for provider in &providers {
+        let account = provider_account(&mut env, &provider.authority).await;
+        assert_eq!(account.outstanding_requests, 0);
+        assert_eq!(account.reveal_count, 1);
+    }
+}
+
+#[tokio::test]
+async fn test_aggregate_request_drops_providers_that_miss_the_threshold() {
+    let (mut env, providers) = start(3, 20_000).await;
+    let refund_address = Pubkey::new_unique();
+
+    let (request_account, request) =
+        request(&mut env, &providers, 2, 0, 0, Some(refund_address)).await;
+    assert_eq!(request.refund_address, refund_address.to_bytes());
+    assert!(reveal(&mut env, request_account, &request, &providers, 2)
+        .await
+        .unwrap()
+        .is_none());
+    let result = reveal(&mut env, request_account, &request, &providers, 2).await;
+    assert!(matches!(result, Err(err) if err == custom(EntropyError::InvalidRevealCall)));
+
+    let rent = lamports(&mut env, request_account).await.unwrap();
+    let dropped_vault_before = lamports(&mut env, providers[1].vault).await.unwrap();
+    let revealed = reveal(&mut env, request_account, &request, &providers, 0)
+        .await
+        .unwrap()
+        .expect("second reveal reaches the threshold");
+    assert_eq!(revealed.revealed_mask, 0b101);
+
+    let dropped = provider_account(&mut env, &providers[1].authority).await;
+    assert_eq!(dropped.outstanding_requests, 0);
+    assert_eq!(dropped.reveal_count, 0);
+    assert_eq!(
+        lamports(&mut env, providers[1].vault).await.unwrap(),
+        dropped_vault_before - 20_000
+    );
+    assert_eq!(
+        lamports(&mut env, refund_address).await,
+        Some(rent + 20_000)
+    );
+    assert_eq!(lamports(&mut env, request_account).await, None);
+}
+
+/// Any `threshold` providers can finalize, so a provider that has seen the
+/// others' reveals can withhold its own. With `threshold == num_providers`
+/// withholding only stalls the request until it expires and is refunded.
+#[tokio::test]
+async fn test_withheld_reveal_leaves_request_to_expire() {
+    let (mut env, providers) = start(2, 20_000).await;
+    let payer = env.payer();
+
+    let (request_account, request) = request(&mut env, &providers, 2, 5, 0, None).await;
+    assert!(reveal(&mut env, request_account, &request, &providers, 0)
+        .await
+        .unwrap()
+        .is_none());
+
+    env.context.warp_to_slot(request.request_slot + 5).unwrap();
+    assert!(lamports(&mut env, request_account).await.is_some());
+    assert_eq!(callback_state(&mut env).await.called, 0);
+
+    env.context.warp_to_slot(request.request_slot + 6).unwrap();
+    let result = reveal(&mut env, request_account, &request, &providers, 1).await;
+    assert!(matches!(result, Err(err) if err == custom(EntropyError::RequestExpired)));
+
+    let withheld_vault_before = lamports(&mut env, providers[1].vault).await.unwrap();
+    let close_ix = close_expired_aggregate_request(env.program_id, request_account, &request);
+    submit_tx(&mut env.context.banks_client, &payer, &[close_ix], &[]).await;
+    assert_eq!(
+        lamports(&mut env, providers[1].vault).await.unwrap(),
+        withheld_vault_before - 20_000
+    );
+    assert_eq!(callback_state(&mut env).await.called, 0);
+    assert_eq!(lamports(&mut env, request_account).await, None);
+}
+
+#[tokio::test]
+async fn test_reveal_aggregate_rejects_wrong_contribution() {
+    let (mut env, providers) = start(2, 0).await;
+    let payer = env.payer();
+
+    let (request_account, request) = request(&mut env, &providers, 2, 0, 0, None).await;
+    let reveal_ix = reveal_aggregate(
+        env.program_id,
+        request_account,
+        &request,
+        RevealAggregateArgs {
+            user_contribution: USER_RANDOMNESS,
+            provider_contribution: [9u8; 32],
+            provider_index: 0,
+            _padding0: [0u8; 7],
+        },
+    );
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &payer, &[reveal_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::IncorrectRevelation));
+}
+
+#[tokio::test]
+async fn test_request_aggregate_rejects_invalid_providers() {
+    let (mut env, providers) = start(2, 0).await;
+    let payer = env.payer();
+    let authorities: Vec<Pubkey> = providers
+        .iter()
+        .map(|provider| provider.authority)
+        .collect();
+
+    let request_account = Keypair::new();
+    let mut instruction = request_ix(
+        &env,
+        request_account.pubkey(),
+        authorities.clone(),
+        2,
+        0,
+        0,
+        None,
+    );
+    instruction.data[FORWARDED_THRESHOLD_OFFSET] = 3;
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::InvalidAggregateThreshold));
+
+    let instruction = request_ix(
+        &env,
+        request_account.pubkey(),
+        vec![authorities[0], authorities[0]],
+        1,
+        0,
+        0,
+        None,
+    );
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::DuplicateAggregateProvider));
+}
+
+#[tokio::test]
+async fn test_close_expired_aggregate_request_refunds_unrevealed_fees() {
+    let (mut env, providers) = start(2, 20_000).await;
+    let payer = env.payer();
+
+    let (request_account, request) = request(&mut env, &providers, 2, 5, 0, None).await;
+    reveal(&mut env, request_account, &request, &providers, 0)
+        .await
+        .unwrap();
+
+    let cranker = Keypair::new();
+    let fund_ix = system_instruction::transfer(&payer.pubkey(), &cranker.pubkey(), 1_000_000_000);
+    submit_tx(&mut env.context.banks_client, &payer, &[fund_ix], &[]).await;
+
+    let close_ix = close_expired_aggregate_request(env.program_id, request_account, &request);
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &cranker, &[close_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::RequestNotExpired));
+
+    env.context.warp_to_slot(request.request_slot + 6).unwrap();
+    let rent = lamports(&mut env, request_account).await.unwrap();
+    let payer_before = lamports(&mut env, payer.pubkey()).await.unwrap();
+    let revealed_vault_before = lamports(&mut env, providers[0].vault).await.unwrap();
+    let unrevealed_vault_before = lamports(&mut env, providers[1].vault).await.unwrap();
+    let close_ix = close_expired_aggregate_request(env.program_id, request_account, &request);
+    submit_tx(&mut env.context.banks_client, &cranker, &[close_ix], &[]).await;
+
+    assert_eq!(
+        lamports(&mut env, payer.pubkey()).await.unwrap(),
+        payer_before + rent + 20_000
+    );
+    assert_eq!(
+        lamports(&mut env, providers[0].vault).await.unwrap(),
+        revealed_vault_before
+    );
+    assert_eq!(
+        lamports(&mut env, providers[1].vault).await.unwrap(),
+        unrevealed_vault_before - 20_000
+    );
+    for provider in &providers {
+        let account = provider_account(&mut env, &provider.authority).await;
+        assert_eq!(account.outstanding_requests, 0);
+    }
+    assert_eq!(lamports(&mut env, request_account).await, None);
+}
+
+#[tokio::test]
+async fn test_reveal_aggregate_enforces_min_reveal_delay() {
+    let (mut env, providers) = start(2, 0).await;
+
+    let (request_account, request) = request(&mut env, &providers, 2, 0, 4, None).await;
+    assert_eq!(request.min_reveal_delay_slots, 4);
+
+    env.context.warp_to_slot(request.request_slot + 3).unwrap();
+    let result = reveal(&mut env, request_account, &request, &providers, 0).await;
+    assert!(matches!(result, Err(err) if err == custom(EntropyError::RevealTooEarly)));
+
+    env.context.warp_to_slot(request.request_slot + 4).unwrap();
+    assert!(reveal(&mut env, request_account, &request, &providers, 0)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(reveal(&mut env, request_account, &request, &providers, 1)
+        .await
+        .unwrap()
+        .is_some());
+}
@@ -0,0 +1,289 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::{
+        accounts::{CallbackAccountList, Request},
+        constants::{MAX_CALLBACK_ACCOUNTS, MAX_CALLBACK_ACCOUNT_LIST_LEN},
+        error::EntropyError,
+        pda::callback_account_list_pda,
+    },
+    entropy_sdk::instruction::{
+        close_callback_account_list, close_expired_request, forward_request_with_callback,
+        with_callback_account_list, write_callback_account_list, RequestParams,
+    },
+    entropy_test_utils::{start_with_requester, submit_tx, submit_tx_expect_err, EntropyTestEnv},
+    simple_requester::{CallbackState, CALLBACK_ACTION},
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    },
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+fn listed_accounts(count: usize) -> Vec<AccountMeta> {
+    (0..count)
+        .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+        .collect()
+}
+
+/// Writes `accounts` to the callback account list of `request_account`, one
+/// transaction per chunk.
+async fn write_list(env: &mut EntropyTestEnv, request_account: &Keypair, accounts: &[AccountMeta]) {
+    let payer = env.payer();
+    for chunk in accounts.chunks(MAX_CALLBACK_ACCOUNTS) {
+        let instruction = write_callback_account_list(
+            env.program_id,
+            payer.pubkey(),
+            request_account.pubkey(),
+            chunk,
+        )
+        .unwrap();
+        submit_tx(
+            &mut env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[request_account],
+        )
+        .await;
+    }
+}
+
+/// A simple-requester callback request for `request_account` whose entropy
+/// instruction carries the callback account list of `list_owner`.
+fn build_request(
+    env: &EntropyTestEnv,
+    request_account: &Keypair,
+    list_owner: &Keypair,
+    ttl_slots: u32,
+) -> Instruction {
+    let entropy_ix = env.callback_request_ix(
+        request_account,
+        CALLBACK_ACTION,
+        RequestParams {
+            compute_unit_limit: 200_000,
+            ttl_slots,
+            ..Default::default()
+        },
+    );
+    let mut entropy_ix = with_callback_account_list(entropy_ix);
+    let (list, _) = callback_account_list_pda(&env.program_id, &list_owner.pubkey());
+    entropy_ix.accounts.last_mut().unwrap().pubkey = list;
+    forward_request_with_callback(env.requester().program_id, &entropy_ix)
+}
+
+async fn fetch<T: bytemuck::Pod>(env: &mut EntropyTestEnv, address: Pubkey) -> Option<T> {
+    env.context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .map(|account| *try_from_bytes::<T>(&account.data).unwrap())
+}
+
+async fn lamports(env: &mut EntropyTestEnv, address: Pubkey) -> Option<u64> {
+    env.context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .map(|account| account.lamports)
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_reveal_passes_listed_accounts() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    let accounts = listed_accounts(MAX_CALLBACK_ACCOUNTS + 1);
+    write_list(&mut env, &request_account, &accounts).await;
+
+    let (list_address, _) = callback_account_list_pda(&env.program_id, &request_account.pubkey());
+    let list: CallbackAccountList = fetch(&mut env, list_address).await.unwrap();
+    assert_eq!(
+        list.callback_accounts()
+            .iter()
+            .map(|meta| Pubkey::new_from_array(meta.pubkey))
+            .collect::<Vec<_>>(),
+        accounts.iter().map(|meta| meta.pubkey).collect::<Vec<_>>()
+    );
+    assert_eq!(list.request_account, request_account.pubkey().to_bytes());
+
+    let instruction = build_request(&env, &request_account, &request_account, 0);
+    submit_tx(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    let request: Request = fetch(&mut env, request_account.pubkey()).await.unwrap();
+    assert_eq!(request.callback_account_list, list_address.to_bytes());
+
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    assert_eq!(reveals.len(), 1);
+    assert!(reveals[0].result.is_ok());
+
+    let callback_state = env.requester().callback_state;
+    let state: CallbackState = fetch(&mut env, callback_state).await.unwrap();
+    assert_eq!(state.called, 1);
+    // The list closes with the request.
+    assert!(lamports(&mut env, list_address).await.is_none());
+    assert!(lamports(&mut env, request_account.pubkey()).await.is_none());
+}
+
+#[tokio::test]
+async fn test_request_rejects_other_requests_list() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+    let list_owner = Keypair::new();
+    write_list(&mut env, &list_owner, &listed_accounts(2)).await;
+
+    let request_account = Keypair::new();
+    let instruction = build_request(&env, &request_account, &list_owner, 0);
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::WrongCallbackAccountList));
+}
+
+#[tokio::test]
+async fn test_write_rejects_made_request() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    write_list(&mut env, &request_account, &listed_accounts(2)).await;
+    let instruction = build_request(&env, &request_account, &request_account, 0);
+    submit_tx(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+
+    let write_ix = write_callback_account_list(
+        env.program_id,
+        payer.pubkey(),
+        request_account.pubkey(),
+        &listed_accounts(1),
+    )
+    .unwrap();
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[write_ix],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::RequestAccountNotEmpty));
+}
+
+#[tokio::test]
+async fn test_write_rejects_list_overflow() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    write_list(
+        &mut env,
+        &request_account,
+        &listed_accounts(MAX_CALLBACK_ACCOUNT_LIST_LEN),
+    )
+    .await;
+
+    let write_ix = write_callback_account_list(
+        env.program_id,
+        payer.pubkey(),
+        request_account.pubkey(),
+        &listed_accounts(1),
+    )
+    .unwrap();
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[write_ix],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::TooManyCallbackAccounts));
+}
+
+#[tokio::test]
+async fn test_close_unused_list_refunds_rent() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    write_list(&mut env, &request_account, &listed_accounts(3)).await;
+    let (list_address, _) = callback_account_list_pda(&env.program_id, &request_account.pubkey());
+
+    let recipient = Pubkey::new_unique();
+    let rent = lamports(&mut env, list_address).await.unwrap();
+    let close_ix = close_callback_account_list(env.program_id, request_account.pubkey(), recipient);
+    submit_tx(
+        &mut env.context.banks_client,
+        &payer,
+        &[close_ix],
+        &[&request_account],
+    )
+    .await;
+
+    assert!(lamports(&mut env, list_address).await.is_none());
+    assert_eq!(lamports(&mut env, recipient).await, Some(rent));
+}
+
+#[tokio::test]
+async fn test_close_expired_request_closes_list() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    write_list(&mut env, &request_account, &listed_accounts(3)).await;
+    let instruction = build_request(&env, &request_account, &request_account, 5);
+    submit_tx(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    let request: Request = fetch(&mut env, request_account.pubkey()).await.unwrap();
+    let list_address = Pubkey::new_from_array(request.callback_account_list);
+
+    // Without the list the close is rejected, so the list cannot be stranded.
+    let mut close_ix = close_expired_request(env.program_id, request_account.pubkey(), &request);
+    env.context.warp_to_slot(request.request_slot + 6).unwrap();
+    let mut without_list = close_ix.clone();
+    without_list.accounts.pop();
+    let err =
+        submit_tx_expect_err(&mut env.context.banks_client, &payer, &[without_list], &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    close_ix.accounts[5].pubkey = env.requester().callback_state;
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[close_ix.clone()],
+        &[],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::WrongCallbackAccountList));
+
+    close_ix.accounts[5].pubkey = list_address;
+    submit_tx(&mut env.context.banks_client, &payer, &[close_ix], &[]).await;
+    assert!(lamports(&mut env, list_address).await.is_none());
+    assert!(lamports(&mut env, request_account.pubkey()).await.is_none());
+}
@@ -0,0 +1,134 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::instruction::INSTRUCTION_DISCRIMINATOR_LEN,
+    entropy_sdk::instruction::{forward_request_with_callback, RequestParams},
+    entropy_test_utils::{
+        start_with_requester, submit_tx_expect_err, EntropyTestEnv, REQUEST_USER_RANDOMNESS,
+    },
+    simple_requester::{CallbackState, CALLBACK_ACTION, CALLBACK_WITH_CONTRIBUTIONS_ACTION},
+    solana_program::{hash::hashv, instruction::Instruction},
+    solana_sdk::{
+        instruction::InstructionError, signature::Keypair, transaction::TransactionError,
+    },
+};
+
+/// Offset of `include_contributions` in `RequestWithCallback` data.
+const INCLUDE_CONTRIBUTIONS_OFFSET: usize = INSTRUCTION_DISCRIMINATOR_LEN + 32 + 4 + 4 + 2;
+
+/// An entropy `RequestWithCallback` for simple-requester, calling back with
+/// `callback_action` into the fixture's callback state.
+fn build_request(
+    env: &EntropyTestEnv,
+    request_account: &Keypair,
+    include_contributions: bool,
+    callback_action: u8,
+) -> Instruction {
+    env.callback_request_ix(
+        request_account,
+        callback_action,
+        RequestParams {
+            compute_unit_limit: 100_000,
+            include_contributions,
+            ..Default::default()
+        },
+    )
+}
+
+async fn callback_state(env: &mut EntropyTestEnv) -> CallbackState {
+    let account = env
+        .context
+        .banks_client
+        .get_account(env.requester().callback_state)
+        .await
+        .unwrap()
+        .unwrap();
+    *try_from_bytes::<CallbackState>(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_callback_receives_contributions() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    let entropy_ix = build_request(
+        &env,
+        &request_account,
+        true,
+        CALLBACK_WITH_CONTRIBUTIONS_ACTION,
+    );
+    let instruction = forward_request_with_callback(env.requester().program_id, &entropy_ix);
+
+    let reveals = keeper
+        .submit(
+            &mut env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await
+        .unwrap();
+    assert_eq!(reveals.len(), 1);
+    assert!(reveals[0].result.is_ok());
+
+    let state = callback_state(&mut env).await;
+    let provider_contribution = env
+        .provider()
+        .chain
+        .contribution_for_sequence(reveals[0].sequence_number)
+        .unwrap();
+    assert_eq!(state.called, 1);
+    assert_eq!(state.user_contribution, REQUEST_USER_RANDOMNESS);
+    assert_eq!(state.provider_contribution, provider_contribution);
+    // Callback requests never mix in a blockhash.
+    assert_eq!(
+        state.random_number,
+        hashv(&[&REQUEST_USER_RANDOMNESS, &provider_contribution, &[0u8; 32]]).to_bytes()
+    );
+}
+
+#[tokio::test]
+async fn test_callback_without_flag_omits_contributions() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    let entropy_ix = build_request(&env, &request_account, false, CALLBACK_ACTION);
+    let instruction = forward_request_with_callback(env.requester().program_id, &entropy_ix);
+
+    let reveals = keeper
+        .submit(
+            &mut env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await
+        .unwrap();
+    assert!(reveals[0].result.is_ok());
+
+    let state = callback_state(&mut env).await;
+    assert_eq!(state.called, 1);
+    assert_eq!(state.user_contribution, [0u8; 32]);
+    assert_eq!(state.provider_contribution, [0u8; 32]);
+}
+
+#[tokio::test]
+async fn test_request_rejects_invalid_include_contributions() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    let mut entropy_ix = build_request(&env, &request_account, true, CALLBACK_ACTION);
+    entropy_ix.data[INCLUDE_CONTRIBUTIONS_OFFSET] = 2;
+    let instruction = forward_request_with_callback(env.requester().program_id, &entropy_ix);
+
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+    );
+}
@@ -0,0 +1,532 @@
+//! Skipping the callbacks of a requester program whose callbacks keep
+//! failing: the provider reports each failure it sees, once per request, and
+//! once its skip policy's limit is reached, with failures outnumbering the
+//! callbacks that succeeded, it may reveal that program's failed requests
+//! without the callback, refunding the provider fee.
+
+#[allow(deprecated)]
+use {
+    bytemuck::{try_from_bytes, Zeroable},
+    entropy::{
+        accounts::{CallbackFailureStats, CallbackStatus, Provider, Request},
+        constants::REQUESTER_SIGNER_SEED,
+        error::EntropyError,
+        instruction::{EntropyInstruction, RequestWithCallbackHeader, RevealArgs},
+        pda::{
+            callback_failure_stats_pda, config_pda, provider_pda, provider_vault_pda,
+            pyth_fee_vault_pda,
+        },
+    },
+    entropy_sdk::{
+        report_callback_failure, reveal_skipping_callback, reveal_with_callback,
+        set_callback_skip_policy,
+    },
+    entropy_test_utils::{
+        build_register_args, build_register_provider_ix, initialize_config,
+        new_entropy_program_test, submit_tx, submit_tx_expect_err,
+    },
+    malicious_requester::{
+        CALLBACK_ACTION, FAIL_ERROR, MODE_FAIL, MODE_SUCCEED, REQUEST_WITH_CALLBACK_ACTION,
+    },
+    solana_program::{
+        hash::hash,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+    solana_program_test::{processor, BanksClient},
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+const CHAIN_LENGTH: usize = 8;
+const PROVIDER_FEE: u64 = 1_000_000;
+const USER_RANDOMNESS: [u8; 32] = [9u8; 32];
+
+struct Env {
+    banks_client: BanksClient,
+    /// Pays for everything and is the provider authority.
+    payer: Keypair,
+    program_id: Pubkey,
+    requester_program_id: Pubkey,
+    /// `chain[i + 1] == hash(chain[i])`; the provider commits to the last one.
+    chain: Vec<[u8; 32]>,
+}
+
+impl Env {
+    async fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let requester_program_id = Pubkey::new_unique();
+        let mut program_test = new_entropy_program_test(program_id);
+        program_test.add_program(
+            "malicious-requester",
+            requester_program_id,
+            processor!(malicious_requester::process_instruction),
+        );
+        let (mut banks_client, payer, _) = program_test.start().await;
+
+        initialize_config(&mut banks_client, &payer, program_id, 0).await;
+
+        let mut chain = vec![[3u8; 32]];
+        for _ in 0..CHAIN_LENGTH {
+            chain.push(hash(chain.last().unwrap()).to_bytes());
+        }
+        let register_ix = build_register_provider_ix(
+            program_id,
+            payer.pubkey(),
+            provider_pda(&program_id, &payer.pubkey()).0,
+            provider_vault_pda(&program_id, &payer.pubkey()).0,
+            build_register_args(PROVIDER_FEE, *chain.last().unwrap(), CHAIN_LENGTH as u64),
+            true,
+        );
+        submit_tx(&mut banks_client, &payer, &[register_ix], &[]).await;
+
+        Self {
+            banks_client,
+            payer,
+            program_id,
+            requester_program_id,
+            chain,
+        }
+    }
+
+    /// Requests through the malicious requester with a callback that always
+    /// fails.
+    async fn request_failing(&mut self) -> (Pubkey, Request) {
+        self.request(MODE_FAIL).await
+    }
+
+    /// Requests through the malicious requester with a callback in `mode`.
+    async fn request(&mut self, mode: u8) -> (Pubkey, Request) {
+        let request_account = Keypair::new();
+        let header = RequestWithCallbackHeader {
+            user_randomness: USER_RANDOMNESS,
+            compute_unit_limit: 100_000,
+            ttl_slots: 0,
+            min_reveal_delay_slots: 0,
+            include_contributions: 0,
+            random_number_version: 0,
+            refund_address: [0u8; 32],
+            ..RequestWithCallbackHeader::zeroed()
+        };
+        let mut callback_ix_data = vec![CALLBACK_ACTION, mode];
+        callback_ix_data.extend_from_slice(self.program_id.as_ref());
+
+        let (requester_signer, requester_signer_bump) = Pubkey::find_program_address(
+            &[REQUESTER_SIGNER_SEED, self.program_id.as_ref()],
+            &self.requester_program_id,
+        );
+
+        let mut data = vec![REQUEST_WITH_CALLBACK_ACTION];
+        data.extend_from_slice(&EntropyInstruction::build_callback_request(
+            &header,
+            requester_signer_bump,
+            &[],
+            &callback_ix_data,
+        ));
+        let instruction = Instruction {
+            program_id: self.requester_program_id,
+            data,
+            accounts: vec![
+                AccountMeta::new_readonly(requester_signer, false),
+                AccountMeta::new(self.payer.pubkey(), true),
+                AccountMeta::new_readonly(self.requester_program_id, false),
+                AccountMeta::new(request_account.pubkey(), true),
+                AccountMeta::new(
+                    provider_pda(&self.program_id, &self.payer.pubkey()).0,
+                    false,
+                ),
+                AccountMeta::new(
+                    provider_vault_pda(&self.program_id, &self.payer.pubkey()).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(config_pda(&self.program_id).0, false),
+                AccountMeta::new(pyth_fee_vault_pda(&self.program_id).0, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(self.requester_program_id, false),
+                AccountMeta::new_readonly(self.program_id, false),
+            ],
+        };
+        submit_tx(
+            &mut self.banks_client,
+            &self.payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await;
+
+        let account = self
+            .banks_client
+            .get_account(request_account.pubkey())
+            .await
+            .unwrap()
+            .unwrap();
+        let request = *try_from_bytes::<Request>(&account.data).unwrap();
+        (request_account.pubkey(), request)
+    }
+
+    async fn request_status(&mut self, request_account: Pubkey) -> CallbackStatus {
+        let account = self
+            .banks_client
+            .get_account(request_account)
+            .await
+            .unwrap()
+            .unwrap();
+        try_from_bytes::<Request>(&account.data)
+            .unwrap()
+            .status()
+            .unwrap()
+    }
+
+    fn reveal_args(&self, request: &Request) -> RevealArgs {
+        RevealArgs {
+            user_contribution: USER_RANDOMNESS,
+            provider_contribution: self.chain[CHAIN_LENGTH - request.sequence_number as usize],
+        }
+    }
+
+    async fn set_policy(&mut self, max_failures: u16, window_slots: u32) {
+        let instruction = set_callback_skip_policy(
+            self.program_id,
+            self.payer.pubkey(),
+            max_failures,
+            window_slots,
+        );
+        submit_tx(&mut self.banks_client, &self.payer, &[instruction], &[]).await;
+    }
+
+    async fn report(&mut self, request_account: Pubkey, request: &Request) {
+        let instruction = report_callback_failure(self.program_id, request_account, request);
+        submit_tx(&mut self.banks_client, &self.payer, &[instruction], &[]).await;
+    }
+
+    async fn stats(&mut self) -> CallbackFailureStats {
+        let (stats, _) = callback_failure_stats_pda(
+            &self.program_id,
+            &self.payer.pubkey(),
+            &self.requester_program_id,
+        );
+        let account = self.banks_client.get_account(stats).await.unwrap().unwrap();
+        *try_from_bytes::<CallbackFailureStats>(&account.data).unwrap()
+    }
+
+    async fn provider(&mut self) -> Provider {
+        let (provider, _) = provider_pda(&self.program_id, &self.payer.pubkey());
+        let account = self
+            .banks_client
+            .get_account(provider)
+            .await
+            .unwrap()
+            .unwrap();
+        *try_from_bytes::<Provider>(&account.data).unwrap()
+    }
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[test]
+fn test_failure_window() {
+    let mut stats = CallbackFailureStats::zeroed();
+    // No window is open before the first failure.
+    stats.record_callback(90, 10);
+    assert_eq!(stats.window_callbacks, 0);
+
+    stats.record_failure(100, 10);
+    stats.record_callback(105, 10);
+    stats.record_failure(109, 10);
+    assert_eq!(stats.window_start_slot, 100);
+    assert_eq!(stats.failures_in_window(109, 10), 2);
+    assert_eq!(stats.callbacks_in_window(109, 10), 1);
+    assert_eq!(stats.failures_in_window(110, 10), 0);
+    assert_eq!(stats.callbacks_in_window(110, 10), 0);
+
+    // A failure after the window closed starts the next one.
+    stats.record_failure(110, 10);
+    assert_eq!(stats.window_start_slot, 110);
+    assert_eq!(stats.failures_in_window(110, 10), 1);
+    assert_eq!(stats.callbacks_in_window(110, 10), 0);
+    assert_eq!(stats.total_failures, 3);
+    assert_eq!(stats.total_callbacks, 2);
+}
+
+#[test]
+fn test_successful_callbacks_outweigh_failures() {
+    let mut provider = Provider::zeroed();
+    provider.callback_skip_max_failures = 3;
+    provider.callback_skip_window_slots = 100;
+    let mut stats = CallbackFailureStats::zeroed();
+    for slot in 0..3 {
+        stats.record_failure(slot, 100);
+    }
+    assert!(provider.skips_callbacks(&stats, 10));
+
+    // As many successful callbacks as failures: the program mostly works.
+    for slot in 10..13 {
+        stats.record_callback(slot, 100);
+    }
+    assert!(!provider.skips_callbacks(&stats, 20));
+
+    stats.record_failure(20, 100);
+    assert!(provider.skips_callbacks(&stats, 20));
+    assert!(!provider.skips_callbacks(&stats, 100));
+}
+
+#[tokio::test]
+async fn test_set_callback_skip_policy() {
+    let mut env = Env::new().await;
+    env.set_policy(3, 1_000).await;
+    let provider = env.provider().await;
+    assert_eq!(provider.callback_skip_max_failures, 3);
+    assert_eq!(provider.callback_skip_window_slots, 1_000);
+
+    for (max_failures, window_slots) in [(3, 0), (1, 1_000), (2, 1_000)] {
+        let instruction = set_callback_skip_policy(
+            env.program_id,
+            env.payer.pubkey(),
+            max_failures,
+            window_slots,
+        );
+        let err =
+            submit_tx_expect_err(&mut env.banks_client, &env.payer, &[instruction], &[]).await;
+        assert_eq!(err, custom(EntropyError::InvalidCallbackSkipPolicy));
+    }
+
+    env.set_policy(0, 0).await;
+    assert_eq!(env.provider().await.callback_skip_max_failures, 0);
+}
+
+#[tokio::test]
+async fn test_reveal_skips_callback_after_reported_failures() {
+    let mut env = Env::new().await;
+    env.set_policy(3, 1_000).await;
+    let (first_account, first) = env.request_failing().await;
+    let (second_account, second) = env.request_failing().await;
+    let (third_account, third) = env.request_failing().await;
+
+    let reveal_ix = reveal_with_callback(
+        env.program_id,
+        first_account,
+        &first,
+        env.reveal_args(&first),
+    );
+    let err = submit_tx_expect_err(&mut env.banks_client, &env.payer, &[reveal_ix], &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(FAIL_ERROR))
+    );
+
+    env.report(first_account, &first).await;
+    let stats = env.stats().await;
+    assert_eq!(stats.window_failures, 1);
+    assert_eq!(
+        stats.requester_program_id,
+        env.requester_program_id.to_bytes()
+    );
+    assert_eq!(
+        env.request_status(first_account).await,
+        CallbackStatus::Failed
+    );
+
+    // One failure is below the policy's limit.
+    let early_skip_ix = reveal_skipping_callback(
+        env.program_id,
+        first_account,
+        &first,
+        env.reveal_args(&first),
+    );
+    let err = submit_tx_expect_err(&mut env.banks_client, &env.payer, &[early_skip_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::CallbackSkipNotAllowed));
+
+    env.report(second_account, &second).await;
+    env.report(third_account, &third).await;
+    assert_eq!(env.stats().await.total_failures, 3);
+
+    let skip_ix = reveal_skipping_callback(
+        env.program_id,
+        first_account,
+        &first,
+        env.reveal_args(&first),
+    );
+
+    let (vault, _) = provider_vault_pda(&env.program_id, &env.payer.pubkey());
+    let vault_before = env.banks_client.get_balance(vault).await.unwrap();
+    let outstanding_before = env.provider().await.outstanding_requests;
+    submit_tx(&mut env.banks_client, &env.payer, &[skip_ix], &[]).await;
+
+    assert!(env
+        .banks_client
+        .get_account(first_account)
+        .await
+        .unwrap()
+        .is_none());
+    let vault_after = env.banks_client.get_balance(vault).await.unwrap();
+    assert_eq!(vault_before - vault_after, first.provider_fee);
+    let provider = env.provider().await;
+    assert_eq!(provider.outstanding_requests, outstanding_before - 1);
+    assert_eq!(provider.current_commitment_sequence_number, 1);
+}
+
+#[tokio::test]
+async fn test_reveal_skipping_callback_needs_policy() {
+    let mut env = Env::new().await;
+    let (request_account, request) = env.request_failing().await;
+    env.report(request_account, &request).await;
+    for _ in 0..2 {
+        let (other_account, other) = env.request_failing().await;
+        env.report(other_account, &other).await;
+    }
+
+    let skip_ix = reveal_skipping_callback(
+        env.program_id,
+        request_account,
+        &request,
+        env.reveal_args(&request),
+    );
+    let err = submit_tx_expect_err(&mut env.banks_client, &env.payer, &[skip_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::CallbackSkipNotAllowed));
+}
+
+#[tokio::test]
+async fn test_request_is_reported_once() {
+    let mut env = Env::new().await;
+    env.set_policy(3, 1_000).await;
+    let (request_account, request) = env.request_failing().await;
+    env.report(request_account, &request).await;
+
+    // The same report again needs a fresh blockhash to be a new transaction.
+    let blockhash = env.banks_client.get_latest_blockhash().await.unwrap();
+    env.banks_client
+        .get_new_latest_blockhash(&blockhash)
+        .await
+        .unwrap();
+    let report_ix = report_callback_failure(env.program_id, request_account, &request);
+    let err = submit_tx_expect_err(&mut env.banks_client, &env.payer, &[report_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::InvalidRevealCall));
+    assert_eq!(env.stats().await.total_failures, 1);
+}
+
+#[tokio::test]
+async fn test_reveal_skipping_callback_needs_report() {
+    let mut env = Env::new().await;
+    env.set_policy(3, 1_000).await;
+    for _ in 0..3 {
+        let (reported_account, reported) = env.request_failing().await;
+        env.report(reported_account, &reported).await;
+    }
+    let (request_account, request) = env.request_failing().await;
+
+    let skip_ix = reveal_skipping_callback(
+        env.program_id,
+        request_account,
+        &request,
+        env.reveal_args(&request),
+    );
+    let err = submit_tx_expect_err(&mut env.banks_client, &env.payer, &[skip_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::InvalidRevealCall));
+}
+
+#[tokio::test]
+async fn test_successful_callbacks_block_skipping() {
+    let mut env = Env::new().await;
+    env.set_policy(3, 1_000).await;
+    let mut failed = Vec::new();
+    for _ in 0..3 {
+        let (request_account, request) = env.request_failing().await;
+        env.report(request_account, &request).await;
+        failed.push((request_account, request));
+    }
+
+    for _ in 0..3 {
+        let (request_account, request) = env.request(MODE_SUCCEED).await;
+        let mut reveal_ix = reveal_with_callback(
+            env.program_id,
+            request_account,
+            &request,
+            env.reveal_args(&request),
+        );
+        // The reveal must carry the stats account to count the callback.
+        let stats_meta = reveal_ix.accounts.pop().unwrap();
+        let err =
+            submit_tx_expect_err(&mut env.banks_client, &env.payer, &[reveal_ix.clone()], &[])
+                .await;
+        assert_eq!(err, custom(EntropyError::WrongCallbackFailureStats));
+
+        reveal_ix.accounts.push(stats_meta);
+        submit_tx(&mut env.banks_client, &env.payer, &[reveal_ix], &[]).await;
+    }
+    let stats = env.stats().await;
+    assert_eq!((stats.window_failures, stats.window_callbacks), (3, 3));
+    assert_eq!(stats.total_callbacks, 3);
+
+    let (request_account, request) = failed[0];
+    let skip_ix = reveal_skipping_callback(
+        env.program_id,
+        request_account,
+        &request,
+        env.reveal_args(&request),
+    );
+    let err = submit_tx_expect_err(&mut env.banks_client, &env.payer, &[skip_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::CallbackSkipNotAllowed));
+}
+
+#[tokio::test]
+async fn test_callbacks_count_without_policy() {
+    let mut env = Env::new().await;
+    env.set_policy(3, 1_000).await;
+    let mut failed = Vec::new();
+    for _ in 0..3 {
+        let (request_account, request) = env.request_failing().await;
+        env.report(request_account, &request).await;
+        failed.push((request_account, request));
+    }
+
+    // Turning the policy off while the callbacks succeed does not leave them
+    // uncounted: the reveal still needs the stats account.
+    env.set_policy(0, 1_000).await;
+    for _ in 0..3 {
+        let (request_account, request) = env.request(MODE_SUCCEED).await;
+        let mut reveal_ix = reveal_with_callback(
+            env.program_id,
+            request_account,
+            &request,
+            env.reveal_args(&request),
+        );
+        let stats_meta = reveal_ix.accounts.pop().unwrap();
+        let err =
+            submit_tx_expect_err(&mut env.banks_client, &env.payer, &[reveal_ix.clone()], &[])
+                .await;
+        assert_eq!(err, custom(EntropyError::WrongCallbackFailureStats));
+
+        reveal_ix.accounts.push(stats_meta);
+        submit_tx(&mut env.banks_client, &env.payer, &[reveal_ix], &[]).await;
+    }
+    let stats = env.stats().await;
+    assert_eq!((stats.window_failures, stats.window_callbacks), (3, 3));
+
+    env.set_policy(3, 1_000).await;
+    let (request_account, request) = failed[0];
+    let skip_ix = reveal_skipping_callback(
+        env.program_id,
+        request_account,
+        &request,
+        env.reveal_args(&request),
+    );
+    let err = submit_tx_expect_err(&mut env.banks_client, &env.payer, &[skip_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::CallbackSkipNotAllowed));
+
+    // A request whose callback never failed cannot be skipped either.
+    let (request_account, request) = env.request(MODE_SUCCEED).await;
+    let skip_ix = reveal_skipping_callback(
+        env.program_id,
+        request_account,
+        &request,
+        env.reveal_args(&request),
+    );
+    let err = submit_tx_expect_err(&mut env.banks_client, &env.payer, &[skip_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::InvalidRevealCall));
+}
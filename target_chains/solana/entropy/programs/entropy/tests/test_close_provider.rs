@@ -0,0 +1,169 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::{accounts::Provider, error::EntropyError},
+    entropy_sdk::instruction::{close_provider, RequestParams},
+    entropy_test_utils::{
+        build_register_args, build_register_provider_ix, start_with_requester, submit_tx,
+        submit_tx_expect_err, EntropyTestEnv, HashChain,
+    },
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey, system_instruction},
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+/// Submits a callback request without revealing it.
+async fn request(env: &mut EntropyTestEnv) {
+    env.submit_callback_request(RequestParams {
+        compute_unit_limit: 100_000,
+        ..Default::default()
+    })
+    .await;
+}
+
+async fn outstanding_requests(env: &mut EntropyTestEnv) -> u32 {
+    let account = env
+        .context
+        .banks_client
+        .get_account(env.provider().address)
+        .await
+        .unwrap()
+        .unwrap();
+    try_from_bytes::<Provider>(&account.data)
+        .unwrap()
+        .outstanding_requests
+}
+
+async fn lamports(env: &mut EntropyTestEnv, address: Pubkey) -> Option<u64> {
+    env.context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .map(|account| account.lamports)
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_outstanding_requests_track_open_requests() {
+    let (mut env, keeper) = start_with_requester(1_000).await;
+    let payer = env.payer();
+    assert_eq!(outstanding_requests(&mut env).await, 0);
+
+    request(&mut env).await;
+    request(&mut env).await;
+    assert_eq!(outstanding_requests(&mut env).await, 2);
+
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    assert_eq!(reveals.len(), 2);
+    assert_eq!(outstanding_requests(&mut env).await, 0);
+}
+
+#[tokio::test]
+async fn test_rotation_keeps_outstanding_requests() {
+    let (mut env, keeper) = start_with_requester(1_000).await;
+    let payer = env.payer();
+
+    request(&mut env).await;
+    let rotate_ix = build_register_provider_ix(
+        env.program_id,
+        payer.pubkey(),
+        env.provider().address,
+        env.provider().vault,
+        build_register_args(1_000, HashChain::from_seed([4u8; 32], 16).commitment(), 16),
+        true,
+    );
+    submit_tx(&mut env.context.banks_client, &payer, &[rotate_ix], &[]).await;
+    assert_eq!(outstanding_requests(&mut env).await, 1);
+
+    // The request still reveals from the chain it was made against.
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    assert!(reveals[0].result.is_ok());
+    assert_eq!(outstanding_requests(&mut env).await, 0);
+}
+
+#[tokio::test]
+async fn test_close_provider_after_requests_settle() {
+    let (mut env, keeper) = start_with_requester(1_000).await;
+    let authority = env.payer();
+
+    request(&mut env).await;
+    let close_ix = close_provider(env.program_id, authority.pubkey(), 0, 0);
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &authority,
+        &[close_ix.clone()],
+        &[],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::ProviderHasOutstandingRequests));
+
+    // Revealing closes the request and leaves its provider fee in the vault.
+    keeper
+        .reveal_pending(&mut env.context.banks_client, &authority)
+        .await;
+
+    // A separate fee payer keeps the authority's refund exact.
+    let fee_payer = Keypair::new();
+    let fund_ix =
+        system_instruction::transfer(&authority.pubkey(), &fee_payer.pubkey(), 1_000_000_000);
+    submit_tx(&mut env.context.banks_client, &authority, &[fund_ix], &[]).await;
+
+    let provider_address = env.provider().address;
+    let vault = env.provider().vault;
+    let provider_lamports = lamports(&mut env, provider_address).await.unwrap();
+    let vault_lamports = lamports(&mut env, vault).await.unwrap();
+    let authority_before = lamports(&mut env, authority.pubkey()).await.unwrap();
+
+    submit_tx(
+        &mut env.context.banks_client,
+        &fee_payer,
+        &[close_ix],
+        &[&authority],
+    )
+    .await;
+
+    assert!(lamports(&mut env, provider_address).await.is_none());
+    assert!(lamports(&mut env, vault).await.is_none());
+    assert_eq!(
+        lamports(&mut env, authority.pubkey()).await.unwrap(),
+        authority_before + provider_lamports + vault_lamports
+    );
+}
+
+#[tokio::test]
+async fn test_close_provider_rejects_other_signers() {
+    let (mut env, _) = start_with_requester(1_000).await;
+    let payer = env.payer();
+
+    let stranger = Keypair::new();
+    let mut close_ix = close_provider(env.program_id, stranger.pubkey(), 0, 0);
+    close_ix.accounts[1] = AccountMeta::new(env.provider().address, false);
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[close_ix],
+        &[&stranger],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::WrongProvider));
+
+    let close_ix = close_provider(env.program_id, stranger.pubkey(), 0, 0);
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[close_ix],
+        &[&stranger],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::WrongAccountOwner));
+}
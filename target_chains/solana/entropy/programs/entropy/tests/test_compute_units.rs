@@ -0,0 +1,416 @@
+//! Compute-unit budgets for the hot instructions. These run against the SBF
+//! builds of entropy and simple-requester, so they are ignored by plain
+//! `cargo test`:
+//!
+//! ```bash
+//! cargo build-sbf
+//! cargo test-sbf -p entropy --test test_compute_units -- --ignored
+//! ```
+//!
+//! Set `ENTROPY_UPDATE_CU_BUDGETS=1` to rewrite `tests/compute_units.budget`
+//! with the measured values after an intended change.
+
+#[allow(deprecated)]
+use {
+    bytemuck::{bytes_of, Zeroable},
+    entropy::{
+        accounts::CallbackMeta,
+        constants::{MAX_CALLBACK_ACCOUNTS, REQUESTER_SIGNER_SEED},
+        instruction::{EntropyInstruction, RequestArgs, RequestWithCallbackHeader, RevealArgs},
+        pda::{
+            callback_failure_stats_pda, config_pda, entropy_signer_pda, provider_pda,
+            provider_vault_pda, pyth_fee_vault_pda,
+        },
+    },
+    entropy_test_utils::{
+        add_deployer, build_register_args, build_register_provider_ix, initialize_config, submit_tx,
+    },
+    simple_requester::{
+        CALLBACK_ACTION, CALLBACK_STATE_LEN, REQUEST_ACTION, REQUEST_WITH_CALLBACK_ACTION,
+    },
+    solana_program::{
+        hash::hash,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_instruction, system_program,
+        sysvar::slot_hashes,
+    },
+    solana_program_test::{BanksClient, ProgramTest},
+    solana_sdk::{
+        rent::Rent,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+    std::collections::BTreeMap,
+};
+
+const BUDGETS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/compute_units.budget");
+const UPDATE_BUDGETS_ENV: &str = "ENTROPY_UPDATE_CU_BUDGETS";
+/// Measurements may exceed their budget by this much before the test fails.
+const REGRESSION_TOLERANCE_PERCENT: u64 = 5;
+const CHAIN_LENGTH: usize = 64;
+const USER_RANDOMNESS: [u8; 32] = [9u8; 32];
+
+struct Bench {
+    banks_client: BanksClient,
+    payer: Keypair,
+    program_id: Pubkey,
+    requester_program_id: Pubkey,
+    callback_state: Pubkey,
+    /// `chain[i + 1] == hash(chain[i])`; the provider commits to the last one.
+    chain: Vec<[u8; 32]>,
+}
+
+impl Bench {
+    async fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let requester_program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(true);
+        program_test.add_program("entropy", program_id, None);
+        program_test.add_program("simple_requester", requester_program_id, None);
+        add_deployer(&mut program_test, program_id);
+        let (mut banks_client, payer, _) = program_test.start().await;
+
+        initialize_config(&mut banks_client, &payer, program_id, 0).await;
+
+        let mut chain = vec![[3u8; 32]];
+        for _ in 0..CHAIN_LENGTH {
+            chain.push(hash(chain.last().unwrap()).to_bytes());
+        }
+        let (provider_address, _) = provider_pda(&program_id, &payer.pubkey());
+        let (provider_vault, _) = provider_vault_pda(&program_id, &payer.pubkey());
+        let register_ix = build_register_provider_ix(
+            program_id,
+            payer.pubkey(),
+            provider_address,
+            provider_vault,
+            build_register_args(1, *chain.last().unwrap(), CHAIN_LENGTH as u64),
+            true,
+        );
+        submit_tx(&mut banks_client, &payer, &[register_ix], &[]).await;
+
+        let callback_state = Keypair::new();
+        let create_callback_state_ix = system_instruction::create_account(
+            &payer.pubkey(),
+            &callback_state.pubkey(),
+            Rent::default().minimum_balance(CALLBACK_STATE_LEN),
+            CALLBACK_STATE_LEN as u64,
+            &requester_program_id,
+        );
+        submit_tx(
+            &mut banks_client,
+            &payer,
+            &[create_callback_state_ix],
+            &[&callback_state],
+        )
+        .await;
+
+        Self {
+            banks_client,
+            payer,
+            program_id,
+            requester_program_id,
+            callback_state: callback_state.pubkey(),
+            chain,
+        }
+    }
+
+    fn request_account_metas(&self, request_account: Pubkey) -> Vec<AccountMeta> {
+        let (requester_signer, _) = Pubkey::find_program_address(
+            &[REQUESTER_SIGNER_SEED, self.program_id.as_ref()],
+            &self.requester_program_id,
+        );
+        vec![
+            AccountMeta::new_readonly(requester_signer, false),
+            AccountMeta::new(self.payer.pubkey(), true),
+            AccountMeta::new_readonly(self.requester_program_id, false),
+            AccountMeta::new(request_account, true),
+            AccountMeta::new(
+                provider_pda(&self.program_id, &self.payer.pubkey()).0,
+                false,
+            ),
+            AccountMeta::new(
+                provider_vault_pda(&self.program_id, &self.payer.pubkey()).0,
+                false,
+            ),
+            AccountMeta::new_readonly(config_pda(&self.program_id).0, false),
+            AccountMeta::new(pyth_fee_vault_pda(&self.program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ]
+    }
+
+    fn request_ix(&self, request_account: Pubkey) -> Instruction {
+        let (_, requester_signer_bump) = Pubkey::find_program_address(
+            &[REQUESTER_SIGNER_SEED, self.program_id.as_ref()],
+            &self.requester_program_id,
+        );
+        let args = RequestArgs {
+            user_commitment: hash(&USER_RANDOMNESS).to_bytes(),
+            use_blockhash: 0,
+            random_number_version: 0,
+            min_reveal_delay_slots: 0,
+            compute_unit_limit: 0,
+            ttl_slots: 0,
+            refund_address: [0u8; 32],
+            requester_signer_bump,
+            _padding0: [0u8; 3],
+        };
+        let mut data = vec![REQUEST_ACTION];
+        data.extend_from_slice(bytes_of(&args));
+        let mut accounts = self.request_account_metas(request_account);
+        accounts.push(AccountMeta::new_readonly(self.program_id, false));
+
+        Instruction {
+            program_id: self.requester_program_id,
+            data,
+            accounts,
+        }
+    }
+
+    /// The callback state account first, padded with read-only accounts the
+    /// requester ignores.
+    fn callback_accounts(&self, count: usize) -> Vec<CallbackMeta> {
+        let mut callback_accounts = vec![CallbackMeta {
+            pubkey: self.callback_state.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        }];
+        callback_accounts.extend((1..count).map(|_| CallbackMeta {
+            pubkey: Pubkey::new_unique().to_bytes(),
+            is_signer: 0,
+            is_writable: 0,
+        }));
+        callback_accounts
+    }
+
+    fn request_with_callback_ix(
+        &self,
+        request_account: Pubkey,
+        callback_accounts: &[CallbackMeta],
+    ) -> Instruction {
+        let header = RequestWithCallbackHeader {
+            user_randomness: USER_RANDOMNESS,
+            compute_unit_limit: 200_000,
+            ttl_slots: 0,
+            min_reveal_delay_slots: 0,
+            include_contributions: 0,
+            random_number_version: 0,
+            refund_address: [0u8; 32],
+            ..RequestWithCallbackHeader::zeroed()
+        };
+        let mut callback_ix_data = vec![CALLBACK_ACTION];
+        callback_ix_data.extend_from_slice(self.program_id.as_ref());
+
+        let (_, requester_signer_bump) = Pubkey::find_program_address(
+            &[REQUESTER_SIGNER_SEED, self.program_id.as_ref()],
+            &self.requester_program_id,
+        );
+
+        let mut data = vec![REQUEST_WITH_CALLBACK_ACTION];
+        data.extend_from_slice(&EntropyInstruction::build_callback_request(
+            &header,
+            requester_signer_bump,
+            callback_accounts,
+            &callback_ix_data,
+        ));
+
+        let mut accounts = self.request_account_metas(request_account);
+        accounts.push(AccountMeta::new_readonly(self.requester_program_id, false));
+        accounts.push(AccountMeta::new_readonly(self.program_id, false));
+
+        Instruction {
+            program_id: self.requester_program_id,
+            data,
+            accounts,
+        }
+    }
+
+    fn reveal_ix(
+        &self,
+        request_account: Pubkey,
+        num_hashes: usize,
+        callback_accounts: &[CallbackMeta],
+    ) -> Instruction {
+        let args = RevealArgs {
+            user_contribution: USER_RANDOMNESS,
+            provider_contribution: self.chain[CHAIN_LENGTH - num_hashes],
+        };
+        let data = EntropyInstruction::build(&args);
+
+        let mut accounts = vec![
+            AccountMeta::new(request_account, false),
+            AccountMeta::new(
+                provider_pda(&self.program_id, &self.payer.pubkey()).0,
+                false,
+            ),
+            AccountMeta::new_readonly(slot_hashes::id(), false),
+            AccountMeta::new_readonly(entropy_signer_pda(&self.program_id).0, false),
+            AccountMeta::new_readonly(self.requester_program_id, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(self.payer.pubkey(), true),
+        ];
+        accounts.extend(callback_accounts.iter().map(|meta| {
+            let pubkey = Pubkey::new_from_array(meta.pubkey);
+            if meta.is_writable == 1 {
+                AccountMeta::new(pubkey, false)
+            } else {
+                AccountMeta::new_readonly(pubkey, false)
+            }
+        }));
+        accounts.push(AccountMeta::new(
+            callback_failure_stats_pda(
+                &self.program_id,
+                &self.payer.pubkey(),
+                &self.requester_program_id,
+            )
+            .0,
+            false,
+        ));
+
+        Instruction {
+            program_id: self.program_id,
+            data,
+            accounts,
+        }
+    }
+
+    /// Simulates to read the entropy program's own CU consumption (callback
+    /// CPIs included), then lands the transaction.
+    async fn measure(&mut self, instruction: Instruction, signers: &[&Keypair]) -> u64 {
+        let recent_blockhash = self.banks_client.get_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let mut transaction =
+            Transaction::new_with_payer(&[instruction.clone()], Some(&self.payer.pubkey()));
+        transaction.sign(&all_signers, recent_blockhash);
+
+        let simulation = self
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        simulation.result.unwrap().unwrap();
+        let logs = simulation.simulation_details.unwrap().logs;
+        let prefix = format!("Program {} consumed ", self.program_id);
+        let units = logs
+            .iter()
+            .find_map(|log| log.strip_prefix(&prefix)?.split_whitespace().next())
+            .expect("entropy did not log its compute units")
+            .parse()
+            .unwrap();
+
+        submit_tx(&mut self.banks_client, &self.payer, &[instruction], signers).await;
+        units
+    }
+}
+
+async fn measure_request() -> u64 {
+    let mut bench = Bench::new().await;
+    let request_account = Keypair::new();
+    let instruction = bench.request_ix(request_account.pubkey());
+    bench.measure(instruction, &[&request_account]).await
+}
+
+async fn measure_request_with_callback(callback_accounts_len: usize) -> u64 {
+    let mut bench = Bench::new().await;
+    let request_account = Keypair::new();
+    let callback_accounts = bench.callback_accounts(callback_accounts_len);
+    let instruction = bench.request_with_callback_ix(request_account.pubkey(), &callback_accounts);
+    bench.measure(instruction, &[&request_account]).await
+}
+
+/// Requests `num_hashes` times and reveals the last request, which is
+/// `num_hashes` steps from the provider's current commitment.
+async fn measure_reveal(num_hashes: usize, callback_accounts_len: usize) -> u64 {
+    let mut bench = Bench::new().await;
+    let callback_accounts = bench.callback_accounts(callback_accounts_len);
+    let mut request_account = Keypair::new();
+    for _ in 0..num_hashes {
+        request_account = Keypair::new();
+        let instruction =
+            bench.request_with_callback_ix(request_account.pubkey(), &callback_accounts);
+        submit_tx(
+            &mut bench.banks_client,
+            &bench.payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await;
+    }
+    let instruction = bench.reveal_ix(request_account.pubkey(), num_hashes, &callback_accounts);
+    bench.measure(instruction, &[]).await
+}
+
+fn read_budgets() -> BTreeMap<String, u64> {
+    let Ok(contents) = std::fs::read_to_string(BUDGETS_PATH) else {
+        return BTreeMap::new();
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, units) = line
+                .split_once(char::is_whitespace)
+                .unwrap_or_else(|| panic!("malformed budget line: {line}"));
+            (name.to_string(), units.trim().parse().unwrap())
+        })
+        .collect()
+}
+
+fn write_budgets(measured: &BTreeMap<String, u64>) {
+    let mut contents = String::from(
+        "# Compute-unit budgets checked by tests/test_compute_units.rs.\n\
+         # Regenerate with ENTROPY_UPDATE_CU_BUDGETS=1.\n",
+    );
+    for (name, units) in measured {
+        contents.push_str(&format!("{name} {units}\n"));
+    }
+    std::fs::write(BUDGETS_PATH, contents).unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build; run with cargo test-sbf -- --ignored"]
+async fn test_compute_unit_budgets() {
+    let mut measured = BTreeMap::new();
+    measured.insert("request".to_string(), measure_request().await);
+    for callback_accounts_len in [1, 4, MAX_CALLBACK_ACCOUNTS] {
+        measured.insert(
+            format!("request_with_callback/accounts={callback_accounts_len}"),
+            measure_request_with_callback(callback_accounts_len).await,
+        );
+    }
+    for (num_hashes, callback_accounts_len) in [(1, 1), (8, 1), (32, 1), (1, MAX_CALLBACK_ACCOUNTS)]
+    {
+        measured.insert(
+            format!("reveal_with_callback/hashes={num_hashes}/accounts={callback_accounts_len}"),
+            measure_reveal(num_hashes, callback_accounts_len).await,
+        );
+    }
+
+    if std::env::var_os(UPDATE_BUDGETS_ENV).is_some() {
+        write_budgets(&measured);
+        return;
+    }
+
+    let budgets = read_budgets();
+    let mut failures = Vec::new();
+    for (name, units) in &measured {
+        match budgets.get(name) {
+            None => failures.push(format!("{name}: no budget (measured {units})")),
+            Some(budget) if units * 100 > budget * (100 + REGRESSION_TOLERANCE_PERCENT) => {
+                failures.push(format!("{name}: {units} CU exceeds budget {budget}"))
+            }
+            Some(budget) if *units < *budget => {
+                println!("{name}: {units} CU is under budget {budget}; consider lowering it")
+            }
+            Some(_) => {}
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "compute-unit regressions (set {UPDATE_BUDGETS_ENV}=1 to accept):\n{}",
+        failures.join("\n")
+    );
+}
@@ -0,0 +1,41 @@
+use {
+    entropy::constants::PROGRAM_VERSION,
+    entropy_sdk::instruction::get_version,
+    entropy_test_utils::{new_entropy_program_test, submit_tx_expect_err},
+    solana_program::pubkey::Pubkey,
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+#[tokio::test]
+async fn test_get_version_returns_program_version() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, recent_blockhash) =
+        new_entropy_program_test(program_id).start().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[get_version(program_id)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let simulation = banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    simulation.result.unwrap().unwrap();
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, program_id);
+    assert_eq!(return_data.data, vec![PROGRAM_VERSION]);
+
+    let mut ix = get_version(program_id);
+    ix.data.push(0);
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+    );
+}
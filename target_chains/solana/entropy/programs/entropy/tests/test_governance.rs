@@ -0,0 +1,428 @@
+#[allow(deprecated)]
+use {
+    bytemuck::try_from_bytes,
+    entropy::{
+        accounts::{Config, GovernanceConfig},
+        discriminator::governance_config_discriminator,
+        error::EntropyError,
+        governance::{serialize_posted_vaa, GovernanceAction, GovernanceMessage},
+        pda::{config_pda, governance_pda, pyth_fee_vault_pda},
+    },
+    entropy_sdk::instruction::{governance, initialize_governance},
+    entropy_test_utils::{
+        build_initialize_ix, deployer, new_entropy_program_test, submit_tx, submit_tx_expect_err,
+    },
+    solana_program::{pubkey::Pubkey, system_instruction},
+    solana_program_test::ProgramTestContext,
+    solana_sdk::{
+        account::AccountSharedData,
+        instruction::InstructionError,
+        rent::Rent,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+const EMITTER_CHAIN: u16 = 1;
+const EMITTER_ADDRESS: [u8; 32] = [0x5a; 32];
+
+struct Env {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    wormhole_program: Pubkey,
+}
+
+impl Env {
+    /// Initializes entropy with the payer as admin and governance pointed at
+    /// a stand-in core bridge.
+    async fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let wormhole_program = Pubkey::new_unique();
+        let mut context = new_entropy_program_test(program_id)
+            .start_with_context()
+            .await;
+        let payer = context.payer.insecure_clone();
+
+        let deployer = deployer();
+        let initialize_ix = build_initialize_ix(
+            program_id,
+            deployer.pubkey(),
+            payer.pubkey(),
+            Pubkey::new_unique(),
+            1_000,
+        );
+        let initialize_governance_ix = initialize_governance(
+            program_id,
+            payer.pubkey(),
+            payer.pubkey(),
+            wormhole_program,
+            EMITTER_CHAIN,
+            EMITTER_ADDRESS,
+        );
+        submit_tx(
+            &mut context.banks_client,
+            &payer,
+            &[initialize_ix, initialize_governance_ix],
+            &[&deployer],
+        )
+        .await;
+
+        Self {
+            context,
+            program_id,
+            wormhole_program,
+        }
+    }
+
+    /// Stores a posted VAA as the core bridge would after verifying it.
+    fn post_vaa(
+        &mut self,
+        owner: Pubkey,
+        emitter: (u16, [u8; 32]),
+        sequence: u64,
+        payload: &[u8],
+    ) -> Pubkey {
+        let data = serialize_posted_vaa(sequence, emitter.0, emitter.1, payload);
+        let mut account = AccountSharedData::new(
+            Rent::default().minimum_balance(data.len()),
+            data.len(),
+            &owner,
+        );
+        account.set_data_from_slice(&data);
+        let address = Pubkey::new_unique();
+        self.context.set_account(&address, &account);
+        address
+    }
+
+    fn post_message(&mut self, sequence: u64, action: GovernanceAction) -> Pubkey {
+        let payload = GovernanceMessage {
+            target_chain_id: 1,
+            action,
+        }
+        .serialize();
+        self.post_vaa(
+            self.wormhole_program,
+            (EMITTER_CHAIN, EMITTER_ADDRESS),
+            sequence,
+            &payload,
+        )
+    }
+
+    async fn execute(&mut self, posted_vaa: Pubkey, recipient: Option<Pubkey>) {
+        let payer = self.context.payer.insecure_clone();
+        let ix = governance(self.program_id, posted_vaa, recipient);
+        submit_tx(&mut self.context.banks_client, &payer, &[ix], &[]).await;
+    }
+
+    async fn execute_expect_err(
+        &mut self,
+        posted_vaa: Pubkey,
+        recipient: Option<Pubkey>,
+    ) -> TransactionError {
+        let payer = self.context.payer.insecure_clone();
+        let ix = governance(self.program_id, posted_vaa, recipient);
+        // A fresh blockhash keeps retries of identical transactions distinct.
+        self.context.get_new_latest_blockhash().await.unwrap();
+        submit_tx_expect_err(&mut self.context.banks_client, &payer, &[ix], &[]).await
+    }
+
+    async fn config(&mut self) -> Config {
+        let account = self
+            .context
+            .banks_client
+            .get_account(config_pda(&self.program_id).0)
+            .await
+            .unwrap()
+            .unwrap();
+        *try_from_bytes::<Config>(&account.data).unwrap()
+    }
+
+    async fn governance_config(&mut self) -> GovernanceConfig {
+        let account = self
+            .context
+            .banks_client
+            .get_account(governance_pda(&self.program_id).0)
+            .await
+            .unwrap()
+            .unwrap();
+        *try_from_bytes::<GovernanceConfig>(&account.data).unwrap()
+    }
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[test]
+fn test_governance_message_round_trip() {
+    let actions = [
+        GovernanceAction::SetFee { value: 5, expo: 3 },
+        GovernanceAction::SetAdmin {
+            new_admin: [1u8; 32],
+        },
+        GovernanceAction::SetDefaultProvider {
+            provider: [2u8; 32],
+        },
+        GovernanceAction::WithdrawFee {
+            target: [3u8; 32],
+            value: 7,
+            expo: 0,
+        },
+        GovernanceAction::SetPermissionedProviders { permissioned: true },
+    ];
+    for action in actions {
+        let message = GovernanceMessage {
+            target_chain_id: 1,
+            action,
+        };
+        let payload = message.serialize();
+        assert_eq!(&payload[..4], b"PTGM");
+        assert_eq!(GovernanceMessage::parse(&payload).unwrap(), message);
+
+        // Trailing bytes and truncation are both rejected.
+        let mut extended = payload.clone();
+        extended.push(0);
+        assert!(GovernanceMessage::parse(&extended).is_err());
+        assert!(GovernanceMessage::parse(&payload[..payload.len() - 1]).is_err());
+    }
+}
+
+#[test]
+fn test_governance_message_rejects_other_modules() {
+    let mut payload = GovernanceMessage {
+        target_chain_id: 1,
+        action: GovernanceAction::SetFee { value: 1, expo: 0 },
+    }
+    .serialize();
+    // Module 1 is the price receiver's target module.
+    payload[4] = 1;
+    assert!(GovernanceMessage::parse(&payload).is_err());
+}
+
+#[tokio::test]
+async fn test_initialize_governance() {
+    let mut env = Env::new().await;
+    let governance_config = env.governance_config().await;
+    assert_eq!(
+        governance_config.discriminator,
+        governance_config_discriminator()
+    );
+    assert_eq!(
+        governance_config.wormhole_program,
+        env.wormhole_program.to_bytes()
+    );
+    assert_eq!(governance_config.emitter_chain, EMITTER_CHAIN);
+    assert_eq!(governance_config.emitter_address, EMITTER_ADDRESS);
+    assert_eq!(governance_config.last_executed_sequence, 0);
+    assert_eq!(governance_config.bump, governance_pda(&env.program_id).1);
+}
+
+#[tokio::test]
+async fn test_initialize_governance_requires_admin() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
+    let deployer = deployer();
+    let initialize_ix = build_initialize_ix(
+        program_id,
+        deployer.pubkey(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        0,
+    );
+    submit_tx(&mut banks_client, &payer, &[initialize_ix], &[&deployer]).await;
+
+    let ix = initialize_governance(
+        program_id,
+        payer.pubkey(),
+        payer.pubkey(),
+        Pubkey::new_unique(),
+        EMITTER_CHAIN,
+        EMITTER_ADDRESS,
+    );
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::Unauthorized));
+}
+
+#[tokio::test]
+async fn test_set_fee_and_replay_rejected() {
+    let mut env = Env::new().await;
+    let vaa = env.post_message(5, GovernanceAction::SetFee { value: 25, expo: 2 });
+    env.execute(vaa, None).await;
+
+    assert_eq!(env.config().await.pyth_fee_lamports, 2_500);
+    assert_eq!(env.governance_config().await.last_executed_sequence, 5);
+
+    let err = env.execute_expect_err(vaa, None).await;
+    assert_eq!(err, custom(EntropyError::OldGovernanceMessage));
+
+    let older = env.post_message(4, GovernanceAction::SetFee { value: 1, expo: 0 });
+    let err = env.execute_expect_err(older, None).await;
+    assert_eq!(err, custom(EntropyError::OldGovernanceMessage));
+    assert_eq!(env.config().await.pyth_fee_lamports, 2_500);
+}
+
+#[tokio::test]
+async fn test_set_fee_overflow_rejected() {
+    let mut env = Env::new().await;
+    let vaa = env.post_message(
+        1,
+        GovernanceAction::SetFee {
+            value: u64::MAX,
+            expo: 1,
+        },
+    );
+    let err = env.execute_expect_err(vaa, None).await;
+    assert_eq!(err, custom(EntropyError::InvalidGovernanceMessage));
+}
+
+#[tokio::test]
+async fn test_set_admin_and_default_provider() {
+    let mut env = Env::new().await;
+    let new_admin = Pubkey::new_unique();
+    let new_provider = Pubkey::new_unique();
+
+    let vaa = env.post_message(
+        1,
+        GovernanceAction::SetAdmin {
+            new_admin: new_admin.to_bytes(),
+        },
+    );
+    env.execute(vaa, None).await;
+    let vaa = env.post_message(
+        2,
+        GovernanceAction::SetDefaultProvider {
+            provider: new_provider.to_bytes(),
+        },
+    );
+    env.execute(vaa, None).await;
+
+    let config = env.config().await;
+    assert_eq!(config.admin, new_admin.to_bytes());
+    assert_eq!(config.default_provider, new_provider.to_bytes());
+}
+
+#[tokio::test]
+async fn test_set_permissioned_providers() {
+    let mut env = Env::new().await;
+    assert_eq!(env.config().await.permissioned_providers, 0);
+
+    for (sequence, permissioned) in [(1, true), (2, false)] {
+        let vaa = env.post_message(
+            sequence,
+            GovernanceAction::SetPermissionedProviders { permissioned },
+        );
+        env.execute(vaa, None).await;
+        assert_eq!(
+            env.config().await.permissioned_providers,
+            u8::from(permissioned)
+        );
+    }
+
+    // Only 0 and 1 encode a flag.
+    let mut payload = GovernanceMessage {
+        target_chain_id: 1,
+        action: GovernanceAction::SetPermissionedProviders { permissioned: true },
+    }
+    .serialize();
+    *payload.last_mut().unwrap() = 2;
+    assert!(GovernanceMessage::parse(&payload).is_err());
+}
+
+#[tokio::test]
+async fn test_rejects_wrong_owner_emitter_and_target() {
+    let mut env = Env::new().await;
+    let payload = GovernanceMessage {
+        target_chain_id: 1,
+        action: GovernanceAction::SetFee { value: 1, expo: 0 },
+    }
+    .serialize();
+
+    let forged = env.post_vaa(
+        Pubkey::new_unique(),
+        (EMITTER_CHAIN, EMITTER_ADDRESS),
+        1,
+        &payload,
+    );
+    let err = env.execute_expect_err(forged, None).await;
+    assert_eq!(err, custom(EntropyError::InvalidVaa));
+
+    let wormhole_program = env.wormhole_program;
+    let wrong_chain = env.post_vaa(wormhole_program, (2, EMITTER_ADDRESS), 1, &payload);
+    let err = env.execute_expect_err(wrong_chain, None).await;
+    assert_eq!(err, custom(EntropyError::InvalidGovernanceDataSource));
+
+    let wrong_address = env.post_vaa(wormhole_program, (EMITTER_CHAIN, [1u8; 32]), 1, &payload);
+    let err = env.execute_expect_err(wrong_address, None).await;
+    assert_eq!(err, custom(EntropyError::InvalidGovernanceDataSource));
+
+    let ethereum_payload = GovernanceMessage {
+        target_chain_id: 2,
+        action: GovernanceAction::SetFee { value: 1, expo: 0 },
+    }
+    .serialize();
+    let wrong_target = env.post_vaa(
+        wormhole_program,
+        (EMITTER_CHAIN, EMITTER_ADDRESS),
+        1,
+        &ethereum_payload,
+    );
+    let err = env.execute_expect_err(wrong_target, None).await;
+    assert_eq!(err, custom(EntropyError::InvalidGovernanceTarget));
+
+    assert_eq!(env.config().await.pyth_fee_lamports, 1_000);
+    assert_eq!(env.governance_config().await.last_executed_sequence, 0);
+}
+
+#[tokio::test]
+async fn test_withdraw_fee() {
+    let mut env = Env::new().await;
+    let payer = env.context.payer.insecure_clone();
+    let (pyth_fee_vault, _) = pyth_fee_vault_pda(&env.program_id);
+    let fund_ix = system_instruction::transfer(&payer.pubkey(), &pyth_fee_vault, 5_000_000_000);
+    submit_tx(&mut env.context.banks_client, &payer, &[fund_ix], &[]).await;
+
+    let recipient = Keypair::new().pubkey();
+    let vaa = env.post_message(
+        1,
+        GovernanceAction::WithdrawFee {
+            target: recipient.to_bytes(),
+            value: 2,
+            expo: 9,
+        },
+    );
+
+    let err = env
+        .execute_expect_err(vaa, Some(Pubkey::new_unique()))
+        .await;
+    assert_eq!(err, custom(EntropyError::WrongRecipient));
+
+    env.execute(vaa, Some(recipient)).await;
+    let balance = env
+        .context
+        .banks_client
+        .get_balance(recipient)
+        .await
+        .unwrap();
+    assert_eq!(balance, 2_000_000_000);
+
+    // The vault keeps its rent-exempt minimum.
+    let vault_balance = env
+        .context
+        .banks_client
+        .get_balance(pyth_fee_vault)
+        .await
+        .unwrap();
+    let vaa = env.post_message(
+        2,
+        GovernanceAction::WithdrawFee {
+            target: recipient.to_bytes(),
+            value: vault_balance,
+            expo: 0,
+        },
+    );
+    let err = env.execute_expect_err(vaa, Some(recipient)).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InsufficientFunds)
+    );
+}
@@ -1,19 +1,27 @@
-mod test_utils;
-
 #[allow(deprecated)]
 use {
-    crate::test_utils::instructions::build_initialize_ix,
     bytemuck::try_from_bytes,
     entropy::{
         accounts::Config,
+        constants::PROGRAM_VERSION,
         discriminator::config_discriminator,
-        pda::{config_pda, pyth_fee_vault_pda},
+        error::EntropyError,
+        pda::{config_pda, entropy_signer_pda, pyth_fee_vault_pda},
+        program_data::program_data_address,
+    },
+    entropy_sdk::instruction::initialize_if_needed,
+    entropy_test_utils::{
+        add_program_data, build_initialize_ix, deployer, new_entropy_program_test, submit_tx,
+        submit_tx_expect_err,
     },
-    solana_program::{pubkey::Pubkey, system_program},
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey, system_program},
+    solana_program_test::{processor, ProgramTest},
     solana_sdk::{
-        instruction::InstructionError, rent::Rent, signature::Signer, transaction::TransactionError,
+        instruction::InstructionError,
+        rent::Rent,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
     },
-    test_utils::{new_entropy_program_test, submit_tx, submit_tx_expect_err},
 };
 
 #[tokio::test]
@@ -25,14 +33,15 @@ async fn test_initialize_happy_path() {
     let default_provider = Pubkey::new_unique();
     let pyth_fee_lamports = 1234;
 
+    let deployer = deployer();
     let instruction = build_initialize_ix(
         program_id,
-        payer.pubkey(),
+        deployer.pubkey(),
         admin,
         default_provider,
         pyth_fee_lamports,
     );
-    submit_tx(&mut banks_client, &payer, &[instruction], &[]).await;
+    submit_tx(&mut banks_client, &payer, &[instruction], &[&deployer]).await;
 
     let (config_address, expected_bump) = config_pda(&program_id);
     let (fee_vault_address, _) = pyth_fee_vault_pda(&program_id);
@@ -52,6 +61,11 @@ async fn test_initialize_happy_path() {
     assert_eq!(config.proposed_admin, [0u8; 32]);
     assert_eq!(config.seed, [0u8; 32]);
     assert_eq!(config.bump, expected_bump);
+    assert_eq!(
+        config.entropy_signer_bump,
+        entropy_signer_pda(&program_id).1
+    );
+    assert_eq!(config.program_version, PROGRAM_VERSION);
 
     let fee_vault_account = banks_client
         .get_account(fee_vault_address)
@@ -78,14 +92,15 @@ async fn test_initialize_records_prefunded_fee_vault() {
     );
     submit_tx(&mut banks_client, &payer, &[prefund_ix], &[]).await;
 
+    let deployer = deployer();
     let instruction = build_initialize_ix(
         program_id,
-        payer.pubkey(),
+        deployer.pubkey(),
         Pubkey::new_unique(),
         Pubkey::new_unique(),
         1234,
     );
-    submit_tx(&mut banks_client, &payer, &[instruction], &[]).await;
+    submit_tx(&mut banks_client, &payer, &[instruction], &[&deployer]).await;
 
     let (config_address, _) = config_pda(&program_id);
     let _config_account = banks_client
@@ -109,14 +124,15 @@ async fn test_initialize_rejects_zero_admin() {
     let program_id = Pubkey::new_unique();
     let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
 
+    let deployer = deployer();
     let instruction = build_initialize_ix(
         program_id,
-        payer.pubkey(),
+        deployer.pubkey(),
         Pubkey::default(),
         Pubkey::new_unique(),
         1,
     );
-    let err = submit_tx_expect_err(&mut banks_client, &payer, &[instruction], &[]).await;
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[instruction], &[&deployer]).await;
     assert_eq!(
         err,
         TransactionError::InstructionError(0, InstructionError::InvalidArgument)
@@ -128,16 +144,225 @@ async fn test_initialize_rejects_zero_default_provider() {
     let program_id = Pubkey::new_unique();
     let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
 
+    let deployer = deployer();
     let instruction = build_initialize_ix(
         program_id,
-        payer.pubkey(),
+        deployer.pubkey(),
         Pubkey::new_unique(),
         Pubkey::default(),
         1,
     );
-    let err = submit_tx_expect_err(&mut banks_client, &payer, &[instruction], &[]).await;
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[instruction], &[&deployer]).await;
     assert_eq!(
         err,
         TransactionError::InstructionError(0, InstructionError::InvalidArgument)
     );
 }
+
+#[tokio::test]
+async fn test_initialize_rejects_squatter() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
+
+    // Anyone watching the deployment could try to initialize first and make
+    // themselves admin.
+    let squatter = Keypair::new();
+    let fund_ix = solana_sdk::system_instruction::transfer(
+        &payer.pubkey(),
+        &squatter.pubkey(),
+        Rent::default().minimum_balance(Config::LEN) * 2,
+    );
+    submit_tx(&mut banks_client, &payer, &[fund_ix], &[]).await;
+    let instruction = build_initialize_ix(
+        program_id,
+        squatter.pubkey(),
+        squatter.pubkey(),
+        squatter.pubkey(),
+        0,
+    );
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[instruction], &[&squatter]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::Unauthorized as u32)
+        )
+    );
+
+    let (config_address, _) = config_pda(&program_id);
+    assert!(banks_client
+        .get_account(config_address)
+        .await
+        .unwrap()
+        .is_none());
+
+    // The deployer can still initialize afterwards.
+    let deployer = deployer();
+    let instruction = build_initialize_ix(
+        program_id,
+        deployer.pubkey(),
+        deployer.pubkey(),
+        Pubkey::new_unique(),
+        0,
+    );
+    submit_tx(&mut banks_client, &payer, &[instruction], &[&deployer]).await;
+}
+
+#[tokio::test]
+async fn test_initialize_rejects_immutable_program() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "entropy",
+        program_id,
+        processor!(entropy::processor::process_instruction),
+    );
+    add_program_data(&mut program_test, program_id, None);
+    let (mut banks_client, payer, _) = program_test.start().await;
+
+    let instruction = build_initialize_ix(
+        program_id,
+        payer.pubkey(),
+        payer.pubkey(),
+        Pubkey::new_unique(),
+        0,
+    );
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[instruction], &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::Unauthorized as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_initialize_rejects_foreign_program_data() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
+
+    // Program data of another program, which the squatter could control.
+    let deployer = deployer();
+    let mut instruction = build_initialize_ix(
+        program_id,
+        deployer.pubkey(),
+        deployer.pubkey(),
+        Pubkey::new_unique(),
+        0,
+    );
+    instruction.accounts[4] =
+        AccountMeta::new_readonly(program_data_address(&Pubkey::new_unique()), false);
+    let err = submit_tx_expect_err(&mut banks_client, &payer, &[instruction], &[&deployer]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::WrongProgramData as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_initialize_if_needed_is_a_no_op_on_matching_config() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
+
+    let deployer = deployer();
+    let admin = Pubkey::new_unique();
+    let default_provider = Pubkey::new_unique();
+    let instruction =
+        initialize_if_needed(program_id, deployer.pubkey(), admin, default_provider, 7);
+    submit_tx(
+        &mut banks_client,
+        &payer,
+        &[instruction.clone()],
+        &[&deployer],
+    )
+    .await;
+
+    let (config_address, _) = config_pda(&program_id);
+    let initialized = banks_client
+        .get_account(config_address)
+        .await
+        .unwrap()
+        .unwrap();
+    let config = try_from_bytes::<Config>(&initialized.data).unwrap();
+    assert_eq!(config.admin, admin.to_bytes());
+    assert_eq!(config.pyth_fee_lamports, 7);
+
+    // Another payer makes the transaction distinct from the first.
+    let other_payer = Keypair::new();
+    let fund_ix =
+        solana_sdk::system_instruction::transfer(&payer.pubkey(), &other_payer.pubkey(), 1_000_000);
+    submit_tx(&mut banks_client, &payer, &[fund_ix], &[]).await;
+    submit_tx(
+        &mut banks_client,
+        &other_payer,
+        &[instruction.clone()],
+        &[&deployer],
+    )
+    .await;
+    let unchanged = banks_client
+        .get_account(config_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(unchanged, initialized);
+
+    let err = submit_tx_expect_err(
+        &mut banks_client,
+        &payer,
+        &[build_initialize_ix(
+            program_id,
+            deployer.pubkey(),
+            admin,
+            default_provider,
+            7,
+        )],
+        &[&deployer],
+    )
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::AccountAlreadyInitialized as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_initialize_if_needed_rejects_mismatched_config() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
+
+    let deployer = deployer();
+    let admin = Pubkey::new_unique();
+    let default_provider = Pubkey::new_unique();
+    let instruction =
+        build_initialize_ix(program_id, deployer.pubkey(), admin, default_provider, 7);
+    submit_tx(&mut banks_client, &payer, &[instruction], &[&deployer]).await;
+
+    for (admin, default_provider, pyth_fee_lamports) in [
+        (Pubkey::new_unique(), default_provider, 7),
+        (admin, Pubkey::new_unique(), 7),
+        (admin, default_provider, 8),
+    ] {
+        let instruction = initialize_if_needed(
+            program_id,
+            deployer.pubkey(),
+            admin,
+            default_provider,
+            pyth_fee_lamports,
+        );
+        let err =
+            submit_tx_expect_err(&mut banks_client, &payer, &[instruction], &[&deployer]).await;
+        assert_eq!(
+            err,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(EntropyError::ConfigMismatch as u32)
+            )
+        );
+    }
+}
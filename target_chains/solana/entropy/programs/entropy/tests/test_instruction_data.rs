@@ -0,0 +1,189 @@
+//! Instruction data built with `EntropyInstruction::build` must parse back to
+//! the same instruction and args.
+
+use {
+    bytemuck::{bytes_of, Zeroable},
+    entropy::{
+        accounts::CallbackMeta,
+        instruction::{
+            parse_args, parse_request_aggregate_args, parse_request_v2_args,
+            parse_request_with_callback_args, EntropyInstruction, GetVersionArgs, InitializeArgs,
+            InitializeIfNeededArgs, InstructionArgs, RequestAggregateHeader, RequestArgs,
+            RequestV2Header, RequestWithCallbackHeader, RevealArgs, RevealSkippingCallbackArgs,
+            SetCallbackSkipPolicyArgs, SetPythFeeArgs, WriteCallbackAccountListArgs,
+            INSTRUCTION_DISCRIMINATOR_LEN,
+        },
+    },
+    solana_program::program_error::ProgramError,
+};
+
+/// Highest instruction discriminator the program accepts.
+const LAST_INSTRUCTION: u64 = EntropyInstruction::RevealSkippingCallback as u64;
+
+fn assert_round_trip<A: InstructionArgs>(instruction: EntropyInstruction, args: &A) {
+    assert_eq!(A::INSTRUCTION, instruction);
+    let data = EntropyInstruction::build(args);
+    assert_eq!(
+        data.len(),
+        INSTRUCTION_DISCRIMINATOR_LEN + core::mem::size_of::<A>()
+    );
+    let (parsed, payload) = EntropyInstruction::parse(&data).unwrap();
+    assert_eq!(parsed, instruction);
+    assert_eq!(bytes_of(parse_args::<A>(payload).unwrap()), bytes_of(args));
+}
+
+fn callback_accounts() -> Vec<CallbackMeta> {
+    (0..3u8)
+        .map(|index| CallbackMeta {
+            pubkey: [index + 1; 32],
+            is_signer: 0,
+            is_writable: u16::from(index % 2),
+        })
+        .collect()
+}
+
+#[test]
+fn test_discriminators_parse_back() {
+    for value in 0..=LAST_INSTRUCTION {
+        let data = value.to_le_bytes();
+        let (instruction, payload) = EntropyInstruction::parse(&data).unwrap();
+        assert_eq!(instruction as u64, value);
+        assert_eq!(instruction.discriminator(), value.to_le_bytes());
+        assert!(payload.is_empty());
+    }
+    assert_eq!(
+        EntropyInstruction::parse(&(LAST_INSTRUCTION + 1).to_le_bytes()).err(),
+        Some(ProgramError::InvalidInstructionData)
+    );
+    assert_eq!(
+        EntropyInstruction::parse(&[0u8; INSTRUCTION_DISCRIMINATOR_LEN - 1]).err(),
+        Some(ProgramError::InvalidInstructionData)
+    );
+}
+
+#[test]
+fn test_fixed_args_round_trip() {
+    let mut request = RequestArgs::zeroed();
+    request.user_commitment = [7u8; 32];
+    request.compute_unit_limit = 150_000;
+    request.ttl_slots = 30;
+    request.refund_address = [9u8; 32];
+    assert_round_trip(EntropyInstruction::Request, &request);
+
+    let reveal = RevealArgs {
+        user_contribution: [1u8; 32],
+        provider_contribution: [2u8; 32],
+    };
+    assert_round_trip(EntropyInstruction::RevealWithCallback, &reveal);
+    assert_round_trip(
+        EntropyInstruction::RevealSkippingCallback,
+        &RevealSkippingCallbackArgs(reveal),
+    );
+
+    let initialize = InitializeArgs {
+        admin: [3u8; 32],
+        pyth_fee_lamports: 1,
+        default_provider: [4u8; 32],
+    };
+    assert_round_trip(EntropyInstruction::Initialize, &initialize);
+    assert_round_trip(
+        EntropyInstruction::InitializeIfNeeded,
+        &InitializeIfNeededArgs(initialize),
+    );
+    assert_round_trip(
+        EntropyInstruction::SetPythFee,
+        &SetPythFeeArgs {
+            pyth_fee_lamports: 5_000,
+        },
+    );
+
+    let mut write_list = WriteCallbackAccountListArgs::zeroed();
+    write_list.callback_accounts[..3].copy_from_slice(&callback_accounts());
+    write_list.num_accounts = 3;
+    assert_round_trip(EntropyInstruction::WriteCallbackAccountList, &write_list);
+
+    assert_round_trip(
+        EntropyInstruction::SetCallbackSkipPolicy,
+        &SetCallbackSkipPolicyArgs {
+            window_slots: 1_000,
+            max_failures: 3,
+            _padding0: [0u8; 2],
+        },
+    );
+
+    assert_round_trip(EntropyInstruction::GetVersion, &GetVersionArgs);
+    assert_eq!(
+        EntropyInstruction::build(&GetVersionArgs),
+        EntropyInstruction::GetVersion.discriminator()
+    );
+}
+
+#[test]
+fn test_parse_args_rejects_wrong_length() {
+    let data = EntropyInstruction::build(&SetPythFeeArgs {
+        pyth_fee_lamports: 1,
+    });
+    let (_, payload) = EntropyInstruction::parse(&data).unwrap();
+    assert!(parse_args::<SetPythFeeArgs>(&payload[..payload.len() - 1]).is_err());
+    let mut longer = payload.to_vec();
+    longer.push(0);
+    assert!(parse_args::<SetPythFeeArgs>(&longer).is_err());
+    assert!(parse_args::<GetVersionArgs>(&[0]).is_err());
+}
+
+#[test]
+fn test_callback_requests_round_trip() {
+    let callback_accounts = callback_accounts();
+    let callback_ix_data = [0xcb, 1, 2, 3];
+
+    let mut header = RequestWithCallbackHeader::zeroed();
+    header.user_randomness = [4u8; 32];
+    header.compute_unit_limit = 200_000;
+    header.include_contributions = 1;
+    let data = EntropyInstruction::build_callback_request(
+        &header,
+        254,
+        &callback_accounts,
+        &callback_ix_data,
+    );
+    let (instruction, payload) = EntropyInstruction::parse(&data).unwrap();
+    assert_eq!(instruction, EntropyInstruction::RequestWithCallback);
+    let args = parse_request_with_callback_args(payload).unwrap();
+    assert_eq!(args.user_randomness, header.user_randomness);
+    assert_eq!(args.compute_unit_limit, header.compute_unit_limit);
+    assert_eq!(args.include_contributions, 1);
+    assert_eq!(args.requester_signer_bump, 254);
+    assert_eq!(
+        bytemuck::cast_slice::<CallbackMeta, u8>(args.callback_accounts),
+        bytemuck::cast_slice::<CallbackMeta, u8>(&callback_accounts)
+    );
+    assert_eq!(args.callback_ix_data, callback_ix_data);
+
+    let mut header = RequestV2Header::zeroed();
+    header.ttl_slots = 12;
+    let data = EntropyInstruction::build_callback_request(
+        &header,
+        253,
+        &callback_accounts,
+        &callback_ix_data,
+    );
+    let (instruction, payload) = EntropyInstruction::parse(&data).unwrap();
+    assert_eq!(instruction, EntropyInstruction::RequestV2);
+    let args = parse_request_v2_args(payload).unwrap();
+    assert_eq!(args.ttl_slots, 12);
+    assert_eq!(args.requester_signer_bump, 253);
+    assert_eq!(args.callback_accounts.len(), callback_accounts.len());
+    assert_eq!(args.callback_ix_data, callback_ix_data);
+
+    let mut header = RequestAggregateHeader::zeroed();
+    header.num_providers = 2;
+    header.threshold = 2;
+    let data = EntropyInstruction::build_callback_request(&header, 252, &[], &[]);
+    let (instruction, payload) = EntropyInstruction::parse(&data).unwrap();
+    assert_eq!(instruction, EntropyInstruction::RequestAggregate);
+    let args = parse_request_aggregate_args(payload).unwrap();
+    assert_eq!((args.num_providers, args.threshold), (2, 2));
+    assert_eq!(args.requester_signer_bump, 252);
+    assert!(args.callback_accounts.is_empty());
+    assert!(args.callback_ix_data.is_empty());
+}
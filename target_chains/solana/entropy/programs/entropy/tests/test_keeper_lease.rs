@@ -0,0 +1,142 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::{accounts::KeeperLease, error::EntropyError, pda::keeper_lease_pda},
+    entropy_sdk::instruction::refresh_keeper_lease,
+    entropy_test_utils::{
+        submit_tx_expect_err, submit_tx_with_metadata, EntropyFixture, EntropyTestEnv,
+    },
+    solana_program::clock::Clock,
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+const KEEPER_A: [u8; 32] = [1u8; 32];
+const KEEPER_B: [u8; 32] = [2u8; 32];
+
+async fn start() -> EntropyTestEnv {
+    EntropyFixture::new().with_provider(1_000, 16).start().await
+}
+
+async fn refresh(
+    env: &mut EntropyTestEnv,
+    keeper: [u8; 32],
+    lease_slots: u64,
+) -> Result<(), TransactionError> {
+    let authority = env.payer();
+    let instruction = refresh_keeper_lease(env.program_id, authority.pubkey(), keeper, lease_slots);
+    let (result, _logs) = submit_tx_with_metadata(
+        &mut env.context.banks_client,
+        &authority,
+        &[instruction],
+        &[],
+    )
+    .await;
+    result
+}
+
+async fn lease(env: &mut EntropyTestEnv) -> KeeperLease {
+    let (address, _) = keeper_lease_pda(&env.program_id, &env.provider().authority);
+    let account = env
+        .context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    *try_from_bytes::<KeeperLease>(&account.data).unwrap()
+}
+
+async fn slot(env: &mut EntropyTestEnv) -> u64 {
+    env.context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .slot
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_refresh_creates_and_renews_lease() {
+    let mut env = start().await;
+
+    refresh(&mut env, KEEPER_A, 100).await.unwrap();
+    let created = lease(&mut env).await;
+    assert_eq!(created.keeper, KEEPER_A);
+    assert_eq!(created.lease_slots, 100);
+    assert_eq!(
+        created.provider_authority,
+        env.provider().authority.to_bytes()
+    );
+
+    env.context
+        .warp_to_slot(created.refreshed_slot + 50)
+        .unwrap();
+    refresh(&mut env, KEEPER_A, 200).await.unwrap();
+    let renewed = lease(&mut env).await;
+    assert_eq!(renewed.refreshed_slot, slot(&mut env).await);
+    assert!(renewed.refreshed_slot > created.refreshed_slot);
+    assert_eq!(renewed.lease_slots, 200);
+    assert!(!renewed.is_stale(created.refreshed_slot + 101));
+}
+
+#[tokio::test]
+async fn test_standby_takes_over_stale_lease() {
+    let mut env = start().await;
+
+    refresh(&mut env, KEEPER_A, 10).await.unwrap();
+    let held = lease(&mut env).await;
+    assert_eq!(
+        refresh(&mut env, KEEPER_B, 10).await,
+        Err(custom(EntropyError::KeeperLeaseHeld))
+    );
+
+    env.context.warp_to_slot(held.refreshed_slot + 10).unwrap();
+    assert_eq!(
+        refresh(&mut env, KEEPER_B, 11).await,
+        Err(custom(EntropyError::KeeperLeaseHeld))
+    );
+
+    env.context.warp_to_slot(held.refreshed_slot + 11).unwrap();
+    refresh(&mut env, KEEPER_B, 10).await.unwrap();
+    assert_eq!(lease(&mut env).await.keeper, KEEPER_B);
+}
+
+#[tokio::test]
+async fn test_released_lease_can_be_claimed_next_slot() {
+    let mut env = start().await;
+
+    refresh(&mut env, KEEPER_A, 1_000).await.unwrap();
+    refresh(&mut env, KEEPER_A, 0).await.unwrap();
+    let released = lease(&mut env).await;
+    assert_eq!(released.lease_slots, 0);
+
+    env.context
+        .warp_to_slot(released.refreshed_slot + 1)
+        .unwrap();
+    refresh(&mut env, KEEPER_B, 1_000).await.unwrap();
+    assert_eq!(lease(&mut env).await.keeper, KEEPER_B);
+}
+
+#[tokio::test]
+async fn test_refresh_requires_registered_provider() {
+    let mut env = start().await;
+    let payer = env.payer();
+    let stranger = Keypair::new();
+
+    let instruction = refresh_keeper_lease(env.program_id, stranger.pubkey(), KEEPER_A, 100);
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&stranger],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::WrongAccountOwner));
+}
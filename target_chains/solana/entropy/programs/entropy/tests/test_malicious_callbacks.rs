@@ -0,0 +1,438 @@
+//! Reveals against consumers whose callbacks misbehave on purpose (see the
+//! malicious-requester program). In every case the reveal must fail as a
+//! whole: the request stays pending and the provider's commitment doesn't
+//! move, so nothing a callback does can consume a random number.
+//!
+//! Panics, compute exhaustion and runtime-enforced account rules only behave
+//! like they do on chain under the SBF loader, so those cases are ignored by
+//! plain `cargo test`:
+//!
+//! ```bash
+//! cargo build-sbf
+//! cargo test-sbf -p entropy --test test_malicious_callbacks -- --ignored
+//! ```
+
+#[allow(deprecated)]
+use {
+    bytemuck::{try_from_bytes, Zeroable},
+    entropy::{
+        accounts::{CallbackMeta, CallbackStatus, Provider, Request},
+        constants::REQUESTER_SIGNER_SEED,
+        error::EntropyError,
+        instruction::{EntropyInstruction, RequestWithCallbackHeader, RevealArgs},
+        pda::{
+            callback_failure_stats_pda, config_pda, entropy_signer_pda, provider_pda,
+            provider_vault_pda, pyth_fee_vault_pda,
+        },
+    },
+    entropy_sdk::parse_callback_failure,
+    entropy_test_utils::{
+        add_deployer, build_register_args, build_register_provider_ix, initialize_config,
+        new_entropy_program_test, submit_tx, submit_tx_expect_err, submit_tx_with_metadata,
+    },
+    malicious_requester::{
+        CALLBACK_ACTION, FAIL_ERROR, MODE_BURN_COMPUTE, MODE_FAIL, MODE_PANIC, MODE_REENTER,
+        MODE_WRITE_FOREIGN, REQUEST_WITH_CALLBACK_ACTION,
+    },
+    solana_program::{
+        hash::hash,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+        sysvar::slot_hashes,
+    },
+    solana_program_test::{processor, BanksClient, ProgramTest},
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+const CHAIN_LENGTH: usize = 8;
+const USER_RANDOMNESS: [u8; 32] = [9u8; 32];
+
+struct Env {
+    banks_client: BanksClient,
+    payer: Keypair,
+    program_id: Pubkey,
+    requester_program_id: Pubkey,
+    provider_address: Pubkey,
+    /// `chain[i + 1] == hash(chain[i])`; the provider commits to the last one.
+    chain: Vec<[u8; 32]>,
+}
+
+impl Env {
+    /// Native processors for both programs, or the SBF builds when `sbf`.
+    async fn new(sbf: bool) -> Self {
+        let program_id = Pubkey::new_unique();
+        let requester_program_id = Pubkey::new_unique();
+        let mut program_test = if sbf {
+            let mut program_test = ProgramTest::default();
+            program_test.prefer_bpf(true);
+            program_test.add_program("entropy", program_id, None);
+            program_test.add_program("malicious_requester", requester_program_id, None);
+            add_deployer(&mut program_test, program_id);
+            program_test
+        } else {
+            let mut program_test = new_entropy_program_test(program_id);
+            program_test.add_program(
+                "malicious-requester",
+                requester_program_id,
+                processor!(malicious_requester::process_instruction),
+            );
+            program_test
+        };
+        let (mut banks_client, payer, _) = program_test.start().await;
+
+        initialize_config(&mut banks_client, &payer, program_id, 0).await;
+
+        let mut chain = vec![[3u8; 32]];
+        for _ in 0..CHAIN_LENGTH {
+            chain.push(hash(chain.last().unwrap()).to_bytes());
+        }
+        let (provider_address, _) = provider_pda(&program_id, &payer.pubkey());
+        let (provider_vault, _) = provider_vault_pda(&program_id, &payer.pubkey());
+        let register_ix = build_register_provider_ix(
+            program_id,
+            payer.pubkey(),
+            provider_address,
+            provider_vault,
+            build_register_args(0, *chain.last().unwrap(), CHAIN_LENGTH as u64),
+            true,
+        );
+        submit_tx(&mut banks_client, &payer, &[register_ix], &[]).await;
+
+        Self {
+            banks_client,
+            payer,
+            program_id,
+            requester_program_id,
+            provider_address,
+            chain,
+        }
+    }
+
+    /// Requests through the malicious requester with its callback set to
+    /// `mode`.
+    async fn request(
+        &mut self,
+        request_account: &Keypair,
+        mode: u8,
+        compute_unit_limit: u32,
+        callback_accounts: &[CallbackMeta],
+    ) {
+        let header = RequestWithCallbackHeader {
+            user_randomness: USER_RANDOMNESS,
+            compute_unit_limit,
+            ttl_slots: 0,
+            min_reveal_delay_slots: 0,
+            include_contributions: 0,
+            random_number_version: 0,
+            refund_address: [0u8; 32],
+            ..RequestWithCallbackHeader::zeroed()
+        };
+        let mut callback_ix_data = vec![CALLBACK_ACTION, mode];
+        callback_ix_data.extend_from_slice(self.program_id.as_ref());
+
+        let (requester_signer, requester_signer_bump) = Pubkey::find_program_address(
+            &[REQUESTER_SIGNER_SEED, self.program_id.as_ref()],
+            &self.requester_program_id,
+        );
+
+        let mut data = vec![REQUEST_WITH_CALLBACK_ACTION];
+        data.extend_from_slice(&EntropyInstruction::build_callback_request(
+            &header,
+            requester_signer_bump,
+            callback_accounts,
+            &callback_ix_data,
+        ));
+        let instruction = Instruction {
+            program_id: self.requester_program_id,
+            data,
+            accounts: vec![
+                AccountMeta::new_readonly(requester_signer, false),
+                AccountMeta::new(self.payer.pubkey(), true),
+                AccountMeta::new_readonly(self.requester_program_id, false),
+                AccountMeta::new(request_account.pubkey(), true),
+                AccountMeta::new(self.provider_address, false),
+                AccountMeta::new(
+                    provider_vault_pda(&self.program_id, &self.payer.pubkey()).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(config_pda(&self.program_id).0, false),
+                AccountMeta::new(pyth_fee_vault_pda(&self.program_id).0, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(self.requester_program_id, false),
+                AccountMeta::new_readonly(self.program_id, false),
+            ],
+        };
+        submit_tx(
+            &mut self.banks_client,
+            &self.payer,
+            &[instruction],
+            &[request_account],
+        )
+        .await;
+    }
+
+    /// Reveals the first request (sequence 1) with the callback accounts the
+    /// request was made with.
+    async fn reveal(
+        &mut self,
+        request_account: Pubkey,
+        callback_accounts: &[CallbackMeta],
+    ) -> TransactionError {
+        let instruction = self.reveal_ix(request_account, callback_accounts);
+        submit_tx_expect_err(&mut self.banks_client, &self.payer, &[instruction], &[]).await
+    }
+
+    fn reveal_ix(
+        &self,
+        request_account: Pubkey,
+        callback_accounts: &[CallbackMeta],
+    ) -> Instruction {
+        let args = RevealArgs {
+            user_contribution: USER_RANDOMNESS,
+            provider_contribution: self.chain[CHAIN_LENGTH - 1],
+        };
+        let data = EntropyInstruction::build(&args);
+
+        let mut accounts = vec![
+            AccountMeta::new(request_account, false),
+            AccountMeta::new(self.provider_address, false),
+            AccountMeta::new_readonly(slot_hashes::id(), false),
+            AccountMeta::new_readonly(entropy_signer_pda(&self.program_id).0, false),
+            AccountMeta::new_readonly(self.requester_program_id, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(self.payer.pubkey(), true),
+        ];
+        accounts.extend(callback_accounts.iter().map(|meta| AccountMeta {
+            pubkey: Pubkey::new_from_array(meta.pubkey),
+            is_signer: meta.is_signer != 0,
+            is_writable: meta.is_writable != 0,
+        }));
+        accounts.push(AccountMeta::new(
+            callback_failure_stats_pda(
+                &self.program_id,
+                &self.payer.pubkey(),
+                &self.requester_program_id,
+            )
+            .0,
+            false,
+        ));
+        Instruction {
+            program_id: self.program_id,
+            data,
+            accounts,
+        }
+    }
+
+    /// Asserts that the failed reveal left the request and the provider
+    /// exactly as they were.
+    async fn assert_still_pending(&mut self, request_account: Pubkey, request_data: &[u8]) {
+        let account = self
+            .banks_client
+            .get_account(request_account)
+            .await
+            .unwrap()
+            .expect("request account should still exist");
+        assert_eq!(account.data, request_data);
+        let request = try_from_bytes::<Request>(&account.data).unwrap();
+        assert_eq!(request.status(), Ok(CallbackStatus::NotStarted));
+
+        let provider_account = self
+            .banks_client
+            .get_account(self.provider_address)
+            .await
+            .unwrap()
+            .unwrap();
+        let provider = try_from_bytes::<Provider>(&provider_account.data).unwrap();
+        assert_eq!(provider.current_commitment_sequence_number, 0);
+        assert_eq!(provider.current_commitment, *self.chain.last().unwrap());
+    }
+
+    async fn request_data(&mut self, request_account: Pubkey) -> Vec<u8> {
+        self.banks_client
+            .get_account(request_account)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+    }
+}
+
+fn writable(pubkey: Pubkey) -> CallbackMeta {
+    CallbackMeta {
+        pubkey: pubkey.to_bytes(),
+        is_signer: 0,
+        is_writable: 1,
+    }
+}
+
+#[tokio::test]
+async fn test_failing_callback_reverts_reveal() {
+    let mut env = Env::new(false).await;
+    let request_account = Keypair::new();
+    env.request(&request_account, MODE_FAIL, 100_000, &[]).await;
+    let request_data = env.request_data(request_account.pubkey()).await;
+
+    let err = env.reveal(request_account.pubkey(), &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(FAIL_ERROR))
+    );
+    env.assert_still_pending(request_account.pubkey(), &request_data)
+        .await;
+}
+
+#[tokio::test]
+async fn test_failing_callback_is_in_reveal_logs() {
+    let mut env = Env::new(false).await;
+    let request_account = Keypair::new();
+    env.request(&request_account, MODE_FAIL, 100_000, &[]).await;
+
+    let instruction = env.reveal_ix(request_account.pubkey(), &[]);
+    let (result, logs) =
+        submit_tx_with_metadata(&mut env.banks_client, &env.payer, &[instruction], &[]).await;
+    assert_eq!(
+        result,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(FAIL_ERROR)
+        ))
+    );
+
+    let failure = parse_callback_failure(&env.program_id, &logs).expect("callback failure");
+    let request = failure.request;
+    assert_eq!(request.request_account, request_account.pubkey().to_bytes());
+    assert_eq!(request.provider, env.payer.pubkey().to_bytes());
+    assert_eq!(
+        request.requester_program_id,
+        env.requester_program_id.to_bytes()
+    );
+    assert_eq!(request.sequence_number, 1);
+    assert_eq!(request.compute_unit_limit, 100_000);
+    assert_eq!(failure.error_code, Some(FAIL_ERROR));
+}
+
+#[tokio::test]
+async fn test_reveal_rejects_mismatched_callback_accounts() {
+    let mut env = Env::new(false).await;
+    let request_account = Keypair::new();
+    let target = Pubkey::new_unique();
+    env.request(&request_account, MODE_FAIL, 100_000, &[writable(target)])
+        .await;
+    let request_data = env.request_data(request_account.pubkey()).await;
+
+    let readonly = CallbackMeta {
+        is_writable: 0,
+        ..writable(target)
+    };
+    for callback_accounts in [vec![], vec![writable(Pubkey::new_unique())], vec![readonly]] {
+        let err = env
+            .reveal(request_account.pubkey(), &callback_accounts)
+            .await;
+        assert_eq!(
+            err,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(EntropyError::CallbackAccountMismatch as u32)
+            )
+        );
+    }
+    env.assert_still_pending(request_account.pubkey(), &request_data)
+        .await;
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build; run with cargo test-sbf -- --ignored"]
+async fn test_panicking_callback_reverts_reveal() {
+    let mut env = Env::new(true).await;
+    let request_account = Keypair::new();
+    env.request(&request_account, MODE_PANIC, 100_000, &[])
+        .await;
+    let request_data = env.request_data(request_account.pubkey()).await;
+
+    let err = env.reveal(request_account.pubkey(), &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::ProgramFailedToComplete)
+    );
+    env.assert_still_pending(request_account.pubkey(), &request_data)
+        .await;
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build; run with cargo test-sbf -- --ignored"]
+async fn test_reentrant_callback_reverts_reveal() {
+    let mut env = Env::new(true).await;
+    let request_account = Keypair::new();
+    // Hand the callback everything a second reveal of the same request would
+    // need. The entropy program itself can't be among them (requests listing
+    // it are rejected), so the runtime refuses the nested invoke.
+    let callback_accounts = [
+        writable(request_account.pubkey()),
+        writable(env.provider_address),
+    ];
+    env.request(&request_account, MODE_REENTER, 100_000, &callback_accounts)
+        .await;
+    let request_data = env.request_data(request_account.pubkey()).await;
+
+    let err = env
+        .reveal(request_account.pubkey(), &callback_accounts)
+        .await;
+    assert!(
+        matches!(err, TransactionError::InstructionError(0, _)),
+        "unexpected error: {err:?}"
+    );
+    env.assert_still_pending(request_account.pubkey(), &request_data)
+        .await;
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build; run with cargo test-sbf -- --ignored"]
+async fn test_callback_cannot_write_request_account() {
+    let mut env = Env::new(true).await;
+    let request_account = Keypair::new();
+    let callback_accounts = [writable(request_account.pubkey())];
+    env.request(
+        &request_account,
+        MODE_WRITE_FOREIGN,
+        100_000,
+        &callback_accounts,
+    )
+    .await;
+    let request_data = env.request_data(request_account.pubkey()).await;
+
+    let err = env
+        .reveal(request_account.pubkey(), &callback_accounts)
+        .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::ExternalAccountDataModified)
+    );
+    env.assert_still_pending(request_account.pubkey(), &request_data)
+        .await;
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build; run with cargo test-sbf -- --ignored"]
+async fn test_compute_burning_callback_reverts_reveal() {
+    let mut env = Env::new(true).await;
+    let request_account = Keypair::new();
+    // The request's own limit is only a fee hint: the reveal doesn't meter
+    // the callback against it, so the burn runs until the transaction's
+    // compute budget is exhausted.
+    env.request(&request_account, MODE_BURN_COMPUTE, 1_000, &[])
+        .await;
+    let request_data = env.request_data(request_account.pubkey()).await;
+
+    let err = env.reveal(request_account.pubkey(), &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::ComputationalBudgetExceeded)
+    );
+    env.assert_still_pending(request_account.pubkey(), &request_data)
+        .await;
+}
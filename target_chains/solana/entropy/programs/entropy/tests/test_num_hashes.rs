@@ -0,0 +1,414 @@
+#[allow(deprecated)]
+use {
+    bytemuck::{try_from_bytes, Zeroable},
+    entropy::{
+        accounts::{CallbackMeta, Provider, Request},
+        constants::{MAX_NUM_HASHES, REQUESTER_SIGNER_SEED},
+        error::EntropyError,
+        instruction::{EntropyInstruction, RequestWithCallbackHeader, RevealArgs},
+        pda::{
+            callback_failure_stats_pda, config_pda, entropy_signer_pda, provider_vault_pda,
+            pyth_fee_vault_pda,
+        },
+    },
+    entropy_test_utils::{
+        build_register_args, build_register_provider_ix, submit_tx, submit_tx_expect_err,
+        EntropyFixture, HashChain,
+    },
+    simple_requester::{CallbackState, CALLBACK_ACTION, REQUEST_WITH_CALLBACK_ACTION},
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+        sysvar::slot_hashes,
+    },
+    solana_program_test::ProgramTestContext,
+    solana_sdk::{
+        account::AccountSharedData,
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+const CHAIN_LENGTH: u64 = 1_000;
+const USER_RANDOMNESS: [u8; 32] = [9u8; 32];
+
+struct Env {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    requester_program_id: Pubkey,
+    provider_address: Pubkey,
+    callback_state: Pubkey,
+    chain: HashChain,
+}
+
+impl Env {
+    async fn new() -> Self {
+        let fixture = EntropyFixture::new()
+            .with_provider(0, CHAIN_LENGTH)
+            .with_requester()
+            .start()
+            .await;
+        let provider = fixture.provider();
+        let requester = fixture.requester();
+
+        Self {
+            program_id: fixture.program_id,
+            requester_program_id: requester.program_id,
+            provider_address: provider.address,
+            callback_state: requester.callback_state,
+            chain: provider.chain.clone(),
+            context: fixture.context,
+        }
+    }
+
+    async fn provider(&mut self) -> Provider {
+        let account = self
+            .context
+            .banks_client
+            .get_account(self.provider_address)
+            .await
+            .unwrap()
+            .unwrap();
+        *try_from_bytes::<Provider>(&account.data).unwrap()
+    }
+
+    /// Rewrites the provider account in place, standing in for provider
+    /// settings and request history that would otherwise take many
+    /// transactions to reach.
+    async fn update_provider(&mut self, update: impl FnOnce(&mut Provider)) {
+        let mut account = self
+            .context
+            .banks_client
+            .get_account(self.provider_address)
+            .await
+            .unwrap()
+            .unwrap();
+        update(bytemuck::from_bytes_mut::<Provider>(&mut account.data));
+        self.context
+            .set_account(&self.provider_address, &AccountSharedData::from(account));
+    }
+
+    fn callback_accounts(&self) -> [CallbackMeta; 1] {
+        [CallbackMeta {
+            pubkey: self.callback_state.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        }]
+    }
+
+    async fn request_with_callback(
+        &mut self,
+        request_account: &Keypair,
+    ) -> Result<(), TransactionError> {
+        let payer = self.context.payer.insecure_clone();
+        let header = RequestWithCallbackHeader {
+            user_randomness: USER_RANDOMNESS,
+            compute_unit_limit: 200_000,
+            ttl_slots: 0,
+            min_reveal_delay_slots: 0,
+            include_contributions: 0,
+            random_number_version: 0,
+            refund_address: [0u8; 32],
+            ..RequestWithCallbackHeader::zeroed()
+        };
+        let mut callback_ix_data = vec![CALLBACK_ACTION];
+        callback_ix_data.extend_from_slice(self.program_id.as_ref());
+
+        let (requester_signer, requester_signer_bump) = Pubkey::find_program_address(
+            &[REQUESTER_SIGNER_SEED, self.program_id.as_ref()],
+            &self.requester_program_id,
+        );
+
+        let mut data = vec![REQUEST_WITH_CALLBACK_ACTION];
+        data.extend_from_slice(&EntropyInstruction::build_callback_request(
+            &header,
+            requester_signer_bump,
+            &self.callback_accounts(),
+            &callback_ix_data,
+        ));
+        let instruction = Instruction {
+            program_id: self.requester_program_id,
+            data,
+            accounts: vec![
+                AccountMeta::new_readonly(requester_signer, false),
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new_readonly(self.requester_program_id, false),
+                AccountMeta::new(request_account.pubkey(), true),
+                AccountMeta::new(self.provider_address, false),
+                AccountMeta::new(
+                    provider_vault_pda(&self.program_id, &payer.pubkey()).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(config_pda(&self.program_id).0, false),
+                AccountMeta::new(pyth_fee_vault_pda(&self.program_id).0, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(self.requester_program_id, false),
+                AccountMeta::new_readonly(self.program_id, false),
+            ],
+        };
+
+        let recent_blockhash = self
+            .context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap();
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, request_account], recent_blockhash);
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap())
+    }
+
+    fn reveal_ix(&self, request_account: Pubkey, provider_contribution: [u8; 32]) -> Instruction {
+        let args = RevealArgs {
+            user_contribution: USER_RANDOMNESS,
+            provider_contribution,
+        };
+        let data = EntropyInstruction::build(&args);
+
+        Instruction {
+            program_id: self.program_id,
+            data,
+            accounts: vec![
+                AccountMeta::new(request_account, false),
+                AccountMeta::new(self.provider_address, false),
+                AccountMeta::new_readonly(slot_hashes::id(), false),
+                AccountMeta::new_readonly(entropy_signer_pda(&self.program_id).0, false),
+                AccountMeta::new_readonly(self.requester_program_id, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(self.context.payer.pubkey(), true),
+                AccountMeta::new(self.callback_state, false),
+                AccountMeta::new(
+                    callback_failure_stats_pda(
+                        &self.program_id,
+                        &self.context.payer.pubkey(),
+                        &self.requester_program_id,
+                    )
+                    .0,
+                    false,
+                ),
+            ],
+        }
+    }
+
+    fn contribution(&self, sequence_number: u64) -> [u8; 32] {
+        self.chain
+            .contribution_for_sequence(sequence_number)
+            .unwrap()
+    }
+}
+
+#[tokio::test]
+async fn test_request_rejected_past_max_num_hashes() {
+    let mut env = Env::new().await;
+    let max_num_hashes = 3;
+    env.update_provider(|provider| provider.max_num_hashes = max_num_hashes)
+        .await;
+
+    // Sequence numbers drift ahead of the unrevealed commitment one request
+    // at a time until the next reveal would need more than max_num_hashes.
+    for _ in 0..max_num_hashes {
+        env.request_with_callback(&Keypair::new()).await.unwrap();
+    }
+    let provider = env.provider().await;
+    assert_eq!(
+        provider.sequence_number - provider.current_commitment_sequence_number,
+        u64::from(max_num_hashes) + 1
+    );
+
+    let err = env
+        .request_with_callback(&Keypair::new())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::LastRevealedTooOld as u32)
+        )
+    );
+    assert_eq!(
+        env.provider().await.sequence_number,
+        provider.sequence_number
+    );
+}
+
+/// `AdvanceProviderCommitment` is not implemented, so a provider stuck past
+/// its cap re-registers with a fresh chain. Requests made before keep their
+/// own commitment and reveal with the old chain.
+#[tokio::test]
+async fn test_reregistering_recovers_provider_past_max_num_hashes() {
+    let mut env = Env::new().await;
+    let max_num_hashes = 3;
+    env.update_provider(|provider| provider.max_num_hashes = max_num_hashes)
+        .await;
+
+    let pending = Keypair::new();
+    env.request_with_callback(&pending).await.unwrap();
+    let pending_sequence_number = env.provider().await.sequence_number - 1;
+    for _ in 1..max_num_hashes {
+        env.request_with_callback(&Keypair::new()).await.unwrap();
+    }
+    let err = env
+        .request_with_callback(&Keypair::new())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::LastRevealedTooOld as u32)
+        )
+    );
+
+    let payer = env.context.payer.insecure_clone();
+    let commitment_sequence_number = env.provider().await.sequence_number;
+    let mut chain = HashChain::from_seed([42u8; 32], CHAIN_LENGTH);
+    chain.set_start_sequence(commitment_sequence_number);
+    let register_ix = build_register_provider_ix(
+        env.program_id,
+        payer.pubkey(),
+        env.provider_address,
+        provider_vault_pda(&env.program_id, &payer.pubkey()).0,
+        build_register_args(0, chain.commitment(), CHAIN_LENGTH),
+        true,
+    );
+    submit_tx(&mut env.context.banks_client, &payer, &[register_ix], &[]).await;
+    let old_chain = std::mem::replace(&mut env.chain, chain);
+    assert_eq!(
+        env.provider().await.current_commitment_sequence_number,
+        commitment_sequence_number
+    );
+
+    let fresh = Keypair::new();
+    env.request_with_callback(&fresh).await.unwrap();
+
+    let pending_ix = env.reveal_ix(
+        pending.pubkey(),
+        old_chain
+            .contribution_for_sequence(pending_sequence_number)
+            .unwrap(),
+    );
+    submit_tx(&mut env.context.banks_client, &payer, &[pending_ix], &[]).await;
+    let fresh_ix = env.reveal_ix(
+        fresh.pubkey(),
+        env.contribution(commitment_sequence_number + 1),
+    );
+    submit_tx(&mut env.context.banks_client, &payer, &[fresh_ix], &[]).await;
+
+    let provider = env.provider().await;
+    assert_eq!(
+        provider.current_commitment_sequence_number,
+        commitment_sequence_number + 1
+    );
+}
+
+#[tokio::test]
+async fn test_max_num_hashes_boundary_after_drift() {
+    let mut env = Env::new().await;
+    let max_num_hashes = 500;
+    env.update_provider(|provider| {
+        provider.max_num_hashes = max_num_hashes;
+        provider.sequence_number = u64::from(max_num_hashes);
+    })
+    .await;
+
+    let at_limit = Keypair::new();
+    env.request_with_callback(&at_limit).await.unwrap();
+    let request_account = env
+        .context
+        .banks_client
+        .get_account(at_limit.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let request = try_from_bytes::<Request>(&request_account.data).unwrap();
+    assert_eq!(request.num_hashes, max_num_hashes);
+
+    let err = env
+        .request_with_callback(&Keypair::new())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::LastRevealedTooOld as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_request_rejected_past_protocol_num_hashes_cap() {
+    let mut env = Env::new().await;
+    // No provider cap; a chain long enough to go past the protocol's.
+    env.update_provider(|provider| {
+        provider.max_num_hashes = 0;
+        provider.sequence_number = u64::from(MAX_NUM_HASHES);
+        provider.end_sequence_number = u64::from(MAX_NUM_HASHES) + 10;
+    })
+    .await;
+
+    env.request_with_callback(&Keypair::new()).await.unwrap();
+    let err = env
+        .request_with_callback(&Keypair::new())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::LastRevealedTooOld as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_reveal_with_large_num_hashes() {
+    let mut env = Env::new().await;
+    let sequence_number = 600;
+    env.update_provider(|provider| provider.sequence_number = sequence_number)
+        .await;
+
+    let request_account = Keypair::new();
+    env.request_with_callback(&request_account).await.unwrap();
+    let payer = env.context.payer.insecure_clone();
+
+    // One hash short of the commitment.
+    let wrong_ix = env.reveal_ix(
+        request_account.pubkey(),
+        env.contribution(sequence_number - 1),
+    );
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &payer, &[wrong_ix], &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::IncorrectRevelation as u32)
+        )
+    );
+
+    let provider_contribution = env.contribution(sequence_number);
+    let reveal_ix = env.reveal_ix(request_account.pubkey(), provider_contribution);
+    submit_tx(&mut env.context.banks_client, &payer, &[reveal_ix], &[]).await;
+
+    let callback_state_account = env
+        .context
+        .banks_client
+        .get_account(env.callback_state)
+        .await
+        .unwrap()
+        .unwrap();
+    let callback_state = bytemuck::from_bytes::<CallbackState>(&callback_state_account.data);
+    assert_eq!(callback_state.called, 1);
+    assert_eq!(callback_state.sequence_number, sequence_number);
+
+    let provider = env.provider().await;
+    assert_eq!(provider.current_commitment_sequence_number, sequence_number);
+    assert_eq!(provider.current_commitment, provider_contribution);
+}
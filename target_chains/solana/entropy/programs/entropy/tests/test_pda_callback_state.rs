@@ -0,0 +1,147 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::accounts::Provider,
+    entropy_sdk::instruction::{request_with_callback, RequestAccounts, RequestParams},
+    entropy_test_utils::{submit_tx_expect_err, EntropyFixture, EntropyTestEnv, MockKeeper},
+    simple_requester::{
+        callback_state_pda, request_with_pda_callback, CallbackState, RequestWithPdaCallbackArgs,
+        CALLBACK_STATE_LEN,
+    },
+    solana_program::{instruction::Instruction, pubkey::Pubkey},
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+async fn next_sequence_number(env: &mut EntropyTestEnv) -> u64 {
+    let account = env
+        .context
+        .banks_client
+        .get_account(env.provider().address)
+        .await
+        .unwrap()
+        .unwrap();
+    try_from_bytes::<Provider>(&account.data)
+        .unwrap()
+        .sequence_number
+}
+
+fn build_request(
+    env: &EntropyTestEnv,
+    request_account: &Keypair,
+    callback_state: Pubkey,
+) -> Instruction {
+    let entropy_ix = request_with_callback(
+        env.program_id,
+        &RequestAccounts {
+            requester_program: env.requester().program_id,
+            payer: env.payer().pubkey(),
+            request_account: request_account.pubkey(),
+            provider_authority: env.provider().authority,
+        },
+        [7u8; 32],
+        &RequestParams {
+            compute_unit_limit: 100_000,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    request_with_pda_callback(
+        &entropy_ix,
+        callback_state,
+        &RequestWithPdaCallbackArgs {
+            user_randomness: [7u8; 32],
+            compute_unit_limit: 100_000,
+            ttl_slots: 0,
+            requester_signer_bump: 0,
+            _padding: [0; 3],
+        },
+    )
+}
+
+#[test]
+fn test_callback_state_is_per_provider() {
+    let requester_program = Pubkey::new_unique();
+    let (first, _) = callback_state_pda(&requester_program, &Pubkey::new_unique(), 0);
+    let (second, _) = callback_state_pda(&requester_program, &Pubkey::new_unique(), 0);
+    assert_ne!(first, second);
+}
+
+#[tokio::test]
+async fn test_callback_state_is_created_per_sequence_number() {
+    let mut env = EntropyFixture::new()
+        .with_provider(0, 16)
+        .with_requester()
+        .start()
+        .await;
+    let keeper = MockKeeper::new(&env);
+    let payer = env.payer();
+    let requester_program = env.requester().program_id;
+
+    for _ in 0..2 {
+        let sequence_number = next_sequence_number(&mut env).await;
+        let (callback_state, _) =
+            callback_state_pda(&requester_program, &env.provider().address, sequence_number);
+        let request_account = Keypair::new();
+        let instruction = build_request(&env, &request_account, callback_state);
+
+        let reveals = keeper
+            .submit(
+                &mut env.context.banks_client,
+                &payer,
+                &[instruction],
+                &[&request_account],
+            )
+            .await
+            .unwrap();
+        assert_eq!(reveals.len(), 1);
+        assert!(reveals[0].result.is_ok());
+
+        let account = env
+            .context
+            .banks_client
+            .get_account(callback_state)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.owner, requester_program);
+        assert_eq!(account.data.len(), CALLBACK_STATE_LEN);
+        let state = bytemuck::from_bytes::<CallbackState>(&account.data);
+        assert_eq!(state.called, 1);
+        assert_eq!(state.sequence_number, sequence_number);
+        assert_eq!(state.random_number, reveals[0].random_number().unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_mispredicted_callback_state_is_rejected() {
+    let mut env = EntropyFixture::new()
+        .with_provider(0, 16)
+        .with_requester()
+        .start()
+        .await;
+    let payer = env.payer();
+
+    let sequence_number = next_sequence_number(&mut env).await;
+    let (callback_state, _) = callback_state_pda(
+        &env.requester().program_id,
+        &env.provider().address,
+        sequence_number + 1,
+    );
+    let request_account = Keypair::new();
+    let instruction = build_request(&env, &request_account, callback_state);
+
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+}
@@ -0,0 +1,95 @@
+use {
+    bytemuck::{try_from_bytes, try_pod_read_unaligned},
+    entropy::{
+        accounts::Provider, error::EntropyError, instruction::RequestPreview,
+        randomness::request_commitment,
+    },
+    entropy_sdk::{instruction::preview_request, preview_request_commitment},
+    entropy_test_utils::{EntropyFixture, EntropyTestEnv},
+    solana_sdk::{
+        hash::hash,
+        instruction::{Instruction, InstructionError},
+        signature::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+/// Simulates `ix` and returns entropy's return data.
+async fn simulate(env: &mut EntropyTestEnv, ix: Instruction) -> Result<Vec<u8>, TransactionError> {
+    let payer = env.payer();
+    let transaction = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        env.context.last_blockhash,
+    );
+    let simulation = env
+        .context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    simulation.result.unwrap()?;
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, env.program_id);
+    Ok(return_data.data)
+}
+
+async fn provider(env: &mut EntropyTestEnv) -> Provider {
+    let account = env
+        .context
+        .banks_client
+        .get_account(env.provider().address)
+        .await
+        .unwrap()
+        .unwrap();
+    *try_from_bytes::<Provider>(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_preview_request_returns_next_request_commitment() {
+    let mut env = EntropyFixture::new().with_provider(0, 16).start().await;
+    let user_randomness = [7u8; 32];
+    let user_commitment = hash(&user_randomness).to_bytes();
+
+    let ix = preview_request(env.program_id, env.provider().authority, user_commitment);
+    let data = simulate(&mut env, ix).await.unwrap();
+    let preview = try_pod_read_unaligned::<RequestPreview>(&data).unwrap();
+
+    let provider = provider(&mut env).await;
+    assert_eq!(preview.sequence_number, provider.sequence_number);
+    assert_eq!(
+        u64::from(preview.num_hashes),
+        provider.sequence_number - provider.current_commitment_sequence_number
+    );
+    assert_eq!(
+        preview.commitment,
+        request_commitment(&user_commitment, &provider.current_commitment)
+    );
+    assert_eq!(
+        preview.commitment,
+        preview_request_commitment(&user_randomness, &provider)
+    );
+}
+
+#[tokio::test]
+async fn test_preview_request_fails_like_a_request() {
+    // A chain of length 1 is used up by registration.
+    let mut env = EntropyFixture::new().with_provider(0, 1).start().await;
+
+    let ix = preview_request(env.program_id, env.provider().authority, [7u8; 32]);
+    assert_eq!(
+        simulate(&mut env, ix).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::OutOfRandomness as u32)
+        )
+    );
+
+    let mut ix = preview_request(env.program_id, env.provider().authority, [7u8; 32]);
+    ix.data.pop();
+    assert_eq!(
+        simulate(&mut env, ix).await.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+    );
+}
@@ -0,0 +1,176 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::{
+        accounts::{Account, ProgramRegistry},
+        error::EntropyError,
+        pda::entropy_signer_pda,
+        program_data::program_data_address,
+    },
+    entropy_sdk::instruction::{
+        forward_request_with_callback, request_with_callback, set_program_registry,
+        RequestAccounts, RequestParams,
+    },
+    entropy_test_utils::{
+        deployer, submit_tx, submit_tx_expect_err, EntropyFixture, EntropyTestEnv, MockKeeper,
+    },
+    simple_requester::CALLBACK_ACTION,
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey},
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+/// `SetProgramRegistry` signed by the deployer as upgrade authority; the
+/// fixture's config admin is a throwaway key.
+fn set_registry_ix(env: &EntropyTestEnv, program_ids: &[Pubkey]) -> Instruction {
+    let mut ix = set_program_registry(
+        env.program_id,
+        deployer().pubkey(),
+        env.payer().pubkey(),
+        program_ids,
+    )
+    .unwrap();
+    ix.accounts.push(AccountMeta::new_readonly(
+        program_data_address(&env.program_id),
+        false,
+    ));
+    ix
+}
+
+async fn registry(env: &mut EntropyTestEnv) -> ProgramRegistry {
+    let (entropy_signer, _) = entropy_signer_pda(&env.program_id);
+    let account = env
+        .context
+        .banks_client
+        .get_account(entropy_signer)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, env.program_id);
+    *try_from_bytes::<ProgramRegistry>(&account.data).unwrap()
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_set_program_registry_creates_and_replaces_registry() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+    let redeployed = Pubkey::new_unique();
+
+    let ix = set_registry_ix(&env, &[env.program_id, redeployed]);
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[&deployer()]).await;
+
+    let created = registry(&mut env).await;
+    assert_eq!(created.discriminator, ProgramRegistry::discriminator());
+    assert_eq!(created.bump, entropy_signer_pda(&env.program_id).1);
+    assert_eq!(created.num_programs, 2);
+    for (entry, program_id) in created
+        .registered_programs()
+        .iter()
+        .zip([env.program_id, redeployed])
+    {
+        assert_eq!(entry.program_id, program_id.to_bytes());
+        assert_eq!(
+            entry.entropy_signer,
+            entropy_signer_pda(&program_id).0.to_bytes()
+        );
+    }
+
+    let ix = set_registry_ix(&env, &[redeployed]);
+    env.context.get_new_latest_blockhash().await.unwrap();
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[&deployer()]).await;
+
+    let replaced = registry(&mut env).await;
+    assert_eq!(replaced.num_programs, 1);
+    assert_eq!(replaced.programs[0].program_id, redeployed.to_bytes());
+    assert_eq!(replaced.programs[1].program_id, [0u8; 32]);
+    assert_eq!(replaced.programs[1].entropy_signer, [0u8; 32]);
+}
+
+#[tokio::test]
+async fn test_set_program_registry_rejects_non_admin() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+
+    let ix = set_program_registry(
+        env.program_id,
+        payer.pubkey(),
+        payer.pubkey(),
+        &[env.program_id],
+    )
+    .unwrap();
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::Unauthorized));
+}
+
+#[tokio::test]
+async fn test_set_program_registry_rejects_zero_and_duplicate_ids() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+
+    for program_ids in [
+        vec![Pubkey::default()],
+        vec![env.program_id, env.program_id],
+    ] {
+        let ix = set_registry_ix(&env, &program_ids);
+        let err =
+            submit_tx_expect_err(&mut env.context.banks_client, &payer, &[ix], &[&deployer()])
+                .await;
+        assert_eq!(err, custom(EntropyError::InvalidProgramRegistry));
+    }
+}
+
+#[tokio::test]
+async fn test_reveal_with_callback_after_registry_is_set() {
+    let mut env = EntropyFixture::new()
+        .with_provider(0, 16)
+        .with_requester()
+        .start()
+        .await;
+    let keeper = MockKeeper::new(&env);
+    let payer = env.payer();
+
+    let ix = set_registry_ix(&env, &[env.program_id]);
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[&deployer()]).await;
+
+    // The entropy signer now holds the registry but must still sign callbacks.
+    let requester_program = env.requester().program_id;
+    let mut callback_ix_data = vec![CALLBACK_ACTION];
+    callback_ix_data.extend_from_slice(env.program_id.as_ref());
+    let request_account = Keypair::new();
+    let entropy_ix = request_with_callback(
+        env.program_id,
+        &RequestAccounts {
+            requester_program,
+            payer: payer.pubkey(),
+            request_account: request_account.pubkey(),
+            provider_authority: env.provider().authority,
+        },
+        [7u8; 32],
+        &RequestParams {
+            compute_unit_limit: 100_000,
+            callback_accounts: &[AccountMeta::new(env.requester().callback_state, false)],
+            callback_ix_data: &callback_ix_data,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let instruction = forward_request_with_callback(requester_program, &entropy_ix);
+    let reveals = keeper
+        .submit(
+            &mut env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await
+        .unwrap();
+    assert_eq!(reveals.len(), 1);
+    assert!(reveals[0].result.is_ok());
+    assert_eq!(registry(&mut env).await.num_programs, 1);
+}
@@ -0,0 +1,178 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::{
+        accounts::{Account, Config, ProviderAllowlist},
+        error::EntropyError,
+        pda::{config_pda, provider_allowlist_pda, provider_pda, provider_vault_pda},
+        program_data::program_data_address,
+    },
+    entropy_sdk::instruction::set_provider_allowlist,
+    entropy_test_utils::{
+        build_register_args, build_register_provider_ix, deployer, submit_tx, submit_tx_expect_err,
+        EntropyFixture, EntropyTestEnv,
+    },
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey},
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+/// `SetProviderAllowlist` signed by the deployer as upgrade authority; the
+/// fixture's config admin is a throwaway key.
+fn set_allowlist_ix(env: &EntropyTestEnv, providers: &[Pubkey], permissioned: bool) -> Instruction {
+    let mut ix = set_provider_allowlist(
+        env.program_id,
+        deployer().pubkey(),
+        env.payer().pubkey(),
+        providers,
+        permissioned,
+    )
+    .unwrap();
+    ix.accounts.push(AccountMeta::new_readonly(
+        program_data_address(&env.program_id),
+        false,
+    ));
+    ix
+}
+
+fn register_ix(env: &EntropyTestEnv, provider_authority: Pubkey) -> Instruction {
+    build_register_provider_ix(
+        env.program_id,
+        provider_authority,
+        provider_pda(&env.program_id, &provider_authority).0,
+        provider_vault_pda(&env.program_id, &provider_authority).0,
+        build_register_args(1, [9u8; 32], 8),
+        true,
+    )
+}
+
+async fn allowlist(env: &mut EntropyTestEnv) -> ProviderAllowlist {
+    let account = env
+        .context
+        .banks_client
+        .get_account(provider_allowlist_pda(&env.program_id).0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, env.program_id);
+    *try_from_bytes::<ProviderAllowlist>(&account.data).unwrap()
+}
+
+async fn config(env: &mut EntropyTestEnv) -> Config {
+    let account = env
+        .context
+        .banks_client
+        .get_account(config_pda(&env.program_id).0)
+        .await
+        .unwrap()
+        .unwrap();
+    *try_from_bytes::<Config>(&account.data).unwrap()
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_set_provider_allowlist_creates_and_replaces_allowlist() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let ix = set_allowlist_ix(&env, &[first, second], true);
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[&deployer()]).await;
+
+    let created = allowlist(&mut env).await;
+    assert_eq!(created.discriminator, ProviderAllowlist::discriminator());
+    assert_eq!(created.bump, provider_allowlist_pda(&env.program_id).1);
+    assert_eq!(
+        created.allowed_providers(),
+        &[first.to_bytes(), second.to_bytes()]
+    );
+    assert_eq!(config(&mut env).await.permissioned_providers, 1);
+
+    let ix = set_allowlist_ix(&env, &[second], false);
+    env.context.get_new_latest_blockhash().await.unwrap();
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[&deployer()]).await;
+
+    let replaced = allowlist(&mut env).await;
+    assert_eq!(replaced.allowed_providers(), &[second.to_bytes()]);
+    assert_eq!(replaced.providers[1], [0u8; 32]);
+    assert!(!replaced.allows(&first.to_bytes()));
+    assert_eq!(config(&mut env).await.permissioned_providers, 0);
+}
+
+#[tokio::test]
+async fn test_set_provider_allowlist_rejects_non_admin_and_invalid_lists() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+
+    let ix = set_provider_allowlist(
+        env.program_id,
+        payer.pubkey(),
+        payer.pubkey(),
+        &[payer.pubkey()],
+        true,
+    )
+    .unwrap();
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::Unauthorized));
+
+    let provider = Pubkey::new_unique();
+    for providers in [vec![Pubkey::default()], vec![provider, provider]] {
+        let ix = set_allowlist_ix(&env, &providers, true);
+        let err =
+            submit_tx_expect_err(&mut env.context.banks_client, &payer, &[ix], &[&deployer()])
+                .await;
+        assert_eq!(err, custom(EntropyError::InvalidProviderAllowlist));
+    }
+}
+
+#[tokio::test]
+async fn test_register_provider_is_permissionless_by_default() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+
+    // No allowlist account exists; registration does not need one.
+    let mut ix = register_ix(&env, payer.pubkey());
+    ix.accounts.pop();
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+}
+
+#[tokio::test]
+async fn test_permissioned_registration_requires_allowlisted_authority() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+    let outsider = Keypair::new();
+
+    let ix = set_allowlist_ix(&env, &[payer.pubkey()], true);
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[&deployer()]).await;
+
+    let ix = register_ix(&env, outsider.pubkey());
+    let err =
+        submit_tx_expect_err(&mut env.context.banks_client, &payer, &[ix], &[&outsider]).await;
+    assert_eq!(err, custom(EntropyError::ProviderNotAllowed));
+
+    // Leaving out the allowlist does not get around it.
+    let mut ix = register_ix(&env, payer.pubkey());
+    ix.accounts.pop();
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::ProviderNotAllowed));
+
+    let mut ix = register_ix(&env, payer.pubkey());
+    ix.accounts[6] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::WrongProviderAllowlist));
+
+    let ix = register_ix(&env, payer.pubkey());
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    assert!(env
+        .context
+        .banks_client
+        .get_account(provider_pda(&env.program_id, &payer.pubkey()).0)
+        .await
+        .unwrap()
+        .is_some());
+}
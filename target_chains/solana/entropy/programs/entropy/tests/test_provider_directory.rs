@@ -0,0 +1,203 @@
+use {
+    bytemuck::{from_bytes_mut, try_from_bytes},
+    entropy::{
+        accounts::{Account, Config, Provider, ProviderDirectoryPage},
+        error::EntropyError,
+        pda::{provider_directory_pda, provider_pda, provider_vault_pda},
+    },
+    entropy_sdk::instruction::{close_provider, register_provider},
+    entropy_test_utils::{
+        build_register_args, build_register_provider_ix, submit_tx, submit_tx_expect_err,
+        EntropyFixture, EntropyTestEnv,
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::{
+        account::AccountSharedData,
+        instruction::{Instruction, InstructionError},
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+fn register_ix(env: &EntropyTestEnv, provider_authority: Pubkey) -> Instruction {
+    build_register_provider_ix(
+        env.program_id,
+        provider_authority,
+        provider_pda(&env.program_id, &provider_authority).0,
+        provider_vault_pda(&env.program_id, &provider_authority).0,
+        build_register_args(1, [9u8; 32], 8),
+        true,
+    )
+}
+
+async fn directory_page(env: &mut EntropyTestEnv, page: u32) -> ProviderDirectoryPage {
+    let account = env
+        .context
+        .banks_client
+        .get_account(provider_directory_pda(&env.program_id, page).0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, env.program_id);
+    *try_from_bytes::<ProviderDirectoryPage>(&account.data).unwrap()
+}
+
+async fn config(env: &mut EntropyTestEnv) -> Config {
+    let account = env
+        .context
+        .banks_client
+        .get_account(env.config)
+        .await
+        .unwrap()
+        .unwrap();
+    *try_from_bytes::<Config>(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_register_provider_lists_new_providers_once() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+    let other = Keypair::new();
+
+    let ix = register_ix(&env, payer.pubkey());
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    let ix = register_ix(&env, other.pubkey());
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[&other]).await;
+
+    // Rotating does not list the provider again.
+    let ix = register_ix(&env, payer.pubkey());
+    env.context.get_new_latest_blockhash().await.unwrap();
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+
+    let page = directory_page(&mut env, 0).await;
+    assert_eq!(page.discriminator, ProviderDirectoryPage::discriminator());
+    assert_eq!(page.page, 0);
+    assert_eq!(page.bump, provider_directory_pda(&env.program_id, 0).1);
+    assert_eq!(
+        page.listed_providers(),
+        &[payer.pubkey().to_bytes(), other.pubkey().to_bytes()]
+    );
+    let config = config(&mut env).await;
+    assert_eq!(config.provider_directory_len, 2);
+    assert_eq!(config.provider_directory_pages(), 1);
+}
+
+#[tokio::test]
+async fn test_close_provider_unlists_provider() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+    let others = [Keypair::new(), Keypair::new()];
+
+    let ix = register_ix(&env, payer.pubkey());
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    for other in &others {
+        let ix = register_ix(&env, other.pubkey());
+        submit_tx(&mut env.context.banks_client, &payer, &[ix], &[other]).await;
+    }
+
+    // The last listed authority takes the closed one's entry.
+    let ix = close_provider(env.program_id, payer.pubkey(), 0, 0);
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(
+        directory_page(&mut env, 0).await.listed_providers(),
+        &[others[1].pubkey().to_bytes(), others[0].pubkey().to_bytes()]
+    );
+    assert_eq!(config(&mut env).await.provider_directory_len, 2);
+
+    // Registering again lists it once.
+    let ix = register_ix(&env, payer.pubkey());
+    env.context.get_new_latest_blockhash().await.unwrap();
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(
+        directory_page(&mut env, 0).await.listed_providers(),
+        &[
+            others[1].pubkey().to_bytes(),
+            others[0].pubkey().to_bytes(),
+            payer.pubkey().to_bytes()
+        ]
+    );
+    assert_eq!(config(&mut env).await.provider_directory_len, 3);
+
+    // Closing the last listed authority only shortens the directory.
+    let ix = close_provider(env.program_id, payer.pubkey(), 0, 0);
+    env.context.get_new_latest_blockhash().await.unwrap();
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    let page = directory_page(&mut env, 0).await;
+    assert_eq!(
+        page.listed_providers(),
+        &[others[1].pubkey().to_bytes(), others[0].pubkey().to_bytes()]
+    );
+    assert_eq!(page.providers[2], [0u8; 32]);
+}
+
+#[tokio::test]
+async fn test_close_provider_rejects_wrong_directory_page() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+
+    let ix = register_ix(&env, payer.pubkey());
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+
+    let ix = close_provider(env.program_id, payer.pubkey(), 0, 1);
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::WrongProviderDirectory as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_register_provider_lists_unlisted_provider_on_rotation() {
+    let mut env = EntropyFixture::new().with_provider(0, 16).start().await;
+    let payer = env.payer();
+    let provider_address = env.provider().address;
+
+    // Stand in for a provider registered before the directory existed.
+    let mut account = env
+        .context
+        .banks_client
+        .get_account(provider_address)
+        .await
+        .unwrap()
+        .unwrap();
+    from_bytes_mut::<Provider>(&mut account.data).in_provider_directory = 0;
+    env.context
+        .set_account(&provider_address, &AccountSharedData::from(account));
+
+    let ix = register_ix(&env, payer.pubkey());
+    submit_tx(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+
+    // The fixture's registration already listed it; the directory only
+    // appends, so the authority now shows up twice.
+    assert_eq!(
+        directory_page(&mut env, 0).await.listed_providers(),
+        &[payer.pubkey().to_bytes(), payer.pubkey().to_bytes()]
+    );
+    assert_eq!(config(&mut env).await.provider_directory_len, 2);
+}
+
+#[tokio::test]
+async fn test_register_provider_rejects_wrong_directory_page() {
+    let mut env = EntropyFixture::new().start().await;
+    let payer = env.payer();
+
+    let ix = register_provider(
+        env.program_id,
+        payer.pubkey(),
+        build_register_args(1, [9u8; 32], 8),
+        1,
+    );
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &payer, &[ix], &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EntropyError::WrongProviderDirectory as u32)
+        )
+    );
+
+    assert_eq!(config(&mut env).await.provider_directory_len, 0);
+}
@@ -0,0 +1,142 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::{
+        constants::{RANDOM_NUMBER_DOMAIN, RANDOM_NUMBER_V0, RANDOM_NUMBER_V1},
+        instruction::INSTRUCTION_DISCRIMINATOR_LEN,
+        randomness::combine_random_values,
+    },
+    entropy_sdk::instruction::{forward_request_with_callback, RequestParams},
+    entropy_test_utils::{start_with_requester, submit_tx_expect_err, EntropyTestEnv, MockKeeper},
+    simple_requester::{CallbackState, CALLBACK_WITH_CONTRIBUTIONS_ACTION},
+    solana_program::{hash::hashv, instruction::Instruction},
+    solana_sdk::{
+        instruction::InstructionError, signature::Keypair, transaction::TransactionError,
+    },
+};
+
+/// Offset of `random_number_version` in `RequestWithCallback` data.
+const RANDOM_NUMBER_VERSION_OFFSET: usize = INSTRUCTION_DISCRIMINATOR_LEN + 32 + 4 + 4 + 2 + 1;
+
+/// A simple-requester callback request that records the contributions, so
+/// the random number can be recomputed from them.
+fn build_request(
+    env: &EntropyTestEnv,
+    request_account: &Keypair,
+    random_number_version: u8,
+) -> Instruction {
+    env.callback_request_ix(
+        request_account,
+        CALLBACK_WITH_CONTRIBUTIONS_ACTION,
+        RequestParams {
+            compute_unit_limit: 100_000,
+            include_contributions: true,
+            random_number_version,
+            ..Default::default()
+        },
+    )
+}
+
+/// Requests and reveals with `random_number_version`, returning the state the
+/// callback recorded.
+async fn request_and_reveal(
+    env: &mut EntropyTestEnv,
+    keeper: &MockKeeper,
+    random_number_version: u8,
+) -> CallbackState {
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    let entropy_ix = build_request(env, &request_account, random_number_version);
+    let instruction = forward_request_with_callback(env.requester().program_id, &entropy_ix);
+    let reveals = keeper
+        .submit(
+            &mut env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await
+        .unwrap();
+    assert_eq!(reveals.len(), 1);
+    assert!(reveals[0].result.is_ok());
+
+    let account = env
+        .context
+        .banks_client
+        .get_account(env.requester().callback_state)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = *try_from_bytes::<CallbackState>(&account.data).unwrap();
+    assert_eq!(state.random_number, reveals[0].random_number().unwrap());
+    state
+}
+
+#[tokio::test]
+async fn test_v1_random_number_binds_program_id() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let state = request_and_reveal(&mut env, &keeper, RANDOM_NUMBER_V1).await;
+
+    assert_eq!(
+        state.random_number,
+        hashv(&[
+            RANDOM_NUMBER_DOMAIN,
+            env.program_id.as_ref(),
+            &state.user_contribution,
+            &state.provider_contribution,
+            &[0u8; 32],
+        ])
+        .to_bytes()
+    );
+    assert_ne!(
+        state.random_number,
+        combine_random_values(
+            RANDOM_NUMBER_V0,
+            &env.program_id,
+            &state.user_contribution,
+            &state.provider_contribution,
+            &[0u8; 32],
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_v0_random_number_is_unchanged() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let state = request_and_reveal(&mut env, &keeper, RANDOM_NUMBER_V0).await;
+
+    assert_eq!(
+        state.random_number,
+        hashv(&[
+            &state.user_contribution,
+            &state.provider_contribution,
+            &[0u8; 32]
+        ])
+        .to_bytes()
+    );
+}
+
+#[tokio::test]
+async fn test_request_rejects_unknown_random_number_version() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+    let request_account = Keypair::new();
+    let mut entropy_ix = build_request(&env, &request_account, RANDOM_NUMBER_V1);
+    assert_eq!(
+        entropy_ix.data[RANDOM_NUMBER_VERSION_OFFSET],
+        RANDOM_NUMBER_V1
+    );
+    entropy_ix.data[RANDOM_NUMBER_VERSION_OFFSET] = RANDOM_NUMBER_V1 + 1;
+    let instruction = forward_request_with_callback(env.requester().program_id, &entropy_ix);
+
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+    );
+}
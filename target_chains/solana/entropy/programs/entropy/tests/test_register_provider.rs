@@ -1,8 +1,5 @@
-mod test_utils;
-
 #[allow(deprecated)]
 use {
-    crate::test_utils::register_args::build_register_args_with_metadata,
     bytemuck::try_from_bytes,
     entropy::{
         accounts::Provider,
@@ -10,6 +7,10 @@ use {
         error::EntropyError,
         pda::{provider_pda, provider_vault_pda},
     },
+    entropy_test_utils::{
+        build_register_args_with_metadata, build_register_provider_ix, initialize_config,
+        new_entropy_program_test, submit_tx, submit_tx_expect_err,
+    },
     solana_program::{pubkey::Pubkey, system_program},
     solana_sdk::{
         account::Account,
@@ -18,10 +19,6 @@ use {
         signature::{Keypair, Signer},
         transaction::TransactionError,
     },
-    test_utils::{
-        build_register_provider_ix, initialize_config, new_entropy_program_test, submit_tx,
-        submit_tx_expect_err,
-    },
 };
 
 #[tokio::test]
@@ -251,7 +248,7 @@ async fn test_register_provider_rejects_wrong_provider_pda() {
         err,
         TransactionError::InstructionError(
             0,
-            InstructionError::Custom(EntropyError::InvalidPda as u32)
+            InstructionError::Custom(EntropyError::WrongProvider as u32)
         )
     );
 }
@@ -299,7 +296,152 @@ async fn test_register_provider_rejects_existing_provider_wrong_owner_or_size()
         err,
         TransactionError::InstructionError(
             0,
-            InstructionError::Custom(EntropyError::InvalidAccount as u32)
+            InstructionError::Custom(EntropyError::AccountAlreadyInitialized as u32)
         )
     );
 }
+
+#[tokio::test]
+async fn test_register_provider_zeroes_unused_metadata_and_uri() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
+
+    initialize_config(&mut banks_client, &payer, program_id, 1234).await;
+
+    let (provider_address, _) = provider_pda(&program_id, &payer.pubkey());
+    let (provider_vault, _) = provider_vault_pda(&program_id, &payer.pubkey());
+    let mut args =
+        build_register_args_with_metadata(1, [3u8; 32], 3, b"meta", b"https://example.com");
+    // Garbage past the declared lengths must not reach the account.
+    args.commitment_metadata[4..].fill(0xff);
+    args.uri[19..].fill(0xff);
+    let instruction = build_register_provider_ix(
+        program_id,
+        payer.pubkey(),
+        provider_address,
+        provider_vault,
+        args,
+        true,
+    );
+    submit_tx(&mut banks_client, &payer, &[instruction], &[]).await;
+
+    let provider_account = banks_client
+        .get_account(provider_address)
+        .await
+        .unwrap()
+        .unwrap();
+    let provider = try_from_bytes::<Provider>(&provider_account.data).unwrap();
+    assert_eq!(&provider.commitment_metadata[..4], b"meta");
+    assert!(provider.commitment_metadata[4..]
+        .iter()
+        .all(|&byte| byte == 0));
+    assert_eq!(&provider.uri[..19], b"https://example.com");
+    assert!(provider.uri[19..].iter().all(|&byte| byte == 0));
+}
+
+#[tokio::test]
+async fn test_register_provider_rejects_nul_or_non_utf8_uri() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
+
+    initialize_config(&mut banks_client, &payer, program_id, 1234).await;
+
+    let (provider_address, _) = provider_pda(&program_id, &payer.pubkey());
+    let (provider_vault, _) = provider_vault_pda(&program_id, &payer.pubkey());
+    for uri in [
+        &b"https://example.com\0/provider"[..],
+        &b"https://\xff\xfe"[..],
+    ] {
+        let args = build_register_args_with_metadata(1, [3u8; 32], 3, b"meta", uri);
+        let instruction = build_register_provider_ix(
+            program_id,
+            payer.pubkey(),
+            provider_address,
+            provider_vault,
+            args,
+            true,
+        );
+        let err = submit_tx_expect_err(&mut banks_client, &payer, &[instruction], &[]).await;
+        assert_eq!(
+            err,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(EntropyError::InvalidProviderUri as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_register_provider_require_https() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = new_entropy_program_test(program_id).start().await;
+
+    initialize_config(&mut banks_client, &payer, program_id, 1234).await;
+
+    let (provider_address, _) = provider_pda(&program_id, &payer.pubkey());
+    let (provider_vault, _) = provider_vault_pda(&program_id, &payer.pubkey());
+    let register_ix = |uri: &[u8], require_https: u8| {
+        let mut args = build_register_args_with_metadata(1, [3u8; 32], 3, b"meta", uri);
+        args.require_https = require_https;
+        build_register_provider_ix(
+            program_id,
+            payer.pubkey(),
+            provider_address,
+            provider_vault,
+            args,
+            true,
+        )
+    };
+
+    for (uri, require_https, expected) in [
+        (
+            &b"http://example.com"[..],
+            1,
+            InstructionError::Custom(EntropyError::InvalidProviderUri as u32),
+        ),
+        (
+            &b""[..],
+            1,
+            InstructionError::Custom(EntropyError::InvalidProviderUri as u32),
+        ),
+        (
+            &b"https://example.com"[..],
+            2,
+            InstructionError::InvalidInstructionData,
+        ),
+    ] {
+        let err = submit_tx_expect_err(
+            &mut banks_client,
+            &payer,
+            &[register_ix(uri, require_https)],
+            &[],
+        )
+        .await;
+        assert_eq!(err, TransactionError::InstructionError(0, expected));
+    }
+
+    submit_tx(
+        &mut banks_client,
+        &payer,
+        &[register_ix(b"https://example.com", 1)],
+        &[],
+    )
+    .await;
+    // Without the flag any UTF-8 URI is accepted.
+    submit_tx(
+        &mut banks_client,
+        &payer,
+        &[register_ix(b"http://example.com", 0)],
+        &[],
+    )
+    .await;
+
+    let provider_account = banks_client
+        .get_account(provider_address)
+        .await
+        .unwrap()
+        .unwrap();
+    let provider = try_from_bytes::<Provider>(&provider_account.data).unwrap();
+    assert_eq!(provider.uri_str(), "http://example.com");
+}
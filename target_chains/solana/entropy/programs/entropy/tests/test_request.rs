@@ -1,15 +1,19 @@
-mod test_utils;
-
 #[allow(deprecated)]
 use {
     bytemuck::{bytes_of, try_from_bytes},
     entropy::{
-        accounts::{Provider, Request},
-        constants::{CALLBACK_NOT_NECESSARY, REQUESTER_SIGNER_SEED},
+        accounts::{CallbackStatus, Provider, Request},
+        constants::REQUESTER_SIGNER_SEED,
         discriminator::{provider_discriminator, request_discriminator},
         error::EntropyError,
         instruction::RequestArgs,
-        pda::{config_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda},
+        pda::{
+            config_pda, entropy_signer_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda,
+        },
+    },
+    entropy_test_utils::{
+        build_register_args, build_register_provider_ix, initialize_config,
+        new_entropy_program_test, submit_tx, submit_tx_expect_err,
     },
     simple_requester,
     solana_program::{
@@ -25,10 +29,6 @@ use {
         signature::{Keypair, Signer},
         transaction::TransactionError,
     },
-    test_utils::{
-        build_register_args, build_register_provider_ix, initialize_config,
-        new_entropy_program_test, submit_tx, submit_tx_expect_err,
-    },
 };
 
 #[allow(clippy::too_many_arguments)]
@@ -118,7 +118,7 @@ async fn test_request_happy_path() {
     let (config_address, _) = config_pda(&program_id);
     let (pyth_fee_vault, _) = pyth_fee_vault_pda(&program_id);
 
-    let (requester_signer, _) = Pubkey::find_program_address(
+    let (requester_signer, requester_signer_bump) = Pubkey::find_program_address(
         &[REQUESTER_SIGNER_SEED, program_id.as_ref()],
         &requester_program_id,
     );
@@ -127,8 +127,13 @@ async fn test_request_happy_path() {
     let args = RequestArgs {
         user_commitment: [9u8; 32],
         use_blockhash: 1,
-        _padding0: [0u8; 3],
+        random_number_version: 0,
+        min_reveal_delay_slots: 0,
         compute_unit_limit: 0,
+        ttl_slots: 0,
+        refund_address: [0u8; 32],
+        requester_signer_bump,
+        _padding0: [0u8; 3],
     };
 
     let provider_vault_before = banks_client
@@ -215,9 +220,17 @@ async fn test_request_happy_path() {
         requester_program_id.to_bytes()
     );
     assert_eq!(request.use_blockhash, 1);
-    assert_eq!(request.callback_status, CALLBACK_NOT_NECESSARY);
+    assert_eq!(request.status(), Ok(CallbackStatus::NotNecessary));
     assert_eq!(request.compute_unit_limit, 0);
     assert!(request.request_slot > 0);
+    assert_eq!(
+        request.provider_bump,
+        provider_pda(&program_id, &payer.pubkey()).1
+    );
+    assert_eq!(
+        request.entropy_signer_bump,
+        entropy_signer_pda(&program_id).1
+    );
 
     let expected_commitment =
         hashv(&[&args.user_commitment, &provider.current_commitment]).to_bytes();
@@ -240,7 +253,7 @@ async fn test_request_out_of_randomness() {
     let (config_address, _) = config_pda(&program_id);
     let (pyth_fee_vault, _) = pyth_fee_vault_pda(&program_id);
 
-    let (requester_signer, _) = Pubkey::find_program_address(
+    let (requester_signer, requester_signer_bump) = Pubkey::find_program_address(
         &[REQUESTER_SIGNER_SEED, program_id.as_ref()],
         &requester_program_id,
     );
@@ -249,8 +262,13 @@ async fn test_request_out_of_randomness() {
     let args = RequestArgs {
         user_commitment: [2u8; 32],
         use_blockhash: 0,
-        _padding0: [0u8; 3],
+        random_number_version: 0,
+        min_reveal_delay_slots: 0,
         compute_unit_limit: 0,
+        ttl_slots: 0,
+        refund_address: [0u8; 32],
+        requester_signer_bump,
+        _padding0: [0u8; 3],
     };
 
     let instruction = build_requester_request_ix(
@@ -299,7 +317,7 @@ async fn test_request_rejects_invalid_blockhash_flag() {
     let (config_address, _) = config_pda(&program_id);
     let (pyth_fee_vault, _) = pyth_fee_vault_pda(&program_id);
 
-    let (requester_signer, _) = Pubkey::find_program_address(
+    let (requester_signer, requester_signer_bump) = Pubkey::find_program_address(
         &[REQUESTER_SIGNER_SEED, program_id.as_ref()],
         &requester_program_id,
     );
@@ -308,8 +326,13 @@ async fn test_request_rejects_invalid_blockhash_flag() {
     let args = RequestArgs {
         user_commitment: [2u8; 32],
         use_blockhash: 2,
-        _padding0: [0u8; 3],
+        random_number_version: 0,
+        min_reveal_delay_slots: 0,
         compute_unit_limit: 0,
+        ttl_slots: 0,
+        refund_address: [0u8; 32],
+        requester_signer_bump,
+        _padding0: [0u8; 3],
     };
 
     let instruction = build_requester_request_ix(
@@ -338,3 +361,74 @@ async fn test_request_rejects_invalid_blockhash_flag() {
         TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
     );
 }
+
+#[tokio::test]
+async fn test_request_reports_which_account_is_wrong() {
+    let program_id = Pubkey::new_unique();
+    let requester_program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, _) =
+        new_program_test_with_requester(program_id, requester_program_id)
+            .start()
+            .await;
+
+    initialize_config(&mut banks_client, &payer, program_id, 0).await;
+
+    let (provider_address, provider_vault) =
+        register_provider(&mut banks_client, &payer, program_id, 1, 3).await;
+    let (config_address, _) = config_pda(&program_id);
+    let (pyth_fee_vault, _) = pyth_fee_vault_pda(&program_id);
+
+    let (requester_signer, requester_signer_bump) = Pubkey::find_program_address(
+        &[REQUESTER_SIGNER_SEED, program_id.as_ref()],
+        &requester_program_id,
+    );
+
+    // Index of the account in `build_requester_request_ix`, and the error
+    // entropy reports when it is replaced by an unrelated key.
+    let cases = [
+        (5, EntropyError::WrongProviderVault),
+        (6, EntropyError::WrongConfig),
+        (7, EntropyError::WrongPythFeeVault),
+    ];
+    for (index, expected) in cases {
+        let request_account = Keypair::new();
+        let args = RequestArgs {
+            user_commitment: [2u8; 32],
+            use_blockhash: 0,
+            random_number_version: 0,
+            min_reveal_delay_slots: 0,
+            compute_unit_limit: 0,
+            ttl_slots: 0,
+            refund_address: [0u8; 32],
+            requester_signer_bump,
+            _padding0: [0u8; 3],
+        };
+        let mut instruction = build_requester_request_ix(
+            requester_program_id,
+            program_id,
+            requester_signer,
+            payer.pubkey(),
+            request_account.pubkey(),
+            provider_address,
+            provider_vault,
+            config_address,
+            pyth_fee_vault,
+            args,
+        );
+        instruction.accounts[index].pubkey = Pubkey::new_unique();
+
+        let err = submit_tx_expect_err(
+            &mut banks_client,
+            &payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await;
+
+        assert_eq!(
+            err,
+            TransactionError::InstructionError(0, InstructionError::Custom(expected as u32)),
+            "account {index}"
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use {
+    bytemuck::{bytes_of, Zeroable},
+    entropy::{
+        accounts::{Account, CallbackMeta, Request, RequestRef},
+        constants::{CALLBACK_IX_DATA_LEN, MAX_CALLBACK_ACCOUNTS},
+        error::EntropyError,
+    },
+    solana_program::program_error::ProgramError,
+};
+
+fn build_request(callback_accounts_len: u8, callback_ix_data_len: u16) -> Request {
+    let mut request = Request::zeroed();
+    request.discriminator = Request::discriminator();
+    request.sequence_number = 7;
+    request.callback_accounts_len = callback_accounts_len;
+    for (index, meta) in request.callback_accounts.iter_mut().enumerate() {
+        *meta = CallbackMeta {
+            pubkey: [index as u8 + 1; 32],
+            is_signer: 0,
+            is_writable: 1,
+        };
+    }
+    request.callback_ix_data_len = callback_ix_data_len;
+    request.callback_ix_data = [0xab; CALLBACK_IX_DATA_LEN];
+    request
+}
+
+#[test]
+fn test_request_ref_reads_only_populated_prefixes() {
+    let request = build_request(2, 5);
+    let request_ref = RequestRef::from_bytes(bytes_of(&request)).unwrap();
+
+    assert_eq!(request_ref.sequence_number, 7);
+    assert_eq!(request_ref.callback_accounts().len(), 2);
+    assert_eq!(request_ref.callback_accounts()[1].pubkey, [2u8; 32]);
+    assert_eq!(request_ref.callback_ix_data(), &[0xab; 5]);
+
+    let empty = build_request(0, 0);
+    assert!(RequestRef::from(&empty).callback_accounts().is_empty());
+    assert!(RequestRef::from(&empty).callback_ix_data().is_empty());
+}
+
+#[test]
+fn test_request_ref_clamps_out_of_range_lengths() {
+    let request = build_request(u8::MAX, u16::MAX);
+    let request_ref = RequestRef::from(&request);
+
+    assert_eq!(request_ref.callback_accounts().len(), MAX_CALLBACK_ACCOUNTS);
+    assert_eq!(request_ref.callback_ix_data().len(), CALLBACK_IX_DATA_LEN);
+}
+
+#[test]
+fn test_request_ref_rejects_other_accounts() {
+    let request = build_request(0, 0);
+    let data = bytes_of(&request);
+
+    assert_eq!(
+        RequestRef::from_bytes(&data[..Request::LEN - 1]).err(),
+        Some(ProgramError::from(EntropyError::WrongAccountSize))
+    );
+
+    let mut other = request;
+    other.discriminator = [0u8; 8];
+    assert_eq!(
+        RequestRef::from_bytes(bytes_of(&other)).err(),
+        Some(ProgramError::from(EntropyError::WrongAccountType))
+    );
+}
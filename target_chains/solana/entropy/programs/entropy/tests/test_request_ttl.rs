@@ -0,0 +1,301 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::{
+        accounts::{Provider, Request},
+        error::EntropyError,
+    },
+    entropy_sdk::instruction::{close_expired_request, withdraw_provider_fees, RequestParams},
+    entropy_test_utils::{start_with_requester, submit_tx, submit_tx_expect_err, EntropyTestEnv},
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey, rent::Rent, system_instruction},
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+/// Submits a callback request without revealing it.
+async fn request(env: &mut EntropyTestEnv, ttl_slots: u32) -> (Pubkey, Request) {
+    request_refunding(env, ttl_slots, None).await
+}
+
+async fn request_refunding(
+    env: &mut EntropyTestEnv,
+    ttl_slots: u32,
+    refund_address: Option<Pubkey>,
+) -> (Pubkey, Request) {
+    env.submit_callback_request(RequestParams {
+        compute_unit_limit: 100_000,
+        ttl_slots,
+        refund_address,
+        ..Default::default()
+    })
+    .await
+}
+
+async fn lamports(env: &mut EntropyTestEnv, address: Pubkey) -> Option<u64> {
+    env.context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .map(|account| account.lamports)
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_request_records_ttl() {
+    let (mut env, _) = start_with_requester(0).await;
+
+    let (_, request) = request(&mut env, 25).await;
+    assert_eq!(request.ttl_slots, 25);
+    assert!(!request.is_expired(request.request_slot + 25));
+    assert!(request.is_expired(request.request_slot + 26));
+}
+
+#[tokio::test]
+async fn test_reveal_within_ttl_succeeds() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let payer = env.payer();
+
+    request(&mut env, 1_000).await;
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    assert_eq!(reveals.len(), 1);
+    assert!(reveals[0].result.is_ok());
+}
+
+#[tokio::test]
+async fn test_reveal_rejects_expired_request() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let payer = env.payer();
+
+    let (request_account, request) = request(&mut env, 5).await;
+    env.context.warp_to_slot(request.request_slot + 6).unwrap();
+
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    assert_eq!(reveals.len(), 1);
+    assert_eq!(
+        reveals[0].result.as_ref().err(),
+        Some(&custom(EntropyError::RequestExpired))
+    );
+    assert!(lamports(&mut env, request_account).await.is_some());
+}
+
+#[tokio::test]
+async fn test_close_expired_request_refunds_payer() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+
+    let (request_account, request) = request(&mut env, 5).await;
+    let close_ix = close_expired_request(env.program_id, request_account, &request);
+
+    // Anyone may close it; fund a separate fee payer so the refund is exact.
+    let cranker = Keypair::new();
+    let fund_ix = system_instruction::transfer(&payer.pubkey(), &cranker.pubkey(), 1_000_000_000);
+    submit_tx(&mut env.context.banks_client, &payer, &[fund_ix], &[]).await;
+
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &cranker,
+        &[close_ix.clone()],
+        &[],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::RequestNotExpired));
+
+    env.context.warp_to_slot(request.request_slot + 6).unwrap();
+    let rent = lamports(&mut env, request_account).await.unwrap();
+    let payer_before = lamports(&mut env, payer.pubkey()).await.unwrap();
+    submit_tx(&mut env.context.banks_client, &cranker, &[close_ix], &[]).await;
+
+    assert!(lamports(&mut env, request_account).await.is_none());
+    assert_eq!(
+        lamports(&mut env, payer.pubkey()).await.unwrap(),
+        payer_before + rent
+    );
+
+    let provider = env
+        .context
+        .banks_client
+        .get_account(env.provider().address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        try_from_bytes::<Provider>(&provider.data)
+            .unwrap()
+            .outstanding_requests,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_close_expired_request_refunds_provider_fee() {
+    let (mut env, _) = start_with_requester(50_000).await;
+    let payer = env.payer();
+    let vault = env.provider().vault;
+
+    let (request_account, request) = request(&mut env, 5).await;
+    assert_eq!(request.provider_fee, 50_000);
+
+    let cranker = Keypair::new();
+    let fund_ix = system_instruction::transfer(&payer.pubkey(), &cranker.pubkey(), 1_000_000_000);
+    submit_tx(&mut env.context.banks_client, &payer, &[fund_ix], &[]).await;
+
+    env.context.warp_to_slot(request.request_slot + 6).unwrap();
+    let rent = lamports(&mut env, request_account).await.unwrap();
+    let payer_before = lamports(&mut env, payer.pubkey()).await.unwrap();
+    let vault_before = lamports(&mut env, vault).await.unwrap();
+    let close_ix = close_expired_request(env.program_id, request_account, &request);
+    submit_tx(&mut env.context.banks_client, &cranker, &[close_ix], &[]).await;
+
+    assert_eq!(
+        lamports(&mut env, payer.pubkey()).await.unwrap(),
+        payer_before + rent + 50_000
+    );
+    assert_eq!(
+        lamports(&mut env, vault).await.unwrap(),
+        vault_before - 50_000
+    );
+}
+
+async fn reserved_fee_lamports(env: &mut EntropyTestEnv) -> u64 {
+    let provider = env
+        .context
+        .banks_client
+        .get_account(env.provider().address)
+        .await
+        .unwrap()
+        .unwrap();
+    try_from_bytes::<Provider>(&provider.data)
+        .unwrap()
+        .reserved_fee_lamports
+}
+
+#[tokio::test]
+async fn test_withdraw_leaves_the_fee_of_an_expired_request_to_refund() {
+    let (mut env, _) = start_with_requester(50_000).await;
+    let payer = env.payer();
+    let vault = env.provider().vault;
+    let vault_floor = Rent::default().minimum_balance(0);
+
+    let (request_account, request) = request(&mut env, 5).await;
+    assert_eq!(reserved_fee_lamports(&mut env).await, 50_000);
+
+    // The fee is reserved for the refund until the request is settled.
+    let withdraw_ix =
+        withdraw_provider_fees(env.program_id, payer.pubkey(), payer.pubkey(), 30_000);
+    let err =
+        submit_tx_expect_err(&mut env.context.banks_client, &payer, &[withdraw_ix], &[]).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InsufficientFunds)
+    );
+    let unreserved = lamports(&mut env, vault).await.unwrap() - vault_floor - 50_000;
+    let withdraw_ix =
+        withdraw_provider_fees(env.program_id, payer.pubkey(), payer.pubkey(), unreserved);
+    submit_tx(&mut env.context.banks_client, &payer, &[withdraw_ix], &[]).await;
+
+    let cranker = Keypair::new();
+    let fund_ix = system_instruction::transfer(&payer.pubkey(), &cranker.pubkey(), 1_000_000_000);
+    submit_tx(&mut env.context.banks_client, &payer, &[fund_ix], &[]).await;
+
+    env.context.warp_to_slot(request.request_slot + 6).unwrap();
+    let rent = lamports(&mut env, request_account).await.unwrap();
+    let payer_before = lamports(&mut env, payer.pubkey()).await.unwrap();
+    let close_ix = close_expired_request(env.program_id, request_account, &request);
+    submit_tx(&mut env.context.banks_client, &cranker, &[close_ix], &[]).await;
+
+    assert_eq!(
+        lamports(&mut env, payer.pubkey()).await.unwrap(),
+        payer_before + rent + 50_000
+    );
+    assert_eq!(lamports(&mut env, vault).await.unwrap(), vault_floor);
+    assert_eq!(reserved_fee_lamports(&mut env).await, 0);
+}
+
+#[tokio::test]
+async fn test_close_rejects_request_without_ttl() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+
+    let (request_account, request) = request(&mut env, 0).await;
+    env.context
+        .warp_to_slot(request.request_slot + 10_000)
+        .unwrap();
+
+    let close_ix = close_expired_request(env.program_id, request_account, &request);
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &payer, &[close_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::RequestNotExpired));
+}
+
+#[tokio::test]
+async fn test_close_rejects_wrong_payer() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+
+    let (request_account, request) = request(&mut env, 5).await;
+    env.context.warp_to_slot(request.request_slot + 6).unwrap();
+
+    let mut close_ix = close_expired_request(env.program_id, request_account, &request);
+    close_ix.accounts[1] = AccountMeta::new(Pubkey::new_unique(), false);
+    let err = submit_tx_expect_err(&mut env.context.banks_client, &payer, &[close_ix], &[]).await;
+    assert_eq!(err, custom(EntropyError::WrongPayer));
+}
+
+#[tokio::test]
+async fn test_close_expired_request_refunds_refund_address() {
+    let (mut env, _) = start_with_requester(50_000).await;
+    let payer = env.payer();
+    let refund_address = Pubkey::new_unique();
+
+    let (request_account, request) = request_refunding(&mut env, 5, Some(refund_address)).await;
+    assert_eq!(request.payer, payer.pubkey().to_bytes());
+    assert_eq!(request.refund_address, refund_address.to_bytes());
+    env.context.warp_to_slot(request.request_slot + 6).unwrap();
+
+    let close_ix = close_expired_request(env.program_id, request_account, &request);
+    let mut payer_close_ix = close_ix.clone();
+    payer_close_ix.accounts[1] = AccountMeta::new(payer.pubkey(), false);
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[payer_close_ix],
+        &[],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::WrongPayer));
+
+    let rent = lamports(&mut env, request_account).await.unwrap();
+    submit_tx(&mut env.context.banks_client, &payer, &[close_ix], &[]).await;
+    assert!(lamports(&mut env, request_account).await.is_none());
+    assert_eq!(
+        lamports(&mut env, refund_address).await,
+        Some(rent + 50_000)
+    );
+}
+
+#[tokio::test]
+async fn test_reveal_refunds_refund_address() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let payer = env.payer();
+    let refund_address = Pubkey::new_unique();
+
+    let (request_account, _) = request_refunding(&mut env, 0, Some(refund_address)).await;
+    let rent = lamports(&mut env, request_account).await.unwrap();
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    assert_eq!(reveals.len(), 1);
+    assert!(reveals[0].result.is_ok());
+
+    assert!(lamports(&mut env, request_account).await.is_none());
+    assert_eq!(lamports(&mut env, refund_address).await, Some(rent));
+}
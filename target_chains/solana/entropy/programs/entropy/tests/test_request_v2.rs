@@ -0,0 +1,140 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::accounts::Config,
+    entropy_sdk::{
+        events::{parse_logs, EntropyEvent},
+        instruction::{forward_request_with_callback, request_v2, RequestAccounts, RequestParams},
+    },
+    entropy_test_utils::{submit_tx_with_metadata, EntropyFixture, EntropyTestEnv, MockKeeper},
+    simple_requester::{CallbackState, CALLBACK_ACTION, REQUEST_V2_ACTION},
+    solana_program::instruction::{AccountMeta, Instruction},
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+async fn config_seed(env: &mut EntropyTestEnv) -> [u8; 32] {
+    let account = env
+        .context
+        .banks_client
+        .get_account(env.config)
+        .await
+        .unwrap()
+        .unwrap();
+    try_from_bytes::<Config>(&account.data).unwrap().seed
+}
+
+/// A `RequestV2` forwarded through simple-requester, calling back into the
+/// fixture's callback state.
+fn build_request_v2(env: &EntropyTestEnv, request_account: &Keypair) -> Instruction {
+    let requester_program = env.requester().program_id;
+    let mut callback_ix_data = vec![CALLBACK_ACTION];
+    callback_ix_data.extend_from_slice(env.program_id.as_ref());
+    let entropy_ix = request_v2(
+        env.program_id,
+        &RequestAccounts {
+            requester_program,
+            payer: env.payer().pubkey(),
+            request_account: request_account.pubkey(),
+            provider_authority: env.provider().authority,
+        },
+        &RequestParams {
+            compute_unit_limit: 100_000,
+            callback_accounts: &[AccountMeta::new(env.requester().callback_state, false)],
+            callback_ix_data: &callback_ix_data,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let mut instruction = forward_request_with_callback(requester_program, &entropy_ix);
+    instruction.data[0] = REQUEST_V2_ACTION;
+    instruction
+}
+
+#[tokio::test]
+async fn test_request_v2_generates_user_randomness() {
+    let mut env = EntropyFixture::new()
+        .with_provider(0, 16)
+        .with_requester()
+        .start()
+        .await;
+    let keeper = MockKeeper::new(&env);
+    let payer = env.payer();
+    let callback_state = env.requester().callback_state;
+
+    let mut seeds = vec![config_seed(&mut env).await];
+    for sequence_number in 1..=2 {
+        let request_account = Keypair::new();
+        let instruction = build_request_v2(&env, &request_account);
+
+        let reveals = keeper
+            .submit(
+                &mut env.context.banks_client,
+                &payer,
+                &[instruction],
+                &[&request_account],
+            )
+            .await
+            .unwrap();
+        assert_eq!(reveals.len(), 1);
+        assert_eq!(reveals[0].sequence_number, sequence_number);
+        assert!(reveals[0].result.is_ok());
+
+        // Each request advances the seed it drew its user randomness from.
+        let seed = config_seed(&mut env).await;
+        assert!(!seeds.contains(&seed));
+        seeds.push(seed);
+
+        let callback_state_account = env
+            .context
+            .banks_client
+            .get_account(callback_state)
+            .await
+            .unwrap()
+            .unwrap();
+        let state = bytemuck::from_bytes::<CallbackState>(&callback_state_account.data);
+        assert_eq!(state.sequence_number, sequence_number);
+        assert_eq!(state.random_number, reveals[0].random_number().unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_request_v2_is_bound_to_the_request() {
+    let mut env = EntropyFixture::new()
+        .with_provider(0, 16)
+        .with_requester()
+        .start()
+        .await;
+    let payer = env.payer();
+    let request_accounts = [Keypair::new(), Keypair::new()];
+    let instructions: Vec<Instruction> = request_accounts
+        .iter()
+        .map(|request_account| build_request_v2(&env, request_account))
+        .collect();
+
+    // Same slot, requester and provider: only the request differs.
+    let (result, logs) = submit_tx_with_metadata(
+        &mut env.context.banks_client,
+        &payer,
+        &instructions,
+        &[&request_accounts[0], &request_accounts[1]],
+    )
+    .await;
+    result.unwrap();
+
+    let generated: Vec<_> = parse_logs(&env.program_id, &logs)
+        .into_iter()
+        .filter_map(|event| match event {
+            EntropyEvent::UserRandomnessGenerated(event) => Some(event),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(generated.len(), 2);
+    for (event, request_account) in generated.iter().zip(&request_accounts) {
+        assert_eq!(event.request_account, request_account.pubkey().to_bytes());
+    }
+    assert_eq!(
+        generated[0].sequence_number + 1,
+        generated[1].sequence_number
+    );
+    assert_ne!(generated[0].user_randomness, generated[1].user_randomness);
+    assert_eq!(config_seed(&mut env).await, generated[1].user_randomness);
+}
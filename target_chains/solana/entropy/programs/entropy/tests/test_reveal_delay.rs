@@ -0,0 +1,101 @@
+use {
+    bytemuck::Zeroable,
+    entropy::{accounts::Request, error::EntropyError},
+    entropy_sdk::instruction::RequestParams,
+    entropy_test_utils::{start_with_requester, EntropyTestEnv},
+    solana_program::pubkey::Pubkey,
+    solana_sdk::{instruction::InstructionError, transaction::TransactionError},
+};
+
+/// Submits a callback request without revealing it.
+async fn request(
+    env: &mut EntropyTestEnv,
+    ttl_slots: u32,
+    min_reveal_delay_slots: u16,
+) -> (Pubkey, Request) {
+    env.submit_callback_request(RequestParams {
+        compute_unit_limit: 100_000,
+        ttl_slots,
+        min_reveal_delay_slots,
+        ..Default::default()
+    })
+    .await
+}
+
+async fn lamports(env: &mut EntropyTestEnv, address: Pubkey) -> Option<u64> {
+    env.context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .map(|account| account.lamports)
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_request_records_min_reveal_delay() {
+    let (mut env, _) = start_with_requester(0).await;
+
+    let (_, request) = request(&mut env, 0, 10).await;
+    assert_eq!(request.min_reveal_delay_slots, 10);
+    assert!(!request.is_revealable_yet(request.request_slot + 9));
+    assert!(request.is_revealable_yet(request.request_slot + 10));
+}
+
+#[tokio::test]
+async fn test_reveal_without_delay_succeeds_immediately() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let payer = env.payer();
+
+    request(&mut env, 0, 0).await;
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    assert_eq!(reveals.len(), 1);
+    assert!(reveals[0].result.is_ok());
+}
+
+#[tokio::test]
+async fn test_reveal_enforces_min_reveal_delay_at_boundary() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let payer = env.payer();
+
+    let (request_account, request) = request(&mut env, 0, 5).await;
+    env.context.warp_to_slot(request.request_slot + 4).unwrap();
+
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    assert_eq!(reveals.len(), 1);
+    assert_eq!(
+        reveals[0].result.as_ref().err(),
+        Some(&custom(EntropyError::RevealTooEarly))
+    );
+    assert!(lamports(&mut env, request_account).await.is_some());
+
+    env.context.warp_to_slot(request.request_slot + 5).unwrap();
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    assert_eq!(reveals.len(), 1);
+    assert!(reveals[0].result.is_ok());
+    assert!(lamports(&mut env, request_account).await.is_none());
+}
+
+#[test]
+fn test_blockhash_request_is_not_revealable_in_its_own_slot() {
+    let mut request = Request::zeroed();
+    request.request_slot = 100;
+    assert!(request.is_revealable_yet(100));
+
+    request.use_blockhash = 1;
+    assert!(!request.is_revealable_yet(100));
+    assert!(request.is_revealable_yet(101));
+
+    request.min_reveal_delay_slots = 5;
+    assert!(!request.is_revealable_yet(104));
+    assert!(request.is_revealable_yet(105));
+}
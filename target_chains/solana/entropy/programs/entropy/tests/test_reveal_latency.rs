@@ -0,0 +1,131 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::accounts::{Provider, Request},
+    entropy_sdk::instruction::RequestParams,
+    entropy_test_utils::{
+        build_register_args, build_register_provider_ix, start_with_requester, submit_tx,
+        EntropyTestEnv, HashChain,
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::{account::AccountSharedData, rent::Rent, signature::Signer},
+};
+
+/// `Provider::LEN` before the latency stats were appended.
+const LEGACY_PROVIDER_LEN: usize = 520;
+
+/// Submits a callback request without revealing it.
+async fn request(env: &mut EntropyTestEnv) -> Request {
+    let (_, request) = env
+        .submit_callback_request(RequestParams {
+            compute_unit_limit: 100_000,
+            ..Default::default()
+        })
+        .await;
+    request
+}
+
+async fn provider(env: &mut EntropyTestEnv, address: Pubkey) -> Provider {
+    let account = env
+        .context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    *try_from_bytes::<Provider>(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_reveal_records_provider_latency() {
+    let (mut env, keeper) = start_with_requester(0).await;
+    let payer = env.payer();
+    let provider_address = env.provider().address;
+
+    let initial = provider(&mut env, provider_address).await;
+    assert_eq!(initial.reveal_count, 0);
+    assert_eq!(initial.average_reveal_latency_slots(), None);
+
+    let slow = request(&mut env).await;
+    env.context.warp_to_slot(slow.request_slot + 20).unwrap();
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    let slow_event = *reveals[0].result.as_ref().unwrap();
+    assert_eq!(slow_event.request_slot, slow.request_slot);
+    assert!(slow_event.reveal_slot >= slow.request_slot + 20);
+    let slow_latency = slow_event.reveal_slot - slow_event.request_slot;
+
+    let after_slow = provider(&mut env, provider_address).await;
+    assert_eq!(after_slow.reveal_count, 1);
+    assert_eq!(after_slow.reveal_latency_sum_slots, slow_latency);
+    assert_eq!(after_slow.reveal_latency_max_slots, slow_latency);
+
+    request(&mut env).await;
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    let fast_event = *reveals[0].result.as_ref().unwrap();
+    let fast_latency = fast_event.reveal_slot - fast_event.request_slot;
+    assert!(fast_latency < slow_latency);
+
+    let after_fast = provider(&mut env, provider_address).await;
+    assert_eq!(after_fast.reveal_count, 2);
+    assert_eq!(
+        after_fast.reveal_latency_sum_slots,
+        slow_latency + fast_latency
+    );
+    assert_eq!(after_fast.reveal_latency_max_slots, slow_latency);
+    assert_eq!(
+        after_fast.average_reveal_latency_slots(),
+        Some((slow_latency + fast_latency) / 2)
+    );
+}
+
+#[tokio::test]
+async fn test_register_migrates_legacy_provider_account() {
+    let (mut env, _) = start_with_requester(0).await;
+    let payer = env.payer();
+    let provider_address = env.provider().address;
+    let vault = env.provider().vault;
+
+    // Stand in for a provider registered before the latency stats existed.
+    let mut account = env
+        .context
+        .banks_client
+        .get_account(provider_address)
+        .await
+        .unwrap()
+        .unwrap();
+    account.data.truncate(LEGACY_PROVIDER_LEN);
+    account.lamports = Rent::default().minimum_balance(LEGACY_PROVIDER_LEN);
+    env.context
+        .set_account(&provider_address, &AccountSharedData::from(account));
+
+    let chain = HashChain::from_seed([5u8; 32], 16);
+    let register_ix = build_register_provider_ix(
+        env.program_id,
+        payer.pubkey(),
+        provider_address,
+        vault,
+        build_register_args(0, chain.commitment(), 16),
+        true,
+    );
+    submit_tx(&mut env.context.banks_client, &payer, &[register_ix], &[]).await;
+
+    let account = env
+        .context
+        .banks_client
+        .get_account(provider_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), Provider::LEN);
+    assert!(account.lamports >= Rent::default().minimum_balance(Provider::LEN));
+
+    let migrated = *try_from_bytes::<Provider>(&account.data).unwrap();
+    assert_eq!(migrated.current_commitment, chain.commitment());
+    assert_eq!(migrated.reveal_count, 0);
+    assert_eq!(migrated.reveal_latency_sum_slots, 0);
+    assert_eq!(migrated.reveal_latency_max_slots, 0);
+    assert_eq!(migrated.reserved_fee_lamports, 0);
+}
@@ -1,18 +1,21 @@
-mod test_utils;
-
 #[allow(deprecated)]
 use {
-    bytemuck::{bytes_of, cast_slice, try_from_bytes, Pod, Zeroable},
+    bytemuck::{try_from_bytes, Zeroable},
     entropy::{
-        accounts::{CallbackMeta, Provider, Request},
-        constants::{CALLBACK_NOT_STARTED, REQUESTER_SIGNER_SEED},
+        accounts::{CallbackMeta, CallbackStatus, Provider, Request},
+        constants::REQUESTER_SIGNER_SEED,
         discriminator::{provider_discriminator, request_discriminator},
         error::EntropyError,
-        instruction::{EntropyInstruction, RevealArgs},
+        instruction::{EntropyInstruction, RequestWithCallbackHeader, RevealArgs},
         pda::{
-            config_pda, entropy_signer_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda,
+            callback_failure_stats_pda, config_pda, entropy_signer_pda, provider_pda,
+            provider_vault_pda, pyth_fee_vault_pda,
         },
     },
+    entropy_test_utils::{
+        build_register_args, build_register_provider_ix, initialize_config,
+        new_entropy_program_test, submit_tx, submit_tx_expect_err,
+    },
     simple_requester::{
         CallbackState, CALLBACK_ACTION, CALLBACK_STATE_LEN, REQUEST_WITH_CALLBACK_ACTION,
     },
@@ -30,20 +33,8 @@ use {
         signature::{Keypair, Signer},
         transaction::TransactionError,
     },
-    test_utils::{
-        build_register_args, build_register_provider_ix, initialize_config,
-        new_entropy_program_test, submit_tx, submit_tx_expect_err,
-    },
 };
 
-#[repr(C)]
-#[derive(Clone, Copy, Pod, Zeroable)]
-struct RequestWithCallbackHeader {
-    user_randomness: [u8; 32],
-    compute_unit_limit: u32,
-    callback_accounts_len: u32,
-}
-
 fn new_program_test_with_requester(
     program_id: Pubkey,
     requester_program_id: Pubkey,
@@ -83,6 +74,7 @@ async fn register_provider(
 }
 
 fn build_request_with_callback_data(
+    requester_signer_bump: u8,
     user_randomness: [u8; 32],
     compute_unit_limit: u32,
     callback_accounts: &[CallbackMeta],
@@ -91,21 +83,15 @@ fn build_request_with_callback_data(
     let header = RequestWithCallbackHeader {
         user_randomness,
         compute_unit_limit,
-        callback_accounts_len: callback_accounts.len() as u32,
+        ..RequestWithCallbackHeader::zeroed()
     };
 
-    let mut data = Vec::with_capacity(
-        8 + core::mem::size_of::<RequestWithCallbackHeader>()
-            + callback_accounts.len() * CallbackMeta::LEN
-            + 4
-            + callback_ix_data.len(),
-    );
-    data.extend_from_slice(&EntropyInstruction::RequestWithCallback.discriminator());
-    data.extend_from_slice(bytes_of(&header));
-    data.extend_from_slice(cast_slice(callback_accounts));
-    data.extend_from_slice(&(callback_ix_data.len() as u32).to_le_bytes());
-    data.extend_from_slice(callback_ix_data);
-    data
+    EntropyInstruction::build_callback_request(
+        &header,
+        requester_signer_bump,
+        callback_accounts,
+        callback_ix_data,
+    )
 }
 
 #[tokio::test]
@@ -133,18 +119,22 @@ async fn test_request_with_callback_rejects_entropy_program_in_callback_accounts
         is_writable: 0,
     }];
 
-    let entropy_request_data =
-        build_request_with_callback_data([9u8; 32], 200_000, &callback_accounts, &[]);
+    let (requester_signer, requester_signer_bump) = Pubkey::find_program_address(
+        &[REQUESTER_SIGNER_SEED, program_id.as_ref()],
+        &requester_program_id,
+    );
+    let entropy_request_data = build_request_with_callback_data(
+        requester_signer_bump,
+        [9u8; 32],
+        200_000,
+        &callback_accounts,
+        &[],
+    );
 
     let mut requester_data = Vec::with_capacity(1 + entropy_request_data.len());
     requester_data.push(REQUEST_WITH_CALLBACK_ACTION);
     requester_data.extend_from_slice(&entropy_request_data);
 
-    let (requester_signer, _) = Pubkey::find_program_address(
-        &[REQUESTER_SIGNER_SEED, program_id.as_ref()],
-        &requester_program_id,
-    );
-
     let request_with_callback_ix = Instruction {
         program_id: requester_program_id,
         data: requester_data,
@@ -175,7 +165,7 @@ async fn test_request_with_callback_rejects_entropy_program_in_callback_accounts
         err,
         TransactionError::InstructionError(
             0,
-            InstructionError::Custom(EntropyError::InvalidAccount as u32)
+            InstructionError::Custom(EntropyError::EntropyInCallbackAccounts as u32)
         )
     );
 }
@@ -231,7 +221,12 @@ async fn test_reveal_with_callback_flow() {
     callback_ix_data.push(CALLBACK_ACTION);
     callback_ix_data.extend_from_slice(program_id.as_ref());
 
+    let (requester_signer, requester_signer_bump) = Pubkey::find_program_address(
+        &[REQUESTER_SIGNER_SEED, program_id.as_ref()],
+        &requester_program_id,
+    );
     let entropy_request_data = build_request_with_callback_data(
+        requester_signer_bump,
         user_randomness,
         compute_unit_limit,
         &callback_accounts,
@@ -242,11 +237,6 @@ async fn test_reveal_with_callback_flow() {
     requester_data.push(REQUEST_WITH_CALLBACK_ACTION);
     requester_data.extend_from_slice(&entropy_request_data);
 
-    let (requester_signer, _) = Pubkey::find_program_address(
-        &[REQUESTER_SIGNER_SEED, program_id.as_ref()],
-        &requester_program_id,
-    );
-
     let request_with_callback_ix = Instruction {
         program_id: requester_program_id,
         data: requester_data,
@@ -280,15 +270,13 @@ async fn test_reveal_with_callback_flow() {
         .unwrap();
     let request = try_from_bytes::<Request>(&request_account_data.data).unwrap();
     assert_eq!(request.discriminator, request_discriminator());
-    assert_eq!(request.callback_status, CALLBACK_NOT_STARTED);
+    assert_eq!(request.status(), Ok(CallbackStatus::NotStarted));
 
     let reveal_args = RevealArgs {
         user_contribution: user_randomness,
         provider_contribution,
     };
-    let mut reveal_data = Vec::with_capacity(8 + core::mem::size_of::<RevealArgs>());
-    reveal_data.extend_from_slice(&EntropyInstruction::RevealWithCallback.discriminator());
-    reveal_data.extend_from_slice(bytes_of(&reveal_args));
+    let reveal_data = EntropyInstruction::build(&reveal_args);
 
     let reveal_ix = Instruction {
         program_id,
@@ -302,6 +290,10 @@ async fn test_reveal_with_callback_flow() {
             AccountMeta::new_readonly(system_program::id(), false),
             AccountMeta::new(payer.pubkey(), true),
             AccountMeta::new(callback_state.pubkey(), false),
+            AccountMeta::new(
+                callback_failure_stats_pda(&program_id, &payer.pubkey(), &requester_program_id).0,
+                false,
+            ),
         ],
     };
 
@@ -319,6 +311,8 @@ async fn test_reveal_with_callback_flow() {
     assert_eq!(callback_state.sequence_number, 1);
     assert_eq!(callback_state.provider, payer.pubkey().to_bytes());
     assert_eq!(callback_state.random_number, expected_random);
+    assert_eq!(callback_state.entropy_signer, entropy_signer.to_bytes());
+    assert!(callback_state.remaining_compute_units > 0);
 
     let provider_account = banks_client
         .get_account(provider_address)
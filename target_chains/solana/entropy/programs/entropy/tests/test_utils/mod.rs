@@ -1,10 +0,0 @@
-pub mod banks;
-pub mod instructions;
-pub mod register_args;
-
-#[allow(unused_imports)]
-pub use banks::{initialize_config, new_entropy_program_test, submit_tx, submit_tx_expect_err};
-#[allow(unused_imports)]
-pub use instructions::build_register_provider_ix;
-#[allow(unused_imports)]
-pub use register_args::build_register_args;
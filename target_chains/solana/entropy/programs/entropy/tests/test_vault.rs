@@ -0,0 +1,13 @@
+use {entropy::vault::withdrawable_fees, solana_program::rent::Rent};
+
+#[test]
+fn test_withdrawable_fees_keep_the_rent_floor() {
+    let rent = Rent::default();
+    let floor = rent.minimum_balance(0);
+
+    assert_eq!(withdrawable_fees(floor + 5, &rent), 5);
+    assert_eq!(withdrawable_fees(floor, &rent), 0);
+    // A vault below the floor, e.g. one funded before rent rose, pays nothing.
+    assert_eq!(withdrawable_fees(floor - 1, &rent), 0);
+    assert_eq!(withdrawable_fees(0, &rent), 0);
+}
@@ -0,0 +1,152 @@
+use {
+    entropy::error::EntropyError,
+    entropy_sdk::instruction::{
+        forward_request_with_callback, request_with_callback, withdraw_provider_fees,
+        RequestAccounts, RequestParams,
+    },
+    entropy_test_utils::{
+        submit_tx, submit_tx_expect_err, EntropyFixture, EntropyTestEnv, MockKeeper,
+    },
+    simple_requester::CALLBACK_ACTION,
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey, rent::Rent, system_instruction},
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+const PROVIDER_FEE: u64 = 1_000;
+
+/// Starts with one revealed request, so the vault holds a provider fee above
+/// its rent floor.
+async fn start_with_fees() -> EntropyTestEnv {
+    let mut env = EntropyFixture::new()
+        .with_provider(PROVIDER_FEE, 16)
+        .with_requester()
+        .start()
+        .await;
+    let keeper = MockKeeper::new(&env);
+    let payer = env.payer();
+    let requester_program = env.requester().program_id;
+    let mut callback_ix_data = vec![CALLBACK_ACTION];
+    callback_ix_data.extend_from_slice(env.program_id.as_ref());
+    let request_account = Keypair::new();
+    let entropy_ix = request_with_callback(
+        env.program_id,
+        &RequestAccounts {
+            requester_program,
+            payer: payer.pubkey(),
+            request_account: request_account.pubkey(),
+            provider_authority: env.provider().authority,
+        },
+        [7u8; 32],
+        &RequestParams {
+            compute_unit_limit: 100_000,
+            callback_accounts: &[AccountMeta::new(env.requester().callback_state, false)],
+            callback_ix_data: &callback_ix_data,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let instruction = forward_request_with_callback(requester_program, &entropy_ix);
+    let reveals = keeper
+        .submit(
+            &mut env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await
+        .unwrap();
+    assert!(reveals[0].result.is_ok());
+    env
+}
+
+async fn lamports(env: &mut EntropyTestEnv, address: Pubkey) -> u64 {
+    env.context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .map_or(0, |account| account.lamports)
+}
+
+fn custom(error: EntropyError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_withdraw_provider_fees_to_destination() {
+    let mut env = start_with_fees().await;
+    let authority = env.payer();
+    let vault = env.provider().vault;
+    let destination = Pubkey::new_unique();
+    let vault_floor = Rent::default().minimum_balance(0);
+    let accrued = lamports(&mut env, vault).await - vault_floor;
+    assert!(accrued >= PROVIDER_FEE);
+
+    // The cold wallet already exists, so any amount can land in it.
+    let fund_ix = system_instruction::transfer(&authority.pubkey(), &destination, vault_floor);
+    submit_tx(&mut env.context.banks_client, &authority, &[fund_ix], &[]).await;
+
+    let withdraw_ix =
+        withdraw_provider_fees(env.program_id, authority.pubkey(), destination, accrued);
+    submit_tx(
+        &mut env.context.banks_client,
+        &authority,
+        &[withdraw_ix],
+        &[],
+    )
+    .await;
+
+    assert_eq!(lamports(&mut env, vault).await, vault_floor);
+    assert_eq!(lamports(&mut env, destination).await, vault_floor + accrued);
+}
+
+#[tokio::test]
+async fn test_withdraw_provider_fees_keeps_the_rent_floor() {
+    let mut env = start_with_fees().await;
+    let authority = env.payer();
+    let vault = env.provider().vault;
+    let vault_floor = Rent::default().minimum_balance(0);
+
+    let available = lamports(&mut env, vault).await - vault_floor;
+    let withdraw_ix = withdraw_provider_fees(
+        env.program_id,
+        authority.pubkey(),
+        authority.pubkey(),
+        available + 1,
+    );
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &authority,
+        &[withdraw_ix],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InsufficientFunds)
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_provider_fees_rejects_other_signers() {
+    let mut env = start_with_fees().await;
+    let payer = env.payer();
+
+    let stranger = Keypair::new();
+    let mut withdraw_ix =
+        withdraw_provider_fees(env.program_id, stranger.pubkey(), stranger.pubkey(), 1);
+    withdraw_ix.accounts[1] = AccountMeta::new_readonly(env.provider().address, false);
+    withdraw_ix.accounts[2] = AccountMeta::new(env.provider().vault, false);
+    let err = submit_tx_expect_err(
+        &mut env.context.banks_client,
+        &payer,
+        &[withdraw_ix],
+        &[&stranger],
+    )
+    .await;
+    assert_eq!(err, custom(EntropyError::WrongProvider));
+}
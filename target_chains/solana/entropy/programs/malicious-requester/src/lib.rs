@@ -0,0 +1,150 @@
+//! A requester whose callback misbehaves on purpose, for exercising how the
+//! entropy program copes with hostile consumers. Requests are forwarded with
+//! the simple-requester ABI; the callback prefix is `[mode, entropy program
+//! id]` and `mode` selects the misbehavior.
+
+use {
+    bytemuck::Zeroable,
+    entropy::instruction::{EntropyInstruction, RevealArgs},
+    entropy_cpi::{parse_callback, parse_requester_signer_bump, RequestAccounts},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction},
+        program::invoke,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+pub const REQUEST_WITH_CALLBACK_ACTION: u8 = 1;
+pub const CALLBACK_ACTION: u8 = 0xCB;
+
+/// Returns [`FAIL_ERROR`] from the callback.
+pub const MODE_FAIL: u8 = 0;
+/// Panics in the callback.
+pub const MODE_PANIC: u8 = 1;
+/// Invokes the entropy program again from inside the callback.
+pub const MODE_REENTER: u8 = 2;
+/// Flips a byte of the first callback account, which the test points at an
+/// account this program doesn't own.
+pub const MODE_WRITE_FOREIGN: u8 = 3;
+/// Spins until the transaction runs out of compute.
+pub const MODE_BURN_COMPUTE: u8 = 4;
+/// Returns successfully, for a requester whose callbacks only fail some of
+/// the time.
+pub const MODE_SUCCEED: u8 = 5;
+
+pub const FAIL_ERROR: u32 = 0xBAD;
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    match data[0] {
+        REQUEST_WITH_CALLBACK_ACTION => {
+            process_request_with_callback(program_id, accounts, &data[1..])
+        }
+        CALLBACK_ACTION => process_callback(accounts, &data[1..]),
+        MODE_SUCCEED => Ok(()),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn process_request_with_callback(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entropy_data: &[u8],
+) -> ProgramResult {
+    let mut account_info_iter = accounts.iter();
+    let requester_signer = next_account_info(&mut account_info_iter)?;
+    let payer = next_account_info(&mut account_info_iter)?;
+    let requester_program = next_account_info(&mut account_info_iter)?;
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let provider = next_account_info(&mut account_info_iter)?;
+    let provider_vault = next_account_info(&mut account_info_iter)?;
+    let config = next_account_info(&mut account_info_iter)?;
+    let pyth_fee_vault = next_account_info(&mut account_info_iter)?;
+    let system_program = next_account_info(&mut account_info_iter)?;
+    let callback_program = next_account_info(&mut account_info_iter)?;
+    if callback_program.key != program_id {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let entropy_program = next_account_info(&mut account_info_iter)?;
+
+    let accounts = RequestAccounts {
+        requester_signer,
+        requester_signer_bump: parse_requester_signer_bump(entropy_data)?,
+        payer,
+        requester_program,
+        request_account,
+        provider,
+        provider_vault,
+        config,
+        pyth_fee_vault,
+        system_program,
+        entropy_program,
+    };
+    entropy_cpi::request_with_callback_raw(program_id, &accounts, entropy_data.to_vec())?;
+    Ok(())
+}
+
+fn process_callback(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (prefix, _callback) = parse_callback(data)?;
+    let (&mode, entropy_program_id) = prefix
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let entropy_program_id = Pubkey::new_from_array(
+        entropy_program_id
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+    );
+
+    match mode {
+        MODE_FAIL => Err(ProgramError::Custom(FAIL_ERROR)),
+        MODE_PANIC => panic!("malicious callback"),
+        MODE_REENTER => {
+            // A reveal is the most damaging thing to re-enter with; the
+            // accounts are whatever the callback was handed.
+            let data = EntropyInstruction::build(&RevealArgs::zeroed());
+            let instruction = Instruction {
+                program_id: entropy_program_id,
+                accounts: accounts
+                    .iter()
+                    .map(|account| AccountMeta {
+                        pubkey: *account.key,
+                        is_signer: account.is_signer,
+                        is_writable: account.is_writable,
+                    })
+                    .collect(),
+                data,
+            };
+            invoke(&instruction, accounts)
+        }
+        MODE_WRITE_FOREIGN => {
+            let target = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let mut target_data = target.try_borrow_mut_data()?;
+            let first = target_data
+                .first_mut()
+                .ok_or(ProgramError::AccountDataTooSmall)?;
+            *first ^= 0xFF;
+            Ok(())
+        }
+        MODE_BURN_COMPUTE => {
+            let mut counter = 0u64;
+            loop {
+                counter = core::hint::black_box(counter.wrapping_add(1));
+            }
+        }
+        MODE_SUCCEED => Ok(()),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
@@ -0,0 +1,24 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[repr(u32)]
+pub enum NftTraitsError {
+    #[error("invalid account")]
+    InvalidAccount = 0,
+    #[error("invalid PDA")]
+    InvalidPda = 1,
+    #[error("invalid arguments")]
+    InvalidArgs = 2,
+    #[error("mint is not an unissued placeholder of this program")]
+    InvalidMint = 3,
+    #[error("traits already revealed")]
+    AlreadyRevealed = 4,
+    #[error("provider is not the collection's provider")]
+    WrongProvider = 5,
+}
+
+impl From<NftTraitsError> for solana_program::program_error::ProgramError {
+    fn from(value: NftTraitsError) -> Self {
+        solana_program::program_error::ProgramError::Custom(value as u32)
+    }
+}
@@ -0,0 +1,127 @@
+#[allow(deprecated)]
+use {
+    crate::state::{authority_pda, collection_pda, traits_pda},
+    bytemuck::{Pod, Zeroable},
+    entropy::pda::{
+        config_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda, requester_signer_pda,
+    },
+    mpl_token_metadata::accounts::Metadata,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+pub const MINT_ACTION: u8 = 0;
+pub const INITIALIZE_COLLECTION_ACTION: u8 = 1;
+pub const CALLBACK_ACTION: u8 = 0xCB;
+
+/// Data of `MINT_ACTION`, after the action byte.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct MintArgs {
+    pub user_randomness: [u8; 32],
+    /// Compute units reserved for the reveal callback. Rewriting Metaplex
+    /// metadata needs considerably more than rolling the traits alone.
+    pub compute_unit_limit: u32,
+    /// Bump of the requester signer; filled in by [`mint`].
+    pub requester_signer_bump: u8,
+    pub _padding: [u8; 3],
+}
+
+/// Data of `INITIALIZE_COLLECTION_ACTION`, after the action byte.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct InitializeCollectionArgs {
+    pub provider_authority: [u8; 32],
+}
+
+/// Creates the collection for `entropy_program_id`, paid by `payer`,
+/// minting only through `provider_authority`.
+pub fn initialize_collection(
+    program_id: Pubkey,
+    entropy_program_id: Pubkey,
+    payer: Pubkey,
+    provider_authority: Pubkey,
+) -> Instruction {
+    let (collection, _) = collection_pda(&program_id, &entropy_program_id);
+    let mut data = vec![INITIALIZE_COLLECTION_ACTION];
+    data.extend_from_slice(bytemuck::bytes_of(&InitializeCollectionArgs {
+        provider_authority: provider_authority.to_bytes(),
+    }));
+    Instruction {
+        program_id,
+        data,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(collection, false),
+            AccountMeta::new_readonly(entropy_program_id, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    }
+}
+
+/// Issues the placeholder token of `mint` to `token_account` and requests
+/// its reveal. `mint` must be an initialized SPL mint with no supply, zero
+/// decimals and [`authority_pda`] as mint authority; `token_account` must
+/// hold that mint for `minter`. `provider_authority` must be the
+/// collection's provider. `request_account` is a fresh keypair that must
+/// sign. With `with_metadata`, the mint also gets Metaplex metadata
+/// that the reveal rewrites.
+#[allow(clippy::too_many_arguments)]
+pub fn mint(
+    program_id: Pubkey,
+    entropy_program_id: Pubkey,
+    minter: Pubkey,
+    mint: Pubkey,
+    token_account: Pubkey,
+    request_account: Pubkey,
+    provider_authority: Pubkey,
+    with_metadata: bool,
+    args: &MintArgs,
+) -> Instruction {
+    let (requester_signer, requester_signer_bump) =
+        requester_signer_pda(&program_id, &entropy_program_id);
+    let args = MintArgs {
+        requester_signer_bump,
+        ..*args
+    };
+    let mut data = vec![MINT_ACTION];
+    data.extend_from_slice(bytemuck::bytes_of(&args));
+    let mut accounts = vec![
+        AccountMeta::new(minter, true),
+        AccountMeta::new(mint, false),
+        AccountMeta::new(token_account, false),
+        AccountMeta::new(traits_pda(&program_id, &mint).0, false),
+        AccountMeta::new_readonly(authority_pda(&program_id, &entropy_program_id).0, false),
+        AccountMeta::new_readonly(collection_pda(&program_id, &entropy_program_id).0, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(requester_signer, false),
+        AccountMeta::new(request_account, true),
+        AccountMeta::new(
+            provider_pda(&entropy_program_id, &provider_authority).0,
+            false,
+        ),
+        AccountMeta::new(
+            provider_vault_pda(&entropy_program_id, &provider_authority).0,
+            false,
+        ),
+        AccountMeta::new_readonly(config_pda(&entropy_program_id).0, false),
+        AccountMeta::new(pyth_fee_vault_pda(&entropy_program_id).0, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(program_id, false),
+        AccountMeta::new_readonly(entropy_program_id, false),
+    ];
+    if with_metadata {
+        accounts.extend([
+            AccountMeta::new(Metadata::find_pda(&mint).0, false),
+            AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+        ]);
+    }
+    Instruction {
+        program_id,
+        data,
+        accounts,
+    }
+}
@@ -0,0 +1,20 @@
+//! Example entropy consumer: an NFT whose traits are rolled in the entropy
+//! callback.
+//!
+//! Minting issues a single placeholder token from a mint the program controls
+//! and requests entropy in the same instruction, from the provider the
+//! collection was initialized with. The callback rolls the traits
+//! into a PDA of the mint and, when the token has Metaplex metadata, rewrites
+//! the metadata to the revealed name and URI. The Metaplex accounts travel as
+//! callback accounts, so a reveal uses four of the
+//! [`MAX_CALLBACK_ACCOUNTS`](entropy::constants::MAX_CALLBACK_ACCOUNTS).
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+pub use processor::process_instruction;
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
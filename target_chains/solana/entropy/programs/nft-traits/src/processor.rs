@@ -0,0 +1,389 @@
+#[allow(deprecated)]
+use {
+    crate::{
+        error::NftTraitsError,
+        instruction::{
+            InitializeCollectionArgs, MintArgs, CALLBACK_ACTION, INITIALIZE_COLLECTION_ACTION,
+            MINT_ACTION,
+        },
+        state::{
+            authority_pda, collection_pda, roll_traits, traits_pda, Collection, Traits,
+            AUTHORITY_SEED, BASE_URI, COLLECTION_SEED, NAME_PREFIX, STATUS_PENDING,
+            STATUS_REVEALED, SYMBOL, TRAITS_SEED,
+        },
+    },
+    bytemuck::{try_from_bytes, try_from_bytes_mut},
+    entropy::pda::provider_pda,
+    entropy_cpi::{
+        callback_meta, parse_callback, verify_entropy_signer, RequestAccounts, RequestParams,
+    },
+    mpl_token_metadata::{
+        accounts::Metadata,
+        instructions::{CreateMetadataAccountV3CpiBuilder, UpdateMetadataAccountV2CpiBuilder},
+        types::DataV2,
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program::invoke_signed,
+        program_error::ProgramError,
+        program_option::COption,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+        sysvar::Sysvar,
+    },
+    spl_token::{
+        instruction::{mint_to, set_authority, AuthorityType},
+        state::{Account as TokenAccount, Mint},
+    },
+};
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (action, data) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    match *action {
+        MINT_ACTION => process_mint(program_id, accounts, data),
+        INITIALIZE_COLLECTION_ACTION => process_initialize_collection(program_id, accounts, data),
+        CALLBACK_ACTION => process_callback(program_id, accounts, data),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn process_initialize_collection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = try_from_bytes::<InitializeCollectionArgs>(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let mut account_info_iter = accounts.iter();
+    let payer = next_account_info(&mut account_info_iter)?;
+    let collection_account = next_account_info(&mut account_info_iter)?;
+    let entropy_program = next_account_info(&mut account_info_iter)?;
+    let system_program = next_account_info(&mut account_info_iter)?;
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (expected_collection, bump) = collection_pda(program_id, entropy_program.key);
+    if collection_account.key != &expected_collection {
+        return Err(NftTraitsError::InvalidPda.into());
+    }
+
+    let create_ix = system_instruction::create_account(
+        payer.key,
+        collection_account.key,
+        Rent::get()?.minimum_balance(Collection::LEN),
+        Collection::LEN as u64,
+        program_id,
+    );
+    invoke_signed(
+        &create_ix,
+        &[
+            payer.clone(),
+            collection_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[COLLECTION_SEED, entropy_program.key.as_ref(), &[bump]]],
+    )?;
+
+    let mut collection_data = collection_account.try_borrow_mut_data()?;
+    let collection = try_from_bytes_mut::<Collection>(&mut collection_data)
+        .map_err(|_| NftTraitsError::InvalidAccount)?;
+    collection.provider_authority = args.provider_authority;
+    collection.bump = bump;
+    Ok(())
+}
+
+/// Issues the single placeholder token, requests the reveal and, when the
+/// Metaplex accounts are passed, creates placeholder metadata. The mint
+/// authority is revoked afterwards, so the supply stays at one.
+fn process_mint(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let args =
+        try_from_bytes::<MintArgs>(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    // Entropy skips the callback when the limit is zero.
+    if args.compute_unit_limit == 0 {
+        return Err(NftTraitsError::InvalidArgs.into());
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let minter = next_account_info(&mut account_info_iter)?;
+    let mint = next_account_info(&mut account_info_iter)?;
+    let token_account = next_account_info(&mut account_info_iter)?;
+    let traits_account = next_account_info(&mut account_info_iter)?;
+    let authority = next_account_info(&mut account_info_iter)?;
+    let collection_account = next_account_info(&mut account_info_iter)?;
+    let token_program = next_account_info(&mut account_info_iter)?;
+    let requester_signer = next_account_info(&mut account_info_iter)?;
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let provider = next_account_info(&mut account_info_iter)?;
+    let provider_vault = next_account_info(&mut account_info_iter)?;
+    let config = next_account_info(&mut account_info_iter)?;
+    let pyth_fee_vault = next_account_info(&mut account_info_iter)?;
+    let system_program = next_account_info(&mut account_info_iter)?;
+    let requester_program = next_account_info(&mut account_info_iter)?;
+    let entropy_program = next_account_info(&mut account_info_iter)?;
+    let metaplex = match account_info_iter.as_slice() {
+        [] => None,
+        [metadata, metadata_program] => Some((metadata, metadata_program)),
+        _ => return Err(NftTraitsError::InvalidAccount.into()),
+    };
+
+    if !minter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if token_program.key != &spl_token::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let (expected_authority, authority_bump) = authority_pda(program_id, entropy_program.key);
+    let (expected_traits, traits_bump) = traits_pda(program_id, mint.key);
+    if authority.key != &expected_authority || traits_account.key != &expected_traits {
+        return Err(NftTraitsError::InvalidPda.into());
+    }
+    let (expected_collection, _collection_bump) = collection_pda(program_id, entropy_program.key);
+    if collection_account.key != &expected_collection {
+        return Err(NftTraitsError::InvalidPda.into());
+    }
+    if collection_account.owner != program_id {
+        return Err(NftTraitsError::InvalidAccount.into());
+    }
+    let collection = *try_from_bytes::<Collection>(&collection_account.try_borrow_data()?)
+        .map_err(|_| NftTraitsError::InvalidAccount)?;
+    let (expected_provider, _provider_bump) = provider_pda(
+        entropy_program.key,
+        &Pubkey::new_from_array(collection.provider_authority),
+    );
+    if provider.key != &expected_provider {
+        return Err(NftTraitsError::WrongProvider.into());
+    }
+    if let Some((metadata, metadata_program)) = metaplex {
+        if metadata_program.key != &mpl_token_metadata::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if metadata.key != &Metadata::find_pda(mint.key).0 {
+            return Err(NftTraitsError::InvalidPda.into());
+        }
+    }
+
+    if mint.owner != &spl_token::ID {
+        return Err(NftTraitsError::InvalidMint.into());
+    }
+    let mint_state = Mint::unpack(&mint.try_borrow_data()?)?;
+    if mint_state.supply != 0
+        || mint_state.decimals != 0
+        || mint_state.mint_authority != COption::Some(*authority.key)
+    {
+        return Err(NftTraitsError::InvalidMint.into());
+    }
+    if token_account.owner != &spl_token::ID {
+        return Err(NftTraitsError::InvalidAccount.into());
+    }
+    let token_state = TokenAccount::unpack(&token_account.try_borrow_data()?)?;
+    if token_state.mint != *mint.key || token_state.owner != *minter.key {
+        return Err(NftTraitsError::InvalidAccount.into());
+    }
+
+    let authority_seeds: &[&[u8]] = &[
+        AUTHORITY_SEED,
+        entropy_program.key.as_ref(),
+        &[authority_bump],
+    ];
+    invoke_signed(
+        &mint_to(
+            token_program.key,
+            mint.key,
+            token_account.key,
+            authority.key,
+            &[],
+            1,
+        )?,
+        &[
+            mint.clone(),
+            token_account.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        &[authority_seeds],
+    )?;
+
+    // Everything the reveal touches travels as callback accounts; the
+    // Metaplex ones only when there is metadata to rewrite.
+    let mut callback_accounts = vec![callback_meta(traits_account.key, false, true)];
+    if let Some((metadata, metadata_program)) = metaplex {
+        callback_accounts.extend([
+            callback_meta(metadata.key, false, true),
+            callback_meta(authority.key, false, false),
+            callback_meta(metadata_program.key, false, false),
+        ]);
+    }
+    // No TTL: a late reveal is still the only way to reveal the token.
+    let sequence_number = entropy_cpi::request_with_callback(
+        program_id,
+        &RequestAccounts {
+            requester_signer,
+            requester_signer_bump: args.requester_signer_bump,
+            payer: minter,
+            requester_program,
+            request_account,
+            provider,
+            provider_vault,
+            config,
+            pyth_fee_vault,
+            system_program,
+            entropy_program,
+        },
+        args.user_randomness,
+        &RequestParams {
+            compute_unit_limit: args.compute_unit_limit,
+            callback_accounts: &callback_accounts,
+            callback_ix_data: &[CALLBACK_ACTION],
+            ..Default::default()
+        },
+    )?;
+
+    if let Some((metadata, metadata_program)) = metaplex {
+        CreateMetadataAccountV3CpiBuilder::new(metadata_program)
+            .metadata(metadata)
+            .mint(mint)
+            .mint_authority(authority)
+            .payer(minter)
+            .update_authority(authority, true)
+            .system_program(system_program)
+            .data(DataV2 {
+                name: format!("{NAME_PREFIX}{sequence_number}"),
+                symbol: SYMBOL.to_string(),
+                uri: format!("{BASE_URI}/unrevealed.json"),
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            })
+            .is_mutable(true)
+            .invoke_signed(&[authority_seeds])?;
+    }
+
+    invoke_signed(
+        &set_authority(
+            token_program.key,
+            mint.key,
+            None,
+            AuthorityType::MintTokens,
+            authority.key,
+            &[],
+        )?,
+        &[mint.clone(), authority.clone(), token_program.clone()],
+        &[authority_seeds],
+    )?;
+
+    let create_ix = system_instruction::create_account(
+        minter.key,
+        traits_account.key,
+        Rent::get()?.minimum_balance(Traits::LEN),
+        Traits::LEN as u64,
+        program_id,
+    );
+    invoke_signed(
+        &create_ix,
+        &[
+            minter.clone(),
+            traits_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[TRAITS_SEED, mint.key.as_ref(), &[traits_bump]]],
+    )?;
+
+    let mut traits_data = traits_account.try_borrow_mut_data()?;
+    let traits = load_mut(&mut traits_data)?;
+    traits.mint = mint.key.to_bytes();
+    traits.entropy_program = entropy_program.key.to_bytes();
+    if let Some((metadata, _)) = metaplex {
+        traits.metadata = metadata.key.to_bytes();
+    }
+    traits.sequence_number = sequence_number;
+    traits.status = STATUS_PENDING;
+    traits.bump = traits_bump;
+    Ok(())
+}
+
+/// Rolls the traits and, for tokens with metadata, rewrites it to the
+/// revealed name and URI.
+fn process_callback(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (prefix, callback) = parse_callback(data)?;
+    if !prefix.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let entropy_signer = next_account_info(&mut account_info_iter)?;
+    let traits_account = next_account_info(&mut account_info_iter)?;
+
+    if traits_account.owner != program_id || !traits_account.is_writable {
+        return Err(NftTraitsError::InvalidAccount.into());
+    }
+    let mut traits_data = traits_account.try_borrow_mut_data()?;
+    let traits = load_mut(&mut traits_data)?;
+    let entropy_program_id = Pubkey::new_from_array(traits.entropy_program);
+    verify_entropy_signer(entropy_signer, &entropy_program_id)?;
+    if traits.status != STATUS_PENDING {
+        return Err(NftTraitsError::AlreadyRevealed.into());
+    }
+    if callback.sequence_number != traits.sequence_number {
+        return Err(NftTraitsError::InvalidAccount.into());
+    }
+    traits.traits = roll_traits(&callback.random_number, callback.sequence_number);
+    traits.status = STATUS_REVEALED;
+    let traits = *traits;
+    drop(traits_data);
+
+    match account_info_iter.as_slice() {
+        [] if !traits.has_metadata() => {}
+        [metadata, authority, metadata_program] if traits.has_metadata() => {
+            let (expected_authority, authority_bump) =
+                authority_pda(program_id, &entropy_program_id);
+            if metadata.key.to_bytes() != traits.metadata
+                || authority.key != &expected_authority
+                || metadata_program.key != &mpl_token_metadata::ID
+            {
+                return Err(NftTraitsError::InvalidAccount.into());
+            }
+            let (name, uri) = traits.revealed_name_and_uri();
+            UpdateMetadataAccountV2CpiBuilder::new(metadata_program)
+                .metadata(metadata)
+                .update_authority(authority)
+                .data(DataV2 {
+                    name,
+                    symbol: SYMBOL.to_string(),
+                    uri,
+                    seller_fee_basis_points: 0,
+                    creators: None,
+                    collection: None,
+                    uses: None,
+                })
+                .invoke_signed(&[&[
+                    AUTHORITY_SEED,
+                    entropy_program_id.as_ref(),
+                    &[authority_bump],
+                ]])?;
+        }
+        _ => return Err(NftTraitsError::InvalidAccount.into()),
+    }
+    msg!(
+        "Revealed traits {:?} for mint {}",
+        traits.traits,
+        Pubkey::new_from_array(traits.mint)
+    );
+    Ok(())
+}
+
+fn load_mut(data: &mut [u8]) -> Result<&mut Traits, ProgramError> {
+    try_from_bytes_mut::<Traits>(data).map_err(|_| NftTraitsError::InvalidAccount.into())
+}
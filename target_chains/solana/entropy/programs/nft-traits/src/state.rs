@@ -0,0 +1,136 @@
+use {
+    bytemuck::{Pod, Zeroable},
+    entropy_cpi::rand::{EntropyRng, RandomSource},
+    solana_program::pubkey::Pubkey,
+};
+
+pub const AUTHORITY_SEED: &[u8] = b"authority";
+pub const COLLECTION_SEED: &[u8] = b"collection";
+pub const TRAITS_SEED: &[u8] = b"traits";
+
+/// Separates the trait rolls from any other stream drawn from the same
+/// request.
+pub const TRAITS_RNG_SEED: &[u8] = b"nft-traits";
+
+pub const STATUS_PENDING: u8 = 0;
+pub const STATUS_REVEALED: u8 = 1;
+
+pub const NAME_PREFIX: &str = "Entropy Critter #";
+pub const SYMBOL: &str = "CRIT";
+pub const BASE_URI: &str = "https://example.com/critters";
+
+pub const TRAIT_COUNT: usize = 4;
+
+/// One trait category and its values with their relative weights.
+pub struct TraitTable {
+    pub name: &'static str,
+    pub values: &'static [(&'static str, u64)],
+}
+
+pub const TRAIT_TABLES: [TraitTable; TRAIT_COUNT] = [
+    TraitTable {
+        name: "Background",
+        values: &[("Dusk", 40), ("Meadow", 30), ("Ocean", 20), ("Nebula", 10)],
+    },
+    TraitTable {
+        name: "Body",
+        values: &[("Fox", 35), ("Owl", 35), ("Axolotl", 25), ("Dragon", 5)],
+    },
+    TraitTable {
+        name: "Eyes",
+        values: &[("Round", 50), ("Sleepy", 30), ("Laser", 20)],
+    },
+    TraitTable {
+        name: "Hat",
+        values: &[("None", 60), ("Beanie", 25), ("Crown", 15)],
+    },
+];
+
+/// The collection minted through one entropy deployment.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Collection {
+    /// The only provider mints may request from. A provider sees the random
+    /// number before revealing it, so one picked by the minter could reveal
+    /// only rare traits.
+    pub provider_authority: [u8; 32],
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl Collection {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+}
+
+/// Traits of one minted token, a PDA of its mint. `traits` holds an index
+/// into each of [`TRAIT_TABLES`] once revealed.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Traits {
+    pub mint: [u8; 32],
+    pub entropy_program: [u8; 32],
+    /// Metaplex metadata account rewritten on reveal, or all zeros if the
+    /// token was minted without metadata.
+    pub metadata: [u8; 32],
+    pub sequence_number: u64,
+    pub status: u8,
+    pub bump: u8,
+    pub traits: [u8; TRAIT_COUNT],
+    pub _padding: [u8; 2],
+}
+
+impl Traits {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    pub fn has_metadata(&self) -> bool {
+        self.metadata != [0u8; 32]
+    }
+
+    /// Name and URI the metadata carries once the traits are revealed.
+    pub fn revealed_name_and_uri(&self) -> (String, String) {
+        let values: Vec<&str> = TRAIT_TABLES
+            .iter()
+            .zip(self.traits)
+            .map(|(table, index)| table.values[usize::from(index)].0)
+            .collect();
+        (
+            format!("{NAME_PREFIX}{}", self.sequence_number),
+            format!("{BASE_URI}/{}.json", values.join("-").to_lowercase()),
+        )
+    }
+}
+
+/// Rolls every trait from the revealed random number. Weighted by
+/// [`TRAIT_TABLES`], so anyone can recompute a token's traits from the
+/// `RandomnessRevealed` event.
+pub fn roll_traits(random_number: &[u8; 32], sequence_number: u64) -> [u8; TRAIT_COUNT] {
+    let mut rng = EntropyRng::new(random_number, sequence_number, TRAITS_RNG_SEED);
+    let mut traits = [0u8; TRAIT_COUNT];
+    for (trait_index, table) in traits.iter_mut().zip(&TRAIT_TABLES) {
+        let total: u64 = table.values.iter().map(|(_, weight)| weight).sum();
+        let mut roll = rng.u64_in_range(0, total - 1);
+        for (index, (_, weight)) in table.values.iter().enumerate() {
+            if roll < *weight {
+                *trait_index = index as u8;
+                break;
+            }
+            roll -= weight;
+        }
+    }
+    traits
+}
+
+/// Mint authority of placeholder mints and update authority of their
+/// metadata, one per entropy deployment. Tokens revealed through a different
+/// entropy program therefore carry a different update authority.
+pub fn authority_pda(program_id: &Pubkey, entropy_program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY_SEED, entropy_program_id.as_ref()], program_id)
+}
+
+pub fn collection_pda(program_id: &Pubkey, entropy_program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COLLECTION_SEED, entropy_program_id.as_ref()], program_id)
+}
+
+pub fn traits_pda(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TRAITS_SEED, mint.as_ref()], program_id)
+}
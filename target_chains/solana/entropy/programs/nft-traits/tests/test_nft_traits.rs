@@ -0,0 +1,268 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy_test_utils::{
+        submit_tx, submit_tx_expect_err, EntropyFixture, EntropyTestEnv, MockKeeper,
+    },
+    nft_traits::{
+        error::NftTraitsError,
+        instruction::{initialize_collection, mint, MintArgs},
+        state::{authority_pda, roll_traits, traits_pda, Traits, STATUS_PENDING, STATUS_REVEALED},
+    },
+    solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey},
+    solana_program_test::processor,
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        rent::Rent,
+        signature::{Keypair, Signer},
+        system_instruction,
+        transaction::TransactionError,
+    },
+    spl_token::state::{Account as TokenAccount, Mint},
+};
+
+struct Collection {
+    env: EntropyTestEnv,
+    keeper: MockKeeper,
+    program_id: Pubkey,
+}
+
+async fn start() -> Collection {
+    let program_id = Pubkey::new_unique();
+    let mut env = EntropyFixture::new()
+        .with_provider(0, 16)
+        .with_setup(move |program_test| {
+            program_test.add_program(
+                "nft_traits",
+                program_id,
+                processor!(nft_traits::process_instruction),
+            )
+        })
+        .start()
+        .await;
+    let keeper = MockKeeper::new(&env);
+    let payer = env.payer();
+    let initialize_ix = initialize_collection(
+        program_id,
+        env.program_id,
+        payer.pubkey(),
+        env.provider().authority,
+    );
+    submit_tx(&mut env.context.banks_client, &payer, &[initialize_ix], &[]).await;
+    Collection {
+        env,
+        keeper,
+        program_id,
+    }
+}
+
+impl Collection {
+    /// Creates a mint with `mint_authority` and a token account of the payer
+    /// for it; returns `(mint, token_account)`.
+    async fn create_mint(&mut self, mint_authority: Pubkey) -> (Pubkey, Pubkey) {
+        let payer = self.env.payer();
+        let mint = Keypair::new();
+        let token_account = Keypair::new();
+        let rent = Rent::default();
+        let instructions = [
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::ID,
+                &mint.pubkey(),
+                &mint_authority,
+                None,
+                0,
+            )
+            .unwrap(),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &token_account.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::ID,
+                &token_account.pubkey(),
+                &mint.pubkey(),
+                &payer.pubkey(),
+            )
+            .unwrap(),
+        ];
+        submit_tx(
+            &mut self.env.context.banks_client,
+            &payer,
+            &instructions,
+            &[&mint, &token_account],
+        )
+        .await;
+        (mint.pubkey(), token_account.pubkey())
+    }
+
+    fn mint_ix(
+        &self,
+        mint_address: Pubkey,
+        token_account: Pubkey,
+        request: &Keypair,
+    ) -> Instruction {
+        self.mint_ix_through(
+            mint_address,
+            token_account,
+            request,
+            self.env.provider().authority,
+        )
+    }
+
+    fn mint_ix_through(
+        &self,
+        mint_address: Pubkey,
+        token_account: Pubkey,
+        request: &Keypair,
+        provider_authority: Pubkey,
+    ) -> Instruction {
+        mint(
+            self.program_id,
+            self.env.program_id,
+            self.env.payer().pubkey(),
+            mint_address,
+            token_account,
+            request.pubkey(),
+            provider_authority,
+            false,
+            &MintArgs {
+                user_randomness: [7u8; 32],
+                compute_unit_limit: 100_000,
+                requester_signer_bump: 0,
+                _padding: [0; 3],
+            },
+        )
+    }
+
+    async fn unpack<T: Pack>(&mut self, address: Pubkey) -> T {
+        let account = self
+            .env
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        T::unpack(&account.data).unwrap()
+    }
+
+    async fn traits(&mut self, mint_address: Pubkey) -> Traits {
+        let (address, _) = traits_pda(&self.program_id, &mint_address);
+        let account = self
+            .env
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        *try_from_bytes::<Traits>(&account.data).unwrap()
+    }
+}
+
+#[tokio::test]
+async fn test_mint_reveals_traits_in_callback() {
+    let mut collection = start().await;
+    let payer = collection.env.payer();
+    let (authority, _) = authority_pda(&collection.program_id, &collection.env.program_id);
+    let (mint_address, token_account) = collection.create_mint(authority).await;
+
+    let request_account = Keypair::new();
+    let instruction = collection.mint_ix(mint_address, token_account, &request_account);
+    submit_tx(
+        &mut collection.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+
+    // The placeholder is issued before the reveal and the supply is fixed.
+    let mint_state = collection.unpack::<Mint>(mint_address).await;
+    assert_eq!(mint_state.supply, 1);
+    assert_eq!(mint_state.mint_authority, COption::None);
+    let token_state = collection.unpack::<TokenAccount>(token_account).await;
+    assert_eq!(token_state.amount, 1);
+    let traits = collection.traits(mint_address).await;
+    assert_eq!(traits.status, STATUS_PENDING);
+    assert!(!traits.has_metadata());
+
+    let reveals = collection
+        .keeper
+        .reveal_pending(&mut collection.env.context.banks_client, &payer)
+        .await;
+    assert_eq!(reveals.len(), 1);
+    let random_number = reveals[0].random_number().unwrap();
+
+    let traits = collection.traits(mint_address).await;
+    assert_eq!(traits.status, STATUS_REVEALED);
+    assert_eq!(traits.sequence_number, reveals[0].sequence_number);
+    assert_eq!(
+        traits.traits,
+        roll_traits(&random_number, reveals[0].sequence_number)
+    );
+}
+
+#[tokio::test]
+async fn test_mint_rejects_foreign_mint_authority() {
+    let mut collection = start().await;
+    let payer = collection.env.payer();
+    let (mint_address, token_account) = collection.create_mint(payer.pubkey()).await;
+
+    let request_account = Keypair::new();
+    let instruction = collection.mint_ix(mint_address, token_account, &request_account);
+    let err = submit_tx_expect_err(
+        &mut collection.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(NftTraitsError::InvalidMint as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_mint_rejects_other_providers() {
+    let mut collection = start().await;
+    let payer = collection.env.payer();
+    let (authority, _) = authority_pda(&collection.program_id, &collection.env.program_id);
+    let (mint_address, token_account) = collection.create_mint(authority).await;
+
+    let request_account = Keypair::new();
+    let instruction = collection.mint_ix_through(
+        mint_address,
+        token_account,
+        &request_account,
+        Pubkey::new_unique(),
+    );
+    let err = submit_tx_expect_err(
+        &mut collection.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(NftTraitsError::WrongProvider as u32)
+        )
+    );
+    assert_eq!(collection.unpack::<Mint>(mint_address).await.supply, 0);
+}
@@ -0,0 +1,34 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[repr(u32)]
+pub enum RaffleError {
+    #[error("invalid account")]
+    InvalidAccount = 0,
+    #[error("invalid PDA")]
+    InvalidPda = 1,
+    #[error("invalid arguments")]
+    InvalidArgs = 2,
+    #[error("ticket sales are closed")]
+    SalesClosed = 3,
+    #[error("ticket sales are still open")]
+    SalesOpen = 4,
+    #[error("raffle has no room for another entrant")]
+    RaffleFull = 5,
+    #[error("raffle has no tickets")]
+    NoTickets = 6,
+    #[error("draw already in progress")]
+    DrawInProgress = 7,
+    #[error("raffle already drawn")]
+    AlreadyDrawn = 8,
+    #[error("only the raffle creator can draw")]
+    NotCreator = 9,
+    #[error("provider is not the raffle's provider")]
+    WrongProvider = 10,
+}
+
+impl From<RaffleError> for solana_program::program_error::ProgramError {
+    fn from(value: RaffleError) -> Self {
+        solana_program::program_error::ProgramError::Custom(value as u32)
+    }
+}
@@ -0,0 +1,130 @@
+#[allow(deprecated)]
+use {
+    crate::state::raffle_pda,
+    bytemuck::{Pod, Zeroable},
+    entropy::pda::{
+        config_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda, requester_signer_pda,
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+pub const CREATE_RAFFLE_ACTION: u8 = 0;
+pub const BUY_TICKETS_ACTION: u8 = 1;
+pub const DRAW_ACTION: u8 = 2;
+pub const CALLBACK_ACTION: u8 = 0xCB;
+
+/// Data of `CREATE_RAFFLE_ACTION`, after the action byte.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct CreateRaffleArgs {
+    pub raffle_id: u64,
+    pub ticket_price: u64,
+    pub close_slot: u64,
+    /// Provider every draw of the raffle requests entropy from.
+    pub provider_authority: [u8; 32],
+}
+
+/// Data of `BUY_TICKETS_ACTION`, after the action byte.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct BuyTicketsArgs {
+    pub tickets: u32,
+}
+
+/// Data of `DRAW_ACTION`, after the action byte.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct DrawArgs {
+    pub user_randomness: [u8; 32],
+    /// Compute units reserved for the callback that picks the winner.
+    pub compute_unit_limit: u32,
+    /// Bump of the requester signer; filled in by [`draw`].
+    pub requester_signer_bump: u8,
+    pub _padding: [u8; 3],
+}
+
+/// Creates raffle `args.raffle_id` of `creator`, drawn with entropy at
+/// `entropy_program_id`.
+pub fn create_raffle(
+    program_id: Pubkey,
+    entropy_program_id: Pubkey,
+    creator: Pubkey,
+    args: &CreateRaffleArgs,
+) -> Instruction {
+    let (raffle, _) = raffle_pda(&program_id, &creator, args.raffle_id);
+    let mut data = vec![CREATE_RAFFLE_ACTION];
+    data.extend_from_slice(bytemuck::bytes_of(args));
+    Instruction {
+        program_id,
+        data,
+        accounts: vec![
+            AccountMeta::new(creator, true),
+            AccountMeta::new(raffle, false),
+            AccountMeta::new_readonly(entropy_program_id, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    }
+}
+
+pub fn buy_tickets(program_id: Pubkey, raffle: Pubkey, buyer: Pubkey, tickets: u32) -> Instruction {
+    let mut data = vec![BUY_TICKETS_ACTION];
+    data.extend_from_slice(bytemuck::bytes_of(&BuyTicketsArgs { tickets }));
+    Instruction {
+        program_id,
+        data,
+        accounts: vec![
+            AccountMeta::new(buyer, true),
+            AccountMeta::new(raffle, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    }
+}
+
+/// Requests the draw of `raffle`, signed and paid by its `creator` through
+/// the raffle's provider. `request_account` is a fresh keypair that must
+/// sign.
+pub fn draw(
+    program_id: Pubkey,
+    entropy_program_id: Pubkey,
+    raffle: Pubkey,
+    creator: Pubkey,
+    request_account: Pubkey,
+    provider_authority: Pubkey,
+    args: &DrawArgs,
+) -> Instruction {
+    let (requester_signer, requester_signer_bump) =
+        requester_signer_pda(&program_id, &entropy_program_id);
+    let args = DrawArgs {
+        requester_signer_bump,
+        ..*args
+    };
+    let mut data = vec![DRAW_ACTION];
+    data.extend_from_slice(bytemuck::bytes_of(&args));
+    Instruction {
+        program_id,
+        data,
+        accounts: vec![
+            AccountMeta::new(creator, true),
+            AccountMeta::new(raffle, false),
+            AccountMeta::new_readonly(requester_signer, false),
+            AccountMeta::new(request_account, true),
+            AccountMeta::new(
+                provider_pda(&entropy_program_id, &provider_authority).0,
+                false,
+            ),
+            AccountMeta::new(
+                provider_vault_pda(&entropy_program_id, &provider_authority).0,
+                false,
+            ),
+            AccountMeta::new_readonly(config_pda(&entropy_program_id).0, false),
+            AccountMeta::new(pyth_fee_vault_pda(&entropy_program_id).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new_readonly(entropy_program_id, false),
+        ],
+    }
+}
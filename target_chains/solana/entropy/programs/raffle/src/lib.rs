@@ -0,0 +1,19 @@
+//! Example entropy consumer: a raffle drawn after ticket sales close.
+//!
+//! Entrants buy tickets into a raffle PDA that holds the pot. Once
+//! `close_slot` passes the creator requests the draw from the provider picked
+//! at creation; entropy calls back with the raffle and every entrant's
+//! account, and the callback maps the random number onto a ticket and pays
+//! the pot to its holder. A draw whose reveal never lands can be requested
+//! again after
+//! [`DRAW_TTL_SLOTS`](state::DRAW_TTL_SLOTS).
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+pub use processor::process_instruction;
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
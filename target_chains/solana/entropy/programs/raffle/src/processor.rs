@@ -0,0 +1,334 @@
+#[allow(deprecated)]
+use {
+    crate::{
+        error::RaffleError,
+        instruction::{
+            BuyTicketsArgs, CreateRaffleArgs, DrawArgs, BUY_TICKETS_ACTION, CALLBACK_ACTION,
+            CREATE_RAFFLE_ACTION, DRAW_ACTION,
+        },
+        state::{
+            raffle_pda, Raffle, DRAW_TTL_SLOTS, MAX_ENTRANTS, RAFFLE_SEED, STATUS_DRAWING,
+            STATUS_DRAWN, STATUS_OPEN,
+        },
+    },
+    bytemuck::{try_from_bytes, try_from_bytes_mut},
+    entropy::pda::provider_pda,
+    entropy_cpi::{
+        callback_meta, parse_callback, verify_entropy_signer, RequestAccounts, RequestParams,
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program::{invoke, invoke_signed},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+        sysvar::{clock::Clock, Sysvar},
+    },
+};
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (action, data) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    match *action {
+        CREATE_RAFFLE_ACTION => process_create_raffle(program_id, accounts, data),
+        BUY_TICKETS_ACTION => process_buy_tickets(program_id, accounts, data),
+        DRAW_ACTION => process_draw(program_id, accounts, data),
+        CALLBACK_ACTION => process_callback(program_id, accounts, data),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn process_create_raffle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = try_from_bytes::<CreateRaffleArgs>(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let mut account_info_iter = accounts.iter();
+    let creator = next_account_info(&mut account_info_iter)?;
+    let raffle_account = next_account_info(&mut account_info_iter)?;
+    let entropy_program = next_account_info(&mut account_info_iter)?;
+    let system_program = next_account_info(&mut account_info_iter)?;
+
+    if !creator.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if args.ticket_price == 0 || args.close_slot <= Clock::get()?.slot {
+        return Err(RaffleError::InvalidArgs.into());
+    }
+    let (expected_raffle, bump) = raffle_pda(program_id, creator.key, args.raffle_id);
+    if raffle_account.key != &expected_raffle {
+        return Err(RaffleError::InvalidPda.into());
+    }
+
+    let create_ix = system_instruction::create_account(
+        creator.key,
+        raffle_account.key,
+        Rent::get()?.minimum_balance(Raffle::LEN),
+        Raffle::LEN as u64,
+        program_id,
+    );
+    invoke_signed(
+        &create_ix,
+        &[
+            creator.clone(),
+            raffle_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[
+            RAFFLE_SEED,
+            creator.key.as_ref(),
+            &args.raffle_id.to_le_bytes(),
+            &[bump],
+        ]],
+    )?;
+
+    let mut raffle_data = raffle_account.try_borrow_mut_data()?;
+    let raffle = load_mut(&mut raffle_data)?;
+    raffle.creator = creator.key.to_bytes();
+    raffle.entropy_program = entropy_program.key.to_bytes();
+    raffle.provider_authority = args.provider_authority;
+    raffle.raffle_id = args.raffle_id;
+    raffle.ticket_price = args.ticket_price;
+    raffle.close_slot = args.close_slot;
+    raffle.status = STATUS_OPEN;
+    raffle.bump = bump;
+    Ok(())
+}
+
+fn process_buy_tickets(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args =
+        try_from_bytes::<BuyTicketsArgs>(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    if args.tickets == 0 {
+        return Err(RaffleError::InvalidArgs.into());
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let buyer = next_account_info(&mut account_info_iter)?;
+    let raffle_account = next_account_info(&mut account_info_iter)?;
+    let system_program = next_account_info(&mut account_info_iter)?;
+
+    if !buyer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_raffle_account(program_id, raffle_account)?;
+
+    let cost = {
+        let mut raffle_data = raffle_account.try_borrow_mut_data()?;
+        let raffle = load_mut(&mut raffle_data)?;
+        if raffle.status != STATUS_OPEN || Clock::get()?.slot >= raffle.close_slot {
+            return Err(RaffleError::SalesClosed.into());
+        }
+
+        let buyer_bytes = buyer.key.to_bytes();
+        let index = match raffle.entrants().iter().position(|e| *e == buyer_bytes) {
+            Some(index) => index,
+            None => {
+                let index = usize::from(raffle.entrant_count);
+                if index == MAX_ENTRANTS {
+                    return Err(RaffleError::RaffleFull.into());
+                }
+                raffle.entrants[index] = buyer_bytes;
+                raffle.entrant_count += 1;
+                index
+            }
+        };
+        raffle.tickets[index] = raffle.tickets[index]
+            .checked_add(args.tickets)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        raffle.total_tickets = raffle
+            .total_tickets
+            .checked_add(args.tickets)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        raffle
+            .ticket_price
+            .checked_mul(u64::from(args.tickets))
+            .ok_or(ProgramError::ArithmeticOverflow)?
+    };
+
+    invoke(
+        &system_instruction::transfer(buyer.key, raffle_account.key, cost),
+        &[
+            buyer.clone(),
+            raffle_account.clone(),
+            system_program.clone(),
+        ],
+    )
+}
+
+/// Requests entropy for a closed raffle from its provider. Only the creator
+/// may draw, so an entrant can neither time the draw nor pick its user
+/// randomness. The callback receives the raffle and every entrant's account,
+/// in entrant order.
+fn process_draw(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let args =
+        try_from_bytes::<DrawArgs>(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    // Entropy skips the callback when the limit is zero.
+    if args.compute_unit_limit == 0 {
+        return Err(RaffleError::InvalidArgs.into());
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let creator = next_account_info(&mut account_info_iter)?;
+    let raffle_account = next_account_info(&mut account_info_iter)?;
+    let requester_signer = next_account_info(&mut account_info_iter)?;
+    let request_account = next_account_info(&mut account_info_iter)?;
+    let provider = next_account_info(&mut account_info_iter)?;
+    let provider_vault = next_account_info(&mut account_info_iter)?;
+    let config = next_account_info(&mut account_info_iter)?;
+    let pyth_fee_vault = next_account_info(&mut account_info_iter)?;
+    let system_program = next_account_info(&mut account_info_iter)?;
+    let requester_program = next_account_info(&mut account_info_iter)?;
+    let entropy_program = next_account_info(&mut account_info_iter)?;
+
+    check_raffle_account(program_id, raffle_account)?;
+    let raffle = *load(&raffle_account.try_borrow_data()?)?;
+    if entropy_program.key.to_bytes() != raffle.entropy_program {
+        return Err(RaffleError::InvalidAccount.into());
+    }
+    if !creator.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if creator.key.to_bytes() != raffle.creator {
+        return Err(RaffleError::NotCreator.into());
+    }
+    let (expected_provider, _provider_bump) = provider_pda(
+        entropy_program.key,
+        &Pubkey::new_from_array(raffle.provider_authority),
+    );
+    if provider.key != &expected_provider {
+        return Err(RaffleError::WrongProvider.into());
+    }
+
+    let slot = Clock::get()?.slot;
+    match raffle.status {
+        STATUS_OPEN if slot < raffle.close_slot => return Err(RaffleError::SalesOpen.into()),
+        STATUS_OPEN => {}
+        // A draw whose reveal can no longer land is replaced.
+        STATUS_DRAWING if raffle.is_draw_expired(slot) => {}
+        STATUS_DRAWING => return Err(RaffleError::DrawInProgress.into()),
+        _ => return Err(RaffleError::AlreadyDrawn.into()),
+    }
+    if raffle.total_tickets == 0 {
+        return Err(RaffleError::NoTickets.into());
+    }
+
+    let mut callback_accounts = Vec::with_capacity(1 + raffle.entrants().len());
+    callback_accounts.push(callback_meta(raffle_account.key, false, true));
+    for entrant in raffle.entrants() {
+        callback_accounts.push(callback_meta(
+            &Pubkey::new_from_array(*entrant),
+            false,
+            true,
+        ));
+    }
+
+    let sequence_number = entropy_cpi::request_with_callback(
+        program_id,
+        &RequestAccounts {
+            requester_signer,
+            requester_signer_bump: args.requester_signer_bump,
+            payer: creator,
+            requester_program,
+            request_account,
+            provider,
+            provider_vault,
+            config,
+            pyth_fee_vault,
+            system_program,
+            entropy_program,
+        },
+        args.user_randomness,
+        &RequestParams {
+            compute_unit_limit: args.compute_unit_limit,
+            ttl_slots: DRAW_TTL_SLOTS,
+            callback_accounts: &callback_accounts,
+            callback_ix_data: &[CALLBACK_ACTION],
+            ..Default::default()
+        },
+    )?;
+
+    let mut raffle_data = raffle_account.try_borrow_mut_data()?;
+    let raffle = load_mut(&mut raffle_data)?;
+    raffle.status = STATUS_DRAWING;
+    raffle.sequence_number = sequence_number;
+    raffle.draw_slot = slot;
+    Ok(())
+}
+
+/// Maps the random number onto a ticket and pays the whole pot to its
+/// holder.
+fn process_callback(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (prefix, callback) = parse_callback(data)?;
+    if !prefix.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut account_info_iter = accounts.iter();
+    let entropy_signer = next_account_info(&mut account_info_iter)?;
+    let raffle_account = next_account_info(&mut account_info_iter)?;
+    let entrant_accounts = account_info_iter.as_slice();
+
+    check_raffle_account(program_id, raffle_account)?;
+    let mut raffle_data = raffle_account.try_borrow_mut_data()?;
+    let raffle = load_mut(&mut raffle_data)?;
+    verify_entropy_signer(
+        entropy_signer,
+        &Pubkey::new_from_array(raffle.entropy_program),
+    )?;
+    if raffle.status != STATUS_DRAWING || callback.sequence_number != raffle.sequence_number {
+        return Err(RaffleError::InvalidAccount.into());
+    }
+    if entrant_accounts.len() != raffle.entrants().len() {
+        return Err(RaffleError::InvalidAccount.into());
+    }
+
+    let winner = raffle.winning_entrant(&callback.random_number);
+    let winner_account = &entrant_accounts[winner];
+    if winner_account.key.to_bytes() != raffle.entrants[winner] || !winner_account.is_writable {
+        return Err(RaffleError::InvalidAccount.into());
+    }
+    raffle.status = STATUS_DRAWN;
+    raffle.winner = winner as u8;
+    drop(raffle_data);
+
+    let rent = Rent::get()?.minimum_balance(Raffle::LEN);
+    let pot = raffle_account.lamports().saturating_sub(rent);
+    let winner_lamports = winner_account
+        .lamports()
+        .checked_add(pot)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **raffle_account.try_borrow_mut_lamports()? = rent;
+    **winner_account.try_borrow_mut_lamports()? = winner_lamports;
+    msg!("Raffle won by {} ({} lamports)", winner_account.key, pot);
+    Ok(())
+}
+
+fn check_raffle_account(program_id: &Pubkey, raffle_account: &AccountInfo) -> ProgramResult {
+    if raffle_account.owner != program_id || !raffle_account.is_writable {
+        return Err(RaffleError::InvalidAccount.into());
+    }
+    Ok(())
+}
+
+fn load(data: &[u8]) -> Result<&Raffle, ProgramError> {
+    try_from_bytes::<Raffle>(data).map_err(|_| RaffleError::InvalidAccount.into())
+}
+
+fn load_mut(data: &mut [u8]) -> Result<&mut Raffle, ProgramError> {
+    try_from_bytes_mut::<Raffle>(data).map_err(|_| RaffleError::InvalidAccount.into())
+}
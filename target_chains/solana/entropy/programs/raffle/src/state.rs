@@ -0,0 +1,89 @@
+use {
+    bytemuck::{Pod, Zeroable},
+    entropy::constants::MAX_CALLBACK_ACCOUNTS,
+    entropy_cpi::rand,
+    solana_program::pubkey::Pubkey,
+};
+
+pub const RAFFLE_SEED: &[u8] = b"raffle";
+
+/// Every entrant is a callback account so the callback can pay the winner;
+/// the raffle itself takes the remaining slot.
+pub const MAX_ENTRANTS: usize = MAX_CALLBACK_ACCOUNTS - 1;
+
+/// Slots a draw waits for its reveal before it can be requested again.
+/// Draws request entropy with the same TTL, so a replaced draw can never
+/// also be revealed.
+pub const DRAW_TTL_SLOTS: u32 = 300;
+
+pub const STATUS_OPEN: u8 = 0;
+pub const STATUS_DRAWING: u8 = 1;
+pub const STATUS_DRAWN: u8 = 2;
+
+/// A raffle and its pot: the account's lamports above rent are the ticket
+/// sales, paid out in full to the winner.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Raffle {
+    pub creator: [u8; 32],
+    pub entropy_program: [u8; 32],
+    /// The only provider the raffle is drawn with. A provider sees the random
+    /// number before revealing it, so draws pinned to the creator's choice
+    /// cannot be steered by an entrant's own provider.
+    pub provider_authority: [u8; 32],
+    pub raffle_id: u64,
+    pub ticket_price: u64,
+    /// First slot at which tickets can no longer be bought and the draw can
+    /// be requested.
+    pub close_slot: u64,
+    /// Sequence number and slot of the latest draw request.
+    pub sequence_number: u64,
+    pub draw_slot: u64,
+    pub total_tickets: u32,
+    pub entrant_count: u8,
+    pub status: u8,
+    /// Index into `entrants` once drawn.
+    pub winner: u8,
+    pub bump: u8,
+    pub entrants: [[u8; 32]; MAX_ENTRANTS],
+    /// Tickets held by the entrant at the same index.
+    pub tickets: [u32; MAX_ENTRANTS],
+    pub _padding: [u8; 4],
+}
+
+impl Raffle {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    pub fn entrants(&self) -> &[[u8; 32]] {
+        &self.entrants[..usize::from(self.entrant_count)]
+    }
+
+    /// Matches entropy's expiry rule for the draw request.
+    pub fn is_draw_expired(&self, slot: u64) -> bool {
+        slot > self.draw_slot.saturating_add(u64::from(DRAW_TTL_SLOTS))
+    }
+
+    /// Index of the entrant holding the winning ticket. Tickets are numbered
+    /// in entrant order, so anyone can recompute the result from the
+    /// `RandomnessRevealed` event. Panics if no tickets were sold.
+    pub fn winning_entrant(&self, random_number: &[u8; 32]) -> usize {
+        let mut ticket = rand::u64_in_range(random_number, 0, u64::from(self.total_tickets) - 1);
+        for (index, tickets) in self.tickets[..usize::from(self.entrant_count)]
+            .iter()
+            .enumerate()
+        {
+            if ticket < u64::from(*tickets) {
+                return index;
+            }
+            ticket -= u64::from(*tickets);
+        }
+        unreachable!("total_tickets is the sum of tickets")
+    }
+}
+
+pub fn raffle_pda(program_id: &Pubkey, creator: &Pubkey, raffle_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RAFFLE_SEED, creator.as_ref(), &raffle_id.to_le_bytes()],
+        program_id,
+    )
+}
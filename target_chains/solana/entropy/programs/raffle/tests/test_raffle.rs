@@ -0,0 +1,340 @@
+use {
+    bytemuck::try_from_bytes,
+    entropy::error::EntropyError,
+    entropy_test_utils::{
+        submit_tx, submit_tx_expect_err, submit_tx_with_metadata, EntropyFixture, EntropyTestEnv,
+        MockKeeper,
+    },
+    raffle::{
+        error::RaffleError,
+        instruction::{buy_tickets, create_raffle, draw, CreateRaffleArgs, DrawArgs},
+        state::{raffle_pda, Raffle, DRAW_TTL_SLOTS, STATUS_DRAWING, STATUS_DRAWN, STATUS_OPEN},
+    },
+    solana_program::{clock::Clock, pubkey::Pubkey, system_instruction},
+    solana_program_test::processor,
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+const TICKET_PRICE: u64 = 10_000_000;
+const SALES_SLOTS: u64 = 20;
+
+struct Game {
+    env: EntropyTestEnv,
+    keeper: MockKeeper,
+    program_id: Pubkey,
+    raffle: Pubkey,
+    entrants: Vec<Keypair>,
+}
+
+/// Starts entropy with a free provider, creates a raffle closing
+/// `SALES_SLOTS` from now and funds three entrants.
+async fn start() -> Game {
+    let program_id = Pubkey::new_unique();
+    let mut env = EntropyFixture::new()
+        .with_provider(0, 16)
+        .with_setup(move |program_test| {
+            program_test.add_program(
+                "raffle",
+                program_id,
+                processor!(raffle::process_instruction),
+            )
+        })
+        .start()
+        .await;
+    let keeper = MockKeeper::new(&env);
+    let payer = env.payer();
+    let slot = env
+        .context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .slot;
+    let entrants: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+
+    let mut instructions = vec![create_raffle(
+        program_id,
+        env.program_id,
+        payer.pubkey(),
+        &CreateRaffleArgs {
+            raffle_id: 1,
+            ticket_price: TICKET_PRICE,
+            close_slot: slot + SALES_SLOTS,
+            provider_authority: env.provider().authority.to_bytes(),
+        },
+    )];
+    for entrant in &entrants {
+        instructions.push(system_instruction::transfer(
+            &payer.pubkey(),
+            &entrant.pubkey(),
+            1_000_000_000,
+        ));
+    }
+    submit_tx(&mut env.context.banks_client, &payer, &instructions, &[]).await;
+
+    let (raffle, _) = raffle_pda(&program_id, &payer.pubkey(), 1);
+    Game {
+        env,
+        keeper,
+        program_id,
+        raffle,
+        entrants,
+    }
+}
+
+impl Game {
+    async fn buy(&mut self, entrant: usize, tickets: u32) -> Result<(), TransactionError> {
+        let payer = self.env.payer();
+        let buyer = self.entrants[entrant].insecure_clone();
+        let instruction = buy_tickets(self.program_id, self.raffle, buyer.pubkey(), tickets);
+        let (result, _) = submit_tx_with_metadata(
+            &mut self.env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&buyer],
+        )
+        .await;
+        result
+    }
+
+    fn draw_ix(&self, request_account: &Keypair) -> Instruction {
+        self.draw_ix_as(
+            request_account,
+            self.env.payer().pubkey(),
+            self.env.provider().authority,
+        )
+    }
+
+    fn draw_ix_as(
+        &self,
+        request_account: &Keypair,
+        drawer: Pubkey,
+        provider_authority: Pubkey,
+    ) -> Instruction {
+        draw(
+            self.program_id,
+            self.env.program_id,
+            self.raffle,
+            drawer,
+            request_account.pubkey(),
+            provider_authority,
+            &DrawArgs {
+                user_randomness: [6u8; 32],
+                compute_unit_limit: 200_000,
+                requester_signer_bump: 0,
+                _padding: [0; 3],
+            },
+        )
+    }
+
+    async fn raffle(&mut self) -> (u64, Raffle) {
+        let account = self
+            .env
+            .context
+            .banks_client
+            .get_account(self.raffle)
+            .await
+            .unwrap()
+            .unwrap();
+        (
+            account.lamports,
+            *try_from_bytes::<Raffle>(&account.data).unwrap(),
+        )
+    }
+
+    async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.env
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap()
+            .lamports
+    }
+
+    async fn close_sales(&mut self) {
+        let (_, raffle) = self.raffle().await;
+        self.env.context.warp_to_slot(raffle.close_slot).unwrap();
+    }
+}
+
+fn custom(code: u32) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code))
+}
+
+#[tokio::test]
+async fn test_draw_pays_pot_to_winning_ticket() {
+    let mut game = start().await;
+    let payer = game.env.payer();
+    game.buy(0, 1).await.unwrap();
+    game.buy(1, 3).await.unwrap();
+    game.buy(2, 2).await.unwrap();
+    // Buying again adds to the existing entry.
+    game.buy(0, 2).await.unwrap();
+
+    let (_, raffle) = game.raffle().await;
+    assert_eq!(raffle.entrant_count, 3);
+    assert_eq!(raffle.total_tickets, 8);
+    assert_eq!(raffle.tickets[..3], [3, 3, 2]);
+
+    let request_account = Keypair::new();
+    let instruction = game.draw_ix(&request_account);
+    let err = submit_tx_expect_err(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(err, custom(RaffleError::SalesOpen as u32));
+
+    game.close_sales().await;
+    assert_eq!(
+        game.buy(2, 1).await.unwrap_err(),
+        custom(RaffleError::SalesClosed as u32)
+    );
+
+    let (pot_before, _) = game.raffle().await;
+    let mut entrants_before = Vec::new();
+    for entrant in 0..3 {
+        let address = game.entrants[entrant].pubkey();
+        entrants_before.push(game.lamports(address).await);
+    }
+
+    let request_account = Keypair::new();
+    let instruction = game.draw_ix(&request_account);
+    let reveals = game
+        .keeper
+        .submit(
+            &mut game.env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await
+        .unwrap();
+    let random_number = reveals[0].random_number().unwrap();
+
+    let (rent, raffle) = game.raffle().await;
+    let winner = raffle.winning_entrant(&random_number);
+    assert_eq!(raffle.status, STATUS_DRAWN);
+    assert_eq!(usize::from(raffle.winner), winner);
+    assert_eq!(pot_before - rent, 8 * TICKET_PRICE);
+    for (entrant, before) in entrants_before.into_iter().enumerate() {
+        let address = game.entrants[entrant].pubkey();
+        let expected = if entrant == winner {
+            before + 8 * TICKET_PRICE
+        } else {
+            before
+        };
+        assert_eq!(game.lamports(address).await, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_expired_draw_can_be_requested_again() {
+    let mut game = start().await;
+    let payer = game.env.payer();
+    game.buy(0, 1).await.unwrap();
+    game.buy(1, 1).await.unwrap();
+    game.close_sales().await;
+
+    let first_request = Keypair::new();
+    let instruction = game.draw_ix(&first_request);
+    submit_tx(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&first_request],
+    )
+    .await;
+    let (_, raffle) = game.raffle().await;
+    assert_eq!(raffle.status, STATUS_DRAWING);
+
+    let second_request = Keypair::new();
+    let instruction = game.draw_ix(&second_request);
+    let err = submit_tx_expect_err(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&second_request],
+    )
+    .await;
+    assert_eq!(err, custom(RaffleError::DrawInProgress as u32));
+
+    game.env
+        .context
+        .warp_to_slot(raffle.draw_slot + u64::from(DRAW_TTL_SLOTS) + 1)
+        .unwrap();
+    let instruction = game.draw_ix(&second_request);
+    let reveals = game
+        .keeper
+        .submit(
+            &mut game.env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&second_request],
+        )
+        .await
+        .unwrap();
+
+    // The stale draw is refused by entropy; the replacement settles the raffle.
+    let first = reveals
+        .iter()
+        .find(|reveal| reveal.request_account == first_request.pubkey())
+        .unwrap();
+    assert_eq!(
+        first.result.as_ref().err(),
+        Some(&custom(EntropyError::RequestExpired as u32))
+    );
+    let second = reveals
+        .iter()
+        .find(|reveal| reveal.request_account == second_request.pubkey())
+        .unwrap();
+    assert!(second.result.is_ok());
+    assert_eq!(game.raffle().await.1.status, STATUS_DRAWN);
+}
+
+#[tokio::test]
+async fn test_draw_rejects_other_drawers_and_providers() {
+    let mut game = start().await;
+    let payer = game.env.payer();
+    game.buy(0, 1).await.unwrap();
+    game.buy(1, 1).await.unwrap();
+    game.close_sales().await;
+
+    // An entrant cannot draw, even through the raffle's provider.
+    let entrant = game.entrants[0].insecure_clone();
+    let request_account = Keypair::new();
+    let instruction = game.draw_ix_as(
+        &request_account,
+        entrant.pubkey(),
+        game.env.provider().authority,
+    );
+    let err = submit_tx_expect_err(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&entrant, &request_account],
+    )
+    .await;
+    assert_eq!(err, custom(RaffleError::NotCreator as u32));
+
+    // Nor can the creator switch to another provider.
+    let request_account = Keypair::new();
+    let instruction = game.draw_ix_as(&request_account, payer.pubkey(), Pubkey::new_unique());
+    let err = submit_tx_expect_err(
+        &mut game.env.context.banks_client,
+        &payer,
+        &[instruction],
+        &[&request_account],
+    )
+    .await;
+    assert_eq!(err, custom(RaffleError::WrongProvider as u32));
+    assert_eq!(game.raffle().await.1.status, STATUS_OPEN);
+}
@@ -1,23 +1,41 @@
+#[allow(deprecated)]
 use {
-    bytemuck::{Pod, Zeroable, bytes_of, try_from_bytes},
+    bytemuck::{Pod, Zeroable},
     entropy::{
-        constants::{ENTROPY_SIGNER_SEED, REQUESTER_SIGNER_SEED},
-        instruction::{EntropyInstruction, RequestArgs},
+        instruction::{RequestArgs, parse_args},
+        pda::requester_signer_pda,
+    },
+    entropy_cpi::{
+        AggregateRequestAccounts, RequestAccounts, RequestParams, callback_meta, parse_callback,
+        parse_callback_with_contributions, parse_requester_signer_bump,
+        rand::{self, EntropyRng, RandomSource},
+        verify_entropy_signer,
     },
     solana_program::{
         account_info::{AccountInfo, next_account_info},
+        compute_units::sol_remaining_compute_units,
         entrypoint::ProgramResult,
         instruction::{AccountMeta, Instruction},
         msg,
         program::invoke_signed,
         program_error::ProgramError,
         pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+        sysvar::Sysvar,
     },
 };
 
 pub const REQUEST_ACTION: u8 = 0;
 pub const REQUEST_WITH_CALLBACK_ACTION: u8 = 1;
+pub const REQUEST_WITH_PDA_CALLBACK_ACTION: u8 = 2;
+pub const REQUEST_V2_ACTION: u8 = 3;
+pub const REQUEST_AGGREGATE_ACTION: u8 = 4;
 pub const CALLBACK_ACTION: u8 = 0xCB;
+/// Callback for requests made with `include_contributions`.
+pub const CALLBACK_WITH_CONTRIBUTIONS_ACTION: u8 = 0xCC;
+pub const CALLBACK_STATE_SEED: &[u8] = b"callback";
+pub const RNG_CONSUMER_SEED: &[u8] = b"simple-requester";
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -27,10 +45,85 @@ pub struct CallbackState {
     pub random_number: [u8; 32],
     pub called: u8,
     pub _padding: [u8; 7],
+    /// First value of the callback's `EntropyRng` stream, keyed with
+    /// [`RNG_CONSUMER_SEED`]; clients recompute it off-chain.
+    pub derived_value: u64,
+    /// Entropy signer the callback was invoked with, as checked by
+    /// `verify_entropy_signer`.
+    pub entropy_signer: [u8; 32],
+    /// Compute units left when the callback started, to compare against the
+    /// request's `compute_unit_limit`.
+    pub remaining_compute_units: u64,
+    /// Set by [`CALLBACK_WITH_CONTRIBUTIONS_ACTION`]; zero otherwise.
+    pub user_contribution: [u8; 32],
+    pub provider_contribution: [u8; 32],
 }
 
 pub const CALLBACK_STATE_LEN: usize = core::mem::size_of::<CallbackState>();
 
+/// Data of `REQUEST_WITH_PDA_CALLBACK_ACTION`, after the action byte.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct RequestWithPdaCallbackArgs {
+    pub user_randomness: [u8; 32],
+    pub compute_unit_limit: u32,
+    pub ttl_slots: u32,
+    /// Bump of the requester signer; filled in by [`request_with_pda_callback`].
+    pub requester_signer_bump: u8,
+    pub _padding: [u8; 3],
+}
+
+/// The callback state for one request:
+/// `[CALLBACK_STATE_SEED, provider, sequence_number (u64 LE)]`, where
+/// `provider` is the entropy provider account the request goes to. Each
+/// provider numbers its requests from zero, so the sequence number alone
+/// would give requests to two providers the same address. Clients predict
+/// `sequence_number` from the provider's next sequence number.
+pub fn callback_state_pda(
+    program_id: &Pubkey,
+    provider: &Pubkey,
+    sequence_number: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            CALLBACK_STATE_SEED,
+            provider.as_ref(),
+            &sequence_number.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Builds `REQUEST_WITH_PDA_CALLBACK_ACTION` from the accounts of an entropy
+/// `RequestWithCallback` instruction for this program; the callback accounts
+/// and data are built on-chain.
+pub fn request_with_pda_callback(
+    entropy_ix: &Instruction,
+    callback_state: Pubkey,
+    args: &RequestWithPdaCallbackArgs,
+) -> Instruction {
+    let requester_program = entropy_ix.accounts[2].pubkey;
+    let (_, requester_signer_bump) =
+        requester_signer_pda(&requester_program, &entropy_ix.program_id);
+    let args = RequestWithPdaCallbackArgs {
+        requester_signer_bump,
+        ..*args
+    };
+    let mut data = vec![REQUEST_WITH_PDA_CALLBACK_ACTION];
+    data.extend_from_slice(bytemuck::bytes_of(&args));
+
+    let mut accounts = entropy_ix.accounts.clone();
+    accounts[0].is_signer = false;
+    accounts.push(AccountMeta::new_readonly(entropy_ix.program_id, false));
+    accounts.push(AccountMeta::new(callback_state, false));
+
+    Instruction {
+        program_id: requester_program,
+        data,
+        accounts,
+    }
+}
+
 #[cfg(not(feature = "no-entrypoint"))]
 solana_program::entrypoint!(process_instruction);
 
@@ -48,7 +141,15 @@ pub fn process_instruction(
         REQUEST_WITH_CALLBACK_ACTION => {
             process_request_with_callback(program_id, accounts, &data[1..])
         }
-        CALLBACK_ACTION => process_callback(program_id, accounts, &data[1..]),
+        REQUEST_WITH_PDA_CALLBACK_ACTION => {
+            process_request_with_pda_callback(program_id, accounts, &data[1..])
+        }
+        REQUEST_V2_ACTION => process_request_v2(program_id, accounts, &data[1..]),
+        REQUEST_AGGREGATE_ACTION => process_request_aggregate(program_id, accounts, &data[1..]),
+        CALLBACK_ACTION => process_callback(program_id, accounts, &data[1..], false),
+        CALLBACK_WITH_CONTRIBUTIONS_ACTION => {
+            process_callback(program_id, accounts, &data[1..], true)
+        }
         _ => {
             if data.len() == core::mem::size_of::<RequestArgs>() {
                 process_request(program_id, accounts, data)
@@ -59,75 +160,83 @@ pub fn process_instruction(
     }
 }
 
-fn process_request(
+/// Reads the accounts forwarded to entropy. Callback requests carry the
+/// callback program (this program) before the entropy program.
+fn request_accounts<'a, 'info>(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    data: &[u8],
-) -> ProgramResult {
-    let args = try_from_bytes::<RequestArgs>(data)
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
-
-    let mut account_info_iter = accounts.iter();
-    let requester_signer = next_account_info(&mut account_info_iter)?;
-    let payer = next_account_info(&mut account_info_iter)?;
-    let requester_program = next_account_info(&mut account_info_iter)?;
-    let request_account = next_account_info(&mut account_info_iter)?;
-    let provider_account = next_account_info(&mut account_info_iter)?;
-    let provider_vault = next_account_info(&mut account_info_iter)?;
-    let config_account = next_account_info(&mut account_info_iter)?;
-    let pyth_fee_vault = next_account_info(&mut account_info_iter)?;
-    let system_program_account = next_account_info(&mut account_info_iter)?;
-    let entropy_program = next_account_info(&mut account_info_iter)?;
-
-    if requester_program.key != program_id {
-        return Err(ProgramError::InvalidArgument);
+    account_info_iter: &mut core::slice::Iter<'a, AccountInfo<'info>>,
+    requester_signer_bump: u8,
+    with_callback: bool,
+) -> Result<RequestAccounts<'a, 'info>, ProgramError> {
+    let requester_signer = next_account_info(account_info_iter)?;
+    let payer = next_account_info(account_info_iter)?;
+    let requester_program = next_account_info(account_info_iter)?;
+    let request_account = next_account_info(account_info_iter)?;
+    let provider = next_account_info(account_info_iter)?;
+    let provider_vault = next_account_info(account_info_iter)?;
+    let config = next_account_info(account_info_iter)?;
+    let pyth_fee_vault = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    if with_callback {
+        let callback_program = next_account_info(account_info_iter)?;
+        if callback_program.key != program_id {
+            return Err(ProgramError::InvalidArgument);
+        }
     }
+    let entropy_program = next_account_info(account_info_iter)?;
 
-    let mut entropy_data = Vec::with_capacity(8 + core::mem::size_of::<RequestArgs>());
-    entropy_data.extend_from_slice(&EntropyInstruction::Request.discriminator());
-    entropy_data.extend_from_slice(bytes_of(args));
-
-    let entropy_ix = Instruction {
-        program_id: *entropy_program.key,
-        data: entropy_data,
-        accounts: vec![
-            AccountMeta::new_readonly(*requester_signer.key, true),
-            AccountMeta::new(*payer.key, true),
-            AccountMeta::new_readonly(*requester_program.key, false),
-            AccountMeta::new(*request_account.key, true),
-            AccountMeta::new(*provider_account.key, false),
-            AccountMeta::new(*provider_vault.key, false),
-            AccountMeta::new_readonly(*config_account.key, false),
-            AccountMeta::new(*pyth_fee_vault.key, false),
-            AccountMeta::new_readonly(*system_program_account.key, false),
-        ],
-    };
+    Ok(RequestAccounts {
+        requester_signer,
+        requester_signer_bump,
+        payer,
+        requester_program,
+        request_account,
+        provider,
+        provider_vault,
+        config,
+        pyth_fee_vault,
+        system_program,
+        entropy_program,
+    })
+}
 
-    let (expected_signer, bump) = Pubkey::find_program_address(
-        &[REQUESTER_SIGNER_SEED, entropy_program.key.as_ref()],
+/// Reads the accounts of a forwarded `RequestWithCallback` or `RequestV2`,
+/// which carry the request's callback account list, if it has one, between
+/// the callback program and the entropy program. The requester signer bump
+/// is the one in the forwarded `entropy_data`.
+fn callback_request_accounts<'a, 'info>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+    entropy_data: &[u8],
+) -> Result<(RequestAccounts<'a, 'info>, Option<&'a AccountInfo<'info>>), ProgramError> {
+    let mut account_info_iter = accounts.iter();
+    let mut request_accounts = request_accounts(
         program_id,
-    );
-    if requester_signer.key != &expected_signer {
-        return Err(ProgramError::InvalidSeeds);
-    }
-
-    let signer_seeds: &[&[u8]] = &[REQUESTER_SIGNER_SEED, entropy_program.key.as_ref(), &[bump]];
-    invoke_signed(
-        &entropy_ix,
-        &[
-            requester_signer.clone(),
-            payer.clone(),
-            requester_program.clone(),
-            request_account.clone(),
-            provider_account.clone(),
-            provider_vault.clone(),
-            config_account.clone(),
-            pyth_fee_vault.clone(),
-            system_program_account.clone(),
-        ],
-        &[signer_seeds],
+        &mut account_info_iter,
+        parse_requester_signer_bump(entropy_data)?,
+        true,
     )?;
+    // With a list, `request_accounts` read it where the entropy program goes
+    // and the entropy program comes next.
+    let callback_account_list = account_info_iter.next().map(|entropy_program| {
+        core::mem::replace(&mut request_accounts.entropy_program, entropy_program)
+    });
+    Ok((request_accounts, callback_account_list))
+}
 
+fn process_request(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<RequestArgs>(data)?;
+    let accounts = request_accounts(
+        program_id,
+        &mut accounts.iter(),
+        args.requester_signer_bump,
+        false,
+    )?;
+    entropy_cpi::request(program_id, &accounts, args)?;
     Ok(())
 }
 
@@ -135,67 +244,139 @@ fn process_request_with_callback(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     entropy_data: &[u8],
+) -> ProgramResult {
+    let (accounts, callback_account_list) = callback_request_accounts(program_id, accounts, entropy_data)?;
+    match callback_account_list {
+        Some(callback_account_list) => entropy_cpi::request_with_callback_account_list_raw(
+            program_id,
+            &accounts,
+            callback_account_list,
+            entropy_data.to_vec(),
+        )?,
+        None => {
+            entropy_cpi::request_with_callback_raw(program_id, &accounts, entropy_data.to_vec())?
+        }
+    };
+    Ok(())
+}
+
+/// Like `process_request_with_callback`, but forwards `RequestV2` data, so
+/// entropy supplies the user randomness.
+fn process_request_v2(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entropy_data: &[u8],
+) -> ProgramResult {
+    let (accounts, callback_account_list) = callback_request_accounts(program_id, accounts, entropy_data)?;
+    match callback_account_list {
+        Some(callback_account_list) => entropy_cpi::request_v2_with_callback_account_list_raw(
+            program_id,
+            &accounts,
+            callback_account_list,
+            entropy_data.to_vec(),
+        )?,
+        None => entropy_cpi::request_v2_raw(program_id, &accounts, entropy_data.to_vec())?,
+    };
+    Ok(())
+}
+
+/// Forwards `RequestAggregate` data. The accounts are those of the entropy
+/// instruction, provider pairs included, followed by the entropy program.
+fn process_request_aggregate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entropy_data: &[u8],
 ) -> ProgramResult {
     let mut account_info_iter = accounts.iter();
     let requester_signer = next_account_info(&mut account_info_iter)?;
     let payer = next_account_info(&mut account_info_iter)?;
     let requester_program = next_account_info(&mut account_info_iter)?;
     let request_account = next_account_info(&mut account_info_iter)?;
-    let provider_account = next_account_info(&mut account_info_iter)?;
-    let provider_vault = next_account_info(&mut account_info_iter)?;
-    let config_account = next_account_info(&mut account_info_iter)?;
+    let config = next_account_info(&mut account_info_iter)?;
     let pyth_fee_vault = next_account_info(&mut account_info_iter)?;
-    let system_program_account = next_account_info(&mut account_info_iter)?;
-    let callback_program = next_account_info(&mut account_info_iter)?;
-    let entropy_program = next_account_info(&mut account_info_iter)?;
+    let system_program = next_account_info(&mut account_info_iter)?;
+    let (entropy_program, providers) = account_info_iter
+        .as_slice()
+        .split_last()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
 
-    if requester_program.key != program_id || callback_program.key != program_id {
-        return Err(ProgramError::InvalidArgument);
-    }
+    let accounts = AggregateRequestAccounts {
+        requester_signer,
+        requester_signer_bump: parse_requester_signer_bump(entropy_data)?,
+        payer,
+        requester_program,
+        request_account,
+        config,
+        pyth_fee_vault,
+        system_program,
+        providers,
+        entropy_program,
+    };
+    entropy_cpi::request_aggregate_raw(program_id, &accounts, entropy_data.to_vec())?;
+    Ok(())
+}
 
-    let (expected_signer, bump) = Pubkey::find_program_address(
-        &[REQUESTER_SIGNER_SEED, entropy_program.key.as_ref()],
+/// Requests a callback into a fresh `callback_state_pda` for the assigned
+/// sequence number, so the client does not create a state account per request.
+fn process_request_with_pda_callback(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = parse_args::<RequestWithPdaCallbackArgs>(data)?;
+    let mut account_info_iter = accounts.iter();
+    let request_accounts = request_accounts(
         program_id,
-    );
-    if requester_signer.key != &expected_signer {
+        &mut account_info_iter,
+        args.requester_signer_bump,
+        true,
+    )?;
+    let callback_state = next_account_info(&mut account_info_iter)?;
+
+    let mut callback_ix_data = vec![CALLBACK_ACTION];
+    callback_ix_data.extend_from_slice(request_accounts.entropy_program.key.as_ref());
+    let sequence_number = entropy_cpi::request_with_callback(
+        program_id,
+        &request_accounts,
+        args.user_randomness,
+        &RequestParams {
+            compute_unit_limit: args.compute_unit_limit,
+            ttl_slots: args.ttl_slots,
+            callback_accounts: &[callback_meta(callback_state.key, false, true)],
+            callback_ix_data: &callback_ix_data,
+            ..Default::default()
+        },
+    )?;
+
+    // The callback account was fixed before the sequence number was known;
+    // reject a misprediction rather than deliver to the wrong account.
+    let (expected_callback_state, bump) =
+        callback_state_pda(program_id, request_accounts.provider.key, sequence_number);
+    if callback_state.key != &expected_callback_state {
         return Err(ProgramError::InvalidSeeds);
     }
 
-    let entropy_ix = Instruction {
-        program_id: *entropy_program.key,
-        data: entropy_data.to_vec(),
-        accounts: vec![
-            AccountMeta::new_readonly(*requester_signer.key, true),
-            AccountMeta::new(*payer.key, true),
-            AccountMeta::new_readonly(*requester_program.key, false),
-            AccountMeta::new(*request_account.key, true),
-            AccountMeta::new(*provider_account.key, false),
-            AccountMeta::new(*provider_vault.key, false),
-            AccountMeta::new_readonly(*config_account.key, false),
-            AccountMeta::new(*pyth_fee_vault.key, false),
-            AccountMeta::new_readonly(*system_program_account.key, false),
-            AccountMeta::new_readonly(*callback_program.key, false),
-        ],
-    };
-
-    let signer_seeds: &[&[u8]] = &[REQUESTER_SIGNER_SEED, entropy_program.key.as_ref(), &[bump]];
+    let create_ix = system_instruction::create_account(
+        request_accounts.payer.key,
+        callback_state.key,
+        Rent::get()?.minimum_balance(CALLBACK_STATE_LEN),
+        CALLBACK_STATE_LEN as u64,
+        program_id,
+    );
     invoke_signed(
-        &entropy_ix,
+        &create_ix,
         &[
-            requester_signer.clone(),
-            payer.clone(),
-            requester_program.clone(),
-            request_account.clone(),
-            provider_account.clone(),
-            provider_vault.clone(),
-            config_account.clone(),
-            pyth_fee_vault.clone(),
-            system_program_account.clone(),
-            callback_program.clone(),
+            request_accounts.payer.clone(),
+            callback_state.clone(),
+            request_accounts.system_program.clone(),
         ],
-        &[signer_seeds],
+        &[&[
+            CALLBACK_STATE_SEED,
+            request_accounts.provider.key.as_ref(),
+            &sequence_number.to_le_bytes(),
+            &[bump],
+        ]],
     )?;
-
     Ok(())
 }
 
@@ -203,10 +384,22 @@ fn process_callback(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
+    include_contributions: bool,
 ) -> ProgramResult {
-    if data.len() != 32 + 8 + 32 + 32 {
-        return Err(ProgramError::InvalidInstructionData);
-    }
+    // Read first so the value reflects the budget entropy handed over.
+    let remaining_compute_units = sol_remaining_compute_units();
+    let (prefix, callback, contributions) = if include_contributions {
+        let (prefix, callback, contributions) = parse_callback_with_contributions(data)?;
+        (prefix, callback, Some(contributions))
+    } else {
+        let (prefix, callback) = parse_callback(data)?;
+        (prefix, callback, None)
+    };
+    let entropy_program_id = Pubkey::new_from_array(
+        prefix
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+    );
 
     let mut account_info_iter = accounts.iter();
     let entropy_signer = next_account_info(&mut account_info_iter)?;
@@ -215,41 +408,26 @@ fn process_callback(
     if callback_state.owner != program_id || !callback_state.is_writable {
         return Err(ProgramError::InvalidAccountData);
     }
+    verify_entropy_signer(entropy_signer, &entropy_program_id)?;
 
-    let entropy_program_id = Pubkey::new_from_array(
-        data[..32]
-            .try_into()
-            .map_err(|_| ProgramError::InvalidInstructionData)?,
-    );
-    let (expected_entropy_signer, _bump) =
-        Pubkey::find_program_address(&[ENTROPY_SIGNER_SEED], &entropy_program_id);
-    if entropy_signer.key != &expected_entropy_signer {
-        return Err(ProgramError::InvalidSeeds);
-    }
-
-    let sequence_number = u64::from_le_bytes(
-        data[32..40]
-            .try_into()
-            .map_err(|_| ProgramError::InvalidInstructionData)?,
-    );
-    let provider = data[40..72]
-        .try_into()
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
-    let random_number:[u8; 32] = data[72..104]
-        .try_into()
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
-
-    let mut rand_bytes = [0u8; 8];
-    rand_bytes.copy_from_slice(&random_number[..8]);
-    let random_value = u64::from_le_bytes(rand_bytes) % 101;
+    let random_value = rand::u64_in_range(&callback.random_number, 0, 100);
     msg!("Random number (0-100): {}", random_value);
 
     let mut state_data = callback_state.try_borrow_mut_data()?;
     let state = bytemuck::from_bytes_mut::<CallbackState>(&mut state_data);
-    state.sequence_number = sequence_number;
-    state.provider = provider;
-    state.random_number = random_number;
+    state.sequence_number = callback.sequence_number;
+    state.provider = callback.provider.to_bytes();
+    state.random_number = callback.random_number;
     state.called = 1;
+    state.derived_value =
+        EntropyRng::new(&callback.random_number, callback.sequence_number, RNG_CONSUMER_SEED)
+            .next_u64();
+    state.entropy_signer = entropy_signer.key.to_bytes();
+    state.remaining_compute_units = remaining_compute_units;
+    if let Some(contributions) = contributions {
+        state.user_contribution = contributions.user_contribution;
+        state.provider_contribution = contributions.provider_contribution;
+    }
 
     Ok(())
 }
@@ -0,0 +1,49 @@
+use {
+    entropy::pda::{
+        config_pda, entropy_signer_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda,
+        requester_signer_pda,
+    },
+    solana_sdk::{pubkey::Pubkey, system_program, sysvar::slot_hashes},
+};
+
+/// Every PDA involved in a request from `requester_program` to
+/// `provider_authority`, derived once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EntropyAddresses {
+    pub program_id: Pubkey,
+    pub config: Pubkey,
+    pub pyth_fee_vault: Pubkey,
+    pub provider: Pubkey,
+    pub provider_vault: Pubkey,
+    pub entropy_signer: Pubkey,
+    /// Derived under `requester_program`, not entropy.
+    pub requester_signer: Pubkey,
+}
+
+impl EntropyAddresses {
+    pub fn new(program_id: Pubkey, provider_authority: Pubkey, requester_program: Pubkey) -> Self {
+        Self {
+            program_id,
+            config: config_pda(&program_id).0,
+            pyth_fee_vault: pyth_fee_vault_pda(&program_id).0,
+            provider: provider_pda(&program_id, &provider_authority).0,
+            provider_vault: provider_vault_pda(&program_id, &provider_authority).0,
+            entropy_signer: entropy_signer_pda(&program_id).0,
+            requester_signer: requester_signer_pda(&requester_program, &program_id).0,
+        }
+    }
+}
+
+/// The accounts every reveal for `provider_authority` uses whatever the
+/// request, which an address lookup table for its keeper should hold.
+pub fn provider_lookup_addresses(program_id: Pubkey, provider_authority: Pubkey) -> Vec<Pubkey> {
+    vec![
+        config_pda(&program_id).0,
+        pyth_fee_vault_pda(&program_id).0,
+        provider_pda(&program_id, &provider_authority).0,
+        provider_vault_pda(&program_id, &provider_authority).0,
+        entropy_signer_pda(&program_id).0,
+        slot_hashes::id(),
+        system_program::id(),
+    ]
+}
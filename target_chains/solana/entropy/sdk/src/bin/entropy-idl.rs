@@ -0,0 +1,19 @@
+//! Prints the entropy IDL as JSON.
+//!
+//! Usage: `entropy-idl [PROGRAM_ID]`. Without a program id the `address` is
+//! the default pubkey, as in the checked-in `idl/entropy.json`.
+
+use {entropy_sdk::idl::entropy_idl, solana_sdk::pubkey::Pubkey, std::str::FromStr};
+
+fn main() {
+    let program_id = match std::env::args().nth(1) {
+        Some(arg) => Pubkey::from_str(&arg).unwrap_or_else(|err| {
+            eprintln!("invalid program id {arg}: {err}");
+            std::process::exit(2);
+        }),
+        None => Pubkey::default(),
+    };
+    let idl =
+        serde_json::to_string_pretty(&entropy_idl(&program_id)).expect("IDL serializes to JSON");
+    println!("{idl}");
+}
@@ -0,0 +1,429 @@
+use {
+    crate::{
+        directory::{listed_providers, provider_directory_addresses},
+        error::EntropySdkError,
+        fee::{request_fee, RequestFee},
+        instruction::{
+            forward_request_with_callback, get_version, preview_request, request_with_callback,
+            RequestAccounts, RequestParams,
+        },
+        stream::RequestStream,
+    },
+    base64::{engine::general_purpose::STANDARD, Engine},
+    bytemuck::try_pod_read_unaligned,
+    entropy::{
+        accounts::{
+            Account, CallbackFailureStats, Config, KeeperLease, ProgramRegistry, Provider,
+            ProviderAllowlist, ProviderDirectoryPage, Request,
+        },
+        instruction::RequestPreview,
+        pda::{
+            callback_failure_stats_pda, config_pda, entropy_signer_pda, keeper_lease_pda,
+            provider_allowlist_pda, provider_pda,
+        },
+    },
+    solana_client::{
+        nonblocking::rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig,
+        rpc_request::MAX_MULTIPLE_ACCOUNTS, rpc_response::RpcSimulateTransactionResult,
+    },
+    solana_sdk::{
+        account::Account as SolanaAccount,
+        hash::hash,
+        instruction::{AccountMeta, Instruction, InstructionError},
+        message::Message,
+        pubkey::Pubkey,
+        signature::{Keypair, Signature},
+        signer::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+/// Parameters for [`EntropyClient::request_with_callback`].
+#[derive(Clone, Debug, Default)]
+pub struct RequestWithCallbackParams {
+    /// Program that forwards the request and receives the callback. It must
+    /// implement the forwarding ABI of
+    /// [`forward_request_with_callback`](crate::instruction::forward_request_with_callback).
+    pub requester_program: Pubkey,
+    pub provider_authority: Pubkey,
+    pub user_randomness: [u8; 32],
+    pub compute_unit_limit: u32,
+    /// Slots after which the request can no longer be revealed and its rent
+    /// can be reclaimed; zero for no expiry.
+    pub ttl_slots: u32,
+    /// Slots that must pass after the request before it can be revealed;
+    /// zero allows a reveal in the same slot.
+    pub min_reveal_delay_slots: u16,
+    /// Appends the user and provider contributions to the callback data;
+    /// see `entropy_cpi::parse_callback_with_contributions`.
+    pub include_contributions: bool,
+    /// [`RANDOM_NUMBER_V1`](entropy::constants::RANDOM_NUMBER_V1) binds the
+    /// random number to this entropy deployment; 0 keeps the original
+    /// derivation.
+    pub random_number_version: u8,
+    /// Receives the request account's rent when it is closed; the payer if
+    /// `None`.
+    pub refund_address: Option<Pubkey>,
+    pub callback_accounts: Vec<AccountMeta>,
+    pub callback_ix_data: Vec<u8>,
+}
+
+/// Outcome of a confirmed request.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestReceipt {
+    pub signature: Signature,
+    pub request_account: Pubkey,
+    pub sequence_number: u64,
+}
+
+/// Async access to Entropy accounts and requests over an [`RpcClient`].
+pub struct EntropyClient {
+    rpc: RpcClient,
+    program_id: Pubkey,
+    websocket_url: String,
+}
+
+impl EntropyClient {
+    /// The websocket endpoint defaults to the RPC URL with a `ws`/`wss`
+    /// scheme and, for the validator's default port 8899, port 8900.
+    pub fn new(rpc: RpcClient, program_id: Pubkey) -> Self {
+        let websocket_url = default_websocket_url(&rpc.url());
+        Self {
+            rpc,
+            program_id,
+            websocket_url,
+        }
+    }
+
+    /// Overrides the websocket endpoint used by [`Self::request_stream`].
+    pub fn with_websocket_url(mut self, websocket_url: impl Into<String>) -> Self {
+        self.websocket_url = websocket_url.into();
+        self
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    pub async fn get_config(&self) -> Result<Config, EntropySdkError> {
+        let (config, _) = config_pda(&self.program_id);
+        self.get_account(&config, "config").await
+    }
+
+    pub async fn get_provider(
+        &self,
+        provider_authority: &Pubkey,
+    ) -> Result<Provider, EntropySdkError> {
+        let (provider, _) = provider_pda(&self.program_id, provider_authority);
+        self.get_account(&provider, "provider").await
+    }
+
+    /// The provider's keeper lease; compare it against the current slot with
+    /// [`KeeperLease::is_stale`] to tell whether its keeper is alive.
+    pub async fn get_keeper_lease(
+        &self,
+        provider_authority: &Pubkey,
+    ) -> Result<KeeperLease, EntropySdkError> {
+        let (keeper_lease, _) = keeper_lease_pda(&self.program_id, provider_authority);
+        self.get_account(&keeper_lease, "keeper lease").await
+    }
+
+    /// The callback failures the provider reported for `requester_program_id`;
+    /// [`Provider::skips_callbacks`] tells whether its policy skips them.
+    pub async fn get_callback_failure_stats(
+        &self,
+        provider_authority: &Pubkey,
+        requester_program_id: &Pubkey,
+    ) -> Result<CallbackFailureStats, EntropySdkError> {
+        let (stats, _) =
+            callback_failure_stats_pda(&self.program_id, provider_authority, requester_program_id);
+        self.get_account(&stats, "callback failure stats").await
+    }
+
+    /// The entropy program ids this program lists as official, kept in its
+    /// entropy signer PDA.
+    pub async fn get_program_registry(&self) -> Result<ProgramRegistry, EntropySdkError> {
+        let (entropy_signer, _) = entropy_signer_pda(&self.program_id);
+        self.get_account(&entropy_signer, "program registry").await
+    }
+
+    /// The provider authorities allowed to register while
+    /// `Config::permissioned_providers` is set.
+    pub async fn get_provider_allowlist(&self) -> Result<ProviderAllowlist, EntropySdkError> {
+        let (provider_allowlist, _) = provider_allowlist_pda(&self.program_id);
+        self.get_account(&provider_allowlist, "provider allowlist")
+            .await
+    }
+
+    /// Every provider in the provider directory with its current account, in
+    /// directory order. A provider closed between the two fetches is
+    /// skipped.
+    pub async fn list_providers(&self) -> Result<Vec<(Pubkey, Provider)>, EntropySdkError> {
+        let config = self.get_config().await?;
+        let page_addresses = provider_directory_addresses(&self.program_id, &config);
+        let mut pages = Vec::with_capacity(page_addresses.len());
+        for (address, account) in page_addresses
+            .iter()
+            .zip(self.get_multiple_accounts(&page_addresses).await?)
+        {
+            let account = account.ok_or(EntropySdkError::AccountNotFound(*address))?;
+            pages.push(decode_account::<ProviderDirectoryPage>(
+                &self.program_id,
+                address,
+                &account,
+                "provider directory page",
+            )?);
+        }
+
+        let authorities = listed_providers(&pages);
+        let provider_addresses: Vec<Pubkey> = authorities
+            .iter()
+            .map(|authority| provider_pda(&self.program_id, authority).0)
+            .collect();
+        let accounts = self.get_multiple_accounts(&provider_addresses).await?;
+        authorities
+            .into_iter()
+            .zip(&provider_addresses)
+            .zip(accounts)
+            .filter_map(|((authority, address), account)| {
+                let provider =
+                    decode_account::<Provider>(&self.program_id, address, &account?, "provider");
+                Some(provider.map(|provider| (authority, provider)))
+            })
+            .collect()
+    }
+
+    pub async fn get_request(&self, request_account: &Pubkey) -> Result<Request, EntropySdkError> {
+        self.get_account(request_account, "request").await
+    }
+
+    /// The deployed program's
+    /// [`PROGRAM_VERSION`](entropy::constants::PROGRAM_VERSION), read by
+    /// simulating `GetVersion` with `fee_payer`, which must be an existing
+    /// account; nothing is signed or sent. Deployments that predate
+    /// `GetVersion` reject it as invalid instruction data and report 0.
+    pub async fn get_program_version(&self, fee_payer: &Pubkey) -> Result<u8, EntropySdkError> {
+        let simulation = self
+            .simulate(fee_payer, get_version(self.program_id))
+            .await?;
+        if let Some(err) = &simulation.err {
+            // Compared in its JSON form, which the RPC types keep stable
+            // across client versions.
+            let unknown_instruction =
+                TransactionError::InstructionError(0, InstructionError::InvalidInstructionData);
+            if serde_json::to_value(err).ok() == serde_json::to_value(unknown_instruction).ok() {
+                return Ok(0);
+            }
+            return Err(EntropySdkError::SimulationFailed(err.to_string()));
+        }
+        self.return_data(simulation)
+            .first()
+            .copied()
+            .ok_or_else(|| {
+                EntropySdkError::SimulationFailed("GetVersion returned no version".to_string())
+            })
+    }
+
+    /// What a request to `provider_authority` made now with `user_randomness`
+    /// would store, read by simulating `PreviewRequest` against the live
+    /// provider account with `fee_payer` (see [`get_program_version`]). Needs
+    /// [`PROGRAM_VERSION`](entropy::constants::PROGRAM_VERSION) 4;
+    /// [`preview_request_commitment`](crate::verify::preview_request_commitment)
+    /// computes the commitment from a fetched provider instead.
+    ///
+    /// [`get_program_version`]: Self::get_program_version
+    pub async fn preview_request(
+        &self,
+        fee_payer: &Pubkey,
+        provider_authority: &Pubkey,
+        user_randomness: &[u8; 32],
+    ) -> Result<RequestPreview, EntropySdkError> {
+        let ix = preview_request(
+            self.program_id,
+            *provider_authority,
+            hash(user_randomness).to_bytes(),
+        );
+        let simulation = self.simulate(fee_payer, ix).await?;
+        if let Some(err) = &simulation.err {
+            return Err(EntropySdkError::SimulationFailed(err.to_string()));
+        }
+        try_pod_read_unaligned(&self.return_data(simulation)).map_err(|_| {
+            EntropySdkError::SimulationFailed("PreviewRequest returned no preview".to_string())
+        })
+    }
+
+    /// Estimates what a request to `provider_authority` with
+    /// `compute_unit_limit` costs, from the current provider and config
+    /// accounts. Limits at or below the provider's default pay the base fee.
+    pub async fn estimate_request_fee(
+        &self,
+        provider_authority: &Pubkey,
+        compute_unit_limit: u32,
+    ) -> Result<RequestFee, EntropySdkError> {
+        let config = self.get_config().await?;
+        let provider = self.get_provider(provider_authority).await?;
+        let request_rent = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(Request::LEN)
+            .await?;
+        Ok(RequestFee {
+            request_rent,
+            ..request_fee(&config, &provider, compute_unit_limit)?
+        })
+    }
+
+    /// Streams the pending requests addressed to `provider_authority`: those
+    /// open when the stream starts (via `getProgramAccounts`), then new ones
+    /// as the program subscription reports them.
+    pub async fn request_stream(
+        &self,
+        provider_authority: &Pubkey,
+    ) -> Result<RequestStream, EntropySdkError> {
+        RequestStream::open(
+            &self.rpc,
+            &self.websocket_url,
+            self.program_id,
+            *provider_authority,
+        )
+        .await
+    }
+
+    /// Submits a `RequestWithCallback` through `params.requester_program` and
+    /// waits for confirmation. `payer` funds the request account and fees.
+    pub async fn request_with_callback(
+        &self,
+        payer: &Keypair,
+        params: RequestWithCallbackParams,
+    ) -> Result<RequestReceipt, EntropySdkError> {
+        let request_account = Keypair::new();
+        let accounts = RequestAccounts {
+            requester_program: params.requester_program,
+            payer: payer.pubkey(),
+            request_account: request_account.pubkey(),
+            provider_authority: params.provider_authority,
+        };
+        let entropy_ix = request_with_callback(
+            self.program_id,
+            &accounts,
+            params.user_randomness,
+            &RequestParams {
+                compute_unit_limit: params.compute_unit_limit,
+                ttl_slots: params.ttl_slots,
+                min_reveal_delay_slots: params.min_reveal_delay_slots,
+                include_contributions: params.include_contributions,
+                random_number_version: params.random_number_version,
+                refund_address: params.refund_address,
+                callback_accounts: &params.callback_accounts,
+                callback_ix_data: &params.callback_ix_data,
+            },
+        )?;
+        let instruction = forward_request_with_callback(params.requester_program, &entropy_ix);
+
+        let recent_blockhash = self.rpc.get_latest_blockhash().await?;
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        transaction.try_sign(&[payer, &request_account], recent_blockhash)?;
+        let signature = self.rpc.send_and_confirm_transaction(&transaction).await?;
+
+        let request = self.get_request(&request_account.pubkey()).await?;
+        Ok(RequestReceipt {
+            signature,
+            request_account: request_account.pubkey(),
+            sequence_number: request.sequence_number,
+        })
+    }
+
+    /// `getMultipleAccounts` in batches the RPC accepts.
+    /// Simulates `ix` alone with `fee_payer`, without checking signatures.
+    async fn simulate(
+        &self,
+        fee_payer: &Pubkey,
+        ix: Instruction,
+    ) -> Result<RpcSimulateTransactionResult, EntropySdkError> {
+        let message = Message::new(&[ix], Some(fee_payer));
+        Ok(self
+            .rpc
+            .simulate_transaction_with_config(
+                &Transaction::new_unsigned(message),
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(self.rpc.commitment()),
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )
+            .await?
+            .value)
+    }
+
+    /// The return data of `simulation` if entropy set it, else empty.
+    fn return_data(&self, simulation: RpcSimulateTransactionResult) -> Vec<u8> {
+        simulation
+            .return_data
+            .filter(|return_data| return_data.program_id == self.program_id.to_string())
+            .and_then(|return_data| STANDARD.decode(return_data.data.0).ok())
+            .unwrap_or_default()
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<SolanaAccount>>, EntropySdkError> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let batch = self
+                .rpc
+                .get_multiple_accounts_with_commitment(chunk, self.rpc.commitment())
+                .await?;
+            accounts.extend(batch.value);
+        }
+        Ok(accounts)
+    }
+
+    async fn get_account<T: Account>(
+        &self,
+        address: &Pubkey,
+        kind: &'static str,
+    ) -> Result<T, EntropySdkError> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .await?
+            .value
+            .ok_or(EntropySdkError::AccountNotFound(*address))?;
+        decode_account(&self.program_id, address, &account, kind)
+    }
+}
+
+/// Checks owner, size and discriminator before reading `T` out of `account`.
+pub(crate) fn decode_account<T: Account>(
+    program_id: &Pubkey,
+    address: &Pubkey,
+    account: &SolanaAccount,
+    kind: &'static str,
+) -> Result<T, EntropySdkError> {
+    if account.owner != *program_id
+        || account.data.len() != T::LEN
+        || account.data.get(..8) != Some(T::discriminator().as_slice())
+    {
+        return Err(EntropySdkError::InvalidAccountData(*address, kind));
+    }
+    try_pod_read_unaligned::<T>(&account.data)
+        .map_err(|_| EntropySdkError::InvalidAccountData(*address, kind))
+}
+
+/// The websocket endpoint of `rpc_url`: its `ws`/`wss` form with the
+/// validator's default port 8899 replaced by 8900.
+pub fn default_websocket_url(rpc_url: &str) -> String {
+    let url = if let Some(rest) = rpc_url.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if let Some(rest) = rpc_url.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else {
+        rpc_url.to_string()
+    };
+    url.replacen(":8899", ":8900", 1)
+}
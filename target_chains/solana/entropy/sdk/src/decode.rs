@@ -0,0 +1,245 @@
+//! Decodes entropy instruction data, accounts and return data with the
+//! program's IDL, for tools that inspect arbitrary transactions.
+//!
+//! Values come out as JSON: integers as numbers, pubkeys in base58, byte
+//! arrays and byte vectors in hex, and structs as objects without their
+//! padding fields.
+
+use {
+    crate::{error::EntropySdkError, idl::entropy_idl},
+    serde_json::{json, Map, Value},
+    solana_sdk::pubkey::Pubkey,
+};
+
+/// Name given to accounts past those the IDL lists, such as callback
+/// accounts and aggregate request providers.
+pub const REMAINING_ACCOUNT: &str = "remaining";
+
+/// An entropy instruction decoded with the IDL.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedInstruction {
+    pub name: String,
+    /// Accounts in instruction order, named by their role.
+    pub accounts: Vec<(String, Pubkey)>,
+    /// The decoded payload; `None` for instructions without one.
+    pub args: Option<Value>,
+    /// IDL type of the instruction's return data, if it sets any.
+    pub returns: Option<Value>,
+}
+
+/// Decodes entropy instructions and accounts. Holds the IDL so it is built
+/// once.
+pub struct IdlDecoder {
+    idl: Value,
+}
+
+impl Default for IdlDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdlDecoder {
+    pub fn new() -> Self {
+        Self {
+            idl: entropy_idl(&Pubkey::default()),
+        }
+    }
+
+    /// Decodes an instruction's `data` given the keys of its `accounts`.
+    pub fn decode(
+        &self,
+        data: &[u8],
+        accounts: &[Pubkey],
+    ) -> Result<DecodedInstruction, EntropySdkError> {
+        let mut payload = data;
+        let discriminator: [u8; 8] =
+            take_array(&mut payload).map_err(|_| invalid("missing discriminator"))?;
+        let instruction = self.idl["instructions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|instruction| discriminator_matches(&instruction["discriminator"], data))
+            .ok_or_else(|| {
+                invalid(format!(
+                    "unknown instruction {}",
+                    u64::from_le_bytes(discriminator)
+                ))
+            })?;
+
+        let roles: Vec<&str> = instruction["accounts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|account| account["name"].as_str())
+            .collect();
+        let accounts = accounts
+            .iter()
+            .enumerate()
+            .map(|(index, pubkey)| {
+                let role = roles.get(index).copied().unwrap_or(REMAINING_ACCOUNT);
+                (role.to_string(), *pubkey)
+            })
+            .collect();
+
+        let args = match instruction["args"].as_array().and_then(|args| args.first()) {
+            Some(arg) => Some(self.decode_all(&arg["type"], &mut payload)?),
+            None if payload.is_empty() => None,
+            None => return Err(trailing(payload)),
+        };
+        Ok(DecodedInstruction {
+            name: instruction["name"].as_str().unwrap_or_default().to_string(),
+            accounts,
+            args,
+            returns: instruction.get("returns").cloned(),
+        })
+    }
+
+    /// Decodes an entropy account, returning its type name and fields.
+    pub fn decode_account(&self, data: &[u8]) -> Result<(String, Value), EntropySdkError> {
+        let name = self.idl["accounts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|account| discriminator_matches(&account["discriminator"], data))
+            .and_then(|account| account["name"].as_str())
+            .ok_or_else(|| invalid("unknown account discriminator"))?;
+        let mut input = data;
+        let value = self.decode_all(&json!({ "defined": { "name": name } }), &mut input)?;
+        Ok((name.to_string(), value))
+    }
+
+    /// Decodes the return data `instruction` set, if it declares any.
+    pub fn decode_return_data(
+        &self,
+        instruction: &DecodedInstruction,
+        mut data: &[u8],
+    ) -> Result<Option<Value>, EntropySdkError> {
+        instruction
+            .returns
+            .as_ref()
+            .map(|ty| self.decode_all(ty, &mut data))
+            .transpose()
+    }
+
+    /// Decodes a `ty` spanning all of `input`.
+    fn decode_all(&self, ty: &Value, input: &mut &[u8]) -> Result<Value, EntropySdkError> {
+        let value = self.decode_value(ty, input)?;
+        if !input.is_empty() {
+            return Err(trailing(input));
+        }
+        Ok(value)
+    }
+
+    fn decode_value(&self, ty: &Value, input: &mut &[u8]) -> Result<Value, EntropySdkError> {
+        match ty {
+            Value::String(name) => match name.as_str() {
+                "u8" => Ok(take(input, 1)?[0].into()),
+                "u16" => Ok(u16::from_le_bytes(take_array(input)?).into()),
+                "u32" => Ok(u32::from_le_bytes(take_array(input)?).into()),
+                "u64" => Ok(u64::from_le_bytes(take_array(input)?).into()),
+                "pubkey" => Ok(Pubkey::new_from_array(take_array(input)?)
+                    .to_string()
+                    .into()),
+                "bytes" => {
+                    let len = u32::from_le_bytes(take_array(input)?) as usize;
+                    Ok(hex(take(input, len)?).into())
+                }
+                other => Err(invalid(format!("unsupported IDL type {other}"))),
+            },
+            Value::Object(map) => {
+                if let Some(array) = map.get("array") {
+                    let len = array[1].as_u64().unwrap_or_default() as usize;
+                    if array[0] == "u8" {
+                        return Ok(hex(take(input, len)?).into());
+                    }
+                    self.decode_sequence(&array[0], len, input)
+                } else if let Some(element) = map.get("vec") {
+                    let len = u32::from_le_bytes(take_array(input)?) as usize;
+                    self.decode_sequence(element, len, input)
+                } else if let Some(name) = map
+                    .get("defined")
+                    .and_then(|defined| defined["name"].as_str())
+                {
+                    self.decode_struct(name, input)
+                } else {
+                    Err(invalid(format!("unsupported IDL type {ty}")))
+                }
+            }
+            _ => Err(invalid(format!("unsupported IDL type {ty}"))),
+        }
+    }
+
+    fn decode_sequence(
+        &self,
+        element: &Value,
+        len: usize,
+        input: &mut &[u8],
+    ) -> Result<Value, EntropySdkError> {
+        // Each element takes at least a byte, so this bounds the allocation.
+        if len > input.len() {
+            return Err(invalid("sequence longer than the data"));
+        }
+        (0..len)
+            .map(|_| self.decode_value(element, input))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array)
+    }
+
+    fn decode_struct(&self, name: &str, input: &mut &[u8]) -> Result<Value, EntropySdkError> {
+        let fields = self.idl["types"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|type_def| type_def["name"] == name)
+            .and_then(|type_def| type_def["type"]["fields"].as_array())
+            .ok_or_else(|| invalid(format!("unknown IDL type {name}")))?;
+        let mut object = Map::new();
+        for field in fields {
+            let value = self.decode_value(&field["type"], input)?;
+            let field_name = field["name"].as_str().unwrap_or_default();
+            if !field_name.starts_with("_padding") {
+                object.insert(field_name.to_string(), value);
+            }
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+/// Whether `data` starts with the IDL `discriminator`.
+fn discriminator_matches(discriminator: &Value, data: &[u8]) -> bool {
+    discriminator.as_array().is_some_and(|expected| {
+        expected.len() <= data.len()
+            && expected
+                .iter()
+                .zip(data)
+                .all(|(expected, byte)| expected.as_u64() == Some(u64::from(*byte)))
+    })
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], EntropySdkError> {
+    if input.len() < len {
+        return Err(invalid("data ends early"));
+    }
+    let (head, rest) = input.split_at(len);
+    *input = rest;
+    Ok(head)
+}
+
+fn take_array<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], EntropySdkError> {
+    let mut array = [0u8; N];
+    array.copy_from_slice(take(input, N)?);
+    Ok(array)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn invalid(reason: impl Into<String>) -> EntropySdkError {
+    EntropySdkError::Undecodable(reason.into())
+}
+
+fn trailing(input: &[u8]) -> EntropySdkError {
+    invalid(format!("{} trailing bytes", input.len()))
+}
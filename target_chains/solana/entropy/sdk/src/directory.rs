@@ -0,0 +1,29 @@
+//! Provider enumeration through the provider directory, which lists every
+//! registered provider authority once across [`ProviderDirectoryPage`]
+//! accounts.
+
+use {
+    entropy::{
+        accounts::{Config, ProviderDirectoryPage},
+        pda::provider_directory_pda,
+    },
+    solana_sdk::pubkey::Pubkey,
+};
+
+/// Addresses of the directory pages in use under `config`, in order.
+pub fn provider_directory_addresses(program_id: &Pubkey, config: &Config) -> Vec<Pubkey> {
+    (0..config.provider_directory_pages())
+        .map(|page| provider_directory_pda(program_id, page).0)
+        .collect()
+}
+
+/// The provider authorities listed in `pages`, in directory order.
+pub fn listed_providers<'a>(
+    pages: impl IntoIterator<Item = &'a ProviderDirectoryPage>,
+) -> Vec<Pubkey> {
+    pages
+        .into_iter()
+        .flat_map(ProviderDirectoryPage::listed_providers)
+        .map(|provider| Pubkey::new_from_array(*provider))
+        .collect()
+}
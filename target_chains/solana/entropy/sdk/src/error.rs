@@ -0,0 +1,53 @@
+use solana_client::{client_error::ClientError, pubsub_client::PubsubClientError};
+use solana_sdk::{pubkey::Pubkey, signer::SignerError};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EntropySdkError {
+    #[error("rpc error: {0}")]
+    Rpc(#[from] Box<ClientError>),
+    #[error("websocket error: {0}")]
+    Pubsub(#[from] Box<PubsubClientError>),
+    #[error("subscription closed")]
+    SubscriptionClosed,
+    #[error("signing failed: {0}")]
+    Signer(#[from] SignerError),
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("account {0} is not a valid {1} account")]
+    InvalidAccountData(Pubkey, &'static str),
+    #[error("too many callback accounts: {0} (max {max})", max = entropy::constants::MAX_CALLBACK_ACCOUNTS)]
+    TooManyCallbackAccounts(usize),
+    #[error("callback data too long: {0} bytes (max {max})", max = entropy::constants::CALLBACK_IX_DATA_LEN)]
+    CallbackDataTooLong(usize),
+    #[error("invalid aggregate threshold {threshold} for {num_providers} providers (max {max})", max = entropy::constants::MAX_AGGREGATE_PROVIDERS)]
+    InvalidAggregateThreshold { threshold: u8, num_providers: usize },
+    #[error("too many registered programs: {0} (max {max})", max = entropy::constants::MAX_REGISTERED_PROGRAMS)]
+    TooManyRegisteredPrograms(usize),
+    #[error("too many allowed providers: {0} (max {max})", max = entropy::constants::MAX_ALLOWED_PROVIDERS)]
+    TooManyAllowedProviders(usize),
+    #[error("simulation failed: {0}")]
+    SimulationFailed(String),
+    #[error("request fee overflows u64")]
+    FeeOverflow,
+    #[error("invalid serialized hash chain")]
+    InvalidHashChain,
+    #[error("{field} too long: {len} bytes (max {max})")]
+    FieldTooLong {
+        field: &'static str,
+        len: usize,
+        max: usize,
+    },
+    #[error("invalid provider uri: {0}")]
+    InvalidProviderUri(String),
+    #[error("invalid provider metadata: {0}")]
+    InvalidProviderMetadata(String),
+    #[error("cannot decode entropy data: {0}")]
+    Undecodable(String),
+}
+
+impl From<ClientError> for EntropySdkError {
+    fn from(value: ClientError) -> Self {
+        Self::Rpc(Box::new(value))
+    }
+}
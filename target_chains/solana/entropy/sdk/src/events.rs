@@ -0,0 +1,198 @@
+//! Decoding of the events entropy logs with `sol_log_data`.
+
+use {
+    base64::{engine::general_purpose::STANDARD, Engine},
+    bytemuck::try_pod_read_unaligned,
+    entropy::events::{
+        AggregateRandomnessRevealed, AggregateRequestCreated, CallbackInvoked, DefaultProviderSet,
+        Event, PythFeeSet, RandomnessRevealed, RequestCreated, UserRandomnessGenerated,
+    },
+    solana_sdk::pubkey::Pubkey,
+};
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+#[derive(Clone, Copy)]
+pub enum EntropyEvent {
+    RequestCreated(RequestCreated),
+    RandomnessRevealed(RandomnessRevealed),
+    PythFeeSet(PythFeeSet),
+    DefaultProviderSet(DefaultProviderSet),
+    UserRandomnessGenerated(UserRandomnessGenerated),
+    AggregateRequestCreated(AggregateRequestCreated),
+    AggregateRandomnessRevealed(AggregateRandomnessRevealed),
+    CallbackInvoked(CallbackInvoked),
+}
+
+/// Decodes one `sol_log_data` payload given as its fields (discriminator,
+/// body). Returns `None` for data that is not an entropy event.
+pub fn decode_event(fields: &[&[u8]]) -> Option<EntropyEvent> {
+    let [discriminator, body] = fields else {
+        return None;
+    };
+    if *discriminator == RequestCreated::discriminator() {
+        try_pod_read_unaligned(body)
+            .ok()
+            .map(EntropyEvent::RequestCreated)
+    } else if *discriminator == RandomnessRevealed::discriminator() {
+        try_pod_read_unaligned(body)
+            .ok()
+            .map(EntropyEvent::RandomnessRevealed)
+    } else if *discriminator == PythFeeSet::discriminator() {
+        try_pod_read_unaligned(body)
+            .ok()
+            .map(EntropyEvent::PythFeeSet)
+    } else if *discriminator == DefaultProviderSet::discriminator() {
+        try_pod_read_unaligned(body)
+            .ok()
+            .map(EntropyEvent::DefaultProviderSet)
+    } else if *discriminator == UserRandomnessGenerated::discriminator() {
+        try_pod_read_unaligned(body)
+            .ok()
+            .map(EntropyEvent::UserRandomnessGenerated)
+    } else if *discriminator == AggregateRequestCreated::discriminator() {
+        try_pod_read_unaligned(body)
+            .ok()
+            .map(EntropyEvent::AggregateRequestCreated)
+    } else if *discriminator == AggregateRandomnessRevealed::discriminator() {
+        try_pod_read_unaligned(body)
+            .ok()
+            .map(EntropyEvent::AggregateRandomnessRevealed)
+    } else if *discriminator == CallbackInvoked::discriminator() {
+        try_pod_read_unaligned(body)
+            .ok()
+            .map(EntropyEvent::CallbackInvoked)
+    } else {
+        None
+    }
+}
+
+/// Decodes a single `Program data: ...` log line, regardless of which program
+/// emitted it. Prefer [`parse_logs`] when the full log is available.
+pub fn decode_log_line(line: &str) -> Option<EntropyEvent> {
+    let encoded = line.strip_prefix(PROGRAM_DATA_PREFIX)?;
+    let fields = encoded
+        .split(' ')
+        .map(|field| STANDARD.decode(field).ok())
+        .collect::<Option<Vec<_>>>()?;
+    let fields: Vec<&[u8]> = fields.iter().map(Vec::as_slice).collect();
+    decode_event(&fields)
+}
+
+/// Extracts the events logged by `program_id` from a transaction's log
+/// messages, skipping data logged by other programs (e.g. callbacks).
+pub fn parse_logs<S: AsRef<str>>(program_id: &Pubkey, logs: &[S]) -> Vec<EntropyEvent> {
+    let program_id = program_id.to_string();
+    let mut invocation_stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let line = line.as_ref();
+        if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let program = words.next().unwrap_or_default();
+            match words.next() {
+                Some("invoke") => {
+                    invocation_stack.push(program);
+                    continue;
+                }
+                Some("success") | Some("failed:") => {
+                    invocation_stack.pop();
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        if invocation_stack.last() == Some(&program_id.as_str()) {
+            events.extend(decode_log_line(line));
+        }
+    }
+    events
+}
+
+/// A callback that failed inside a reveal, put together from the failed
+/// transaction's logs: the [`CallbackInvoked`] entropy logged before the
+/// callback and the runtime's lines for the callback program.
+#[derive(Clone)]
+pub struct CallbackFailure {
+    /// The request whose callback failed.
+    pub request: CallbackInvoked,
+    /// The error as the runtime logged it, e.g. `custom program error: 0x1`.
+    pub error: String,
+    /// The code of a custom program error.
+    pub error_code: Option<u32>,
+    /// Compute units the callback consumed; the runtime logs them for SBF
+    /// programs only.
+    pub compute_units_consumed: Option<u64>,
+}
+
+/// Finds the callback that failed in a reveal transaction's log messages.
+/// `None` if the logs show no callback invoked by `program_id` failing, e.g.
+/// when the reveal failed before or after its callback.
+pub fn parse_callback_failure<S: AsRef<str>>(
+    program_id: &Pubkey,
+    logs: &[S],
+) -> Option<CallbackFailure> {
+    let program_id = program_id.to_string();
+    let mut invocation_stack: Vec<&str> = Vec::new();
+    // The announced request, with the depth its callback runs at once invoked.
+    let mut invoked: Option<(CallbackInvoked, Option<usize>)> = None;
+    let mut compute_units_consumed = None;
+
+    for line in logs {
+        let line = line.as_ref();
+        let in_callback =
+            matches!(invoked, Some((_, Some(depth))) if depth == invocation_stack.len());
+        if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let program = words.next().unwrap_or_default();
+            match words.next() {
+                Some("invoke") => {
+                    if let Some((_, depth @ None)) = &mut invoked {
+                        if invocation_stack.last() == Some(&program_id.as_str()) {
+                            *depth = Some(invocation_stack.len() + 1);
+                        }
+                    }
+                    invocation_stack.push(program);
+                    continue;
+                }
+                Some("consumed") if in_callback => {
+                    compute_units_consumed = words.next().and_then(|units| units.parse().ok());
+                    continue;
+                }
+                Some("success") => {
+                    if in_callback {
+                        invoked = None;
+                    }
+                    invocation_stack.pop();
+                    continue;
+                }
+                Some("failed:") => {
+                    if let (true, Some((request, _))) = (in_callback, invoked) {
+                        let error = line.split_once("failed: ").map_or("", |(_, error)| error);
+                        return Some(CallbackFailure {
+                            request,
+                            error: error.to_string(),
+                            error_code: error
+                                .strip_prefix("custom program error: 0x")
+                                .and_then(|code| u32::from_str_radix(code, 16).ok()),
+                            compute_units_consumed,
+                        });
+                    }
+                    invocation_stack.pop();
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        if invocation_stack.last() == Some(&program_id.as_str()) {
+            if let Some(EntropyEvent::CallbackInvoked(event)) = decode_log_line(line) {
+                invoked = Some((event, None));
+                compute_units_consumed = None;
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,46 @@
+use {
+    crate::error::EntropySdkError,
+    entropy::accounts::{Config, Provider},
+};
+
+/// What a request costs the payer, as charged by the program.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RequestFee {
+    /// Paid into the provider's vault; scales with `compute_unit_limit`
+    /// above the provider's default.
+    pub provider_fee: u64,
+    /// Protocol fee paid into the Pyth fee vault.
+    pub pyth_fee: u64,
+    /// Rent deposit for the request account, refunded to the payer when the
+    /// request is revealed.
+    pub request_rent: u64,
+}
+
+impl RequestFee {
+    /// Fees kept by the provider and protocol, excluding the refundable rent.
+    pub fn fee(&self) -> u64 {
+        self.provider_fee.saturating_add(self.pyth_fee)
+    }
+
+    /// Lamports the payer needs on hand to submit the request.
+    pub fn total(&self) -> u64 {
+        self.fee().saturating_add(self.request_rent)
+    }
+}
+
+/// Reproduces the fees the program charges for a request with
+/// `compute_unit_limit` against `provider`. `request_rent` is left at zero.
+pub fn request_fee(
+    config: &Config,
+    provider: &Provider,
+    compute_unit_limit: u32,
+) -> Result<RequestFee, EntropySdkError> {
+    let provider_fee = provider
+        .calculate_provider_fee(compute_unit_limit)
+        .map_err(|_| EntropySdkError::FeeOverflow)?;
+    Ok(RequestFee {
+        provider_fee,
+        pyth_fee: config.pyth_fee_lamports,
+        request_rent: 0,
+    })
+}
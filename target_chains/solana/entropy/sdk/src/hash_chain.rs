@@ -0,0 +1,112 @@
+//! Provider hash chains.
+//!
+//! A provider commits to the last element of a chain `seed, h(seed), ...,
+//! h^length(seed)` when registering at sequence number `start_sequence`. The
+//! contribution for request `sequence` is the element `sequence -
+//! start_sequence` hashes before the commitment, so it can be revealed in any
+//! order.
+
+use {
+    crate::error::EntropySdkError,
+    solana_sdk::hash::{hash, hashv},
+};
+
+const DERIVATION_DOMAIN: &[u8] = b"entropy-hash-chain";
+
+/// Length of [`HashChain::to_bytes`]: seed, length and start sequence.
+pub const HASH_CHAIN_SERIALIZED_LEN: usize = 32 + 8 + 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashChain {
+    seed: [u8; 32],
+    start_sequence: u64,
+    /// `chain[i]` is `h^i(seed)`; the last element is the commitment.
+    chain: Vec<[u8; 32]>,
+}
+
+impl HashChain {
+    pub fn from_seed(seed: [u8; 32], length: u64) -> Self {
+        let mut chain = Vec::with_capacity(length as usize + 1);
+        chain.push(seed);
+        for index in 0..length as usize {
+            chain.push(hash(&chain[index]).to_bytes());
+        }
+        Self {
+            seed,
+            start_sequence: 0,
+            chain,
+        }
+    }
+
+    /// Deterministically derives a chain from a long-lived secret, so a
+    /// keeper can rebuild it after a restart. Use a distinct `nonce` for each
+    /// registration (e.g. the provider's sequence number at that time).
+    pub fn derive(secret: &[u8], nonce: u64, length: u64) -> Self {
+        let seed = hashv(&[DERIVATION_DOMAIN, secret, &nonce.to_le_bytes()]).to_bytes();
+        Self::from_seed(seed, length)
+    }
+
+    /// Records the provider's commitment sequence number for this chain, as
+    /// read back from the provider account after registration.
+    pub fn set_start_sequence(&mut self, start_sequence: u64) {
+        self.start_sequence = start_sequence;
+    }
+
+    pub fn seed(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    pub fn length(&self) -> u64 {
+        (self.chain.len() - 1) as u64
+    }
+
+    pub fn start_sequence(&self) -> u64 {
+        self.start_sequence
+    }
+
+    pub fn commitment(&self) -> [u8; 32] {
+        self.chain[self.chain.len() - 1]
+    }
+
+    /// Returns the provider contribution for `sequence`, or `None` if the
+    /// sequence falls outside this chain.
+    pub fn contribution_for_sequence(&self, sequence: u64) -> Option<[u8; 32]> {
+        let offset = sequence.checked_sub(self.start_sequence)?;
+        let index = self.length().checked_sub(offset)?;
+        self.chain.get(usize::try_from(index).ok()?).copied()
+    }
+
+    /// Serializes the seed, length and start sequence; the chain itself is
+    /// recomputed by [`HashChain::from_bytes`].
+    pub fn to_bytes(&self) -> [u8; HASH_CHAIN_SERIALIZED_LEN] {
+        let mut bytes = [0u8; HASH_CHAIN_SERIALIZED_LEN];
+        bytes[..32].copy_from_slice(&self.seed);
+        bytes[32..40].copy_from_slice(&self.length().to_le_bytes());
+        bytes[40..].copy_from_slice(&self.start_sequence.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EntropySdkError> {
+        let bytes: &[u8; HASH_CHAIN_SERIALIZED_LEN] = bytes
+            .try_into()
+            .map_err(|_| EntropySdkError::InvalidHashChain)?;
+        let (seed, rest) = bytes.split_at(32);
+        let (length, start_sequence) = rest.split_at(8);
+
+        let mut chain = Self::from_seed(
+            seed.try_into()
+                .map_err(|_| EntropySdkError::InvalidHashChain)?,
+            u64::from_le_bytes(
+                length
+                    .try_into()
+                    .map_err(|_| EntropySdkError::InvalidHashChain)?,
+            ),
+        );
+        chain.set_start_sequence(u64::from_le_bytes(
+            start_sequence
+                .try_into()
+                .map_err(|_| EntropySdkError::InvalidHashChain)?,
+        ));
+        Ok(chain)
+    }
+}
@@ -0,0 +1,1460 @@
+//! Anchor-format IDL for the entropy program, for TypeScript clients.
+//!
+//! Every struct is described field by field with its Rust offset and size, so
+//! `tests/test_idl.rs` can check the IDL against the actual `#[repr(C)]`
+//! layouts. The Pod structs spell out their padding, so their bytes match
+//! the Borsh encoding Anchor clients use, and `RequestWithCallback`'s trailing
+//! callback accounts and data are length-prefixed like Borsh vectors. Events
+//! are described under `types` only: entropy logs the discriminator and body
+//! as separate `sol_log_data` fields, which Anchor's event parser does not
+//! expect.
+
+use {
+    entropy::{
+        accounts::{
+            Account, AggregateContribution, AggregateRequest, CallbackAccountList,
+            CallbackFailureStats, CallbackMeta, Config, GovernanceConfig, KeeperLease,
+            ProgramRegistry, Provider, ProviderAllowlist, ProviderDirectoryPage, RegisteredProgram,
+            Request,
+        },
+        constants::{
+            CALLBACK_ACCOUNT_LIST_SEED, CALLBACK_FAILURES_SEED, CALLBACK_IX_DATA_LEN,
+            COMMITMENT_METADATA_LEN, CONFIG_SEED, ENTROPY_SIGNER_SEED, GOVERNANCE_SEED,
+            KEEPER_LEASE_SEED, MAX_AGGREGATE_PROVIDERS, MAX_ALLOWED_PROVIDERS,
+            MAX_CALLBACK_ACCOUNTS, MAX_CALLBACK_ACCOUNT_LIST_LEN, MAX_REGISTERED_PROGRAMS,
+            PROVIDER_ALLOWLIST_SEED, PROVIDER_DIRECTORY_PAGE_LEN, PROVIDER_SEED,
+            PROVIDER_VAULT_SEED, PYTH_FEE_VAULT_SEED, URI_LEN,
+        },
+        error::EntropyError,
+        events::{
+            AggregateRandomnessRevealed, AggregateRequestCreated, CallbackInvoked,
+            DefaultProviderSet, PythFeeSet, RandomnessRevealed, RequestCreated,
+            UserRandomnessGenerated,
+        },
+        instruction::{
+            EntropyInstruction, InitializeArgs, InitializeGovernanceArgs, PreviewRequestArgs,
+            RefreshKeeperLeaseArgs, RegisterProviderArgs, RequestAggregateHeader, RequestArgs,
+            RequestPreview, RequestV2Header, RequestWithCallbackHeader, RevealAggregateArgs,
+            RevealArgs, SetCallbackSkipPolicyArgs, SetDefaultProviderArgs, SetProgramRegistryArgs,
+            SetProviderAllowlistArgs, SetPythFeeArgs, WithdrawProviderFeesArgs,
+            WriteCallbackAccountListArgs,
+        },
+    },
+    serde_json::{json, Value},
+    solana_sdk::{pubkey::Pubkey, system_program, sysvar::slot_hashes},
+};
+
+pub const IDL_VERSION: &str = "0.1.0";
+
+#[derive(Clone, Debug)]
+pub struct IdlField {
+    pub name: &'static str,
+    pub ty: Value,
+    /// Offset and size of the Rust field; `None` for the variable-length
+    /// tail of `RequestWithCallback`.
+    pub layout: Option<(usize, usize)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct IdlStruct {
+    pub name: &'static str,
+    /// `size_of` the Rust type, or `None` if it has a variable-length tail.
+    pub size: Option<usize>,
+    pub fields: Vec<IdlField>,
+}
+
+fn field_size<T, F>(_: fn(&T) -> &F) -> usize {
+    core::mem::size_of::<F>()
+}
+
+macro_rules! idl_struct {
+    ($name:literal, $ty:ty { $($field:ident: $idl_ty:expr),* $(,)? }) => {
+        IdlStruct {
+            name: $name,
+            size: Some(core::mem::size_of::<$ty>()),
+            fields: vec![$(IdlField {
+                name: stringify!($field),
+                ty: $idl_ty,
+                layout: Some((
+                    core::mem::offset_of!($ty, $field),
+                    field_size(|value: &$ty| &value.$field),
+                )),
+            }),*],
+        }
+    };
+}
+
+fn pubkey() -> Value {
+    json!("pubkey")
+}
+
+fn bytes(len: usize) -> Value {
+    json!({ "array": ["u8", len] })
+}
+
+fn defined(name: &str) -> Value {
+    json!({ "defined": { "name": name } })
+}
+
+/// All struct types referenced by the IDL, in the order they are emitted.
+pub fn idl_structs() -> Vec<IdlStruct> {
+    let mut request_with_callback = idl_struct!(
+        "RequestWithCallbackArgs",
+        RequestWithCallbackHeader {
+            user_randomness: bytes(32),
+            compute_unit_limit: json!("u32"),
+            ttl_slots: json!("u32"),
+            min_reveal_delay_slots: json!("u16"),
+            include_contributions: json!("u8"),
+            random_number_version: json!("u8"),
+            refund_address: pubkey(),
+            requester_signer_bump: json!("u8"),
+            _padding0: bytes(3),
+        }
+    );
+    let mut request_v2 = idl_struct!(
+        "RequestV2Args",
+        RequestV2Header {
+            compute_unit_limit: json!("u32"),
+            ttl_slots: json!("u32"),
+            min_reveal_delay_slots: json!("u16"),
+            include_contributions: json!("u8"),
+            random_number_version: json!("u8"),
+            refund_address: pubkey(),
+            requester_signer_bump: json!("u8"),
+            _padding0: bytes(3),
+        }
+    );
+    let mut request_aggregate = idl_struct!(
+        "RequestAggregateArgs",
+        RequestAggregateHeader {
+            user_randomness: bytes(32),
+            compute_unit_limit: json!("u32"),
+            ttl_slots: json!("u32"),
+            num_providers: json!("u8"),
+            threshold: json!("u8"),
+            min_reveal_delay_slots: json!("u16"),
+            use_blockhash: json!("u8"),
+            random_number_version: json!("u8"),
+            requester_signer_bump: json!("u8"),
+            _padding0: bytes(1),
+            refund_address: pubkey(),
+        }
+    );
+    // `callback_accounts_len` doubles as the Borsh length prefix of the
+    // callback account vector that follows the header.
+    for args in [
+        &mut request_with_callback,
+        &mut request_v2,
+        &mut request_aggregate,
+    ] {
+        args.size = None;
+        args.fields.extend([
+            IdlField {
+                name: "callback_accounts",
+                ty: json!({ "vec": defined("CallbackMeta") }),
+                layout: None,
+            },
+            IdlField {
+                name: "callback_ix_data",
+                ty: json!("bytes"),
+                layout: None,
+            },
+        ]);
+    }
+
+    vec![
+        idl_struct!(
+            "InitializeArgs",
+            InitializeArgs {
+                admin: pubkey(),
+                pyth_fee_lamports: json!("u64"),
+                default_provider: pubkey(),
+            }
+        ),
+        idl_struct!(
+            "RegisterProviderArgs",
+            RegisterProviderArgs {
+                fee_lamports: json!("u64"),
+                commitment: bytes(32),
+                commitment_metadata_len: json!("u16"),
+                _padding0: bytes(6),
+                commitment_metadata: bytes(COMMITMENT_METADATA_LEN),
+                chain_length: json!("u64"),
+                uri_len: json!("u16"),
+                uri: bytes(URI_LEN),
+                require_https: json!("u8"),
+                _padding1: bytes(5),
+            }
+        ),
+        idl_struct!(
+            "RequestArgs",
+            RequestArgs {
+                user_commitment: bytes(32),
+                use_blockhash: json!("u8"),
+                random_number_version: json!("u8"),
+                min_reveal_delay_slots: json!("u16"),
+                compute_unit_limit: json!("u32"),
+                ttl_slots: json!("u32"),
+                refund_address: pubkey(),
+                requester_signer_bump: json!("u8"),
+                _padding0: bytes(3),
+            }
+        ),
+        request_with_callback,
+        request_v2,
+        request_aggregate,
+        idl_struct!(
+            "RevealArgs",
+            RevealArgs {
+                user_contribution: bytes(32),
+                provider_contribution: bytes(32),
+            }
+        ),
+        idl_struct!(
+            "RevealAggregateArgs",
+            RevealAggregateArgs {
+                user_contribution: bytes(32),
+                provider_contribution: bytes(32),
+                provider_index: json!("u8"),
+                _padding0: bytes(7),
+            }
+        ),
+        idl_struct!(
+            "InitializeGovernanceArgs",
+            InitializeGovernanceArgs {
+                wormhole_program: pubkey(),
+                emitter_address: bytes(32),
+                emitter_chain: json!("u16"),
+                _padding0: bytes(6),
+            }
+        ),
+        idl_struct!(
+            "SetPythFeeArgs",
+            SetPythFeeArgs {
+                pyth_fee_lamports: json!("u64"),
+            }
+        ),
+        idl_struct!(
+            "SetDefaultProviderArgs",
+            SetDefaultProviderArgs {
+                default_provider: pubkey(),
+            }
+        ),
+        idl_struct!(
+            "WithdrawProviderFeesArgs",
+            WithdrawProviderFeesArgs {
+                amount: json!("u64"),
+            }
+        ),
+        idl_struct!(
+            "SetProgramRegistryArgs",
+            SetProgramRegistryArgs {
+                program_ids: json!({ "array": [pubkey(), MAX_REGISTERED_PROGRAMS] }),
+                num_programs: json!("u8"),
+                _padding0: bytes(7),
+            }
+        ),
+        idl_struct!(
+            "SetProviderAllowlistArgs",
+            SetProviderAllowlistArgs {
+                providers: json!({ "array": [pubkey(), MAX_ALLOWED_PROVIDERS] }),
+                num_providers: json!("u8"),
+                permissioned_providers: json!("u8"),
+                _padding0: bytes(6),
+            }
+        ),
+        idl_struct!(
+            "RefreshKeeperLeaseArgs",
+            RefreshKeeperLeaseArgs {
+                keeper: bytes(32),
+                lease_slots: json!("u64"),
+            }
+        ),
+        idl_struct!(
+            "PreviewRequestArgs",
+            PreviewRequestArgs {
+                user_commitment: bytes(32),
+            }
+        ),
+        idl_struct!(
+            "WriteCallbackAccountListArgs",
+            WriteCallbackAccountListArgs {
+                callback_accounts: json!({
+                    "array": [defined("CallbackMeta"), MAX_CALLBACK_ACCOUNTS]
+                }),
+                num_accounts: json!("u8"),
+                _padding0: bytes(1),
+            }
+        ),
+        idl_struct!(
+            "SetCallbackSkipPolicyArgs",
+            SetCallbackSkipPolicyArgs {
+                window_slots: json!("u32"),
+                max_failures: json!("u16"),
+                _padding0: bytes(2),
+            }
+        ),
+        idl_struct!(
+            "RequestPreview",
+            RequestPreview {
+                sequence_number: json!("u64"),
+                num_hashes: json!("u32"),
+                _padding0: bytes(4),
+                commitment: bytes(32),
+            }
+        ),
+        idl_struct!(
+            "CallbackMeta",
+            CallbackMeta {
+                pubkey: pubkey(),
+                is_signer: json!("u16"),
+                is_writable: json!("u16"),
+            }
+        ),
+        idl_struct!(
+            "Config",
+            Config {
+                discriminator: bytes(8),
+                admin: pubkey(),
+                pyth_fee_lamports: json!("u64"),
+                default_provider: pubkey(),
+                proposed_admin: pubkey(),
+                seed: bytes(32),
+                bump: json!("u8"),
+                permissioned_providers: json!("u8"),
+                entropy_signer_bump: json!("u8"),
+                program_version: json!("u8"),
+                provider_directory_len: json!("u32"),
+            }
+        ),
+        idl_struct!(
+            "Provider",
+            Provider {
+                discriminator: bytes(8),
+                provider_authority: pubkey(),
+                fee_lamports: json!("u64"),
+                original_commitment: bytes(32),
+                original_commitment_sequence_number: json!("u64"),
+                commitment_metadata_len: json!("u16"),
+                commitment_metadata: bytes(COMMITMENT_METADATA_LEN),
+                uri_len: json!("u16"),
+                uri: bytes(URI_LEN),
+                outstanding_requests: json!("u32"),
+                end_sequence_number: json!("u64"),
+                sequence_number: json!("u64"),
+                current_commitment: bytes(32),
+                current_commitment_sequence_number: json!("u64"),
+                fee_manager: pubkey(),
+                max_num_hashes: json!("u32"),
+                default_compute_unit_limit: json!("u32"),
+                bump: json!("u8"),
+                in_provider_directory: json!("u8"),
+                callback_skip_max_failures: json!("u16"),
+                callback_skip_window_slots: json!("u32"),
+                reveal_count: json!("u64"),
+                reveal_latency_sum_slots: json!("u64"),
+                reveal_latency_max_slots: json!("u64"),
+                reserved_fee_lamports: json!("u64"),
+            }
+        ),
+        idl_struct!(
+            "Request",
+            Request {
+                discriminator: bytes(8),
+                provider: pubkey(),
+                sequence_number: json!("u64"),
+                num_hashes: json!("u32"),
+                commitment: bytes(32),
+                ttl_slots: json!("u32"),
+                request_slot: json!("u64"),
+                requester_program_id: pubkey(),
+                callback_account_list: pubkey(),
+                payer: pubkey(),
+                use_blockhash: json!("u8"),
+                callback_status: json!("u8"),
+                min_reveal_delay_slots: json!("u16"),
+                compute_unit_limit: json!("u32"),
+                callback_accounts_len: json!("u8"),
+                include_contributions: json!("u8"),
+                callback_accounts: json!({
+                    "array": [defined("CallbackMeta"), MAX_CALLBACK_ACCOUNTS]
+                }),
+                callback_ix_data_len: json!("u16"),
+                callback_ix_data: bytes(CALLBACK_IX_DATA_LEN),
+                bump: json!("u8"),
+                random_number_version: json!("u8"),
+                provider_bump: json!("u8"),
+                entropy_signer_bump: json!("u8"),
+                provider_fee: json!("u64"),
+                refund_address: pubkey(),
+            }
+        ),
+        idl_struct!(
+            "CallbackAccountList",
+            CallbackAccountList {
+                discriminator: bytes(8),
+                request_account: pubkey(),
+                num_accounts: json!("u8"),
+                bump: json!("u8"),
+                _padding0: bytes(6),
+                callback_accounts: json!({
+                    "array": [defined("CallbackMeta"), MAX_CALLBACK_ACCOUNT_LIST_LEN]
+                }),
+            }
+        ),
+        idl_struct!(
+            "GovernanceConfig",
+            GovernanceConfig {
+                discriminator: bytes(8),
+                wormhole_program: pubkey(),
+                emitter_address: bytes(32),
+                emitter_chain: json!("u16"),
+                _padding0: bytes(6),
+                last_executed_sequence: json!("u64"),
+                bump: json!("u8"),
+                _padding1: bytes(7),
+            }
+        ),
+        idl_struct!(
+            "CallbackFailureStats",
+            CallbackFailureStats {
+                discriminator: bytes(8),
+                provider_authority: pubkey(),
+                requester_program_id: pubkey(),
+                total_failures: json!("u64"),
+                total_callbacks: json!("u64"),
+                window_start_slot: json!("u64"),
+                window_failures: json!("u32"),
+                window_callbacks: json!("u32"),
+                bump: json!("u8"),
+                _padding0: bytes(7),
+            }
+        ),
+        idl_struct!(
+            "KeeperLease",
+            KeeperLease {
+                discriminator: bytes(8),
+                provider_authority: pubkey(),
+                keeper: bytes(32),
+                refreshed_slot: json!("u64"),
+                lease_slots: json!("u64"),
+                bump: json!("u8"),
+                _padding0: bytes(7),
+            }
+        ),
+        idl_struct!(
+            "RegisteredProgram",
+            RegisteredProgram {
+                program_id: pubkey(),
+                entropy_signer: pubkey(),
+            }
+        ),
+        idl_struct!(
+            "ProgramRegistry",
+            ProgramRegistry {
+                discriminator: bytes(8),
+                num_programs: json!("u8"),
+                bump: json!("u8"),
+                _padding0: bytes(6),
+                programs: json!({
+                    "array": [defined("RegisteredProgram"), MAX_REGISTERED_PROGRAMS]
+                }),
+            }
+        ),
+        idl_struct!(
+            "ProviderAllowlist",
+            ProviderAllowlist {
+                discriminator: bytes(8),
+                num_providers: json!("u8"),
+                bump: json!("u8"),
+                _padding0: bytes(6),
+                providers: json!({ "array": [pubkey(), MAX_ALLOWED_PROVIDERS] }),
+            }
+        ),
+        idl_struct!(
+            "ProviderDirectoryPage",
+            ProviderDirectoryPage {
+                discriminator: bytes(8),
+                page: json!("u32"),
+                num_providers: json!("u8"),
+                bump: json!("u8"),
+                _padding0: bytes(2),
+                providers: json!({ "array": [pubkey(), PROVIDER_DIRECTORY_PAGE_LEN] }),
+            }
+        ),
+        idl_struct!(
+            "AggregateContribution",
+            AggregateContribution {
+                provider: pubkey(),
+                sequence_number: json!("u64"),
+                provider_commitment: bytes(32),
+                contribution: bytes(32),
+                provider_fee: json!("u64"),
+                num_hashes: json!("u32"),
+                revealed: json!("u8"),
+                _padding0: bytes(3),
+            }
+        ),
+        idl_struct!(
+            "AggregateRequest",
+            AggregateRequest {
+                discriminator: bytes(8),
+                user_commitment: bytes(32),
+                requester_program_id: pubkey(),
+                payer: pubkey(),
+                request_slot: json!("u64"),
+                ttl_slots: json!("u32"),
+                compute_unit_limit: json!("u32"),
+                num_providers: json!("u8"),
+                threshold: json!("u8"),
+                revealed_count: json!("u8"),
+                callback_accounts_len: json!("u8"),
+                callback_ix_data_len: json!("u16"),
+                min_reveal_delay_slots: json!("u16"),
+                refund_address: pubkey(),
+                use_blockhash: json!("u8"),
+                random_number_version: json!("u8"),
+                _padding0: bytes(6),
+                contributions: json!({
+                    "array": [defined("AggregateContribution"), MAX_AGGREGATE_PROVIDERS]
+                }),
+                callback_accounts: json!({
+                    "array": [defined("CallbackMeta"), MAX_CALLBACK_ACCOUNTS]
+                }),
+                callback_ix_data: bytes(CALLBACK_IX_DATA_LEN),
+            }
+        ),
+        idl_struct!(
+            "RequestCreated",
+            RequestCreated {
+                provider: pubkey(),
+                requester_program_id: pubkey(),
+                request_account: pubkey(),
+                payer: pubkey(),
+                sequence_number: json!("u64"),
+                request_slot: json!("u64"),
+                compute_unit_limit: json!("u32"),
+                use_blockhash: json!("u8"),
+                callback_status: json!("u8"),
+                random_number_version: json!("u8"),
+                _padding0: bytes(1),
+            }
+        ),
+        idl_struct!(
+            "RandomnessRevealed",
+            RandomnessRevealed {
+                provider: pubkey(),
+                requester_program_id: pubkey(),
+                request_account: pubkey(),
+                sequence_number: json!("u64"),
+                random_number: bytes(32),
+                callback_invoked: json!("u8"),
+                _padding0: bytes(7),
+                request_slot: json!("u64"),
+                reveal_slot: json!("u64"),
+            }
+        ),
+        idl_struct!(
+            "PythFeeSet",
+            PythFeeSet {
+                old_fee_lamports: json!("u64"),
+                new_fee_lamports: json!("u64"),
+            }
+        ),
+        idl_struct!(
+            "DefaultProviderSet",
+            DefaultProviderSet {
+                old_default_provider: pubkey(),
+                new_default_provider: pubkey(),
+            }
+        ),
+        idl_struct!(
+            "UserRandomnessGenerated",
+            UserRandomnessGenerated {
+                provider: pubkey(),
+                request_account: pubkey(),
+                sequence_number: json!("u64"),
+                user_randomness: bytes(32),
+            }
+        ),
+        idl_struct!(
+            "AggregateRequestCreated",
+            AggregateRequestCreated {
+                requester_program_id: pubkey(),
+                request_account: pubkey(),
+                payer: pubkey(),
+                request_slot: json!("u64"),
+                num_providers: json!("u8"),
+                threshold: json!("u8"),
+                _padding0: bytes(6),
+            }
+        ),
+        idl_struct!(
+            "AggregateRandomnessRevealed",
+            AggregateRandomnessRevealed {
+                requester_program_id: pubkey(),
+                request_account: pubkey(),
+                random_number: bytes(32),
+                request_slot: json!("u64"),
+                reveal_slot: json!("u64"),
+                revealed_mask: json!("u8"),
+                callback_invoked: json!("u8"),
+                _padding0: bytes(6),
+            }
+        ),
+        idl_struct!(
+            "CallbackInvoked",
+            CallbackInvoked {
+                provider: pubkey(),
+                requester_program_id: pubkey(),
+                request_account: pubkey(),
+                sequence_number: json!("u64"),
+                request_slot: json!("u64"),
+                reveal_slot: json!("u64"),
+                compute_unit_limit: json!("u32"),
+                _padding0: bytes(4),
+            }
+        ),
+    ]
+}
+
+fn account(name: &str, writable: bool, signer: bool) -> Value {
+    json!({ "name": name, "writable": writable, "signer": signer })
+}
+
+fn fixed_account(name: &str, address: &Pubkey) -> Value {
+    json!({ "name": name, "address": address.to_string() })
+}
+
+fn pda_account(name: &str, writable: bool, seeds: Value) -> Value {
+    json!({ "name": name, "writable": writable, "pda": { "seeds": seeds } })
+}
+
+/// The program's `ProgramData` account, which names its upgrade authority.
+fn program_data_account(optional: bool) -> Value {
+    json!({
+        "name": "program_data",
+        "optional": optional,
+        "docs": ["The program's ProgramData account under the upgradeable loader."],
+    })
+}
+
+fn const_seed(seed: &[u8]) -> Value {
+    json!({ "kind": "const", "value": seed })
+}
+
+fn account_seed(path: &str) -> Value {
+    json!({ "kind": "account", "path": path })
+}
+
+/// `args` names the Pod payload type; `None` for instructions without one.
+fn instruction(
+    instruction: EntropyInstruction,
+    name: &str,
+    args: Option<&str>,
+    accounts: Vec<Value>,
+) -> Value {
+    let args: Vec<Value> = args
+        .map(|args| json!({ "name": "args", "type": defined(args) }))
+        .into_iter()
+        .collect();
+    json!({
+        "name": name,
+        "discriminator": instruction.discriminator(),
+        "accounts": accounts,
+        "args": args,
+    })
+}
+
+fn initialize_accounts() -> Vec<Value> {
+    vec![
+        json!({
+            "name": "payer",
+            "writable": true,
+            "signer": true,
+            "docs": ["Must be the program's upgrade authority."],
+        }),
+        pda_account("config", true, json!([const_seed(CONFIG_SEED)])),
+        pda_account(
+            "pyth_fee_vault",
+            true,
+            json!([const_seed(PYTH_FEE_VAULT_SEED)]),
+        ),
+        fixed_account("system_program", &system_program::id()),
+        program_data_account(false),
+    ]
+}
+
+/// `RequestV2` also writes the config, whose seed it advances.
+fn request_accounts(config_writable: bool) -> Vec<Value> {
+    vec![
+        json!({
+            "name": "requester_signer",
+            "signer": true,
+            "docs": ["PDA of the requester program with seeds [\"requester_signer\", entropy program id]."],
+        }),
+        account("payer", true, true),
+        account("requester_program", false, false),
+        json!({
+            "name": "request_account",
+            "writable": true,
+            "signer": true,
+            "docs": ["Fresh keypair; the program allocates the request account at this address."],
+        }),
+        account("provider", true, false),
+        account("provider_vault", true, false),
+        pda_account("config", config_writable, json!([const_seed(CONFIG_SEED)])),
+        pda_account(
+            "pyth_fee_vault",
+            true,
+            json!([const_seed(PYTH_FEE_VAULT_SEED)]),
+        ),
+        fixed_account("system_program", &system_program::id()),
+    ]
+}
+
+/// The provider's [`CallbackFailureStats`] for the request's requester
+/// program.
+fn callback_failure_stats_account(writable: bool) -> Value {
+    pda_account(
+        "callback_failure_stats",
+        writable,
+        json!([
+            const_seed(CALLBACK_FAILURES_SEED),
+            account_seed("provider_authority"),
+            {
+                "kind": "account",
+                "path": "request_account.requester_program_id",
+                "account": "Request"
+            }
+        ]),
+    )
+}
+
+fn admin_accounts() -> Vec<Value> {
+    vec![
+        json!({
+            "name": "admin",
+            "signer": true,
+            "docs": ["The config admin, or the upgrade authority if program_data follows."],
+        }),
+        pda_account("config", true, json!([const_seed(CONFIG_SEED)])),
+        program_data_account(true),
+    ]
+}
+
+fn instructions() -> Vec<Value> {
+    vec![
+        instruction(
+            EntropyInstruction::Initialize,
+            "initialize",
+            Some("InitializeArgs"),
+            initialize_accounts(),
+        ),
+        instruction(
+            EntropyInstruction::RegisterProvider,
+            "register_provider",
+            Some("RegisterProviderArgs"),
+            vec![
+                account("provider_authority", true, true),
+                pda_account(
+                    "provider",
+                    true,
+                    json!([
+                        const_seed(PROVIDER_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                pda_account(
+                    "provider_vault",
+                    true,
+                    json!([
+                        const_seed(PROVIDER_VAULT_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                fixed_account("system_program", &system_program::id()),
+                pda_account("config", true, json!([const_seed(CONFIG_SEED)])),
+                json!({
+                    "name": "provider_directory",
+                    "writable": true,
+                    "docs": [
+                        "Provider directory page with seeds [\"provider_directory\", page as u32 LE], where",
+                        "page = config.provider_directory_len / PROVIDER_DIRECTORY_PAGE_LEN. Only used by a",
+                        "provider's first registration."
+                    ],
+                }),
+                json!({
+                    "name": "provider_allowlist",
+                    "optional": true,
+                    "pda": { "seeds": [const_seed(PROVIDER_ALLOWLIST_SEED)] },
+                    "docs": ["Required while config.permissioned_providers is set."],
+                }),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::Request,
+            "request",
+            Some("RequestArgs"),
+            request_accounts(false),
+        ),
+        instruction(
+            EntropyInstruction::RequestWithCallback,
+            "request_with_callback",
+            Some("RequestWithCallbackArgs"),
+            request_accounts(false),
+        ),
+        instruction(
+            EntropyInstruction::RequestV2,
+            "request_v2",
+            Some("RequestV2Args"),
+            request_accounts(true),
+        ),
+        instruction(
+            EntropyInstruction::RevealWithCallback,
+            "reveal_with_callback",
+            Some("RevealArgs"),
+            vec![
+                account("request_account", true, false),
+                account("provider", true, false),
+                fixed_account("slot_hashes", &slot_hashes::id()),
+                pda_account(
+                    "entropy_signer",
+                    false,
+                    json!([const_seed(ENTROPY_SIGNER_SEED)]),
+                ),
+                json!({
+                    "name": "callback_program",
+                    "docs": ["The request's requester program. Callback accounts follow as remaining accounts, then the provider's callback failure stats for the program, which a provider with a callback skip policy requires."],
+                }),
+                fixed_account("system_program", &system_program::id()),
+                json!({
+                    "name": "payer",
+                    "writable": true,
+                    "docs": ["The request's payer; receives the request account's rent."],
+                }),
+                json!({
+                    "name": "callback_account_list",
+                    "writable": true,
+                    "optional": true,
+                    "pda": {
+                        "seeds": [
+                            const_seed(CALLBACK_ACCOUNT_LIST_SEED),
+                            account_seed("request_account")
+                        ]
+                    },
+                    "docs": [
+                        "Required if the request has a callback account list, which is closed into the",
+                        "payer. Its accounts follow the request's callback accounts."
+                    ],
+                }),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::WithdrawProviderFees,
+            "withdraw_provider_fees",
+            Some("WithdrawProviderFeesArgs"),
+            vec![
+                account("provider_authority", false, true),
+                pda_account(
+                    "provider",
+                    false,
+                    json!([
+                        const_seed(PROVIDER_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                pda_account(
+                    "provider_vault",
+                    true,
+                    json!([
+                        const_seed(PROVIDER_VAULT_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                json!({
+                    "name": "destination",
+                    "writable": true,
+                    "docs": ["Any account; receives the withdrawn fees."],
+                }),
+                fixed_account("system_program", &system_program::id()),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::Governance,
+            "governance",
+            None,
+            vec![
+                pda_account("config", true, json!([const_seed(CONFIG_SEED)])),
+                pda_account("governance", true, json!([const_seed(GOVERNANCE_SEED)])),
+                json!({
+                    "name": "posted_vaa",
+                    "docs": ["VAA account posted by the Wormhole core bridge."],
+                }),
+                json!({
+                    "name": "pyth_fee_vault",
+                    "writable": true,
+                    "optional": true,
+                    "pda": { "seeds": [const_seed(PYTH_FEE_VAULT_SEED)] },
+                    "docs": ["WithdrawFee only, followed by the recipient and the system program."],
+                }),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::InitializeGovernance,
+            "initialize_governance",
+            Some("InitializeGovernanceArgs"),
+            vec![
+                json!({
+                    "name": "admin",
+                    "signer": true,
+                    "docs": ["The config admin, or the upgrade authority if program_data follows."],
+                }),
+                account("payer", true, true),
+                pda_account("config", false, json!([const_seed(CONFIG_SEED)])),
+                pda_account("governance", true, json!([const_seed(GOVERNANCE_SEED)])),
+                fixed_account("system_program", &system_program::id()),
+                program_data_account(true),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::ClaimAdmin,
+            "claim_admin",
+            None,
+            vec![
+                account("upgrade_authority", false, true),
+                pda_account("config", true, json!([const_seed(CONFIG_SEED)])),
+                program_data_account(false),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::SetPythFee,
+            "set_pyth_fee",
+            Some("SetPythFeeArgs"),
+            admin_accounts(),
+        ),
+        instruction(
+            EntropyInstruction::SetDefaultProvider,
+            "set_default_provider",
+            Some("SetDefaultProviderArgs"),
+            admin_accounts(),
+        ),
+        instruction(
+            EntropyInstruction::CloseExpiredRequest,
+            "close_expired_request",
+            None,
+            vec![
+                account("request_account", true, false),
+                json!({
+                    "name": "payer",
+                    "writable": true,
+                    "docs": ["The request's payer; receives the request account's rent."],
+                }),
+                json!({
+                    "name": "provider",
+                    "writable": true,
+                    "docs": ["The request's provider PDA; its pending request count drops."],
+                }),
+                json!({
+                    "name": "provider_vault",
+                    "writable": true,
+                    "docs": ["The request's provider vault; refunds the provider fee."],
+                }),
+                fixed_account("system_program", &system_program::id()),
+                json!({
+                    "name": "callback_account_list",
+                    "writable": true,
+                    "optional": true,
+                    "pda": {
+                        "seeds": [
+                            const_seed(CALLBACK_ACCOUNT_LIST_SEED),
+                            account_seed("request_account")
+                        ]
+                    },
+                    "docs": ["Required if the request has a callback account list, which is closed into the payer."],
+                }),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::RefreshKeeperLease,
+            "refresh_keeper_lease",
+            Some("RefreshKeeperLeaseArgs"),
+            vec![
+                account("provider_authority", true, true),
+                pda_account(
+                    "provider",
+                    false,
+                    json!([
+                        const_seed(PROVIDER_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                pda_account(
+                    "keeper_lease",
+                    true,
+                    json!([
+                        const_seed(KEEPER_LEASE_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                fixed_account("system_program", &system_program::id()),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::CloseProvider,
+            "close_provider",
+            None,
+            vec![
+                account("provider_authority", true, true),
+                pda_account(
+                    "provider",
+                    true,
+                    json!([
+                        const_seed(PROVIDER_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                pda_account(
+                    "provider_vault",
+                    true,
+                    json!([
+                        const_seed(PROVIDER_VAULT_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                fixed_account("system_program", &system_program::id()),
+                pda_account("config", true, json!([const_seed(CONFIG_SEED)])),
+                json!({
+                    "name": "provider_directory",
+                    "writable": true,
+                    "docs": [
+                        "Provider directory page with seeds [\"provider_directory\", page as u32 LE] that",
+                        "lists provider_authority. Only read for a listed provider."
+                    ],
+                }),
+                json!({
+                    "name": "last_provider_directory",
+                    "writable": true,
+                    "docs": [
+                        "Provider directory page with seeds [\"provider_directory\", page as u32 LE], where",
+                        "page = (config.provider_directory_len - 1) / PROVIDER_DIRECTORY_PAGE_LEN. Only read",
+                        "for a listed provider."
+                    ],
+                }),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::RequestAggregate,
+            "request_aggregate",
+            Some("RequestAggregateArgs"),
+            vec![
+                json!({
+                    "name": "requester_signer",
+                    "signer": true,
+                    "docs": ["PDA of the requester program with seeds [\"requester_signer\", entropy program id]."],
+                }),
+                account("payer", true, true),
+                account("requester_program", false, false),
+                json!({
+                    "name": "request_account",
+                    "writable": true,
+                    "signer": true,
+                    "docs": ["Fresh keypair; the program allocates the aggregate request at this address."],
+                }),
+                pda_account("config", false, json!([const_seed(CONFIG_SEED)])),
+                pda_account(
+                    "pyth_fee_vault",
+                    true,
+                    json!([const_seed(PYTH_FEE_VAULT_SEED)]),
+                ),
+                json!({
+                    "name": "system_program",
+                    "address": system_program::id().to_string(),
+                    "docs": ["Followed by a writable provider and provider_vault pair per provider, as remaining accounts."],
+                }),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::RevealAggregate,
+            "reveal_aggregate",
+            Some("RevealAggregateArgs"),
+            vec![
+                account("request_account", true, false),
+                fixed_account("slot_hashes", &slot_hashes::id()),
+                pda_account(
+                    "entropy_signer",
+                    false,
+                    json!([const_seed(ENTROPY_SIGNER_SEED)]),
+                ),
+                json!({
+                    "name": "callback_program",
+                    "docs": ["The request's requester program."],
+                }),
+                fixed_account("system_program", &system_program::id()),
+                json!({
+                    "name": "refund_address",
+                    "writable": true,
+                    "docs": [
+                        "The request's refund address; receives the rent and unrevealed providers' fees.",
+                        "Followed by a writable provider and provider_vault pair per provider, in order,",
+                        "then the callback accounts."
+                    ],
+                }),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::CloseExpiredAggregateRequest,
+            "close_expired_aggregate_request",
+            None,
+            vec![
+                account("request_account", true, false),
+                json!({
+                    "name": "refund_address",
+                    "writable": true,
+                    "docs": ["The request's refund address; receives the rent and unrevealed providers' fees."],
+                }),
+                json!({
+                    "name": "system_program",
+                    "address": system_program::id().to_string(),
+                    "docs": ["Followed by a writable provider and provider_vault pair per provider, in order."],
+                }),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::SetProgramRegistry,
+            "set_program_registry",
+            Some("SetProgramRegistryArgs"),
+            vec![
+                json!({
+                    "name": "admin",
+                    "signer": true,
+                    "docs": ["The config admin, or the upgrade authority if program_data follows."],
+                }),
+                pda_account("config", false, json!([const_seed(CONFIG_SEED)])),
+                account("payer", true, true),
+                pda_account(
+                    "entropy_signer",
+                    true,
+                    json!([const_seed(ENTROPY_SIGNER_SEED)]),
+                ),
+                fixed_account("system_program", &system_program::id()),
+                program_data_account(true),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::SetProviderAllowlist,
+            "set_provider_allowlist",
+            Some("SetProviderAllowlistArgs"),
+            vec![
+                json!({
+                    "name": "admin",
+                    "signer": true,
+                    "docs": ["The config admin, or the upgrade authority if program_data follows."],
+                }),
+                pda_account("config", true, json!([const_seed(CONFIG_SEED)])),
+                account("payer", true, true),
+                pda_account(
+                    "provider_allowlist",
+                    true,
+                    json!([const_seed(PROVIDER_ALLOWLIST_SEED)]),
+                ),
+                fixed_account("system_program", &system_program::id()),
+                program_data_account(true),
+            ],
+        ),
+        {
+            let mut get_version =
+                instruction(EntropyInstruction::GetVersion, "get_version", None, vec![]);
+            get_version["returns"] = json!("u8");
+            get_version
+        },
+        instruction(
+            EntropyInstruction::InitializeIfNeeded,
+            "initialize_if_needed",
+            Some("InitializeArgs"),
+            initialize_accounts(),
+        ),
+        {
+            let mut preview_request = instruction(
+                EntropyInstruction::PreviewRequest,
+                "preview_request",
+                Some("PreviewRequestArgs"),
+                vec![account("provider", false, false)],
+            );
+            preview_request["returns"] = defined("RequestPreview");
+            preview_request
+        },
+        instruction(
+            EntropyInstruction::WriteCallbackAccountList,
+            "write_callback_account_list",
+            Some("WriteCallbackAccountListArgs"),
+            vec![
+                account("payer", true, true),
+                json!({
+                    "name": "request_account",
+                    "signer": true,
+                    "docs": ["The keypair of a request not made yet."],
+                }),
+                pda_account(
+                    "callback_account_list",
+                    true,
+                    json!([
+                        const_seed(CALLBACK_ACCOUNT_LIST_SEED),
+                        account_seed("request_account")
+                    ]),
+                ),
+                fixed_account("system_program", &system_program::id()),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::CloseCallbackAccountList,
+            "close_callback_account_list",
+            None,
+            vec![
+                json!({
+                    "name": "request_account",
+                    "signer": true,
+                    "docs": ["The keypair of a request not made yet."],
+                }),
+                pda_account(
+                    "callback_account_list",
+                    true,
+                    json!([
+                        const_seed(CALLBACK_ACCOUNT_LIST_SEED),
+                        account_seed("request_account")
+                    ]),
+                ),
+                json!({
+                    "name": "recipient",
+                    "writable": true,
+                    "docs": ["Any account; receives the list's rent."],
+                }),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::SetCallbackSkipPolicy,
+            "set_callback_skip_policy",
+            Some("SetCallbackSkipPolicyArgs"),
+            vec![
+                account("provider_authority", false, true),
+                pda_account(
+                    "provider",
+                    true,
+                    json!([
+                        const_seed(PROVIDER_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::ReportCallbackFailure,
+            "report_callback_failure",
+            None,
+            vec![
+                account("provider_authority", true, true),
+                pda_account(
+                    "provider",
+                    false,
+                    json!([
+                        const_seed(PROVIDER_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                json!({
+                    "name": "request_account",
+                    "writable": true,
+                    "docs": ["A pending callback request to the provider whose callback failed, not reported before."],
+                }),
+                callback_failure_stats_account(true),
+                fixed_account("system_program", &system_program::id()),
+            ],
+        ),
+        instruction(
+            EntropyInstruction::RevealSkippingCallback,
+            "reveal_skipping_callback",
+            Some("RevealArgs"),
+            vec![
+                account("provider_authority", false, true),
+                account("request_account", true, false),
+                pda_account(
+                    "provider",
+                    true,
+                    json!([
+                        const_seed(PROVIDER_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                fixed_account("slot_hashes", &slot_hashes::id()),
+                callback_failure_stats_account(false),
+                pda_account(
+                    "provider_vault",
+                    true,
+                    json!([
+                        const_seed(PROVIDER_VAULT_SEED),
+                        account_seed("provider_authority")
+                    ]),
+                ),
+                json!({
+                    "name": "payer",
+                    "writable": true,
+                    "docs": ["The request's payer; receives the request account's rent and the provider fee."],
+                }),
+                fixed_account("system_program", &system_program::id()),
+                json!({
+                    "name": "callback_account_list",
+                    "writable": true,
+                    "optional": true,
+                    "pda": {
+                        "seeds": [
+                            const_seed(CALLBACK_ACCOUNT_LIST_SEED),
+                            account_seed("request_account")
+                        ]
+                    },
+                    "docs": ["Required if the request has a callback account list, which is closed into the payer."],
+                }),
+            ],
+        ),
+    ]
+}
+
+fn type_def(idl_struct: &IdlStruct) -> Value {
+    let fields: Vec<Value> = idl_struct
+        .fields
+        .iter()
+        .map(|field| json!({ "name": field.name, "type": field.ty }))
+        .collect();
+    json!({
+        "name": idl_struct.name,
+        "type": { "kind": "struct", "fields": fields },
+    })
+}
+
+fn errors() -> Vec<Value> {
+    [
+        EntropyError::InvalidInstruction,
+        EntropyError::InvalidAccount,
+        EntropyError::InvalidPda,
+        EntropyError::NotImplemented,
+        EntropyError::OutOfRandomness,
+        EntropyError::LastRevealedTooOld,
+        EntropyError::IncorrectRevelation,
+        EntropyError::BlockhashUnavailable,
+        EntropyError::InvalidRevealCall,
+        EntropyError::InsufficientGas,
+        EntropyError::Unauthorized,
+        EntropyError::InvalidVaa,
+        EntropyError::InvalidGovernanceDataSource,
+        EntropyError::OldGovernanceMessage,
+        EntropyError::InvalidGovernanceMessage,
+        EntropyError::InvalidGovernanceTarget,
+        EntropyError::RequestExpired,
+        EntropyError::RequestNotExpired,
+        EntropyError::ProviderHasOutstandingRequests,
+        EntropyError::AccountNotWritable,
+        EntropyError::WrongSystemProgram,
+        EntropyError::WrongConfig,
+        EntropyError::WrongPythFeeVault,
+        EntropyError::WrongProvider,
+        EntropyError::WrongProviderVault,
+        EntropyError::WrongRequesterSigner,
+        EntropyError::WrongEntropySigner,
+        EntropyError::WrongGovernance,
+        EntropyError::WrongSlotHashes,
+        EntropyError::WrongProgramData,
+        EntropyError::InvalidProgramData,
+        EntropyError::WrongAccountOwner,
+        EntropyError::WrongAccountSize,
+        EntropyError::WrongAccountType,
+        EntropyError::AccountAlreadyInitialized,
+        EntropyError::InvalidVault,
+        EntropyError::RequestAccountNotEmpty,
+        EntropyError::ProviderAuthorityMismatch,
+        EntropyError::WrongPayer,
+        EntropyError::WrongCallbackProgram,
+        EntropyError::CallbackAccountMismatch,
+        EntropyError::EntropyInCallbackAccounts,
+        EntropyError::TooManyCallbackAccounts,
+        EntropyError::CallbackDataTooLong,
+        EntropyError::FeeTransferFailed,
+        EntropyError::WrongRecipient,
+        EntropyError::KeeperLeaseHeld,
+        EntropyError::WrongKeeperLease,
+        EntropyError::InvalidAggregateThreshold,
+        EntropyError::DuplicateAggregateProvider,
+        EntropyError::RevealTooEarly,
+        EntropyError::InvalidProgramRegistry,
+        EntropyError::InvalidProviderUri,
+        EntropyError::ProviderNotAllowed,
+        EntropyError::InvalidProviderAllowlist,
+        EntropyError::WrongProviderAllowlist,
+        EntropyError::ConfigMismatch,
+        EntropyError::WrongProviderDirectory,
+        EntropyError::WrongCallbackAccountList,
+        EntropyError::InvalidCallbackSkipPolicy,
+        EntropyError::WrongCallbackFailureStats,
+        EntropyError::CallbackSkipNotAllowed,
+        EntropyError::ProviderVaultShort,
+    ]
+    .into_iter()
+    .map(|error| {
+        json!({
+            "name": format!("{error:?}"),
+            "msg": error.to_string(),
+            "code": error as u32,
+        })
+    })
+    .collect()
+}
+
+/// Builds the IDL. `program_id` fills the top-level `address`; the checked-in
+/// artifact uses the default pubkey as a placeholder.
+pub fn entropy_idl(program_id: &Pubkey) -> Value {
+    json!({
+        "address": program_id.to_string(),
+        "metadata": {
+            "name": "entropy",
+            "version": IDL_VERSION,
+            "spec": "0.1.0",
+            "description": "Pyth Entropy on Solana",
+        },
+        "instructions": instructions(),
+        "accounts": [
+            { "name": "Config", "discriminator": Config::discriminator() },
+            { "name": "Provider", "discriminator": Provider::discriminator() },
+            { "name": "Request", "discriminator": Request::discriminator() },
+            {
+                "name": "GovernanceConfig",
+                "discriminator": GovernanceConfig::discriminator(),
+            },
+            { "name": "KeeperLease", "discriminator": KeeperLease::discriminator() },
+            {
+                "name": "AggregateRequest",
+                "discriminator": AggregateRequest::discriminator(),
+            },
+            {
+                "name": "ProgramRegistry",
+                "discriminator": ProgramRegistry::discriminator(),
+            },
+            {
+                "name": "ProviderAllowlist",
+                "discriminator": ProviderAllowlist::discriminator(),
+            },
+            {
+                "name": "ProviderDirectoryPage",
+                "discriminator": ProviderDirectoryPage::discriminator(),
+            },
+            {
+                "name": "CallbackAccountList",
+                "discriminator": CallbackAccountList::discriminator(),
+            },
+            {
+                "name": "CallbackFailureStats",
+                "discriminator": CallbackFailureStats::discriminator(),
+            },
+        ],
+        "errors": errors(),
+        "types": idl_structs().iter().map(type_def).collect::<Vec<_>>(),
+    })
+}
@@ -0,0 +1,1180 @@
+#[allow(deprecated)]
+use {
+    bytemuck::Zeroable,
+    entropy::{
+        accounts::{
+            AggregateContribution, AggregateRequest, CallbackAccountList, CallbackMeta, Request,
+            RequestRef,
+        },
+        constants::{
+            CALLBACK_IX_DATA_LEN, COMMITMENT_METADATA_LEN, MAX_AGGREGATE_PROVIDERS,
+            MAX_ALLOWED_PROVIDERS, MAX_CALLBACK_ACCOUNTS, MAX_REGISTERED_PROGRAMS, URI_LEN,
+        },
+        instruction::{
+            CallbackRequestHeader, ClaimAdminArgs, CloseCallbackAccountListArgs,
+            CloseExpiredAggregateRequestArgs, CloseExpiredRequestArgs, CloseProviderArgs,
+            EntropyInstruction, GetVersionArgs, GovernanceArgs, InitializeArgs,
+            InitializeGovernanceArgs, InitializeIfNeededArgs, PreviewRequestArgs,
+            RefreshKeeperLeaseArgs, RegisterProviderArgs, ReportCallbackFailureArgs,
+            RequestAggregateHeader, RequestArgs, RequestV2Header, RequestWithCallbackHeader,
+            RevealAggregateArgs, RevealArgs, RevealSkippingCallbackArgs, SetCallbackSkipPolicyArgs,
+            SetDefaultProviderArgs, SetProgramRegistryArgs, SetProviderAllowlistArgs,
+            SetPythFeeArgs, WithdrawProviderFeesArgs, WriteCallbackAccountListArgs,
+        },
+        pda::{
+            callback_account_list_pda, callback_failure_stats_pda, config_pda, entropy_signer_pda,
+            governance_pda, keeper_lease_pda, provider_allowlist_pda, provider_directory_pda,
+            provider_pda, provider_vault_pda, pyth_fee_vault_pda, requester_signer_pda,
+        },
+        program_data::program_data_address,
+    },
+    solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+        sysvar::slot_hashes,
+    },
+};
+
+use crate::{addresses::EntropyAddresses, error::EntropySdkError};
+
+/// First data byte of the forwarding requester ABI (see
+/// [`forward_request_with_callback`]).
+pub const FORWARD_REQUEST_WITH_CALLBACK_ACTION: u8 = 1;
+
+/// Action byte for forwarding `RequestAggregate` (see
+/// [`forward_request_aggregate`]).
+pub const FORWARD_REQUEST_AGGREGATE_ACTION: u8 = 4;
+
+/// Builds `Initialize`. `payer` must be the program's upgrade authority.
+pub fn initialize(
+    program_id: Pubkey,
+    payer: Pubkey,
+    admin: Pubkey,
+    default_provider: Pubkey,
+    pyth_fee_lamports: u64,
+) -> Instruction {
+    let args = InitializeArgs {
+        admin: admin.to_bytes(),
+        pyth_fee_lamports,
+        default_provider: default_provider.to_bytes(),
+    };
+    initialize_instruction(program_id, payer, EntropyInstruction::build(&args))
+}
+
+/// Builds `InitializeIfNeeded`: [`initialize`] that is a no-op if the config
+/// already holds these arguments.
+pub fn initialize_if_needed(
+    program_id: Pubkey,
+    payer: Pubkey,
+    admin: Pubkey,
+    default_provider: Pubkey,
+    pyth_fee_lamports: u64,
+) -> Instruction {
+    let args = InitializeIfNeededArgs(InitializeArgs {
+        admin: admin.to_bytes(),
+        pyth_fee_lamports,
+        default_provider: default_provider.to_bytes(),
+    });
+    initialize_instruction(program_id, payer, EntropyInstruction::build(&args))
+}
+
+fn initialize_instruction(program_id: Pubkey, payer: Pubkey, data: Vec<u8>) -> Instruction {
+    let (config, _) = config_pda(&program_id);
+    let (pyth_fee_vault, _) = pyth_fee_vault_pda(&program_id);
+
+    Instruction {
+        program_id,
+        data,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new(pyth_fee_vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(program_data_address(&program_id), false),
+        ],
+    }
+}
+
+pub fn register_provider_args(
+    fee_lamports: u64,
+    commitment: [u8; 32],
+    chain_length: u64,
+    commitment_metadata: &[u8],
+    uri: &[u8],
+    require_https: bool,
+) -> Result<RegisterProviderArgs, EntropySdkError> {
+    if commitment_metadata.len() > COMMITMENT_METADATA_LEN {
+        return Err(EntropySdkError::FieldTooLong {
+            field: "commitment metadata",
+            len: commitment_metadata.len(),
+            max: COMMITMENT_METADATA_LEN,
+        });
+    }
+    if uri.len() > URI_LEN {
+        return Err(EntropySdkError::FieldTooLong {
+            field: "uri",
+            len: uri.len(),
+            max: URI_LEN,
+        });
+    }
+
+    let mut args = RegisterProviderArgs {
+        fee_lamports,
+        commitment,
+        commitment_metadata_len: commitment_metadata.len() as u16,
+        _padding0: [0u8; 6],
+        commitment_metadata: [0u8; COMMITMENT_METADATA_LEN],
+        chain_length,
+        uri_len: uri.len() as u16,
+        uri: [0u8; URI_LEN],
+        require_https: u8::from(require_https),
+        _padding1: [0u8; 5],
+    };
+    args.commitment_metadata[..commitment_metadata.len()].copy_from_slice(commitment_metadata);
+    args.uri[..uri.len()].copy_from_slice(uri);
+    Ok(args)
+}
+
+/// Builds `RegisterProvider`. The provider allowlist is always passed, so
+/// the instruction works whether or not registration is permissioned.
+/// `provider_directory_page` is where a provider registering for the first
+/// time is listed: [`Config::provider_directory_next_page`] of the current
+/// config.
+///
+/// [`Config::provider_directory_next_page`]: entropy::accounts::Config::provider_directory_next_page
+pub fn register_provider(
+    program_id: Pubkey,
+    provider_authority: Pubkey,
+    args: RegisterProviderArgs,
+    provider_directory_page: u32,
+) -> Instruction {
+    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
+    let (provider_vault, _) = provider_vault_pda(&program_id, &provider_authority);
+    let (config, _) = config_pda(&program_id);
+    let (provider_directory, _) = provider_directory_pda(&program_id, provider_directory_page);
+    let (provider_allowlist, _) = provider_allowlist_pda(&program_id);
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts: vec![
+            AccountMeta::new(provider_authority, true),
+            AccountMeta::new(provider_account, false),
+            AccountMeta::new(provider_vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(config, false),
+            AccountMeta::new(provider_directory, false),
+            AccountMeta::new_readonly(provider_allowlist, false),
+        ],
+    }
+}
+
+/// Accounts shared by `Request` and `RequestWithCallback`. The remaining
+/// accounts are PDAs derived from these.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestAccounts {
+    pub requester_program: Pubkey,
+    pub payer: Pubkey,
+    /// Fresh keypair; the program allocates the request account at this address.
+    pub request_account: Pubkey,
+    pub provider_authority: Pubkey,
+}
+
+/// Indices of the request and config accounts in [`RequestAccounts::metas`].
+const REQUEST_ACCOUNT_INDEX: usize = 3;
+const CONFIG_ACCOUNT_INDEX: usize = 6;
+
+impl RequestAccounts {
+    fn metas(&self, program_id: &Pubkey) -> Vec<AccountMeta> {
+        let addresses =
+            EntropyAddresses::new(*program_id, self.provider_authority, self.requester_program);
+
+        vec![
+            AccountMeta::new_readonly(addresses.requester_signer, true),
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(self.requester_program, false),
+            AccountMeta::new(self.request_account, true),
+            AccountMeta::new(addresses.provider, false),
+            AccountMeta::new(addresses.provider_vault, false),
+            AccountMeta::new_readonly(addresses.config, false),
+            AccountMeta::new(addresses.pyth_fee_vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ]
+    }
+}
+
+/// Builds the entropy `Request` instruction, with the requester signer bump
+/// of `args` filled in. The requester signer is a PDA of the requester
+/// program, so this must be issued through that program's CPI.
+pub fn request(program_id: Pubkey, accounts: &RequestAccounts, args: RequestArgs) -> Instruction {
+    let (_, requester_signer_bump) = requester_signer_pda(&accounts.requester_program, &program_id);
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&RequestArgs {
+            requester_signer_bump,
+            ..args
+        }),
+        accounts: accounts.metas(&program_id),
+    }
+}
+
+pub fn callback_meta(meta: &AccountMeta) -> CallbackMeta {
+    CallbackMeta {
+        pubkey: meta.pubkey.to_bytes(),
+        is_signer: u16::from(meta.is_signer),
+        is_writable: u16::from(meta.is_writable),
+    }
+}
+
+/// Options of a request built with [`request_with_callback`] or
+/// [`request_v2`]. The default request never expires, may be revealed in
+/// the slot it lands, uses the provider's default compute unit limit,
+/// refunds the payer and calls back with no accounts or data; set the
+/// fields that differ and take the rest from `..Default::default()`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestParams<'a> {
+    /// Compute units for the callback; the provider's default if lower.
+    pub compute_unit_limit: u32,
+    /// Slots after which the request can no longer be revealed and its rent
+    /// can be reclaimed; zero for no expiry.
+    pub ttl_slots: u32,
+    /// Slots that must pass after the request before it can be revealed;
+    /// zero allows a reveal in the same slot.
+    pub min_reveal_delay_slots: u16,
+    /// Appends the user and provider contributions to the callback data;
+    /// see `entropy_cpi::parse_callback_with_contributions`.
+    pub include_contributions: bool,
+    /// [`RANDOM_NUMBER_V1`](entropy::constants::RANDOM_NUMBER_V1) binds the
+    /// random number to this entropy deployment; 0 keeps the original
+    /// derivation.
+    pub random_number_version: u8,
+    /// Receives the request account's rent when it is closed; the payer if
+    /// `None`.
+    pub refund_address: Option<Pubkey>,
+    /// Accounts the callback receives, after the entropy signer.
+    pub callback_accounts: &'a [AccountMeta],
+    /// Prefix of the callback instruction data.
+    pub callback_ix_data: &'a [u8],
+}
+
+/// Options of a request built with [`request_aggregate`]; see
+/// [`RequestParams`], whose defaults these share. Aggregate requests do not
+/// pass contributions to the callback.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AggregateRequestParams<'a> {
+    pub compute_unit_limit: u32,
+    pub ttl_slots: u32,
+    pub min_reveal_delay_slots: u16,
+    /// Mixes the blockhash of the request slot into the random number, which
+    /// keeps the request unrevealable for at least one slot.
+    pub use_blockhash: bool,
+    pub random_number_version: u8,
+    pub refund_address: Option<Pubkey>,
+    pub callback_accounts: &'a [AccountMeta],
+    pub callback_ix_data: &'a [u8],
+}
+
+/// Packs callback request data for `header`, checking the callback
+/// accounts and data fit a request.
+fn callback_request_data<H: CallbackRequestHeader>(
+    header: &H,
+    requester_signer_bump: u8,
+    callback_accounts: &[AccountMeta],
+    callback_ix_data: &[u8],
+) -> Result<Vec<u8>, EntropySdkError> {
+    if callback_accounts.len() > MAX_CALLBACK_ACCOUNTS {
+        return Err(EntropySdkError::TooManyCallbackAccounts(
+            callback_accounts.len(),
+        ));
+    }
+    if callback_ix_data.len() > CALLBACK_IX_DATA_LEN {
+        return Err(EntropySdkError::CallbackDataTooLong(callback_ix_data.len()));
+    }
+
+    let callback_metas: Vec<CallbackMeta> = callback_accounts.iter().map(callback_meta).collect();
+    Ok(EntropyInstruction::build_callback_request(
+        header,
+        requester_signer_bump,
+        &callback_metas,
+        callback_ix_data,
+    ))
+}
+
+fn refund_address_bytes(refund_address: Option<Pubkey>) -> [u8; 32] {
+    refund_address.map_or([0u8; 32], |address| address.to_bytes())
+}
+
+/// Builds the entropy `RequestWithCallback` instruction. The callback is
+/// delivered to the requester program with `params.callback_accounts`
+/// appended.
+pub fn request_with_callback(
+    program_id: Pubkey,
+    accounts: &RequestAccounts,
+    user_randomness: [u8; 32],
+    params: &RequestParams,
+) -> Result<Instruction, EntropySdkError> {
+    let header = RequestWithCallbackHeader {
+        user_randomness,
+        compute_unit_limit: params.compute_unit_limit,
+        ttl_slots: params.ttl_slots,
+        min_reveal_delay_slots: params.min_reveal_delay_slots,
+        include_contributions: u8::from(params.include_contributions),
+        random_number_version: params.random_number_version,
+        refund_address: refund_address_bytes(params.refund_address),
+        ..RequestWithCallbackHeader::zeroed()
+    };
+    let (_, requester_signer_bump) = requester_signer_pda(&accounts.requester_program, &program_id);
+    let data = callback_request_data(
+        &header,
+        requester_signer_bump,
+        params.callback_accounts,
+        params.callback_ix_data,
+    )?;
+
+    let mut metas = accounts.metas(&program_id);
+    metas.push(AccountMeta::new_readonly(accounts.requester_program, false));
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts: metas,
+    })
+}
+
+/// Builds the entropy `RequestV2` instruction: [`request_with_callback`]
+/// with the user randomness generated by entropy from its config seed, which
+/// makes the config account writable.
+pub fn request_v2(
+    program_id: Pubkey,
+    accounts: &RequestAccounts,
+    params: &RequestParams,
+) -> Result<Instruction, EntropySdkError> {
+    let header = RequestV2Header {
+        compute_unit_limit: params.compute_unit_limit,
+        ttl_slots: params.ttl_slots,
+        min_reveal_delay_slots: params.min_reveal_delay_slots,
+        include_contributions: u8::from(params.include_contributions),
+        random_number_version: params.random_number_version,
+        refund_address: refund_address_bytes(params.refund_address),
+        ..RequestV2Header::zeroed()
+    };
+    let (_, requester_signer_bump) = requester_signer_pda(&accounts.requester_program, &program_id);
+    let data = callback_request_data(
+        &header,
+        requester_signer_bump,
+        params.callback_accounts,
+        params.callback_ix_data,
+    )?;
+
+    let mut metas = accounts.metas(&program_id);
+    metas[CONFIG_ACCOUNT_INDEX].is_writable = true;
+    metas.push(AccountMeta::new_readonly(accounts.requester_program, false));
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts: metas,
+    })
+}
+
+/// Adds the callback account list of the request to a
+/// [`request_with_callback`] or [`request_v2`] instruction, so the callback
+/// also receives the listed accounts. Write the list with
+/// [`write_callback_account_list`] first, in the same transaction or an
+/// earlier one.
+pub fn with_callback_account_list(mut entropy_ix: Instruction) -> Instruction {
+    let request_account = entropy_ix.accounts[REQUEST_ACCOUNT_INDEX].pubkey;
+    let (list, _) = callback_account_list_pda(&entropy_ix.program_id, &request_account);
+    entropy_ix
+        .accounts
+        .push(AccountMeta::new_readonly(list, false));
+    entropy_ix
+}
+
+/// Builds `WriteCallbackAccountList`, which appends up to
+/// [`MAX_CALLBACK_ACCOUNTS`] accounts to the callback account list of a
+/// request not made yet; longer lists take one instruction per chunk. The
+/// request account's keypair must sign.
+pub fn write_callback_account_list(
+    program_id: Pubkey,
+    payer: Pubkey,
+    request_account: Pubkey,
+    callback_accounts: &[AccountMeta],
+) -> Result<Instruction, EntropySdkError> {
+    if callback_accounts.len() > MAX_CALLBACK_ACCOUNTS {
+        return Err(EntropySdkError::TooManyCallbackAccounts(
+            callback_accounts.len(),
+        ));
+    }
+    let mut args = WriteCallbackAccountListArgs::zeroed();
+    args.num_accounts = callback_accounts.len() as u8;
+    for (slot, meta) in args.callback_accounts.iter_mut().zip(callback_accounts) {
+        *slot = callback_meta(meta);
+    }
+    let (list, _) = callback_account_list_pda(&program_id, &request_account);
+
+    Ok(Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(request_account, true),
+            AccountMeta::new(list, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    })
+}
+
+/// Builds `CloseCallbackAccountList`, which returns the rent of the
+/// callback account list of a request that was never made to `recipient`.
+pub fn close_callback_account_list(
+    program_id: Pubkey,
+    request_account: Pubkey,
+    recipient: Pubkey,
+) -> Instruction {
+    let (list, _) = callback_account_list_pda(&program_id, &request_account);
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&CloseCallbackAccountListArgs),
+        accounts: vec![
+            AccountMeta::new_readonly(request_account, true),
+            AccountMeta::new(list, false),
+            AccountMeta::new(recipient, false),
+        ],
+    }
+}
+
+/// Accounts for `RequestAggregate`: [`RequestAccounts`] with one provider
+/// authority per contribution, in the order the request stores them.
+#[derive(Clone, Debug)]
+pub struct AggregateRequestAccounts {
+    pub requester_program: Pubkey,
+    pub payer: Pubkey,
+    /// Fresh keypair; the program allocates the request account at this address.
+    pub request_account: Pubkey,
+    pub provider_authorities: Vec<Pubkey>,
+}
+
+/// Builds the entropy `RequestAggregate` instruction: a callback request
+/// served by every provider in `accounts`, whose callback fires once
+/// `threshold` of them have revealed. Each provider is paid its own fee.
+///
+/// Any `threshold` providers can finalize the request, and a provider that
+/// has seen the others' reveals may withhold its own to drop its
+/// contribution. A `threshold` below the number of providers trusts that
+/// fewer than `threshold` of them collude.
+pub fn request_aggregate(
+    program_id: Pubkey,
+    accounts: &AggregateRequestAccounts,
+    user_randomness: [u8; 32],
+    threshold: u8,
+    params: &AggregateRequestParams,
+) -> Result<Instruction, EntropySdkError> {
+    let num_providers = accounts.provider_authorities.len();
+    if num_providers == 0
+        || num_providers > MAX_AGGREGATE_PROVIDERS
+        || threshold == 0
+        || usize::from(threshold) > num_providers
+    {
+        return Err(EntropySdkError::InvalidAggregateThreshold {
+            threshold,
+            num_providers,
+        });
+    }
+    let header = RequestAggregateHeader {
+        user_randomness,
+        compute_unit_limit: params.compute_unit_limit,
+        ttl_slots: params.ttl_slots,
+        num_providers: num_providers as u8,
+        threshold,
+        min_reveal_delay_slots: params.min_reveal_delay_slots,
+        use_blockhash: u8::from(params.use_blockhash),
+        random_number_version: params.random_number_version,
+        refund_address: refund_address_bytes(params.refund_address),
+        ..RequestAggregateHeader::zeroed()
+    };
+    let (requester_signer, requester_signer_bump) =
+        requester_signer_pda(&accounts.requester_program, &program_id);
+    let data = callback_request_data(
+        &header,
+        requester_signer_bump,
+        params.callback_accounts,
+        params.callback_ix_data,
+    )?;
+
+    let (config, _) = config_pda(&program_id);
+    let (pyth_fee_vault, _) = pyth_fee_vault_pda(&program_id);
+    let mut metas = Vec::with_capacity(7 + 2 * accounts.provider_authorities.len());
+    metas.push(AccountMeta::new_readonly(requester_signer, true));
+    metas.push(AccountMeta::new(accounts.payer, true));
+    metas.push(AccountMeta::new_readonly(accounts.requester_program, false));
+    metas.push(AccountMeta::new(accounts.request_account, true));
+    metas.push(AccountMeta::new_readonly(config, false));
+    metas.push(AccountMeta::new(pyth_fee_vault, false));
+    metas.push(AccountMeta::new_readonly(system_program::id(), false));
+    for provider_authority in &accounts.provider_authorities {
+        let (provider_account, _) = provider_pda(&program_id, provider_authority);
+        let (provider_vault, _) = provider_vault_pda(&program_id, provider_authority);
+        metas.push(AccountMeta::new(provider_account, false));
+        metas.push(AccountMeta::new(provider_vault, false));
+    }
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts: metas,
+    })
+}
+
+/// Wraps an entropy `RequestWithCallback` instruction for a requester program
+/// that forwards it verbatim (the simple-requester ABI): one action byte
+/// followed by the entropy data, with the entropy program appended to the
+/// accounts. The requester program signs for its PDA, so the outer
+/// instruction does not mark it as a signer.
+pub fn forward_request_with_callback(
+    requester_program: Pubkey,
+    entropy_ix: &Instruction,
+) -> Instruction {
+    forward_request(
+        FORWARD_REQUEST_WITH_CALLBACK_ACTION,
+        requester_program,
+        entropy_ix,
+    )
+}
+
+/// Like [`forward_request_with_callback`], for a `RequestAggregate`
+/// instruction.
+pub fn forward_request_aggregate(
+    requester_program: Pubkey,
+    entropy_ix: &Instruction,
+) -> Instruction {
+    forward_request(
+        FORWARD_REQUEST_AGGREGATE_ACTION,
+        requester_program,
+        entropy_ix,
+    )
+}
+
+fn forward_request(action: u8, requester_program: Pubkey, entropy_ix: &Instruction) -> Instruction {
+    let mut data = Vec::with_capacity(1 + entropy_ix.data.len());
+    data.push(action);
+    data.extend_from_slice(&entropy_ix.data);
+
+    let mut accounts = entropy_ix.accounts.clone();
+    if let Some(requester_signer) = accounts.first_mut() {
+        requester_signer.is_signer = false;
+    }
+    accounts.push(AccountMeta::new_readonly(entropy_ix.program_id, false));
+
+    Instruction {
+        program_id: requester_program,
+        data,
+        accounts,
+    }
+}
+
+/// Builds `RevealWithCallback` for a pending request, taking the callback
+/// program, payer and callback accounts from the stored request. The
+/// provider's callback failure stats for the requester program follow the
+/// callback accounts, for the program to count the callback against the
+/// failures the provider reports. A request with a callback account list needs
+/// [`reveal_with_callback_account_list`] instead.
+pub fn reveal_with_callback(
+    program_id: Pubkey,
+    request_account: Pubkey,
+    request: &Request,
+    args: RevealArgs,
+) -> Instruction {
+    reveal_instruction(program_id, request_account, request, None, args)
+}
+
+/// [`reveal_with_callback`] for a request with a callback account list,
+/// given the list's contents.
+pub fn reveal_with_callback_account_list(
+    program_id: Pubkey,
+    request_account: Pubkey,
+    request: &Request,
+    list: &CallbackAccountList,
+    args: RevealArgs,
+) -> Instruction {
+    reveal_instruction(program_id, request_account, request, Some(list), args)
+}
+
+fn reveal_instruction(
+    program_id: Pubkey,
+    request_account: Pubkey,
+    request: &Request,
+    list: Option<&CallbackAccountList>,
+    args: RevealArgs,
+) -> Instruction {
+    let provider_authority = Pubkey::new_from_array(request.provider);
+    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
+    let (entropy_signer, _) = entropy_signer_pda(&program_id);
+    let requester_program_id = Pubkey::new_from_array(request.requester_program_id);
+    let (stats, _) =
+        callback_failure_stats_pda(&program_id, &provider_authority, &requester_program_id);
+    let listed_accounts = list.map_or(&[][..], |list| list.callback_accounts());
+    let callback_accounts = RequestRef::from(request).callback_accounts();
+
+    let mut accounts = Vec::with_capacity(9 + callback_accounts.len() + listed_accounts.len());
+    accounts.push(AccountMeta::new(request_account, false));
+    accounts.push(AccountMeta::new(provider_account, false));
+    accounts.push(AccountMeta::new_readonly(slot_hashes::id(), false));
+    accounts.push(AccountMeta::new_readonly(entropy_signer, false));
+    accounts.push(AccountMeta::new_readonly(requester_program_id, false));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new(
+        Pubkey::new_from_array(request.refund_address),
+        false,
+    ));
+    if list.is_some() {
+        accounts.push(AccountMeta::new(
+            Pubkey::new_from_array(request.callback_account_list),
+            false,
+        ));
+    }
+
+    for meta in callback_accounts.iter().chain(listed_accounts) {
+        let key = Pubkey::new_from_array(meta.pubkey);
+        if meta.is_writable == 1 {
+            accounts.push(AccountMeta::new(key, meta.is_signer == 1));
+        } else {
+            accounts.push(AccountMeta::new_readonly(key, meta.is_signer == 1));
+        }
+    }
+    accounts.push(AccountMeta::new(stats, false));
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts,
+    }
+}
+
+/// Builds `SetCallbackSkipPolicy`, which lets the provider skip the
+/// callbacks of a requester program once `max_failures` of them have been
+/// reported within `window_slots`, more than succeeded there; a zero
+/// `max_failures` disables skipping, and any other value must be at least
+/// [`MIN_CALLBACK_SKIP_MAX_FAILURES`](entropy::constants::MIN_CALLBACK_SKIP_MAX_FAILURES).
+pub fn set_callback_skip_policy(
+    program_id: Pubkey,
+    provider_authority: Pubkey,
+    max_failures: u16,
+    window_slots: u32,
+) -> Instruction {
+    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
+    let args = SetCallbackSkipPolicyArgs {
+        window_slots,
+        max_failures,
+        _padding0: [0u8; 2],
+    };
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts: vec![
+            AccountMeta::new_readonly(provider_authority, true),
+            AccountMeta::new(provider_account, false),
+        ],
+    }
+}
+
+/// Builds `ReportCallbackFailure`, which counts the failed callback of a
+/// pending request against its requester program and marks the request
+/// failed; each request can be reported once. The request's provider
+/// authority signs and pays the stats account's rent on first use.
+pub fn report_callback_failure(
+    program_id: Pubkey,
+    request_account: Pubkey,
+    request: &Request,
+) -> Instruction {
+    let provider_authority = Pubkey::new_from_array(request.provider);
+    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
+    let (stats, _) = callback_failure_stats_pda(
+        &program_id,
+        &provider_authority,
+        &Pubkey::new_from_array(request.requester_program_id),
+    );
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&ReportCallbackFailureArgs),
+        accounts: vec![
+            AccountMeta::new(provider_authority, true),
+            AccountMeta::new_readonly(provider_account, false),
+            AccountMeta::new(request_account, false),
+            AccountMeta::new(stats, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
+
+/// Builds `RevealSkippingCallback`, which reveals a pending request reported
+/// with [`report_callback_failure`] without invoking its callback and refunds the provider fee to its refund address.
+/// The request's provider authority signs; the program only accepts it while
+/// the provider's skip policy covers the request's requester program.
+pub fn reveal_skipping_callback(
+    program_id: Pubkey,
+    request_account: Pubkey,
+    request: &Request,
+    args: RevealArgs,
+) -> Instruction {
+    let provider_authority = Pubkey::new_from_array(request.provider);
+    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
+    let (provider_vault, _) = provider_vault_pda(&program_id, &provider_authority);
+    let (stats, _) = callback_failure_stats_pda(
+        &program_id,
+        &provider_authority,
+        &Pubkey::new_from_array(request.requester_program_id),
+    );
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(provider_authority, true),
+        AccountMeta::new(request_account, false),
+        AccountMeta::new(provider_account, false),
+        AccountMeta::new_readonly(slot_hashes::id(), false),
+        AccountMeta::new_readonly(stats, false),
+        AccountMeta::new(provider_vault, false),
+        AccountMeta::new(Pubkey::new_from_array(request.refund_address), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if request.has_callback_account_list() {
+        accounts.push(AccountMeta::new(
+            Pubkey::new_from_array(request.callback_account_list),
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&RevealSkippingCallbackArgs(args)),
+        accounts,
+    }
+}
+
+/// Builds `RevealAggregate` for one provider's contribution to a pending
+/// aggregate request. The accounts cover the final reveal too, so any reveal
+/// may be the one that reaches the threshold, refunds the providers that
+/// have not revealed and invokes the callback.
+pub fn reveal_aggregate(
+    program_id: Pubkey,
+    request_account: Pubkey,
+    request: &AggregateRequest,
+    args: RevealAggregateArgs,
+) -> Instruction {
+    let (entropy_signer, _) = entropy_signer_pda(&program_id);
+    let contributions = request.active_contributions();
+    let callback_accounts_len =
+        usize::from(request.callback_accounts_len).min(MAX_CALLBACK_ACCOUNTS);
+    let callback_accounts = &request.callback_accounts[..callback_accounts_len];
+
+    let mut accounts = Vec::with_capacity(6 + 2 * contributions.len() + callback_accounts.len());
+    accounts.push(AccountMeta::new(request_account, false));
+    accounts.push(AccountMeta::new_readonly(slot_hashes::id(), false));
+    accounts.push(AccountMeta::new_readonly(entropy_signer, false));
+    accounts.push(AccountMeta::new_readonly(
+        Pubkey::new_from_array(request.requester_program_id),
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new(
+        Pubkey::new_from_array(request.refund_address),
+        false,
+    ));
+    accounts.extend(provider_account_pairs(&program_id, contributions));
+
+    for meta in callback_accounts {
+        let key = Pubkey::new_from_array(meta.pubkey);
+        if meta.is_writable == 1 {
+            accounts.push(AccountMeta::new(key, meta.is_signer == 1));
+        } else {
+            accounts.push(AccountMeta::new_readonly(key, meta.is_signer == 1));
+        }
+    }
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts,
+    }
+}
+
+/// Builds `CloseExpiredAggregateRequest`, which refunds an expired aggregate
+/// request's rent, and the fees of providers that never revealed, to its
+/// refund address. No signature is needed.
+pub fn close_expired_aggregate_request(
+    program_id: Pubkey,
+    request_account: Pubkey,
+    request: &AggregateRequest,
+) -> Instruction {
+    let contributions = request.active_contributions();
+    let mut accounts = Vec::with_capacity(3 + 2 * contributions.len());
+    accounts.push(AccountMeta::new(request_account, false));
+    accounts.push(AccountMeta::new(
+        Pubkey::new_from_array(request.refund_address),
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.extend(provider_account_pairs(&program_id, contributions));
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&CloseExpiredAggregateRequestArgs),
+        accounts,
+    }
+}
+
+/// The writable `[provider, provider vault]` pair of each contribution, in
+/// request order.
+fn provider_account_pairs<'a>(
+    program_id: &'a Pubkey,
+    contributions: &'a [AggregateContribution],
+) -> impl Iterator<Item = AccountMeta> + 'a {
+    contributions.iter().flat_map(move |contribution| {
+        let provider_authority = Pubkey::new_from_array(contribution.provider);
+        let (provider_account, _) = provider_pda(program_id, &provider_authority);
+        let (provider_vault, _) = provider_vault_pda(program_id, &provider_authority);
+        [
+            AccountMeta::new(provider_account, false),
+            AccountMeta::new(provider_vault, false),
+        ]
+    })
+}
+
+/// Builds `CloseExpiredRequest`, which refunds an expired request's rent,
+/// its callback account list's rent and its provider fee to its refund
+/// address. No signature is needed.
+pub fn close_expired_request(
+    program_id: Pubkey,
+    request_account: Pubkey,
+    request: &Request,
+) -> Instruction {
+    let provider_authority = Pubkey::new_from_array(request.provider);
+    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
+    let (provider_vault, _) = provider_vault_pda(&program_id, &provider_authority);
+
+    let mut accounts = vec![
+        AccountMeta::new(request_account, false),
+        AccountMeta::new(Pubkey::new_from_array(request.refund_address), false),
+        AccountMeta::new(provider_account, false),
+        AccountMeta::new(provider_vault, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if request.has_callback_account_list() {
+        accounts.push(AccountMeta::new(
+            Pubkey::new_from_array(request.callback_account_list),
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&CloseExpiredRequestArgs),
+        accounts,
+    }
+}
+
+/// Builds `CloseProvider`, which refunds the provider account and vault to
+/// `provider_authority` once it has no pending requests and removes it from
+/// the provider directory. `provider_directory_page` is the page listing the
+/// authority and `last_provider_directory_page` the page of the last listed
+/// authority, [`Config::provider_directory_pages`] minus one; neither is read
+/// for a provider that was never listed.
+///
+/// [`Config::provider_directory_pages`]: entropy::accounts::Config::provider_directory_pages
+pub fn close_provider(
+    program_id: Pubkey,
+    provider_authority: Pubkey,
+    provider_directory_page: u32,
+    last_provider_directory_page: u32,
+) -> Instruction {
+    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
+    let (provider_vault, _) = provider_vault_pda(&program_id, &provider_authority);
+    let (config, _) = config_pda(&program_id);
+    let (provider_directory, _) = provider_directory_pda(&program_id, provider_directory_page);
+    let (last_provider_directory, _) =
+        provider_directory_pda(&program_id, last_provider_directory_page);
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&CloseProviderArgs),
+        accounts: vec![
+            AccountMeta::new(provider_authority, true),
+            AccountMeta::new(provider_account, false),
+            AccountMeta::new(provider_vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(config, false),
+            AccountMeta::new(provider_directory, false),
+            AccountMeta::new(last_provider_directory, false),
+        ],
+    }
+}
+
+/// Builds `GetVersion`, which takes no accounts and returns the program's
+/// [`PROGRAM_VERSION`](entropy::constants::PROGRAM_VERSION) as return data.
+pub fn get_version(program_id: Pubkey) -> Instruction {
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&GetVersionArgs),
+        accounts: vec![],
+    }
+}
+
+/// Builds `PreviewRequest`, which returns the
+/// [`RequestPreview`](entropy::instruction::RequestPreview) of a request to
+/// `provider_authority` with `user_commitment` (the hash of the user
+/// randomness) as return data. It changes nothing, so simulate it.
+pub fn preview_request(
+    program_id: Pubkey,
+    provider_authority: Pubkey,
+    user_commitment: [u8; 32],
+) -> Instruction {
+    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&PreviewRequestArgs { user_commitment }),
+        accounts: vec![AccountMeta::new_readonly(provider_account, false)],
+    }
+}
+
+/// Builds `WithdrawProviderFees`, which moves `amount` lamports of accrued
+/// fees from the provider vault to `destination`.
+/// Builds `RefreshKeeperLease`, claiming or renewing the provider's keeper
+/// lease for `keeper`. The authority pays the lease account's rent on first
+/// use; a zero `lease_slots` releases the lease.
+pub fn refresh_keeper_lease(
+    program_id: Pubkey,
+    provider_authority: Pubkey,
+    keeper: [u8; 32],
+    lease_slots: u64,
+) -> Instruction {
+    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
+    let (keeper_lease, _) = keeper_lease_pda(&program_id, &provider_authority);
+    let args = RefreshKeeperLeaseArgs {
+        keeper,
+        lease_slots,
+    };
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts: vec![
+            AccountMeta::new(provider_authority, true),
+            AccountMeta::new_readonly(provider_account, false),
+            AccountMeta::new(keeper_lease, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
+
+pub fn withdraw_provider_fees(
+    program_id: Pubkey,
+    provider_authority: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (provider_account, _) = provider_pda(&program_id, &provider_authority);
+    let (provider_vault, _) = provider_vault_pda(&program_id, &provider_authority);
+    let args = WithdrawProviderFeesArgs { amount };
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts: vec![
+            AccountMeta::new_readonly(provider_authority, true),
+            AccountMeta::new_readonly(provider_account, false),
+            AccountMeta::new(provider_vault, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
+
+/// Builds `InitializeGovernance`, which `admin` must sign.
+pub fn initialize_governance(
+    program_id: Pubkey,
+    admin: Pubkey,
+    payer: Pubkey,
+    wormhole_program: Pubkey,
+    emitter_chain: u16,
+    emitter_address: [u8; 32],
+) -> Instruction {
+    let (config, _) = config_pda(&program_id);
+    let (governance, _) = governance_pda(&program_id);
+    let args = InitializeGovernanceArgs {
+        wormhole_program: wormhole_program.to_bytes(),
+        emitter_address,
+        emitter_chain,
+        _padding0: [0u8; 6],
+    };
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(governance, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
+
+/// Builds `Governance` for a VAA posted by the core bridge. A `WithdrawFee`
+/// message also needs its `recipient`, the target named in the message.
+pub fn governance(
+    program_id: Pubkey,
+    posted_vaa: Pubkey,
+    recipient: Option<Pubkey>,
+) -> Instruction {
+    let (config, _) = config_pda(&program_id);
+    let (governance, _) = governance_pda(&program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(config, false),
+        AccountMeta::new(governance, false),
+        AccountMeta::new_readonly(posted_vaa, false),
+    ];
+    if let Some(recipient) = recipient {
+        let (pyth_fee_vault, _) = pyth_fee_vault_pda(&program_id);
+        accounts.push(AccountMeta::new(pyth_fee_vault, false));
+        accounts.push(AccountMeta::new(recipient, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&GovernanceArgs),
+        accounts,
+    }
+}
+
+/// Builds `ClaimAdmin`, which makes the program's upgrade authority the admin.
+pub fn claim_admin(program_id: Pubkey, upgrade_authority: Pubkey) -> Instruction {
+    let (config, _) = config_pda(&program_id);
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&ClaimAdminArgs),
+        accounts: vec![
+            AccountMeta::new_readonly(upgrade_authority, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(program_data_address(&program_id), false),
+        ],
+    }
+}
+
+fn admin_accounts(program_id: Pubkey, admin: Pubkey) -> Vec<AccountMeta> {
+    let (config, _) = config_pda(&program_id);
+    vec![
+        AccountMeta::new_readonly(admin, true),
+        AccountMeta::new(config, false),
+    ]
+}
+
+/// Builds `SetPythFee`. `admin` must be the config admin; to sign with the
+/// upgrade authority instead, append the program data account.
+pub fn set_pyth_fee(program_id: Pubkey, admin: Pubkey, pyth_fee_lamports: u64) -> Instruction {
+    let args = SetPythFeeArgs { pyth_fee_lamports };
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts: admin_accounts(program_id, admin),
+    }
+}
+
+/// Builds `SetDefaultProvider`, authorized like [`set_pyth_fee`].
+pub fn set_default_provider(
+    program_id: Pubkey,
+    admin: Pubkey,
+    default_provider: Pubkey,
+) -> Instruction {
+    let args = SetDefaultProviderArgs {
+        default_provider: default_provider.to_bytes(),
+    };
+
+    Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts: admin_accounts(program_id, admin),
+    }
+}
+
+/// Builds `SetProgramRegistry`, authorized like [`set_pyth_fee`]. `payer`
+/// funds the registry account the first time it is written.
+pub fn set_program_registry(
+    program_id: Pubkey,
+    admin: Pubkey,
+    payer: Pubkey,
+    program_ids: &[Pubkey],
+) -> Result<Instruction, EntropySdkError> {
+    if program_ids.len() > MAX_REGISTERED_PROGRAMS {
+        return Err(EntropySdkError::TooManyRegisteredPrograms(
+            program_ids.len(),
+        ));
+    }
+    let mut args = SetProgramRegistryArgs {
+        program_ids: [[0u8; 32]; MAX_REGISTERED_PROGRAMS],
+        num_programs: program_ids.len() as u8,
+        _padding0: [0u8; 7],
+    };
+    for (slot, registered) in args.program_ids.iter_mut().zip(program_ids) {
+        *slot = registered.to_bytes();
+    }
+
+    let (config, _) = config_pda(&program_id);
+    let (entropy_signer, _) = entropy_signer_pda(&program_id);
+    Ok(Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new(entropy_signer, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    })
+}
+
+/// Builds `SetProviderAllowlist`, authorized like [`set_pyth_fee`]. While
+/// `permissioned_providers` is set only the listed provider authorities can
+/// register. `payer` funds the allowlist account the first time it is
+/// written.
+pub fn set_provider_allowlist(
+    program_id: Pubkey,
+    admin: Pubkey,
+    payer: Pubkey,
+    providers: &[Pubkey],
+    permissioned_providers: bool,
+) -> Result<Instruction, EntropySdkError> {
+    if providers.len() > MAX_ALLOWED_PROVIDERS {
+        return Err(EntropySdkError::TooManyAllowedProviders(providers.len()));
+    }
+    let mut args = SetProviderAllowlistArgs {
+        providers: [[0u8; 32]; MAX_ALLOWED_PROVIDERS],
+        num_providers: providers.len() as u8,
+        permissioned_providers: u8::from(permissioned_providers),
+        _padding0: [0u8; 6],
+    };
+    for (slot, provider) in args.providers.iter_mut().zip(providers) {
+        *slot = provider.to_bytes();
+    }
+
+    let (config, _) = config_pda(&program_id);
+    let (provider_allowlist, _) = provider_allowlist_pda(&program_id);
+    Ok(Instruction {
+        program_id,
+        data: EntropyInstruction::build(&args),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new(provider_allowlist, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    })
+}
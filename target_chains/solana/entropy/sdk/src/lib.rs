@@ -0,0 +1,39 @@
+//! Client-side helpers for the Entropy program: instruction builders, PDA
+//! derivations, the zero-copy account types and an async [`EntropyClient`].
+
+pub mod addresses;
+pub mod client;
+pub mod decode;
+pub mod directory;
+pub mod error;
+pub mod events;
+pub mod fee;
+pub mod hash_chain;
+pub mod idl;
+pub mod instruction;
+pub mod provider_metadata;
+pub mod stream;
+pub mod verify;
+
+pub use addresses::*;
+pub use client::*;
+pub use decode::*;
+pub use directory::*;
+pub use entropy::{
+    accounts::{
+        CallbackAccountList, CallbackFailureStats, CallbackMeta, CallbackStatus, Config, Provider,
+        ProviderDirectoryPage, PubkeyBytes, Request, RequestRef,
+    },
+    constants,
+    events::{CallbackInvoked, RandomnessRevealed, RequestCreated},
+    instruction::RequestPreview,
+    pda,
+};
+pub use error::*;
+pub use events::*;
+pub use fee::*;
+pub use hash_chain::*;
+pub use instruction::*;
+pub use provider_metadata::*;
+pub use stream::*;
+pub use verify::*;
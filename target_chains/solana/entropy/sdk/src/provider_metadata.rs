@@ -0,0 +1,109 @@
+//! The provider metadata document.
+//!
+//! A provider's on-chain `uri` points at a JSON document describing who runs
+//! it and what it charges:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "name": "Example Provider",
+//!   "description": "Low-latency randomness for Solana",
+//!   "contact": { "email": "ops@example.com", "url": "https://example.com" },
+//!   "fees": [
+//!     { "max_compute_unit_limit": 100000, "fee_lamports": 5000 },
+//!     { "fee_lamports": 20000, "description": "larger callbacks" }
+//!   ]
+//! }
+//! ```
+//!
+//! The document is informational: the fee actually charged is always the one
+//! in the provider account.
+
+use {
+    crate::error::EntropySdkError,
+    entropy::{accounts::Provider, constants::URI_LEN},
+    serde::{Deserialize, Serialize},
+};
+
+/// Highest metadata `version` this SDK understands.
+pub const PROVIDER_METADATA_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderMetadata {
+    pub version: u32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub contact: ProviderContact,
+    /// Fee tiers, cheapest first.
+    #[serde(default)]
+    pub fees: Vec<FeeSchedule>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderContact {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// The fee for callbacks up to `max_compute_unit_limit`, or any callback if
+/// that is absent.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_compute_unit_limit: Option<u32>,
+    pub fee_lamports: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl ProviderMetadata {
+    /// Parses a metadata document, rejecting versions newer than
+    /// [`PROVIDER_METADATA_VERSION`].
+    pub fn from_json(bytes: &[u8]) -> Result<Self, EntropySdkError> {
+        let metadata: Self = serde_json::from_slice(bytes)
+            .map_err(|err| EntropySdkError::InvalidProviderMetadata(err.to_string()))?;
+        if metadata.version == 0 || metadata.version > PROVIDER_METADATA_VERSION {
+            return Err(EntropySdkError::InvalidProviderMetadata(format!(
+                "unsupported version {}",
+                metadata.version
+            )));
+        }
+        Ok(metadata)
+    }
+}
+
+/// Checks that `uri` fits the provider account and is an `http(s)` URL with
+/// a host. With `require_https`, plain `http` is rejected.
+pub fn validate_provider_uri(uri: &str, require_https: bool) -> Result<(), EntropySdkError> {
+    let invalid = |reason: &str| Err(EntropySdkError::InvalidProviderUri(reason.to_string()));
+    if uri.len() > URI_LEN {
+        return Err(EntropySdkError::FieldTooLong {
+            field: "uri",
+            len: uri.len(),
+            max: URI_LEN,
+        });
+    }
+    if !uri.bytes().all(|byte| byte.is_ascii_graphic()) {
+        return invalid("must be printable ASCII without whitespace");
+    }
+    let rest = match uri.split_once("://") {
+        Some(("https", rest)) => rest,
+        Some(("http", _)) if require_https => return invalid("must use https"),
+        Some(("http", rest)) => rest,
+        _ => return invalid("must be an http(s) URL"),
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if host.is_empty() || host.starts_with(':') {
+        return invalid("missing host");
+    }
+    Ok(())
+}
+
+/// The provider's `uri`, or `None` if unset or not UTF-8.
+pub fn provider_uri(provider: &Provider) -> Option<&str> {
+    Some(provider.uri_str()).filter(|uri| !uri.is_empty())
+}
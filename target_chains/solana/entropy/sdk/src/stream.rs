@@ -0,0 +1,172 @@
+use {
+    crate::{client::decode_account, error::EntropySdkError},
+    entropy::accounts::{Account, Request},
+    futures::{Stream, StreamExt},
+    solana_account_decoder::UiAccountEncoding,
+    solana_client::{
+        nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
+    solana_sdk::{
+        account::Account as SolanaAccount, commitment_config::CommitmentConfig, pubkey::Pubkey,
+    },
+    std::{
+        collections::{HashSet, VecDeque},
+        pin::Pin,
+        str::FromStr,
+        task::{Context, Poll},
+    },
+    tokio::{
+        sync::{mpsc, oneshot},
+        task::JoinHandle,
+    },
+};
+
+/// A pending request account and its decoded contents.
+pub type PendingRequest = (Pubkey, Request);
+
+type Item = Result<PendingRequest, EntropySdkError>;
+
+/// Program-account filters matching the `Request` accounts of one provider.
+pub(crate) fn request_filters(provider_authority: &Pubkey) -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::DataSize(Request::LEN as u64),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, Request::discriminator().to_vec())),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            core::mem::offset_of!(Request, provider),
+            provider_authority.to_bytes().to_vec(),
+        )),
+    ]
+}
+
+pub(crate) fn request_accounts_config(
+    provider_authority: &Pubkey,
+    commitment: CommitmentConfig,
+) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(request_filters(provider_authority)),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    }
+}
+
+/// Pending requests for one provider, as returned by
+/// [`EntropyClient::request_stream`](crate::EntropyClient::request_stream).
+///
+/// Yields the requests that already existed when the stream was opened, then
+/// each new request as the websocket subscription reports it. A request is
+/// pending for as long as its account exists, since reveals close it. The
+/// stream ends if the subscription drops; callers that need to run
+/// indefinitely should open a new one (the backfill covers the gap).
+pub struct RequestStream {
+    backfill: VecDeque<PendingRequest>,
+    backfilled: HashSet<Pubkey>,
+    live: mpsc::UnboundedReceiver<Item>,
+    subscription: JoinHandle<()>,
+}
+
+impl RequestStream {
+    /// Subscribes first and backfills second, so that no request created in
+    /// between is missed. Requests seen by both are yielded once.
+    pub(crate) async fn open(
+        rpc: &RpcClient,
+        websocket_url: &str,
+        program_id: Pubkey,
+        provider_authority: Pubkey,
+    ) -> Result<Self, EntropySdkError> {
+        let pubsub = PubsubClient::new(websocket_url).await.map_err(Box::new)?;
+        let config = request_accounts_config(&provider_authority, rpc.commitment());
+
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let (live_tx, live) = mpsc::unbounded_channel();
+        let subscription_config = config.clone();
+        let subscription = tokio::spawn(async move {
+            let (mut updates, unsubscribe) = match pubsub
+                .program_subscribe(&program_id, Some(subscription_config))
+                .await
+            {
+                Ok(subscription) => {
+                    let _ = ready_tx.send(Ok(()));
+                    subscription
+                }
+                Err(err) => {
+                    let _ = ready_tx.send(Err(EntropySdkError::from(Box::new(err))));
+                    return;
+                }
+            };
+
+            while let Some(update) = updates.next().await {
+                let item = Pubkey::from_str(&update.value.pubkey)
+                    .ok()
+                    .and_then(|address| {
+                        let account = update.value.account.decode::<SolanaAccount>()?;
+                        Some(
+                            decode_account::<Request>(&program_id, &address, &account, "request")
+                                .map(|request| (address, request)),
+                        )
+                    });
+                // Skip notifications whose account data can't be decoded.
+                let Some(item) = item else {
+                    continue;
+                };
+                if live_tx.send(item).is_err() {
+                    break;
+                }
+            }
+            drop(updates);
+            unsubscribe().await;
+        });
+
+        match ready_rx.await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => return Err(err),
+            Err(_) => return Err(EntropySdkError::SubscriptionClosed),
+        }
+
+        let backfill = rpc
+            .get_program_accounts_with_config(&program_id, config)
+            .await?
+            .into_iter()
+            .filter_map(|(address, account)| {
+                decode_account::<Request>(&program_id, &address, &account, "request")
+                    .ok()
+                    .map(|request| (address, request))
+            })
+            .collect::<VecDeque<_>>();
+        let backfilled = backfill.iter().map(|(address, _)| *address).collect();
+
+        Ok(Self {
+            backfill,
+            backfilled,
+            live,
+            subscription,
+        })
+    }
+}
+
+impl Stream for RequestStream {
+    type Item = Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Item>> {
+        if let Some(pending) = self.backfill.pop_front() {
+            return Poll::Ready(Some(Ok(pending)));
+        }
+        loop {
+            match self.live.poll_recv(cx) {
+                Poll::Ready(Some(Ok((address, _)))) if self.backfilled.contains(&address) => {}
+                other => return other,
+            }
+        }
+    }
+}
+
+impl Drop for RequestStream {
+    fn drop(&mut self) {
+        self.subscription.abort();
+    }
+}
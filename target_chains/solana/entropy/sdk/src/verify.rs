@@ -0,0 +1,73 @@
+//! Off-chain re-computation of request commitments and reveals, mirroring
+//! the request instructions and `RevealWithCallback`.
+
+use {
+    entropy::{
+        accounts::{Provider, Request},
+        randomness::{combine_random_values, request_commitment},
+    },
+    solana_sdk::{hash::hash, pubkey::Pubkey},
+};
+
+/// The commitment a request made with `user_randomness` would store, given
+/// the provider account as currently fetched. A request only stores this if
+/// the provider's commitment does not change before it lands; simulate
+/// [`preview_request`](crate::instruction::preview_request) to check
+/// against the live account instead.
+pub fn preview_request_commitment(user_randomness: &[u8; 32], provider: &Provider) -> [u8; 32] {
+    request_commitment(
+        &hash(user_randomness).to_bytes(),
+        &provider.current_commitment,
+    )
+}
+
+/// Checks the contributions against a request commitment and returns the
+/// random number the program derives from them, or `None` if they do not
+/// match. `blockhash` is the request slot's blockhash for requests made with
+/// `use_blockhash`, and `None` otherwise. `program_id` is the entropy
+/// program the request was made to, which version 1 random numbers commit to.
+pub fn verify_reveal(
+    program_id: &Pubkey,
+    random_number_version: u8,
+    user_contribution: [u8; 32],
+    provider_contribution: [u8; 32],
+    num_hashes: u32,
+    commitment: [u8; 32],
+    blockhash: Option<[u8; 32]>,
+) -> Option<[u8; 32]> {
+    let mut provider_commitment = provider_contribution;
+    for _ in 0..num_hashes {
+        provider_commitment = hash(&provider_commitment).to_bytes();
+    }
+    let user_commitment = hash(&user_contribution).to_bytes();
+    if request_commitment(&user_commitment, &provider_commitment) != commitment {
+        return None;
+    }
+
+    Some(combine_random_values(
+        random_number_version,
+        program_id,
+        &user_contribution,
+        &provider_contribution,
+        &blockhash.unwrap_or_default(),
+    ))
+}
+
+/// [`verify_reveal`] against a fetched request account.
+pub fn verify_request_reveal(
+    program_id: &Pubkey,
+    request: &Request,
+    user_contribution: [u8; 32],
+    provider_contribution: [u8; 32],
+    blockhash: Option<[u8; 32]>,
+) -> Option<[u8; 32]> {
+    verify_reveal(
+        program_id,
+        request.random_number_version,
+        user_contribution,
+        provider_contribution,
+        request.num_hashes,
+        request.commitment,
+        blockhash.filter(|_| request.use_blockhash == 1),
+    )
+}
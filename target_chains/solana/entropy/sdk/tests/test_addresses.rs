@@ -0,0 +1,147 @@
+use {
+    bytemuck::Zeroable,
+    entropy::instruction::{RequestArgs, RevealArgs},
+    entropy_sdk::{
+        pda, provider_lookup_addresses, request, reveal_with_callback, EntropyAddresses, Request,
+        RequestAccounts,
+    },
+    solana_sdk::pubkey::Pubkey,
+};
+
+#[test]
+fn test_addresses_match_request_accounts() {
+    let program_id = Pubkey::new_unique();
+    let provider_authority = Pubkey::new_unique();
+    let requester_program = Pubkey::new_unique();
+    let addresses = EntropyAddresses::new(program_id, provider_authority, requester_program);
+
+    assert_eq!(addresses.config, pda::config_pda(&program_id).0);
+    assert_eq!(
+        addresses.requester_signer,
+        pda::requester_signer_pda(&requester_program, &program_id).0
+    );
+
+    let instruction = request(
+        program_id,
+        &RequestAccounts {
+            requester_program,
+            payer: Pubkey::new_unique(),
+            request_account: Pubkey::new_unique(),
+            provider_authority,
+        },
+        RequestArgs::zeroed(),
+    );
+    let keys: Vec<Pubkey> = instruction
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect();
+    assert_eq!(keys[0], addresses.requester_signer);
+    assert_eq!(
+        keys[4..8],
+        [
+            addresses.provider,
+            addresses.provider_vault,
+            addresses.config,
+            addresses.pyth_fee_vault
+        ]
+    );
+}
+
+#[test]
+fn test_provider_lookup_addresses_cover_reveal_accounts() {
+    let program_id = Pubkey::new_unique();
+    let provider_authority = Pubkey::new_unique();
+    let addresses = provider_lookup_addresses(program_id, provider_authority);
+
+    let mut request = Request::zeroed();
+    request.provider = provider_authority.to_bytes();
+    request.requester_program_id = Pubkey::new_unique().to_bytes();
+    request.refund_address = Pubkey::new_unique().to_bytes();
+    let request_account = Pubkey::new_unique();
+    let instruction =
+        reveal_with_callback(program_id, request_account, &request, RevealArgs::zeroed());
+    // Only the per-request accounts are left out, the callback failure stats
+    // among them since they depend on the requester program.
+    let requester_program_id = Pubkey::new_from_array(request.requester_program_id);
+    let per_request = [
+        request_account,
+        requester_program_id,
+        Pubkey::new_from_array(request.refund_address),
+        pda::callback_failure_stats_pda(&program_id, &provider_authority, &requester_program_id).0,
+    ];
+    for meta in &instruction.accounts {
+        assert!(
+            addresses.contains(&meta.pubkey) || per_request.contains(&meta.pubkey),
+            "{} is not covered",
+            meta.pubkey
+        );
+    }
+    assert!(addresses.contains(
+        &EntropyAddresses::new(program_id, provider_authority, Pubkey::new_unique()).provider_vault
+    ));
+}
+
+#[test]
+fn test_pdas_with_bump_match_the_search() {
+    let program_id = Pubkey::new_unique();
+    let provider_authority = Pubkey::new_unique();
+
+    let provider = pda::provider_pda(&program_id, &provider_authority);
+    let entropy_signer = pda::entropy_signer_pda(&program_id);
+    assert_eq!(
+        pda::provider_pda_with_bump(&program_id, &provider_authority, provider.1),
+        provider
+    );
+    assert_eq!(
+        pda::entropy_signer_pda_with_bump(&program_id, entropy_signer.1),
+        entropy_signer
+    );
+    // Zero marks an unrecorded bump.
+    assert_eq!(
+        pda::provider_pda_with_bump(&program_id, &provider_authority, 0),
+        provider
+    );
+    assert_eq!(
+        pda::entropy_signer_pda_with_bump(&program_id, 0),
+        entropy_signer
+    );
+}
+
+#[test]
+fn test_requester_signer_pda_with_bump() {
+    let program_id = Pubkey::new_unique();
+    let requester_program = Pubkey::new_unique();
+    let canonical = pda::requester_signer_pda(&requester_program, &program_id);
+
+    assert_eq!(
+        pda::requester_signer_pda_with_bump(&requester_program, &program_id, canonical.1),
+        canonical
+    );
+    assert_eq!(
+        pda::requester_signer_pda_with_bump(&requester_program, &program_id, 0),
+        canonical
+    );
+    // Any valid bump is taken as given; a non-canonical one derives another
+    // address, which entropy rejects unless the requester signed for it.
+    let (other, other_bump) = (1..canonical.1)
+        .rev()
+        .find_map(|bump| {
+            Pubkey::create_program_address(
+                &[
+                    entropy::constants::REQUESTER_SIGNER_SEED,
+                    program_id.as_ref(),
+                    &[bump],
+                ],
+                &requester_program,
+            )
+            .ok()
+            .map(|address| (address, bump))
+        })
+        .unwrap();
+    assert_ne!(other, canonical.0);
+    assert_eq!(
+        pda::requester_signer_pda_with_bump(&requester_program, &program_id, other_bump),
+        (other, other_bump)
+    );
+}
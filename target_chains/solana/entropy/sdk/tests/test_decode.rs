@@ -0,0 +1,194 @@
+use {
+    bytemuck::{bytes_of, Zeroable},
+    entropy::{
+        accounts::{Account, Provider},
+        instruction::RequestPreview,
+    },
+    entropy_sdk::{
+        get_version, preview_request, request_with_callback, set_pyth_fee, EntropySdkError,
+        IdlDecoder, RequestAccounts, RequestParams, REMAINING_ACCOUNT,
+    },
+    serde_json::json,
+    solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    },
+};
+
+fn keys(instruction: &Instruction) -> Vec<Pubkey> {
+    instruction
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect()
+}
+
+#[test]
+fn test_decode_request_with_callback() {
+    let program_id = Pubkey::new_unique();
+    let callback_account = Pubkey::new_unique();
+    let refund_address = Pubkey::new_unique();
+    let instruction = request_with_callback(
+        program_id,
+        &RequestAccounts {
+            requester_program: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            request_account: Pubkey::new_unique(),
+            provider_authority: Pubkey::new_unique(),
+        },
+        [7u8; 32],
+        &RequestParams {
+            compute_unit_limit: 200_000,
+            ttl_slots: 150,
+            min_reveal_delay_slots: 2,
+            include_contributions: true,
+            random_number_version: 1,
+            refund_address: Some(refund_address),
+            callback_accounts: &[AccountMeta::new(callback_account, false)],
+            callback_ix_data: &[0xcb, 0x01],
+        },
+    )
+    .unwrap();
+
+    let decoded = IdlDecoder::new()
+        .decode(&instruction.data, &keys(&instruction))
+        .unwrap();
+    assert_eq!(decoded.name, "request_with_callback");
+    let roles: Vec<&str> = decoded
+        .accounts
+        .iter()
+        .map(|(role, _)| role.as_str())
+        .collect();
+    assert_eq!(
+        &roles[..5],
+        [
+            "requester_signer",
+            "payer",
+            "requester_program",
+            "request_account",
+            "provider"
+        ]
+    );
+    assert_eq!(decoded.accounts.len(), instruction.accounts.len());
+    let args = decoded.args.unwrap();
+    assert_eq!(args["user_randomness"], "07".repeat(32));
+    assert_eq!(args["compute_unit_limit"], 200_000);
+    assert_eq!(args["ttl_slots"], 150);
+    assert_eq!(args["min_reveal_delay_slots"], 2);
+    assert_eq!(args["include_contributions"], 1);
+    assert_eq!(args["refund_address"], refund_address.to_string());
+    assert_eq!(
+        args["callback_accounts"],
+        json!([{ "pubkey": callback_account.to_string(), "is_signer": 0, "is_writable": 1 }])
+    );
+    assert_eq!(args["callback_ix_data"], "cb01");
+    assert!(args.get("_padding0").is_none());
+}
+
+#[test]
+fn test_decode_names_extra_accounts_remaining() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let mut instruction = set_pyth_fee(program_id, admin, 5_000);
+    let program_data = Pubkey::new_unique();
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(program_data, false));
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+
+    let decoded = IdlDecoder::new()
+        .decode(&instruction.data, &keys(&instruction))
+        .unwrap();
+    assert_eq!(decoded.name, "set_pyth_fee");
+    assert_eq!(decoded.accounts[0], ("admin".to_string(), admin));
+    assert_eq!(
+        decoded.accounts[2],
+        ("program_data".to_string(), program_data)
+    );
+    assert_eq!(decoded.accounts[3].0, REMAINING_ACCOUNT);
+    assert_eq!(decoded.args.unwrap()["pyth_fee_lamports"], 5_000);
+}
+
+#[test]
+fn test_decode_return_data() {
+    let decoder = IdlDecoder::new();
+    let program_id = Pubkey::new_unique();
+    let instruction = preview_request(program_id, Pubkey::new_unique(), [3u8; 32]);
+    let decoded = decoder
+        .decode(&instruction.data, &keys(&instruction))
+        .unwrap();
+    assert_eq!(
+        decoded.args.as_ref().unwrap()["user_commitment"],
+        "03".repeat(32)
+    );
+
+    let preview = RequestPreview {
+        sequence_number: 42,
+        num_hashes: 3,
+        _padding0: [0; 4],
+        commitment: [9u8; 32],
+    };
+    let returned = decoder
+        .decode_return_data(&decoded, bytes_of(&preview))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        returned,
+        json!({ "sequence_number": 42, "num_hashes": 3, "commitment": "09".repeat(32) })
+    );
+
+    let version = decoder.decode(&get_version(program_id).data, &[]).unwrap();
+    assert_eq!(version.args, None);
+    assert_eq!(
+        decoder.decode_return_data(&version, &[4]).unwrap(),
+        Some(json!(4))
+    );
+}
+
+#[test]
+fn test_decode_rejects_malformed_data() {
+    let decoder = IdlDecoder::new();
+    let program_id = Pubkey::new_unique();
+    let mut data = set_pyth_fee(program_id, Pubkey::new_unique(), 1).data;
+
+    for bad in [
+        &data[..4],
+        &data[..data.len() - 1],
+        &[4, 0, 0, 0, 0, 0, 0, 0][..],
+    ] {
+        assert!(matches!(
+            decoder.decode(bad, &[]),
+            Err(EntropySdkError::Undecodable(_))
+        ));
+    }
+    data.push(0);
+    assert!(matches!(
+        decoder.decode(&data, &[]),
+        Err(EntropySdkError::Undecodable(_))
+    ));
+}
+
+#[test]
+fn test_decode_account() {
+    let decoder = IdlDecoder::new();
+    let authority = Pubkey::new_unique();
+    let mut provider = Provider::zeroed();
+    provider.discriminator = Provider::discriminator();
+    provider.provider_authority = authority.to_bytes();
+    provider.sequence_number = 12;
+    provider.current_commitment = [5u8; 32];
+
+    let (name, value) = decoder.decode_account(bytes_of(&provider)).unwrap();
+    assert_eq!(name, "Provider");
+    assert_eq!(value["provider_authority"], authority.to_string());
+    assert_eq!(value["sequence_number"], 12);
+    assert_eq!(value["current_commitment"], "05".repeat(32));
+
+    assert!(decoder
+        .decode_account(&bytes_of(&provider)[..Provider::LEN - 1])
+        .is_err());
+    provider.discriminator = [0xff; 8];
+    assert!(decoder.decode_account(bytes_of(&provider)).is_err());
+}
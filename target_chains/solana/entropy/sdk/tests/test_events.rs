@@ -0,0 +1,260 @@
+use {
+    base64::{engine::general_purpose::STANDARD, Engine},
+    bytemuck::bytes_of,
+    entropy::events::{
+        AggregateRandomnessRevealed, AggregateRequestCreated, CallbackInvoked, DefaultProviderSet,
+        Event, PythFeeSet, RandomnessRevealed, RequestCreated, UserRandomnessGenerated,
+    },
+    entropy_sdk::{decode_log_line, parse_callback_failure, parse_logs, EntropyEvent},
+    solana_sdk::pubkey::Pubkey,
+};
+
+fn program_data<T: Event>(event: &T) -> String {
+    format!(
+        "Program data: {} {}",
+        STANDARD.encode(T::discriminator()),
+        STANDARD.encode(bytes_of(event))
+    )
+}
+
+fn revealed(sequence_number: u64) -> RandomnessRevealed {
+    RandomnessRevealed {
+        provider: [1u8; 32],
+        requester_program_id: [2u8; 32],
+        request_account: [3u8; 32],
+        sequence_number,
+        random_number: [4u8; 32],
+        callback_invoked: 1,
+        _padding0: [0u8; 7],
+        request_slot: 100,
+        reveal_slot: 103,
+    }
+}
+
+fn invoked(sequence_number: u64) -> CallbackInvoked {
+    CallbackInvoked {
+        provider: [1u8; 32],
+        requester_program_id: [2u8; 32],
+        request_account: [3u8; 32],
+        sequence_number,
+        request_slot: 100,
+        reveal_slot: 103,
+        compute_unit_limit: 200_000,
+        _padding0: [0u8; 4],
+    }
+}
+
+#[test]
+fn test_decode_log_line_request_created() {
+    let event = RequestCreated {
+        provider: [1u8; 32],
+        requester_program_id: [2u8; 32],
+        request_account: [3u8; 32],
+        payer: [4u8; 32],
+        sequence_number: 7,
+        request_slot: 99,
+        compute_unit_limit: 200_000,
+        use_blockhash: 0,
+        callback_status: 1,
+        random_number_version: 1,
+        _padding0: [0u8; 1],
+    };
+
+    let Some(EntropyEvent::RequestCreated(decoded)) = decode_log_line(&program_data(&event)) else {
+        panic!("expected RequestCreated");
+    };
+    assert_eq!(bytes_of(&decoded), bytes_of(&event));
+}
+
+#[test]
+fn test_decode_log_line_user_randomness_generated() {
+    let event = UserRandomnessGenerated {
+        provider: [1u8; 32],
+        request_account: [2u8; 32],
+        sequence_number: 7,
+        user_randomness: [3u8; 32],
+    };
+
+    let Some(EntropyEvent::UserRandomnessGenerated(decoded)) =
+        decode_log_line(&program_data(&event))
+    else {
+        panic!("expected UserRandomnessGenerated");
+    };
+    assert_eq!(bytes_of(&decoded), bytes_of(&event));
+}
+
+#[test]
+fn test_decode_log_line_aggregate_events() {
+    let created = AggregateRequestCreated {
+        requester_program_id: [1u8; 32],
+        request_account: [2u8; 32],
+        payer: [3u8; 32],
+        request_slot: 99,
+        num_providers: 3,
+        threshold: 2,
+        _padding0: [0u8; 6],
+    };
+    let Some(EntropyEvent::AggregateRequestCreated(decoded)) =
+        decode_log_line(&program_data(&created))
+    else {
+        panic!("expected AggregateRequestCreated");
+    };
+    assert_eq!(bytes_of(&decoded), bytes_of(&created));
+
+    let revealed = AggregateRandomnessRevealed {
+        requester_program_id: [1u8; 32],
+        request_account: [2u8; 32],
+        random_number: [4u8; 32],
+        request_slot: 99,
+        reveal_slot: 104,
+        revealed_mask: 0b011,
+        callback_invoked: 1,
+        _padding0: [0u8; 6],
+    };
+    let Some(EntropyEvent::AggregateRandomnessRevealed(decoded)) =
+        decode_log_line(&program_data(&revealed))
+    else {
+        panic!("expected AggregateRandomnessRevealed");
+    };
+    assert_eq!(bytes_of(&decoded), bytes_of(&revealed));
+}
+
+#[test]
+fn test_decode_log_line_admin_events() {
+    let fee_set = PythFeeSet {
+        old_fee_lamports: 1,
+        new_fee_lamports: 2,
+    };
+    let Some(EntropyEvent::PythFeeSet(decoded)) = decode_log_line(&program_data(&fee_set)) else {
+        panic!("expected PythFeeSet");
+    };
+    assert_eq!(bytes_of(&decoded), bytes_of(&fee_set));
+
+    let provider_set = DefaultProviderSet {
+        old_default_provider: [1u8; 32],
+        new_default_provider: [2u8; 32],
+    };
+    let Some(EntropyEvent::DefaultProviderSet(decoded)) =
+        decode_log_line(&program_data(&provider_set))
+    else {
+        panic!("expected DefaultProviderSet");
+    };
+    assert_eq!(bytes_of(&decoded), bytes_of(&provider_set));
+}
+
+#[test]
+fn test_decode_log_line_ignores_foreign_data() {
+    assert!(decode_log_line("Program log: hello").is_none());
+    assert!(decode_log_line("Program data: not-base64!").is_none());
+    let foreign = format!("Program data: {}", STANDARD.encode([0u8; 40]));
+    assert!(decode_log_line(&foreign).is_none());
+}
+
+#[test]
+fn test_parse_logs_attributes_data_to_entropy_only() {
+    let entropy = Pubkey::new_unique();
+    let callback = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {entropy} invoke [1]"),
+        format!("Program {callback} invoke [2]"),
+        // Same bytes logged by the callback program must not count.
+        program_data(&revealed(1)),
+        format!("Program {callback} success"),
+        program_data(&revealed(2)),
+        format!("Program {entropy} consumed 5000 of 200000 compute units"),
+        format!("Program {entropy} success"),
+    ];
+
+    let events = parse_logs(&entropy, &logs);
+    assert_eq!(events.len(), 1);
+    let EntropyEvent::RandomnessRevealed(event) = events[0] else {
+        panic!("expected RandomnessRevealed");
+    };
+    assert_eq!(event.sequence_number, 2);
+}
+
+#[test]
+fn test_decode_log_line_callback_invoked() {
+    let event = invoked(7);
+    let Some(EntropyEvent::CallbackInvoked(decoded)) = decode_log_line(&program_data(&event))
+    else {
+        panic!("expected CallbackInvoked");
+    };
+    assert_eq!(bytes_of(&decoded), bytes_of(&event));
+}
+
+#[test]
+fn test_parse_callback_failure() {
+    let entropy = Pubkey::new_unique();
+    let callback = Pubkey::new_unique();
+    let inner = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {entropy} invoke [1]"),
+        program_data(&invoked(7)),
+        format!("Program {callback} invoke [2]"),
+        // A failure the callback recovers from is not the callback's.
+        format!("Program {inner} invoke [3]"),
+        format!("Program {inner} consumed 100 of 150000 compute units"),
+        format!("Program {inner} failed: custom program error: 0x2"),
+        format!("Program {callback} consumed 4200 of 180000 compute units"),
+        format!("Program {callback} failed: custom program error: 0x1770"),
+        format!("Program {entropy} consumed 20000 of 200000 compute units"),
+        format!("Program {entropy} failed: custom program error: 0x1770"),
+    ];
+
+    let failure = parse_callback_failure(&entropy, &logs).expect("callback failure");
+    assert_eq!(bytes_of(&failure.request), bytes_of(&invoked(7)));
+    assert_eq!(failure.error, "custom program error: 0x1770");
+    assert_eq!(failure.error_code, Some(6000));
+    assert_eq!(failure.compute_units_consumed, Some(4200));
+}
+
+#[test]
+fn test_parse_callback_failure_without_compute_units() {
+    let entropy = Pubkey::new_unique();
+    let callback = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {entropy} invoke [1]"),
+        program_data(&invoked(7)),
+        format!("Program {callback} invoke [2]"),
+        format!("Program {callback} failed: SBF program panicked"),
+        format!("Program {entropy} failed: SBF program panicked"),
+    ];
+
+    let failure = parse_callback_failure(&entropy, &logs).expect("callback failure");
+    assert_eq!(failure.error, "SBF program panicked");
+    assert_eq!(failure.error_code, None);
+    assert_eq!(failure.compute_units_consumed, None);
+}
+
+#[test]
+fn test_parse_callback_failure_ignores_other_failures() {
+    let entropy = Pubkey::new_unique();
+    let callback = Pubkey::new_unique();
+    // The reveal failed before invoking the callback.
+    let before = vec![
+        format!("Program {entropy} invoke [1]"),
+        format!("Program {entropy} failed: custom program error: 0x5"),
+    ];
+    assert!(parse_callback_failure(&entropy, &before).is_none());
+
+    // The callback succeeded and the reveal failed afterwards.
+    let after = vec![
+        format!("Program {entropy} invoke [1]"),
+        program_data(&invoked(7)),
+        format!("Program {callback} invoke [2]"),
+        format!("Program {callback} success"),
+        format!("Program {entropy} failed: custom program error: 0x5"),
+    ];
+    assert!(parse_callback_failure(&entropy, &after).is_none());
+
+    // Another program logging the same bytes does not announce a callback.
+    let foreign = vec![
+        format!("Program {callback} invoke [1]"),
+        program_data(&invoked(7)),
+        format!("Program {entropy} invoke [2]"),
+        format!("Program {entropy} failed: custom program error: 0x5"),
+        format!("Program {callback} failed: custom program error: 0x5"),
+    ];
+    assert!(parse_callback_failure(&entropy, &foreign).is_none());
+}
@@ -0,0 +1,45 @@
+use {
+    bytemuck::Zeroable,
+    entropy_sdk::{request_fee, Config, Provider, RequestFee},
+};
+
+fn accounts() -> (Config, Provider) {
+    let mut config = Config::zeroed();
+    config.pyth_fee_lamports = 1_000;
+    let mut provider = Provider::zeroed();
+    provider.fee_lamports = 5_000;
+    provider.default_compute_unit_limit = 100_000;
+    (config, provider)
+}
+
+#[test]
+fn test_request_fee_at_default_limit() {
+    let (config, provider) = accounts();
+    for compute_unit_limit in [0, 100_000] {
+        let fee = request_fee(&config, &provider, compute_unit_limit).unwrap();
+        assert_eq!(
+            fee,
+            RequestFee {
+                provider_fee: 5_000,
+                pyth_fee: 1_000,
+                request_rent: 0,
+            }
+        );
+        assert_eq!(fee.fee(), 6_000);
+    }
+}
+
+#[test]
+fn test_request_fee_scales_above_default_limit() {
+    let (config, provider) = accounts();
+    let fee = request_fee(&config, &provider, 250_000).unwrap();
+    assert_eq!(fee.provider_fee, 5_000 + 7_500);
+    assert_eq!(
+        RequestFee {
+            request_rent: 2_000,
+            ..fee
+        }
+        .total(),
+        12_500 + 1_000 + 2_000
+    );
+}
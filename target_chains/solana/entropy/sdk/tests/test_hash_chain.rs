@@ -0,0 +1,87 @@
+use {
+    entropy_sdk::{verify_reveal, HashChain, HASH_CHAIN_SERIALIZED_LEN},
+    solana_sdk::{
+        hash::{hash, hashv},
+        pubkey::Pubkey,
+    },
+};
+
+#[test]
+fn test_hash_chain_commitment_and_contributions() {
+    let seed = [3u8; 32];
+    let mut chain = HashChain::from_seed(seed, 4);
+    chain.set_start_sequence(10);
+
+    let mut expected = seed;
+    for _ in 0..4 {
+        expected = hash(&expected).to_bytes();
+    }
+    assert_eq!(chain.commitment(), expected);
+    assert_eq!(chain.length(), 4);
+
+    // The commitment itself sits at the start sequence; each later sequence
+    // is one hash further back.
+    assert_eq!(
+        chain.contribution_for_sequence(10),
+        Some(chain.commitment())
+    );
+    for sequence in 11..=14 {
+        let contribution = chain.contribution_for_sequence(sequence).unwrap();
+        let mut hashed = contribution;
+        for _ in 0..sequence - 10 {
+            hashed = hash(&hashed).to_bytes();
+        }
+        assert_eq!(hashed, chain.commitment());
+    }
+    assert_eq!(chain.contribution_for_sequence(14), Some(seed));
+    assert_eq!(chain.contribution_for_sequence(9), None);
+    assert_eq!(chain.contribution_for_sequence(15), None);
+}
+
+#[test]
+fn test_hash_chain_reveals_out_of_order() {
+    let mut chain = HashChain::from_seed([5u8; 32], 8);
+    chain.set_start_sequence(2);
+    let user = [1u8; 32];
+
+    // A request at sequence 6 made while the on-chain commitment was still at
+    // sequence 4 (after an earlier reveal) carries num_hashes = 2.
+    let provider_commitment = chain.contribution_for_sequence(4).unwrap();
+    let commitment = hashv(&[&hash(&user).to_bytes(), &provider_commitment]).to_bytes();
+    let contribution = chain.contribution_for_sequence(6).unwrap();
+    assert!(verify_reveal(
+        &Pubkey::default(),
+        0,
+        user,
+        contribution,
+        2,
+        commitment,
+        None
+    )
+    .is_some());
+}
+
+#[test]
+fn test_hash_chain_derive_is_deterministic() {
+    let a = HashChain::derive(b"secret", 7, 16);
+    assert_eq!(a, HashChain::derive(b"secret", 7, 16));
+    assert_ne!(
+        a.commitment(),
+        HashChain::derive(b"secret", 8, 16).commitment()
+    );
+    assert_ne!(
+        a.commitment(),
+        HashChain::derive(b"other", 7, 16).commitment()
+    );
+}
+
+#[test]
+fn test_hash_chain_serialization_round_trip() {
+    let mut chain = HashChain::derive(b"secret", 1, 32);
+    chain.set_start_sequence(99);
+
+    let bytes = chain.to_bytes();
+    assert_eq!(bytes.len(), HASH_CHAIN_SERIALIZED_LEN);
+    assert_eq!(HashChain::from_bytes(&bytes).unwrap(), chain);
+    assert!(HashChain::from_bytes(&bytes[1..]).is_err());
+}
@@ -0,0 +1,85 @@
+use {
+    entropy::instruction::{RequestAggregateHeader, RequestV2Header, RequestWithCallbackHeader},
+    entropy_sdk::idl::{entropy_idl, idl_structs, IdlStruct},
+    serde_json::Value,
+    solana_sdk::pubkey::Pubkey,
+    std::collections::HashMap,
+};
+
+/// Encoded size of an IDL type, or `None` for variable-length types.
+fn encoded_size(ty: &Value, structs: &HashMap<&str, &IdlStruct>) -> Option<usize> {
+    match ty {
+        Value::String(name) => match name.as_str() {
+            "u8" => Some(1),
+            "u16" => Some(2),
+            "u32" => Some(4),
+            "u64" => Some(8),
+            "pubkey" => Some(32),
+            _ => None,
+        },
+        Value::Object(map) => {
+            if let Some(array) = map.get("array") {
+                let len = array[1].as_u64()? as usize;
+                Some(encoded_size(&array[0], structs)? * len)
+            } else if let Some(defined) = map.get("defined") {
+                structs.get(defined["name"].as_str()?)?.size
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+#[test]
+fn test_idl_matches_rust_layouts() {
+    let structs = idl_structs();
+    let by_name: HashMap<&str, &IdlStruct> = structs.iter().map(|s| (s.name, s)).collect();
+
+    for idl_struct in &structs {
+        let mut offset = 0;
+        for field in &idl_struct.fields {
+            let Some((field_offset, field_size)) = field.layout else {
+                continue;
+            };
+            assert_eq!(
+                field_offset, offset,
+                "{}.{} is not where the IDL places it",
+                idl_struct.name, field.name
+            );
+            assert_eq!(
+                encoded_size(&field.ty, &by_name),
+                Some(field_size),
+                "{}.{} has the wrong IDL type",
+                idl_struct.name,
+                field.name
+            );
+            offset += field_size;
+        }
+        match idl_struct.size {
+            Some(size) => assert_eq!(offset, size, "{} has unlisted fields", idl_struct.name),
+            // The header's trailing u32 is the Borsh length prefix of the
+            // callback account vector.
+            None => {
+                let header_size = match idl_struct.name {
+                    "RequestWithCallbackArgs" => core::mem::size_of::<RequestWithCallbackHeader>(),
+                    "RequestV2Args" => core::mem::size_of::<RequestV2Header>(),
+                    "RequestAggregateArgs" => core::mem::size_of::<RequestAggregateHeader>(),
+                    name => panic!("{name} has no known header"),
+                };
+                assert_eq!(offset + 4, header_size);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_checked_in_idl_is_up_to_date() {
+    let checked_in: Value = serde_json::from_str(include_str!("../idl/entropy.json")).unwrap();
+    assert_eq!(
+        checked_in,
+        entropy_idl(&Pubkey::default()),
+        "idl/entropy.json is stale; regenerate it with \
+         `cargo run -p entropy-sdk --bin entropy-idl > sdk/idl/entropy.json`"
+    );
+}
@@ -0,0 +1,347 @@
+//! Property-style checks of the program's instruction data parsers against the
+//! SDK builders, driven by a fixed-seed generator so failures reproduce.
+
+use {
+    entropy::{
+        accounts::CallbackMeta,
+        constants::{CALLBACK_IX_DATA_LEN, MAX_AGGREGATE_PROVIDERS, MAX_CALLBACK_ACCOUNTS},
+        instruction::{
+            parse_request_aggregate_args, parse_request_v2_args, parse_request_with_callback_args,
+            EntropyInstruction, RequestWithCallbackHeader, INSTRUCTION_DISCRIMINATOR_LEN,
+        },
+        pda::requester_signer_pda,
+    },
+    entropy_sdk::{
+        request_aggregate, request_v2, request_with_callback, AggregateRequestAccounts,
+        AggregateRequestParams, EntropySdkError, RequestAccounts, RequestParams,
+    },
+    solana_sdk::{instruction::AccountMeta, pubkey::Pubkey},
+};
+
+const CASES: usize = 500;
+
+/// xorshift64*; good enough to spread inputs, and deterministic.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+struct Case {
+    user_randomness: [u8; 32],
+    compute_unit_limit: u32,
+    ttl_slots: u32,
+    min_reveal_delay_slots: u16,
+    include_contributions: bool,
+    random_number_version: u8,
+    refund_address: Option<Pubkey>,
+    callback_accounts: Vec<AccountMeta>,
+    callback_ix_data: Vec<u8>,
+}
+
+fn request_accounts() -> RequestAccounts {
+    RequestAccounts {
+        requester_program: Pubkey::new_from_array([1u8; 32]),
+        payer: Pubkey::new_from_array([2u8; 32]),
+        request_account: Pubkey::new_from_array([3u8; 32]),
+        provider_authority: Pubkey::new_from_array([4u8; 32]),
+    }
+}
+
+fn aggregate_request_accounts(num_providers: usize) -> AggregateRequestAccounts {
+    let accounts = request_accounts();
+    AggregateRequestAccounts {
+        requester_program: accounts.requester_program,
+        payer: accounts.payer,
+        request_account: accounts.request_account,
+        provider_authorities: (0..num_providers)
+            .map(|index| Pubkey::new_from_array([5 + index as u8; 32]))
+            .collect(),
+    }
+}
+
+fn random_case(rng: &mut Rng) -> Case {
+    let mut user_randomness = [0u8; 32];
+    user_randomness.copy_from_slice(&rng.bytes(32));
+    let callback_accounts = (0..rng.below(MAX_CALLBACK_ACCOUNTS + 1))
+        .map(|_| {
+            let mut pubkey = [0u8; 32];
+            pubkey.copy_from_slice(&rng.bytes(32));
+            AccountMeta {
+                pubkey: Pubkey::new_from_array(pubkey),
+                is_signer: rng.below(2) == 1,
+                is_writable: rng.below(2) == 1,
+            }
+        })
+        .collect();
+    let callback_ix_data_len = rng.below(CALLBACK_IX_DATA_LEN + 1);
+    Case {
+        user_randomness,
+        compute_unit_limit: rng.next() as u32,
+        ttl_slots: rng.next() as u32,
+        min_reveal_delay_slots: rng.next() as u16,
+        include_contributions: rng.below(2) == 1,
+        random_number_version: rng.next() as u8,
+        refund_address: (rng.below(2) == 1).then(|| {
+            let mut address = [0u8; 32];
+            address.copy_from_slice(&rng.bytes(32));
+            Pubkey::new_from_array(address)
+        }),
+        callback_accounts,
+        callback_ix_data: rng.bytes(callback_ix_data_len),
+    }
+}
+
+fn encode(case: &Case) -> Vec<u8> {
+    request_with_callback(
+        Pubkey::default(),
+        &request_accounts(),
+        case.user_randomness,
+        &RequestParams {
+            compute_unit_limit: case.compute_unit_limit,
+            ttl_slots: case.ttl_slots,
+            min_reveal_delay_slots: case.min_reveal_delay_slots,
+            include_contributions: case.include_contributions,
+            random_number_version: case.random_number_version,
+            refund_address: case.refund_address,
+            callback_accounts: &case.callback_accounts,
+            callback_ix_data: &case.callback_ix_data,
+        },
+    )
+    .unwrap()
+    .data
+}
+
+/// On success the parsed pieces must account for every input byte.
+fn assert_exact_consumption(payload: &[u8]) {
+    if let Ok(args) = parse_request_with_callback_args(payload) {
+        assert_eq!(
+            core::mem::size_of::<RequestWithCallbackHeader>()
+                + args.callback_accounts.len() * CallbackMeta::LEN
+                + 4
+                + args.callback_ix_data.len(),
+            payload.len()
+        );
+    }
+}
+
+#[test]
+fn test_request_with_callback_round_trips() {
+    let mut rng = Rng(0x5eed_0001);
+    for _ in 0..CASES {
+        let case = random_case(&mut rng);
+        let data = encode(&case);
+
+        let (instruction, payload) = EntropyInstruction::parse(&data).unwrap();
+        assert!(matches!(
+            instruction,
+            EntropyInstruction::RequestWithCallback
+        ));
+        let args = parse_request_with_callback_args(payload).unwrap();
+        assert_eq!(args.user_randomness, case.user_randomness);
+        assert_eq!(args.compute_unit_limit, case.compute_unit_limit);
+        assert_eq!(args.ttl_slots, case.ttl_slots);
+        assert_eq!(args.min_reveal_delay_slots, case.min_reveal_delay_slots);
+        assert_eq!(
+            args.include_contributions,
+            u8::from(case.include_contributions)
+        );
+        assert_eq!(args.random_number_version, case.random_number_version);
+        assert_eq!(
+            args.refund_address,
+            case.refund_address
+                .map_or([0u8; 32], |address| address.to_bytes())
+        );
+        assert_eq!(args.callback_accounts.len(), case.callback_accounts.len());
+        for (parsed, expected) in args.callback_accounts.iter().zip(&case.callback_accounts) {
+            assert_eq!(parsed.pubkey, expected.pubkey.to_bytes());
+            assert_eq!(parsed.is_signer, u16::from(expected.is_signer));
+            assert_eq!(parsed.is_writable, u16::from(expected.is_writable));
+        }
+        assert_eq!(args.callback_ix_data, case.callback_ix_data.as_slice());
+        assert_eq!(
+            args.requester_signer_bump,
+            requester_signer_pda(&request_accounts().requester_program, &Pubkey::default()).1
+        );
+    }
+}
+
+#[test]
+fn test_request_v2_round_trips() {
+    let mut rng = Rng(0x5eed_0005);
+    for _ in 0..CASES {
+        let case = random_case(&mut rng);
+        let data = request_v2(
+            Pubkey::default(),
+            &request_accounts(),
+            &RequestParams {
+                compute_unit_limit: case.compute_unit_limit,
+                ttl_slots: case.ttl_slots,
+                min_reveal_delay_slots: case.min_reveal_delay_slots,
+                include_contributions: case.include_contributions,
+                random_number_version: case.random_number_version,
+                refund_address: case.refund_address,
+                callback_accounts: &case.callback_accounts,
+                callback_ix_data: &case.callback_ix_data,
+            },
+        )
+        .unwrap()
+        .data;
+
+        let (instruction, payload) = EntropyInstruction::parse(&data).unwrap();
+        assert!(matches!(instruction, EntropyInstruction::RequestV2));
+        // `RequestWithCallback`'s payload without the user randomness.
+        assert_eq!(
+            payload,
+            &encode(&case)[INSTRUCTION_DISCRIMINATOR_LEN + 32..]
+        );
+        let args = parse_request_v2_args(payload).unwrap();
+        assert_eq!(args.user_randomness, [0u8; 32]);
+        assert_eq!(args.compute_unit_limit, case.compute_unit_limit);
+        assert_eq!(args.ttl_slots, case.ttl_slots);
+        assert_eq!(args.min_reveal_delay_slots, case.min_reveal_delay_slots);
+        assert_eq!(
+            args.include_contributions,
+            u8::from(case.include_contributions)
+        );
+        assert_eq!(args.random_number_version, case.random_number_version);
+        assert_eq!(
+            args.refund_address,
+            case.refund_address
+                .map_or([0u8; 32], |address| address.to_bytes())
+        );
+        assert_eq!(args.callback_accounts.len(), case.callback_accounts.len());
+        assert_eq!(args.callback_ix_data, case.callback_ix_data.as_slice());
+        assert!(parse_request_v2_args(&payload[..payload.len() - 1]).is_err());
+    }
+}
+
+#[test]
+fn test_request_aggregate_round_trips() {
+    let mut rng = Rng(0x5eed_0006);
+    for _ in 0..CASES {
+        let case = random_case(&mut rng);
+        let num_providers = 1 + rng.below(MAX_AGGREGATE_PROVIDERS);
+        let threshold = 1 + rng.below(num_providers) as u8;
+        let data = request_aggregate(
+            Pubkey::default(),
+            &aggregate_request_accounts(num_providers),
+            case.user_randomness,
+            threshold,
+            &AggregateRequestParams {
+                compute_unit_limit: case.compute_unit_limit,
+                ttl_slots: case.ttl_slots,
+                min_reveal_delay_slots: case.min_reveal_delay_slots,
+                use_blockhash: case.include_contributions,
+                random_number_version: case.random_number_version,
+                refund_address: case.refund_address,
+                callback_accounts: &case.callback_accounts,
+                callback_ix_data: &case.callback_ix_data,
+            },
+        )
+        .unwrap()
+        .data;
+
+        let (instruction, payload) = EntropyInstruction::parse(&data).unwrap();
+        assert!(matches!(instruction, EntropyInstruction::RequestAggregate));
+        let args = parse_request_aggregate_args(payload).unwrap();
+        assert_eq!(args.user_randomness, case.user_randomness);
+        assert_eq!(args.compute_unit_limit, case.compute_unit_limit);
+        assert_eq!(args.ttl_slots, case.ttl_slots);
+        assert_eq!(args.min_reveal_delay_slots, case.min_reveal_delay_slots);
+        assert_eq!(usize::from(args.num_providers), num_providers);
+        assert_eq!(args.threshold, threshold);
+        assert_eq!(args.use_blockhash, u8::from(case.include_contributions));
+        assert_eq!(args.random_number_version, case.random_number_version);
+        assert_eq!(
+            args.refund_address,
+            case.refund_address
+                .map_or([0u8; 32], |address| address.to_bytes())
+        );
+        assert_eq!(args.callback_accounts.len(), case.callback_accounts.len());
+        assert_eq!(args.callback_ix_data, case.callback_ix_data.as_slice());
+        assert!(parse_request_aggregate_args(&payload[..payload.len() - 1]).is_err());
+    }
+}
+
+#[test]
+fn test_request_aggregate_rejects_invalid_threshold() {
+    for (num_providers, threshold) in [(0, 0), (2, 0), (2, 3), (MAX_AGGREGATE_PROVIDERS + 1, 1)] {
+        assert!(matches!(
+            request_aggregate(
+                Pubkey::default(),
+                &aggregate_request_accounts(num_providers),
+                [0u8; 32],
+                threshold,
+                &AggregateRequestParams::default()
+            ),
+            Err(EntropySdkError::InvalidAggregateThreshold { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_request_with_callback_rejects_truncated_and_extended_data() {
+    let mut rng = Rng(0x5eed_0002);
+    for _ in 0..CASES / 10 {
+        let data = encode(&random_case(&mut rng));
+        let payload = &data[INSTRUCTION_DISCRIMINATOR_LEN..];
+        for len in 0..payload.len() {
+            assert!(parse_request_with_callback_args(&payload[..len]).is_err());
+        }
+
+        let mut extended = payload.to_vec();
+        extended.push(0);
+        assert!(parse_request_with_callback_args(&extended).is_err());
+    }
+}
+
+#[test]
+fn test_request_with_callback_mutations_never_panic() {
+    let mut rng = Rng(0x5eed_0003);
+    for _ in 0..CASES {
+        let data = encode(&random_case(&mut rng));
+        let mut payload = data[INSTRUCTION_DISCRIMINATOR_LEN..].to_vec();
+        for _ in 0..=rng.below(4) {
+            let index = rng.below(payload.len());
+            payload[index] = rng.next() as u8;
+        }
+        assert_exact_consumption(&payload);
+    }
+}
+
+#[test]
+fn test_request_with_callback_hostile_lengths_never_panic() {
+    let header_len = core::mem::size_of::<RequestWithCallbackHeader>();
+    let mut rng = Rng(0x5eed_0004);
+    for accounts_len in [0, 1, 16, 17, u32::MAX / 36, u32::MAX / 36 + 1, u32::MAX] {
+        for ix_len in [0, 1, 256, 257, u32::MAX] {
+            let mut payload = rng.bytes(header_len);
+            payload[header_len - 4..].copy_from_slice(&accounts_len.to_le_bytes());
+            let accounts_bytes = rng.below(2 * CallbackMeta::LEN);
+            payload.extend(rng.bytes(accounts_bytes));
+            payload.extend(ix_len.to_le_bytes());
+            let ix_bytes = rng.below(300);
+            payload.extend(rng.bytes(ix_bytes));
+            assert_exact_consumption(&payload);
+        }
+    }
+    for _ in 0..CASES {
+        let len = rng.below(2048);
+        assert_exact_consumption(&rng.bytes(len));
+        let _ = EntropyInstruction::parse(&rng.bytes(len));
+    }
+}
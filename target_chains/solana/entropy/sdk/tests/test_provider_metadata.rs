@@ -0,0 +1,110 @@
+use {
+    bytemuck::Zeroable,
+    entropy_sdk::{
+        constants::URI_LEN, provider_uri, validate_provider_uri, EntropySdkError, FeeSchedule,
+        Provider, ProviderContact, ProviderMetadata,
+    },
+};
+
+#[test]
+fn test_parse_metadata_document() {
+    let document = br#"{
+        "version": 1,
+        "name": "Example Provider",
+        "contact": { "email": "ops@example.com" },
+        "fees": [
+            { "max_compute_unit_limit": 100000, "fee_lamports": 5000 },
+            { "fee_lamports": 20000, "description": "larger callbacks" }
+        ],
+        "unknown_field": true
+    }"#;
+    let metadata = ProviderMetadata::from_json(document).unwrap();
+    assert_eq!(
+        metadata,
+        ProviderMetadata {
+            version: 1,
+            name: "Example Provider".to_string(),
+            description: None,
+            contact: ProviderContact {
+                email: Some("ops@example.com".to_string()),
+                url: None,
+            },
+            fees: vec![
+                FeeSchedule {
+                    max_compute_unit_limit: Some(100_000),
+                    fee_lamports: 5_000,
+                    description: None,
+                },
+                FeeSchedule {
+                    max_compute_unit_limit: None,
+                    fee_lamports: 20_000,
+                    description: Some("larger callbacks".to_string()),
+                },
+            ],
+        }
+    );
+
+    let round_trip = serde_json::to_vec(&metadata).unwrap();
+    assert_eq!(ProviderMetadata::from_json(&round_trip).unwrap(), metadata);
+}
+
+#[test]
+fn test_reject_invalid_metadata() {
+    for document in [
+        &br#"{"version": 2, "name": "Future"}"#[..],
+        br#"{"version": 0, "name": "Zero"}"#,
+        br#"{"version": 1}"#,
+        b"not json",
+    ] {
+        assert!(matches!(
+            ProviderMetadata::from_json(document),
+            Err(EntropySdkError::InvalidProviderMetadata(_))
+        ));
+    }
+}
+
+#[test]
+fn test_validate_provider_uri() {
+    validate_provider_uri("https://example.com/entropy.json", true).unwrap();
+    validate_provider_uri("http://localhost:8080", false).unwrap();
+
+    for (uri, require_https) in [
+        ("http://example.com", true),
+        ("ftp://example.com", false),
+        ("example.com", false),
+        ("https://", false),
+        ("https://:443/path", false),
+        ("https://example.com/a b", false),
+    ] {
+        assert!(
+            matches!(
+                validate_provider_uri(uri, require_https),
+                Err(EntropySdkError::InvalidProviderUri(_))
+            ),
+            "{uri}"
+        );
+    }
+
+    let long = format!("https://example.com/{}", "a".repeat(URI_LEN));
+    assert!(matches!(
+        validate_provider_uri(&long, false),
+        Err(EntropySdkError::FieldTooLong { field: "uri", .. })
+    ));
+}
+
+#[test]
+fn test_provider_uri() {
+    let mut provider = Provider::zeroed();
+    assert_eq!(provider_uri(&provider), None);
+
+    let uri = b"https://example.com";
+    provider.uri[..uri.len()].copy_from_slice(uri);
+    provider.uri_len = uri.len() as u16;
+    assert_eq!(provider_uri(&provider), Some("https://example.com"));
+    assert_eq!(provider.uri_str(), "https://example.com");
+
+    // Registered before the program checked URIs.
+    provider.uri[0] = 0xff;
+    assert_eq!(provider.uri_str(), "");
+    assert_eq!(provider_uri(&provider), None);
+}
@@ -0,0 +1,142 @@
+use {
+    bytemuck::Zeroable,
+    entropy::constants::{RANDOM_NUMBER_DOMAIN, RANDOM_NUMBER_V0, RANDOM_NUMBER_V1},
+    entropy_sdk::{preview_request_commitment, verify_reveal, Provider},
+    solana_sdk::{
+        hash::{hash, hashv},
+        pubkey::Pubkey,
+    },
+};
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([4u8; 32]);
+
+/// Builds a commitment the way `Request` does: the provider commitment is
+/// `num_hashes` hashes past the revealed contribution.
+fn commit(user: [u8; 32], provider_contribution: [u8; 32], num_hashes: u32) -> [u8; 32] {
+    let mut provider_commitment = provider_contribution;
+    for _ in 0..num_hashes {
+        provider_commitment = hash(&provider_commitment).to_bytes();
+    }
+    hashv(&[&hash(&user).to_bytes(), &provider_commitment]).to_bytes()
+}
+
+#[test]
+fn test_verify_reveal_matches_program_derivation() {
+    let user = [1u8; 32];
+    let provider = [2u8; 32];
+    let commitment = commit(user, provider, 3);
+
+    let random = verify_reveal(
+        &PROGRAM_ID,
+        RANDOM_NUMBER_V0,
+        user,
+        provider,
+        3,
+        commitment,
+        None,
+    )
+    .unwrap();
+    assert_eq!(random, hashv(&[&user, &provider, &[0u8; 32]]).to_bytes());
+
+    let blockhash = [9u8; 32];
+    let random = verify_reveal(
+        &PROGRAM_ID,
+        RANDOM_NUMBER_V0,
+        user,
+        provider,
+        3,
+        commitment,
+        Some(blockhash),
+    )
+    .unwrap();
+    assert_eq!(random, hashv(&[&user, &provider, &blockhash]).to_bytes());
+}
+
+#[test]
+fn test_verify_reveal_v1_binds_program_id() {
+    let user = [1u8; 32];
+    let provider = [2u8; 32];
+    let blockhash = [9u8; 32];
+    let commitment = commit(user, provider, 3);
+
+    let random = verify_reveal(
+        &PROGRAM_ID,
+        RANDOM_NUMBER_V1,
+        user,
+        provider,
+        3,
+        commitment,
+        Some(blockhash),
+    )
+    .unwrap();
+    assert_eq!(
+        random,
+        hashv(&[
+            b"entropy:random_number:v1",
+            PROGRAM_ID.as_ref(),
+            &user,
+            &provider,
+            &blockhash,
+        ])
+        .to_bytes()
+    );
+    assert_eq!(RANDOM_NUMBER_DOMAIN, b"entropy:random_number:v1");
+
+    // The same contributions give unrelated numbers on another deployment and
+    // under the original derivation.
+    let other_program = verify_reveal(
+        &Pubkey::new_from_array([5u8; 32]),
+        RANDOM_NUMBER_V1,
+        user,
+        provider,
+        3,
+        commitment,
+        Some(blockhash),
+    )
+    .unwrap();
+    assert_ne!(random, other_program);
+    assert_ne!(random, hashv(&[&user, &provider, &blockhash]).to_bytes());
+}
+
+#[test]
+fn test_verify_reveal_rejects_mismatch() {
+    let user = [1u8; 32];
+    let provider = [2u8; 32];
+    let commitment = commit(user, provider, 3);
+    let verify = |user, provider, num_hashes| {
+        verify_reveal(
+            &PROGRAM_ID,
+            RANDOM_NUMBER_V1,
+            user,
+            provider,
+            num_hashes,
+            commitment,
+            None,
+        )
+    };
+
+    assert!(verify([5u8; 32], provider, 3).is_none());
+    assert!(verify(user, [5u8; 32], 3).is_none());
+    assert!(verify(user, provider, 2).is_none());
+}
+
+#[test]
+fn test_preview_request_commitment_matches_reveal() {
+    let user = [1u8; 32];
+    let provider_contribution = [2u8; 32];
+    let mut provider = Provider::zeroed();
+    provider.current_commitment = hash(&hash(&provider_contribution).to_bytes()).to_bytes();
+
+    let commitment = preview_request_commitment(&user, &provider);
+    assert_eq!(commitment, commit(user, provider_contribution, 2));
+    assert!(verify_reveal(
+        &PROGRAM_ID,
+        RANDOM_NUMBER_V1,
+        user,
+        provider_contribution,
+        2,
+        commitment,
+        None,
+    )
+    .is_some());
+}
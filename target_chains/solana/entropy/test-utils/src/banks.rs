@@ -10,23 +10,26 @@ use {
     },
 };
 
-use super::instructions::build_initialize_ix;
+use crate::{
+    instructions::build_initialize_ix,
+    program_data::{add_deployer, deployer},
+};
 
-#[allow(dead_code)]
 pub async fn initialize_config(
     banks_client: &mut BanksClient,
     payer: &Keypair,
     program_id: solana_program::pubkey::Pubkey,
     pyth_fee_lamports: u64,
 ) {
+    let deployer = deployer();
     let instruction = build_initialize_ix(
         program_id,
-        payer.pubkey(),
+        deployer.pubkey(),
         solana_program::pubkey::Pubkey::new_unique(),
         solana_program::pubkey::Pubkey::new_unique(),
         pyth_fee_lamports,
     );
-    submit_tx(banks_client, payer, &[instruction], &[]).await;
+    submit_tx(banks_client, payer, &[instruction], &[&deployer]).await;
 
     let (config_address, _) = config_pda(&program_id);
     let config_account = banks_client
@@ -76,10 +79,40 @@ pub async fn submit_tx_expect_err(
         .unwrap()
 }
 
+/// Like [`submit_tx`], but returns the transaction's result and log messages
+/// instead of panicking on failure.
+pub async fn submit_tx_with_metadata(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    additional_signers: &[&Keypair],
+) -> (Result<(), TransactionError>, Vec<String>) {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut signers = Vec::with_capacity(1 + additional_signers.len());
+    signers.push(payer);
+    for signer in additional_signers {
+        signers.push(*signer);
+    }
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    transaction.sign(&signers, recent_blockhash);
+    let outcome = banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    let logs = outcome
+        .metadata
+        .map(|metadata| metadata.log_messages)
+        .unwrap_or_default();
+    (outcome.result, logs)
+}
+
+/// Entropy as a native processor, deployed by [`deployer`].
 pub fn new_entropy_program_test(program_id: Pubkey) -> ProgramTest {
-    ProgramTest::new(
+    let mut program_test = ProgramTest::new(
         "entropy",
         program_id,
         processor!(entropy::processor::process_instruction),
-    )
+    );
+    add_deployer(&mut program_test, program_id);
+    program_test
 }
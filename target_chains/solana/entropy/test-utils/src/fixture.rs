@@ -0,0 +1,332 @@
+#[allow(deprecated)]
+use {
+    crate::{
+        banks::{initialize_config, submit_tx},
+        instructions::build_register_provider_ix,
+        keeper::MockKeeper,
+        program_data::add_deployer,
+        register_args::build_register_args,
+    },
+    bytemuck::try_from_bytes,
+    entropy::{
+        accounts::Request,
+        constants::REQUESTER_SIGNER_SEED,
+        pda::{
+            config_pda, entropy_signer_pda, provider_pda, provider_vault_pda, pyth_fee_vault_pda,
+        },
+    },
+    entropy_sdk::{
+        hash_chain::HashChain,
+        instruction::{
+            forward_request_with_callback, request_with_callback, RequestAccounts, RequestParams,
+        },
+    },
+    simple_requester::{CALLBACK_ACTION, CALLBACK_STATE_LEN},
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+    },
+    solana_program_test::{processor, ProgramTest, ProgramTestContext},
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+/// Seed of the provider hash chain registered by [`EntropyFixture`].
+pub const PROVIDER_CHAIN_SEED: [u8; 32] = [3u8; 32];
+
+/// User randomness of the requests built by
+/// [`EntropyTestEnv::callback_request_ix`].
+pub const REQUEST_USER_RANDOMNESS: [u8; 32] = [7u8; 32];
+
+/// Starts the setup most request tests share: a provider charging
+/// `provider_fee_lamports` with a chain of 16, simple-requester, and a
+/// [`MockKeeper`] for the provider.
+pub async fn start_with_requester(provider_fee_lamports: u64) -> (EntropyTestEnv, MockKeeper) {
+    let env = EntropyFixture::new()
+        .with_provider(provider_fee_lamports, 16)
+        .with_requester()
+        .start()
+        .await;
+    let keeper = MockKeeper::new(&env);
+    (env, keeper)
+}
+
+/// Builds a program-test bank with the entropy program initialized and,
+/// optionally, a registered provider and the simple-requester consumer:
+///
+/// ```ignore
+/// let env = EntropyFixture::new()
+///     .with_provider(1_000, 64)
+///     .with_requester()
+///     .start()
+///     .await;
+/// ```
+///
+/// The programs run as native processors and the payer doubles as the
+/// provider authority. Entropy records the user randomness of callback
+/// requests so a [`MockKeeper`](crate::MockKeeper) can reveal them.
+pub struct EntropyFixture {
+    program_id: Pubkey,
+    pyth_fee_lamports: u64,
+    provider: Option<(u64, u64)>,
+    requester: bool,
+    setup: Vec<Box<dyn FnOnce(&mut ProgramTest)>>,
+}
+
+impl Default for EntropyFixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntropyFixture {
+    pub fn new() -> Self {
+        Self {
+            program_id: Pubkey::new_unique(),
+            pyth_fee_lamports: 0,
+            provider: None,
+            requester: false,
+            setup: Vec::new(),
+        }
+    }
+
+    pub fn with_program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    pub fn with_pyth_fee(mut self, pyth_fee_lamports: u64) -> Self {
+        self.pyth_fee_lamports = pyth_fee_lamports;
+        self
+    }
+
+    /// Registers the payer as a provider charging `fee_lamports`, committed
+    /// to a hash chain of `chain_length` from [`PROVIDER_CHAIN_SEED`].
+    pub fn with_provider(mut self, fee_lamports: u64, chain_length: u64) -> Self {
+        self.provider = Some((fee_lamports, chain_length));
+        self
+    }
+
+    /// Adds simple-requester and a callback state account it owns.
+    pub fn with_requester(mut self) -> Self {
+        self.requester = true;
+        self
+    }
+
+    /// Runs `setup` on the `ProgramTest` before it starts, e.g. to add a
+    /// consumer program:
+    ///
+    /// ```ignore
+    /// .with_setup(move |program_test| {
+    ///     program_test.add_program("coin_flip", coin_flip_id, processor!(coin_flip::process_instruction))
+    /// })
+    /// ```
+    pub fn with_setup(mut self, setup: impl FnOnce(&mut ProgramTest) + 'static) -> Self {
+        self.setup.push(Box::new(setup));
+        self
+    }
+
+    pub async fn start(self) -> EntropyTestEnv {
+        let program_id = self.program_id;
+        let requester_program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::new(
+            "entropy",
+            program_id,
+            processor!(crate::keeper::process_instruction),
+        );
+        add_deployer(&mut program_test, program_id);
+        if self.requester {
+            program_test.add_program(
+                "simple-requester",
+                requester_program_id,
+                processor!(simple_requester::process_instruction),
+            );
+        }
+        for setup in self.setup {
+            setup(&mut program_test);
+        }
+        let mut context = program_test.start_with_context().await;
+        let payer = context.payer.insecure_clone();
+
+        initialize_config(
+            &mut context.banks_client,
+            &payer,
+            program_id,
+            self.pyth_fee_lamports,
+        )
+        .await;
+
+        let provider = match self.provider {
+            Some((fee_lamports, chain_length)) => {
+                let chain = HashChain::from_seed(PROVIDER_CHAIN_SEED, chain_length);
+                let (address, _) = provider_pda(&program_id, &payer.pubkey());
+                let (vault, _) = provider_vault_pda(&program_id, &payer.pubkey());
+                let register_ix = build_register_provider_ix(
+                    program_id,
+                    payer.pubkey(),
+                    address,
+                    vault,
+                    build_register_args(fee_lamports, chain.commitment(), chain_length),
+                    true,
+                );
+                submit_tx(&mut context.banks_client, &payer, &[register_ix], &[]).await;
+                Some(TestProvider {
+                    authority: payer.pubkey(),
+                    address,
+                    vault,
+                    chain,
+                })
+            }
+            None => None,
+        };
+
+        let requester = if self.requester {
+            let callback_state = Keypair::new();
+            let create_callback_state_ix = system_instruction::create_account(
+                &payer.pubkey(),
+                &callback_state.pubkey(),
+                Rent::default().minimum_balance(CALLBACK_STATE_LEN),
+                CALLBACK_STATE_LEN as u64,
+                &requester_program_id,
+            );
+            submit_tx(
+                &mut context.banks_client,
+                &payer,
+                &[create_callback_state_ix],
+                &[&callback_state],
+            )
+            .await;
+            let (signer, _) = Pubkey::find_program_address(
+                &[REQUESTER_SIGNER_SEED, program_id.as_ref()],
+                &requester_program_id,
+            );
+            Some(TestRequester {
+                program_id: requester_program_id,
+                signer,
+                callback_state: callback_state.pubkey(),
+            })
+        } else {
+            None
+        };
+
+        EntropyTestEnv {
+            context,
+            program_id,
+            config: config_pda(&program_id).0,
+            pyth_fee_vault: pyth_fee_vault_pda(&program_id).0,
+            entropy_signer: entropy_signer_pda(&program_id).0,
+            provider,
+            requester,
+        }
+    }
+}
+
+/// A provider registered by [`EntropyFixture::with_provider`].
+pub struct TestProvider {
+    pub authority: Pubkey,
+    pub address: Pubkey,
+    pub vault: Pubkey,
+    /// Starts at sequence 0, the provider's commitment after registration.
+    pub chain: HashChain,
+}
+
+/// The simple-requester deployment added by [`EntropyFixture::with_requester`].
+pub struct TestRequester {
+    pub program_id: Pubkey,
+    /// The requester-signer PDA simple-requester signs entropy requests with.
+    pub signer: Pubkey,
+    pub callback_state: Pubkey,
+}
+
+/// A started [`EntropyFixture`].
+pub struct EntropyTestEnv {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    pub config: Pubkey,
+    pub pyth_fee_vault: Pubkey,
+    pub entropy_signer: Pubkey,
+    pub provider: Option<TestProvider>,
+    pub requester: Option<TestRequester>,
+}
+
+impl EntropyTestEnv {
+    /// The fee payer, which is also the provider authority.
+    pub fn payer(&self) -> Keypair {
+        self.context.payer.insecure_clone()
+    }
+
+    pub fn provider(&self) -> &TestProvider {
+        self.provider
+            .as_ref()
+            .expect("fixture was started without with_provider")
+    }
+
+    pub fn requester(&self) -> &TestRequester {
+        self.requester
+            .as_ref()
+            .expect("fixture was started without with_requester")
+    }
+
+    /// The entropy `RequestWithCallback` of a simple-requester request from
+    /// the payer to the fixture's provider, with [`REQUEST_USER_RANDOMNESS`].
+    /// It calls back into the requester's callback state with
+    /// `[callback_action, entropy program id]`, in place of the callback
+    /// accounts and data of `params`. Send it through
+    /// `forward_request_with_callback`.
+    pub fn callback_request_ix(
+        &self,
+        request_account: &Keypair,
+        callback_action: u8,
+        params: RequestParams,
+    ) -> Instruction {
+        let mut callback_ix_data = vec![callback_action];
+        callback_ix_data.extend_from_slice(self.program_id.as_ref());
+        request_with_callback(
+            self.program_id,
+            &RequestAccounts {
+                requester_program: self.requester().program_id,
+                payer: self.context.payer.pubkey(),
+                request_account: request_account.pubkey(),
+                provider_authority: self.provider().authority,
+            },
+            REQUEST_USER_RANDOMNESS,
+            &RequestParams {
+                callback_accounts: &[AccountMeta::new(self.requester().callback_state, false)],
+                callback_ix_data: &callback_ix_data,
+                ..params
+            },
+        )
+        .unwrap()
+    }
+
+    /// Submits a [`callback_request_ix`](Self::callback_request_ix) calling
+    /// back with `CALLBACK_ACTION`, without revealing it, and returns the
+    /// request account and the request.
+    pub async fn submit_callback_request(
+        &mut self,
+        params: RequestParams<'_>,
+    ) -> (Pubkey, Request) {
+        let payer = self.payer();
+        let request_account = Keypair::new();
+        let entropy_ix = self.callback_request_ix(&request_account, CALLBACK_ACTION, params);
+        let instruction = forward_request_with_callback(self.requester().program_id, &entropy_ix);
+        submit_tx(
+            &mut self.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await;
+
+        let account = self
+            .context
+            .banks_client
+            .get_account(request_account.pubkey())
+            .await
+            .unwrap()
+            .unwrap();
+        let request = *try_from_bytes::<Request>(&account.data).unwrap();
+        (request_account.pubkey(), request)
+    }
+}
@@ -1,9 +1,9 @@
 #[allow(deprecated)]
 use {
-    bytemuck::bytes_of,
     entropy::{
         instruction::{EntropyInstruction, InitializeArgs, RegisterProviderArgs},
-        pda::{config_pda, pyth_fee_vault_pda},
+        pda::{config_pda, provider_allowlist_pda, provider_directory_pda, pyth_fee_vault_pda},
+        program_data::program_data_address,
     },
     solana_program::{
         instruction::{AccountMeta, Instruction},
@@ -26,23 +26,21 @@ pub fn build_initialize_ix(
         pyth_fee_lamports,
         default_provider: default_provider.to_bytes(),
     };
-    let mut data = Vec::with_capacity(8 + core::mem::size_of::<InitializeArgs>());
-    data.extend_from_slice(&EntropyInstruction::Initialize.discriminator());
-    data.extend_from_slice(bytes_of(&args));
-
     Instruction {
         program_id,
-        data,
+        data: EntropyInstruction::build(&args),
         accounts: vec![
             AccountMeta::new(payer, true),
             AccountMeta::new(config, false),
             AccountMeta::new(pyth_fee_vault, false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(program_data_address(&program_id), false),
         ],
     }
 }
 
-#[allow(dead_code)]
+/// Lists a first-time provider in directory page 0, which holds every
+/// provider a test registers.
 pub fn build_register_provider_ix(
     program_id: Pubkey,
     provider_authority: Pubkey,
@@ -51,18 +49,17 @@ pub fn build_register_provider_ix(
     args: RegisterProviderArgs,
     provider_authority_is_signer: bool,
 ) -> Instruction {
-    let mut data = Vec::with_capacity(8 + core::mem::size_of::<RegisterProviderArgs>());
-    data.extend_from_slice(&EntropyInstruction::RegisterProvider.discriminator());
-    data.extend_from_slice(bytes_of(&args));
-
     Instruction {
         program_id,
-        data,
+        data: EntropyInstruction::build(&args),
         accounts: vec![
             AccountMeta::new(provider_authority, provider_authority_is_signer),
             AccountMeta::new(provider_account, false),
             AccountMeta::new(provider_vault, false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(config_pda(&program_id).0, false),
+            AccountMeta::new(provider_directory_pda(&program_id, 0).0, false),
+            AccountMeta::new_readonly(provider_allowlist_pda(&program_id).0, false),
         ],
     }
 }
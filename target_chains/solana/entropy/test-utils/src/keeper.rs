@@ -0,0 +1,245 @@
+//! An in-process stand-in for a provider's keeper, so consumer programs can
+//! test their callbacks end to end without running the provider side.
+//!
+//! Entropy only stores the commitment to a request's user randomness; a real
+//! keeper recovers the randomness from the request transaction. In program
+//! tests the fixture runs entropy through [`process_instruction`], which
+//! records the randomness of every successful `RequestWithCallback` or
+//! `RequestV2` no matter how deep the CPI that issued it. [`MockKeeper`]
+//! reveals those requests with the provider's hash chain.
+
+use {
+    crate::{banks::submit_tx_with_metadata, fixture::EntropyTestEnv},
+    bytemuck::try_from_bytes,
+    entropy::{
+        accounts::{CallbackAccountList, Config, Request},
+        events::RandomnessRevealed,
+        instruction::{parse_request_with_callback_args, EntropyInstruction, RevealArgs},
+    },
+    entropy_sdk::{
+        events::{parse_logs, EntropyEvent},
+        hash_chain::HashChain,
+        instruction::{reveal_with_callback, reveal_with_callback_account_list},
+    },
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    solana_program_test::BanksClient,
+    solana_sdk::{instruction::Instruction, signature::Keypair, transaction::TransactionError},
+    std::{collections::BTreeMap, sync::Mutex},
+};
+
+/// Indices of the request and config accounts in `RequestWithCallback`'s and
+/// `RequestV2`'s accounts.
+const REQUEST_ACCOUNT_INDEX: usize = 3;
+const CONFIG_ACCOUNT_INDEX: usize = 6;
+
+/// User randomness of recorded requests, keyed by entropy program id and
+/// request account. Tests in one binary share it, but every fixture deploys
+/// entropy at its own address.
+static USER_RANDOMNESS: Mutex<BTreeMap<(Pubkey, Pubkey), [u8; 32]>> = Mutex::new(BTreeMap::new());
+
+/// Entropy's processor, additionally recording the user randomness of each
+/// successful `RequestWithCallback` and `RequestV2` for [`MockKeeper`].
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    entropy::processor::process_instruction(program_id, accounts, data)?;
+
+    let user_randomness = match EntropyInstruction::parse(data) {
+        Ok((EntropyInstruction::RequestWithCallback, payload)) => {
+            parse_request_with_callback_args(payload)?.user_randomness
+        }
+        // Entropy leaves the randomness it generated as the config seed.
+        Ok((EntropyInstruction::RequestV2, _)) => match accounts.get(CONFIG_ACCOUNT_INDEX) {
+            Some(config) => {
+                try_from_bytes::<Config>(&config.try_borrow_data()?)
+                    .map_err(|_| ProgramError::InvalidAccountData)?
+                    .seed
+            }
+            None => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+    if let Some(request_account) = accounts.get(REQUEST_ACCOUNT_INDEX) {
+        USER_RANDOMNESS
+            .lock()
+            .unwrap()
+            .insert((*program_id, *request_account.key), user_randomness);
+    }
+    Ok(())
+}
+
+/// The outcome of revealing one request.
+pub struct Reveal {
+    pub request_account: Pubkey,
+    pub sequence_number: u64,
+    /// The reveal's `RandomnessRevealed` event, or why the reveal failed
+    /// (e.g. the callback returned an error).
+    pub result: Result<RandomnessRevealed, TransactionError>,
+}
+
+/// Reveals the callback requests made to the fixture's provider.
+pub struct MockKeeper {
+    program_id: Pubkey,
+    provider_authority: Pubkey,
+    chain: HashChain,
+}
+
+impl MockKeeper {
+    pub fn new(env: &EntropyTestEnv) -> Self {
+        let provider = env.provider();
+        Self {
+            program_id: env.program_id,
+            provider_authority: provider.authority,
+            chain: provider.chain.clone(),
+        }
+    }
+
+    /// Submits `instructions`, then reveals every pending request, including
+    /// the ones they created.
+    pub async fn submit(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        instructions: &[Instruction],
+        additional_signers: &[&Keypair],
+    ) -> Result<Vec<Reveal>, TransactionError> {
+        let (result, _) =
+            submit_tx_with_metadata(banks_client, payer, instructions, additional_signers).await;
+        result?;
+        Ok(self.reveal_pending(banks_client, payer).await)
+    }
+
+    /// Reveals every recorded request that is still pending, in sequence
+    /// order. A failed reveal leaves its request pending, so it is retried
+    /// on the next call.
+    pub async fn reveal_pending(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+    ) -> Vec<Reveal> {
+        let recorded: Vec<(Pubkey, [u8; 32])> = USER_RANDOMNESS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((program_id, _), _)| *program_id == self.program_id)
+            .map(|((_, request_account), user_randomness)| (*request_account, *user_randomness))
+            .collect();
+
+        let mut pending = Vec::new();
+        for (request_account, user_randomness) in recorded {
+            let request = banks_client
+                .get_account(request_account)
+                .await
+                .unwrap()
+                .filter(|account| account.owner == self.program_id)
+                .and_then(|account| try_from_bytes::<Request>(&account.data).ok().copied());
+            match request {
+                Some(request)
+                    if Pubkey::new_from_array(request.provider) == self.provider_authority
+                        && request.awaits_callback() =>
+                {
+                    pending.push((request_account, request, user_randomness));
+                }
+                Some(_) => {}
+                None => self.forget(&request_account),
+            }
+        }
+        pending.sort_by_key(|(_, request, _)| request.sequence_number);
+
+        let mut reveals = Vec::with_capacity(pending.len());
+        for (request_account, request, user_randomness) in pending {
+            reveals.push(
+                self.reveal(
+                    banks_client,
+                    payer,
+                    request_account,
+                    &request,
+                    user_randomness,
+                )
+                .await,
+            );
+        }
+        reveals
+    }
+
+    async fn reveal(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        request_account: Pubkey,
+        request: &Request,
+        user_randomness: [u8; 32],
+    ) -> Reveal {
+        let sequence_number = request.sequence_number;
+        let Some(provider_contribution) = self.chain.contribution_for_sequence(sequence_number)
+        else {
+            panic!("sequence {sequence_number} is past the end of the provider's hash chain");
+        };
+        let args = RevealArgs {
+            user_contribution: user_randomness,
+            provider_contribution,
+        };
+        let reveal_ix = if request.has_callback_account_list() {
+            let list = banks_client
+                .get_account(Pubkey::new_from_array(request.callback_account_list))
+                .await
+                .unwrap()
+                .and_then(|account| {
+                    try_from_bytes::<CallbackAccountList>(&account.data)
+                        .ok()
+                        .copied()
+                })
+                .expect("request's callback account list is missing");
+            reveal_with_callback_account_list(
+                self.program_id,
+                request_account,
+                request,
+                &list,
+                args,
+            )
+        } else {
+            reveal_with_callback(self.program_id, request_account, request, args)
+        };
+
+        let (result, logs) = submit_tx_with_metadata(banks_client, payer, &[reveal_ix], &[]).await;
+        let result = result.map(|()| {
+            parse_logs(&self.program_id, &logs)
+                .into_iter()
+                .find_map(|event| match event {
+                    EntropyEvent::RandomnessRevealed(revealed) => Some(revealed),
+                    _ => None,
+                })
+                .expect("reveal succeeded without a RandomnessRevealed event")
+        });
+        if result.is_ok() {
+            self.forget(&request_account);
+        }
+
+        Reveal {
+            request_account,
+            sequence_number,
+            result,
+        }
+    }
+
+    fn forget(&self, request_account: &Pubkey) {
+        USER_RANDOMNESS
+            .lock()
+            .unwrap()
+            .remove(&(self.program_id, *request_account));
+    }
+}
+
+impl Reveal {
+    pub fn random_number(&self) -> Option<[u8; 32]> {
+        self.result
+            .as_ref()
+            .ok()
+            .map(|revealed| revealed.random_number)
+    }
+}
@@ -0,0 +1,22 @@
+//! Program-test helpers shared by the entropy program's tests and by consumer
+//! programs that want to test against entropy: transaction submission,
+//! instruction builders, the [`EntropyFixture`] builder and a [`MockKeeper`]
+//! that reveals requests in place of a provider.
+
+pub mod banks;
+pub mod fixture;
+pub mod instructions;
+pub mod keeper;
+pub mod program_data;
+pub mod register_args;
+
+pub use banks::{
+    initialize_config, new_entropy_program_test, submit_tx, submit_tx_expect_err,
+    submit_tx_with_metadata,
+};
+pub use entropy_sdk::hash_chain::HashChain;
+pub use fixture::*;
+pub use instructions::{build_initialize_ix, build_register_provider_ix};
+pub use keeper::{MockKeeper, Reveal};
+pub use program_data::{add_deployer, add_program_data, deployer};
+pub use register_args::{build_register_args, build_register_args_with_metadata};
@@ -0,0 +1,60 @@
+#[allow(deprecated)]
+use {
+    entropy::program_data::{program_data_address, PROGRAM_DATA_METADATA_LEN},
+    solana_program::{
+        bpf_loader_upgradeable, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, rent::Rent,
+        system_program,
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::{
+        account::Account,
+        signature::{keypair_from_seed, Keypair, Signer},
+    },
+};
+
+/// The upgrade authority [`add_deployer`] records for entropy. `Initialize`
+/// must be paid by it.
+pub fn deployer() -> Keypair {
+    keypair_from_seed(&[7u8; 32]).unwrap()
+}
+
+/// Records [`deployer`] as `program_id`'s upgrade authority and funds it.
+pub fn add_deployer(program_test: &mut ProgramTest, program_id: Pubkey) {
+    let deployer = deployer().pubkey();
+    add_program_data(program_test, program_id, Some(deployer));
+    program_test.add_account(
+        deployer,
+        Account::new(1_000 * LAMPORTS_PER_SOL, 0, &system_program::id()),
+    );
+}
+
+/// Adds the `ProgramData` account the upgradeable loader would hold for
+/// `program_id`. Native processors have none, so tests of upgrade-authority
+/// checks add it by hand. Returns its address.
+#[allow(deprecated)]
+pub fn add_program_data(
+    program_test: &mut ProgramTest,
+    program_id: Pubkey,
+    upgrade_authority: Option<Pubkey>,
+) -> Pubkey {
+    let mut data = vec![0u8; PROGRAM_DATA_METADATA_LEN];
+    // `UpgradeableLoaderState::ProgramData` tag, then a zero deployment slot.
+    data[..4].copy_from_slice(&3u32.to_le_bytes());
+    if let Some(upgrade_authority) = upgrade_authority {
+        data[12] = 1;
+        data[13..].copy_from_slice(upgrade_authority.as_ref());
+    }
+
+    let address = program_data_address(&program_id);
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    address
+}
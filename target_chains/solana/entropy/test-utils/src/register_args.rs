@@ -3,7 +3,6 @@ use entropy::{
     instruction::RegisterProviderArgs,
 };
 
-#[allow(dead_code)]
 pub fn build_register_args_with_metadata(
     fee_lamports: u64,
     commitment: [u8; 32],
@@ -29,11 +28,11 @@ pub fn build_register_args_with_metadata(
         chain_length,
         uri_len: uri.len() as u16,
         uri: uri_buf,
-        _padding1: [0u8; 6],
+        require_https: 0,
+        _padding1: [0u8; 5],
     }
 }
 
-#[allow(dead_code)]
 pub fn build_register_args(
     fee_lamports: u64,
     commitment: [u8; 32],
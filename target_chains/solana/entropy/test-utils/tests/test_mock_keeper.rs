@@ -0,0 +1,87 @@
+use {
+    entropy_cpi::rand::{EntropyRng, RandomSource},
+    entropy_sdk::instruction::{
+        forward_request_with_callback, request_with_callback, RequestAccounts, RequestParams,
+    },
+    entropy_test_utils::{EntropyFixture, MockKeeper},
+    simple_requester::{CallbackState, CALLBACK_ACTION, RNG_CONSUMER_SEED},
+    solana_program::instruction::AccountMeta,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn test_mock_keeper_reveals_callback_requests() {
+    let mut env = EntropyFixture::new()
+        .with_provider(0, 16)
+        .with_requester()
+        .start()
+        .await;
+    let keeper = MockKeeper::new(&env);
+    let payer = env.payer();
+    let requester_program = env.requester().program_id;
+    let callback_state = env.requester().callback_state;
+
+    let mut callback_ix_data = vec![CALLBACK_ACTION];
+    callback_ix_data.extend_from_slice(env.program_id.as_ref());
+    let request_account = Keypair::new();
+    let entropy_ix = request_with_callback(
+        env.program_id,
+        &RequestAccounts {
+            requester_program,
+            payer: payer.pubkey(),
+            request_account: request_account.pubkey(),
+            provider_authority: env.provider().authority,
+        },
+        [7u8; 32],
+        &RequestParams {
+            compute_unit_limit: 100_000,
+            callback_accounts: &[AccountMeta::new(callback_state, false)],
+            callback_ix_data: &callback_ix_data,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let instruction = forward_request_with_callback(requester_program, &entropy_ix);
+
+    let reveals = keeper
+        .submit(
+            &mut env.context.banks_client,
+            &payer,
+            &[instruction],
+            &[&request_account],
+        )
+        .await
+        .unwrap();
+    assert_eq!(reveals.len(), 1);
+    assert_eq!(reveals[0].request_account, request_account.pubkey());
+    assert_eq!(reveals[0].sequence_number, 1);
+    let random_number = reveals[0].random_number().unwrap();
+
+    let callback_state_account = env
+        .context
+        .banks_client
+        .get_account(callback_state)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = bytemuck::from_bytes::<CallbackState>(&callback_state_account.data);
+    assert_eq!(state.called, 1);
+    assert_eq!(state.random_number, random_number);
+    // The stream the program drew on-chain matches the one computed here.
+    assert_eq!(
+        state.derived_value,
+        EntropyRng::new(&random_number, 1, RNG_CONSUMER_SEED).next_u64()
+    );
+    assert!(env
+        .context
+        .banks_client
+        .get_account(request_account.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+
+    let reveals = keeper
+        .reveal_pending(&mut env.context.banks_client, &payer)
+        .await;
+    assert!(reveals.is_empty());
+}